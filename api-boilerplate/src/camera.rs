@@ -0,0 +1,232 @@
+//! Cameras and camera controllers.
+//!
+//! Projection matrices are built for the clip space conventions of the backend
+//! (see [autograph_api::clip]). Controllers are updated from the [InputState] of the app:
+//! ```ignore
+//! let mut controller = OrbitController::new(glm::vec3(0.0, 0.0, 0.0), 5.0);
+//! let projection = Projection::perspective(45.0f32.to_radians(), 0.1, 100.0);
+//! // each frame, after `poll_events`
+//! controller.update(&app.input());
+//! let camera = Camera::new(controller.view_matrix(), projection);
+//! let view_proj = camera.view_projection(r, aspect);
+//! ```
+use crate::input::InputState;
+use autograph_api::{
+    clip::{ortho_for, perspective_for},
+    glm, Api, Backend,
+};
+use winit::{MouseButton, VirtualKeyCode};
+
+/// Projection of a camera.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    Perspective {
+        /// Vertical field of view, in radians.
+        fovy: f32,
+        near: f32,
+        far: f32,
+    },
+    Orthographic {
+        /// Height of the view volume. The width depends on the aspect ratio.
+        height: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+impl Projection {
+    pub fn perspective(fovy: f32, near: f32, far: f32) -> Projection {
+        Projection::Perspective { fovy, near, far }
+    }
+
+    pub fn orthographic(height: f32, near: f32, far: f32) -> Projection {
+        Projection::Orthographic { height, near, far }
+    }
+
+    /// Returns the projection matrix for the clip space of the backend of `api`.
+    pub fn matrix<B: Backend>(&self, api: &Api<B>, aspect: f32) -> glm::Mat4 {
+        match *self {
+            Projection::Perspective { fovy, near, far } => {
+                perspective_for(api, aspect, fovy, near, far)
+            }
+            Projection::Orthographic { height, near, far } => {
+                let half_height = 0.5 * height;
+                let half_width = half_height * aspect;
+                ortho_for(
+                    api,
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    near,
+                    far,
+                )
+            }
+        }
+    }
+}
+
+/// A view matrix and a projection.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// World-to-view transform.
+    pub view: glm::Mat4,
+    pub projection: Projection,
+}
+
+impl Camera {
+    pub fn new(view: glm::Mat4, projection: Projection) -> Camera {
+        Camera { view, projection }
+    }
+
+    /// Returns the projection matrix for a viewport with the specified aspect ratio
+    /// (width / height).
+    pub fn projection_matrix<B: Backend>(&self, api: &Api<B>, aspect: f32) -> glm::Mat4 {
+        self.projection.matrix(api, aspect)
+    }
+
+    /// Returns the product of the projection and view matrices.
+    pub fn view_projection<B: Backend>(&self, api: &Api<B>, aspect: f32) -> glm::Mat4 {
+        self.projection_matrix(api, aspect) * self.view
+    }
+}
+
+/// Maximum pitch of the controllers, slightly less than 90° to avoid a degenerate basis.
+const MAX_PITCH: f32 = 1.55;
+
+/// Returns the forward direction (Y up) for the specified yaw and pitch in radians.
+fn direction(yaw: f32, pitch: f32) -> glm::Vec3 {
+    glm::vec3(
+        pitch.cos() * yaw.sin(),
+        pitch.sin(),
+        -pitch.cos() * yaw.cos(),
+    )
+}
+
+/// Rotates around a target point with the left mouse button, pans with the middle button,
+/// and zooms with the scroll wheel.
+#[derive(Copy, Clone, Debug)]
+pub struct OrbitController {
+    pub target: glm::Vec3,
+    pub distance: f32,
+    /// Rotation around the Y axis, in radians.
+    pub yaw: f32,
+    /// Elevation above the XZ plane, in radians.
+    pub pitch: f32,
+    /// Radians per pixel of mouse movement.
+    pub rotate_sensitivity: f32,
+    /// Fraction of the distance to the target per pixel of mouse movement.
+    pub pan_sensitivity: f32,
+    /// Fraction of the distance to the target per line scrolled.
+    pub zoom_sensitivity: f32,
+}
+
+impl OrbitController {
+    pub fn new(target: glm::Vec3, distance: f32) -> OrbitController {
+        OrbitController {
+            target,
+            distance,
+            yaw: 0.0,
+            pitch: 0.0,
+            rotate_sensitivity: 0.01,
+            pan_sensitivity: 0.002,
+            zoom_sensitivity: 0.1,
+        }
+    }
+
+    /// Updates the controller from the input state of the last frame.
+    pub fn update(&mut self, input: &InputState) {
+        let (dx, dy) = input.cursor_delta();
+        let (dx, dy) = (dx as f32, dy as f32);
+
+        if input.is_button_down(MouseButton::Left) {
+            self.yaw += dx * self.rotate_sensitivity;
+            self.pitch = (self.pitch + dy * self.rotate_sensitivity)
+                .max(-MAX_PITCH)
+                .min(MAX_PITCH);
+        }
+
+        if input.is_button_down(MouseButton::Middle) {
+            let forward = direction(self.yaw, self.pitch);
+            let right = glm::normalize(&glm::cross(&forward, &glm::vec3(0.0, 1.0, 0.0)));
+            let up = glm::cross(&right, &forward);
+            let scale = self.pan_sensitivity * self.distance;
+            self.target += (-dx * scale) * right + (dy * scale) * up;
+        }
+
+        let (_, scroll) = input.scroll_delta();
+        if scroll != 0.0 {
+            self.distance *= (1.0 - self.zoom_sensitivity).powf(scroll as f32);
+        }
+    }
+
+    /// Returns the position of the eye.
+    pub fn eye(&self) -> glm::Vec3 {
+        self.target - self.distance * direction(self.yaw, self.pitch)
+    }
+
+    /// Returns the world-to-view transform.
+    pub fn view_matrix(&self) -> glm::Mat4 {
+        glm::look_at(&self.eye(), &self.target, &glm::vec3(0.0, 1.0, 0.0))
+    }
+}
+
+/// Moves with the W, A, S, D (horizontal) and Q, E (vertical) keys, and looks around
+/// with the right mouse button.
+#[derive(Copy, Clone, Debug)]
+pub struct FlyController {
+    pub position: glm::Vec3,
+    /// Rotation around the Y axis, in radians.
+    pub yaw: f32,
+    /// Elevation above the XZ plane, in radians.
+    pub pitch: f32,
+    /// Units per second.
+    pub speed: f32,
+    /// Radians per pixel of mouse movement.
+    pub look_sensitivity: f32,
+}
+
+impl FlyController {
+    pub fn new(position: glm::Vec3) -> FlyController {
+        FlyController {
+            position,
+            yaw: 0.0,
+            pitch: 0.0,
+            speed: 1.0,
+            look_sensitivity: 0.005,
+        }
+    }
+
+    /// Updates the controller from the input state of the last frame, which lasted
+    /// `delta_time` seconds.
+    pub fn update(&mut self, input: &InputState, delta_time: f64) {
+        if input.is_button_down(MouseButton::Right) {
+            let (dx, dy) = input.cursor_delta();
+            self.yaw += dx as f32 * self.look_sensitivity;
+            self.pitch = (self.pitch - dy as f32 * self.look_sensitivity)
+                .max(-MAX_PITCH)
+                .min(MAX_PITCH);
+        }
+
+        let forward = direction(self.yaw, self.pitch);
+        let right = glm::normalize(&glm::cross(&forward, &glm::vec3(0.0, 1.0, 0.0)));
+        let up = glm::vec3(0.0, 1.0, 0.0);
+        let axis = |positive: VirtualKeyCode, negative: VirtualKeyCode| {
+            (input.is_key_down(positive) as i32 - input.is_key_down(negative) as i32) as f32
+        };
+        let motion = axis(VirtualKeyCode::W, VirtualKeyCode::S) * forward
+            + axis(VirtualKeyCode::D, VirtualKeyCode::A) * right
+            + axis(VirtualKeyCode::E, VirtualKeyCode::Q) * up;
+        self.position += (self.speed * delta_time as f32) * motion;
+    }
+
+    /// Returns the world-to-view transform.
+    pub fn view_matrix(&self) -> glm::Mat4 {
+        let forward = direction(self.yaw, self.pitch);
+        glm::look_at(
+            &self.position,
+            &(self.position + forward),
+            &glm::vec3(0.0, 1.0, 0.0),
+        )
+    }
+}
@@ -0,0 +1,178 @@
+//! Aggregated keyboard and mouse state.
+use std::collections::HashSet;
+use winit::{
+    dpi::LogicalPosition, ElementState, Event, KeyboardInput, ModifiersState, MouseButton,
+    MouseScrollDelta, VirtualKeyCode, WindowEvent,
+};
+
+/// Number of pixels corresponding to one line of scrolling.
+const PIXELS_PER_LINE: f64 = 20.0;
+
+/// State of the keyboard and mouse, updated from window events.
+///
+/// "Pressed" and "released" queries refer to the transitions that happened since the last call
+/// to [InputState::begin_frame] (i.e. during the last call to `App::poll_events`).
+#[derive(Clone, Debug, Default)]
+pub struct InputState {
+    keys_down: HashSet<VirtualKeyCode>,
+    keys_pressed: HashSet<VirtualKeyCode>,
+    keys_released: HashSet<VirtualKeyCode>,
+    buttons_down: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    modifiers: ModifiersState,
+    cursor_position: Option<LogicalPosition>,
+    cursor_delta: (f64, f64),
+    scroll_delta: (f64, f64),
+}
+
+impl InputState {
+    pub fn new() -> InputState {
+        InputState::default()
+    }
+
+    /// Resets the per-frame state (transitions and deltas).
+    pub fn begin_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.cursor_delta = (0.0, 0.0);
+        self.scroll_delta = (0.0, 0.0);
+    }
+
+    /// Updates the state from an event.
+    pub fn update(&mut self, event: &Event) {
+        let event = match event {
+            Event::WindowEvent { event, .. } => event,
+            _ => return,
+        };
+
+        match *event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(key),
+                        modifiers,
+                        ..
+                    },
+                ..
+            } => {
+                self.modifiers = modifiers;
+                match state {
+                    ElementState::Pressed => {
+                        // ignore key repeats
+                        if self.keys_down.insert(key) {
+                            self.keys_pressed.insert(key);
+                        }
+                    }
+                    ElementState::Released => {
+                        self.keys_down.remove(&key);
+                        self.keys_released.insert(key);
+                    }
+                }
+            }
+            WindowEvent::MouseInput {
+                state,
+                button,
+                modifiers,
+                ..
+            } => {
+                self.modifiers = modifiers;
+                match state {
+                    ElementState::Pressed => {
+                        self.buttons_down.insert(button);
+                        self.buttons_pressed.insert(button);
+                    }
+                    ElementState::Released => {
+                        self.buttons_down.remove(&button);
+                        self.buttons_released.insert(button);
+                    }
+                }
+            }
+            WindowEvent::CursorMoved {
+                position,
+                modifiers,
+                ..
+            } => {
+                self.modifiers = modifiers;
+                if let Some(prev) = self.cursor_position {
+                    self.cursor_delta.0 += position.x - prev.x;
+                    self.cursor_delta.1 += position.y - prev.y;
+                }
+                self.cursor_position = Some(position);
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor_position = None;
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let (dx, dy) = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => (x as f64, y as f64),
+                    MouseScrollDelta::PixelDelta(pos) => {
+                        (pos.x / PIXELS_PER_LINE, pos.y / PIXELS_PER_LINE)
+                    }
+                };
+                self.scroll_delta.0 += dx;
+                self.scroll_delta.1 += dy;
+            }
+            WindowEvent::Focused(false) => {
+                // release events are not received when the window is not focused
+                self.keys_released.extend(self.keys_down.drain());
+                self.buttons_released.extend(self.buttons_down.drain());
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns whether the key is currently held down.
+    pub fn is_key_down(&self, key: VirtualKeyCode) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    /// Returns whether the key was pressed during the last frame.
+    pub fn is_key_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    /// Returns whether the key was released during the last frame.
+    pub fn is_key_released(&self, key: VirtualKeyCode) -> bool {
+        self.keys_released.contains(&key)
+    }
+
+    /// Returns whether the mouse button is currently held down.
+    pub fn is_button_down(&self, button: MouseButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    /// Returns whether the mouse button was pressed during the last frame.
+    pub fn is_button_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    /// Returns whether the mouse button was released during the last frame.
+    pub fn is_button_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Returns the state of the modifier keys.
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// Returns the position of the cursor in the window, or `None` if the cursor
+    /// is outside the window.
+    pub fn cursor_position(&self) -> Option<LogicalPosition> {
+        self.cursor_position
+    }
+
+    /// Returns the movement of the cursor during the last frame, in logical pixels.
+    pub fn cursor_delta(&self) -> (f64, f64) {
+        self.cursor_delta
+    }
+
+    /// Returns the amount scrolled during the last frame, in lines.
+    pub fn scroll_delta(&self) -> (f64, f64) {
+        self.scroll_delta
+    }
+}
@@ -1,19 +1,30 @@
 //! Boilerplate code for creating a window and an OpenGL context with winit/glutin.
 extern crate image as img;
 use self::img::GenericImageView;
-use autograph_api::*;
+use autograph_api::{swapchain::Swapchain, *};
 use autograph_api_gl::{create_instance_and_window, InstanceConfig, OpenGlBackend};
 use pretty_env_logger;
-use std::cell::RefCell;
+use std::cell::{Ref, RefCell};
 use winit;
 
+pub mod camera;
+pub mod input;
+pub mod sequence;
+pub mod timer;
+
+pub use self::{
+    input::InputState,
+    sequence::FileSequence,
+    timer::{FixedTimestep, FrameTimer},
+};
+
 use glutin::GlWindow;
 use std::sync::Arc;
 pub use winit::{
     dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
     AxisId, ButtonId, DeviceId, ElementState, Event, EventsLoop, KeyboardInput, ModifiersState,
     MouseButton, MouseScrollDelta, Touch, TouchPhase, VirtualKeyCode, Window, WindowBuilder,
-    WindowEvent,
+    WindowEvent, WindowId,
 };
 
 /*
@@ -71,10 +82,21 @@ pub fn load_image_2d<'a, B: Backend, P: AsRef<Path>>(
     ))
 }*/
 
+/// A window created with [App::create_window].
+struct SecondaryWindow {
+    window: Arc<GlWindow>,
+    /// Owned by the instance of the renderer, which outlives the window.
+    swapchain: *const <OpenGlBackend as Backend>::Swapchain,
+    closed: bool,
+}
+
 pub struct App {
     pub events_loop: RefCell<winit::EventsLoop>,
     pub renderer: Api<OpenGlBackend>,
     pub window: Arc<GlWindow>,
+    windows: RefCell<Vec<SecondaryWindow>>,
+    input: RefCell<InputState>,
+    frame_timer: RefCell<FrameTimer>,
 }
 
 impl Default for App {
@@ -104,29 +126,123 @@ impl App {
             events_loop: RefCell::new(events_loop),
             renderer,
             window,
+            windows: RefCell::new(Vec::new()),
+            input: RefCell::new(InputState::new()),
+            frame_timer: RefCell::new(FrameTimer::new()),
+        }
+    }
+
+    /// Creates an additional window with its own swapchain.
+    ///
+    /// Images can be presented to the swapchain of each window (see [App::swapchain])
+    /// in the same frame. Events of the window are passed to the callback of
+    /// [App::poll_events], and can be told apart by their `window_id`.
+    pub fn create_window(&self, window_builder: WindowBuilder) -> WindowId {
+        let (swapchain, window) = self
+            .renderer
+            .instance()
+            .create_window_swapchain(&self.events_loop.borrow(), window_builder);
+        let id = window.id();
+        self.windows.borrow_mut().push(SecondaryWindow {
+            window,
+            swapchain,
+            closed: false,
+        });
+        id
+    }
+
+    /// Returns the ID of the main window.
+    pub fn main_window_id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    /// Returns the window with the specified ID, or `None` if there is no such window or if it
+    /// has been closed.
+    pub fn window_by_id(&self, id: WindowId) -> Option<Arc<GlWindow>> {
+        if id == self.main_window_id() {
+            return Some(self.window.clone());
         }
+        self.windows
+            .borrow()
+            .iter()
+            .find(|w| !w.closed && w.window.id() == id)
+            .map(|w| w.window.clone())
     }
 
+    /// Returns the swapchain of the window with the specified ID, or `None` if there is no such
+    /// window or if it has been closed.
+    pub fn swapchain(&self, id: WindowId) -> Option<Swapchain<OpenGlBackend>> {
+        if id == self.main_window_id() {
+            return self.renderer.default_swapchain();
+        }
+        self.windows
+            .borrow()
+            .iter()
+            .find(|w| !w.closed && w.window.id() == id)
+            .map(|w| Swapchain(unsafe { &*w.swapchain }))
+    }
+
+    /// Returns whether the window with the specified ID is still open.
+    pub fn is_window_open(&self, id: WindowId) -> bool {
+        self.window_by_id(id).is_some()
+    }
+
+    /// Polls the events of all windows.
+    ///
+    /// Returns true if the main window was closed. Additional windows are hidden when closed,
+    /// and their close events are passed to `callback`.
+    ///
+    /// The input state returned by [App::input] is updated with the events, and a new frame
+    /// is started in the timer returned by [App::frame_timer].
     pub fn poll_events<F>(&self, mut callback: F) -> bool
     where
         F: FnMut(winit::Event),
     {
+        let main_window_id = self.main_window_id();
         let mut should_close = false;
+        self.frame_timer.borrow_mut().tick();
+        let mut input = self.input.borrow_mut();
+        input.begin_frame();
         self.events_loop.borrow_mut().poll_events(|event| {
+            input.update(&event);
             // event handling
             match event {
                 Event::WindowEvent {
                     event: WindowEvent::CloseRequested,
-                    ..
-                } => {
+                    window_id,
+                } if window_id == main_window_id => {
                     should_close = true;
                 }
+                Event::WindowEvent {
+                    event: WindowEvent::CloseRequested,
+                    window_id,
+                } => {
+                    for w in self.windows.borrow_mut().iter_mut() {
+                        if w.window.id() == window_id {
+                            w.window.hide();
+                            w.closed = true;
+                        }
+                    }
+                    callback(event)
+                }
                 _ => callback(event),
             }
         });
         should_close
     }
 
+    /// Returns the state of the keyboard and mouse after the last call to [App::poll_events].
+    ///
+    /// Must not be called from the callback of `poll_events`.
+    pub fn input(&self) -> Ref<InputState> {
+        self.input.borrow()
+    }
+
+    /// Returns the timer measuring the time between calls to [App::poll_events].
+    pub fn frame_timer(&self) -> Ref<FrameTimer> {
+        self.frame_timer.borrow()
+    }
+
     pub fn renderer(&self) -> &Api<OpenGlBackend> {
         &self.renderer
     }
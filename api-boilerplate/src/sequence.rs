@@ -0,0 +1,129 @@
+//! Output of frames to a sequence of image files.
+use crate::img;
+use autograph_api::{
+    format::Format,
+    image::ImageUsageFlags,
+    sink::{FrameSink, SinkFrame},
+    Arena, Backend,
+};
+use log::error;
+use std::{io, path::PathBuf};
+
+/// A [FrameSink] that writes each frame to a numbered image file, for batch renders of
+/// animations.
+///
+/// The path of each frame is given by a pattern in which the first run of `#` characters is
+/// replaced by the frame number, padded with zeros to the length of the run
+/// (`"frames/shot_####.png"` gives `frames/shot_0000.png`, `frames/shot_0001.png`, ...).
+/// The format of the files is deduced from their extension.
+///
+/// Frames are rendered to images in the `R8G8B8A8_SRGB` format, which are written as-is.
+/// Since the contents of a frame are only available once it has been submitted, each frame
+/// is written when the next one begins, or by [FileSequence::flush].
+pub struct FileSequence {
+    pattern: String,
+    size: (u32, u32),
+    /// Texels of the last frame.
+    pixels: Vec<u8>,
+    /// Number of the next frame.
+    next_frame: u32,
+    /// Whether the last frame has not been written yet.
+    pending: bool,
+}
+
+impl FileSequence {
+    /// Creates a sequence of frames of the specified size, numbered from 0.
+    ///
+    /// Panics if the pattern does not contain a `#` character.
+    pub fn new(pattern: impl Into<String>, size: (u32, u32)) -> FileSequence {
+        let pattern = pattern.into();
+        assert!(
+            pattern.contains('#'),
+            "the path pattern of a file sequence must contain `#` characters"
+        );
+        FileSequence {
+            pattern,
+            size,
+            pixels: vec![0; 4 * size.0 as usize * size.1 as usize],
+            next_frame: 0,
+            pending: false,
+        }
+    }
+
+    /// Returns the path of the specified frame.
+    pub fn path(&self, frame: u32) -> PathBuf {
+        let start = self.pattern.find('#').unwrap();
+        let len = self.pattern[start..]
+            .find(|c| c != '#')
+            .unwrap_or(self.pattern.len() - start);
+        format!(
+            "{}{:0width$}{}",
+            &self.pattern[..start],
+            frame,
+            &self.pattern[start + len..],
+            width = len
+        )
+        .into()
+    }
+
+    /// Returns the number of frames begun so far.
+    pub fn frame_count(&self) -> u32 {
+        self.next_frame
+    }
+
+    /// Writes the last frame, if it has not been written yet. The frame must have been
+    /// submitted.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if self.pending {
+            self.pending = false;
+            let path = self.path(self.next_frame - 1);
+            img::save_buffer(
+                &path,
+                &self.pixels,
+                self.size.0,
+                self.size.1,
+                img::ColorType::RGBA(8),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<B: Backend> FrameSink<B> for FileSequence {
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Writes the previous frame, and begins a new one.
+    ///
+    /// Panics if the previous frame could not be written.
+    fn begin_frame<'a, 'r>(&'a mut self, arena: &'a Arena<'r, B>) -> SinkFrame<'a, B> {
+        if let Err(e) = self.flush() {
+            panic!(
+                "failed to write frame {}: {}",
+                self.path(self.next_frame - 1).display(),
+                e
+            );
+        }
+        self.pending = true;
+        self.next_frame += 1;
+        let (width, height) = self.size;
+        let image = arena
+            .image_2d(Format::R8G8B8A8_SRGB, width, height)
+            .usage(ImageUsageFlags::COLOR_ATTACHMENT)
+            .build();
+        SinkFrame::readback(image, self.size, arena.host_readback(&mut self.pixels[..]))
+    }
+}
+
+impl Drop for FileSequence {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            error!(
+                "failed to write frame {}: {}",
+                self.path(self.next_frame - 1).display(),
+                e
+            );
+        }
+    }
+}
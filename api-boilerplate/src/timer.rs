@@ -0,0 +1,162 @@
+//! Frame timing.
+use std::time::{Duration, Instant};
+
+/// Weight of the last frame in the smoothed frame time.
+const SMOOTHING_FACTOR: f64 = 0.1;
+
+fn duration_to_secs(d: Duration) -> f64 {
+    (d.as_secs() as f64) + (d.subsec_nanos() as f64 / 1_000_000_000.0)
+}
+
+/// Measures the time between frames.
+#[derive(Clone, Debug)]
+pub struct FrameTimer {
+    start: Instant,
+    last_tick: Instant,
+    delta: Duration,
+    smoothed_delta: f64,
+    frame_count: u64,
+}
+
+impl FrameTimer {
+    pub fn new() -> FrameTimer {
+        let now = Instant::now();
+        FrameTimer {
+            start: now,
+            last_tick: now,
+            delta: Duration::from_secs(0),
+            smoothed_delta: 0.0,
+            frame_count: 0,
+        }
+    }
+
+    /// Starts a new frame.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        self.delta = now - self.last_tick;
+        self.last_tick = now;
+        let dt = duration_to_secs(self.delta);
+        self.smoothed_delta = if self.frame_count == 0 {
+            dt
+        } else {
+            self.smoothed_delta + SMOOTHING_FACTOR * (dt - self.smoothed_delta)
+        };
+        self.frame_count += 1;
+    }
+
+    /// Returns the time between the last two frames.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Returns the time between the last two frames, in seconds.
+    pub fn delta_time(&self) -> f64 {
+        duration_to_secs(self.delta)
+    }
+
+    /// Returns the average time between frames in seconds, smoothed over the last frames.
+    pub fn smoothed_delta_time(&self) -> f64 {
+        self.smoothed_delta
+    }
+
+    /// Returns the number of frames per second, smoothed over the last frames.
+    pub fn fps(&self) -> f64 {
+        if self.smoothed_delta > 0.0 {
+            1.0 / self.smoothed_delta
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the number of frames since the creation of the timer.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Returns the time since the creation of the timer, in seconds.
+    pub fn elapsed_time(&self) -> f64 {
+        duration_to_secs(self.last_tick - self.start)
+    }
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Accumulates frame times to run updates with a fixed timestep.
+///
+/// ```ignore
+/// let steps = fixed_timestep.advance(app.frame_timer().delta_time());
+/// for _ in 0..steps {
+///     simulation.step(fixed_timestep.step());
+/// }
+/// // interpolate between the last two simulation states for rendering
+/// let alpha = fixed_timestep.alpha();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct FixedTimestep {
+    step: f64,
+    accumulator: f64,
+    max_steps: u32,
+}
+
+impl FixedTimestep {
+    /// Creates an accumulator for updates of `step` seconds.
+    ///
+    /// At most `max_steps` updates are run per frame: the rest of the accumulated time is
+    /// dropped, so that the application does not spiral after a long frame.
+    pub fn new(step: f64, max_steps: u32) -> FixedTimestep {
+        assert!(step > 0.0, "invalid timestep");
+        assert!(max_steps > 0, "invalid maximum number of steps");
+        FixedTimestep {
+            step,
+            accumulator: 0.0,
+            max_steps,
+        }
+    }
+
+    /// Returns the duration of an update, in seconds.
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Adds the time of the last frame, in seconds, and returns the number of updates to run.
+    pub fn advance(&mut self, delta_time: f64) -> u32 {
+        self.accumulator += delta_time;
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+            if steps == self.max_steps {
+                self.accumulator %= self.step;
+                break;
+            }
+        }
+        steps
+    }
+
+    /// Returns the fraction of an update remaining in the accumulator, between 0 and 1.
+    pub fn alpha(&self) -> f64 {
+        (self.accumulator / self.step).min(1.0)
+    }
+}
+
+/// Draws a small window with the frame rate and frame time.
+#[cfg(feature = "imgui")]
+pub fn frame_stats_overlay(ui: &imgui::Ui, timer: &FrameTimer) {
+    use imgui::{im_str, ImGuiCond};
+    ui.window(im_str!("Frame statistics"))
+        .position((10.0, 10.0), ImGuiCond::FirstUseEver)
+        .always_auto_resize(true)
+        .resizable(false)
+        .save_settings(false)
+        .build(|| {
+            ui.text(im_str!("{:.1} FPS", timer.fps()));
+            ui.text(im_str!(
+                "{:.2} ms/frame",
+                timer.smoothed_delta_time() * 1000.0
+            ));
+        });
+}
@@ -0,0 +1,10 @@
+#version 450
+
+layout(set=0, binding=0) uniform sampler2D u_source;
+
+layout(location=0) in vec2 v_texcoord;
+layout(location=0) out vec4 out_color;
+
+void main() {
+  out_color = texture(u_source, v_texcoord);
+}
@@ -0,0 +1,12 @@
+#version 450
+// Screen-space quad (see `quad::QuadVertices`).
+
+layout(location=0) in vec2 a_position;
+layout(location=1) in vec2 a_texcoord;
+
+layout(location=0) out vec2 v_texcoord;
+
+void main() {
+  gl_Position = vec4(a_position, 0.0, 1.0);
+  v_texcoord = a_texcoord;
+}
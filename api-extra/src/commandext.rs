@@ -1,10 +1,56 @@
+//! Extension methods of command buffers for screen-space quads.
+//!
+//! [CommandBufferExt::blit] copies an image into a render target with a built-in pipeline,
+//! cached by the renderer. [CommandBufferExt::fullscreen] runs a user pipeline over the whole
+//! target and uploads its uniforms in the arena:
+//! ```ignore
+//! cmdbuf.blit(0x200, &frame_arena, color.mipmaps(0..1), backbuffer.into(), viewport, Filter::Linear);
+//! cmdbuf.fullscreen(0x100, &frame_arena, tonemap_pipeline, &TonemapParams { exposure }, TonemapArguments { .. });
+//! ```
 use crate::quad::Quad;
 use autograph_api::{
+    buffer::{StructuredBufferData, TypedConstantBufferView},
     command::{CommandBuffer, DrawParams},
-    pipeline::{Arguments, GraphicsPipeline, TypedGraphicsPipeline, TypedSignature},
+    image::{
+        Filter, Image2dMipmaps, RenderTargetView, SamplerAddressMode, SamplerDescription,
+        SamplerMipmapMode, TextureSampler2dView,
+    },
+    include_glsl,
+    pipeline::{
+        Arguments, ColorBlendState, DepthStencilState, GraphicsPipeline,
+        GraphicsPipelineCreateInfo, InputAssemblyState, MultisampleState, RasterisationState,
+        ReflectedShader, TypedGraphicsPipeline, TypedSignature, ViewCount, Viewport, ViewportState,
+    },
     Arena, Backend,
 };
 
+static BLIT_VERT: ReflectedShader = include_glsl!("blit.vert");
+static BLIT_FRAG: ReflectedShader = include_glsl!("blit.frag");
+
+/// Key of the blit pipeline in the pipeline cache of the renderer.
+struct BlitPipeline;
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct BlitArguments<'a, B: Backend> {
+    #[argument(render_target)]
+    dst: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+    #[argument(descriptor)]
+    src: TextureSampler2dView<'a, B>,
+}
+
+/// Arguments of a pipeline drawn with [CommandBufferExt::fullscreen].
+///
+/// The uniforms are bound at binding 0 of the descriptor set that follows the ones of `A`.
+#[derive(Arguments)]
+pub struct Fullscreen<'a, B: Backend, T: StructuredBufferData, A: Arguments<'a, B>> {
+    #[argument(inherit)]
+    pub arguments: A,
+    #[argument(descriptor)]
+    pub uniforms: TypedConstantBufferView<'a, B, T>,
+}
+
 pub trait CommandBufferExt<'a, B: Backend> {
     fn draw_quad<A: Arguments<'a, B>>(
         &mut self,
@@ -13,6 +59,34 @@ pub trait CommandBufferExt<'a, B: Backend> {
         pipeline: TypedGraphicsPipeline<'a, B, Quad<'a, B, A>>,
         arguments: A,
     );
+
+    /// Copies `src` to the area of `dst` covered by `viewport`, sampling with `filter`.
+    ///
+    /// Mipmaps are selected with the nearest level: use `image.mipmaps(n..n + 1)` to copy a
+    /// single level.
+    fn blit(
+        &mut self,
+        sortkey: u64,
+        arena: &'a Arena<B>,
+        src: Image2dMipmaps<'a, B>,
+        dst: RenderTargetView<'a, B>,
+        viewport: Viewport,
+        filter: Filter,
+    );
+
+    /// Draws a quad covering the render targets with `pipeline`, after uploading `uniforms`
+    /// in the arena.
+    ///
+    /// The draw is recorded with [CommandBuffer::draw_fullscreen]: the viewports and scissors of
+    /// the arguments must cover the render targets.
+    fn fullscreen<T: StructuredBufferData + Copy, A: Arguments<'a, B>>(
+        &mut self,
+        sortkey: u64,
+        arena: &'a Arena<B>,
+        pipeline: TypedGraphicsPipeline<'a, B, Quad<'a, B, Fullscreen<'a, B, T, A>>>,
+        uniforms: &T,
+        arguments: A,
+    );
 }
 
 impl<'a, B: Backend> CommandBufferExt<'a, B> for CommandBuffer<'a, B> {
@@ -31,4 +105,78 @@ impl<'a, B: Backend> CommandBufferExt<'a, B> for CommandBuffer<'a, B> {
             DrawParams::quad(),
         )
     }
+
+    fn blit(
+        &mut self,
+        sortkey: u64,
+        arena: &'a Arena<B>,
+        src: Image2dMipmaps<'a, B>,
+        dst: RenderTargetView<'a, B>,
+        viewport: Viewport,
+        filter: Filter,
+    ) {
+        let pipeline = arena
+            .api()
+            .get_cached_graphics_pipeline::<BlitPipeline, Quad<'a, B, BlitArguments<'a, B>>>(
+                BLIT_VERT,
+                BLIT_FRAG,
+                |shader_stages| GraphicsPipelineCreateInfo {
+                    shader_stages,
+                    viewport_state: ViewportState::default(),
+                    rasterization_state: RasterisationState::DEFAULT,
+                    multisample_state: MultisampleState::default(),
+                    depth_stencil_state: DepthStencilState::default(),
+                    input_assembly_state: InputAssemblyState::default(),
+                    color_blend_state: ColorBlendState::DISABLED,
+                    view_count: ViewCount::default(),
+                },
+            );
+
+        let sampler = SamplerDescription {
+            addr_u: SamplerAddressMode::Clamp,
+            addr_v: SamplerAddressMode::Clamp,
+            addr_w: SamplerAddressMode::Clamp,
+            min_filter: filter,
+            mag_filter: filter,
+            mipmap_mode: SamplerMipmapMode::Nearest,
+        };
+
+        let arguments = BlitArguments {
+            dst,
+            viewport,
+            src: src.sampled(sampler),
+        };
+        if viewport == Viewport::full(dst) {
+            // replaces the contents of the whole target: preceding clears can be elided
+            self.draw_fullscreen(
+                sortkey,
+                arena,
+                pipeline,
+                Quad::new(arguments),
+                DrawParams::quad(),
+            )
+        } else {
+            self.draw_quad(sortkey, arena, pipeline, arguments)
+        }
+    }
+
+    fn fullscreen<T: StructuredBufferData + Copy, A: Arguments<'a, B>>(
+        &mut self,
+        sortkey: u64,
+        arena: &'a Arena<B>,
+        pipeline: TypedGraphicsPipeline<'a, B, Quad<'a, B, Fullscreen<'a, B, T, A>>>,
+        uniforms: &T,
+        arguments: A,
+    ) {
+        self.draw_fullscreen(
+            sortkey,
+            arena,
+            pipeline,
+            Quad::new(Fullscreen {
+                arguments,
+                uniforms: arena.upload(uniforms).into(),
+            }),
+            DrawParams::quad(),
+        )
+    }
 }
@@ -0,0 +1,325 @@
+//! Frustum culling of objects on the CPU.
+//!
+//! A [Culler] tests the bounds of objects against the view frustum of a camera and only records
+//! the draws of visible objects in a command buffer. Since commands are sorted by sort key
+//! before submission, culling can be done independently per pass or per group of objects:
+//! ```ignore
+//! let frustum = Frustum::from_view_proj(&view_proj, api.clip_space_info().depth_range);
+//! let mut culler = Culler::with_cache(frustum, &mut visibility_cache);
+//! for object in objects.iter() {
+//!     culler.draw(&mut cmdbuf, object.id, &object.bounds, |cmdbuf| {
+//!         cmdbuf.draw_indexed(0x100, &frame_arena, pipeline, object.arguments, object.params);
+//!     });
+//! }
+//! let stats = culler.stats();
+//! ```
+//!
+//! With a [VisibilityCache], the plane that rejected an object in the previous frame is tested
+//! first: as the camera moves slowly relative to the objects, most invisible objects
+//! are rejected after a single plane test.
+use autograph_api::{clip::DepthRange, command::CommandBuffer, Backend};
+use std::collections::HashMap;
+
+/// Axis-aligned bounding box.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Aabb {
+    pub fn new(min: [f32; 3], max: [f32; 3]) -> Aabb {
+        Aabb { min, max }
+    }
+
+    /// Returns the corner with the index `i` (bit 0: X, bit 1: Y, bit 2: Z; set for max).
+    pub fn corner(&self, i: usize) -> [f32; 3] {
+        [
+            if i & 1 != 0 { self.max[0] } else { self.min[0] },
+            if i & 2 != 0 { self.max[1] } else { self.min[1] },
+            if i & 4 != 0 { self.max[2] } else { self.min[2] },
+        ]
+    }
+
+    /// Returns the bounding box of this box transformed by `transform` (column-major).
+    pub fn transform(&self, transform: &[[f32; 4]; 4]) -> Aabb {
+        let m = transform;
+        let mut min = [std::f32::INFINITY; 3];
+        let mut max = [std::f32::NEG_INFINITY; 3];
+        for i in 0..8 {
+            let c = self.corner(i);
+            for row in 0..3 {
+                let v = m[0][row] * c[0] + m[1][row] * c[1] + m[2][row] * c[2] + m[3][row];
+                min[row] = min[row].min(v);
+                max[row] = max[row].max(v);
+            }
+        }
+        Aabb { min, max }
+    }
+}
+
+/// A plane `a*x + b*y + c*z + d = 0`, with a normal `(a, b, c)` pointing inside the frustum.
+pub type Plane = [f32; 4];
+
+/// The six planes of a view frustum, in world space.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frustum {
+    /// Left, right, bottom, top, near, far.
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extracts the frustum planes from a view-projection matrix (column-major).
+    ///
+    /// `depth_range` is the range of normalized device depth of the backend, as returned by
+    /// [Api::clip_space_info](autograph_api::Api::clip_space_info).
+    pub fn from_view_proj(view_proj: &[[f32; 4]; 4], depth_range: DepthRange) -> Frustum {
+        let m = view_proj;
+        let row = |i: usize| [m[0][i], m[1][i], m[2][i], m[3][i]];
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+        let add = |a: [f32; 4], b: [f32; 4]| [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]];
+        let sub = |a: [f32; 4], b: [f32; 4]| [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]];
+        let near = match depth_range {
+            DepthRange::NegativeOneToOne => add(r3, r2),
+            DepthRange::ZeroToOne => r2,
+        };
+
+        let mut planes = [
+            add(r3, r0),
+            sub(r3, r0),
+            add(r3, r1),
+            sub(r3, r1),
+            near,
+            sub(r3, r2),
+        ];
+        for p in planes.iter_mut() {
+            let len = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+            if len > 0.0 {
+                for c in p.iter_mut() {
+                    *c /= len;
+                }
+            }
+        }
+        Frustum { planes }
+    }
+
+    /// Returns whether the box is entirely on the outer side of the plane with index `plane`.
+    fn is_outside_plane(&self, plane: usize, bounds: &Aabb) -> bool {
+        let p = &self.planes[plane];
+        // the corner of the box that is the furthest along the normal
+        let x = if p[0] >= 0.0 {
+            bounds.max[0]
+        } else {
+            bounds.min[0]
+        };
+        let y = if p[1] >= 0.0 {
+            bounds.max[1]
+        } else {
+            bounds.min[1]
+        };
+        let z = if p[2] >= 0.0 {
+            bounds.max[2]
+        } else {
+            bounds.min[2]
+        };
+        p[0] * x + p[1] * y + p[2] * z + p[3] < 0.0
+    }
+
+    /// Returns the index of the first plane that rejects the box, testing `first` before the
+    /// others, or `None` if the box may be visible.
+    fn rejecting_plane(&self, bounds: &Aabb, first: usize) -> Option<usize> {
+        if self.is_outside_plane(first, bounds) {
+            return Some(first);
+        }
+        (0..6).find(|&i| i != first && self.is_outside_plane(i, bounds))
+    }
+
+    /// Returns whether the box intersects the frustum.
+    ///
+    /// This test is conservative: some boxes near the corners of the frustum may be reported
+    /// as visible even if they are outside.
+    pub fn intersects(&self, bounds: &Aabb) -> bool {
+        self.rejecting_plane(bounds, 0).is_none()
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct CacheEntry {
+    visible: bool,
+    /// Plane that rejected the object the last time it was culled.
+    rejecting_plane: u8,
+    last_frame: u64,
+}
+
+/// Visibility of objects in previous frames, keyed by object ID.
+#[derive(Clone, Debug)]
+pub struct VisibilityCache {
+    entries: HashMap<u64, CacheEntry>,
+    frame: u64,
+    max_age: u64,
+}
+
+impl VisibilityCache {
+    /// Creates an empty cache. Entries of objects that have not been culled for more than
+    /// `max_age` frames are removed.
+    pub fn new(max_age: u64) -> VisibilityCache {
+        VisibilityCache {
+            entries: HashMap::new(),
+            frame: 0,
+            max_age,
+        }
+    }
+
+    /// Starts a new frame and removes stale entries.
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+        let (frame, max_age) = (self.frame, self.max_age);
+        self.entries.retain(|_, e| frame - e.last_frame <= max_age);
+    }
+
+    /// Returns whether the object was visible the last time it was culled,
+    /// or `None` if the object is not in the cache.
+    pub fn was_visible(&self, id: u64) -> Option<bool> {
+        self.entries.get(&id).map(|e| e.visible)
+    }
+
+    /// Returns the number of objects in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl Default for VisibilityCache {
+    fn default() -> Self {
+        VisibilityCache::new(60)
+    }
+}
+
+/// Statistics of a [Culler].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CullStats {
+    /// Number of objects tested.
+    pub tested: u32,
+    /// Number of visible objects.
+    pub visible: u32,
+    /// Number of visible objects that were not visible (or not in the cache) in the last frame.
+    pub became_visible: u32,
+}
+
+/// Tests objects against a frustum and records the draws of the visible ones.
+#[derive(Debug)]
+pub struct Culler<'c> {
+    frustum: Frustum,
+    cache: Option<&'c mut VisibilityCache>,
+    stats: CullStats,
+}
+
+impl<'c> Culler<'c> {
+    /// Creates a culler without temporal coherence.
+    pub fn new(frustum: Frustum) -> Culler<'c> {
+        Culler {
+            frustum,
+            cache: None,
+            stats: CullStats::default(),
+        }
+    }
+
+    /// Creates a culler that uses and updates the visibility of objects in `cache`.
+    ///
+    /// [VisibilityCache::begin_frame] should be called once per frame before culling.
+    pub fn with_cache(frustum: Frustum, cache: &'c mut VisibilityCache) -> Culler<'c> {
+        Culler {
+            frustum,
+            cache: Some(cache),
+            stats: CullStats::default(),
+        }
+    }
+
+    pub fn frustum(&self) -> &Frustum {
+        &self.frustum
+    }
+
+    /// Returns whether an object is visible. `id` identifies the object in the visibility cache
+    /// and is ignored if there is no cache.
+    pub fn is_visible(&mut self, id: u64, bounds: &Aabb) -> bool {
+        let (visible, was_visible) = match self.cache {
+            Some(ref mut cache) => {
+                let prev = cache.entries.get(&id).cloned();
+                let first = prev.map(|e| e.rejecting_plane as usize).unwrap_or(0);
+                let rejecting_plane = self.frustum.rejecting_plane(bounds, first);
+                cache.entries.insert(
+                    id,
+                    CacheEntry {
+                        visible: rejecting_plane.is_none(),
+                        rejecting_plane: rejecting_plane.unwrap_or(first) as u8,
+                        last_frame: cache.frame,
+                    },
+                );
+                (
+                    rejecting_plane.is_none(),
+                    prev.map(|e| e.visible).unwrap_or(false),
+                )
+            }
+            None => (self.frustum.intersects(bounds), false),
+        };
+
+        self.stats.tested += 1;
+        if visible {
+            self.stats.visible += 1;
+            if !was_visible {
+                self.stats.became_visible += 1;
+            }
+        }
+        visible
+    }
+
+    /// Calls `draw` to record the draw commands of an object into `cmdbuf` if the object
+    /// is visible. Returns whether the object is visible.
+    pub fn draw<'a, B: Backend, F>(
+        &mut self,
+        cmdbuf: &mut CommandBuffer<'a, B>,
+        id: u64,
+        bounds: &Aabb,
+        draw: F,
+    ) -> bool
+    where
+        F: FnOnce(&mut CommandBuffer<'a, B>),
+    {
+        let visible = self.is_visible(id, bounds);
+        if visible {
+            draw(cmdbuf);
+        }
+        visible
+    }
+
+    /// Same as [draw](Culler::draw) for all objects of an iterator of `(id, bounds, object)`.
+    pub fn draw_all<'a, B: Backend, T, I, F>(
+        &mut self,
+        cmdbuf: &mut CommandBuffer<'a, B>,
+        objects: I,
+        mut draw: F,
+    ) where
+        I: IntoIterator<Item = (u64, Aabb, T)>,
+        F: FnMut(&mut CommandBuffer<'a, B>, T),
+    {
+        for (id, bounds, object) in objects {
+            if self.is_visible(id, &bounds) {
+                draw(cmdbuf, object);
+            }
+        }
+    }
+
+    /// Returns the statistics since the creation of the culler.
+    pub fn stats(&self) -> CullStats {
+        self.stats
+    }
+}
@@ -0,0 +1,16 @@
+#version 450
+// Debug lines, transformed by a single view-projection matrix.
+
+layout(std140, set=0, binding=0) uniform Params {
+  mat4 view_proj;
+};
+
+layout(location=0) in vec3 a_position;
+layout(location=1) in vec4 a_color;
+
+layout(location=0) out vec4 v_color;
+
+void main() {
+  gl_Position = view_proj * vec4(a_position, 1.0);
+  v_color = a_color;
+}
@@ -0,0 +1,203 @@
+//! Drawing of lines, boxes and coordinate axes for debugging.
+//!
+//! A [DebugDraw] accumulates line segments during a frame. All segments are uploaded in a single
+//! vertex buffer and rendered with one draw call by [DebugDraw::draw], which can be layered over
+//! any pass by choosing an appropriate sort key:
+//! ```ignore
+//! debug_draw.line([0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0, 1.0]);
+//! debug_draw.aabb(bounds.min, bounds.max, [0.0, 1.0, 0.0, 1.0]);
+//! debug_draw.axes(&model_matrix);
+//! // after the main pass (sortkey 0x100)
+//! debug_draw.draw(&frame_arena, &mut cmdbuf, 0x1000, targets, &view_proj);
+//! ```
+//!
+//! Lines are drawn with a dynamic line width: use [CommandBuffer::set_line_width] with a lower
+//! sort key to change it.
+use autograph_api::{
+    buffer::{Buffer, StructuredBufferData, TypedConstantBufferView},
+    command::{CommandBuffer, DrawParams},
+    image::{DepthStencilView, RenderTargetView},
+    include_glsl,
+    pipeline::{
+        Arguments, ColorBlendState, DepthStencilState, GraphicsPipelineCreateInfo,
+        InputAssemblyState, LineWidth, MultisampleState, PrimitiveTopology, RasterisationState,
+        ReflectedShader, TypedGraphicsPipeline, ViewCount, Viewport, ViewportState,
+    },
+    vertex::VertexData,
+    Arena, Backend,
+};
+
+static DEBUG_DRAW_VERT: ReflectedShader = include_glsl!("debug_draw.vert");
+static DEBUG_DRAW_FRAG: ReflectedShader = include_glsl!("debug_draw.frag");
+
+/// Vertex of a debug line.
+#[derive(Copy, Clone, Debug, VertexData)]
+#[repr(C)]
+pub struct DebugVertex {
+    pub pos: [f32; 3],
+    pub color: [f32; 4],
+}
+
+#[derive(Copy, Clone, Debug, StructuredBufferData)]
+#[repr(C)]
+struct DebugDrawParams {
+    view_proj: [[f32; 4]; 4],
+}
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct DebugDrawArguments<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(depth_stencil_render_target)]
+    depth: DepthStencilView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, DebugDrawParams>,
+    #[argument(vertex_buffer)]
+    vertices: Buffer<'a, B, [DebugVertex]>,
+}
+
+/// Render targets of the debug draw.
+#[derive(Copy, Clone, Debug)]
+pub struct DebugDrawTargets<'a, B: Backend> {
+    pub color: RenderTargetView<'a, B>,
+    /// Depth buffer of the scene. Not written to.
+    pub depth: DepthStencilView<'a, B>,
+    pub viewport: Viewport,
+}
+
+/// Accumulates debug lines and draws them in a single draw call.
+pub struct DebugDraw<'a, B: Backend> {
+    pipeline: TypedGraphicsPipeline<'a, B, DebugDrawArguments<'a, B>>,
+    vertices: Vec<DebugVertex>,
+}
+
+impl<'a, B: Backend> DebugDraw<'a, B> {
+    /// Creates the debug draw pipeline in the specified arena.
+    ///
+    /// If `depth_test` is true, lines are hidden by the geometry in the depth buffer.
+    /// Lines are alpha-blended over the color target.
+    pub fn new(arena: &'a Arena<B>, depth_test: bool) -> DebugDraw<'a, B> {
+        let create_info = GraphicsPipelineCreateInfo {
+            shader_stages: arena
+                .create_vertex_fragment_shader_stages(DEBUG_DRAW_VERT, DEBUG_DRAW_FRAG),
+            viewport_state: ViewportState::default(),
+            rasterization_state: RasterisationState::DEFAULT.with_line_width(LineWidth::Dynamic),
+            multisample_state: MultisampleState::default(),
+            depth_stencil_state: DepthStencilState::LESS_EQUAL_READ_ONLY
+                .with_depth_test(depth_test),
+            input_assembly_state: InputAssemblyState {
+                topology: PrimitiveTopology::LineList,
+                primitive_restart_enable: false,
+            },
+            color_blend_state: ColorBlendState::ALPHA_BLENDING,
+            view_count: ViewCount::default(),
+        };
+
+        DebugDraw {
+            pipeline: arena.create_graphics_pipeline(&create_info),
+            vertices: Vec::new(),
+        }
+    }
+
+    /// Adds a line segment between `p0` and `p1`.
+    pub fn line(&mut self, p0: [f32; 3], p1: [f32; 3], color: [f32; 4]) {
+        self.vertices.push(DebugVertex { pos: p0, color });
+        self.vertices.push(DebugVertex { pos: p1, color });
+    }
+
+    /// Adds the edges of an axis-aligned box.
+    pub fn aabb(&mut self, min: [f32; 3], max: [f32; 3], color: [f32; 4]) {
+        let corner = |i: usize| {
+            [
+                if i & 1 != 0 { max[0] } else { min[0] },
+                if i & 2 != 0 { max[1] } else { min[1] },
+                if i & 4 != 0 { max[2] } else { min[2] },
+            ]
+        };
+        // edges connect corners that differ by one coordinate
+        for i in 0..8 {
+            for axis in &[1, 2, 4] {
+                if i & axis == 0 {
+                    self.line(corner(i), corner(i | axis), color);
+                }
+            }
+        }
+    }
+
+    /// Adds the unit X (red), Y (green) and Z (blue) axes of a coordinate frame.
+    ///
+    /// `transform` is a column-major matrix: its first three columns give the axes and
+    /// the last one the origin.
+    pub fn axes(&mut self, transform: &[[f32; 4]; 4]) {
+        let origin = [transform[3][0], transform[3][1], transform[3][2]];
+        let colors = [
+            [1.0, 0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0, 1.0],
+        ];
+        for (axis, &color) in transform.iter().zip(colors.iter()) {
+            let end = [
+                origin[0] + axis[0],
+                origin[1] + axis[1],
+                origin[2] + axis[2],
+            ];
+            self.line(origin, end, color);
+        }
+    }
+
+    /// Returns whether no lines have been added since the last draw.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Removes all lines without drawing them.
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    /// Records a draw of all accumulated lines, transformed by `view_proj` (column-major),
+    /// and clears the accumulator. Does nothing if there are no lines.
+    ///
+    /// The vertex buffer is allocated in `frame_arena`.
+    pub fn draw<'b>(
+        &mut self,
+        frame_arena: &'b Arena<'b, B>,
+        cmdbuf: &mut CommandBuffer<'b, B>,
+        sortkey: u64,
+        targets: DebugDrawTargets<'b, B>,
+        view_proj: &[[f32; 4]; 4],
+    ) where
+        'a: 'b,
+    {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        let vertices = frame_arena.upload_slice(&self.vertices);
+        let params = frame_arena.upload(&DebugDrawParams {
+            view_proj: *view_proj,
+        });
+
+        cmdbuf.draw(
+            sortkey,
+            frame_arena,
+            self.pipeline,
+            DebugDrawArguments {
+                color: targets.color,
+                depth: targets.depth,
+                viewport: targets.viewport,
+                params: params.into(),
+                vertices,
+            },
+            DrawParams {
+                vertex_count: self.vertices.len() as u32,
+                instance_count: 1,
+                first_vertex: 0,
+                first_instance: 0,
+            },
+        );
+        self.vertices.clear();
+    }
+}
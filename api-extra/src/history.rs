@@ -0,0 +1,195 @@
+//! Access to the results of previous frames.
+//!
+//! Temporal passes (antialiasing, motion blur, reprojection of reflections...) read what they
+//! wrote in previous frames. An [ImageHistory] keeps a ring of images in a long-lived arena, and
+//! hands out a different one each frame:
+//! ```ignore
+//! let mut taa_history = ImageHistory::new(
+//!     &persistent_arena,
+//!     Format::R16G16B16A16_SFLOAT,
+//!     Dimensions::swapchain_relative(1.0, 1.0),
+//!     2,
+//! );
+//! // each frame
+//! let output = taa_history.begin_frame();
+//! match taa_history.previous() {
+//!     // blend the current frame with the previous output
+//!     Some(previous) => taa_resolve(output, color, previous.sampled_linear()),
+//!     // first frame, or the window was resized: nothing to blend with
+//!     None => blit(output, color),
+//! }
+//! ```
+//!
+//! The images are allocated once, when the history is created. Swapchain-relative images
+//! follow the size of the window: they are resized by the backend, and the history is discarded
+//! when that happens.
+use autograph_api::{
+    format::{ColorSpace, Format},
+    image::{Dimensions, Image2d, ImageUsageFlags, MipmapsOption, RenderTargetView},
+    AliasScope, Arena, Backend,
+};
+
+/// Ring of versions of a resource, one for each of the last frames.
+///
+/// Version 0 is the version of the current frame, version 1 the one of the previous frame, and
+/// so on. Versions that were not written since the creation of the history, or since the last
+/// [invalidation](History::invalidate), are not returned.
+#[derive(Clone, Debug)]
+pub struct History<T> {
+    versions: Vec<T>,
+    /// Index of the version of the current frame.
+    current: usize,
+    /// Number of previous frames with valid versions.
+    valid: usize,
+}
+
+impl<T: Copy> History<T> {
+    /// Creates a history over the specified versions: their number is the number of frames
+    /// (including the current one) that are kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `versions` is empty.
+    pub fn new(versions: Vec<T>) -> History<T> {
+        assert!(!versions.is_empty(), "a history needs at least one version");
+        History {
+            versions,
+            current: 0,
+            valid: 0,
+        }
+    }
+
+    /// Returns the number of kept frames, including the current one.
+    pub fn frame_count(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// Starts a new frame: the oldest version becomes the version of the current frame.
+    pub fn advance(&mut self) {
+        self.current = (self.current + 1) % self.versions.len();
+        self.valid = (self.valid + 1).min(self.versions.len() - 1);
+    }
+
+    /// Returns the version of the current frame.
+    pub fn current(&self) -> T {
+        self.versions[self.current]
+    }
+
+    /// Returns the version written `age` frames ago, if any.
+    pub fn get(&self, age: usize) -> Option<T> {
+        if age > self.valid {
+            return None;
+        }
+        let len = self.versions.len();
+        Some(self.versions[(self.current + len - age) % len])
+    }
+
+    /// Returns the version of the previous frame, if any.
+    pub fn previous(&self) -> Option<T> {
+        self.get(1)
+    }
+
+    /// Discards the versions of the previous frames.
+    pub fn invalidate(&mut self) {
+        self.valid = 0;
+    }
+}
+
+/// History of a 2D image that follows the size of the frame.
+pub struct ImageHistory<'a, B: Backend> {
+    history: History<Image2d<'a, B>>,
+    /// Size of the images during the previous frame.
+    size: Option<(u32, u32)>,
+}
+
+impl<'a, B: Backend> ImageHistory<'a, B> {
+    /// Creates a history of `len` images (including the one of the current frame) allocated in
+    /// `arena`.
+    ///
+    /// With [swapchain-relative](Dimensions::SwapchainRelative) dimensions, the images are
+    /// resized with the default swapchain (see [Arena::create_image]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero, or if `dimensions` are not 2D dimensions with one array layer.
+    pub fn new(
+        arena: &'a Arena<'a, B>,
+        format: Format,
+        dimensions: Dimensions,
+        len: usize,
+    ) -> ImageHistory<'a, B> {
+        assert!(len > 0, "a history needs at least one image");
+        match dimensions {
+            Dimensions::Dim2d {
+                array_layers: 1, ..
+            }
+            | Dimensions::SwapchainRelative { .. } => {}
+            _ => panic!("invalid dimensions for an image history: {:?}", dimensions),
+        }
+        let usage = if format.color_space() == ColorSpace::Srgb {
+            ImageUsageFlags::COLOR_ATTACHMENT | ImageUsageFlags::SAMPLED
+        } else {
+            ImageUsageFlags::COLOR_ATTACHMENT | ImageUsageFlags::SAMPLED | ImageUsageFlags::STORAGE
+        };
+        let images = (0..len)
+            .map(|_| {
+                let image = arena.create_image(
+                    AliasScope::no_alias(),
+                    format,
+                    dimensions,
+                    MipmapsOption::NoMipmap,
+                    1,
+                    usage,
+                    None,
+                );
+                unsafe { Image2d::from_raw(image.inner()) }
+            })
+            .collect();
+        ImageHistory {
+            history: History::new(images),
+            size: None,
+        }
+    }
+
+    /// Starts a new frame and returns the image to write during this frame.
+    ///
+    /// If the images were resized since the previous frame, the previous frames are discarded.
+    pub fn begin_frame(&mut self) -> Image2d<'a, B> {
+        let size = self.size();
+        if self.size.is_some() {
+            self.history.advance();
+        }
+        if self.size != Some(size) {
+            self.history.invalidate();
+            self.size = Some(size);
+        }
+        self.history.current()
+    }
+
+    /// Returns the image of the current frame.
+    pub fn current(&self) -> Image2d<'a, B> {
+        self.history.current()
+    }
+
+    /// Returns the image written `age` frames ago, if any.
+    pub fn get(&self, age: usize) -> Option<Image2d<'a, B>> {
+        self.history.get(age)
+    }
+
+    /// Returns the image of the previous frame, if any.
+    pub fn previous(&self) -> Option<Image2d<'a, B>> {
+        self.get(1)
+    }
+
+    /// Discards the images of the previous frames (e.g. after a camera cut).
+    pub fn invalidate(&mut self) {
+        self.history.invalidate();
+    }
+
+    /// Returns the current size of the images.
+    ///
+    /// The size of swapchain-relative images is the one of the last frame submission.
+    pub fn size(&self) -> (u32, u32) {
+        RenderTargetView::from(self.history.current()).size()
+    }
+}
@@ -1,3 +1,12 @@
+#![feature(proc_macro_hygiene)]
 pub mod blackboard;
 pub mod commandext;
+pub mod culling;
+pub mod debugdraw;
+pub mod history;
+pub mod picking;
 pub mod quad;
+pub mod reduction;
+pub mod sprite;
+pub mod tiles;
+pub mod transient;
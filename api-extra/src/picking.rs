@@ -0,0 +1,178 @@
+//! Selection of objects under the cursor.
+//!
+//! Objects are drawn in an `R32_UINT` image of object IDs (see [create_id_image]), with
+//! pipelines whose arguments inherit [PickingTargets] and whose fragment shaders output the ID
+//! of the object:
+//! ```glsl
+//! layout(location=0) out uint out_id;
+//! ```
+//! ID 0 is reserved for the background: the image should be cleared to 0 before drawing.
+//!
+//! A [Picker] then copies the ID under the cursor to host memory. The result can be retrieved
+//! once the frame has been submitted and its arena dropped, typically at the beginning of the
+//! next frame:
+//! ```ignore
+//! // frame N
+//! cmdbuf.clear_render_target(0x000, ids, &[0u32; 4]);
+//! // ... draw objects in `ids` with sortkeys 0x100..0x1FF
+//! picker.pick(&frame_arena, &mut cmdbuf, 0x200, ids.into(), cursor);
+//! // frame N + 1
+//! if let Some(result) = picker.poll() {
+//!     selection = result.id;
+//! }
+//! ```
+use autograph_api::{
+    buffer::{StructuredBufferData, TypedConstantBufferView, TypedRwBufferView},
+    command::CommandBuffer,
+    format::Format,
+    image::{DepthStencilView, Image2d, RenderTargetView, RwImage2dView},
+    include_glsl,
+    pipeline::{Arguments, ReflectedShader, TypedComputePipeline, Viewport},
+    Arena, Backend,
+};
+
+static PICK_COMP: ReflectedShader = include_glsl!("pick.comp");
+
+/// Format of the images of object IDs.
+pub const ID_FORMAT: Format = Format::R32_UINT;
+
+/// Creates an image of object IDs of the specified size, usable as a render target and as a
+/// storage image.
+pub fn create_id_image<'a, B: Backend>(
+    arena: &'a Arena<B>,
+    (width, height): (u32, u32),
+) -> Image2d<'a, B> {
+    arena.image_2d(ID_FORMAT, width, height).build()
+}
+
+/// Render targets of the passes that write object IDs.
+///
+/// Inherit in the arguments of the pipelines of these passes:
+/// ```ignore
+/// #[derive(Arguments)]
+/// struct PickingMeshArguments<'a, B: Backend> {
+///     #[argument(inherit)]
+///     targets: PickingTargets<'a, B>,
+///     #[argument(descriptor)]
+///     object: TypedConstantBufferView<'a, B, ObjectId>,
+///     #[argument(vertex_buffer)]
+///     vertices: Buffer<'a, B, [Vertex]>,
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Arguments)]
+pub struct PickingTargets<'a, B: Backend> {
+    /// Image of object IDs.
+    #[argument(render_target)]
+    pub ids: RenderTargetView<'a, B>,
+    #[argument(depth_stencil_render_target)]
+    pub depth: DepthStencilView<'a, B>,
+    #[argument(viewport)]
+    pub viewport: Viewport,
+}
+
+#[derive(Copy, Clone, Debug, StructuredBufferData)]
+#[repr(C)]
+struct PickParams {
+    x: u32,
+    y: u32,
+}
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct PickArguments<'a, B: Backend> {
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, PickParams>,
+    #[argument(descriptor)]
+    ids: RwImage2dView<'a, B>,
+    #[argument(descriptor)]
+    result: TypedRwBufferView<'a, B, [u32]>,
+}
+
+/// Result of a pick.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PickResult {
+    /// Position passed to [Picker::pick].
+    pub position: (u32, u32),
+    /// ID of the object at this position, or `None` for the background.
+    pub id: Option<u32>,
+}
+
+/// Reads back object IDs at the position of the cursor.
+pub struct Picker<'a, B: Backend> {
+    pipeline: TypedComputePipeline<'a, B, PickArguments<'a, B>>,
+    /// Destination of the readback.
+    result: [u32; 1],
+    /// Position of the last pick that was not polled.
+    pending: Option<(u32, u32)>,
+}
+
+impl<'a, B: Backend> Picker<'a, B> {
+    /// Creates the picking pipeline in the specified arena.
+    pub fn new(arena: &'a Arena<B>) -> Picker<'a, B> {
+        let shader = arena.create_shader_module(PICK_COMP);
+        Picker {
+            pipeline: arena.create_compute_pipeline(shader),
+            result: [0],
+            pending: None,
+        }
+    }
+
+    /// Records the readback of the ID at `position` (in pixels, from the top-left corner) in
+    /// `ids`.
+    ///
+    /// The picker stays borrowed until the frame is submitted and `frame_arena` is dropped.
+    /// The ID must be drawn by commands with a lower sort key. A pick that was not polled is
+    /// replaced.
+    pub fn pick<'b>(
+        &'b mut self,
+        frame_arena: &'b Arena<'b, B>,
+        cmdbuf: &mut CommandBuffer<'b, B>,
+        sortkey: u64,
+        ids: RwImage2dView<'b, B>,
+        position: (u32, u32),
+    ) where
+        'a: 'b,
+    {
+        let params = frame_arena.upload(&PickParams {
+            x: position.0,
+            y: position.1,
+        });
+        let result = frame_arena.upload_slice(&[0u32]);
+
+        cmdbuf.dispatch(
+            sortkey,
+            frame_arena,
+            self.pipeline,
+            PickArguments {
+                params: params.into(),
+                ids,
+                result: result.into(),
+            },
+            (1, 1, 1),
+        );
+        // same sort key: executed after the dispatch
+        cmdbuf.copy_buffer_to_host(
+            sortkey,
+            result,
+            frame_arena.host_readback(&mut self.result[..]),
+        );
+        self.pending = Some(position);
+    }
+
+    /// Returns the result of the last pick, if it was not already returned.
+    ///
+    /// The result is only valid once the frame of the pick has been submitted.
+    pub fn poll(&mut self) -> Option<PickResult> {
+        let result = self.result[0];
+        self.pending.take().map(|position| PickResult {
+            position,
+            id: if result == 0 { None } else { Some(result) },
+        })
+    }
+
+    /// Calls `f` with the result of the last pick, if it was not already returned.
+    pub fn poll_with(&mut self, f: impl FnOnce(PickResult)) {
+        if let Some(result) = self.poll() {
+            f(result)
+        }
+    }
+}
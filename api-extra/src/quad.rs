@@ -33,6 +33,7 @@ unsafe impl VertexData for QuadVertex {
                 }),
                 format: Format::R32G32_SFLOAT,
                 offset: 0,
+                name: Some("pos"),
             },
             VertexLayoutElement {
                 semantic: Some(Semantic {
@@ -41,6 +42,7 @@ unsafe impl VertexData for QuadVertex {
                 }),
                 format: Format::R32G32_SFLOAT,
                 offset: 8,
+                name: Some("tex"),
             },
         ],
         stride: 16,
@@ -68,7 +70,9 @@ impl<'a, B: Backend> Arguments<'a, B> for QuadVertices<'a, B> {
         signature: TypedSignature<'a, B, Self::IntoInterface>,
         arena: &'a Arena<B>,
     ) -> ArgumentBlock<'a, B, TypedSignature<'a, B, Self::IntoInterface>> {
-        let (left, top, right, bottom) = (-1.0, -1.0, 1.0, 1.0);
+        // texcoord (0,0) is the top-left corner of the image
+        let top = arena.api().clip_space_info().top();
+        let (left, right, bottom) = (-1.0, 1.0, -top);
 
         let verts = arena.upload_slice(&[
             QuadVertex::new([left, top], [0.0, 0.0]),
@@ -89,6 +93,7 @@ impl<'a, B: Backend> Arguments<'a, B> for QuadVertices<'a, B> {
             None,
             iter::empty(),
             iter::empty(),
+            None,
         )
     }
 }
@@ -146,6 +151,7 @@ impl<'a, B: Backend, A: Arguments<'a, B> + 'a> Arguments<'a, B> for Quad<'a, B,
             None,
             iter::empty(),
             iter::empty(),
+            None,
         )
     }
 }
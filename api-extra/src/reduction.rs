@@ -0,0 +1,240 @@
+//! Compute passes that reduce an image to a pyramid of min, max or average values.
+//!
+//! A [ReductionPass] reads a texture and writes a chain of `R32_SFLOAT` images, each one half
+//! the size of the previous one (rounded down, at least 1). Typical uses are:
+//! - depth pyramids for occlusion culling (Hi-Z), with [ReductionOp::Max] over the red channel
+//!   of the depth buffer: each texel of a level covers all the texels of the previous level
+//!   under it, including the extra row or column of odd sizes;
+//! - the average log-luminance of a frame for auto-exposure, with [ReductionOp::Average] over
+//!   [ReductionSource::LogLuminance] and [ReductionMode::SinglePass]:
+//! ```ignore
+//! let exposure = ReductionPass::new(
+//!     &arena,
+//!     ReductionOp::Average,
+//!     ReductionSource::LogLuminance,
+//!     ReductionMode::SinglePass,
+//! );
+//! let src = color.sampled_nearest();
+//! exposure.record(&mut cmdbuf, 0x200, &frame_arena, src, (width, height), &[avg_log_lum.into()]);
+//! ```
+use autograph_api::{
+    buffer::{StructuredBufferData, TypedConstantBufferView},
+    command::CommandBuffer,
+    image::{RwImage2dView, TextureSampler2dView},
+    include_glsl,
+    pipeline::{Arguments, ReflectedShader, TypedComputePipeline},
+    Arena, Backend,
+};
+
+static REDUCE_FIRST_COMP: ReflectedShader = include_glsl!("reduce_first.comp");
+static REDUCE_NEXT_COMP: ReflectedShader = include_glsl!("reduce_next.comp");
+static REDUCE_SINGLE_COMP: ReflectedShader = include_glsl!("reduce_single.comp");
+
+/// Workgroup size of the per-level shaders, in both dimensions.
+const LEVEL_GROUP_SIZE: u32 = 8;
+
+/// Operation applied to the texels covered by each texel of a level.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ReductionOp {
+    Min,
+    Max,
+    Average,
+}
+
+/// Value read from each texel of the source texture.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ReductionSource {
+    /// The red channel (e.g. depth).
+    Red,
+    /// The natural logarithm of the luminance of the RGB channels (Rec. 709 coefficients).
+    LogLuminance,
+}
+
+/// How the levels are computed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ReductionMode {
+    /// One dispatch per level, each one reading the previous level.
+    PingPong,
+    /// A single dispatch of one workgroup, which reduces the whole source to one value.
+    ///
+    /// The destination chain must contain a single 1x1 image. Faster than [ReductionMode::PingPong]
+    /// when only the final value is needed and the source is small (e.g. a downscaled frame).
+    SinglePass,
+}
+
+#[derive(Copy, Clone, Debug, StructuredBufferData)]
+#[repr(C)]
+struct ReductionParams {
+    src_width: u32,
+    src_height: u32,
+    dst_width: u32,
+    dst_height: u32,
+    op: u32,
+    source: u32,
+    _pad0: u32,
+    _pad1: u32,
+}
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct TextureReductionArguments<'a, B: Backend> {
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, ReductionParams>,
+    #[argument(descriptor)]
+    src: TextureSampler2dView<'a, B>,
+    #[argument(descriptor)]
+    dst: RwImage2dView<'a, B>,
+}
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct ImageReductionArguments<'a, B: Backend> {
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, ReductionParams>,
+    #[argument(descriptor)]
+    src: RwImage2dView<'a, B>,
+    #[argument(descriptor)]
+    dst: RwImage2dView<'a, B>,
+}
+
+enum Pipelines<'a, B: Backend> {
+    PingPong {
+        first: TypedComputePipeline<'a, B, TextureReductionArguments<'a, B>>,
+        next: TypedComputePipeline<'a, B, ImageReductionArguments<'a, B>>,
+    },
+    SinglePass(TypedComputePipeline<'a, B, TextureReductionArguments<'a, B>>),
+}
+
+/// Reduces textures to pyramids of values.
+pub struct ReductionPass<'a, B: Backend> {
+    op: ReductionOp,
+    source: ReductionSource,
+    pipelines: Pipelines<'a, B>,
+}
+
+/// Returns the size of the level that follows a level of size `(width, height)`.
+fn next_level_size((width, height): (u32, u32)) -> (u32, u32) {
+    ((width / 2).max(1), (height / 2).max(1))
+}
+
+impl<'a, B: Backend> ReductionPass<'a, B> {
+    /// Creates the pipelines of the pass in the specified arena.
+    pub fn new(
+        arena: &'a Arena<B>,
+        op: ReductionOp,
+        source: ReductionSource,
+        mode: ReductionMode,
+    ) -> ReductionPass<'a, B> {
+        let pipelines = match mode {
+            ReductionMode::PingPong => Pipelines::PingPong {
+                first: arena.create_compute_pipeline(arena.create_shader_module(REDUCE_FIRST_COMP)),
+                next: arena.create_compute_pipeline(arena.create_shader_module(REDUCE_NEXT_COMP)),
+            },
+            ReductionMode::SinglePass => Pipelines::SinglePass(
+                arena.create_compute_pipeline(arena.create_shader_module(REDUCE_SINGLE_COMP)),
+            ),
+        };
+        ReductionPass {
+            op,
+            source,
+            pipelines,
+        }
+    }
+
+    fn params(&self, src_size: (u32, u32), dst_size: (u32, u32)) -> ReductionParams {
+        ReductionParams {
+            src_width: src_size.0,
+            src_height: src_size.1,
+            dst_width: dst_size.0,
+            dst_height: dst_size.1,
+            op: match self.op {
+                ReductionOp::Min => 0,
+                ReductionOp::Max => 1,
+                ReductionOp::Average => 2,
+            },
+            source: match self.source {
+                ReductionSource::Red => 0,
+                ReductionSource::LogLuminance => 1,
+            },
+            _pad0: 0,
+            _pad1: 0,
+        }
+    }
+
+    /// Records the reduction of `src` (of size `src_size`, read at its base level) into the
+    /// images of `dst_chain`.
+    ///
+    /// Level `i` of the chain must be an `R32_SFLOAT` image of the size of the previous level
+    /// (or `src_size` for the first level) divided by two, rounded down, and at least 1.
+    /// All dispatches have sort key `sortkey`: the results can be used by commands with a greater
+    /// sort key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pass is single-pass and `dst_chain` does not contain exactly one image.
+    pub fn record<'b>(
+        &self,
+        cmdbuf: &mut CommandBuffer<'b, B>,
+        sortkey: u64,
+        frame_arena: &'b Arena<'b, B>,
+        src: TextureSampler2dView<'b, B>,
+        src_size: (u32, u32),
+        dst_chain: &[RwImage2dView<'b, B>],
+    ) where
+        'a: 'b,
+    {
+        match self.pipelines {
+            Pipelines::PingPong { first, next } => {
+                let mut size = src_size;
+                for (i, &dst) in dst_chain.iter().enumerate() {
+                    let dst_size = next_level_size(size);
+                    let params = frame_arena.upload(&self.params(size, dst_size)).into();
+                    let group_count = (
+                        (dst_size.0 + LEVEL_GROUP_SIZE - 1) / LEVEL_GROUP_SIZE,
+                        (dst_size.1 + LEVEL_GROUP_SIZE - 1) / LEVEL_GROUP_SIZE,
+                        1,
+                    );
+                    if i == 0 {
+                        cmdbuf.dispatch(
+                            sortkey,
+                            frame_arena,
+                            first,
+                            TextureReductionArguments { params, src, dst },
+                            group_count,
+                        );
+                    } else {
+                        cmdbuf.dispatch(
+                            sortkey,
+                            frame_arena,
+                            next,
+                            ImageReductionArguments {
+                                params,
+                                src: dst_chain[i - 1],
+                                dst,
+                            },
+                            group_count,
+                        );
+                    }
+                    size = dst_size;
+                }
+            }
+            Pipelines::SinglePass(pipeline) => {
+                assert_eq!(
+                    dst_chain.len(),
+                    1,
+                    "single-pass reductions have exactly one destination"
+                );
+                let params = frame_arena.upload(&self.params(src_size, (1, 1))).into();
+                cmdbuf.dispatch(
+                    sortkey,
+                    frame_arena,
+                    pipeline,
+                    TextureReductionArguments {
+                        params,
+                        src,
+                        dst: dst_chain[0],
+                    },
+                    (1, 1, 1),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,11 @@
+#version 450
+
+layout(set=0, binding=1) uniform sampler2D u_texture;
+
+layout(location=0) in vec2 v_texcoord;
+layout(location=1) in vec4 v_color;
+layout(location=0) out vec4 out_color;
+
+void main() {
+  out_color = v_color * texture(u_texture, v_texcoord);
+}
@@ -0,0 +1,285 @@
+//! Batched rendering of textured 2D quads.
+//!
+//! A [SpriteBatch] accumulates sprites during a frame. On [SpriteBatch::draw], the sprites are
+//! sorted by layer, then by texture, and their vertices are uploaded in a single vertex buffer.
+//! One draw call is recorded for each run of sprites that share a layer and a texture:
+//! ```ignore
+//! let projection = clip::ortho_for(arena.api(), 0.0, width, height, 0.0, -1.0, 1.0);
+//! sprites.push(Sprite::new(tiles, [0.0, 0.0], [32.0, 32.0]).with_uv([0.0, 0.0, 0.25, 0.25]));
+//! sprites.push(Sprite::new(cursor, mouse_pos, [16.0, 16.0]).with_layer(1));
+//! sprites.draw(&frame_arena, &mut cmdbuf, 0x1000, targets, &projection);
+//! ```
+//!
+//! The layer of a sprite is added to the sort key of its draw calls: sprites of higher layers
+//! are drawn over the ones of lower layers, and commands of other passes can be placed between
+//! two layers.
+use autograph_api::{
+    buffer::{Buffer, StructuredBufferData, TypedConstantBufferView},
+    command::{CommandBuffer, DrawParams},
+    glm,
+    image::{RenderTargetView, TextureSampler2dView},
+    include_glsl,
+    pipeline::{
+        Arguments, ColorBlendState, DepthStencilState, GraphicsPipelineCreateInfo,
+        InputAssemblyState, MultisampleState, RasterisationState, ReflectedShader,
+        TypedGraphicsPipeline, ViewCount, Viewport, ViewportState,
+    },
+    vertex::VertexData,
+    Arena, Backend,
+};
+
+static SPRITE_VERT: ReflectedShader = include_glsl!("sprite.vert");
+static SPRITE_FRAG: ReflectedShader = include_glsl!("sprite.frag");
+
+/// Vertex of a sprite, in the space of the projection.
+#[derive(Copy, Clone, Debug, VertexData)]
+#[repr(C)]
+pub struct SpriteVertex {
+    pub pos: [f32; 2],
+    pub tex: [f32; 2],
+    pub color: [f32; 4],
+}
+
+#[derive(Copy, Clone, Debug, StructuredBufferData)]
+#[repr(C)]
+struct SpriteParams {
+    projection: glm::Mat4,
+}
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct SpriteArguments<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, SpriteParams>,
+    #[argument(descriptor)]
+    texture: TextureSampler2dView<'a, B>,
+    #[argument(vertex_buffer)]
+    vertices: Buffer<'a, B, [SpriteVertex]>,
+}
+
+/// Render target of a sprite batch.
+#[derive(Copy, Clone, Debug)]
+pub struct SpriteTargets<'a, B: Backend> {
+    pub color: RenderTargetView<'a, B>,
+    pub viewport: Viewport,
+}
+
+/// A textured quad.
+#[derive(Copy, Clone, Debug)]
+pub struct Sprite<'a, B: Backend> {
+    pub texture: TextureSampler2dView<'a, B>,
+    /// Area of the texture, in texture coordinates: `[left, top, right, bottom]`.
+    pub uv: [f32; 4],
+    /// Size of the quad before the transform.
+    pub size: [f32; 2],
+    /// Affine transform of the quad, whose corners are `(0,0)` and `size` before the transform.
+    ///
+    /// The columns are the X axis, the Y axis and the translation.
+    pub transform: [[f32; 2]; 3],
+    /// Color multiplied with the texture.
+    pub color: [f32; 4],
+    /// Layer of the sprite, added to the sort key of the batch.
+    pub layer: u16,
+}
+
+impl<'a, B: Backend> Sprite<'a, B> {
+    /// Creates a sprite showing the whole texture, with its top-left corner at `pos`.
+    pub fn new(texture: TextureSampler2dView<'a, B>, pos: [f32; 2], size: [f32; 2]) -> Self {
+        Sprite {
+            texture,
+            uv: [0.0, 0.0, 1.0, 1.0],
+            size,
+            transform: [[1.0, 0.0], [0.0, 1.0], pos],
+            color: [1.0, 1.0, 1.0, 1.0],
+            layer: 0,
+        }
+    }
+
+    pub fn with_uv(mut self, uv: [f32; 4]) -> Self {
+        self.uv = uv;
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: u16) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Replaces the transform of the sprite.
+    ///
+    /// The position given to [Sprite::new] is the translation of the default transform, and is
+    /// overwritten.
+    pub fn with_transform(mut self, transform: [[f32; 2]; 3]) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    fn corner(&self, x: f32, y: f32) -> [f32; 2] {
+        let t = &self.transform;
+        [
+            t[0][0] * x + t[1][0] * y + t[2][0],
+            t[0][1] * x + t[1][1] * y + t[2][1],
+        ]
+    }
+
+    /// Appends the two triangles of the sprite.
+    fn append_vertices(&self, out: &mut Vec<SpriteVertex>) {
+        let [w, h] = self.size;
+        let [u0, v0, u1, v1] = self.uv;
+        let color = self.color;
+        let vertex = |x, y, u, v| SpriteVertex {
+            pos: self.corner(x, y),
+            tex: [u, v],
+            color,
+        };
+        let top_left = vertex(0.0, 0.0, u0, v0);
+        let top_right = vertex(w, 0.0, u1, v0);
+        let bottom_left = vertex(0.0, h, u0, v1);
+        let bottom_right = vertex(w, h, u1, v1);
+        out.extend_from_slice(&[
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_left,
+            top_right,
+            bottom_right,
+        ]);
+    }
+}
+
+fn texture_address<B: Backend>(texture: &TextureSampler2dView<B>) -> usize {
+    texture.inner() as *const B::Image as usize
+}
+
+fn same_texture<B: Backend>(a: &TextureSampler2dView<B>, b: &TextureSampler2dView<B>) -> bool {
+    texture_address(a) == texture_address(b)
+        && a.subresource() == b.subresource()
+        && a.sampler() == b.sampler()
+}
+
+/// Accumulates sprites and draws them with as few draw calls as possible.
+pub struct SpriteBatch<'a, B: Backend> {
+    pipeline: TypedGraphicsPipeline<'a, B, SpriteArguments<'a, B>>,
+    sprites: Vec<Sprite<'a, B>>,
+    vertices: Vec<SpriteVertex>,
+}
+
+impl<'a, B: Backend> SpriteBatch<'a, B> {
+    /// Creates the sprite pipeline in the specified arena.
+    ///
+    /// Sprites are alpha-blended over the color target, without depth test.
+    pub fn new(arena: &'a Arena<B>) -> SpriteBatch<'a, B> {
+        let create_info = GraphicsPipelineCreateInfo {
+            shader_stages: arena.create_vertex_fragment_shader_stages(SPRITE_VERT, SPRITE_FRAG),
+            viewport_state: ViewportState::default(),
+            rasterization_state: RasterisationState::DEFAULT,
+            multisample_state: MultisampleState::default(),
+            depth_stencil_state: DepthStencilState::default(),
+            input_assembly_state: InputAssemblyState::default(),
+            color_blend_state: ColorBlendState::ALPHA_BLENDING,
+            view_count: ViewCount::default(),
+        };
+
+        SpriteBatch {
+            pipeline: arena.create_graphics_pipeline(&create_info),
+            sprites: Vec::new(),
+            vertices: Vec::new(),
+        }
+    }
+
+    /// Adds a sprite to the batch.
+    pub fn push(&mut self, sprite: Sprite<'a, B>) {
+        self.sprites.push(sprite);
+    }
+
+    /// Returns the number of sprites added since the last draw.
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    /// Returns whether no sprites have been added since the last draw.
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// Removes all sprites without drawing them.
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+
+    /// Records the draws of all accumulated sprites, transformed by `projection`, and clears the
+    /// batch. Does nothing if there are no sprites.
+    ///
+    /// The draws of the sprites of layer `n` have the sort key `sortkey + n`. Within a layer,
+    /// sprites that share a texture are drawn in the order in which they were added, but the
+    /// order between textures is unspecified.
+    ///
+    /// The vertex buffer is allocated in `frame_arena`.
+    pub fn draw<'b>(
+        &mut self,
+        frame_arena: &'b Arena<'b, B>,
+        cmdbuf: &mut CommandBuffer<'b, B>,
+        sortkey: u64,
+        targets: SpriteTargets<'b, B>,
+        projection: &glm::Mat4,
+    ) where
+        'a: 'b,
+    {
+        if self.sprites.is_empty() {
+            return;
+        }
+
+        // stable sort: keeps the order of the sprites of each texture
+        self.sprites
+            .sort_by_key(|s| (s.layer, texture_address(&s.texture)));
+
+        self.vertices.clear();
+        for sprite in self.sprites.iter() {
+            sprite.append_vertices(&mut self.vertices);
+        }
+        let vertices = frame_arena.upload_slice(&self.vertices);
+        let params = frame_arena.upload(&SpriteParams {
+            projection: *projection,
+        });
+
+        let mut first = 0;
+        while first < self.sprites.len() {
+            let sprite = &self.sprites[first];
+            let count = self.sprites[first..]
+                .iter()
+                .take_while(|s| {
+                    s.layer == sprite.layer && same_texture(&s.texture, &sprite.texture)
+                })
+                .count();
+
+            cmdbuf.draw(
+                sortkey + u64::from(sprite.layer),
+                frame_arena,
+                self.pipeline,
+                SpriteArguments {
+                    color: targets.color,
+                    viewport: targets.viewport,
+                    params: params.into(),
+                    texture: sprite.texture,
+                    vertices,
+                },
+                DrawParams {
+                    vertex_count: 6 * count as u32,
+                    instance_count: 1,
+                    first_vertex: 6 * first as u32,
+                    first_instance: 0,
+                },
+            );
+            first += count;
+        }
+        self.sprites.clear();
+    }
+}
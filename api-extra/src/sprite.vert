@@ -0,0 +1,19 @@
+#version 450
+// Sprites, with vertices already transformed in the space of the projection.
+
+layout(std140, set=0, binding=0) uniform Params {
+  mat4 projection;
+};
+
+layout(location=0) in vec2 a_position;
+layout(location=1) in vec2 a_texcoord;
+layout(location=2) in vec4 a_color;
+
+layout(location=0) out vec2 v_texcoord;
+layout(location=1) out vec4 v_color;
+
+void main() {
+  gl_Position = projection * vec4(a_position, 0.0, 1.0);
+  v_texcoord = a_texcoord;
+  v_color = a_color;
+}
@@ -0,0 +1,135 @@
+//! Screen-space tile classification.
+//!
+//! Expensive screen-space filters often only need to run on the parts of the screen covered by
+//! some groups of objects. The [TileClassifier] runs a compute pass over an image of per-pixel
+//! object group masks (one bit per group, in an `R32_UINT` image) and builds the list of
+//! `TILE_SIZE`x`TILE_SIZE` tiles that contain at least one pixel of the requested groups,
+//! along with the parameters of an indirect dispatch with one workgroup per tile.
+//!
+//! Subsequent filters are dispatched with [CommandBuffer::dispatch_indirect] and read the
+//! coordinates of their tile from the tile list:
+//! ```glsl
+//! layout(std430, set=0, binding=0) buffer Tiles { uint tiles[]; };
+//! ...
+//! uint t = tiles[gl_WorkGroupID.x];
+//! uvec2 tile = uvec2(t & 0xFFFF, t >> 16);
+//! ```
+use autograph_api::{
+    buffer::{Buffer, StructuredBufferData, TypedConstantBufferView, TypedRwBufferView},
+    command::{CommandBuffer, DispatchIndirectCommand},
+    image::RwImage2dView,
+    include_glsl,
+    pipeline::{Arguments, ReflectedShader, TypedComputePipeline},
+    Arena, Backend,
+};
+use std::mem;
+
+/// Size in pixels of the side of a tile.
+pub const TILE_SIZE: u32 = 16;
+
+static TILE_CLASSIFY_COMP: ReflectedShader = include_glsl!("tile_classify.comp");
+
+#[derive(Copy, Clone, Debug, StructuredBufferData)]
+#[repr(C)]
+struct ClassifyParams {
+    group_mask: u32,
+    width: u32,
+    height: u32,
+    _pad: u32,
+}
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct ClassifyArguments<'a, B: Backend> {
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, ClassifyParams>,
+    #[argument(descriptor)]
+    object_groups: RwImage2dView<'a, B>,
+    #[argument(descriptor)]
+    dispatch: TypedRwBufferView<'a, B, DispatchIndirectCommand>,
+    #[argument(descriptor)]
+    tiles: TypedRwBufferView<'a, B, [u32]>,
+}
+
+/// Output of a classification pass.
+#[derive(Copy, Clone, Debug)]
+pub struct ClassifiedTiles<'a, B: Backend> {
+    /// Indirect dispatch parameters: one workgroup for each tile in `tiles`.
+    pub dispatch: Buffer<'a, B, DispatchIndirectCommand>,
+    /// Coordinates of the selected tiles, packed as `x | y << 16`.
+    pub tiles: Buffer<'a, B, [u32]>,
+    /// Number of tiles horizontally and vertically.
+    pub num_tiles: (u32, u32),
+}
+
+/// Classifies screen tiles by object group masks.
+pub struct TileClassifier<'a, B: Backend> {
+    pipeline: TypedComputePipeline<'a, B, ClassifyArguments<'a, B>>,
+}
+
+impl<'a, B: Backend> TileClassifier<'a, B> {
+    /// Creates the classification pipeline in the specified arena.
+    pub fn new(arena: &'a Arena<B>) -> TileClassifier<'a, B> {
+        let shader = arena.create_shader_module(TILE_CLASSIFY_COMP);
+        TileClassifier {
+            pipeline: arena.create_compute_pipeline(shader),
+        }
+    }
+
+    /// Records a classification pass of the tiles of `object_groups` (of size `width`x`height`)
+    /// against `group_mask`.
+    ///
+    /// The returned buffers are allocated in `frame_arena` and are written by the GPU when the
+    /// command is executed: they can only be used by commands with a greater sort key.
+    pub fn classify<'b>(
+        &self,
+        frame_arena: &'b Arena<'b, B>,
+        cmdbuf: &mut CommandBuffer<'b, B>,
+        sortkey: u64,
+        object_groups: RwImage2dView<'b, B>,
+        (width, height): (u32, u32),
+        group_mask: u32,
+    ) -> ClassifiedTiles<'b, B>
+    where
+        'a: 'b,
+    {
+        let num_tiles = (
+            (width + TILE_SIZE - 1) / TILE_SIZE,
+            (height + TILE_SIZE - 1) / TILE_SIZE,
+        );
+
+        let params = frame_arena.upload(&ClassifyParams {
+            group_mask,
+            width,
+            height,
+            _pad: 0,
+        });
+        // the shader increments group_count_x for each selected tile
+        let dispatch = frame_arena.upload(&DispatchIndirectCommand {
+            group_count_x: 0,
+            group_count_y: 1,
+            group_count_z: 1,
+        });
+        let tiles_size = (num_tiles.0 * num_tiles.1) as usize * mem::size_of::<u32>();
+        let tiles: Buffer<B, [u32]> =
+            unsafe { Buffer::from_raw(frame_arena.create_buffer_typeless(tiles_size as u64).0) };
+
+        cmdbuf.dispatch(
+            sortkey,
+            frame_arena,
+            self.pipeline,
+            ClassifyArguments {
+                params: params.into(),
+                object_groups,
+                dispatch: dispatch.into(),
+                tiles: tiles.into(),
+            },
+            (num_tiles.0, num_tiles.1, 1),
+        );
+
+        ClassifiedTiles {
+            dispatch,
+            tiles,
+            num_tiles,
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Pool of temporary images for post-processing chains.
+//!
+//! Intermediate images of a post-processing chain are typically only used by a few passes.
+//! Instead of allocating one image per intermediate result in a long-lived arena, passes acquire
+//! them from a [TransientPool], specifying the [AliasScope] in which they use the image.
+//! An image of the pool is handed out again to another pass of the same frame if the scopes
+//! of the two passes do not overlap:
+//! ```ignore
+//! let mut pool = TransientPool::new(&persistent_arena);
+//! // each frame
+//! pool.reset();
+//! // blur pass: sortkeys 0x1000..0x1FFF
+//! let blur_h = pool.acquire(Format::R16G16B16A16_SFLOAT, (w, h), AliasScope { value: 0x1000, mask: 0xF000 });
+//! // tonemapping pass: sortkeys 0x2000..0x2FFF, may reuse the image of the blur pass
+//! let tonemapped = pool.acquire(Format::R16G16B16A16_SFLOAT, (w, h), AliasScope { value: 0x2000, mask: 0xF000 });
+//! ```
+//!
+//! Images are kept across frames. Images of a new format or size are allocated in the arena of
+//! the pool, and are only freed with the arena: when the size of the frame changes, the pool
+//! should be recreated in a new arena.
+use autograph_api::{
+    format::Format,
+    image::{Image2d, RenderTargetView, SamplerDescription, TextureSampler2dView},
+    AliasScope, Arena, Backend,
+};
+
+/// An image acquired from a [TransientPool].
+///
+/// The image must only be used by commands with sortkeys inside its scope.
+#[derive(Copy, Clone, Debug)]
+pub struct PooledImage<'a, B: Backend> {
+    image: Image2d<'a, B>,
+    scope: AliasScope,
+}
+
+impl<'a, B: Backend> PooledImage<'a, B> {
+    pub fn image(&self) -> Image2d<'a, B> {
+        self.image
+    }
+
+    /// Returns the scope in which the image can be used.
+    pub fn scope(&self) -> AliasScope {
+        self.scope
+    }
+
+    pub fn render_target_view(&self) -> RenderTargetView<'a, B> {
+        self.image.into()
+    }
+
+    pub fn sampled(&self, sampler: SamplerDescription) -> TextureSampler2dView<'a, B> {
+        self.image.sampled(sampler)
+    }
+}
+
+impl<'a, B: Backend> From<PooledImage<'a, B>> for Image2d<'a, B> {
+    fn from(pooled: PooledImage<'a, B>) -> Self {
+        pooled.image
+    }
+}
+
+struct PoolEntry<'a, B: Backend> {
+    format: Format,
+    size: (u32, u32),
+    image: Image2d<'a, B>,
+    /// Scopes in which the image has been handed out since the last reset.
+    uses: Vec<AliasScope>,
+}
+
+/// Hands out temporary images, recycling them between passes with non-overlapping scopes.
+pub struct TransientPool<'a, B: Backend> {
+    arena: &'a Arena<'a, B>,
+    entries: Vec<PoolEntry<'a, B>>,
+}
+
+impl<'a, B: Backend> TransientPool<'a, B> {
+    /// Creates an empty pool allocating its images in `arena`.
+    pub fn new(arena: &'a Arena<'a, B>) -> TransientPool<'a, B> {
+        TransientPool {
+            arena,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns an image with the specified format and size that is not used by other commands
+    /// inside `scope`.
+    ///
+    /// The image is either an image of the pool that was only handed out in scopes that don't
+    /// overlap with `scope` since the last [reset](TransientPool::reset), or a new image.
+    pub fn acquire(
+        &mut self,
+        format: Format,
+        size: (u32, u32),
+        scope: AliasScope,
+    ) -> PooledImage<'a, B> {
+        let entry = self.entries.iter().position(|e| {
+            e.format == format && e.size == size && e.uses.iter().all(|u| !u.overlaps(&scope))
+        });
+
+        let entry = match entry {
+            Some(index) => &mut self.entries[index],
+            None => {
+                // the pool handles the reuse of images itself: allocate without aliasing
+                let image = self.arena.image_2d(format, size.0, size.1).build();
+                self.entries.push(PoolEntry {
+                    format,
+                    size,
+                    image,
+                    uses: Vec::new(),
+                });
+                self.entries.last_mut().unwrap()
+            }
+        };
+
+        entry.uses.push(scope);
+        PooledImage {
+            image: entry.image,
+            scope,
+        }
+    }
+
+    /// Makes all the images of the pool available again. Call at the beginning of each frame.
+    pub fn reset(&mut self) {
+        for entry in self.entries.iter_mut() {
+            entry.uses.clear();
+        }
+    }
+
+    /// Returns the number of images allocated by the pool.
+    pub fn image_count(&self) -> usize {
+        self.entries.len()
+    }
+}
@@ -12,7 +12,7 @@ fn main() {
         (4, 6),
         Profile::Core,
         Fallbacks::All,
-        ["GL_ARB_sparse_texture"],
+        ["GL_ARB_sparse_texture", "GL_OVR_multiview"],
     )
     .write_bindings(StructGenerator, &mut file)
     .unwrap();
@@ -0,0 +1,94 @@
+//! Compares the streaming strategies of the upload buffers.
+//!
+//! Each frame uploads many small constant buffers and a few large vertex buffers in a frame
+//! arena, as a typical renderer would, and reads back a part of the last upload so that the GPU
+//! actually accesses the data.
+//!
+//! Run with `cargo run --release -p autograph-api-gl --example upload_benchmark [frames]`.
+use autograph_api::{command::CommandBuffer, Api};
+use autograph_api_gl::{
+    create_instance_and_window, InstanceConfig, OpenGlBackend, StreamingStrategy,
+};
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+/// Number of small uploads per frame.
+const SMALL_UPLOADS: usize = 2000;
+/// Size of the small uploads, in `f32`s (a 4x4 matrix).
+const SMALL_UPLOAD_LEN: usize = 16;
+/// Number of large uploads per frame.
+const LARGE_UPLOADS: usize = 8;
+/// Size of the large uploads, in `f32`s (256 KiB).
+const LARGE_UPLOAD_LEN: usize = 64 * 1024;
+
+fn run(strategy: StreamingStrategy, frames: usize) {
+    let cfg = InstanceConfig {
+        streaming_strategy: strategy,
+        ..InstanceConfig::default()
+    };
+    let events_loop = winit::EventsLoop::new();
+    let window_builder = winit::WindowBuilder::new()
+        .with_title("upload benchmark")
+        .with_dimensions((320, 240).into())
+        .with_visibility(false);
+    let (instance, _window) = create_instance_and_window(&cfg, &events_loop, window_builder);
+    let api: Api<OpenGlBackend> = Api::new(instance);
+
+    let small = [1.0f32; SMALL_UPLOAD_LEN];
+    let large = vec![1.0f32; LARGE_UPLOAD_LEN];
+    let mut readback = [0.0f32; 4];
+    let mut upload_time = Duration::from_secs(0);
+
+    let start = Instant::now();
+    for _ in 0..frames {
+        let arena = api.create_arena();
+        let mut cmdbuf: CommandBuffer<OpenGlBackend> = api.create_command_buffer();
+
+        let upload_start = Instant::now();
+        for _ in 0..LARGE_UPLOADS {
+            arena.upload_slice(&large[..]);
+        }
+        let mut last = None;
+        for _ in 0..SMALL_UPLOADS {
+            last = Some(arena.upload_slice(&small[..]));
+        }
+        upload_time += upload_start.elapsed();
+
+        cmdbuf.copy_buffer_to_host(0, last.unwrap(), arena.host_readback(&mut readback[..]));
+        api.submit_frame(vec![cmdbuf]);
+    }
+    let total = start.elapsed();
+
+    let ms = |d: Duration| d.as_secs() as f64 * 1000.0 + f64::from(d.subsec_micros()) / 1000.0;
+    let stats = api.instance().upload_stats();
+    println!("{:?}:", strategy);
+    println!(
+        "    {:.3} ms/frame ({:.3} ms/frame in uploads)",
+        ms(total) / frames as f64,
+        ms(upload_time) / frames as f64
+    );
+    println!(
+        "    high water mark {} KiB, {} overflow chunks, {} orphaned buffers",
+        stats.high_water_mark / 1024,
+        stats.overflow_chunks,
+        stats.orphaned_buffers
+    );
+    assert_eq!(readback, [1.0; 4]);
+}
+
+fn main() {
+    let frames = env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("invalid number of frames"))
+        .unwrap_or(500);
+    let bytes = (SMALL_UPLOADS * SMALL_UPLOAD_LEN + LARGE_UPLOADS * LARGE_UPLOAD_LEN) * 4;
+    println!("{} frames, {} KiB uploaded per frame", frames, bytes / 1024);
+    for &strategy in &[
+        StreamingStrategy::PersistentRing,
+        StreamingStrategy::Orphaning,
+    ] {
+        run(strategy, frames);
+    }
+}
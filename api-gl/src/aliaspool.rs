@@ -76,6 +76,14 @@ impl<D: Eq + Clone, K: slotmap::Key + Copy, T> AliasPool<D, K, T> {
         }*/
     }
 
+    /// Returns the objects of the pool, with their description and the scopes in which they
+    /// are in use.
+    pub fn iter(&self) -> impl Iterator<Item = (&D, &T, &[AliasScope])> {
+        self.entries
+            .iter()
+            .map(|(_, e)| (&e.description, &e.object, &e.live_scopes[..]))
+    }
+
     // TODO
     fn _evict<F: FnMut(T)>(&mut self, _until_frame: u64, _deleter: F) {
         /*self.store.retain(|k, e| {
@@ -2,47 +2,66 @@ use crate::{
     aliaspool::AliasPool,
     api as gl,
     api::{types::*, Gl},
-    buffer::{create_buffer, GlBuffer, MappedBuffer, RawBuffer, UploadBuffer},
-    command::{StateCache, SubmissionContext},
+    buffer::{
+        create_dynamic_buffer, create_immutable_buffer, GlBuffer, GlBufferView, GlHostReadback,
+        GlHostReference, RawBuffer, StreamingStrategy, UploadBuffer, UploadRing, UploadStats,
+    },
+    command::{StateCache, StateCacheCounters, SubmissionContext},
+    format::{query_format_properties, GlFormatInfo},
     framebuffer::GlFramebuffer,
-    image::{upload_image_region, GlImage, ImageAliasKey, ImageDescription, RawImage},
+    image::{
+        texture_level_size, upload_image_region, upload_image_region_with_pitch, GlImage,
+        ImageAliasKey, ImageDescription, RawImage, SharedImage,
+    },
+    sync::GpuSyncObject,
     pipeline::{
-        create_graphics_pipeline_internal, GlArgumentBlock, GlGraphicsPipeline, GlShaderModule,
-        GlSignature,
+        create_compute_pipeline_internal, create_graphics_pipeline_internal, GlArgumentBlock,
+        GlComputePipeline, GlGraphicsPipeline, GlShaderModule, GlSignature,
     },
+    query::GlQuery,
     sampler::SamplerCache,
-    swapchain::GlSwapchain,
-    sync::{GpuSyncObject, Timeline},
+    swapchain::{GlSwapchain, SwapchainTarget},
+    sync::Timeline,
+    tracking::{ArenaGeneration, ArenaTag},
+    window::create_shared_window,
     AliasInfo, ImplementationParameters,
 };
 use autograph_api::{
-    command::Command,
+    allocator::{AllocationKind, AllocationRequest},
+    clip::{ClipSpaceInfo, DepthRange, YDirection},
+    command::{ClearValue, Command, PresentEncoding},
     descriptor::Descriptor,
-    format::Format,
-    image::{DepthStencilView, Dimensions, ImageUsageFlags, MipmapsOption, RenderTargetView},
+    format::{ColorSpace, Format, FormatProperties},
+    image::{
+        DepthStencilView, Dimensions, ImageRegionUpdate, ImageUsageFlags, MipmapsOption,
+        RenderTargetView,
+    },
     pipeline::{
-        BareArgumentBlock, GraphicsPipelineCreateInfo, Scissor, ShaderStageFlags,
+        BareArgumentBlock, GraphicsPipelineCreateInfo, PipelineRef, Scissor, ShaderStageFlags,
         SignatureDescription, Viewport,
     },
+    report::{
+        FrameReport, LifetimeReport, PipelineCompileTime, PipelineKind, ResourceKind, SubmitStats,
+    },
     vertex::{IndexBufferView, VertexBufferView},
-    AliasScope, Backend, Instance,
+    traits::Swapchain,
+    AliasScope, ArenaAllocator, ArenaOptions, Backend, DeviceLimits, Instance,
 };
 use dropless_arena::DroplessArena;
 use glutin::{GlContext, GlWindow};
 use std::{
     cell::{Cell, RefCell},
-    collections::VecDeque,
     ffi::CStr,
     mem,
     os::raw::c_char,
     ptr, slice, str,
-    sync::Arc,
-    time::Duration,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
 };
 use typed_arena::Arena;
 use std::ffi::{CString, c_void};
 use winapi::um::libloaderapi::{GetModuleHandleA, LoadLibraryA};
-use winit::Window;
+use winit::{EventsLoop, Window, WindowBuilder};
 
 //--------------------------------------------------------------------------------------------------
 extern "system" fn debug_callback(
@@ -76,38 +95,62 @@ impl Backend for OpenGlBackend {
     type Swapchain = GlSwapchain;
     type Image = GlImage;
     type Buffer = GlBuffer;
+    type BufferView = GlBufferView;
     type ShaderModule = GlShaderModule;
     type GraphicsPipeline = GlGraphicsPipeline;
+    type ComputePipeline = GlComputePipeline;
     type Signature = GlSignature;
     type ArgumentBlock = GlArgumentBlock;
-    type HostReference = ();
+    type HostReference = GlHostReference;
+    type HostReadback = GlHostReadback;
+    type Query = GlQuery;
 }
 
 //--------------------------------------------------------------------------------------------------
 pub struct GlArena {
-    pub(crate) _swapchains: Arena<GlSwapchain>,
+    pub(crate) swapchains: Arena<GlSwapchain>,
     pub(crate) buffers: Arena<GlBuffer>,
+    pub(crate) buffer_views: Arena<GlBufferView>,
     pub(crate) images: Arena<GlImage>,
     pub(crate) shader_modules: Arena<GlShaderModule>,
     pub(crate) signatures: Arena<GlSignature>,
+    pub(crate) argument_blocks: Arena<GlArgumentBlock>,
     pub(crate) graphics_pipelines: Arena<GlGraphicsPipeline>,
+    pub(crate) compute_pipelines: Arena<GlComputePipeline>,
     pub(crate) framebuffers: Arena<GlFramebuffer>,
+    pub(crate) host_references: Arena<GlHostReference>,
+    pub(crate) host_readbacks: Arena<GlHostReadback>,
+    pub(crate) queries: Arena<GlQuery>,
     pub(crate) upload_buffer: UploadBuffer,
     pub(crate) other: DroplessArena,
+    /// Minimum size of the chunks allocated by the arena in the upload ring.
+    pub(crate) upload_chunk_size: usize,
+    pub(crate) allocator: Box<dyn ArenaAllocator>,
+    /// Generation checked when the objects of the arena are used (see `tracking`).
+    pub(crate) generation: ArenaGeneration,
 }
 
 impl GlArena {
-    pub(crate) fn new(upload_buffer: UploadBuffer) -> GlArena {
+    pub(crate) fn new(options: ArenaOptions) -> GlArena {
         GlArena {
-            _swapchains: Arena::new(),
+            swapchains: Arena::new(),
             buffers: Arena::new(),
+            buffer_views: Arena::new(),
             images: Arena::new(),
             shader_modules: Arena::new(),
             signatures: Arena::new(),
+            argument_blocks: Arena::new(),
             graphics_pipelines: Arena::new(),
+            compute_pipelines: Arena::new(),
             framebuffers: Arena::new(),
-            upload_buffer,
+            host_references: Arena::new(),
+            host_readbacks: Arena::new(),
+            queries: Arena::new(),
+            upload_buffer: UploadBuffer::new(),
             other: DroplessArena::new(),
+            upload_chunk_size: options.block_size as usize,
+            allocator: options.allocator,
+            generation: ArenaGeneration::new(),
         }
     }
 }
@@ -116,49 +159,67 @@ impl GlArena {
 pub(crate) type ImagePool = AliasPool<ImageDescription, ImageAliasKey, RawImage>;
 //pub(crate) type BufferPool = AliasPool<BufferDescription, BufferAliasKey, RawBuffer>;
 
+/// An image with swapchain-relative dimensions, resized on frame submission.
+struct RelativeImage {
+    arena: *const GlArena,
+    image: *const GlImage,
+    dimensions: Dimensions,
+    mipmaps: MipmapsOption,
+    desc: ImageDescription,
+}
+
+/// An image cleared at the start of its alias scope in each frame
+/// (see `Arena::create_image_cleared`).
+struct ClearedImage {
+    arena: *const GlArena,
+    image: *const GlImage,
+    scope: AliasScope,
+    value: ClearValue,
+}
+
 ///
 struct Resources {
     image_pool: ImagePool,
-    upload_buffer_size: usize,
-    upload_buffers: Vec<MappedBuffer>,
-    upload_buffers_in_use: VecDeque<GpuSyncObject<Vec<MappedBuffer>>>,
+    upload_ring: UploadRing,
+    relative_images: Vec<RelativeImage>,
+    cleared_images: Vec<ClearedImage>,
+    /// Host references created since the last frame submission, with their arena.
+    pending_host_references: Vec<(*const GlArena, *const GlHostReference)>,
 }
 
 impl Resources {
-    fn new(upload_buffer_size: usize) -> Resources {
+    fn new(upload_buffer_size: usize, streaming_strategy: StreamingStrategy) -> Resources {
         Resources {
             image_pool: ImagePool::new(),
             //buffer_pool: BufferPool::new(),
-            upload_buffer_size,
-            upload_buffers: Vec::new(),
-            upload_buffers_in_use: VecDeque::new(),
+            upload_ring: UploadRing::new(upload_buffer_size, streaming_strategy),
+            relative_images: Vec::new(),
+            cleared_images: Vec::new(),
+            pending_host_references: Vec::new(),
         }
     }
 
-    fn alloc_upload_buffer(&mut self, gl: &Gl) -> UploadBuffer {
-        self.reclaim_upload_buffers(gl);
-        if self.upload_buffers.is_empty() {
-            UploadBuffer::new(MappedBuffer::new(gl, self.upload_buffer_size))
+    /// Writes data into the upload chunks of the arena, allocating a new chunk if necessary.
+    fn upload(&mut self, gl: &Gl, arena: &GlArena, data: &[u8], align: usize) -> (GLuint, usize) {
+        if let Some(result) = arena.upload_buffer.write(gl, data, align) {
+            result
         } else {
-            UploadBuffer::new(self.upload_buffers.pop().unwrap())
+            let size = arena.upload_chunk_size.max(data.len() + align);
+            let chunk = self.upload_ring.alloc_chunk(gl, size);
+            arena.upload_buffer.write_in_new_chunk(gl, chunk, data, align)
         }
     }
 
-    fn reclaim_upload_buffers(&mut self, gl: &Gl) {
-        while !self.upload_buffers_in_use.is_empty() {
-            let ready = self.upload_buffers_in_use.front().unwrap().try_wait(gl);
-            if ready.is_ok() {
-                let buffers = self.upload_buffers_in_use.pop_front().unwrap();
-                let mut buffers = unsafe { buffers.into_inner_unsynchronized(gl) };
-                self.upload_buffers.append(&mut buffers);
-            } else {
-                break;
-            }
-        }
+    fn create_arena(&mut self, _gl: &Gl, options: ArenaOptions) -> Box<GlArena> {
+        Box::new(GlArena::new(options))
     }
 
-    fn create_arena(&mut self, gl: &Gl) -> Box<GlArena> {
-        Box::new(GlArena::new(self.alloc_upload_buffer(gl)))
+    /// Removes the references to the objects of an arena that is about to be dropped.
+    fn forget_arena(&mut self, arena: &GlArena) {
+        let arena_ptr = arena as *const GlArena;
+        self.relative_images.retain(|r| r.arena != arena_ptr);
+        self.cleared_images.retain(|c| c.arena != arena_ptr);
+        self.pending_host_references.retain(|&(a, _)| a != arena_ptr);
     }
 
     // arena can't drop before commands that refer to the objects inside are submitted
@@ -166,12 +227,14 @@ impl Resources {
     where
         Self: Sized,
     {
+        self.forget_arena(&arena);
+
         // recover resources
         arena.images.into_vec().into_iter().for_each(|image| {
-            if image.should_destroy {
-                image.raw.destroy(gl)
+            if image.should_destroy.get() {
+                image.raw().destroy(gl)
             } else {
-                if let Some(ref alias_info) = image.alias_info {
+                if let Some(alias_info) = image.alias_info.get() {
                     self.image_pool
                         .destroy(alias_info.key, alias_info.scope, |image| {
                             image.destroy(gl);
@@ -182,9 +245,19 @@ impl Resources {
             }
         });
 
+        // views first: they reference the buffers
+        arena.buffer_views.into_vec().into_iter().for_each(|view| {
+            view.destroy(gl);
+        });
+
+        let allocator = &arena.allocator;
         arena.buffers.into_vec().into_iter().for_each(|buf| {
             if buf.should_destroy {
-                buf.raw.destroy(gl)
+                buf.raw.destroy(gl);
+                allocator.free(&AllocationRequest {
+                    kind: AllocationKind::Buffer,
+                    size: buf.raw.size as u64,
+                });
             }
         });
 
@@ -192,10 +265,12 @@ impl Resources {
             fb.destroy(gl);
         });
 
-        self.upload_buffers_in_use.push_back(GpuSyncObject::new(
-            gl,
-            vec![arena.upload_buffer.into_inner()],
-        ));
+        arena.queries.into_vec().into_iter().for_each(|query| {
+            query.destroy(gl);
+        });
+
+        self.upload_ring
+            .retire(gl, arena.upload_buffer.into_chunks());
     }
 
     //----------------------------------------------------------------------------------------------
@@ -211,11 +286,81 @@ impl Resources {
             .alloc(scope, *desc, |d| RawImage::new(gl, d));
 
         arena.images.alloc(GlImage {
-            alias_info: AliasInfo { key, scope }.into(),
-            raw: raw.clone(),
-            should_destroy: false,
+            alias_info: Cell::new(Some(AliasInfo { key, scope })),
+            raw: RwLock::new(raw.clone()),
+            should_destroy: Cell::new(false),
+            tag: ArenaTag::new(&arena.generation),
         })
     }
+
+    /// Adds the images of the alias pool to the lifetime report, with their uses in the frame.
+    fn report_image_lifetimes(&self, report: &mut LifetimeReport, uses: &[(GLuint, u64)]) {
+        for (desc, image, scopes) in self.image_pool.iter() {
+            for &scope in scopes {
+                report.add_resource(
+                    ResourceKind::Image,
+                    u64::from(image.obj),
+                    scope,
+                    format!("{:?} {:?}", desc.format, desc.dimensions),
+                );
+            }
+        }
+        for &(obj, sortkey) in uses {
+            report.record_use(u64::from(obj), sortkey);
+        }
+    }
+
+    /// Uploads the data of the host references created since the last frame submission.
+    /// Returns the number of bytes uploaded.
+    fn upload_host_references(&mut self, gl: &Gl, align: usize) -> usize {
+        let pending = mem::replace(&mut self.pending_host_references, Vec::new());
+        let mut bytes = 0;
+        for (arena, host_ref) in pending {
+            // the arena is still alive, otherwise the entry would have been removed in `drop_arena`
+            let (arena, host_ref) = unsafe { (&*arena, &*host_ref) };
+            let data = unsafe { slice::from_raw_parts(host_ref.data, host_ref.size) };
+            let location = self.upload(gl, arena, data, align);
+            host_ref.location.set(location);
+            bytes += data.len();
+        }
+        bytes
+    }
+
+    /// Recreates the swapchain-relative images whose size changed.
+    fn resize_relative_images(&mut self, gl: &Gl, swapchain_size: (u32, u32)) {
+        for r in self.relative_images.iter_mut() {
+            let desc = ImageDescription::new(
+                r.desc.format,
+                r.dimensions.resolve(swapchain_size),
+                r.mipmaps,
+                r.desc.samples,
+                r.desc.usage,
+            );
+            if desc != r.desc {
+                // the arena holding the image is still alive, otherwise it would have been
+                // removed from the list in `drop_arena`
+                let image = unsafe { &*r.image };
+                if let Some(alias_info) = image.alias_info.get() {
+                    // release the entry of the old size, and alias an entry of the new size
+                    // in the same scope
+                    self.image_pool
+                        .destroy(alias_info.key, alias_info.scope, |_| {});
+                    let (key, raw) = self
+                        .image_pool
+                        .alloc(alias_info.scope, desc, |d| RawImage::new(gl, d));
+                    image.set_raw(raw.clone());
+                    image.alias_info.set(Some(AliasInfo {
+                        key,
+                        scope: alias_info.scope,
+                    }));
+                } else {
+                    image.raw().destroy(gl);
+                    image.set_raw(RawImage::new(gl, &desc));
+                }
+                r.desc = desc;
+            }
+        }
+    }
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -223,28 +368,69 @@ pub struct OpenGlInstance {
     rsrc: RefCell<Resources>,
     timeline: RefCell<Timeline>,
     frame_num: Cell<u64>, // replace with AtomicU64 once stabilized
+    /// End of the last call to `submit_frame`, for the estimation of the idle time of the GPU.
+    last_submit_end: Cell<Option<Instant>>,
+    /// Dropped arenas, with the frame of their drop, kept alive until their objects can't be
+    /// referenced by the commands of a frame anymore (see `tracking`).
+    #[cfg(feature = "arena-tracking")]
+    retired_arenas: RefCell<Vec<(u64, Box<GlArena>)>>,
     state_cache: RefCell<StateCache>,
+    state_counters: Cell<Option<StateCacheCounters>>,
     sampler_cache: RefCell<SamplerCache>,
     limits: ImplementationParameters,
     window: Option<Arc<GlWindow>>,
     def_swapchain: Option<GlSwapchain>,
+    /// Swapchains of the windows created with `create_window_swapchain`.
+    window_swapchains: Arena<GlSwapchain>,
     cfg: InstanceConfig,
+    /// Depth range of normalized device coordinates, set with `glClipControl`.
+    depth_range: DepthRange,
     gl: gl::Gl,
 }
 
 #[derive(Copy, Clone, Debug)]
 pub struct InstanceConfig {
+    /// Size of the upload ring, used to upload host data referenced by GPU commands.
     pub upload_buffer_size: usize,
+    /// How uploaded host data is streamed to the GPU. `upload_buffer_size` is ignored with
+    /// `StreamingStrategy::Orphaning`.
+    pub streaming_strategy: StreamingStrategy,
     pub max_frames_in_flight: u32,
     pub vsync: bool,
+    /// Collect statistics about the binding operations issued and skipped during each frame.
+    /// See `OpenGlInstance::state_counters`.
+    pub debug_counters: bool,
+    /// Merge consecutive draws that share the same pipeline and argument block into
+    /// multi-draw indirect calls.
+    pub batch_draws: bool,
+    /// Color space of the default framebuffer of the window. With `ColorSpace::Srgb`,
+    /// linear images are encoded to sRGB when presented.
+    pub swapchain_color_space: ColorSpace,
+    /// Conversion of the presented images, unless overridden in the present command.
+    /// With `PresentEncoding::Default`, `GL_FRAMEBUFFER_SRGB` is enabled during the copy to the
+    /// default framebuffer only if `swapchain_color_space` is `ColorSpace::Srgb`.
+    /// Some drivers create sRGB default framebuffers even when not requested: use
+    /// `PresentEncoding::Srgb` or `PresentEncoding::Raw` to force the conversion.
+    pub present_encoding: PresentEncoding,
+    /// Use `glClipControl` to set the depth range of normalized device coordinates to 0..1
+    /// (as in Vulkan and D3D) instead of -1..1. This is required to benefit from the precision
+    /// of reverse-Z depth buffers (see `pipeline::DepthRange::OneToZero`).
+    /// Ignored if `glClipControl` is not available.
+    pub zero_to_one_depth: bool,
 }
 
 impl Default for InstanceConfig {
     fn default() -> Self {
         InstanceConfig {
             upload_buffer_size: 4 * 1024 * 1024,
+            streaming_strategy: StreamingStrategy::PersistentRing,
             max_frames_in_flight: 1,
             vsync: false,
+            debug_counters: false,
+            batch_draws: true,
+            swapchain_color_space: ColorSpace::Linear,
+            present_encoding: PresentEncoding::Default,
+            zero_to_one_depth: true,
         }
     }
 }
@@ -304,14 +490,15 @@ fn load_gl_pointers_from_window(window: &Arc<GlWindow>) -> Result<Gl, InstanceEr
     Ok(gl)
 }
 
+#[cfg(windows)]
 fn load_gl_pointers_from_current_context() -> Result<Gl, InstanceError> {
-    // load API
-    let gl = crate::api::Gl::load_with(|symbol| {
-        let ptr = gl_get_proc_address(symbol).unwrap_or(ptr::null()) as *const _;
-        ptr
-    });
+    Ok(load_gl_pointers_with(|symbol| {
+        gl_get_proc_address(symbol).unwrap_or(ptr::null())
+    }))
+}
 
-    Ok(gl)
+fn load_gl_pointers_with(mut get_proc_address: impl FnMut(&str) -> *const c_void) -> Gl {
+    crate::api::Gl::load_with(|symbol| get_proc_address(symbol) as *const _)
 }
 
 #[derive(Copy,Clone,Debug,Eq,PartialEq)]
@@ -319,6 +506,8 @@ pub enum InstanceError
 {
     ContextNotInitialized,
     UnsupportedVersion,
+    /// The context is an OpenGL ES context.
+    UnsupportedProfile,
     ExtensionUnavailable
 }
 
@@ -327,6 +516,7 @@ impl std::fmt::Display for InstanceError {
         match self {
             InstanceError::ContextNotInitialized => write!(formatter, "context not initialized"),
             InstanceError::UnsupportedVersion => write!(formatter, "unsupported OpenGL version"),
+            InstanceError::UnsupportedProfile => write!(formatter, "OpenGL ES contexts are not supported"),
             InstanceError::ExtensionUnavailable => write!(formatter, "extension unavailable")
         }
     }
@@ -334,26 +524,332 @@ impl std::fmt::Display for InstanceError {
 
 impl ::std::error::Error for InstanceError {}
 
+impl Drop for OpenGlInstance {
+    fn drop(&mut self) {
+        self.state_cache.get_mut().delete_objects(&self.gl);
+    }
+}
+
 impl OpenGlInstance {
     /// Returns the associated [glutin::GlWindow] if there is one.
     pub fn window(&self) -> Option<&Arc<GlWindow>> {
         self.window.as_ref()
     }
 
+    /// Returns statistics about the usage of the upload ring.
+    pub fn upload_stats(&self) -> UploadStats {
+        self.rsrc.borrow().upload_ring.stats()
+    }
+
+    /// Returns the binding statistics of the last submitted frame.
+    ///
+    /// Returns `None` if `InstanceConfig::debug_counters` was not set.
+    pub fn state_counters(&self) -> Option<StateCacheCounters> {
+        self.state_counters.get()
+    }
+
+    /// Submits a frame, optionally collecting statistics in `report`.
+    unsafe fn submit_frame_inner<'a>(
+        &self,
+        frame: &[Command<'a, OpenGlBackend>],
+        mut report: Option<&mut FrameReport>,
+    ) -> SubmitStats {
+        let mut scache = self.state_cache.borrow_mut();
+
+        // invalidate the cache, because deletion of objects in arenas between two calls
+        // to `submit_frame` may have automatically 'unbound' objects from the pipeline.
+        scache.invalidate();
+
+        #[cfg(feature = "arena-tracking")]
+        crate::tracking::check_commands(frame);
+
+        let start = Instant::now();
+
+        // if the GPU has already executed all previous frames, it may have been idle since the
+        // end of the last submission
+        let gpu_idle_estimate = match self.last_submit_end.get() {
+            Some(last_end) if self.timeline.borrow_mut().is_idle(&self.gl) => start - last_end,
+            _ => Duration::from_secs(0),
+        };
+
+        // upload host data referenced by the commands
+        let upload_bytes = self
+            .rsrc
+            .borrow_mut()
+            .upload_host_references(&self.gl, self.limits.uniform_buffer_alignment);
+
+        // execute commands
+        if let Some(ref mut report) = report {
+            report.upload_bytes = upload_bytes as u64;
+        }
+        let image_uses;
+        let present_time;
+        {
+            let mut subctxt = SubmissionContext::new(
+                &self.gl,
+                &mut scache,
+                &self.limits,
+                self.cfg.batch_draws,
+                report.as_mut().map(|r| &mut **r),
+            );
+            subctxt.set_initial_clears(
+                self.rsrc
+                    .borrow()
+                    .cleared_images
+                    .iter()
+                    .map(|c| (c.image, c.scope, c.value))
+                    .collect(),
+            );
+            subctxt.submit_commands(frame);
+            image_uses = subctxt.take_image_uses();
+            present_time = subctxt.present_time();
+        }
+        self.state_counters.set(scache.take_counters());
+        if let Some(report) = report {
+            report.submit_time = start.elapsed();
+            if let Some(ref mut lifetimes) = report.lifetimes {
+                self.rsrc
+                    .borrow()
+                    .report_image_lifetimes(lifetimes, &image_uses);
+            }
+        }
+
+        let fnum = self.frame_num.get();
+        let mut timeline = self.timeline.borrow_mut();
+        timeline.signal(&self.gl, fnum);
+
+        // wait for previous frames before starting a new one
+        // if max_frames_in_flight is zero, then will wait on the previously signalled point.
+        let wait_start = Instant::now();
+        if fnum > u64::from(self.cfg.max_frames_in_flight) {
+            let timeout = !timeline.client_sync(
+                &self.gl,
+                fnum - u64::from(self.cfg.max_frames_in_flight),
+                FRAME_WAIT_TIMEOUT,
+            );
+            if timeout {
+                panic!(
+                    "timeout ({:?}) waiting for frame to finish",
+                    FRAME_WAIT_TIMEOUT
+                )
+            }
+        }
+        let frame_wait_time = wait_start.elapsed();
+
+        self.frame_num.set(fnum + 1);
+
+        #[cfg(feature = "arena-tracking")]
+        {
+            let mut retired = self.retired_arenas.borrow_mut();
+            let mut rsrc = self.rsrc.borrow_mut();
+            let (expired, kept) = retired.drain(..).partition::<Vec<_>, _>(|&(frame, _)| {
+                frame + crate::tracking::RETIRED_ARENA_FRAMES <= fnum
+            });
+            *retired = kept;
+            for (_, arena) in expired {
+                rsrc.drop_arena(&self.gl, arena);
+            }
+        }
+
+        // follow the size of the window
+        if let Some(ref swapchain) = self.def_swapchain {
+            self.rsrc
+                .borrow_mut()
+                .resize_relative_images(&self.gl, swapchain.size());
+        }
+
+        self.last_submit_end.set(Some(Instant::now()));
+        SubmitStats::from_durations(frame_wait_time + present_time, gpu_idle_estimate)
+    }
+
     /// Creates a new OpenGlInstance associated to the given window.
     ///
     /// This also creates a _default swapchain_ that you can use to draw to the given window.
     pub fn from_gl_window(cfg: &InstanceConfig, window: Arc<GlWindow>) -> Result<OpenGlInstance, InstanceError> {
-        Self::from(cfg, Some(window))
+        let gl = load_gl_pointers_from_window(&window)?;
+        Self::from(cfg, gl, Some(window))
     }
 
     /// Creates a new OpenGlInstance from the current OpenGL context.
     ///
-    /// Panics if no context is currently bound, or if the current context does not
-    /// satisfy the minimum requirements of the backend implementation.
+    /// See `from_current_context_with_loader` for the requirements on the context.
+    ///
+    /// Returns an error if no context is currently bound, or if the current context does not
+    /// satisfy the minimum requirements of the backend implementation (OpenGL 4.5).
     #[cfg(windows)]
-    pub fn from_current_context(cfg: &InstanceConfig) -> Result<OpenGlInstance, InstanceError> {
-        Self::from(cfg, None)
+    pub unsafe fn from_current_context(cfg: &InstanceConfig) -> Result<OpenGlInstance, InstanceError> {
+        let gl = load_gl_pointers_from_current_context()?;
+        Self::from(cfg, gl, None)
+    }
+
+    /// Creates a new OpenGlInstance from the OpenGL context that is current on the calling thread,
+    /// loading OpenGL functions with `get_proc_address`.
+    ///
+    /// This is intended for embedding autograph in a host application that owns the context
+    /// (Qt, Blender, Maya...). The instance has no default swapchain: render into textures
+    /// imported with `import_texture`, or present to a framebuffer imported with
+    /// `import_framebuffer`.
+    ///
+    /// Returns an error if no context is currently bound, or if the current context does not
+    /// satisfy the minimum requirements of the backend implementation (OpenGL 4.5).
+    ///
+    /// # Safety
+    ///
+    /// * the context must be current on the calling thread whenever the instance is used
+    /// (including when it is dropped).
+    /// * the host application may change the OpenGL state between two calls to `submit_frame`
+    /// (the backend does not assume anything about the state at the beginning of a frame), but must
+    /// restore the framebuffer, program and vertex array bindings it relies on after
+    /// `submit_frame` returns.
+    pub unsafe fn from_current_context_with_loader(
+        cfg: &InstanceConfig,
+        get_proc_address: impl FnMut(&str) -> *const c_void,
+    ) -> Result<OpenGlInstance, InstanceError> {
+        let gl = load_gl_pointers_with(get_proc_address);
+        Self::from(cfg, gl, None)
+    }
+
+    /// Wraps an OpenGL texture owned by another application so that it can be used as an image.
+    ///
+    /// The returned image is allocated in `arena` and can be converted into an image type of the
+    /// API with `from_raw` (e.g. `Image2d::from_raw`). The dimensions of the texture must match
+    /// `desc.dimensions` (they determine the texture target: 1D, 2D or 3D).
+    /// The texture is not deleted when the arena is dropped.
+    ///
+    /// # Safety
+    ///
+    /// * `obj` must be the name of a texture object of the current context (or of a context
+    /// sharing objects with it), with storage allocated of the format, dimensions, number of mip
+    /// levels and samples specified in `desc`.
+    /// * the texture must not be deleted or re-allocated until the arena is dropped.
+    /// * the backend does not synchronize with the host application: accesses to the texture by the
+    /// host must happen between calls to `submit_frame`.
+    pub unsafe fn import_texture<'a>(
+        &self,
+        arena: &'a GlArena,
+        obj: GLuint,
+        desc: &ImageDescription,
+    ) -> &'a GlImage {
+        arena.images.alloc(GlImage {
+            raw: RwLock::new(RawImage::from_raw_texture(obj, desc)),
+            should_destroy: Cell::new(false),
+            alias_info: Cell::new(None),
+            tag: ArenaTag::new(&arena.generation),
+        })
+    }
+
+    /// Exports an image so that it can be used by another instance whose context shares objects
+    /// with the context of this instance, without a copy.
+    ///
+    /// This is typically used to load and upload textures on a background thread, with its own
+    /// context and instance, and then hand them over to the main instance (see `import_image`).
+    /// The handle becomes usable by the importing instance once all commands using the image
+    /// submitted so far (including the upload of its initial data) have completed: the importing
+    /// instance waits for them on the GPU.
+    ///
+    /// The ownership of the texture is transferred to the handle: it is not deleted anymore when
+    /// the arena of `image` is dropped.
+    ///
+    /// Panics if the image is aliasable, has swapchain-relative dimensions,
+    /// or is not owned by the backend.
+    ///
+    /// # Safety
+    ///
+    /// `image` must not be used anymore by this instance after this call.
+    pub unsafe fn export_image(&self, image: &GlImage) -> SharedImage {
+        assert!(
+            image.should_destroy.get() && image.alias_info.get().is_none(),
+            "only images with a dedicated allocation can be exported"
+        );
+        assert!(
+            !self
+                .rsrc
+                .borrow()
+                .relative_images
+                .iter()
+                .any(|r| r.image == image as *const GlImage),
+            "swapchain-relative images cannot be exported"
+        );
+        image.should_destroy.set(false);
+        let shared = SharedImage {
+            inner: GpuSyncObject::new(&self.gl, image.raw()),
+        };
+        // make sure that the fence reaches the GPU, so that other contexts can wait on it
+        self.gl.Flush();
+        shared
+    }
+
+    /// Imports an image exported by another instance with `export_image`.
+    ///
+    /// The image is allocated in `arena`, and its texture is deleted when the arena is dropped.
+    /// It can be converted into an image type of the API with `from_raw` (e.g. `Image2d::from_raw`).
+    ///
+    /// # Safety
+    ///
+    /// The context of this instance must share objects with the context of the exporting instance.
+    pub unsafe fn import_image<'a>(&self, arena: &'a GlArena, image: SharedImage) -> &'a GlImage {
+        let raw = image.inner.into_inner_server_wait(&self.gl);
+        arena.images.alloc(GlImage {
+            raw: RwLock::new(raw),
+            should_destroy: Cell::new(true),
+            alias_info: Cell::new(None),
+            tag: ArenaTag::new(&arena.generation),
+        })
+    }
+
+    /// Wraps a framebuffer object owned by another application into a swapchain.
+    ///
+    /// Presenting an image to this swapchain copies the image into the color attachment 0 of the
+    /// framebuffer. Buffers are not swapped: this is left to the host application.
+    /// The framebuffer is not deleted when the arena is dropped.
+    ///
+    /// With [ColorSpace::Srgb], the color attachment must have a sRGB format: linear images are
+    /// encoded when presented.
+    ///
+    /// # Safety
+    ///
+    /// `obj` must be the name of a complete framebuffer object of the current context,
+    /// of size `size`, and must not be deleted until the arena is dropped.
+    pub unsafe fn import_framebuffer<'a>(
+        &self,
+        arena: &'a GlArena,
+        obj: GLuint,
+        size: (u32, u32),
+        color_space: ColorSpace,
+    ) -> &'a GlSwapchain {
+        arena.swapchains.alloc(GlSwapchain {
+            target: SwapchainTarget::Framebuffer { obj, size },
+            color_space,
+            present_encoding: self.cfg.present_encoding,
+        })
+    }
+
+    /// Creates a new window and its swapchain.
+    ///
+    /// The context of the window shares objects with the context of the main window of this
+    /// instance, which stays current: images can be presented to the new swapchain like to
+    /// the default one. The swapchain lives as long as the instance.
+    ///
+    /// Panics if the instance was not created with a window.
+    pub fn create_window_swapchain(
+        &self,
+        events_loop: &EventsLoop,
+        window_builder: WindowBuilder,
+    ) -> (&GlSwapchain, Arc<GlWindow>) {
+        let main = self
+            .window
+            .as_ref()
+            .expect("the instance has no main window");
+        let window = create_shared_window(&self.cfg, events_loop, window_builder, main);
+        let swapchain = self.window_swapchains.alloc(GlSwapchain {
+            target: SwapchainTarget::SharedWindow {
+                window: window.clone(),
+                main: main.clone(),
+            },
+            color_space: self.cfg.swapchain_color_space,
+            present_encoding: self.cfg.present_encoding,
+        });
+        (swapchain, window)
     }
 
     /// Checks if the specified extension is supported.
@@ -393,30 +889,73 @@ impl OpenGlInstance {
         }
 
         self.try_enable_debug_output();
+
+        // sRGB encoding is only enabled while presenting: don't rely on the default of the driver
+        // (OpenGL ES always encodes when writing to sRGB framebuffers)
+        if !self.limits.es {
+            unsafe {
+                self.gl.Disable(gl::FRAMEBUFFER_SRGB);
+            }
+        }
+
+        if cfg.zero_to_one_depth && self.gl.ClipControl.is_loaded() {
+            // keep the default origin: images are stored upside-down anyway
+            unsafe {
+                self.gl.ClipControl(gl::LOWER_LEFT, gl::ZERO_TO_ONE);
+            }
+            self.depth_range = DepthRange::ZeroToOne;
+        }
     }
 
-    fn from(cfg: &InstanceConfig, window: Option<Arc<GlWindow>>) -> Result<OpenGlInstance, InstanceError> {
-        let gl = match window {
-            Some(ref window) => load_gl_pointers_from_window(window)?,
-            None => load_gl_pointers_from_current_context()?
-        };
+    fn from(cfg: &InstanceConfig, gl: Gl, window: Option<Arc<GlWindow>>) -> Result<OpenGlInstance, InstanceError> {
+        // a context must be current, with direct state access (GL 4.5)
+        if !gl.GetString.is_loaded() || unsafe { gl.GetString(gl::VERSION) }.is_null() {
+            return Err(InstanceError::ContextNotInitialized);
+        }
+        let limits = ImplementationParameters::populate(&gl);
+        // OpenGL ES contexts are detected, but not supported yet: objects are created and edited
+        // with direct state access, and pipelines can only be created from SPIR-V
+        if limits.es {
+            return Err(InstanceError::UnsupportedProfile);
+        }
+        if limits.version < (4, 5) {
+            return Err(InstanceError::UnsupportedVersion);
+        }
+
+        let mut cfg = *cfg;
+        if cfg.streaming_strategy == StreamingStrategy::PersistentRing && !limits.buffer_storage {
+            warn!("persistent mapping is not supported: using StreamingStrategy::Orphaning");
+            cfg.streaming_strategy = StreamingStrategy::Orphaning;
+        }
+        if cfg.batch_draws && !limits.multi_draw_indirect {
+            warn!("multi-draw indirect is not supported: draws are not batched");
+            cfg.batch_draws = false;
+        }
+        let cfg = &cfg;
 
         let timeline = Timeline::new(0);
-        let limits = ImplementationParameters::populate(&gl);
-        let state_cache = StateCache::new(&limits);
+        let state_cache = StateCache::new(&limits, cfg.debug_counters);
 
         let mut instance = OpenGlInstance {
-            rsrc: RefCell::new(Resources::new(cfg.upload_buffer_size)),
+            rsrc: RefCell::new(Resources::new(cfg.upload_buffer_size, cfg.streaming_strategy)),
             timeline: RefCell::new(timeline),
             frame_num: Cell::new(1),
+            last_submit_end: Cell::new(None),
+            #[cfg(feature = "arena-tracking")]
+            retired_arenas: RefCell::new(Vec::new()),
             window: window.clone(),
             def_swapchain: window.clone().map(|window| GlSwapchain {
-                window
+                target: SwapchainTarget::Window(window),
+                color_space: cfg.swapchain_color_space,
+                present_encoding: cfg.present_encoding,
             }),
+            window_swapchains: Arena::new(),
             gl,
             cfg: *cfg,
+            depth_range: DepthRange::NegativeOneToOne,
             limits,
             state_cache: RefCell::new(state_cache),
+            state_counters: Cell::new(None),
             sampler_cache: RefCell::new(SamplerCache::new()),
         };
         instance.init(cfg);
@@ -428,14 +967,29 @@ impl OpenGlInstance {
 const SPIRV_MAGIC: u32 = 0x0723_0203;
 const UPLOAD_DEDICATED_THRESHOLD: usize = 65536;
 const FRAME_WAIT_TIMEOUT: Duration = Duration::from_millis(500);
+/// Size of the buffer read by the vertex attributes during pipeline warm-up:
+/// larger than any vertex.
+const WARM_UP_SCRATCH_BUFFER_SIZE: usize = 4096;
 
 impl Instance<OpenGlBackend> for OpenGlInstance {
-    unsafe fn create_arena(&self) -> Box<GlArena> {
-        self.rsrc.borrow_mut().create_arena(&self.gl)
+    unsafe fn create_arena(&self, options: ArenaOptions) -> Box<GlArena> {
+        self.rsrc.borrow_mut().create_arena(&self.gl, options)
     }
 
     unsafe fn drop_arena(&self, arena: Box<GlArena>) {
-        self.rsrc.borrow_mut().drop_arena(&self.gl, arena)
+        #[cfg(feature = "arena-tracking")]
+        {
+            // keep the objects readable so that the uses of dangling references can be reported
+            arena.generation.retire();
+            self.rsrc.borrow_mut().forget_arena(&arena);
+            self.retired_arenas
+                .borrow_mut()
+                .push((self.frame_num.get(), arena));
+        }
+        #[cfg(not(feature = "arena-tracking"))]
+        {
+            self.rsrc.borrow_mut().drop_arena(&self.gl, arena)
+        }
     }
 
     //----------------------------------------------------------------------------------------------
@@ -447,6 +1001,48 @@ impl Instance<OpenGlBackend> for OpenGlInstance {
         self.def_swapchain.as_ref()
     }
 
+    unsafe fn acquire_swapchain_image<'a>(
+        &self,
+        arena: &'a GlArena,
+        swapchain: &'a GlSwapchain,
+    ) -> &'a GlImage {
+        // the images of the default framebuffer are not accessible: render into an intermediate
+        // image, blitted to the framebuffer of the swapchain in `cmd_present`
+        let (width, height) = swapchain.size();
+        let desc = ImageDescription::new(
+            Format::R16G16B16A16_SFLOAT,
+            Dimensions::Dim2d {
+                width,
+                height,
+                array_layers: 1,
+            },
+            MipmapsOption::NoMipmap,
+            1,
+            ImageUsageFlags::COLOR_ATTACHMENT,
+        );
+        // returned to the pool when the arena is dropped, and reused on the next acquisition
+        self.rsrc
+            .borrow_mut()
+            .alloc_aliased_image(&self.gl, arena, AliasScope::no_alias(), &desc)
+    }
+
+    fn limits(&self) -> DeviceLimits {
+        self.limits.device_limits()
+    }
+
+    fn format_properties(&self, format: Format) -> FormatProperties {
+        unsafe { query_format_properties(&self.gl, format) }
+    }
+
+    fn clip_space_info(&self) -> ClipSpaceInfo {
+        // image data is stored upside-down (see the crate documentation), so the first scanline
+        // is at Y = -1 as in Vulkan. The depth range depends on `InstanceConfig::zero_to_one_depth`.
+        ClipSpaceInfo {
+            depth_range: self.depth_range,
+            y_direction: YDirection::Down,
+        }
+    }
+
     //----------------------------------------------------------------------------------------------
     unsafe fn create_image<'a>(
         &self,
@@ -459,9 +1055,20 @@ impl Instance<OpenGlBackend> for OpenGlInstance {
         usage: ImageUsageFlags,
         initial_data: Option<&[u8]>,
     ) -> &'a GlImage {
-        let d = ImageDescription::new(format, dimensions, mipmaps, samples, usage);
+        let resolved_dimensions = if dimensions.is_swapchain_relative() {
+            // contents are lost on resize
+            assert!(initial_data.is_none());
+            let swapchain = self
+                .def_swapchain
+                .as_ref()
+                .expect("swapchain-relative image requires a default swapchain");
+            dimensions.resolve(swapchain.size())
+        } else {
+            dimensions
+        };
+        let d = ImageDescription::new(format, resolved_dimensions, mipmaps, samples, usage);
 
-        if scope != AliasScope::no_alias() {
+        let image = if scope != AliasScope::no_alias() {
             // cannot specify initial data for aliasable image
             assert!(initial_data.is_none());
             self.rsrc
@@ -479,17 +1086,54 @@ impl Instance<OpenGlBackend> for OpenGlInstance {
                     format,
                     0,
                     (0, 0, 0),
-                    dimensions.width_height_depth(),
+                    resolved_dimensions.width_height_depth(),
                     data,
                 );
             }
 
             arena.images.alloc(GlImage {
-                should_destroy: true,
-                raw,
-                alias_info: None,
+                should_destroy: Cell::new(true),
+                raw: RwLock::new(raw),
+                alias_info: Cell::new(None),
+                tag: ArenaTag::new(&arena.generation),
             })
+        };
+
+        if dimensions.is_swapchain_relative() {
+            self.rsrc.borrow_mut().relative_images.push(RelativeImage {
+                arena,
+                image,
+                dimensions,
+                mipmaps,
+                desc: d,
+            });
         }
+
+        image
+    }
+
+    unsafe fn create_image_cleared<'a>(
+        &self,
+        arena: &'a GlArena,
+        scope: AliasScope,
+        format: Format,
+        dimensions: Dimensions,
+        mipmaps: MipmapsOption,
+        samples: u32,
+        usage: ImageUsageFlags,
+        clear_value: ClearValue,
+    ) -> &'a GlImage {
+        let image = self.create_image(
+            arena, scope, format, dimensions, mipmaps, samples, usage, None,
+        );
+        // cleared on submission, before the first command inside the scope
+        self.rsrc.borrow_mut().cleared_images.push(ClearedImage {
+            arena,
+            image,
+            scope,
+            value: clear_value,
+        });
+        image
     }
 
     //----------------------------------------------------------------------------------------------
@@ -501,10 +1145,12 @@ impl Instance<OpenGlBackend> for OpenGlInstance {
     ) -> &'a GlBuffer {
         if size < UPLOAD_DEDICATED_THRESHOLD as u64 {
             // if the buffer is small enough, allocate through the upload buffer
-            let (obj, offset) = arena
-                .upload_buffer
-                .write(data, self.limits.uniform_buffer_alignment)
-                .unwrap();
+            let (obj, offset) = self.rsrc.borrow_mut().upload(
+                &self.gl,
+                arena,
+                data,
+                self.limits.uniform_buffer_alignment,
+            );
             arena.buffers.alloc(GlBuffer {
                 raw: RawBuffer {
                     obj,
@@ -513,24 +1159,114 @@ impl Instance<OpenGlBackend> for OpenGlInstance {
                 offset,
                 alias_info: None,
                 should_destroy: false,
+                tag: ArenaTag::new(&arena.generation),
             })
         } else {
             // otherwise, allocate a dedicated buffer
+            let request = AllocationRequest {
+                kind: AllocationKind::Buffer,
+                size,
+            };
+            if let Err(e) = arena.allocator.allocate(&request) {
+                panic!(
+                    "allocation of a buffer of {} bytes refused by the arena allocator: {:?}",
+                    size, e
+                );
+            }
             arena.buffers.alloc(GlBuffer {
                 raw: RawBuffer {
-                    obj: create_buffer(&self.gl, size as usize, 0, Some(data)),
+                    obj: create_immutable_buffer(&self.gl, &self.limits, data),
                     size: size as usize,
                 },
                 offset: 0,
                 should_destroy: true,
                 alias_info: None,
+                tag: ArenaTag::new(&arena.generation),
             })
         }
     }
 
     //----------------------------------------------------------------------------------------------
-    unsafe fn create_buffer<'a>(&self, _arena: &'a GlArena, _size: u64) -> &'a GlBuffer {
-        unimplemented!()
+    unsafe fn create_buffer<'a>(&self, arena: &'a GlArena, size: u64) -> &'a GlBuffer {
+        // always a dedicated buffer: the upload buffer cannot be updated
+        let request = AllocationRequest {
+            kind: AllocationKind::Buffer,
+            size,
+        };
+        if let Err(e) = arena.allocator.allocate(&request) {
+            panic!(
+                "allocation of a buffer of {} bytes refused by the arena allocator: {:?}",
+                size, e
+            );
+        }
+        arena.buffers.alloc(GlBuffer {
+            raw: RawBuffer {
+                obj: create_dynamic_buffer(&self.gl, &self.limits, size as usize),
+                size: size as usize,
+            },
+            offset: 0,
+            should_destroy: true,
+            alias_info: None,
+            tag: ArenaTag::new(&arena.generation),
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    unsafe fn update_buffer(&self, buffer: &GlBuffer, offset: usize, data: &[u8]) {
+        assert!(
+            offset + data.len() <= buffer.raw.size,
+            "update of {} bytes at offset {} outside of a buffer of {} bytes",
+            data.len(),
+            offset,
+            buffer.raw.size
+        );
+        // the driver orders the write after the commands already submitted that use the buffer
+        self.gl.NamedBufferSubData(
+            buffer.raw.obj,
+            (buffer.offset + offset) as isize,
+            data.len() as isize,
+            data.as_ptr() as *const GLvoid,
+        );
+    }
+
+    //----------------------------------------------------------------------------------------------
+    unsafe fn create_buffer_view<'a>(
+        &self,
+        arena: &'a GlArena,
+        buffer: &'a GlBuffer,
+        format: Format,
+        offset: usize,
+        size: Option<usize>,
+    ) -> &'a GlBufferView {
+        let size = size.unwrap_or(buffer.raw.size - offset);
+        assert!(
+            offset + size <= buffer.raw.size,
+            "buffer view out of bounds (offset {}, size {}, buffer size {})",
+            offset,
+            size,
+            buffer.raw.size
+        );
+        assert!(
+            (buffer.offset + offset) % self.limits.texture_buffer_offset_alignment == 0,
+            "buffer view offset must be a multiple of {} bytes",
+            self.limits.texture_buffer_offset_alignment
+        );
+        let fmtinfo = GlFormatInfo::from_format(format);
+        let texel_count = size / format.byte_size();
+        assert!(
+            texel_count <= self.limits.max_texture_buffer_size as usize,
+            "buffer view too large ({} texels, maximum is {})",
+            texel_count,
+            self.limits.max_texture_buffer_size
+        );
+        arena.buffer_views.alloc(GlBufferView::new(
+            &self.gl,
+            buffer,
+            fmtinfo.internal_fmt,
+            offset,
+            size,
+            ArenaTag::new(&arena.generation),
+        ))
     }
 
     //----------------------------------------------------------------------------------------------
@@ -545,6 +1281,11 @@ impl Instance<OpenGlBackend> for OpenGlInstance {
         // FIXME clippy warning: data may be misaligned
         let module = if data.len() >= 4 && *(data.as_ptr() as *const u32) == SPIRV_MAGIC {
             assert!(data.len() % 4 == 0);
+            assert!(
+                self.limits.spirv,
+                "SPIR-V shaders are not supported by this context \
+                 (OpenGL 4.6 or GL_ARB_gl_spirv is required)"
+            );
             // reinterpret as u32
             // FIXME clippy warning: data may be misaligned
             let data_u32 =
@@ -580,6 +1321,58 @@ impl Instance<OpenGlBackend> for OpenGlInstance {
         )
     }
 
+    unsafe fn create_compute_pipeline<'a>(
+        &self,
+        arena: &'a GlArena,
+        root_signature: &'a GlSignature,
+        root_signature_description: &SignatureDescription,
+        shader: &'a GlShaderModule,
+    ) -> &'a GlComputePipeline {
+        create_compute_pipeline_internal(
+            &self.gl,
+            arena,
+            root_signature,
+            root_signature_description,
+            shader,
+        )
+    }
+
+    //----------------------------------------------------------------------------------------------
+    unsafe fn warm_up_pipelines(
+        &self,
+        pipelines: &[PipelineRef<OpenGlBackend>],
+    ) -> Vec<PipelineCompileTime> {
+        let gl = &self.gl;
+        let mut scache = self.state_cache.borrow_mut();
+        // same as in submit_frame: objects may have been deleted since the last use of the cache
+        scache.invalidate();
+
+        // source of all vertex attributes during warm-up draws
+        let scratch_data = [0u8; WARM_UP_SCRATCH_BUFFER_SIZE];
+        let scratch_buffer = create_immutable_buffer(gl, &self.limits, &scratch_data);
+
+        let times = pipelines
+            .iter()
+            .map(|pipeline| match *pipeline {
+                PipelineRef::Graphics(pipeline) => PipelineCompileTime {
+                    kind: PipelineKind::Graphics,
+                    create_time: pipeline.create_time,
+                    warm_up_time: pipeline.warm_up(gl, &mut scache, scratch_buffer),
+                },
+                PipelineRef::Compute(pipeline) => PipelineCompileTime {
+                    kind: PipelineKind::Compute,
+                    create_time: pipeline.create_time,
+                    warm_up_time: pipeline.warm_up(gl, &mut scache),
+                },
+            })
+            .collect();
+
+        gl.DeleteBuffers(1, &scratch_buffer);
+        // the VAOs of the pipelines now reference the scratch buffer
+        scache.invalidate();
+        times
+    }
+
     //----------------------------------------------------------------------------------------------
     unsafe fn create_argument_block<'a>(
         &self,
@@ -593,11 +1386,13 @@ impl Instance<OpenGlBackend> for OpenGlInstance {
         depth_stencil_render_target: Option<DepthStencilView<'a, OpenGlBackend>>,
         viewports: impl IntoIterator<Item = Viewport>,
         scissors: impl IntoIterator<Item = Scissor>,
+        stencil_reference: Option<u32>,
     ) -> &'a GlArgumentBlock {
         let mut sampler_cache = self.sampler_cache.borrow_mut();
         GlArgumentBlock::new(
             arena,
             &self.gl,
+            self.limits.native_multiview,
             &mut sampler_cache,
             signature,
             inherited,
@@ -608,6 +1403,7 @@ impl Instance<OpenGlBackend> for OpenGlInstance {
             depth_stencil_render_target,
             viewports,
             scissors,
+            stencil_reference,
         )
     }
 
@@ -623,57 +1419,109 @@ impl Instance<OpenGlBackend> for OpenGlInstance {
     }
 
     //----------------------------------------------------------------------------------------------
-    unsafe fn create_host_reference<'a>(&self, _arena: &'a GlArena, _data: &'a [u8]) -> &'a () {
-        unimplemented!()
+    unsafe fn create_host_reference<'a>(
+        &self,
+        arena: &'a GlArena,
+        data: &'a [u8],
+    ) -> &'a GlHostReference {
+        let host_ref = arena.host_references.alloc(GlHostReference {
+            data: data.as_ptr(),
+            size: data.len(),
+            location: Cell::new((0, 0)),
+        });
+        self.rsrc
+            .borrow_mut()
+            .pending_host_references
+            .push((arena as *const _, host_ref as *const _));
+        host_ref
     }
 
     //----------------------------------------------------------------------------------------------
-    unsafe fn submit_frame<'a>(&self, frame: &[Command<'a, OpenGlBackend>]) {
-        let mut scache = self.state_cache.borrow_mut();
-
-        //self.gl.ClipControl(gl::UPPER_LEFT, gl::NEGATIVE_ONE_TO_ONE);
-        // invalidate the cache, because deletion of objects in arenas between two calls
-        // to `submit_frame` may have automatically 'unbound' objects from the pipeline.
-        scache.invalidate();
-
-        // execute commands
-        {
-            let mut subctxt = SubmissionContext::new(&self.gl, &mut scache, &self.limits);
-            for cmd in frame.iter() {
-                subctxt.submit_command(cmd);
-            }
-        }
+    unsafe fn create_host_readback<'a>(
+        &self,
+        arena: &'a GlArena,
+        dest: &'a mut [u8],
+    ) -> &'a GlHostReadback {
+        arena.host_readbacks.alloc(GlHostReadback {
+            dest: dest.as_mut_ptr(),
+            size: dest.len(),
+        })
+    }
 
-        let fnum = self.frame_num.get();
-        let mut timeline = self.timeline.borrow_mut();
-        timeline.signal(&self.gl, fnum);
+    //----------------------------------------------------------------------------------------------
+    unsafe fn create_occlusion_query<'a>(&self, arena: &'a GlArena) -> &'a GlQuery {
+        arena.queries.alloc(GlQuery::new_occlusion(&self.gl))
+    }
 
-        // wait for previous frames before starting a new one
-        // if max_frames_in_flight is zero, then will wait on the previously signalled point.
-        if fnum > u64::from(self.cfg.max_frames_in_flight) {
-            let timeout = !timeline.client_sync(
-                &self.gl,
-                fnum - u64::from(self.cfg.max_frames_in_flight),
-                FRAME_WAIT_TIMEOUT,
-            );
-            if timeout {
-                panic!(
-                    "timeout ({:?}) waiting for frame to finish",
-                    FRAME_WAIT_TIMEOUT
-                )
-            }
-        }
+    //----------------------------------------------------------------------------------------------
+    unsafe fn submit_frame<'a>(&self, frame: &[Command<'a, OpenGlBackend>]) -> SubmitStats {
+        self.submit_frame_inner(frame, None)
+    }
 
-        self.frame_num.set(fnum + 1);
+    unsafe fn submit_frame_with_report<'a>(
+        &self,
+        frame: &[Command<'a, OpenGlBackend>],
+        report: &mut FrameReport,
+    ) -> SubmitStats {
+        self.submit_frame_inner(frame, Some(report))
     }
 
     unsafe fn update_image(
         &self,
-        _image: &GlImage,
-        _min_extent: (u32, u32, u32),
-        _max_extent: (u32, u32, u32),
-        _data: &[u8],
+        image: &GlImage,
+        mip_level: u32,
+        min_extent: (u32, u32, u32),
+        max_extent: (u32, u32, u32),
+        data: &[u8],
     ) {
-        unimplemented!()
+        // same checks as batched updates
+        self.update_image_regions(
+            image,
+            &[ImageRegionUpdate {
+                mip_level,
+                offset: min_extent,
+                size: (
+                    max_extent.0 - min_extent.0,
+                    max_extent.1 - min_extent.1,
+                    max_extent.2 - min_extent.2,
+                ),
+                row_pitch: 0,
+                data,
+            }],
+        );
+    }
+
+    unsafe fn update_image_regions(&self, image: &GlImage, regions: &[ImageRegionUpdate]) {
+        let raw = image.raw();
+        assert_ne!(
+            raw.target,
+            gl::RENDERBUFFER,
+            "cannot update an image that is only usable as a render target"
+        );
+        for r in regions {
+            let level_size = texture_level_size(&self.gl, raw.obj, r.mip_level as i32)
+                .unwrap_or_else(|| panic!("invalid mipmap level: {}", r.mip_level));
+            assert!(
+                r.offset.0 + r.size.0 <= level_size.0
+                    && r.offset.1 + r.size.1 <= level_size.1
+                    && r.offset.2 + r.size.2 <= level_size.2,
+                "image region (offset {:?}, size {:?}) out of bounds of mipmap level {} ({:?})",
+                r.offset,
+                r.size,
+                r.mip_level,
+                level_size
+            );
+            upload_image_region_with_pitch(
+                &self.gl,
+                raw.target,
+                raw.obj,
+                raw.format,
+                r.mip_level as i32,
+                r.offset,
+                r.size,
+                r.row_pitch,
+                r.data,
+            );
+        }
     }
 }
@@ -1,13 +1,16 @@
 use crate::{
+    api,
     api::{types::*, Gl},
-    AliasInfo,
+    tracking::ArenaTag,
+    AliasInfo, ImplementationParameters,
 };
 use slotmap::new_key_type;
-use std::ptr;
+use std::{cell::Cell, ptr};
 
 mod upload;
 
-pub(crate) use self::upload::{MappedBuffer, UploadBuffer};
+pub use self::upload::{StreamingStrategy, UploadStats};
+pub(crate) use self::upload::{UploadBuffer, UploadRing};
 
 //--------------------------------------------------------------------------------------------------
 
@@ -56,6 +59,41 @@ pub fn create_buffer(
     obj
 }
 
+/// Creates a buffer initialized with `data`, that is not modified afterwards.
+///
+/// The buffer has immutable storage if supported, and mutable storage otherwise.
+pub fn create_immutable_buffer(gl: &Gl, limits: &ImplementationParameters, data: &[u8]) -> GLuint {
+    if limits.buffer_storage {
+        return create_buffer(gl, data.len(), 0, Some(data));
+    }
+    let mut obj: GLuint = 0;
+    unsafe {
+        gl.CreateBuffers(1, &mut obj);
+        gl.NamedBufferData(
+            obj,
+            data.len() as isize,
+            data.as_ptr() as *const GLvoid,
+            api::STATIC_DRAW,
+        );
+    }
+    obj
+}
+
+/// Creates a buffer with unspecified contents, that can be updated with `glNamedBufferSubData`.
+///
+/// The buffer has immutable storage if supported, and mutable storage otherwise.
+pub fn create_dynamic_buffer(gl: &Gl, limits: &ImplementationParameters, size: usize) -> GLuint {
+    if limits.buffer_storage {
+        return create_buffer(gl, size, api::DYNAMIC_STORAGE_BIT, None);
+    }
+    let mut obj: GLuint = 0;
+    unsafe {
+        gl.CreateBuffers(1, &mut obj);
+        gl.NamedBufferData(obj, size as isize, ptr::null(), api::DYNAMIC_DRAW);
+    }
+    obj
+}
+
 new_key_type! {
     pub(crate) struct BufferAliasKey;
 }
@@ -66,4 +104,73 @@ pub struct GlBuffer {
     pub(crate) should_destroy: bool,
     pub(crate) alias_info: Option<AliasInfo<BufferAliasKey>>,
     pub(crate) offset: usize,
+    pub(crate) tag: ArenaTag,
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Buffer texture (`GL_TEXTURE_BUFFER`) over a range of a buffer.
+///
+/// Bound like a texture, with sampler object 0.
+#[derive(Debug)]
+pub struct GlBufferView {
+    pub(crate) obj: GLuint,
+    pub(crate) tag: ArenaTag,
+}
+
+impl GlBufferView {
+    pub(crate) fn new(
+        gl: &Gl,
+        buffer: &GlBuffer,
+        internal_fmt: GLenum,
+        offset: usize,
+        size: usize,
+        tag: ArenaTag,
+    ) -> GlBufferView {
+        let mut obj = 0;
+        unsafe {
+            gl.CreateTextures(api::TEXTURE_BUFFER, 1, &mut obj);
+            gl.TextureBufferRange(
+                obj,
+                internal_fmt,
+                buffer.raw.obj,
+                (buffer.offset + offset) as isize,
+                size as isize,
+            );
+        }
+        GlBufferView { obj, tag }
+    }
+
+    pub(crate) fn destroy(&self, gl: &Gl) {
+        unsafe {
+            gl.DeleteTextures(1, &self.obj);
+        }
+    }
 }
+
+//--------------------------------------------------------------------------------------------------
+
+/// Host data referenced by argument blocks, uploaded to the arena's upload buffer
+/// at the beginning of the next call to `submit_frame`.
+#[derive(Debug)]
+pub struct GlHostReference {
+    pub(crate) data: *const u8,
+    pub(crate) size: usize,
+    /// Buffer object and offset of the uploaded data. Valid once the frame has been submitted.
+    pub(crate) location: Cell<(GLuint, usize)>,
+}
+
+// The referenced data is borrowed immutably for the lifetime of the arena, and the location is
+// only written in `submit_frame`.
+unsafe impl Sync for GlHostReference {}
+
+/// Host memory written by a buffer-to-host copy during `submit_frame`.
+#[derive(Debug)]
+pub struct GlHostReadback {
+    pub(crate) dest: *mut u8,
+    pub(crate) size: usize,
+}
+
+// The destination is borrowed mutably for the lifetime of the arena, and only written
+// in `submit_frame`.
+unsafe impl Sync for GlHostReadback {}
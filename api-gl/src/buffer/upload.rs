@@ -1,11 +1,50 @@
 //! Upload buffers
+//!
+//! Host data referenced by GPU commands is written into a persistently-mapped _upload ring_.
+//! Arenas allocate chunks of the ring and sub-allocate from them. When an arena is dropped,
+//! its chunks are retired behind a fence; the space is recycled once the fence is signalled,
+//! i.e. once the GPU has finished reading the data.
+//!
+//! Chunks are recycled in allocation order: a long-lived arena holding the oldest chunk
+//! prevents the space after it from being reused. If the ring is full, chunks are allocated in
+//! dedicated _overflow_ buffers, which are deleted once the GPU is done with them.
+//!
+//! Alternatively, with [StreamingStrategy::Orphaning], each chunk is a separate buffer written
+//! with `glBufferSubData`. Buffers are _orphaned_ when their arena is dropped and reused right
+//! away, without waiting for the GPU.
 use crate::{
     api as gl,
     api::{types::*, Gl},
     buffer::create_buffer,
+    sync::GpuSyncObject,
 };
 use autograph_api::align_offset;
-use std::{ptr::copy_nonoverlapping, sync::Mutex};
+use std::{
+    collections::VecDeque,
+    ptr::{self, copy_nonoverlapping},
+    sync::Mutex,
+};
+
+/// How host data referenced by GPU commands is streamed to the GPU.
+///
+/// The best strategy depends on the driver: see the `upload_benchmark` example.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum StreamingStrategy {
+    /// Data is written in a persistently-mapped ring buffer. The space used by an arena is
+    /// recycled once a fence, inserted when the arena is dropped, is signalled.
+    PersistentRing,
+    /// Data is written with `glBufferSubData` in buffers with mutable storage. When an arena is
+    /// dropped, its buffers are orphaned (`glBufferData(NULL)`) and immediately reused for new
+    /// chunks: the driver allocates new storage for them while the GPU still reads the old one.
+    /// Does not use fences, but relies on the renaming heuristics of the driver.
+    Orphaning,
+}
+
+impl Default for StreamingStrategy {
+    fn default() -> Self {
+        StreamingStrategy::PersistentRing
+    }
+}
 
 pub(crate) struct MappedBuffer {
     buffer: GLuint,
@@ -57,39 +96,322 @@ impl MappedBuffer {
     pub(crate) fn raw_buffer(&self) -> GLuint {
         self.buffer
     }
+
+    pub(crate) fn destroy(self, gl: &Gl) {
+        unsafe {
+            gl.UnmapNamedBuffer(self.buffer);
+            gl.DeleteBuffers(1, &self.buffer);
+        }
+    }
 }
 
-struct UploadBufferInner {
-    buffer: MappedBuffer,
-    offset: usize,
+/// Statistics about the usage of the upload ring.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct UploadStats {
+    /// Size of the upload ring in bytes (0 with [StreamingStrategy::Orphaning]).
+    pub ring_size: usize,
+    /// Number of bytes of the ring currently allocated (in use by arenas or waiting for the GPU).
+    /// With [StreamingStrategy::Orphaning], size of the buffers in use by arenas.
+    pub used: usize,
+    /// Maximum value of `used` since the creation of the ring.
+    pub high_water_mark: usize,
+    /// Number of chunks that did not fit in the ring and were allocated in dedicated buffers.
+    pub overflow_chunks: usize,
+    /// Total size of overflow chunks in bytes.
+    pub overflow_bytes: usize,
+    /// Number of buffers orphaned for reuse, with [StreamingStrategy::Orphaning].
+    pub orphaned_buffers: usize,
 }
 
-pub(crate) struct UploadBuffer(Mutex<UploadBufferInner>);
+/// A region of upload memory owned by an arena.
+pub(crate) struct UploadChunk {
+    buffer: GLuint,
+    /// Null if the buffer is not mapped.
+    ptr: *mut u8,
+    /// End offset of the chunk in the buffer.
+    end: usize,
+    /// Current allocation offset in the buffer.
+    offset: usize,
+    kind: ChunkKind,
+}
 
-impl UploadBuffer {
-    pub(crate) fn new(buffer: MappedBuffer) -> UploadBuffer {
-        UploadBuffer(Mutex::new(UploadBufferInner { buffer, offset: 0 }))
-    }
+unsafe impl Send for UploadChunk {}
 
-    /// Returns the offset.
-    pub(crate) fn write(&self, data: &[u8], align: usize) -> Option<(GLuint, usize)> {
-        let mut self_ = self.0.lock().unwrap();
+enum ChunkKind {
+    /// Chunk allocated in the upload ring, with the given region ID.
+    Ring(u64),
+    /// Chunk allocated in a dedicated buffer because the ring was full.
+    Overflow(MappedBuffer),
+    /// Unmapped buffer with mutable storage of the given size, orphaned on retirement.
+    Orphan(usize),
+}
 
+impl UploadChunk {
+    /// Writes data into the chunk, returns the buffer and the offset of the data in the buffer,
+    /// or `None` if there is not enough space left.
+    fn write(&mut self, gl: &Gl, data: &[u8], align: usize) -> Option<(GLuint, usize)> {
         let offset = align_offset(
             data.len() as u64,
             align as u64,
-            (self_.offset as u64)..(self_.buffer.size as u64),
+            (self.offset as u64)..(self.end as u64),
         )? as usize;
-        self_.buffer.write(data, offset);
-        self_.offset = offset + data.len();
-        Some((self_.buffer.raw_buffer(), offset))
+        unsafe {
+            if self.ptr.is_null() {
+                gl.NamedBufferSubData(
+                    self.buffer,
+                    offset as isize,
+                    data.len() as isize,
+                    data.as_ptr() as *const GLvoid,
+                );
+            } else {
+                copy_nonoverlapping(data.as_ptr(), self.ptr.add(offset), data.len());
+            }
+        }
+        self.offset = offset + data.len();
+        Some((self.buffer, offset))
     }
+}
 
-    /*pub(crate) fn flush(&self, gl: &Gl) {
-        self.0.lock().unwrap().buffer.flush(gl)
-    }*/
+/// Upload chunks of an arena.
+pub(crate) struct UploadBuffer(Mutex<Vec<UploadChunk>>);
+
+impl UploadBuffer {
+    pub(crate) fn new() -> UploadBuffer {
+        UploadBuffer(Mutex::new(Vec::new()))
+    }
+
+    /// Writes data into the last allocated chunk. Returns the buffer and the offset of the data
+    /// in the buffer, or `None` if a new chunk must be allocated.
+    pub(crate) fn write(&self, gl: &Gl, data: &[u8], align: usize) -> Option<(GLuint, usize)> {
+        self.0.lock().unwrap().last_mut()?.write(gl, data, align)
+    }
+
+    /// Writes data into a newly-allocated chunk, which becomes the current one.
+    pub(crate) fn write_in_new_chunk(
+        &self,
+        gl: &Gl,
+        mut chunk: UploadChunk,
+        data: &[u8],
+        align: usize,
+    ) -> (GLuint, usize) {
+        let result = chunk
+            .write(gl, data, align)
+            .expect("upload chunk too small");
+        self.0.lock().unwrap().push(chunk);
+        result
+    }
+
+    pub(crate) fn into_chunks(self) -> Vec<UploadChunk> {
+        self.0.into_inner().unwrap()
+    }
+}
+
+struct RingRegion {
+    id: u64,
+    start: usize,
+    end: usize,
+    /// Whether the GPU has finished using the region.
+    free: bool,
+}
+
+/// Ring allocator for upload chunks.
+pub(crate) struct UploadRing {
+    strategy: StreamingStrategy,
+    /// Created on first allocation.
+    buffer: Option<MappedBuffer>,
+    size: usize,
+    /// Allocation offset.
+    head: usize,
+    /// Allocated regions, in allocation order.
+    regions: VecDeque<RingRegion>,
+    next_id: u64,
+    /// Chunks retired by dropped arenas, waiting for the GPU.
+    retired: VecDeque<GpuSyncObject<Vec<UploadChunk>>>,
+    /// Orphaned buffers available for new chunks (`StreamingStrategy::Orphaning`), with their
+    /// size.
+    orphans: Vec<(GLuint, usize)>,
+    stats: UploadStats,
+}
+
+impl UploadRing {
+    pub(crate) fn new(size: usize, strategy: StreamingStrategy) -> UploadRing {
+        let ring_size = match strategy {
+            StreamingStrategy::PersistentRing => size,
+            StreamingStrategy::Orphaning => 0,
+        };
+        UploadRing {
+            strategy,
+            buffer: None,
+            size: ring_size,
+            head: 0,
+            regions: VecDeque::new(),
+            next_id: 0,
+            retired: VecDeque::new(),
+            orphans: Vec::new(),
+            stats: UploadStats {
+                ring_size,
+                ..UploadStats::default()
+            },
+        }
+    }
+
+    pub(crate) fn stats(&self) -> UploadStats {
+        self.stats
+    }
+
+    /// Finds space for `size` bytes in the ring.
+    fn find_space(&self, size: usize) -> Option<usize> {
+        let tail = if let Some(front) = self.regions.front() {
+            front.start
+        } else {
+            // ring is empty
+            return if size <= self.size { Some(0) } else { None };
+        };
+
+        if self.head > tail {
+            // free space is [head, end) + [0, tail)
+            if self.size - self.head >= size {
+                Some(self.head)
+            } else if tail >= size {
+                Some(0)
+            } else {
+                None
+            }
+        } else if tail - self.head >= size {
+            // free space is [head, tail)
+            Some(self.head)
+        } else {
+            None
+        }
+    }
+
+    /// Allocates a chunk of at least `size` bytes.
+    pub(crate) fn alloc_chunk(&mut self, gl: &Gl, size: usize) -> UploadChunk {
+        if self.strategy == StreamingStrategy::Orphaning {
+            return self.alloc_orphan_chunk(gl, size);
+        }
+        self.reclaim(gl);
 
-    pub(crate) fn into_inner(self) -> MappedBuffer {
-        self.0.into_inner().unwrap().buffer
+        if let Some(start) = self.find_space(size) {
+            let ring_size = self.size;
+            let buffer = self
+                .buffer
+                .get_or_insert_with(|| MappedBuffer::new(gl, ring_size));
+            let id = self.next_id;
+            self.next_id += 1;
+            let end = start + size;
+            self.regions.push_back(RingRegion {
+                id,
+                start,
+                end,
+                free: false,
+            });
+            self.head = end;
+            self.stats.used += size;
+            self.stats.high_water_mark = self.stats.high_water_mark.max(self.stats.used);
+            UploadChunk {
+                buffer: buffer.raw_buffer(),
+                ptr: buffer.ptr,
+                end,
+                offset: start,
+                kind: ChunkKind::Ring(id),
+            }
+        } else {
+            let buffer = MappedBuffer::new(gl, size);
+            self.stats.overflow_chunks += 1;
+            self.stats.overflow_bytes += size;
+            UploadChunk {
+                buffer: buffer.raw_buffer(),
+                ptr: buffer.ptr,
+                end: size,
+                offset: 0,
+                kind: ChunkKind::Overflow(buffer),
+            }
+        }
+    }
+
+    /// Allocates a chunk in an orphaned buffer that is large enough, or in a new buffer.
+    fn alloc_orphan_chunk(&mut self, gl: &Gl, size: usize) -> UploadChunk {
+        // don't waste more than half of a reused buffer
+        let (buffer, buffer_size) = match self
+            .orphans
+            .iter()
+            .position(|&(_, s)| s >= size && s / 2 <= size)
+        {
+            Some(i) => self.orphans.swap_remove(i),
+            None => {
+                let mut obj = 0;
+                unsafe {
+                    gl.CreateBuffers(1, &mut obj);
+                    gl.NamedBufferData(obj, size as isize, ptr::null(), gl::STREAM_DRAW);
+                }
+                (obj, size)
+            }
+        };
+        self.stats.used += buffer_size;
+        self.stats.high_water_mark = self.stats.high_water_mark.max(self.stats.used);
+        UploadChunk {
+            buffer,
+            ptr: ptr::null_mut(),
+            end: buffer_size,
+            offset: 0,
+            kind: ChunkKind::Orphan(buffer_size),
+        }
+    }
+
+    /// Retires the chunks of an arena. The chunks are reclaimed once all commands submitted
+    /// before this call have completed.
+    ///
+    /// Orphan chunks are reclaimed immediately: their buffers are orphaned, so the commands
+    /// already submitted keep reading the previous storage.
+    pub(crate) fn retire(&mut self, gl: &Gl, chunks: Vec<UploadChunk>) {
+        let mut fenced = Vec::new();
+        for chunk in chunks {
+            if let ChunkKind::Orphan(size) = chunk.kind {
+                unsafe {
+                    gl.NamedBufferData(chunk.buffer, size as isize, ptr::null(), gl::STREAM_DRAW);
+                }
+                self.orphans.push((chunk.buffer, size));
+                self.stats.used -= size;
+                self.stats.orphaned_buffers += 1;
+            } else {
+                fenced.push(chunk);
+            }
+        }
+        if !fenced.is_empty() {
+            self.retired.push_back(GpuSyncObject::new(gl, fenced));
+        }
+    }
+
+    /// Recycles the space of retired chunks that are not used by the GPU anymore.
+    pub(crate) fn reclaim(&mut self, gl: &Gl) {
+        while !self.retired.is_empty() {
+            let ready = self.retired.front().unwrap().try_wait(gl);
+            if ready.is_err() {
+                break;
+            }
+            let chunks = self.retired.pop_front().unwrap();
+            let chunks = unsafe { chunks.into_inner_unsynchronized(gl) };
+            for chunk in chunks {
+                match chunk.kind {
+                    ChunkKind::Ring(id) => {
+                        if let Some(region) = self.regions.iter_mut().find(|r| r.id == id) {
+                            region.free = true;
+                        }
+                    }
+                    ChunkKind::Overflow(buffer) => buffer.destroy(gl),
+                    ChunkKind::Orphan(_) => unreachable!(),
+                }
+            }
+        }
+
+        // advance the tail past contiguous free regions
+        while self.regions.front().map_or(false, |r| r.free) {
+            let region = self.regions.pop_front().unwrap();
+            self.stats.used -= region.end - region.start;
+        }
+        if self.regions.is_empty() {
+            self.head = 0;
+        }
     }
 }
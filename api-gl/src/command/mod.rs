@@ -1,25 +1,76 @@
 use crate::api as gl;
 //use crate::api::types::*;
 use crate::{
-    api::Gl, image::GlImage, pipeline::GlGraphicsPipeline, swapchain::GlSwapchain,
+    api::{types::*, Gl},
+    buffer::{GlBuffer, GlHostReadback},
+    framebuffer::{Attachment, GlFramebuffer},
+    image::{texture_level_size, GlImage},
+    pipeline::{GlComputePipeline, GlGraphicsPipeline},
+    query::GlQuery,
+    swapchain::{GlSwapchain, SwapchainTarget},
     ImplementationParameters,
 };
-use autograph_api::command::{Command, CommandInner};
+use autograph_api::{
+    command::{
+        AccessFlags, BarrierDesc, ClearColor, ClearValue, Command, CommandInner, PresentEncoding,
+    },
+    AliasScope,
+};
 
 mod state;
-pub use self::state::StateCache;
+pub use self::state::{BindCounter, StateCache, StateCacheCounters};
+use self::state::{DrawArraysIndirectCommand, DrawElementsIndirectCommand};
 use crate::{
     backend::OpenGlBackend,
     pipeline::{GlArgumentBlock, StateBlock},
 };
-use autograph_api::{pipeline::Scissor, traits::Swapchain};
-use std::slice;
+use autograph_api::{
+    format::{ColorSpace, NumericFormat},
+    pipeline::{LineWidth, LoadOp, Scissor, StoreOp, Viewport},
+    query::Predicate,
+    report::FrameReport,
+    traits::Swapchain,
+    vertex::IndexFormat,
+};
+use glutin::GlContext;
+use std::{
+    collections::HashSet,
+    mem, ptr, slice,
+    time::{Duration, Instant},
+};
 
 pub struct SubmissionContext<'a, 'rcx> {
     state_cache: &'a mut StateCache,
     gl: &'a Gl,
     _impl_params: &'a ImplementationParameters,
     current_pipeline: Option<&'rcx GlGraphicsPipeline>,
+    batch_draws: bool,
+    /// Whether a conditional rendering block is open, and if so, whether it is implemented
+    /// with `glBeginConditionalRender` (`true`) or ignored (`false`).
+    conditional: Option<bool>,
+    /// Statistics collected during submission, if requested.
+    report: Option<&'a mut FrameReport>,
+    /// Line width for pipelines with a dynamic line width, set by `SetLineWidth` commands.
+    dynamic_line_width: f32,
+    /// Attachments already used in the frame: their load operation has been performed.
+    loaded_attachments: HashSet<(*const GlImage, u32, u32)>,
+    /// Attachments with `StoreOp::DontCare`, invalidated at the end of the frame
+    /// (framebuffer object and attachment point).
+    discarded_attachments: Vec<(GLuint, GLenum)>,
+    /// Sort key of the command being submitted.
+    sortkey: u64,
+    /// Uses of images (texture or renderbuffer object, sort key of the command), recorded if the
+    /// report requests a lifetime analysis.
+    image_uses: Option<Vec<(GLuint, u64)>>,
+    /// Time spent in `SwapBuffers`, which blocks if the swapchain has no free image.
+    present_time: Duration,
+    /// Framebuffer bound by the last arguments with render targets, for the emulation of
+    /// multiview.
+    current_framebuffer: Option<*const GlFramebuffer>,
+    /// Number of open debug groups.
+    debug_group_depth: u32,
+    /// Images to clear before the first command inside their alias scope.
+    initial_clears: Vec<(*const GlImage, AliasScope, ClearValue)>,
 }
 
 #[derive(Default)]
@@ -39,12 +90,90 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
         gl: &'a Gl,
         state_cache: &'a mut StateCache,
         impl_params: &'a ImplementationParameters,
+        batch_draws: bool,
+        report: Option<&'a mut FrameReport>,
     ) -> SubmissionContext<'a, 'rcx> {
+        let image_uses = match report {
+            Some(ref report) if report.lifetimes.is_some() => Some(Vec::new()),
+            _ => None,
+        };
         SubmissionContext {
             state_cache,
             gl,
             _impl_params: impl_params,
             current_pipeline: None,
+            batch_draws,
+            conditional: None,
+            report,
+            dynamic_line_width: 1.0,
+            loaded_attachments: HashSet::new(),
+            discarded_attachments: Vec::new(),
+            sortkey: 0,
+            image_uses,
+            present_time: Duration::from_secs(0),
+            current_framebuffer: None,
+            debug_group_depth: 0,
+            initial_clears: Vec::new(),
+        }
+    }
+
+    /// Sets the images to clear at the start of their alias scope
+    /// (see `Arena::create_image_cleared`).
+    pub fn set_initial_clears(&mut self, clears: Vec<(*const GlImage, AliasScope, ClearValue)>) {
+        self.initial_clears = clears;
+    }
+
+    /// Clears the images whose alias scope contains `sortkey` and that were not cleared yet.
+    ///
+    /// Commands are sorted: this is the first command inside their scope.
+    fn apply_initial_clears(&mut self, sortkey: u64) {
+        if self.initial_clears.is_empty() {
+            return;
+        }
+        let (due, pending): (Vec<_>, Vec<_>) = mem::replace(&mut self.initial_clears, Vec::new())
+            .into_iter()
+            .partition(|&(_, scope, _)| scope.contains(sortkey));
+        self.initial_clears = pending;
+        self.sortkey = sortkey;
+        for (image, _, value) in due {
+            let image = unsafe { &*image };
+            self.record_image_uses(&[image.raw().obj]);
+            match value {
+                ClearValue::Color(color) => self.cmd_clear_image(image, color),
+                ClearValue::DepthStencil { depth, stencil } => {
+                    self.cmd_clear_depth_stencil_image(image, depth, stencil)
+                }
+            }
+        }
+    }
+
+    /// Records statistics about a command (or a batch of draws) in the frame report.
+    ///
+    /// A new pass is started whenever the command changed the bound framebuffer, unless the
+    /// current pass is empty (e.g. a named pass that was just started).
+    fn record(
+        &mut self,
+        sortkey: u64,
+        framebuffer_before: Option<GLuint>,
+        issued_before: u64,
+        draws: u32,
+        dispatches: u32,
+    ) {
+        if let Some(ref mut report) = self.report {
+            if self.state_cache.draw_framebuffer() != framebuffer_before {
+                // the framebuffer of a named pass is bound by its first draw, and passes started
+                // at the end of a named pass are empty until the next command
+                let empty = report.passes.last().map_or(false, |p| {
+                    p.draws == 0 && p.dispatches == 0 && (p.name.is_some() || p.state_changes == 0)
+                });
+                if !empty {
+                    report.begin_pass(sortkey);
+                }
+            }
+            let pass = report.current_pass(sortkey);
+            pass.draws += draws;
+            pass.dispatches += dispatches;
+            pass.state_changes += (self.state_cache.issued() - issued_before) as u32;
         }
     }
 
@@ -53,8 +182,28 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
         self.state_cache.set_scissors(self.gl, &[Scissor::Disabled]);
     }
 
-    fn cmd_clear_image_float(&mut self, image: &GlImage, color: &[f32; 4]) {
-        if image.raw.target == gl::RENDERBUFFER {
+    fn cmd_clear_image(&mut self, image: &GlImage, color: ClearColor) {
+        // the clear value is converted to the numeric format of the image
+        let format_type = &image.raw().format.get_format_info().format_type;
+        let (color, uint_color, int_color) = match color {
+            ClearColor::Float(c) => (
+                c,
+                [c[0] as u32, c[1] as u32, c[2] as u32, c[3] as u32],
+                [c[0] as i32, c[1] as i32, c[2] as i32, c[3] as i32],
+            ),
+            ClearColor::Uint(c) => (
+                [c[0] as f32, c[1] as f32, c[2] as f32, c[3] as f32],
+                c,
+                [c[0] as i32, c[1] as i32, c[2] as i32, c[3] as i32],
+            ),
+            ClearColor::Int(c) => (
+                [c[0] as f32, c[1] as f32, c[2] as f32, c[3] as f32],
+                [c[0] as u32, c[1] as u32, c[2] as u32, c[3] as u32],
+                c,
+            ),
+        };
+
+        if image.raw().target == gl::RENDERBUFFER {
             // create temporary framebuffer
             let mut tmpfb = 0;
             unsafe {
@@ -63,32 +212,65 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
                     tmpfb,
                     gl::COLOR_ATTACHMENT0,
                     gl::RENDERBUFFER,
-                    image.raw.obj,
+                    image.raw().obj,
                 );
                 self.gl
                     .NamedFramebufferDrawBuffers(tmpfb, 1, (&[gl::COLOR_ATTACHMENT0]).as_ptr());
+                let color_write_enabled = self.state_cache.color_write_enabled();
+                self.state_cache.set_color_write_enable(self.gl, true);
                 self.disable_scissor_test();
-                self.gl
-                    .ClearNamedFramebufferfv(tmpfb, gl::COLOR, 0, color.as_ptr());
+                match format_type {
+                    NumericFormat::UINT => {
+                        self.gl
+                            .ClearNamedFramebufferuiv(tmpfb, gl::COLOR, 0, uint_color.as_ptr())
+                    }
+                    NumericFormat::SINT => {
+                        self.gl
+                            .ClearNamedFramebufferiv(tmpfb, gl::COLOR, 0, int_color.as_ptr())
+                    }
+                    _ => self
+                        .gl
+                        .ClearNamedFramebufferfv(tmpfb, gl::COLOR, 0, color.as_ptr()),
+                }
+                if let Some(enabled) = color_write_enabled {
+                    self.state_cache.set_color_write_enable(self.gl, enabled);
+                }
                 self.gl.DeleteFramebuffers(1, &tmpfb);
             }
         } else {
             // TODO specify which level to clear in command
+            let obj = image.raw().obj;
             unsafe {
-                self.gl.ClearTexImage(
-                    image.raw.obj,
-                    0,
-                    gl::RGBA,
-                    gl::FLOAT,
-                    color.as_ptr() as *const _,
-                );
+                match format_type {
+                    NumericFormat::UINT => self.gl.ClearTexImage(
+                        obj,
+                        0,
+                        gl::RGBA_INTEGER,
+                        gl::UNSIGNED_INT,
+                        uint_color.as_ptr() as *const _,
+                    ),
+                    NumericFormat::SINT => self.gl.ClearTexImage(
+                        obj,
+                        0,
+                        gl::RGBA_INTEGER,
+                        gl::INT,
+                        int_color.as_ptr() as *const _,
+                    ),
+                    _ => self.gl.ClearTexImage(
+                        obj,
+                        0,
+                        gl::RGBA,
+                        gl::FLOAT,
+                        color.as_ptr() as *const _,
+                    ),
+                }
             }
         }
     }
 
     fn cmd_clear_depth_stencil_image(&mut self, image: &GlImage, depth: f32, stencil: Option<u8>) {
-        let obj = image.raw.obj;
-        if image.raw.target == gl::RENDERBUFFER {
+        let obj = image.raw().obj;
+        if image.raw().target == gl::RENDERBUFFER {
             // create temporary framebuffer
             let mut tmpfb = 0;
             unsafe {
@@ -125,6 +307,110 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
         }
     }
 
+    /// Performs the load operations of the attachments of the framebuffer that are used for the
+    /// first time in the frame, and schedules the store operations.
+    ///
+    /// With emulated views, the operations are performed on the framebuffers of all views.
+    fn apply_load_ops(&mut self, fb: &GlFramebuffer) {
+        for (index, a) in fb.color_attachments.iter().enumerate() {
+            let attachment_point = gl::COLOR_ATTACHMENT0 + index as u32;
+            if !self.first_use(a, fb, attachment_point) {
+                continue;
+            }
+            for &obj in fb.objs() {
+                match a.load_op {
+                    LoadOp::Load => {}
+                    LoadOp::Clear(color) => unsafe {
+                        // color writes must be enabled for the clear to have an effect
+                        let color_write_enabled = self.state_cache.color_write_enabled();
+                        self.state_cache.set_color_write_enable(self.gl, true);
+                        self.disable_scissor_test();
+                        self.gl.ClearNamedFramebufferfv(
+                            obj,
+                            gl::COLOR,
+                            index as i32,
+                            color.as_ptr(),
+                        );
+                        if let Some(enabled) = color_write_enabled {
+                            self.state_cache.set_color_write_enable(self.gl, enabled);
+                        }
+                    },
+                    LoadOp::DontCare => unsafe {
+                        self.gl
+                            .InvalidateNamedFramebufferData(obj, 1, &attachment_point);
+                    },
+                }
+            }
+        }
+
+        if let Some(ref a) = fb.depth_stencil_attachment {
+            if !self.first_use(a, fb, gl::DEPTH_ATTACHMENT) {
+                return;
+            }
+            for &obj in fb.objs() {
+                match a.load_op {
+                    LoadOp::Load => {}
+                    LoadOp::Clear(value) => unsafe {
+                        // depth writes must be enabled for the clear to have an effect
+                        let depth_write_enabled = self.state_cache.depth_write_enabled();
+                        self.state_cache.set_depth_write_enable(self.gl, true);
+                        self.disable_scissor_test();
+                        self.gl
+                            .ClearNamedFramebufferfv(obj, gl::DEPTH, 0, &value[0]);
+                        if let Some(enabled) = depth_write_enabled {
+                            self.state_cache.set_depth_write_enable(self.gl, enabled);
+                        }
+                    },
+                    LoadOp::DontCare => unsafe {
+                        self.gl
+                            .InvalidateNamedFramebufferData(obj, 1, &gl::DEPTH_ATTACHMENT);
+                    },
+                }
+            }
+        }
+    }
+
+    /// Returns whether this is the first use of the attachment in the frame, and if so,
+    /// schedules its store operation.
+    fn first_use(&mut self, a: &Attachment, fb: &GlFramebuffer, attachment_point: GLenum) -> bool {
+        if !self.loaded_attachments.insert(a.key()) {
+            return false;
+        }
+        if a.store_op == StoreOp::DontCare {
+            self.discarded_attachments
+                .extend(fb.objs().iter().map(|&obj| (obj, attachment_point)));
+        }
+        true
+    }
+
+    /// Records uses of images by the current command, if a lifetime analysis was requested.
+    fn record_image_uses(&mut self, objs: &[GLuint]) {
+        if let Some(ref mut uses) = self.image_uses {
+            let sortkey = self.sortkey;
+            uses.extend(objs.iter().map(|&obj| (obj, sortkey)));
+        }
+    }
+
+    /// Returns the uses of images recorded during submission (see `FrameReport::lifetimes`).
+    pub fn take_image_uses(&mut self) -> Vec<(GLuint, u64)> {
+        self.image_uses.take().unwrap_or_default()
+    }
+
+    /// Returns the time spent presenting images to windows.
+    pub fn present_time(&self) -> Duration {
+        self.present_time
+    }
+
+    /// Invalidates the attachments with `StoreOp::DontCare` at the end of the frame.
+    fn apply_store_ops(&mut self) {
+        for (fbo, attachment_point) in self.discarded_attachments.drain(..) {
+            unsafe {
+                self.gl
+                    .InvalidateNamedFramebufferData(fbo, 1, &attachment_point);
+            }
+        }
+    }
+
     //pub fn cmd_set_attachments(&mut self, color_attachments: &[R::])
 
     //unsafe fn cmd_set_pipeline_arguments_rec(&mut self, args: &GlPipelineArguments) {}
@@ -135,8 +421,8 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
         base_slots: &mut BaseSlots,
         is_root: bool,
     ) {
-        let pipeline = self.current_pipeline.unwrap();
-        let _descriptor_map = pipeline.descriptor_map();
+        // no graphics pipeline bound if the arguments are for a compute pipeline
+        let _descriptor_map = self.current_pipeline.map(|p| p.descriptor_map());
 
         // could also fetch the signature from the pipeline
         let sig = unsafe { &*args.signature };
@@ -154,18 +440,40 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
                     buffers,
                     offsets,
                     sizes,
+                    host_references,
                 } => {
                     let n = sig.num_uniform_buffers;
                     let buffers = unsafe { slice::from_raw_parts(buffers, n) };
                     let offsets = unsafe { slice::from_raw_parts(offsets, n) };
                     let sizes = unsafe { slice::from_raw_parts(sizes, n) };
-                    self.state_cache.set_uniform_buffers(
-                        self.gl,
-                        base_slots.ubo,
-                        buffers,
-                        offsets,
-                        sizes,
-                    );
+                    let host_references = unsafe { slice::from_raw_parts(host_references, n) };
+                    if host_references.iter().all(|r| r.is_null()) {
+                        self.state_cache.set_uniform_buffers(
+                            self.gl,
+                            base_slots.ubo,
+                            buffers,
+                            offsets,
+                            sizes,
+                        );
+                    } else {
+                        // fetch the location of the host data uploaded during this submission
+                        let mut buffers = smallvec::SmallVec::<[GLuint; 8]>::from_slice(buffers);
+                        let mut offsets = smallvec::SmallVec::<[GLintptr; 8]>::from_slice(offsets);
+                        for (i, &r) in host_references.iter().enumerate() {
+                            if !r.is_null() {
+                                let (obj, offset) = unsafe { (*r).location.get() };
+                                buffers[i] = obj;
+                                offsets[i] = offset as GLintptr;
+                            }
+                        }
+                        self.state_cache.set_uniform_buffers(
+                            self.gl,
+                            base_slots.ubo,
+                            &buffers,
+                            &offsets,
+                            sizes,
+                        );
+                    }
                     base_slots.ubo += n;
                 }
                 &StateBlock::ShaderStorageBuffers {
@@ -214,6 +522,7 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
                 }
                 &StateBlock::Textures(textures) => {
                     let textures = unsafe { slice::from_raw_parts(textures, sig.num_textures) };
+                    self.record_image_uses(textures);
                     self.state_cache
                         .set_textures(self.gl, base_slots.tex, textures);
                     base_slots.tex += sig.num_textures;
@@ -226,6 +535,7 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
                 }
                 &StateBlock::Images(images) => {
                     let images = unsafe { slice::from_raw_parts(images, sig.num_images) };
+                    self.record_image_uses(images);
                     self.state_cache.set_images(self.gl, base_slots.img, images);
                     base_slots.img += sig.num_images;
                 }
@@ -239,8 +549,18 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
                         panic!("RenderTarget block in root signature")
                     }
                 }
-                &StateBlock::Framebuffer(obj) => {
-                    self.state_cache.set_draw_framebuffer(self.gl, obj);
+                &StateBlock::Framebuffer(fb) => {
+                    self.current_framebuffer = Some(fb);
+                    let fb = unsafe { &*fb };
+                    self.state_cache.set_draw_framebuffer(self.gl, fb.obj);
+                    self.apply_load_ops(fb);
+                    for a in fb
+                        .color_attachments
+                        .iter()
+                        .chain(&fb.depth_stencil_attachment)
+                    {
+                        self.record_image_uses(&[unsafe { (&*a.image).raw().obj }]);
+                    }
                 }
                 &StateBlock::Viewports(viewports) => {
                     let viewports = unsafe { slice::from_raw_parts(viewports, sig.num_viewports) };
@@ -252,39 +572,88 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
                     let scissors = unsafe { slice::from_raw_parts(scissors, sig.num_scissors) };
                     self.state_cache.set_scissors(self.gl, scissors);
                     //base_slots.viewports += sig.num_scissors;
+                }
+                &StateBlock::StencilReference(reference) => {
+                    if let Some(pipeline) = self.current_pipeline {
+                        let state = &pipeline.depth_stencil_state;
+                        if state.dynamic_stencil_reference {
+                            self.state_cache.set_stencil_test_with_reference(
+                                self.gl,
+                                &state.stencil_test,
+                                reference,
+                            );
+                        }
+                    }
                 } //&StateBlock::Empty => {}
             }
         }
     }
 
-    fn cmd_present(&mut self, image: &GlImage, swapchain: &GlSwapchain) {
+    fn cmd_present(&mut self, image: &GlImage, swapchain: &GlSwapchain, encoding: PresentEncoding) {
         // only handle default swapchain for now
         //assert_eq!(swapchain, 0, "invalid swapchain handle");
+        // windows other than the main window have their own context: framebuffers are not shared
+        // between contexts, so the blit is done in the context of the window
+        let shared_window = match swapchain.target {
+            SwapchainTarget::SharedWindow { ref window, .. } => {
+                unsafe {
+                    window
+                        .make_current()
+                        .expect("failed to make context current");
+                }
+                true
+            }
+            _ => false,
+        };
+
         // make a framebuffer and bind the image to it
         unsafe {
             let mut tmpfb = 0;
             self.gl.CreateFramebuffers(1, &mut tmpfb);
             // bind image to it
-            if image.raw.target == gl::RENDERBUFFER {
+            if image.raw().target == gl::RENDERBUFFER {
                 self.gl.NamedFramebufferRenderbuffer(
                     tmpfb,
                     gl::COLOR_ATTACHMENT0,
                     gl::RENDERBUFFER,
-                    image.raw.obj,
+                    image.raw().obj,
                 );
             } else {
                 // TODO other levels / layers?
                 self.gl
-                    .NamedFramebufferTexture(tmpfb, gl::COLOR_ATTACHMENT0, image.raw.obj, 0);
+                    .NamedFramebufferTexture(tmpfb, gl::COLOR_ATTACHMENT0, image.raw().obj, 0);
             }
             // blit to default framebuffer
             let (w, h): (u32, u32) = swapchain.size();
 
-            self.disable_scissor_test();
+            // the state cache tracks the state of the main context only, and the scissor test is
+            // never enabled in the other contexts
+            if !shared_window {
+                self.disable_scissor_test();
+            }
+
+            // Encode linear images to sRGB. The state is always set explicitly: some drivers give
+            // the default framebuffer a sRGB format even when it was not requested.
+            // The blit flips the image vertically, which does not interfere with the conversion:
+            // sRGB source images are decoded when read, and the values encoded when written.
+            let encoding = match encoding {
+                PresentEncoding::Default => swapchain.present_encoding,
+                other => other,
+            };
+            let srgb = match encoding {
+                PresentEncoding::Default => swapchain.color_space == ColorSpace::Srgb,
+                PresentEncoding::Srgb => true,
+                PresentEncoding::Raw => false,
+            };
+            if srgb {
+                self.gl.Enable(gl::FRAMEBUFFER_SRGB);
+            } else {
+                self.gl.Disable(gl::FRAMEBUFFER_SRGB);
+            }
 
             self.gl.BlitNamedFramebuffer(
                 tmpfb,
-                0,
+                swapchain.framebuffer(),
                 0,        // srcX0
                 0,        // srcY0
                 w as i32, // srcX1,
@@ -297,21 +666,233 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
                 gl::NEAREST,
             );
 
+            if srgb {
+                self.gl.Disable(gl::FRAMEBUFFER_SRGB);
+            }
+
             // destroy temp framebuffer
             self.gl.DeleteFramebuffers(1, &tmpfb);
         }
 
-        // swap buffers
-        swapchain
-            .window
-            .swap_buffers()
-            .expect("failed to swap buffers")
+        // swap buffers (imported framebuffers are presented by the host application)
+        let swap_start = Instant::now();
+        match swapchain.target {
+            SwapchainTarget::Window(ref window) => {
+                window.swap_buffers().expect("failed to swap buffers")
+            }
+            SwapchainTarget::SharedWindow {
+                ref window,
+                ref main,
+            } => unsafe {
+                window.swap_buffers().expect("failed to swap buffers");
+                main.make_current().expect("failed to make context current");
+            },
+            SwapchainTarget::Framebuffer { .. } => {}
+        }
+        self.present_time += swap_start.elapsed();
     }
 
     fn cmd_set_graphics_pipeline(&mut self, pipeline: &'rcx GlGraphicsPipeline) {
         // switching pipelines
         self.current_pipeline = Some(pipeline);
         pipeline.bind(self.gl, self.state_cache);
+        if pipeline.rasterization_state.line_width == LineWidth::Dynamic {
+            self.state_cache
+                .set_line_width(self.gl, self.dynamic_line_width);
+        }
+    }
+
+    fn cmd_set_line_width(&mut self, width: f32) {
+        self.dynamic_line_width = width;
+        match self.current_pipeline {
+            Some(pipeline) if pipeline.rasterization_state.line_width == LineWidth::Dynamic => {
+                self.state_cache.set_line_width(self.gl, width)
+            }
+            _ => {}
+        }
+    }
+
+    fn cmd_set_compute_pipeline(&mut self, pipeline: &'rcx GlComputePipeline) {
+        pipeline.bind(self.gl, self.state_cache);
+    }
+
+    fn cmd_dispatch(&mut self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        unsafe {
+            self.gl
+                .DispatchCompute(group_count_x, group_count_y, group_count_z);
+            // make shader writes visible to all subsequent commands
+            self.gl.MemoryBarrier(gl::ALL_BARRIER_BITS);
+        }
+    }
+
+    fn cmd_barrier(&mut self, desc: &BarrierDesc) {
+        let barriers = memory_barrier_bits(desc.dst_access);
+        if barriers != 0 {
+            unsafe {
+                self.gl.MemoryBarrier(barriers);
+            }
+        }
+    }
+
+    fn cmd_dispatch_indirect(&mut self, buffer: &GlBuffer, offset: u64) {
+        unsafe {
+            self.gl
+                .BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, buffer.raw.obj);
+            self.gl
+                .DispatchComputeIndirect((buffer.offset as u64 + offset) as isize);
+            self.gl.MemoryBarrier(gl::ALL_BARRIER_BITS);
+        }
+    }
+
+    fn cmd_copy_buffer_to_host(&mut self, buffer: &GlBuffer, readback: &GlHostReadback) {
+        assert!(
+            readback.size <= buffer.raw.size - buffer.offset,
+            "readback destination is larger than the source buffer"
+        );
+        unsafe {
+            // blocks until the preceding commands writing to the buffer have completed
+            self.gl.GetNamedBufferSubData(
+                buffer.raw.obj,
+                buffer.offset as isize,
+                readback.size as isize,
+                readback.dest as *mut _,
+            );
+        }
+    }
+
+    fn cmd_copy_image_to_host(&mut self, image: &GlImage, readback: &GlHostReadback) {
+        let raw = image.raw();
+        unsafe {
+            let (width, height) = if raw.target == gl::RENDERBUFFER {
+                let mut width = 0;
+                let mut height = 0;
+                self.gl.GetNamedRenderbufferParameteriv(
+                    raw.obj,
+                    gl::RENDERBUFFER_WIDTH,
+                    &mut width,
+                );
+                self.gl.GetNamedRenderbufferParameteriv(
+                    raw.obj,
+                    gl::RENDERBUFFER_HEIGHT,
+                    &mut height,
+                );
+                (width as u32, height as u32)
+            } else {
+                let (width, height, _) =
+                    texture_level_size(self.gl, raw.obj, 0).expect("image has no storage");
+                (width, height)
+            };
+            assert_eq!(
+                readback.size,
+                4 * width as usize * height as usize,
+                "readback destination does not have the size of the image ({}x{} RGBA8 texels)",
+                width,
+                height
+            );
+
+            // read from a temporary framebuffer, as for presentation
+            let mut tmpfb = 0;
+            self.gl.CreateFramebuffers(1, &mut tmpfb);
+            if raw.target == gl::RENDERBUFFER {
+                self.gl.NamedFramebufferRenderbuffer(
+                    tmpfb,
+                    gl::COLOR_ATTACHMENT0,
+                    gl::RENDERBUFFER,
+                    raw.obj,
+                );
+            } else {
+                self.gl
+                    .NamedFramebufferTexture(tmpfb, gl::COLOR_ATTACHMENT0, raw.obj, 0);
+            }
+            self.gl
+                .NamedFramebufferReadBuffer(tmpfb, gl::COLOR_ATTACHMENT0);
+            // the read framebuffer binding is not tracked by the state cache
+            self.gl.BindFramebuffer(gl::READ_FRAMEBUFFER, tmpfb);
+            // blocks until the preceding commands writing to the image have completed
+            // (rows of RGBA8 texels are 4-byte aligned, as the default pack alignment)
+            self.gl.ReadnPixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                readback.size as i32,
+                readback.dest as *mut _,
+            );
+            self.gl.BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+            self.gl.DeleteFramebuffers(1, &tmpfb);
+        }
+    }
+
+    fn cmd_begin_query(&mut self, query: &GlQuery) {
+        unsafe {
+            self.gl.BeginQuery(query.target, query.obj);
+        }
+    }
+
+    fn cmd_end_query(&mut self, query: &GlQuery) {
+        unsafe {
+            self.gl.EndQuery(query.target);
+        }
+    }
+
+    fn cmd_begin_conditional(&mut self, predicate: Predicate<OpenGlBackend>) {
+        assert!(
+            self.conditional.is_none(),
+            "conditional rendering blocks cannot be nested"
+        );
+        match predicate {
+            Predicate::Query(query) => {
+                unsafe {
+                    self.gl.BeginConditionalRender(query.obj, gl::QUERY_WAIT);
+                }
+                self.conditional = Some(true);
+            }
+            Predicate::Buffer { .. } => {
+                // no buffer predicates in core GL: draw unconditionally
+                warn!("buffer predicates are not supported: rendering unconditionally");
+                self.conditional = Some(false);
+            }
+        }
+    }
+
+    fn cmd_end_conditional(&mut self) {
+        if let Some(true) = self.conditional.take() {
+            unsafe {
+                self.gl.EndConditionalRender();
+            }
+        }
+    }
+
+    fn cmd_begin_debug_group(&mut self, sortkey: u64, name: &'static str) {
+        unsafe {
+            self.gl.PushDebugGroup(
+                gl::DEBUG_SOURCE_APPLICATION,
+                0,
+                name.len() as i32,
+                name.as_ptr() as *const GLchar,
+            );
+        }
+        self.debug_group_depth += 1;
+        if let Some(ref mut report) = self.report {
+            report.begin_named_pass(sortkey, name);
+        }
+    }
+
+    fn cmd_end_debug_group(&mut self, sortkey: u64) {
+        // unbalanced groups (e.g. after an unstable sort) are ignored
+        if self.debug_group_depth == 0 {
+            return;
+        }
+        unsafe {
+            self.gl.PopDebugGroup();
+        }
+        self.debug_group_depth -= 1;
+        if let Some(ref mut report) = self.report {
+            // following commands are not part of the named pass
+            report.begin_pass(sortkey);
+        }
     }
 
     /*fn cmd_set_vertex_buffers(&mut self, buffers: &[&'rcx dyn traits::Buffer]) {
@@ -354,6 +935,36 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
         );
     }*/
 
+    /// Calls `draw` once, or once per view if the current pipeline renders to several views
+    /// and the views of the framebuffer are emulated (see `GlFramebuffer::view_objs`).
+    ///
+    /// View _i_ is drawn in the framebuffer of layer _i_, with viewport _i_ if there is one.
+    fn for_each_view(&mut self, mut draw: impl FnMut(&mut Self)) {
+        let view_count = self.current_pipeline.map_or(1, |p| p.view_count);
+        let fb = match self.current_framebuffer {
+            Some(fb) if view_count > 1 => unsafe { &*fb },
+            _ => return draw(self),
+        };
+        if fb.view_objs.is_empty() || self.state_cache.draw_framebuffer() != Some(fb.obj) {
+            return draw(self);
+        }
+
+        let viewports: smallvec::SmallVec<[Viewport; 4]> =
+            self.state_cache.viewports().iter().cloned().collect();
+        for (view, &obj) in fb.view_objs.iter().enumerate().take(view_count as usize) {
+            self.state_cache.set_draw_framebuffer(self.gl, obj);
+            if let Some(viewport) = viewports.get(view) {
+                self.state_cache
+                    .set_viewports(self.gl, slice::from_ref(viewport));
+            }
+            draw(self);
+        }
+        self.state_cache.set_draw_framebuffer(self.gl, fb.obj);
+        if !viewports.is_empty() {
+            self.state_cache.set_viewports(self.gl, &viewports);
+        }
+    }
+
     fn cmd_draw(
         &mut self,
         vertex_count: u32,
@@ -364,14 +975,17 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
         let pipeline = self
             .current_pipeline
             .expect("cmd_set_vertex_buffers called with no pipeline bound");
-        self.state_cache.draw(
-            self.gl,
-            pipeline.input_assembly_state.topology,
-            vertex_count,
-            instance_count,
-            first_vertex,
-            first_instance,
-        );
+        let topology = pipeline.input_assembly_state.topology;
+        self.for_each_view(|this| {
+            this.state_cache.draw(
+                this.gl,
+                topology,
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            )
+        });
     }
 
     fn cmd_draw_indexed(
@@ -385,35 +999,165 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
         let pipeline = self
             .current_pipeline
             .expect("cmd_set_vertex_buffers called with no pipeline bound");
-        self.state_cache.draw_indexed(
-            self.gl,
-            pipeline.input_assembly_state.topology,
-            index_count,
-            instance_count,
-            first_index,
-            vertex_offset,
-            first_instance,
-        );
+        let topology = pipeline.input_assembly_state.topology;
+        self.for_each_view(|this| {
+            this.state_cache.draw_indexed(
+                this.gl,
+                topology,
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            )
+        });
+    }
+
+    /// Submits the draw commands of a batch of draws sharing the same pipeline and arguments
+    /// (see `draw_batch_len`) with a single multi-draw indirect call.
+    fn cmd_draw_batch(&mut self, batch: &[Command<'rcx, OpenGlBackend>]) {
+        let pipeline = self
+            .current_pipeline
+            .expect("cmd_draw_batch called with no pipeline bound");
+        let topology = pipeline.input_assembly_state.topology;
+
+        match batch[2].cmd {
+            CommandInner::Draw { .. } => {
+                let commands: Vec<_> = batch
+                    .chunks(3)
+                    .map(|c| match c[2].cmd {
+                        CommandInner::Draw {
+                            vertex_count,
+                            instance_count,
+                            first_vertex,
+                            first_instance,
+                        } => DrawArraysIndirectCommand {
+                            count: vertex_count,
+                            instance_count,
+                            first: first_vertex,
+                            base_instance: first_instance,
+                        },
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                self.for_each_view(|this| {
+                    this.state_cache.multi_draw(this.gl, topology, &commands)
+                });
+            }
+            CommandInner::DrawIndexed { .. } => {
+                let commands: Vec<_> = batch
+                    .chunks(3)
+                    .map(|c| match c[2].cmd {
+                        CommandInner::DrawIndexed {
+                            index_count,
+                            instance_count,
+                            first_index,
+                            vertex_offset,
+                            first_instance,
+                        } => DrawElementsIndirectCommand {
+                            count: index_count,
+                            instance_count,
+                            first_index,
+                            base_vertex: vertex_offset,
+                            base_instance: first_instance,
+                        },
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                self.for_each_view(|this| {
+                    // the commands are modified in place by `multi_draw_indexed`
+                    let mut commands = commands.clone();
+                    this.state_cache
+                        .multi_draw_indexed(this.gl, topology, &mut commands)
+                });
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Submits a sequence of commands, merging consecutive draws that share the same pipeline
+    /// and arguments into multi-draw calls if batching is enabled.
+    pub unsafe fn submit_commands(&mut self, commands: &[Command<'rcx, OpenGlBackend>]) {
+        let mut i = 0;
+        while i < commands.len() {
+            self.apply_initial_clears(commands[i].sortkey);
+            if self.batch_draws {
+                let n = draw_batch_len(&commands[i..]);
+                if n > 1 {
+                    // header and arguments are the same for all draws in the batch
+                    let first_use = self.image_uses.as_ref().map(|uses| uses.len());
+                    self.submit_command(&commands[i]);
+                    self.submit_command(&commands[i + 1]);
+                    if let (Some(first_use), Some(uses)) = (first_use, self.image_uses.as_mut()) {
+                        // the images bound by the arguments are used until the last draw
+                        let last = commands[i + 3 * n - 1].sortkey;
+                        for k in first_use..uses.len() {
+                            let obj = uses[k].0;
+                            uses.push((obj, last));
+                        }
+                    }
+                    let framebuffer = self.state_cache.draw_framebuffer();
+                    let issued = self.state_cache.issued();
+                    self.cmd_draw_batch(&commands[i..i + 3 * n]);
+                    self.record(commands[i].sortkey, framebuffer, issued, n as u32, 0);
+                    i += 3 * n;
+                    continue;
+                }
+            }
+            self.submit_command(&commands[i]);
+            i += 1;
+        }
+        while self.debug_group_depth > 0 {
+            self.gl.PopDebugGroup();
+            self.debug_group_depth -= 1;
+        }
+        self.apply_store_ops();
     }
 
     pub unsafe fn submit_command(&mut self, command: &Command<'rcx, OpenGlBackend>) {
+        self.sortkey = command.sortkey;
+        let framebuffer = self.state_cache.draw_framebuffer();
+        let issued = self.state_cache.issued();
+        let (draws, dispatches) = match command.cmd {
+            CommandInner::Draw { .. } | CommandInner::DrawIndexed { .. } => (1, 0),
+            CommandInner::Dispatch { .. } | CommandInner::DispatchIndirect { .. } => (0, 1),
+            _ => (0, 0),
+        };
+        self.execute_command(command);
+        self.record(command.sortkey, framebuffer, issued, draws, dispatches);
+    }
+
+    unsafe fn execute_command(&mut self, command: &Command<'rcx, OpenGlBackend>) {
         match command.cmd {
-            CommandInner::PipelineBarrier {} => {
-                // no-op on GL
+            CommandInner::PipelineBarrier { desc } => {
+                self.cmd_barrier(&desc);
             }
             CommandInner::ClearImageFloat { image, color } => {
-                self.cmd_clear_image_float(image, &color);
+                self.record_image_uses(&[image.raw().obj]);
+                self.cmd_clear_image(image, ClearColor::Float(color));
+            }
+            CommandInner::ClearImageUint { image, color } => {
+                self.record_image_uses(&[image.raw().obj]);
+                self.cmd_clear_image(image, ClearColor::Uint(color));
+            }
+            CommandInner::ClearImageInt { image, color } => {
+                self.record_image_uses(&[image.raw().obj]);
+                self.cmd_clear_image(image, ClearColor::Int(color));
             }
             CommandInner::ClearDepthStencilImage {
                 image,
                 depth,
                 stencil,
             } => {
+                self.record_image_uses(&[image.raw().obj]);
                 self.cmd_clear_depth_stencil_image(image, depth, stencil);
             }
             CommandInner::SetPipelineArguments { arguments } => {
                 self.cmd_set_pipeline_arguments(arguments, &mut BaseSlots::default(), true);
             }
+            CommandInner::SetLineWidth { width } => {
+                self.cmd_set_line_width(width);
+            }
             /*CommandInner::SetDescriptorSets {
                 ref descriptor_sets,
             } => {
@@ -432,6 +1176,9 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
             CommandInner::DrawHeader { pipeline } => {
                 self.cmd_set_graphics_pipeline(pipeline);
             }
+            CommandInner::DispatchHeader { pipeline } => {
+                self.cmd_set_compute_pipeline(pipeline);
+            }
             /*CommandInner::SetScissors { .. } => {}
             //CommandInner::SetAllScissors { scissor } => {}
             CommandInner::SetViewports { ref viewports } => {
@@ -460,9 +1207,158 @@ impl<'a, 'rcx> SubmissionContext<'a, 'rcx> {
                 vertex_offset,
                 first_instance,
             ),
-            CommandInner::Present { image, swapchain } => {
-                self.cmd_present(image, swapchain);
+            CommandInner::Dispatch {
+                group_count_x,
+                group_count_y,
+                group_count_z,
+            } => self.cmd_dispatch(group_count_x, group_count_y, group_count_z),
+            CommandInner::DispatchIndirect { buffer, offset } => {
+                self.cmd_dispatch_indirect(buffer, offset)
             }
+            CommandInner::CopyBufferToHost { buffer, readback } => {
+                self.cmd_copy_buffer_to_host(buffer, readback);
+            }
+            CommandInner::CopyImageToHost { image, readback } => {
+                self.record_image_uses(&[image.raw().obj]);
+                self.cmd_copy_image_to_host(image, readback);
+            }
+            CommandInner::Present {
+                image,
+                swapchain,
+                encoding,
+            } => {
+                self.record_image_uses(&[image.raw().obj]);
+                self.cmd_present(image, swapchain, encoding);
+            }
+            CommandInner::BeginQuery { query } => self.cmd_begin_query(query),
+            CommandInner::EndQuery { query } => self.cmd_end_query(query),
+            CommandInner::BeginConditional { predicate } => self.cmd_begin_conditional(predicate),
+            CommandInner::EndConditional {} => self.cmd_end_conditional(),
+            CommandInner::BeginDebugGroup { name } => {
+                self.cmd_begin_debug_group(command.sortkey, name)
+            }
+            CommandInner::EndDebugGroup {} => self.cmd_end_debug_group(command.sortkey),
         }
     }
 }
+
+/// Returns the number of consecutive draws at the start of `commands` that can be merged
+/// into a single multi-draw call.
+///
+/// A draw is a sequence of `DrawHeader`, `SetPipelineArguments` and `Draw` (or `DrawIndexed`)
+/// commands. Draws can be merged if they have the same pipeline, the same argument block,
+/// and are of the same kind (indexed or non-indexed).
+/// Converts access flags to the corresponding `glMemoryBarrier` bits.
+fn memory_barrier_bits(access: AccessFlags) -> GLbitfield {
+    if access.contains(AccessFlags::ALL) {
+        return gl::ALL_BARRIER_BITS;
+    }
+    let mut bits = 0;
+    let mapping = [
+        (
+            AccessFlags::VERTEX_ATTRIBUTE_READ,
+            gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT,
+        ),
+        (AccessFlags::INDEX_READ, gl::ELEMENT_ARRAY_BARRIER_BIT),
+        (AccessFlags::UNIFORM_READ, gl::UNIFORM_BARRIER_BIT),
+        (AccessFlags::TEXTURE_READ, gl::TEXTURE_FETCH_BARRIER_BIT),
+        (
+            AccessFlags::STORAGE_IMAGE,
+            gl::SHADER_IMAGE_ACCESS_BARRIER_BIT,
+        ),
+        (AccessFlags::STORAGE_BUFFER, gl::SHADER_STORAGE_BARRIER_BIT),
+        (AccessFlags::INDIRECT_COMMAND_READ, gl::COMMAND_BARRIER_BIT),
+        (
+            AccessFlags::TRANSFER,
+            gl::BUFFER_UPDATE_BARRIER_BIT
+                | gl::TEXTURE_UPDATE_BARRIER_BIT
+                | gl::PIXEL_BUFFER_BARRIER_BIT,
+        ),
+        (AccessFlags::RENDER_TARGET, gl::FRAMEBUFFER_BARRIER_BIT),
+    ];
+    for &(flag, bit) in mapping.iter() {
+        if access.contains(flag) {
+            bits |= bit;
+        }
+    }
+    bits
+}
+
+/// Returns the offset and format of the index buffer bound by an argument block or the blocks it
+/// inherits, if any.
+fn index_buffer_binding(args: &GlArgumentBlock) -> Option<(usize, IndexFormat)> {
+    let sig = unsafe { &*args.signature };
+    let blocks = unsafe { slice::from_raw_parts(args.blocks, sig.num_state_blocks) };
+    let mut binding = None;
+    for sb in blocks {
+        match sb {
+            &StateBlock::Inherited(args) => {
+                let args = unsafe { slice::from_raw_parts(args, sig.inherited.len()) };
+                for &a in args {
+                    binding = index_buffer_binding(unsafe { &*a }).or(binding);
+                }
+            }
+            &StateBlock::IndexBuffer { format, offset, .. } => binding = Some((offset, format)),
+            _ => {}
+        }
+    }
+    binding
+}
+
+fn draw_batch_len(commands: &[Command<OpenGlBackend>]) -> usize {
+    fn draw<'a, 'b>(
+        commands: &'b [Command<'a, OpenGlBackend>],
+    ) -> Option<(
+        &'a GlGraphicsPipeline,
+        &'a GlArgumentBlock,
+        &'b CommandInner<'a, OpenGlBackend>,
+    )> {
+        if commands.len() < 3 {
+            return None;
+        }
+        let draw = &commands[2].cmd;
+        match draw {
+            CommandInner::Draw { .. } | CommandInner::DrawIndexed { .. } => {}
+            _ => return None,
+        }
+        match (&commands[0].cmd, &commands[1].cmd) {
+            (
+                &CommandInner::DrawHeader { pipeline },
+                &CommandInner::SetPipelineArguments { arguments },
+            ) => Some((pipeline, arguments, draw)),
+            _ => None,
+        }
+    }
+
+    let (pipeline, arguments, first_draw) = if let Some(d) = draw(commands) {
+        d
+    } else {
+        return 0;
+    };
+
+    if let CommandInner::DrawIndexed { .. } = first_draw {
+        // indirect draws have no byte offset into the index buffer: it is folded into the
+        // `first_index` of the draws, which requires an offset aligned to the size of an index
+        let aligned = match index_buffer_binding(arguments) {
+            Some((offset, IndexFormat::U16)) => offset % 2 == 0,
+            Some((offset, IndexFormat::U32)) => offset % 4 == 0,
+            // bound by previous arguments: the offset is not known here
+            None => false,
+        };
+        if !aligned {
+            return 1;
+        }
+    }
+
+    commands
+        .chunks(3)
+        .take_while(|c| match draw(c) {
+            Some((p, a, d)) => {
+                ptr::eq(p, pipeline)
+                    && ptr::eq(a, arguments)
+                    && mem::discriminant(d) == mem::discriminant(first_draw)
+            }
+            None => false,
+        })
+        .count()
+}
@@ -5,12 +5,94 @@ use crate::{
 };
 use autograph_api::{
     pipeline::{
-        BlendFactor, BlendOp, ColorBlendAttachmentState, CompareOp, CullModeFlags, PolygonMode,
-        PrimitiveTopology, Scissor, StencilOp, StencilOpState, StencilTest, Viewport,
+        BlendFactor, BlendOp, ColorBlendAttachmentState, CompareOp, CullModeFlags,
+        MultisampleState, PointSize, PolygonMode, PrimitiveTopology, SampleShading, Scissor,
+        StencilOp, StencilOpState, StencilTest, Viewport,
     },
     vertex::IndexFormat,
 };
 use ordered_float::NotNan;
+use std::{mem, ops::Range, ptr};
+
+/// Number of binding operations issued to the driver and skipped by the state cache.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct BindCounter {
+    pub issued: u64,
+    pub skipped: u64,
+}
+
+/// Binding statistics collected by the state cache during a frame.
+///
+/// Indexed bindings (textures, samplers, buffers...) are counted per slot.
+/// Only collected if `InstanceConfig::debug_counters` is set.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StateCacheCounters {
+    pub programs: BindCounter,
+    pub vertex_arrays: BindCounter,
+    pub framebuffers: BindCounter,
+    pub textures: BindCounter,
+    pub samplers: BindCounter,
+    pub images: BindCounter,
+    pub uniform_buffers: BindCounter,
+    pub shader_storage_buffers: BindCounter,
+    pub vertex_buffers: BindCounter,
+    pub index_buffers: BindCounter,
+}
+
+/// Parameters of a non-indexed indirect draw, as expected by `glMultiDrawArraysIndirect`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct DrawArraysIndirectCommand {
+    pub count: u32,
+    pub instance_count: u32,
+    pub first: u32,
+    pub base_instance: u32,
+}
+
+/// Parameters of an indexed indirect draw, as expected by `glMultiDrawElementsIndirect`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct DrawElementsIndirectCommand {
+    pub count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    pub base_instance: u32,
+}
+
+/// Cached bindings of an indexed binding point (texture units, buffer binding slots...).
+struct SlotCache<T> {
+    slots: Vec<Option<T>>,
+}
+
+impl<T: Copy + Eq> SlotCache<T> {
+    fn new() -> SlotCache<T> {
+        SlotCache { slots: Vec::new() }
+    }
+
+    /// Updates the cached bindings of slots `first..first+len` with the bindings returned by `f`
+    /// and returns the range (relative to `first`) of the slots that changed, if any.
+    fn update<F: Fn(usize) -> T>(
+        &mut self,
+        first: usize,
+        len: usize,
+        f: F,
+    ) -> Option<Range<usize>> {
+        if self.slots.len() < first + len {
+            self.slots.resize(first + len, None);
+        }
+
+        let mut dirty: Option<Range<usize>> = None;
+        for (i, slot) in self.slots[first..first + len].iter_mut().enumerate() {
+            let binding = f(i);
+            if *slot != Some(binding) {
+                *slot = Some(binding);
+                dirty = Some(dirty.map_or(i..i + 1, |r| r.start..i + 1));
+            }
+        }
+        dirty
+    }
+}
 
 pub struct ColorBlendCache {
     all: bool,
@@ -25,6 +107,11 @@ pub struct StateCache {
     cull_enable: Option<bool>,
     cull_mode: Option<CullModeFlags>,
     polygon_mode: Option<PolygonMode>,
+    line_width: Option<NotNan<f32>>,
+    line_smooth_enabled: Option<bool>,
+    point_size: Option<PointSize>,
+    primitive_restart_enabled: Option<bool>,
+    multisample: Option<MultisampleState>,
     //front_face: Option<GLenum>,
     program: Option<GLuint>,
     vertex_array: Option<GLuint>,
@@ -38,13 +125,28 @@ pub struct StateCache {
     depth_write_enabled: Option<bool>,
     depth_compare_op: Option<CompareOp>,
     //depth_bounds_test: Option<DepthBoundTest>,
+    color_write_enabled: Option<bool>,
     blend: Option<ColorBlendCache>,
     viewports: Option<Vec<Viewport>>,
+    /// Number of viewports set by the last call to `set_viewports`.
+    viewport_count: usize,
     scissors: Option<Vec<Scissor>>,
     //viewports: Option<(Vec<ViewportEntry>, Vec<DepthRangeEntry>)>,
     index_buffer: Option<GLuint>,
     index_buffer_offset: Option<usize>,
     index_buffer_type: Option<GLenum>,
+    textures: SlotCache<GLuint>,
+    samplers: SlotCache<GLuint>,
+    images: SlotCache<GLuint>,
+    uniform_buffers: SlotCache<(GLuint, GLintptr, GLintptr)>,
+    shader_storage_buffers: SlotCache<(GLuint, GLintptr, GLintptr)>,
+    vertex_buffers: SlotCache<(GLuint, GLintptr, GLsizei)>,
+    counters: Option<StateCacheCounters>,
+    /// Total number of binding operations issued, always tracked (for frame reports).
+    issued: u64,
+    /// Buffer object holding the parameters of batched draws. Created on first use, kept across
+    /// invalidations, and deleted by `delete_objects`.
+    indirect_buffer: Option<GLuint>,
     /*textures: Option<Vec<GLuint>>,
     samplers: Option<Vec<GLuint>>,
     images: Option<Vec<GLuint>>,
@@ -59,7 +161,10 @@ pub struct StateCache {
 fn topology_to_gl(topo: PrimitiveTopology) -> GLenum {
     match topo {
         PrimitiveTopology::TriangleList => gl::TRIANGLES,
+        PrimitiveTopology::TriangleStrip => gl::TRIANGLE_STRIP,
+        PrimitiveTopology::TriangleFan => gl::TRIANGLE_FAN,
         PrimitiveTopology::LineList => gl::LINES,
+        PrimitiveTopology::LineStrip => gl::LINE_STRIP,
         PrimitiveTopology::PointList => gl::POINTS,
     }
 }
@@ -125,14 +230,18 @@ fn blend_op_to_gl(op: BlendOp) -> GLenum {
 }
 
 trait CacheOptionExt<T: Eq> {
-    fn update_cached<F: FnOnce()>(&mut self, new: T, f: F);
+    /// Calls `f` if the cached value is different from `new`. Returns whether `f` was called.
+    fn update_cached<F: FnOnce()>(&mut self, new: T, f: F) -> bool;
 }
 
 impl<T: Eq> CacheOptionExt<T> for Option<T> {
-    fn update_cached<F: FnOnce()>(&mut self, new: T, f: F) {
+    fn update_cached<F: FnOnce()>(&mut self, new: T, f: F) -> bool {
         if self.as_ref().map_or(true, |v| *v != new) {
             self.replace(new);
             f();
+            true
+        } else {
+            false
         }
     }
 }
@@ -164,23 +273,24 @@ fn update_cached_slice<T: Copy + Eq>(
     let len = new.len();
     assert!(first + len <= max);
     if let Some(cur) = cached.as_mut() {
-        let len = new.len();
-        if new != &cur[first..len] {
-            (&mut cur[first..len]).copy_from_slice(new);
+        if new != &cur[first..first + len] {
+            (&mut cur[first..first + len]).copy_from_slice(new);
             true
         } else {
             false
         }
     } else {
         let mut v = vec![default; max];
-        (&mut v[first..len]).copy_from_slice(new);
+        (&mut v[first..first + len]).copy_from_slice(new);
         *cached = Some(v);
         true
     }
 }
 
 impl StateCache {
-    pub fn new(params: &ImplementationParameters) -> StateCache {
+    /// Creates a new state cache. If `debug_counters` is true, the cache keeps track of the
+    /// number of binding operations issued and skipped (see `take_counters`).
+    pub fn new(params: &ImplementationParameters, debug_counters: bool) -> StateCache {
         StateCache {
             max_draw_buffers: params.max_draw_buffers as usize,
             _max_color_attachments: params.max_color_attachments as usize,
@@ -188,6 +298,11 @@ impl StateCache {
             cull_enable: None,
             cull_mode: None,
             polygon_mode: None,
+            line_width: None,
+            line_smooth_enabled: None,
+            point_size: None,
+            primitive_restart_enabled: None,
+            multisample: None,
             //front_face: None,
             program: None,
             vertex_array: None,
@@ -199,12 +314,27 @@ impl StateCache {
             depth_write_enabled: None,
             depth_compare_op: None,
             //depth_bounds_test: None,
+            color_write_enabled: None,
             blend: None,
             viewports: None,
+            viewport_count: 0,
             scissors: None,
             index_buffer: None,
             index_buffer_offset: None,
             index_buffer_type: None,
+            textures: SlotCache::new(),
+            samplers: SlotCache::new(),
+            images: SlotCache::new(),
+            uniform_buffers: SlotCache::new(),
+            shader_storage_buffers: SlotCache::new(),
+            vertex_buffers: SlotCache::new(),
+            counters: if debug_counters {
+                Some(StateCacheCounters::default())
+            } else {
+                None
+            },
+            issued: 0,
+            indirect_buffer: None,
             /*textures: None,
             samplers: None,
             images: None,
@@ -217,6 +347,7 @@ impl StateCache {
         }
     }
 
+    /// Forgets all cached state. Does not reset the counters.
     pub fn invalidate(&mut self) {
         *self = StateCache {
             max_draw_buffers: self.max_draw_buffers,
@@ -225,6 +356,11 @@ impl StateCache {
             cull_enable: None,
             cull_mode: None,
             polygon_mode: None,
+            line_width: None,
+            line_smooth_enabled: None,
+            point_size: None,
+            primitive_restart_enabled: None,
+            multisample: None,
             //front_face: None,
             program: None,
             vertex_array: None,
@@ -236,12 +372,23 @@ impl StateCache {
             depth_write_enabled: None,
             depth_compare_op: None,
             //depth_bounds_test: None,
+            color_write_enabled: None,
             blend: None,
             viewports: None,
+            viewport_count: 0,
             scissors: None,
             index_buffer: None,
             index_buffer_offset: None,
             index_buffer_type: None,
+            textures: SlotCache::new(),
+            samplers: SlotCache::new(),
+            images: SlotCache::new(),
+            uniform_buffers: SlotCache::new(),
+            shader_storage_buffers: SlotCache::new(),
+            vertex_buffers: SlotCache::new(),
+            counters: self.counters.take(),
+            issued: self.issued,
+            indirect_buffer: self.indirect_buffer,
             /*textures: None,
             samplers: None,
             images: None,
@@ -254,22 +401,90 @@ impl StateCache {
         };
     }
 
+    /// Deletes the objects owned by the cache. The cache is still usable afterwards: they are
+    /// created again on first use.
+    pub fn delete_objects(&mut self, gl: &Gl) {
+        if let Some(buffer) = self.indirect_buffer.take() {
+            unsafe {
+                gl.DeleteBuffers(1, &buffer);
+            }
+        }
+    }
+
+    /// Returns the counters accumulated since the last call and resets them.
+    ///
+    /// Returns `None` if the cache was not created with `debug_counters`.
+    pub fn take_counters(&mut self) -> Option<StateCacheCounters> {
+        self.counters
+            .as_mut()
+            .map(|c| mem::replace(c, StateCacheCounters::default()))
+    }
+
+    /// Returns the total number of binding operations issued since the creation of the cache.
+    pub fn issued(&self) -> u64 {
+        self.issued
+    }
+
+    /// Returns the currently bound draw framebuffer, if known.
+    pub fn draw_framebuffer(&self) -> Option<GLuint> {
+        self.framebuffer
+    }
+
+    /// Returns the viewports set by the last call to `set_viewports`.
+    pub fn viewports(&self) -> &[Viewport] {
+        match self.viewports {
+            Some(ref viewports) => &viewports[..self.viewport_count],
+            None => &[],
+        }
+    }
+
+    /// Returns the cached depth write mask, if known.
+    pub fn depth_write_enabled(&self) -> Option<bool> {
+        self.depth_write_enabled
+    }
+
+    pub fn color_write_enabled(&self) -> Option<bool> {
+        self.color_write_enabled
+    }
+
+    fn count(
+        &mut self,
+        counter: fn(&mut StateCacheCounters) -> &mut BindCounter,
+        issued: usize,
+        total: usize,
+    ) {
+        self.issued += issued as u64;
+        if let Some(ref mut counters) = self.counters {
+            let c = counter(counters);
+            c.issued += issued as u64;
+            c.skipped += (total - issued) as u64;
+        }
+    }
+
     pub fn set_program(&mut self, gl: &Gl, program: GLuint) {
-        self.program.update_cached(program, || unsafe {
+        let issued = self.program.update_cached(program, || unsafe {
             gl.UseProgram(program);
         });
+        self.count(|c| &mut c.programs, issued as usize, 1);
     }
 
     pub fn set_vertex_array(&mut self, gl: &Gl, vertex_array: GLuint) {
-        self.vertex_array.update_cached(vertex_array, || unsafe {
+        let issued = self.vertex_array.update_cached(vertex_array, || unsafe {
             gl.BindVertexArray(vertex_array);
         });
+        if issued {
+            // vertex buffer and index buffer bindings are part of the VAO state
+            self.vertex_buffers = SlotCache::new();
+            self.index_buffer = None;
+        }
+        self.count(|c| &mut c.vertex_arrays, issued as usize, 1);
     }
 
     pub fn set_draw_framebuffer(&mut self, gl: &Gl, framebuffer: GLuint) {
-        self.framebuffer.update_cached(framebuffer, || unsafe {
+        let issued = self.framebuffer.update_cached(framebuffer, || unsafe {
             gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, framebuffer);
         });
+        self.count(|c| &mut c.framebuffers, issued as usize, 1);
     }
 
     pub fn set_all_blend(&mut self, gl: &Gl, state: &ColorBlendAttachmentState) {
@@ -373,6 +588,7 @@ impl StateCache {
                 max_depth: 0.0.into(),
             },
         );
+        self.viewport_count = viewports.len();
 
         if do_update {
             let mut gl_viewports: Vec<ViewportEntry> = Vec::with_capacity(self.max_viewports);
@@ -503,7 +719,7 @@ impl StateCache {
                 } else {
                     gl.Disable(gl::DEPTH_TEST);
                 }
-            })
+            });
     }
 
     pub fn set_depth_write_enable(&mut self, gl: &Gl, depth_write_enable: bool) {
@@ -514,16 +730,30 @@ impl StateCache {
                 } else {
                     gl.DepthMask(gl::FALSE);
                 }
-            })
+            });
     }
 
     //pub fn set_depth_bounds_test(&mut self, )
 
+    /// Enables or disables writes to all components of all color attachments
+    /// (disabled for pipelines without a fragment shader).
+    pub fn set_color_write_enable(&mut self, gl: &Gl, color_write_enable: bool) {
+        self.color_write_enabled
+            .update_cached(color_write_enable, || unsafe {
+                let mask = if color_write_enable {
+                    gl::TRUE
+                } else {
+                    gl::FALSE
+                };
+                gl.ColorMask(mask, mask, mask, mask);
+            });
+    }
+
     pub fn set_depth_compare_op(&mut self, gl: &Gl, depth_compare_op: CompareOp) {
         self.depth_compare_op
             .update_cached(depth_compare_op, || unsafe {
                 gl.DepthFunc(compare_op_to_gl(depth_compare_op));
-            })
+            });
     }
 
     fn set_cull_enable(&mut self, gl: &Gl, cull_enable: bool) {
@@ -559,7 +789,85 @@ impl StateCache {
             match polygon_mode {
                 PolygonMode::Fill => gl.PolygonMode(gl::FRONT_AND_BACK, gl::FILL),
                 PolygonMode::Line => gl.PolygonMode(gl::FRONT_AND_BACK, gl::LINE),
+                PolygonMode::Point => gl.PolygonMode(gl::FRONT_AND_BACK, gl::POINT),
+            }
+        });
+    }
+
+    pub fn set_line_width(&mut self, gl: &Gl, width: f32) {
+        let width = NotNan::new(width).expect("line width is NaN");
+        self.line_width.update_cached(width, || unsafe {
+            gl.LineWidth(width.into_inner());
+        });
+    }
+
+    pub fn set_line_smooth_enable(&mut self, gl: &Gl, enabled: bool) {
+        self.line_smooth_enabled.update_cached(enabled, || unsafe {
+            if enabled {
+                gl.Enable(gl::LINE_SMOOTH);
+            } else {
+                gl.Disable(gl::LINE_SMOOTH);
+            }
+        });
+    }
+
+    pub fn set_point_size(&mut self, gl: &Gl, point_size: PointSize) {
+        self.point_size.update_cached(point_size, || unsafe {
+            match point_size {
+                PointSize::Static(size) => {
+                    gl.Disable(gl::PROGRAM_POINT_SIZE);
+                    gl.PointSize(size.into_inner());
+                }
+                PointSize::Program => gl.Enable(gl::PROGRAM_POINT_SIZE),
+            }
+        });
+    }
+
+    /// Primitive restart uses the maximum value of the index type as the restart index.
+    pub fn set_primitive_restart_enable(&mut self, gl: &Gl, enabled: bool) {
+        self.primitive_restart_enabled
+            .update_cached(enabled, || unsafe {
+                if enabled {
+                    gl.Enable(gl::PRIMITIVE_RESTART_FIXED_INDEX);
+                } else {
+                    gl.Disable(gl::PRIMITIVE_RESTART_FIXED_INDEX);
+                }
+            });
+    }
+
+    /// Sets the sample mask, sample shading, and alpha-to-coverage states.
+    ///
+    /// Only the 32 first bits of the sample mask are used (`GL_MAX_SAMPLE_MASK_WORDS` is 1 on
+    /// common implementations).
+    pub fn set_multisample_state(&mut self, gl: &Gl, state: &MultisampleState) {
+        let state = *state;
+        self.multisample.update_cached(state, || unsafe {
+            let enable = |cap, enabled| {
+                if enabled {
+                    gl.Enable(cap);
+                } else {
+                    gl.Disable(cap);
+                }
+            };
+
+            let mask = state.effective_sample_mask();
+            let all_samples = state.rasterization_samples >= 64
+                || mask == (1u64 << state.rasterization_samples) - 1;
+            enable(gl::SAMPLE_MASK, !all_samples);
+            if !all_samples {
+                gl.SampleMaski(0, mask as u32);
             }
+
+            match state.sample_shading {
+                SampleShading::Enabled { min_sample_shading } => {
+                    enable(gl::SAMPLE_SHADING, true);
+                    gl.MinSampleShading(min_sample_shading.into_inner());
+                }
+                SampleShading::Disabled => enable(gl::SAMPLE_SHADING, false),
+            }
+
+            enable(gl::SAMPLE_ALPHA_TO_COVERAGE, state.alpha_to_coverage_enable);
+            enable(gl::SAMPLE_ALPHA_TO_ONE, state.alpha_to_one_enable);
         });
     }
 
@@ -609,6 +917,27 @@ impl StateCache {
         }
     }
 
+    /// Same as `set_stencil_test`, but replaces the reference value of the stencil operations
+    /// (for pipelines with a dynamic stencil reference).
+    pub fn set_stencil_test_with_reference(
+        &mut self,
+        gl: &Gl,
+        stencil_test: &StencilTest,
+        reference: u32,
+    ) {
+        match *stencil_test {
+            StencilTest::Disabled => self.set_stencil_test_enabled(gl, false),
+            StencilTest::Enabled { front, back } => {
+                self.set_stencil_test_enabled(gl, true);
+                self.set_stencil_op(
+                    gl,
+                    &StencilOpState { reference, ..front },
+                    &StencilOpState { reference, ..back },
+                );
+            }
+        }
+    }
+
     pub fn set_uniform_buffers(
         &mut self,
         gl: &Gl,
@@ -617,21 +946,27 @@ impl StateCache {
         buffer_offsets: &[GLintptr],
         buffer_sizes: &[GLintptr],
     ) {
-        // passthrough, for now
-        // may do a comparison, or a quick diff in the future
-        unsafe {
-            let count = buffers.len();
-            if count != 0 {
+        let count = buffers.len();
+        let dirty = self.uniform_buffers.update(first, count, |i| {
+            (buffers[i], buffer_offsets[i], buffer_sizes[i])
+        });
+        if let Some(ref r) = dirty {
+            unsafe {
                 gl.BindBuffersRange(
                     gl::UNIFORM_BUFFER,
-                    first as u32,
-                    count as i32,
-                    buffers.as_ptr(),
-                    buffer_offsets.as_ptr(),
-                    buffer_sizes.as_ptr(),
+                    (first + r.start) as u32,
+                    r.len() as i32,
+                    buffers[r.clone()].as_ptr(),
+                    buffer_offsets[r.clone()].as_ptr(),
+                    buffer_sizes[r.clone()].as_ptr(),
                 );
             }
         }
+        self.count(
+            |c| &mut c.uniform_buffers,
+            dirty.map_or(0, |r| r.len()),
+            count,
+        );
     }
 
     pub fn set_shader_storage_buffers(
@@ -642,39 +977,72 @@ impl StateCache {
         buffer_offsets: &[GLintptr],
         buffer_sizes: &[GLintptr],
     ) {
-        // passthrough, for now
-        // may do a comparison, or a quick diff in the future
-        unsafe {
-            let count = buffers.len();
-            if count != 0 {
+        let count = buffers.len();
+        let dirty = self.shader_storage_buffers.update(first, count, |i| {
+            (buffers[i], buffer_offsets[i], buffer_sizes[i])
+        });
+        if let Some(ref r) = dirty {
+            unsafe {
                 gl.BindBuffersRange(
                     gl::SHADER_STORAGE_BUFFER,
-                    first as u32,
-                    count as i32,
-                    buffers.as_ptr(),
-                    buffer_offsets.as_ptr(),
-                    buffer_sizes.as_ptr(),
+                    (first + r.start) as u32,
+                    r.len() as i32,
+                    buffers[r.clone()].as_ptr(),
+                    buffer_offsets[r.clone()].as_ptr(),
+                    buffer_sizes[r.clone()].as_ptr(),
                 );
             }
         }
+        self.count(
+            |c| &mut c.shader_storage_buffers,
+            dirty.map_or(0, |r| r.len()),
+            count,
+        );
     }
 
     pub fn set_samplers(&mut self, gl: &Gl, first: usize, samplers: &[GLuint]) {
-        // passthrough, for now
-        // may do a comparison, or a quick diff in the future
-        unsafe { gl.BindSamplers(first as u32, samplers.len() as i32, samplers.as_ptr()) }
+        let count = samplers.len();
+        let dirty = self.samplers.update(first, count, |i| samplers[i]);
+        if let Some(ref r) = dirty {
+            unsafe {
+                gl.BindSamplers(
+                    (first + r.start) as u32,
+                    r.len() as i32,
+                    samplers[r.clone()].as_ptr(),
+                )
+            }
+        }
+        self.count(|c| &mut c.samplers, dirty.map_or(0, |r| r.len()), count);
     }
 
     pub fn set_textures(&mut self, gl: &Gl, first: usize, textures: &[GLuint]) {
-        // passthrough, for now
-        // may do a comparison, or a quick diff in the future
-        unsafe { gl.BindTextures(first as u32, textures.len() as i32, textures.as_ptr()) }
+        let count = textures.len();
+        let dirty = self.textures.update(first, count, |i| textures[i]);
+        if let Some(ref r) = dirty {
+            unsafe {
+                gl.BindTextures(
+                    (first + r.start) as u32,
+                    r.len() as i32,
+                    textures[r.clone()].as_ptr(),
+                )
+            }
+        }
+        self.count(|c| &mut c.textures, dirty.map_or(0, |r| r.len()), count);
     }
 
     pub fn set_images(&mut self, gl: &Gl, first: usize, images: &[GLuint]) {
-        // passthrough, for now
-        // may do a comparison, or a quick diff in the future
-        unsafe { gl.BindImageTextures(first as u32, images.len() as i32, images.as_ptr()) }
+        let count = images.len();
+        let dirty = self.images.update(first, count, |i| images[i]);
+        if let Some(ref r) = dirty {
+            unsafe {
+                gl.BindImageTextures(
+                    (first + r.start) as u32,
+                    r.len() as i32,
+                    images[r.clone()].as_ptr(),
+                )
+            }
+        }
+        self.count(|c| &mut c.images, dirty.map_or(0, |r| r.len()), count);
     }
 
     pub fn set_vertex_buffers(
@@ -685,26 +1053,33 @@ impl StateCache {
         buffer_offsets: &[GLintptr],
         buffer_strides: &[GLsizei],
     ) {
-        // passthrough, for now
-        // may do a comparison, or a quick diff in the future
-        unsafe {
-            let count = buffers.len();
-            if count != 0 {
+        let count = buffers.len();
+        let dirty = self.vertex_buffers.update(first, count, |i| {
+            (buffers[i], buffer_offsets[i], buffer_strides[i])
+        });
+        if let Some(ref r) = dirty {
+            unsafe {
                 gl.BindVertexBuffers(
-                    first as u32,
-                    count as i32,
-                    buffers.as_ptr(),
-                    buffer_offsets.as_ptr(),
-                    buffer_strides.as_ptr(),
+                    (first + r.start) as u32,
+                    r.len() as i32,
+                    buffers[r.clone()].as_ptr(),
+                    buffer_offsets[r.clone()].as_ptr(),
+                    buffer_strides[r.clone()].as_ptr(),
                 )
             }
         }
+        self.count(
+            |c| &mut c.vertex_buffers,
+            dirty.map_or(0, |r| r.len()),
+            count,
+        );
     }
 
     pub fn set_index_buffer(&mut self, gl: &Gl, buffer: GLuint, offset: usize, ty: IndexFormat) {
-        self.index_buffer.update_cached(buffer, || unsafe {
+        let issued = self.index_buffer.update_cached(buffer, || unsafe {
             gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, buffer);
         });
+        self.count(|c| &mut c.index_buffers, issued as usize, 1);
 
         self.index_buffer_offset = Some(offset);
         self.index_buffer_type = Some(match ty {
@@ -767,5 +1142,70 @@ impl StateCache {
         }
     }
 
+    /// Uploads indirect draw parameters and binds the indirect buffer to `GL_DRAW_INDIRECT_BUFFER`.
+    fn upload_indirect_commands<T: Copy>(&mut self, gl: &Gl, commands: &[T]) {
+        let buffer = *self.indirect_buffer.get_or_insert_with(|| unsafe {
+            let mut obj = 0;
+            gl.CreateBuffers(1, &mut obj);
+            obj
+        });
+
+        unsafe {
+            // orphan the previous contents: they may still be in use by the GPU
+            gl.NamedBufferData(
+                buffer,
+                mem::size_of_val(commands) as GLsizeiptr,
+                commands.as_ptr() as *const GLvoid,
+                gl::STREAM_DRAW,
+            );
+            gl.BindBuffer(gl::DRAW_INDIRECT_BUFFER, buffer);
+        }
+    }
+
+    pub fn multi_draw(
+        &mut self,
+        gl: &Gl,
+        topo: PrimitiveTopology,
+        commands: &[DrawArraysIndirectCommand],
+    ) {
+        let mode = topology_to_gl(topo);
+        self.upload_indirect_commands(gl, commands);
+        unsafe {
+            gl.MultiDrawArraysIndirect(mode, ptr::null(), commands.len() as i32, 0);
+        }
+    }
+
+    /// Issues a batch of indexed draws with the currently bound index buffer.
+    ///
+    /// The `first_index` of each command is relative to the offset of the index buffer.
+    pub fn multi_draw_indexed(
+        &mut self,
+        gl: &Gl,
+        topo: PrimitiveTopology,
+        commands: &mut [DrawElementsIndirectCommand],
+    ) {
+        let mode = topology_to_gl(topo);
+        let idx_offset = self
+            .index_buffer_offset
+            .expect("no index buffer was bound before indexed draw operation");
+        let ty = self.index_buffer_type.unwrap();
+        let idx_stride = match ty {
+            gl::UNSIGNED_SHORT => 2,
+            gl::UNSIGNED_INT => 4,
+            _ => unreachable!(),
+        };
+        // indirect draws have no byte offset into the index buffer: fold it into `first_index`
+        // (draws with misaligned offsets are not batched)
+        debug_assert_eq!(idx_offset % idx_stride, 0);
+        for cmd in commands.iter_mut() {
+            cmd.first_index += (idx_offset / idx_stride) as u32;
+        }
+
+        self.upload_indirect_commands(gl, commands);
+        unsafe {
+            gl.MultiDrawElementsIndirect(mode, ty, ptr::null(), commands.len() as i32, 0);
+        }
+    }
+
     //pub fn set_blend_mode(&mut self)
 }
@@ -1,5 +1,8 @@
 use crate::{api as gl, api::types::*};
-use autograph_api::Format;
+use autograph_api::{
+    format::{ComponentLayout, FormatFeatureFlags, FormatProperties, NumericFormat},
+    Format,
+};
 
 /// Equivalent OpenGL format information for a given [Format](autograph_api::Format).
 pub struct GlFormatInfo {
@@ -161,8 +164,16 @@ static GLF_D32_SFLOAT: GlFormatInfo = GlFormatInfo {
 
 impl GlFormatInfo {
     /// Returns the equivalent OpenGL format information for the specified format.
+    ///
+    /// Panics if the format is not supported by the backend.
     pub fn from_format(fmt: Format) -> &'static GlFormatInfo {
-        match fmt {
+        Self::try_from_format(fmt).unwrap_or_else(|| panic!("Unsupported format: {:?}", fmt))
+    }
+
+    /// Returns the equivalent OpenGL format information for the specified format, or `None` if
+    /// the format is not supported by the backend.
+    pub fn try_from_format(fmt: Format) -> Option<&'static GlFormatInfo> {
+        let info = match fmt {
             Format::R8_UNORM => &GLF_R8_UNORM,
             Format::R8_SNORM => &GLF_R8_SNORM,
             Format::R8_UINT => &GLF_R8_UINT,
@@ -194,7 +205,79 @@ impl GlFormatInfo {
             Format::R8G8B8_SRGB => &GLF_R8G8B8_SRGB,
             Format::R8G8B8A8_SRGB => &GLF_R8G8B8A8_SRGB,
             Format::D32_SFLOAT => &GLF_D32_SFLOAT,
-            _ => panic!("Unsupported format: {:?}", fmt),
+            _ => return None,
+        };
+        Some(info)
+    }
+}
+
+/// Queries the support of a format by the implementation (ARB_internalformat_query2).
+pub unsafe fn query_format_properties(gl: &gl::Gl, fmt: Format) -> FormatProperties {
+    let info = if let Some(info) = GlFormatInfo::try_from_format(fmt) {
+        info
+    } else {
+        return FormatProperties {
+            features: FormatFeatureFlags::empty(),
+            emulated: false,
+        };
+    };
+
+    let get = |pname| {
+        let mut v = 0;
+        gl.GetInternalformativ(gl::TEXTURE_2D, info.internal_fmt, pname, 1, &mut v);
+        v as GLenum
+    };
+
+    if get(gl::INTERNALFORMAT_SUPPORTED) != gl::TRUE as GLenum {
+        return FormatProperties {
+            features: FormatFeatureFlags::empty(),
+            emulated: false,
+        };
+    }
+
+    let mut features = FormatFeatureFlags::empty();
+    if get(gl::FRAGMENT_TEXTURE) != gl::NONE {
+        features |= FormatFeatureFlags::SAMPLED_IMAGE;
+    }
+    if get(gl::SHADER_IMAGE_LOAD) != gl::NONE && get(gl::SHADER_IMAGE_STORE) != gl::NONE {
+        features |= FormatFeatureFlags::STORAGE_IMAGE;
+    }
+    if get(gl::FRAMEBUFFER_RENDERABLE) == gl::FULL_SUPPORT {
+        match fmt.get_format_info().component_layout {
+            ComponentLayout::D | ComponentLayout::DS | ComponentLayout::S | ComponentLayout::XD => {
+                features |= FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT
+            }
+            _ => features |= FormatFeatureFlags::COLOR_ATTACHMENT,
         }
     }
+
+    FormatProperties {
+        features,
+        emulated: get(gl::INTERNALFORMAT_PREFERRED) != info.internal_fmt,
+    }
+}
+
+/// Returns the OpenGL component type of vertex attributes of the specified format, and whether
+/// the components are integers (as opposed to normalized or floating-point values).
+///
+/// Unlike [GlFormatInfo::upload_ty], this returns the exact type
+/// (e.g. `GL_HALF_FLOAT` for 16-bit floating-point formats).
+pub fn vertex_attrib_type(fmt: Format) -> (GLenum, bool) {
+    let info = fmt.get_format_info();
+    let bits = info.component_bits[0];
+    match (bits, &info.format_type) {
+        (8, NumericFormat::UNORM) | (8, NumericFormat::USCALED) => (gl::UNSIGNED_BYTE, false),
+        (8, NumericFormat::SNORM) | (8, NumericFormat::SSCALED) => (gl::BYTE, false),
+        (8, NumericFormat::UINT) => (gl::UNSIGNED_BYTE, true),
+        (8, NumericFormat::SINT) => (gl::BYTE, true),
+        (16, NumericFormat::UNORM) | (16, NumericFormat::USCALED) => (gl::UNSIGNED_SHORT, false),
+        (16, NumericFormat::SNORM) | (16, NumericFormat::SSCALED) => (gl::SHORT, false),
+        (16, NumericFormat::UINT) => (gl::UNSIGNED_SHORT, true),
+        (16, NumericFormat::SINT) => (gl::SHORT, true),
+        (16, NumericFormat::SFLOAT) => (gl::HALF_FLOAT, false),
+        (32, NumericFormat::UINT) => (gl::UNSIGNED_INT, true),
+        (32, NumericFormat::SINT) => (gl::INT, true),
+        (32, NumericFormat::SFLOAT) => (gl::FLOAT, false),
+        _ => panic!("Unsupported vertex attribute format: {:?}", fmt),
+    }
 }
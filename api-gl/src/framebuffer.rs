@@ -3,11 +3,118 @@ use crate::{
     api::{types::*, Gl},
     image::GlImage,
 };
+use autograph_api::{
+    image::SubresourceRange,
+    pipeline::{LoadOp, StoreOp},
+};
+use std::slice;
+
+/// An image (and the mip level / layer of it) attached to a framebuffer.
+///
+/// The image is a raw pointer for the same reasons as in `StateBlock`.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Attachment {
+    pub(crate) image: *const GlImage,
+    pub(crate) subresource: SubresourceRange,
+    /// Load operation, from the fragment output description of the signature.
+    pub(crate) load_op: LoadOp,
+    /// Store operation, from the fragment output description of the signature.
+    pub(crate) store_op: StoreOp,
+}
+
+impl Attachment {
+    pub(crate) fn new(
+        image: &GlImage,
+        subresource: SubresourceRange,
+        load_op: LoadOp,
+        store_op: StoreOp,
+    ) -> Attachment {
+        Attachment {
+            image: image as *const GlImage,
+            subresource,
+            load_op,
+            store_op,
+        }
+    }
+
+    /// Identifies the attached subresource, for tracking the first use of an attachment in a
+    /// frame.
+    pub(crate) fn key(&self) -> (*const GlImage, u32, u32) {
+        (
+            self.image,
+            self.subresource.base_mip_level,
+            self.subresource.base_array_layer,
+        )
+    }
+
+    /// Returns the number of array layers of this attachment (one per view with multiview).
+    pub(crate) fn layer_count(&self) -> u32 {
+        self.subresource.layer_count.unwrap_or(1)
+    }
+
+    /// Attaches the image to the specified attachment point of the framebuffer.
+    ///
+    /// Layered textures (3D textures) are attached one layer only (`subresource.base_array_layer`
+    /// + `view`).
+    unsafe fn attach(&self, gl: &Gl, fbo: GLuint, attachment_point: GLenum, view: u32) {
+        let raw = (&*self.image).raw();
+        let level = self.subresource.base_mip_level as i32;
+        match raw.target {
+            gl::RENDERBUFFER => {
+                gl.NamedFramebufferRenderbuffer(fbo, attachment_point, gl::RENDERBUFFER, raw.obj);
+            }
+            gl::TEXTURE_3D | gl::TEXTURE_1D_ARRAY | gl::TEXTURE_2D_ARRAY => {
+                gl.NamedFramebufferTextureLayer(
+                    fbo,
+                    attachment_point,
+                    raw.obj,
+                    level,
+                    (self.subresource.base_array_layer + view) as i32,
+                );
+            }
+            _ => {
+                gl.NamedFramebufferTexture(fbo, attachment_point, raw.obj, level);
+            }
+        }
+    }
+
+    /// Attaches all the layers of this attachment to the specified attachment point of the
+    /// framebuffer with `GL_OVR_multiview`: layer `base_array_layer + i` is rendered by view `i`.
+    ///
+    /// The framebuffer is temporarily bound to `GL_DRAW_FRAMEBUFFER`, as the extension has no
+    /// direct state access variant.
+    unsafe fn attach_multiview(&self, gl: &Gl, fbo: GLuint, attachment_point: GLenum) {
+        let raw = (&*self.image).raw();
+        assert_eq!(
+            raw.target,
+            gl::TEXTURE_2D_ARRAY,
+            "multiview attachments must be 2D array textures"
+        );
+        let mut prev = 0;
+        gl.GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut prev);
+        gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, fbo);
+        gl.FramebufferTextureMultiviewOVR(
+            gl::DRAW_FRAMEBUFFER,
+            attachment_point,
+            raw.obj,
+            self.subresource.base_mip_level as i32,
+            self.subresource.base_array_layer as i32,
+            self.layer_count() as i32,
+        );
+        gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, prev as GLuint);
+    }
+}
 
 /// Wrapper around OpenGL framebuffers.
 #[derive(Debug)]
 pub(crate) struct GlFramebuffer {
     pub(crate) obj: GLuint,
+    /// Color attachments, in attachment point order, for scheduling load and store operations.
+    pub(crate) color_attachments: smallvec::SmallVec<[Attachment; 8]>,
+    pub(crate) depth_stencil_attachment: Option<Attachment>,
+    /// When views are emulated, one framebuffer per view, each one with the layer of the view
+    /// attached (the first one is `obj`). Empty otherwise.
+    pub(crate) view_objs: smallvec::SmallVec<[GLuint; 4]>,
 }
 
 impl GlFramebuffer {
@@ -16,100 +123,140 @@ impl GlFramebuffer {
     /// The specified _n_ color attachments are bound to GL_COLOR_ATTACHMENT0 to
     /// GL_COLOR_ATTACHMENT_n_. The _n_ first draw buffers of the FBO are enabled,
     /// and mapped to the color attachments.
-    /// For texture attachments, the topmost layer is attached.
+    /// For texture attachments, the mip level of the attachment is attached, and for 3D textures,
+    /// only the slice `base_array_layer`.
+    ///
+    /// If an attachment has more than one layer, the framebuffer has one view per layer. With
+    /// `native_multiview`, all the layers are attached with `GL_OVR_multiview`. Otherwise, one
+    /// FBO is created per view (see `view_objs`), and draws are repeated for each of them.
     ///
     /// Panics if the number of color attachments is greater than 8.
     ///
-    pub(crate) fn new(
+    /// The images of the attachments must be alive.
+    pub(crate) unsafe fn new(
         gl: &Gl,
-        color_attachments: &[&GlImage],
-        depth_stencil_attachment: Option<&GlImage>,
+        color_attachments: &[Attachment],
+        depth_stencil_attachment: Option<Attachment>,
+        native_multiview: bool,
     ) -> Result<GlFramebuffer, GLenum> {
         assert!(color_attachments.len() < 8);
 
-        let mut obj = 0;
-        unsafe {
-            gl.CreateFramebuffers(1, &mut obj);
-        }
+        let view_count = color_attachments
+            .iter()
+            .chain(depth_stencil_attachment.iter())
+            .map(|a| a.layer_count())
+            .max()
+            .unwrap_or(1);
 
-        // color attachments
-        for (index, &img) in color_attachments.iter().enumerate() {
-            let index = index as u32;
-            match img.raw.target {
-                gl::RENDERBUFFER => unsafe {
-                    gl.NamedFramebufferRenderbuffer(
-                        obj,
-                        gl::COLOR_ATTACHMENT0 + index,
-                        gl::RENDERBUFFER,
-                        img.raw.obj,
-                    );
-                },
-                _ => unsafe {
-                    gl.NamedFramebufferTexture(
-                        obj,
-                        gl::COLOR_ATTACHMENT0 + index,
-                        img.raw.obj,
-                        0, // TODO
-                    );
-                },
-            }
+        if view_count > 1 && native_multiview {
+            let obj = create_fbo(
+                gl,
+                color_attachments,
+                depth_stencil_attachment,
+                |a, fbo, point| a.attach_multiview(gl, fbo, point),
+            )?;
+            return Ok(GlFramebuffer {
+                obj,
+                color_attachments: color_attachments.iter().cloned().collect(),
+                depth_stencil_attachment,
+                view_objs: smallvec::SmallVec::new(),
+            });
         }
 
-        // depth-stencil attachment
-        if let Some(img) = depth_stencil_attachment {
-            match img.raw.target {
-                gl::RENDERBUFFER => unsafe {
-                    gl.NamedFramebufferRenderbuffer(
-                        obj,
-                        gl::DEPTH_ATTACHMENT,
-                        gl::RENDERBUFFER,
-                        img.raw.obj,
-                    );
-                },
-                _ => unsafe {
-                    gl.NamedFramebufferTexture(
-                        obj,
-                        gl::DEPTH_ATTACHMENT,
-                        img.raw.obj,
-                        0, // TODO
-                    );
-                },
+        let mut view_objs = smallvec::SmallVec::<[GLuint; 4]>::new();
+        for view in 0..view_count {
+            let fbo = create_fbo(
+                gl,
+                color_attachments,
+                depth_stencil_attachment,
+                |a, fbo, point| a.attach(gl, fbo, point, view.min(a.layer_count() - 1)),
+            );
+            match fbo {
+                Ok(fbo) => view_objs.push(fbo),
+                Err(status) => {
+                    gl.DeleteFramebuffers(view_objs.len() as i32, view_objs.as_ptr());
+                    return Err(status);
+                }
             }
         }
 
-        // enable draw buffers
-        unsafe {
-            gl.NamedFramebufferDrawBuffers(
-                obj,
-                color_attachments.len() as i32,
-                [
-                    gl::COLOR_ATTACHMENT0,
-                    gl::COLOR_ATTACHMENT0 + 1,
-                    gl::COLOR_ATTACHMENT0 + 2,
-                    gl::COLOR_ATTACHMENT0 + 3,
-                    gl::COLOR_ATTACHMENT0 + 4,
-                    gl::COLOR_ATTACHMENT0 + 5,
-                    gl::COLOR_ATTACHMENT0 + 6,
-                    gl::COLOR_ATTACHMENT0 + 7,
-                ]
-                .as_ptr(),
-            )
+        let obj = view_objs[0];
+        if view_count == 1 {
+            view_objs.clear();
         }
 
-        // check framebuffer completeness
-        let status = unsafe { gl.CheckNamedFramebufferStatus(obj, gl::DRAW_FRAMEBUFFER) };
+        Ok(GlFramebuffer {
+            obj,
+            color_attachments: color_attachments.iter().cloned().collect(),
+            depth_stencil_attachment,
+            view_objs,
+        })
+    }
 
-        if status == gl::FRAMEBUFFER_COMPLETE {
-            Ok(GlFramebuffer { obj: obj })
+    /// Returns the framebuffer objects of all the views (`obj` if the views are not emulated).
+    pub(crate) fn objs(&self) -> &[GLuint] {
+        if self.view_objs.is_empty() {
+            slice::from_ref(&self.obj)
         } else {
-            Err(status)
+            &self.view_objs
         }
     }
 
     /// Destroys this framebuffer object.
     pub(crate) fn destroy(self, gl: &Gl) {
         unsafe {
-            gl.DeleteFramebuffers(1, &self.obj);
+            let objs = self.objs();
+            gl.DeleteFramebuffers(objs.len() as i32, objs.as_ptr());
         }
     }
 }
+
+/// Creates an FBO, attaches the attachments with `attach` and checks its completeness.
+///
+/// The FBO is deleted if it is not complete.
+unsafe fn create_fbo(
+    gl: &Gl,
+    color_attachments: &[Attachment],
+    depth_stencil_attachment: Option<Attachment>,
+    attach: impl Fn(&Attachment, GLuint, GLenum),
+) -> Result<GLuint, GLenum> {
+    let mut obj = 0;
+    gl.CreateFramebuffers(1, &mut obj);
+
+    // color attachments
+    for (index, a) in color_attachments.iter().enumerate() {
+        attach(a, obj, gl::COLOR_ATTACHMENT0 + index as u32);
+    }
+
+    // depth-stencil attachment
+    if let Some(ref a) = depth_stencil_attachment {
+        attach(a, obj, gl::DEPTH_ATTACHMENT);
+    }
+
+    // enable draw buffers
+    gl.NamedFramebufferDrawBuffers(
+        obj,
+        color_attachments.len() as i32,
+        [
+            gl::COLOR_ATTACHMENT0,
+            gl::COLOR_ATTACHMENT0 + 1,
+            gl::COLOR_ATTACHMENT0 + 2,
+            gl::COLOR_ATTACHMENT0 + 3,
+            gl::COLOR_ATTACHMENT0 + 4,
+            gl::COLOR_ATTACHMENT0 + 5,
+            gl::COLOR_ATTACHMENT0 + 6,
+            gl::COLOR_ATTACHMENT0 + 7,
+        ]
+        .as_ptr(),
+    );
+
+    // check framebuffer completeness
+    let status = gl.CheckNamedFramebufferStatus(obj, gl::DRAW_FRAMEBUFFER);
+
+    if status == gl::FRAMEBUFFER_COMPLETE {
+        Ok(obj)
+    } else {
+        gl.DeleteFramebuffers(1, &obj);
+        Err(status)
+    }
+}
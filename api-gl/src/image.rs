@@ -2,13 +2,15 @@ use crate::{
     api as gl,
     api::{types::*, Gl},
     format::GlFormatInfo,
+    sync::GpuSyncObject,
+    tracking::ArenaTag,
     AliasInfo,
 };
 use autograph_api::{
-    get_texture_mip_map_count, Dimensions, Format, ImageUsageFlags, MipmapsOption,
+    get_texture_mip_map_count, traits, Dimensions, Format, ImageUsageFlags, MipmapsOption,
 };
 use slotmap::new_key_type;
-use std::cmp::max;
+use std::{cell::Cell, cmp::max, fmt, sync::RwLock};
 
 //--------------------------------------------------------------------------------------------------
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -28,11 +30,10 @@ impl ImageDescription {
         samples: u32,
         usage: ImageUsageFlags,
     ) -> ImageDescription {
-        let (w, h, _d) = dimensions.width_height_depth();
+        let (w, h, d) = dimensions.width_height_depth();
         let mipcount = match mipmaps {
-            // TODO mipcount for 3D textures?
             MipmapsOption::Allocate | MipmapsOption::Generate => {
-                get_texture_mip_map_count(max(w, h))
+                get_texture_mip_map_count(max(max(w, h), d))
             }
             MipmapsOption::GenerateCount(n) | MipmapsOption::AllocateCount(n) => {
                 if samples > 1 {
@@ -108,13 +109,21 @@ impl ExtentsAndType {
 pub struct RawImage {
     pub(crate) obj: GLuint,
     pub(crate) target: GLenum,
-    //pub format: Format,
+    pub(crate) format: Format,
+    /// Number of mipmap levels (1 for renderbuffers).
+    pub(crate) mip_levels: u32,
+    /// Dimensions of the image (never swapchain-relative).
+    pub(crate) dimensions: Dimensions,
 }
 
 impl RawImage {
     pub fn new(gl: &Gl, d: &ImageDescription) -> RawImage {
-        if d.usage != ImageUsageFlags::COLOR_ATTACHMENT {
-            // will be used as storage or sampled image
+        let is_3d = match d.dimensions {
+            Dimensions::Dim3d { .. } => true,
+            _ => false,
+        };
+        if d.usage != ImageUsageFlags::COLOR_ATTACHMENT || is_3d {
+            // will be used as storage or sampled image, or is a volume (no 3D renderbuffers)
             RawImage::new_texture(gl, d.format, &d.dimensions, d.mipcount, d.samples)
         } else {
             // only used as color attachments: can use a renderbuffer instead
@@ -167,7 +176,7 @@ impl RawImage {
                 gl::TEXTURE_3D => {
                     gl.TextureStorage3D(
                         obj,
-                        1,
+                        mipcount as i32,
                         glfmt.internal_fmt,
                         et.width as i32,
                         et.height as i32,
@@ -187,7 +196,9 @@ impl RawImage {
         RawImage {
             obj,
             target: et.target,
-            //format
+            format,
+            mip_levels: mipcount,
+            dimensions: *dimensions,
         }
     }
 
@@ -226,7 +237,20 @@ impl RawImage {
         RawImage {
             obj,
             target: gl::RENDERBUFFER,
-            //format
+            format,
+            mip_levels: 1,
+            dimensions: *dimensions,
+        }
+    }
+
+    /// Wraps an existing texture object, described by `desc`.
+    pub fn from_raw_texture(obj: GLuint, desc: &ImageDescription) -> RawImage {
+        RawImage {
+            obj,
+            target: ExtentsAndType::from_dimensions(&desc.dimensions).target,
+            format: desc.format,
+            mip_levels: desc.mipcount,
+            dimensions: desc.dimensions,
         }
     }
 
@@ -264,13 +288,55 @@ pub unsafe fn upload_image_region(
         (size.0 * size.1 * size.2) as usize * fmtinfo.byte_size(),
         "image data size mismatch"
     );
+    upload_image_region_with_pitch(gl, target, img, fmt, mip_level, offset, size, 0, data)
+}
+
+/// Same as `upload_image_region`, with `row_pitch` bytes between the starts of two consecutive
+/// rows in `data` (0 if tightly packed).
+pub unsafe fn upload_image_region_with_pitch(
+    gl: &Gl,
+    target: GLenum,
+    img: GLuint,
+    fmt: Format,
+    mip_level: i32,
+    offset: (u32, u32, u32),
+    size: (u32, u32, u32),
+    row_pitch: usize,
+    data: &[u8],
+) {
+    let texel_size = fmt.get_format_info().byte_size();
+    let row_size = size.0 as usize * texel_size;
+    let row_pitch = if row_pitch == 0 { row_size } else { row_pitch };
+    assert!(
+        row_pitch >= row_size && row_pitch % texel_size == 0,
+        "invalid row pitch ({}) for rows of {} texels of {} bytes",
+        row_pitch,
+        size.0,
+        texel_size
+    );
+    let rows = (size.1 * size.2) as usize;
+    if rows == 0 || row_size == 0 {
+        return;
+    }
+    assert!(
+        data.len() >= row_pitch * (rows - 1) + row_size,
+        "image data size mismatch"
+    );
 
     // TODO check size of mip level
     let glfmt = GlFormatInfo::from_format(fmt);
 
+    // rows start at multiples of the texel size: specify the stride with UNPACK_ROW_LENGTH,
+    // and disable the default 4-byte alignment of rows
     let mut prev_unpack_alignment = 0;
+    let mut prev_unpack_row_length = 0;
+    let mut prev_unpack_image_height = 0;
     gl.GetIntegerv(gl::UNPACK_ALIGNMENT, &mut prev_unpack_alignment);
+    gl.GetIntegerv(gl::UNPACK_ROW_LENGTH, &mut prev_unpack_row_length);
+    gl.GetIntegerv(gl::UNPACK_IMAGE_HEIGHT, &mut prev_unpack_image_height);
     gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+    gl.PixelStorei(gl::UNPACK_ROW_LENGTH, (row_pitch / texel_size) as i32);
+    gl.PixelStorei(gl::UNPACK_IMAGE_HEIGHT, 0);
 
     match target {
         gl::TEXTURE_1D => {
@@ -316,6 +382,23 @@ pub unsafe fn upload_image_region(
     };
 
     gl.PixelStorei(gl::UNPACK_ALIGNMENT, prev_unpack_alignment);
+    gl.PixelStorei(gl::UNPACK_ROW_LENGTH, prev_unpack_row_length);
+    gl.PixelStorei(gl::UNPACK_IMAGE_HEIGHT, prev_unpack_image_height);
+}
+
+/// Returns the size of a mipmap level of a texture, or `None` if the level does not exist.
+pub unsafe fn texture_level_size(gl: &Gl, img: GLuint, mip_level: i32) -> Option<(u32, u32, u32)> {
+    let mut width = 0;
+    let mut height = 0;
+    let mut depth = 0;
+    gl.GetTextureLevelParameteriv(img, mip_level, gl::TEXTURE_WIDTH, &mut width);
+    gl.GetTextureLevelParameteriv(img, mip_level, gl::TEXTURE_HEIGHT, &mut height);
+    gl.GetTextureLevelParameteriv(img, mip_level, gl::TEXTURE_DEPTH, &mut depth);
+    if width == 0 {
+        None
+    } else {
+        Some((width as u32, height as u32, depth as u32))
+    }
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -327,9 +410,56 @@ new_key_type! {
 /// OpenGL image.
 ///
 /// It can be either a texture object or a renderbuffer object if sampling is not required.
+///
+/// The underlying object can be replaced during frame submission if the image has
+/// swapchain-relative dimensions and the swapchain was resized.
 #[derive(Debug)]
 pub struct GlImage {
-    pub(crate) raw: RawImage,
-    pub(crate) should_destroy: bool,
-    pub(crate) alias_info: Option<AliasInfo<ImageAliasKey>>,
+    /// Behind a lock because it is read by `Image::dimensions`, which can be called from any
+    /// thread, including during the frame submission that replaces it.
+    pub(crate) raw: RwLock<RawImage>,
+    /// Whether the underlying object is deleted with the arena
+    /// (false for aliased, imported or exported images).
+    pub(crate) should_destroy: Cell<bool>,
+    /// Entry of the alias pool, replaced when a swapchain-relative image is resized.
+    pub(crate) alias_info: Cell<Option<AliasInfo<ImageAliasKey>>>,
+    pub(crate) tag: ArenaTag,
+}
+
+// `should_destroy` and `alias_info` are only accessed by the methods of `OpenGlInstance`, which
+// is not `Sync`: other threads only hold references to the image.
+unsafe impl Sync for GlImage {}
+
+impl GlImage {
+    /// Returns the current underlying texture or renderbuffer.
+    pub(crate) fn raw(&self) -> RawImage {
+        *self.raw.read().unwrap()
+    }
+
+    /// Replaces the underlying texture or renderbuffer.
+    pub(crate) fn set_raw(&self, raw: RawImage) {
+        *self.raw.write().unwrap() = raw;
+    }
+}
+
+impl traits::Image for GlImage {
+    fn dimensions(&self) -> Dimensions {
+        // updated when swapchain-relative images are resized
+        self.raw().dimensions
+    }
+}
+
+/// An image exported from an instance, to be imported into another instance whose context
+/// shares objects with the context of the exporting instance.
+///
+/// See `OpenGlInstance::export_image` and `OpenGlInstance::import_image`.
+/// The texture is leaked if the handle is dropped without being imported.
+pub struct SharedImage {
+    pub(crate) inner: GpuSyncObject<RawImage>,
+}
+
+impl fmt::Debug for SharedImage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SharedImage {{..}}")
+    }
 }
@@ -14,6 +14,33 @@
 //! The "present" command then copies the specified image to the default framebuffer with
 //! `glBlitFramebuffer`, and then calls `SwapBuffers`.
 //!
+//! ### Embedding in a host application
+//!
+//! An instance can be created from an OpenGL context owned by another application with
+//! `OpenGlInstance::from_current_context_with_loader`. Textures and framebuffers of the host
+//! can then be used with `OpenGlInstance::import_texture` and `OpenGlInstance::import_framebuffer`.
+//! Presenting to an imported framebuffer only copies the image; the host swaps buffers.
+//!
+//! ### Sharing images between instances
+//!
+//! Images can be handed over without a copy to another instance whose context shares objects
+//! with the context of the first one (e.g. a background context uploading textures), with
+//! `OpenGlInstance::export_image` and `OpenGlInstance::import_image`.
+//!
+//! ### OpenGL ES
+//!
+//! OpenGL ES contexts (including ANGLE and WebGL 2) are not supported: instance creation fails
+//! with `InstanceError::UnsupportedProfile`. The backend creates and edits its objects with the
+//! direct state access functions of OpenGL 4.5, and pipelines can only be created from SPIR-V
+//! shaders. ES contexts can be detected beforehand with `ImplementationParameters::populate`
+//! (`ImplementationParameters::es`).
+//!
+//! ### Debugging dangling references
+//!
+//! With the `arena-tracking` feature, the objects referenced by the commands are checked on
+//! each submission, and a panic reports where an object was created if its arena was already
+//! dropped. Dropped arenas are then released a few frames later, instead of immediately.
+//!
 //! ### Texture & viewport coordinates
 //!
 //! OpenGL sets the origin of viewports and textures to the lower-left corner. For clip-space,
@@ -45,20 +72,25 @@ mod framebuffer;
 mod image;
 mod pipeline;
 pub mod prelude;
+mod query;
 mod sampler;
 mod swapchain;
 mod sync;
+mod tracking;
 mod util;
 mod window;
 
 pub use self::{
     backend::{InstanceConfig, OpenGlBackend, OpenGlInstance},
+    buffer::{StreamingStrategy, UploadStats},
+    command::{BindCounter, StateCacheCounters},
+    image::{ImageDescription, SharedImage},
     window::create_instance_and_window,
 };
 
 use crate::api as gl;
-use autograph_api::AliasScope;
-use std::mem;
+use autograph_api::{AliasScope, ApiProfile, DeviceLimits};
+use std::{ffi::CStr, os::raw::c_char};
 
 #[derive(Copy, Clone, Debug)]
 struct AliasInfo<K: slotmap::Key> {
@@ -69,24 +101,174 @@ struct AliasInfo<K: slotmap::Key> {
 //--------------------------------------------------------------------------------------------------
 pub struct ImplementationParameters {
     pub uniform_buffer_alignment: usize,
+    pub texture_buffer_offset_alignment: usize,
+    pub max_texture_buffer_size: u32,
     pub max_draw_buffers: u32,
     pub max_color_attachments: u32,
     pub max_viewports: u32,
+    pub max_texture_size: u32,
+    pub max_3d_texture_size: u32,
+    pub max_cube_map_texture_size: u32,
+    pub max_array_texture_layers: u32,
+    pub max_samples: u32,
+    pub max_texture_max_anisotropy: f32,
+    pub max_uniform_block_size: u32,
+    pub max_compute_work_group_count: [u32; 3],
+    pub aliased_line_width_range: [f32; 2],
+    pub point_size_range: [f32; 2],
+    /// Whether `GL_OVR_multiview` is supported.
+    pub native_multiview: bool,
+    /// Maximum number of views of `GL_OVR_multiview` (0 if not supported).
+    pub max_views: u32,
+    /// Version of the context.
+    pub version: (i32, i32),
+    /// Whether the context is an OpenGL ES context.
+    pub es: bool,
+    /// Whether shaders can be created from SPIR-V (OpenGL 4.6 or `GL_ARB_gl_spirv`).
+    pub spirv: bool,
+    /// Whether buffers can have immutable storage, and be mapped persistently
+    /// (OpenGL 4.4 or `GL_EXT_buffer_storage`).
+    pub buffer_storage: bool,
+    /// Whether draws can be batched in `glMultiDraw*Indirect` calls
+    /// (OpenGL 4.3 or `GL_EXT_multi_draw_indirect`).
+    pub multi_draw_indirect: bool,
 }
 
 impl ImplementationParameters {
     pub fn populate(gl: &gl::Gl) -> ImplementationParameters {
+        // parameters that don't exist in the profile of the context are left to 0
         let getint = |param| unsafe {
-            let mut v = mem::uninitialized();
+            let mut v = 0;
             gl.GetIntegerv(param, &mut v);
             v
         };
 
+        let getint_indexed = |param, index| unsafe {
+            let mut v = 0;
+            gl.GetIntegeri_v(param, index, &mut v);
+            v
+        };
+
+        let max_texture_max_anisotropy = unsafe {
+            let mut v = 1.0;
+            gl.GetFloatv(gl::MAX_TEXTURE_MAX_ANISOTROPY, &mut v);
+            v
+        };
+
+        let getfloat2 = |param| unsafe {
+            let mut v = [0.0f32; 2];
+            gl.GetFloatv(param, v.as_mut_ptr());
+            v
+        };
+
+        let version = (getint(gl::MAJOR_VERSION), getint(gl::MINOR_VERSION));
+        let es = is_es_context(gl);
+        let spirv = !es && (version >= (4, 6) || has_extension(gl, "GL_ARB_gl_spirv"));
+        let buffer_storage = if es {
+            has_extension(gl, "GL_EXT_buffer_storage")
+        } else {
+            version >= (4, 4) || has_extension(gl, "GL_ARB_buffer_storage")
+        };
+        let multi_draw_indirect = if es {
+            has_extension(gl, "GL_EXT_multi_draw_indirect")
+        } else {
+            version >= (4, 3)
+        };
+
+        let native_multiview = has_extension(gl, "GL_OVR_multiview");
+        let max_views = if native_multiview {
+            getint(gl::MAX_VIEWS_OVR) as u32
+        } else {
+            0
+        };
+
         ImplementationParameters {
-            uniform_buffer_alignment: getint(gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT) as usize,
+            uniform_buffer_alignment: getint(gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT).max(1) as usize,
+            // no texture buffers before OpenGL ES 3.2
+            texture_buffer_offset_alignment: getint(gl::TEXTURE_BUFFER_OFFSET_ALIGNMENT).max(1)
+                as usize,
+            max_texture_buffer_size: getint(gl::MAX_TEXTURE_BUFFER_SIZE) as u32,
             max_draw_buffers: getint(gl::MAX_DRAW_BUFFERS) as u32,
             max_color_attachments: getint(gl::MAX_COLOR_ATTACHMENTS) as u32,
-            max_viewports: getint(gl::MAX_VIEWPORTS) as u32,
+            // no viewport arrays in OpenGL ES
+            max_viewports: getint(gl::MAX_VIEWPORTS).max(1) as u32,
+            max_texture_size: getint(gl::MAX_TEXTURE_SIZE) as u32,
+            max_3d_texture_size: getint(gl::MAX_3D_TEXTURE_SIZE) as u32,
+            max_cube_map_texture_size: getint(gl::MAX_CUBE_MAP_TEXTURE_SIZE) as u32,
+            max_array_texture_layers: getint(gl::MAX_ARRAY_TEXTURE_LAYERS) as u32,
+            max_samples: getint(gl::MAX_SAMPLES) as u32,
+            max_texture_max_anisotropy,
+            max_uniform_block_size: getint(gl::MAX_UNIFORM_BLOCK_SIZE) as u32,
+            max_compute_work_group_count: [
+                getint_indexed(gl::MAX_COMPUTE_WORK_GROUP_COUNT, 0) as u32,
+                getint_indexed(gl::MAX_COMPUTE_WORK_GROUP_COUNT, 1) as u32,
+                getint_indexed(gl::MAX_COMPUTE_WORK_GROUP_COUNT, 2) as u32,
+            ],
+            aliased_line_width_range: getfloat2(gl::ALIASED_LINE_WIDTH_RANGE),
+            point_size_range: getfloat2(gl::POINT_SIZE_RANGE),
+            native_multiview,
+            max_views,
+            version,
+            es,
+            spirv,
+            buffer_storage,
+            multi_draw_indirect,
         }
     }
+
+    /// Returns the limits in the form expected by the API.
+    pub fn device_limits(&self) -> DeviceLimits {
+        DeviceLimits {
+            max_image_dimension_1d: self.max_texture_size,
+            max_image_dimension_2d: self.max_texture_size,
+            max_image_dimension_3d: self.max_3d_texture_size,
+            max_image_dimension_cube: self.max_cube_map_texture_size,
+            max_image_array_layers: self.max_array_texture_layers,
+            max_color_attachments: self.max_color_attachments.min(self.max_draw_buffers),
+            max_samples: self.max_samples,
+            max_sampler_anisotropy: self.max_texture_max_anisotropy,
+            max_viewports: self.max_viewports,
+            max_uniform_buffer_range: self.max_uniform_block_size,
+            min_uniform_buffer_offset_alignment: self.uniform_buffer_alignment as u32,
+            max_compute_work_group_count: self.max_compute_work_group_count,
+            line_width_range: self.aliased_line_width_range,
+            point_size_range: self.point_size_range,
+            // without the extension, views are emulated with one draw per viewport
+            max_multiview_view_count: if self.native_multiview {
+                self.max_views
+            } else {
+                self.max_viewports
+            },
+            native_multiview: self.native_multiview,
+            profile: if self.es {
+                ApiProfile::Embedded
+            } else {
+                ApiProfile::Desktop
+            },
+            spirv_shaders: self.spirv,
+        }
+    }
+}
+
+/// Returns whether the current context is an OpenGL ES context.
+fn is_es_context(gl: &gl::Gl) -> bool {
+    unsafe {
+        let version = gl.GetString(gl::VERSION);
+        !version.is_null()
+            && CStr::from_ptr(version as *const c_char)
+                .to_bytes()
+                .starts_with(b"OpenGL ES")
+    }
+}
+
+/// Returns whether the current context supports the specified extension.
+fn has_extension(gl: &gl::Gl, name: &str) -> bool {
+    unsafe {
+        let mut count = 0;
+        gl.GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+        (0..count as u32).any(|i| {
+            let ext = gl.GetStringi(gl::EXTENSIONS, i);
+            !ext.is_null() && CStr::from_ptr(ext as *const c_char).to_bytes() == name.as_bytes()
+        })
+    }
 }
@@ -1,18 +1,21 @@
 use crate::{
     api::{types::*, Gl},
     backend::GlArena,
-    framebuffer::GlFramebuffer,
-    image::GlImage,
+    buffer::GlHostReference,
+    framebuffer::{Attachment, GlFramebuffer},
     sampler::SamplerCache,
+    tracking::{ArenaTag, TrackedReferences},
     OpenGlBackend,
 };
 use autograph_api::{
     descriptor::{Descriptor, ResourceBindingType},
-    image::{DepthStencilView, RenderTargetView},
-    pipeline::{BareArgumentBlock, Scissor, SignatureDescription, Viewport},
+    image::{DepthStencilView, LodClamp, RenderTargetView},
+    pipeline::{
+        BareArgumentBlock, FragmentOutputDescription, Scissor, SignatureDescription, Viewport,
+    },
     vertex::{IndexBufferView, IndexFormat, VertexBufferView},
 };
-use std::slice;
+use std::{ptr, slice};
 
 /// Proposal: flatten signature?
 /// At least, no need to store inherited (only the length matters)
@@ -30,11 +33,16 @@ pub struct GlSignature {
     pub(crate) num_images: usize,
     pub(crate) num_viewports: usize,
     pub(crate) num_scissors: usize,
+    pub(crate) has_stencil_reference: bool,
     pub(crate) num_render_targets: usize,
     pub(crate) has_index_buffer: bool,
     pub(crate) has_depth_render_target: bool,
+    /// Load and store operations of the render targets.
+    pub(crate) fragment_outputs: Vec<FragmentOutputDescription>,
+    pub(crate) depth_stencil_fragment_output: Option<FragmentOutputDescription>,
     pub(crate) is_root_fragment_output_signature: bool,
     pub(crate) is_root_vertex_input_signature: bool,
+    pub(crate) tag: ArenaTag,
 }
 
 // It's read-only so it should be safe?
@@ -70,15 +78,17 @@ impl GlSignature {
         //let mut num_render_targets = 0;
         for d in descriptor_map.iter() {
             match d {
-                ResourceBindingType::TextureSampler(_) => num_textures += 1,
+                // textures without samplers are bound with sampler object 0
+                ResourceBindingType::TextureSampler(_)
+                | ResourceBindingType::Texture(_)
+                | ResourceBindingType::TexelBuffer => num_textures += 1,
                 ResourceBindingType::RwImage(_) => num_images += 1,
                 ResourceBindingType::ConstantBuffer => num_uniform_buffers += 1,
                 ResourceBindingType::RwBuffer => num_shader_storage_buffers += 1,
                 //ResourceBindingType::InputAttachment => _num_input_attachments += 1,
-                ResourceBindingType::TexelBuffer
-                | ResourceBindingType::RwTexelBuffer
-                | ResourceBindingType::Texture(_)
-                | ResourceBindingType::Sampler => unimplemented!(),
+                ResourceBindingType::RwTexelBuffer | ResourceBindingType::Sampler => {
+                    unimplemented!()
+                }
             }
         }
         let num_vertex_buffers = description.vertex_inputs.len();
@@ -123,6 +133,9 @@ impl GlSignature {
         if description.num_scissors > 0 {
             num_state_blocks += 1;
         }
+        if description.has_stencil_reference {
+            num_state_blocks += 1;
+        }
         if inherited.len() > 0 {
             num_state_blocks += 1;
         }
@@ -141,8 +154,12 @@ impl GlSignature {
             num_render_targets,
             num_viewports: description.num_viewports,
             num_scissors: description.num_scissors,
+            has_stencil_reference: description.has_stencil_reference,
+            fragment_outputs: description.fragment_outputs.to_vec(),
+            depth_stencil_fragment_output: description.depth_stencil_fragment_output,
             is_root_fragment_output_signature: description.is_root_fragment_output_signature,
             is_root_vertex_input_signature: description.is_root_vertex_input_signature,
+            tag: ArenaTag::new(&arena.generation),
         })
     }
 }
@@ -154,6 +171,9 @@ pub(crate) enum StateBlock {
         buffers: *const GLuint,
         offsets: *const GLintptr,
         sizes: *const GLintptr,
+        /// Non-null for the uniform buffers sourced from host references: the buffer and offset
+        /// are only known once the frame is submitted.
+        host_references: *const *const GlHostReference,
     },
     ShaderStorageBuffers {
         buffers: *const GLuint,
@@ -173,19 +193,22 @@ pub(crate) enum StateBlock {
     Textures(*const GLuint),
     Images(*const GLuint),
     Samplers(*const GLuint),
-    RenderTarget(*const *const GlImage),
-    DepthStencilRenderTarget(*const GlImage),
-    Framebuffer(GLuint),
+    RenderTarget(*const Attachment),
+    DepthStencilRenderTarget(Attachment),
+    Framebuffer(*const GlFramebuffer),
     Viewports(*const Viewport),
     Scissors(*const Scissor),
+    StencilReference(u32),
     //Empty,
 }
 
 struct StateBlocks<'a> {
     inherited: &'a mut [&'a GlArgumentBlock],
+    references: TrackedReferences,
     uniform_buffers: &'a mut [GLuint],
     uniform_buffer_offsets: &'a mut [GLintptr],
     uniform_buffer_sizes: &'a mut [GLintptr],
+    uniform_buffer_host_references: &'a mut [*const GlHostReference],
     shader_storage_buffers: &'a mut [GLuint],
     shader_storage_buffer_offsets: &'a mut [GLintptr],
     shader_storage_buffer_sizes: &'a mut [GLintptr],
@@ -195,13 +218,14 @@ struct StateBlocks<'a> {
     index_buffer: GLuint,
     index_format: IndexFormat,
     index_offset: usize,
-    render_targets: &'a mut [&'a GlImage],
-    depth_stencil_target: Option<&'a GlImage>,
+    render_targets: &'a mut [Attachment],
+    depth_stencil_target: Option<Attachment>,
     textures: &'a mut [GLuint],
     samplers: &'a mut [GLuint],
     images: &'a mut [GLuint],
     viewports: &'a mut [Viewport],
     scissors: &'a mut [Scissor],
+    stencil_reference: u32,
 }
 
 impl<'a> StateBlocks<'a> {
@@ -212,16 +236,21 @@ impl<'a> StateBlocks<'a> {
             &mut [][..]
         };
 
-        let (uniform_buffers, uniform_buffer_offsets, uniform_buffer_sizes) =
-            if sig.num_uniform_buffers != 0 {
-                (
-                    arena.other.alloc_uninitialized(sig.num_uniform_buffers),
-                    arena.other.alloc_uninitialized(sig.num_uniform_buffers),
-                    arena.other.alloc_uninitialized(sig.num_uniform_buffers),
-                )
-            } else {
-                (&mut [][..], &mut [][..], &mut [][..])
-            };
+        let (
+            uniform_buffers,
+            uniform_buffer_offsets,
+            uniform_buffer_sizes,
+            uniform_buffer_host_references,
+        ) = if sig.num_uniform_buffers != 0 {
+            (
+                arena.other.alloc_uninitialized(sig.num_uniform_buffers),
+                arena.other.alloc_uninitialized(sig.num_uniform_buffers),
+                arena.other.alloc_uninitialized(sig.num_uniform_buffers),
+                arena.other.alloc_uninitialized(sig.num_uniform_buffers),
+            )
+        } else {
+            (&mut [][..], &mut [][..], &mut [][..], &mut [][..])
+        };
 
         let (shader_storage_buffers, shader_storage_buffer_offsets, shader_storage_buffer_sizes) =
             if sig.num_shader_storage_buffers != 0 {
@@ -286,9 +315,11 @@ impl<'a> StateBlocks<'a> {
 
         StateBlocks {
             inherited,
+            references: TrackedReferences::default(),
             uniform_buffers,
             uniform_buffer_offsets,
             uniform_buffer_sizes,
+            uniform_buffer_host_references,
             shader_storage_buffers,
             shader_storage_buffer_offsets,
             shader_storage_buffer_sizes,
@@ -305,6 +336,7 @@ impl<'a> StateBlocks<'a> {
             samplers,
             viewports,
             scissors,
+            stencil_reference: 0,
         }
     }
 
@@ -312,6 +344,7 @@ impl<'a> StateBlocks<'a> {
         self,
         arena: &'a GlArena,
         gl: &Gl,
+        native_multiview: bool,
         signature: &GlSignature,
     ) -> &'a GlArgumentBlock {
         let state_blocks = arena.other.alloc_uninitialized(signature.num_state_blocks);
@@ -327,6 +360,7 @@ impl<'a> StateBlocks<'a> {
                 buffers: self.uniform_buffers.as_ptr(),
                 offsets: self.uniform_buffer_offsets.as_ptr(),
                 sizes: self.uniform_buffer_sizes.as_ptr(),
+                host_references: self.uniform_buffer_host_references.as_ptr(),
             };
             i += 1;
         }
@@ -367,11 +401,11 @@ impl<'a> StateBlocks<'a> {
                 // build framebuffer
                 // put in arena so that it's deleted at the same time as the argument block
                 let fb = arena.framebuffers.alloc(
-                    GlFramebuffer::new(gl, &tmp_color[..], tmp_depth_stencil)
+                    GlFramebuffer::new(gl, &tmp_color[..], tmp_depth_stencil, native_multiview)
                         .expect("failed to create framebuffer"),
                 );
 
-                state_blocks[i] = StateBlock::Framebuffer(fb.obj);
+                state_blocks[i] = StateBlock::Framebuffer(fb);
                 i += 1;
             } else {
                 // TODO once the new constraint is in place, remove this
@@ -380,8 +414,7 @@ impl<'a> StateBlocks<'a> {
                         StateBlock::DepthStencilRenderTarget(self.depth_stencil_target.unwrap());
                     i += 1;
                 }
-                state_blocks[i] =
-                    StateBlock::RenderTarget(self.render_targets.as_ptr() as *const *const GlImage);
+                state_blocks[i] = StateBlock::RenderTarget(self.render_targets.as_ptr());
                 i += 1;
             }
         }
@@ -403,6 +436,10 @@ impl<'a> StateBlocks<'a> {
             state_blocks[i] = StateBlock::Scissors(self.scissors.as_ptr());
             i += 1;
         }
+        if signature.has_stencil_reference {
+            state_blocks[i] = StateBlock::StencilReference(self.stencil_reference);
+            i += 1;
+        }
         if signature.has_index_buffer {
             state_blocks[i] = StateBlock::IndexBuffer {
                 buffer: self.index_buffer,
@@ -412,9 +449,11 @@ impl<'a> StateBlocks<'a> {
             //i += 1;
         }
 
-        arena.other.alloc(GlArgumentBlock {
+        arena.argument_blocks.alloc(GlArgumentBlock {
             signature: signature as *const GlSignature,
             blocks: state_blocks.as_ptr(),
+            references: self.references,
+            tag: ArenaTag::new(&arena.generation),
         })
     }
 }
@@ -425,10 +464,13 @@ impl<'a> StateBlocks<'a> {
 /// Validity of the pointers:
 /// - the signature lives at least as long as the arguments (guaranteed by the arena lifetime in the instance API).
 /// - the blocks are allocated on the same arena as the object.
-#[derive(Copy, Clone, Debug)]
+#[derive(Debug)]
 pub struct GlArgumentBlock {
     pub(crate) signature: *const GlSignature,
     pub(crate) blocks: *const StateBlock,
+    /// Tags of the images, buffers and buffer views referenced by the state blocks.
+    pub(crate) references: TrackedReferences,
+    pub(crate) tag: ArenaTag,
 }
 
 unsafe impl Sync for GlArgumentBlock {}
@@ -444,11 +486,30 @@ fn copy_iter<T>(it: impl Iterator<Item = T>, out: &mut [T]) -> usize {
 }
 
 impl GlArgumentBlock {
+    /// Panics if the arena of the block, of its signature, or of one of the objects it
+    /// references (including through inherited blocks) was dropped.
+    ///
+    /// Unsafe: reads the tags through the raw pointers of the block.
+    pub(crate) unsafe fn check_tags(&self) {
+        self.tag.check("argument block");
+        let signature = &*self.signature;
+        signature.tag.check("signature");
+        self.references.check();
+        // sub-arguments must be the first block
+        if !signature.inherited.is_empty() {
+            if let StateBlock::Inherited(args) = *self.blocks {
+                for &a in slice::from_raw_parts(args, signature.inherited.len()) {
+                    (&*a).check_tags();
+                }
+            }
+        }
+    }
+
     /// Unsafe access to contents.
-    pub(crate) unsafe fn collect_render_targets<'a>(
-        &'a self,
-        color_targets: &mut smallvec::SmallVec<[&'a GlImage; 8]>,
-        depth_stencil_target: &mut Option<&'a GlImage>,
+    pub(crate) unsafe fn collect_render_targets(
+        &self,
+        color_targets: &mut smallvec::SmallVec<[Attachment; 8]>,
+        depth_stencil_target: &mut Option<Attachment>,
     ) {
         let signature = &*self.signature;
         // sub-arguments must be the first block
@@ -465,10 +526,10 @@ impl GlArgumentBlock {
             match block {
                 &StateBlock::RenderTarget(rt) => {
                     let num_targets = signature.num_render_targets;
-                    let rt = slice::from_raw_parts(rt as *const _, num_targets);
+                    let rt = slice::from_raw_parts(rt, num_targets);
                     color_targets.extend_from_slice(rt)
                 }
-                &StateBlock::DepthStencilRenderTarget(rt) => *depth_stencil_target = Some(&*rt),
+                &StateBlock::DepthStencilRenderTarget(rt) => *depth_stencil_target = Some(rt),
                 _ => {}
             }
         }
@@ -477,6 +538,7 @@ impl GlArgumentBlock {
     pub(crate) fn new<'a>(
         arena: &'a GlArena,
         gl: &Gl,
+        native_multiview: bool,
         sampler_cache: &mut SamplerCache,
         signature: &'a GlSignature,
         inherited: impl IntoIterator<Item = BareArgumentBlock<'a, OpenGlBackend>>,
@@ -487,6 +549,7 @@ impl GlArgumentBlock {
         depth_stencil_target: Option<DepthStencilView<'a, OpenGlBackend>>,
         viewports: impl IntoIterator<Item = Viewport>,
         scissors: impl IntoIterator<Item = Scissor>,
+        stencil_reference: Option<u32>,
     ) -> &'a GlArgumentBlock {
         let mut stb = unsafe { StateBlocks::new(arena, signature) };
 
@@ -499,7 +562,16 @@ impl GlArgumentBlock {
         for d in descriptors.into_iter() {
             match d {
                 Descriptor::Sampler { .. } => unimplemented!(),
-                Descriptor::Texture { .. } => unimplemented!(),
+                Descriptor::Texture { image, subresource } => {
+                    if subresource.base_array_layer != 0 || subresource.base_mip_level != 0 {
+                        unimplemented!("texture subresource views");
+                    }
+                    stb.references.push("image", &image.tag);
+                    stb.textures[i_textures_samplers] = image.raw().obj;
+                    // no sampler: texel fetches only
+                    stb.samplers[i_textures_samplers] = 0;
+                    i_textures_samplers += 1;
+                }
                 Descriptor::TextureSampler {
                     image,
                     subresource,
@@ -508,15 +580,40 @@ impl GlArgumentBlock {
                     if subresource.base_array_layer != 0 || subresource.base_mip_level != 0 {
                         unimplemented!("texture subresource views");
                     }
-                    stb.textures[i_textures_samplers] = image.raw.obj;
+                    stb.references.push("image", &image.tag);
+                    stb.textures[i_textures_samplers] = image.raw().obj;
                     stb.samplers[i_textures_samplers] = sampler_cache.get_sampler(gl, &sampler);
                     i_textures_samplers += 1;
                 }
+                Descriptor::TextureSamplerLod {
+                    image,
+                    subresource,
+                    sampler,
+                    lod,
+                } => {
+                    if subresource.base_array_layer != 0 {
+                        unimplemented!("texture subresource views");
+                    }
+                    stb.references.push("image", &image.tag);
+                    let raw = image.raw();
+                    lod.validate(&subresource, raw.mip_levels);
+                    // the whole texture is bound: the levels of the clamp are offset by the
+                    // base level of the view instead
+                    let lod = LodClamp {
+                        min_lod: subresource.base_mip_level + lod.min_lod,
+                        max_lod: subresource.base_mip_level + lod.max_lod,
+                    };
+                    stb.textures[i_textures_samplers] = raw.obj;
+                    stb.samplers[i_textures_samplers] =
+                        sampler_cache.get_sampler_with_lod_clamp(gl, &sampler, Some(lod));
+                    i_textures_samplers += 1;
+                }
                 Descriptor::RwImage { image, subresource } => {
                     if subresource.base_array_layer != 0 || subresource.base_mip_level != 0 {
                         unimplemented!("texture subresource views");
                     }
-                    stb.images[i_images] = image.raw.obj;
+                    stb.references.push("image", &image.tag);
+                    stb.images[i_images] = image.raw().obj;
                     i_images += 1;
                 }
                 Descriptor::ConstantBuffer {
@@ -524,11 +621,21 @@ impl GlArgumentBlock {
                     offset,
                     size,
                 } => {
+                    stb.references.push("buffer", &buffer.tag);
                     stb.uniform_buffers[i_uniform_buffers] = buffer.raw.obj;
                     stb.uniform_buffer_offsets[i_uniform_buffers] =
                         (buffer.offset + offset) as isize;
                     stb.uniform_buffer_sizes[i_uniform_buffers] =
                         size.unwrap_or(buffer.raw.size - offset) as isize;
+                    stb.uniform_buffer_host_references[i_uniform_buffers] = ptr::null();
+                    i_uniform_buffers += 1;
+                }
+                Descriptor::HostConstantBuffer { reference } => {
+                    // buffer and offset are patched when binding
+                    stb.uniform_buffers[i_uniform_buffers] = 0;
+                    stb.uniform_buffer_offsets[i_uniform_buffers] = 0;
+                    stb.uniform_buffer_sizes[i_uniform_buffers] = reference.size as isize;
+                    stb.uniform_buffer_host_references[i_uniform_buffers] = reference;
                     i_uniform_buffers += 1;
                 }
                 Descriptor::RwBuffer {
@@ -536,6 +643,7 @@ impl GlArgumentBlock {
                     offset,
                     size,
                 } => {
+                    stb.references.push("buffer", &buffer.tag);
                     stb.shader_storage_buffers[i_shader_storage_buffers] = buffer.raw.obj;
                     stb.shader_storage_buffer_offsets[i_shader_storage_buffers] =
                         (buffer.offset + offset) as isize;
@@ -543,7 +651,13 @@ impl GlArgumentBlock {
                         size.unwrap_or(buffer.raw.size - offset) as isize;
                     i_shader_storage_buffers += 1;
                 }
-                Descriptor::TexelBuffer { .. } => unimplemented!(),
+                Descriptor::TexelBuffer { view } => {
+                    stb.references.push("buffer view", &view.tag);
+                    // buffer textures are bound like other textures
+                    stb.textures[i_textures_samplers] = view.obj;
+                    stb.samplers[i_textures_samplers] = 0;
+                    i_textures_samplers += 1;
+                }
                 Descriptor::RwTexelBuffer { .. } => unimplemented!(),
                 Descriptor::Empty => unimplemented!(),
             }
@@ -551,22 +665,43 @@ impl GlArgumentBlock {
 
         let mut i_vertex_buffers = 0;
         for v in vertex_buffers.into_iter() {
+            stb.references.push("buffer", &v.buffer().tag);
             stb.vertex_buffers[i_vertex_buffers] = v.buffer().raw.obj;
             stb.vertex_buffer_offsets[i_vertex_buffers] = (v.offset() + v.buffer().offset) as isize;
             stb.vertex_buffer_strides[i_vertex_buffers] = v.stride() as i32;
             i_vertex_buffers += 1;
         }
 
+        let references = &mut stb.references;
         let i_render_targets = copy_iter(
-            render_targets.into_iter().map(|rt| rt.inner()),
+            render_targets.into_iter().enumerate().map(|(i, rt)| {
+                references.push("image", &rt.inner().tag);
+                let output = &signature.fragment_outputs[i];
+                Attachment::new(
+                    rt.inner(),
+                    rt.subresource(),
+                    output.load_op,
+                    output.store_op,
+                )
+            }),
             stb.render_targets,
         );
 
         if let Some(ds) = depth_stencil_target {
-            stb.depth_stencil_target = Some(ds.inner());
+            stb.references.push("image", &ds.inner().tag);
+            let output = signature
+                .depth_stencil_fragment_output
+                .expect("unexpected depth-stencil render target");
+            stb.depth_stencil_target = Some(Attachment::new(
+                ds.inner(),
+                ds.subresource(),
+                output.load_op,
+                output.store_op,
+            ));
         }
 
         if let Some(ib) = index_buffer {
+            stb.references.push("buffer", &ib.buffer.tag);
             stb.index_buffer = ib.buffer.raw.obj;
             stb.index_format = ib.format;
             stb.index_offset = ib.buffer.offset + ib.offset;
@@ -574,6 +709,9 @@ impl GlArgumentBlock {
 
         let i_viewports = copy_iter(viewports.into_iter(), stb.viewports);
         let i_scissors = copy_iter(scissors.into_iter(), stb.scissors);
+        if let Some(reference) = stencil_reference {
+            stb.stencil_reference = reference;
+        }
 
         assert_eq!(i_inherited, signature.inherited.len());
         assert_eq!(i_uniform_buffers, signature.num_uniform_buffers);
@@ -587,7 +725,8 @@ impl GlArgumentBlock {
         assert_eq!(i_images, signature.num_images);
         assert_eq!(i_viewports, signature.num_viewports);
         assert_eq!(i_scissors, signature.num_scissors);
+        assert_eq!(stencil_reference.is_some(), signature.has_stencil_reference);
 
-        unsafe { stb.into_argument_block(arena, gl, signature) }
+        unsafe { stb.into_argument_block(arena, gl, native_multiview, signature) }
     }
 }
@@ -1,32 +1,35 @@
 use crate::{
+    api as gl,
     api::{types::*, Gl},
     backend::{GlArena, OpenGlBackend},
     command::StateCache,
+    tracking::ArenaTag,
 };
 use autograph_api::{
     image::SamplerDescription,
     pipeline::{
         ColorBlendAttachmentState, ColorBlendAttachments, DepthStencilState, InputAssemblyState,
-        LogicOp, MultisampleState, RasterisationState,
+        LineWidth, LogicOp, MultisampleState, PrimitiveTopology, RasterisationState,
     },
 };
 use ordered_float::NotNan;
+use std::time::{Duration, Instant};
 
 mod arguments;
 mod program;
 mod shader;
 mod vao;
 
-use self::program::create_graphics_program;
+use self::program::{create_compute_program, create_graphics_program};
 
 pub(crate) use self::{
     arguments::{GlArgumentBlock, GlSignature, StateBlock},
     shader::{DescriptorMap, GlShaderModule},
 };
-use crate::format::GlFormatInfo;
+use crate::format::vertex_attrib_type;
 use autograph_api::pipeline::{
-    GraphicsPipelineCreateInfo, ScissorsOwned, SignatureDescription, VertexInputBinding,
-    ViewportsOwned,
+    validate::vertex_attribute_locations, GraphicsPipelineCreateInfo, ScissorsOwned,
+    SignatureDescription, VertexInputBinding, ViewportsOwned,
 };
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -57,11 +60,21 @@ pub struct GlGraphicsPipeline {
     pub(crate) input_assembly_state: InputAssemblyState,
     //pub(crate) vertex_input_bindings: Vec<VertexInputBinding>,
     pub(crate) color_blend_state: PipelineColorBlendStateOwned,
+    /// Whether the pipeline has a fragment shader. Color writes are disabled otherwise
+    /// (depth-only rendering, e.g. shadow maps).
+    pub(crate) has_fragment_shader: bool,
     pub(crate) descriptor_map: DescriptorMap,
     pub(crate) viewports: ViewportsOwned,
     pub(crate) scissors: ScissorsOwned,
+    /// Number of views rendered by each draw.
+    pub(crate) view_count: u32,
     pub(crate) program: GLuint,
     pub(crate) vao: GLuint,
+    /// Number of vertex buffer bindings of the VAO.
+    pub(crate) vertex_binding_count: usize,
+    /// Time spent compiling and linking the program.
+    pub(crate) create_time: Duration,
+    pub(crate) tag: ArenaTag,
 }
 
 impl GlGraphicsPipeline {
@@ -72,12 +85,15 @@ impl GlGraphicsPipeline {
 
 /// Converts a sequence of VertexInputBinding (one for each vertex buffer) into a VAO.
 ///
-/// This function generates vertex attributes for each element in all layouts,
-/// and laid out sequentially : i.e. if buffer #0 has 4 elements,
-/// and buffer #1 has 2 elements, then 6 attributes will be generated:
-/// attributes 0..=3 will map to vertex buffer 0 and attributes 4..=5 will map to vertex buffer 1.
-pub(crate) fn create_vertex_array_object(gl: &Gl, bindings: &[VertexInputBinding]) -> GLuint {
-    let mut location = 0;
+/// This function generates vertex attributes for each element in all layouts, at the locations
+/// given by `locations` (one entry per element, see `vertex_attribute_locations`). Elements
+/// without a location are not bound.
+pub(crate) fn create_vertex_array_object(
+    gl: &Gl,
+    bindings: &[VertexInputBinding],
+    locations: &[Option<u32>],
+) -> GLuint {
+    let mut locations = locations.iter();
 
     let mut vao = 0;
     unsafe {
@@ -85,24 +101,26 @@ pub(crate) fn create_vertex_array_object(gl: &Gl, bindings: &[VertexInputBinding
     }
 
     for (binding_index, &binding) in bindings.iter().enumerate() {
-        if let Some(base_location) = binding.base_location {
-            assert!(base_location >= location);
-            location = base_location;
-        }
         for &e in binding.layout.elements.iter() {
+            let location = if let Some(&Some(location)) = locations.next() {
+                location
+            } else {
+                continue;
+            };
             unsafe {
                 gl.EnableVertexArrayAttrib(vao, location);
                 let fmtinfo = e.format.get_format_info();
                 let normalized = fmtinfo.is_normalized() as u8;
                 let size = fmtinfo.num_components() as i32;
-                let glfmt = GlFormatInfo::from_format(e.format);
-                let ty = glfmt.upload_ty;
+                let (ty, integer) = vertex_attrib_type(e.format);
 
-                gl.VertexArrayAttribFormat(vao, location, size, ty, normalized, e.offset);
+                if integer {
+                    gl.VertexArrayAttribIFormat(vao, location, size, ty, e.offset);
+                } else {
+                    gl.VertexArrayAttribFormat(vao, location, size, ty, normalized, e.offset);
+                }
                 gl.VertexArrayAttribBinding(vao, location, binding_index as u32);
             }
-
-            location += 1;
         }
     }
 
@@ -127,6 +145,7 @@ pub(crate) unsafe fn create_graphics_pipeline_internal<'a>(
     root_signature_description: &SignatureDescription,
     ci: &GraphicsPipelineCreateInfo<'a, '_, OpenGlBackend>,
 ) -> &'a GlGraphicsPipeline {
+    let start = Instant::now();
     let (program, descriptor_map) = {
         let vs = ci.shader_stages.vertex.inner();
         let fs = ci.shader_stages.fragment.map(|s| s.inner());
@@ -135,12 +154,17 @@ pub(crate) unsafe fn create_graphics_pipeline_internal<'a>(
         let tes = ci.shader_stages.tess_eval.map(|s| s.inner());
         create_graphics_program(gl, vs, fs, gs, tcs, tes).expect("failed to create program")
     };
+    let create_time = start.elapsed();
 
     // collect vertex bindings
     // TODO should be in the same argblock anyway
     let mut vertex_bindings = Vec::new();
     collect_vertex_bindings(root_signature_description, &mut vertex_bindings);
-    let vao = create_vertex_array_object(gl, &vertex_bindings);
+    let locations = vertex_attribute_locations(
+        root_signature_description,
+        ci.shader_stages.vertex.reflection().vertex_input_attributes,
+    );
+    let vao = create_vertex_array_object(gl, &vertex_bindings, &locations);
 
     /*    // count number of viewports
     let num_viewports = match ci.viewport_state.viewports {
@@ -170,10 +194,15 @@ pub(crate) unsafe fn create_graphics_pipeline_internal<'a>(
         //vertex_input_bindings,
         program,
         vao,
+        vertex_binding_count: vertex_bindings.len(),
+        create_time,
         descriptor_map,
         color_blend_state,
+        has_fragment_shader: ci.shader_stages.fragment.is_some(),
         viewports: ci.viewport_state.viewports.into(),
         scissors: ci.viewport_state.scissors.into(),
+        view_count: ci.view_count.0,
+        tag: ArenaTag::new(&arena.generation),
     };
 
     arena.graphics_pipelines.alloc(g)
@@ -185,10 +214,22 @@ impl GlGraphicsPipeline {
         state_cache.set_vertex_array(gl, self.vao);
         state_cache.set_cull_mode(gl, self.rasterization_state.cull_mode);
         state_cache.set_polygon_mode(gl, self.rasterization_state.polygon_mode);
-        state_cache.set_stencil_test(gl, &self.depth_stencil_state.stencil_test);
+        if let LineWidth::Static(width) = self.rasterization_state.line_width {
+            state_cache.set_line_width(gl, width.into_inner());
+        }
+        state_cache.set_line_smooth_enable(gl, self.rasterization_state.line_smooth_enable);
+        state_cache.set_point_size(gl, self.rasterization_state.point_size);
+        state_cache
+            .set_primitive_restart_enable(gl, self.input_assembly_state.primitive_restart_enable);
+        state_cache.set_multisample_state(gl, &self.multisample_state);
+        // the stencil test of pipelines with a dynamic reference is set with the arguments
+        if !self.depth_stencil_state.dynamic_stencil_reference {
+            state_cache.set_stencil_test(gl, &self.depth_stencil_state.stencil_test);
+        }
         state_cache.set_depth_test_enable(gl, self.depth_stencil_state.depth_test_enable);
         state_cache.set_depth_write_enable(gl, self.depth_stencil_state.depth_write_enable);
         state_cache.set_depth_compare_op(gl, self.depth_stencil_state.depth_compare_op);
+        state_cache.set_color_write_enable(gl, self.has_fragment_shader);
         match self.color_blend_state.attachments {
             PipelineColorBlendAttachmentsOwned::All(ref state) => {
                 state_cache.set_all_blend(gl, state)
@@ -207,4 +248,93 @@ impl GlGraphicsPipeline {
             state_cache.set_scissors(gl, sc);
         }
     }
+
+    /// Issues a draw with the pipeline to force the driver to compile the program for its
+    /// state, and waits for completion. Returns the time spent.
+    ///
+    /// Rasterization is disabled during the draw, and all vertex buffer bindings read from
+    /// `scratch_buffer` (with a zero stride). Other resources are left unbound: the shaders run
+    /// once per vertex of a single primitive, and their results are discarded.
+    pub(crate) fn warm_up(
+        &self,
+        gl: &Gl,
+        state_cache: &mut StateCache,
+        scratch_buffer: GLuint,
+    ) -> Duration {
+        let start = Instant::now();
+        self.bind(gl, state_cache);
+        let vertex_count = match self.input_assembly_state.topology {
+            PrimitiveTopology::PointList => 1,
+            PrimitiveTopology::LineList | PrimitiveTopology::LineStrip => 2,
+            PrimitiveTopology::TriangleList
+            | PrimitiveTopology::TriangleStrip
+            | PrimitiveTopology::TriangleFan => 3,
+        };
+        unsafe {
+            for i in 0..self.vertex_binding_count {
+                gl.VertexArrayVertexBuffer(self.vao, i as u32, scratch_buffer, 0, 0);
+            }
+            gl.Enable(gl::RASTERIZER_DISCARD);
+        }
+        state_cache.draw(
+            gl,
+            self.input_assembly_state.topology,
+            vertex_count,
+            1,
+            0,
+            0,
+        );
+        unsafe {
+            gl.Disable(gl::RASTERIZER_DISCARD);
+            gl.Finish();
+        }
+        start.elapsed()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+#[derive(Clone, Debug)]
+pub struct GlComputePipeline {
+    pub(crate) descriptor_map: DescriptorMap,
+    pub(crate) program: GLuint,
+    /// Time spent compiling and linking the program.
+    pub(crate) create_time: Duration,
+    pub(crate) tag: ArenaTag,
+}
+
+pub(crate) unsafe fn create_compute_pipeline_internal<'a>(
+    gl: &Gl,
+    arena: &'a GlArena,
+    _root_signature: &'a GlSignature,
+    _root_signature_description: &SignatureDescription,
+    shader: &GlShaderModule,
+) -> &'a GlComputePipeline {
+    let start = Instant::now();
+    let (program, descriptor_map) =
+        create_compute_program(gl, shader).expect("failed to create program");
+    arena.compute_pipelines.alloc(GlComputePipeline {
+        descriptor_map,
+        program,
+        create_time: start.elapsed(),
+        tag: ArenaTag::new(&arena.generation),
+    })
+}
+
+impl GlComputePipeline {
+    pub(crate) fn bind(&self, gl: &Gl, state_cache: &mut StateCache) {
+        state_cache.set_program(gl, self.program);
+    }
+
+    /// Binds the program and waits for the completion of its compilation.
+    ///
+    /// Compute programs do not depend on other states: unlike graphics pipelines, there is no need
+    /// to dispatch anything.
+    pub(crate) fn warm_up(&self, gl: &Gl, state_cache: &mut StateCache) -> Duration {
+        let start = Instant::now();
+        self.bind(gl, state_cache);
+        unsafe {
+            gl.Finish();
+        }
+        start.elapsed()
+    }
 }
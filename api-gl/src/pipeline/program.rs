@@ -192,3 +192,34 @@ pub(crate) fn create_graphics_program(
         Ok((program, dm))
     }
 }
+
+pub(crate) fn create_compute_program(
+    gl: &Gl,
+    comp: &GlShaderModule,
+) -> Result<(GLuint, DescriptorMap), ProgramCreationError> {
+    let comp = if let Some(ref spirv) = comp.spirv {
+        spirv
+    } else {
+        unimplemented!("descriptor map for GLSL compilation path")
+    };
+
+    let mut dmb = DescriptorMapBuilder::new();
+    let cs = {
+        let comp = translate_spirv_to_gl_flavor(comp, ShaderStageFlags::COMPUTE, &mut dmb);
+        create_specialized_spirv_shader(gl, ShaderStageFlags::COMPUTE, "main", &comp)?
+    };
+    let dm = dmb.into();
+    debug!("inferred descriptor map: {:#?}", dm);
+
+    unsafe {
+        let program = gl.CreateProgram();
+        gl.AttachShader(program, cs);
+        let result = link_program(gl, program);
+        gl.DeleteShader(cs);
+        result.map_err(|log| {
+            gl.DeleteProgram(program);
+            ProgramCreationError(format!("program link error: {}", log))
+        })?;
+        Ok((program, dm))
+    }
+}
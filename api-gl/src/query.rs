@@ -0,0 +1,32 @@
+use crate::{
+    api as gl,
+    api::{types::*, Gl},
+};
+
+/// OpenGL query object.
+///
+/// Occlusion queries are `GL_ANY_SAMPLES_PASSED` queries.
+#[derive(Debug)]
+pub struct GlQuery {
+    pub(crate) obj: GLuint,
+    pub(crate) target: GLenum,
+}
+
+impl GlQuery {
+    pub(crate) fn new_occlusion(gl: &Gl) -> GlQuery {
+        let mut obj = 0;
+        unsafe {
+            gl.CreateQueries(gl::ANY_SAMPLES_PASSED, 1, &mut obj);
+        }
+        GlQuery {
+            obj,
+            target: gl::ANY_SAMPLES_PASSED,
+        }
+    }
+
+    pub(crate) fn destroy(&self, gl: &Gl) {
+        unsafe {
+            gl.DeleteQueries(1, &self.obj);
+        }
+    }
+}
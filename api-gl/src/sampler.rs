@@ -2,12 +2,14 @@ use crate::{
     api as gl,
     api::{types::*, Gl},
 };
-use autograph_api::image::{Filter, SamplerAddressMode, SamplerDescription, SamplerMipmapMode};
+use autograph_api::image::{
+    Filter, LodClamp, SamplerAddressMode, SamplerDescription, SamplerMipmapMode,
+};
 use fxhash::{FxBuildHasher, FxHashMap};
 
 pub struct SamplerCache {
     // samplers are never deleted
-    samplers: FxHashMap<SamplerDescription, GLuint>,
+    samplers: FxHashMap<(SamplerDescription, Option<LodClamp>), GLuint>,
 }
 
 impl SamplerCache {
@@ -18,7 +20,18 @@ impl SamplerCache {
     }
 
     pub fn get_sampler(&mut self, gl: &Gl, desc: &SamplerDescription) -> GLuint {
-        *self.samplers.entry(desc.clone()).or_insert_with(|| unsafe {
+        self.get_sampler_with_lod_clamp(gl, desc, None)
+    }
+
+    /// Returns a sampler that only selects the levels of detail in `lod`, if specified
+    /// (`GL_TEXTURE_MIN_LOD` and `GL_TEXTURE_MAX_LOD`).
+    pub fn get_sampler_with_lod_clamp(
+        &mut self,
+        gl: &Gl,
+        desc: &SamplerDescription,
+        lod: Option<LodClamp>,
+    ) -> GLuint {
+        *self.samplers.entry((*desc, lod)).or_insert_with(|| unsafe {
             let mut obj = 0;
             gl.GenSamplers(1, &mut obj);
             gl.SamplerParameteri(
@@ -46,6 +59,10 @@ impl SamplerCache {
                 gl::TEXTURE_WRAP_T,
                 address_mode_to_glenum(desc.addr_w) as i32,
             );
+            if let Some(lod) = lod {
+                gl.SamplerParameterf(obj, gl::TEXTURE_MIN_LOD, lod.min_lod as f32);
+                gl.SamplerParameterf(obj, gl::TEXTURE_MAX_LOD, lod.max_lod as f32);
+            }
             obj
         })
     }
@@ -1,4 +1,5 @@
-use autograph_api::traits;
+use crate::api::types::*;
+use autograph_api::{command::PresentEncoding, format::ColorSpace, traits};
 use glutin::GlWindow;
 use std::{fmt, sync::Arc};
 
@@ -8,18 +9,65 @@ use std::{fmt, sync::Arc};
 /// underlying window system. This type wraps around window handles and provides an interface
 /// for getting the size of the swapchain (default framebuffer) and present an image to the screen
 /// (swap buffers).
+///
+/// It can also wrap a framebuffer object owned by a host application
+/// (see `OpenGlInstance::import_framebuffer`): presenting to it only copies the image into the
+/// framebuffer, swapping buffers is left to the host.
 pub struct GlSwapchain {
-    pub(crate) window: Arc<GlWindow>,
+    pub(crate) target: SwapchainTarget,
+    pub(crate) color_space: ColorSpace,
+    /// Default conversion of the presented images (`InstanceConfig::present_encoding`).
+    pub(crate) present_encoding: PresentEncoding,
+}
+
+pub(crate) enum SwapchainTarget {
+    /// Default framebuffer of a window.
+    Window(Arc<GlWindow>),
+    /// Default framebuffer of a window whose context shares objects with the context of the
+    /// main window of the instance (see `OpenGlInstance::create_window_swapchain`).
+    SharedWindow {
+        window: Arc<GlWindow>,
+        main: Arc<GlWindow>,
+    },
+    /// Framebuffer object owned by another application.
+    Framebuffer { obj: GLuint, size: (u32, u32) },
+}
+
+impl GlSwapchain {
+    /// Returns the framebuffer object that presented images are copied into.
+    pub(crate) fn framebuffer(&self) -> GLuint {
+        match self.target {
+            SwapchainTarget::Window(_) | SwapchainTarget::SharedWindow { .. } => 0,
+            SwapchainTarget::Framebuffer { obj, .. } => obj,
+        }
+    }
 }
 
 impl fmt::Debug for GlSwapchain {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f, "Swapchain {{..}}")
+        match self.target {
+            SwapchainTarget::Window(_) | SwapchainTarget::SharedWindow { .. } => {
+                write!(f, "Swapchain {{..}}")
+            }
+            SwapchainTarget::Framebuffer { obj, size } => {
+                write!(f, "Swapchain {{ framebuffer: {}, size: {:?} }}", obj, size)
+            }
+        }
     }
 }
 
 impl traits::Swapchain for GlSwapchain {
     fn size(&self) -> (u32, u32) {
-        self.window.get_inner_size().unwrap().into()
+        match self.target {
+            SwapchainTarget::Window(ref window)
+            | SwapchainTarget::SharedWindow { ref window, .. } => {
+                window.get_inner_size().unwrap().into()
+            }
+            SwapchainTarget::Framebuffer { size, .. } => size,
+        }
+    }
+
+    fn color_space(&self) -> ColorSpace {
+        self.color_space
     }
 }
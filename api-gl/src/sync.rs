@@ -49,6 +49,16 @@ impl<T> GpuSyncObject<T> {
         self.wait_timeout(gl, 0)
     }
 
+    /// Extracts the wrapper object, making all subsequent commands of the current context wait
+    /// on the GPU until the fence is signalled. Does not block the application.
+    ///
+    /// The fence may have been signalled by another context sharing objects with the current one.
+    pub unsafe fn into_inner_server_wait(self, gl: &Gl) -> T {
+        gl.WaitSync(self.sync, 0, gl::TIMEOUT_IGNORED);
+        gl.DeleteSync(self.sync);
+        self.obj
+    }
+
     /// Extracts the wrapper object without waiting for the driver.
     pub unsafe fn into_inner_unsynchronized(self, gl: &Gl) -> T {
         gl.DeleteSync(self.sync);
@@ -121,6 +131,16 @@ impl Timeline {
         self.sync_points.push_back(SyncPoint { sync, value });
     }
 
+    /// Returns whether all signal operations have completed, without blocking.
+    pub fn is_idle(&mut self, gl: &Gl) -> bool {
+        let last = self
+            .sync_points
+            .back()
+            .map(|sp| sp.value)
+            .unwrap_or(self.current_value);
+        self.client_sync(gl, last, Duration::from_secs(0))
+    }
+
     /// Waits for the given value to be reached (on the application side).
     ///
     /// Timeout is for a single ClientWaitSync only: there may be more than one.
@@ -0,0 +1,191 @@
+//! Detection of uses of objects after the arena that owns them is dropped.
+//!
+//! Resources are borrowed from their arena, so this cannot happen in safe code. It can happen
+//! with transmuted lifetimes, or with pointers cached outside of an arena. With the
+//! `arena-tracking` feature:
+//! - each arena gets a unique generation number, which stays live until the arena is dropped;
+//! - images, buffers, buffer views, pipelines, signatures and argument blocks are tagged with
+//!   the generation of their arena and the backtrace of their creation;
+//! - argument blocks also record the tags of the objects they reference (images, buffers and
+//!   buffer views), since the backend only keeps the GL names of these objects, and inherited
+//!   blocks are checked recursively;
+//! - dropped arenas are kept for a few frames (see `RETIRED_ARENA_FRAMES`), so that the tags of
+//!   their objects can still be read;
+//! - the objects referenced by the commands are checked on each submission, which panics with the
+//!   creation backtrace of the first object whose arena was dropped.
+//!
+//! Without the feature, tags are empty and the checks do nothing.
+use std::fmt;
+
+#[cfg(feature = "arena-tracking")]
+use crate::backend::OpenGlBackend;
+#[cfg(feature = "arena-tracking")]
+use autograph_api::command::{Command, CommandInner};
+#[cfg(feature = "arena-tracking")]
+use backtrace::Backtrace;
+#[cfg(feature = "arena-tracking")]
+use lazy_static::lazy_static;
+#[cfg(feature = "arena-tracking")]
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+/// Number of frames during which the objects of a dropped arena are kept alive.
+#[cfg(feature = "arena-tracking")]
+pub(crate) const RETIRED_ARENA_FRAMES: u64 = 8;
+
+#[cfg(feature = "arena-tracking")]
+static NEXT_GENERATION: AtomicUsize = AtomicUsize::new(1);
+
+#[cfg(feature = "arena-tracking")]
+lazy_static! {
+    /// Generations of the arenas that have not been dropped.
+    static ref LIVE_GENERATIONS: Mutex<HashSet<usize>> = Mutex::new(HashSet::new());
+}
+
+/// Generation number of an arena.
+pub(crate) struct ArenaGeneration {
+    #[cfg(feature = "arena-tracking")]
+    generation: usize,
+}
+
+impl ArenaGeneration {
+    pub(crate) fn new() -> ArenaGeneration {
+        #[cfg(feature = "arena-tracking")]
+        {
+            let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+            LIVE_GENERATIONS.lock().unwrap().insert(generation);
+            ArenaGeneration { generation }
+        }
+        #[cfg(not(feature = "arena-tracking"))]
+        {
+            ArenaGeneration {}
+        }
+    }
+
+    /// Marks the objects of the arena as dead.
+    pub(crate) fn retire(&self) {
+        #[cfg(feature = "arena-tracking")]
+        {
+            LIVE_GENERATIONS.lock().unwrap().remove(&self.generation);
+        }
+    }
+}
+
+/// Generation of the arena of an object, and where the object was created.
+#[derive(Clone)]
+pub(crate) struct ArenaTag {
+    #[cfg(feature = "arena-tracking")]
+    generation: usize,
+    #[cfg(feature = "arena-tracking")]
+    backtrace: Backtrace,
+}
+
+impl ArenaTag {
+    pub(crate) fn new(arena: &ArenaGeneration) -> ArenaTag {
+        #[cfg(feature = "arena-tracking")]
+        {
+            // symbols are only resolved if the check fails
+            ArenaTag {
+                generation: arena.generation,
+                backtrace: Backtrace::new_unresolved(),
+            }
+        }
+        #[cfg(not(feature = "arena-tracking"))]
+        {
+            let _ = arena;
+            ArenaTag {}
+        }
+    }
+
+    /// Panics if the arena of the object was dropped.
+    pub(crate) fn check(&self, kind: &str) {
+        #[cfg(feature = "arena-tracking")]
+        {
+            if !LIVE_GENERATIONS.lock().unwrap().contains(&self.generation) {
+                let mut backtrace = self.backtrace.clone();
+                backtrace.resolve();
+                panic!(
+                    "{} used after its arena (generation {}) was dropped; created at:\n{:?}",
+                    kind, self.generation, backtrace
+                );
+            }
+        }
+        #[cfg(not(feature = "arena-tracking"))]
+        {
+            let _ = kind;
+        }
+    }
+}
+
+/// Tags of the objects referenced by an argument block.
+///
+/// The tags are read through pointers: they stay valid while the arena of the object is alive or
+/// retired (see `RETIRED_ARENA_FRAMES`).
+#[derive(Default, Debug)]
+pub(crate) struct TrackedReferences {
+    #[cfg(feature = "arena-tracking")]
+    objects: Vec<(&'static str, *const ArenaTag)>,
+}
+
+impl TrackedReferences {
+    pub(crate) fn push(&mut self, kind: &'static str, tag: &ArenaTag) {
+        #[cfg(feature = "arena-tracking")]
+        {
+            self.objects.push((kind, tag));
+        }
+        #[cfg(not(feature = "arena-tracking"))]
+        {
+            let _ = (kind, tag);
+        }
+    }
+
+    /// Panics if the arena of one of the objects was dropped.
+    pub(crate) unsafe fn check(&self) {
+        #[cfg(feature = "arena-tracking")]
+        {
+            for &(kind, tag) in self.objects.iter() {
+                (*tag).check(kind);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ArenaTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        #[cfg(feature = "arena-tracking")]
+        {
+            write!(f, "ArenaTag({})", self.generation)
+        }
+        #[cfg(not(feature = "arena-tracking"))]
+        {
+            f.write_str("ArenaTag")
+        }
+    }
+}
+
+/// Checks the tags of the objects referenced by the commands of a frame, directly or through
+/// argument blocks.
+#[cfg(feature = "arena-tracking")]
+pub(crate) fn check_commands(commands: &[Command<OpenGlBackend>]) {
+    for command in commands.iter() {
+        match command.cmd {
+            CommandInner::ClearImageFloat { image, .. }
+            | CommandInner::ClearImageUint { image, .. }
+            | CommandInner::ClearImageInt { image, .. }
+            | CommandInner::ClearDepthStencilImage { image, .. }
+            | CommandInner::CopyImageToHost { image, .. }
+            | CommandInner::Present { image, .. } => image.tag.check("image"),
+            CommandInner::CopyBufferToHost { buffer, .. }
+            | CommandInner::DispatchIndirect { buffer, .. } => buffer.tag.check("buffer"),
+            CommandInner::DrawHeader { pipeline } => pipeline.tag.check("graphics pipeline"),
+            CommandInner::DispatchHeader { pipeline } => pipeline.tag.check("compute pipeline"),
+            CommandInner::SetPipelineArguments { arguments } => unsafe { arguments.check_tags() },
+            _ => {}
+        }
+    }
+}
@@ -1,26 +1,49 @@
 use crate::backend::{InstanceConfig, OpenGlInstance};
-use glutin::{self, GlWindow};
+use autograph_api::format::ColorSpace;
+use glutin::{self, GlContext, GlWindow};
 use std::sync::Arc;
 use winit::{EventsLoop, WindowBuilder};
 
-pub fn create_instance_and_window(
-    cfg: &InstanceConfig,
-    events_loop: &EventsLoop,
-    window_builder: WindowBuilder,
-) -> (OpenGlInstance, Arc<GlWindow>) {
+fn context_builder<'a>(cfg: &InstanceConfig) -> glutin::ContextBuilder<'a> {
     // TODO get config from config file
-    let context_builder = glutin::ContextBuilder::new()
+    glutin::ContextBuilder::new()
         .with_gl_profile(glutin::GlProfile::Core)
         .with_gl_debug_flag(true)
         //.with_vsync(true)
-        //.with_srgb(true)
-        .with_gl(glutin::GlRequest::Specific(glutin::Api::OpenGl, (4, 6)));
+        .with_srgb(cfg.swapchain_color_space == ColorSpace::Srgb)
+        .with_gl(glutin::GlRequest::Specific(glutin::Api::OpenGl, (4, 6)))
+}
 
+pub fn create_instance_and_window(
+    cfg: &InstanceConfig,
+    events_loop: &EventsLoop,
+    window_builder: WindowBuilder,
+) -> (OpenGlInstance, Arc<GlWindow>) {
     let window = Arc::new(
-        glutin::GlWindow::new(window_builder, context_builder, events_loop)
+        glutin::GlWindow::new(window_builder, context_builder(cfg), events_loop)
             .expect("unable to create window"),
     );
 
     let inst = OpenGlInstance::from_gl_window(cfg, window.clone()).expect("failed to create instance");
     (inst, window)
 }
+
+/// Creates a window with a context that shares objects with the context of `main`.
+///
+/// The context of `main` is made current again before returning.
+pub(crate) fn create_shared_window(
+    cfg: &InstanceConfig,
+    events_loop: &EventsLoop,
+    window_builder: WindowBuilder,
+    main: &GlWindow,
+) -> Arc<GlWindow> {
+    let context_builder = context_builder(cfg).with_shared_lists(main.context());
+    let window = Arc::new(
+        glutin::GlWindow::new(window_builder, context_builder, events_loop)
+            .expect("unable to create window"),
+    );
+    unsafe {
+        main.make_current().unwrap();
+    }
+    window
+}
@@ -0,0 +1,304 @@
+//! Assertions on the sorted commands of a frame, for unit-testing code that records commands.
+//!
+//! ```ignore
+//! let mut cmdbuf = CommandBuffer::new_detached();
+//! renderer.render(&mut cmdbuf, ...);
+//! let capture = CommandCapture::from_command_buffers(iter::once(cmdbuf), &SubmitOptions::DEFAULT);
+//! assert_draws(&capture, 3);
+//! assert_order(&capture, present_after(draw()));
+//! ```
+//!
+//! Captures can also be retrieved after an actual submission with
+//! `Api::set_command_capture` and `Api::last_command_capture`.
+use autograph_api::capture::{CapturedCommand, CapturedCommandKind, CommandCapture};
+use std::fmt;
+
+/// A predicate on captured commands.
+pub struct Matcher {
+    description: String,
+    predicate: Box<dyn Fn(&CapturedCommand) -> bool>,
+}
+
+impl Matcher {
+    pub fn new(
+        description: impl Into<String>,
+        predicate: impl Fn(&CapturedCommand) -> bool + 'static,
+    ) -> Matcher {
+        Matcher {
+            description: description.into(),
+            predicate: Box::new(predicate),
+        }
+    }
+
+    pub fn matches(&self, cmd: &CapturedCommand) -> bool {
+        (self.predicate)(cmd)
+    }
+
+    /// Restricts the matcher to commands with the specified sortkey.
+    pub fn with_sortkey(self, sortkey: u64) -> Matcher {
+        let Matcher {
+            description,
+            predicate,
+        } = self;
+        Matcher {
+            description: format!("{} with sortkey {:#x}", description, sortkey),
+            predicate: Box::new(move |cmd| cmd.sortkey == sortkey && predicate(cmd)),
+        }
+    }
+
+    /// Returns a constraint that all commands matched by `self` are executed after all commands
+    /// matched by `other`.
+    pub fn after(self, other: Matcher) -> OrderConstraint {
+        OrderConstraint {
+            first: other,
+            then: self,
+        }
+    }
+}
+
+impl fmt::Debug for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.description)
+    }
+}
+
+/// Matches draws (indexed or not).
+pub fn draw() -> Matcher {
+    Matcher::new("draw", |cmd| cmd.kind.is_draw())
+}
+
+/// Matches dispatches (direct or indirect).
+pub fn dispatch() -> Matcher {
+    Matcher::new("dispatch", |cmd| cmd.kind.is_dispatch())
+}
+
+/// Matches presentation commands.
+pub fn present() -> Matcher {
+    Matcher::new("present", |cmd| match cmd.kind {
+        CapturedCommandKind::Present { .. } => true,
+        _ => false,
+    })
+}
+
+/// Matches manual memory barriers (see `CommandBuffer::barrier`).
+pub fn barrier() -> Matcher {
+    Matcher::new("barrier", |cmd| match cmd.kind {
+        CapturedCommandKind::PipelineBarrier { .. } => true,
+        _ => false,
+    })
+}
+
+/// Matches clears of color or depth-stencil render targets.
+pub fn clear() -> Matcher {
+    Matcher::new("clear", |cmd| match cmd.kind {
+        CapturedCommandKind::ClearImageFloat { .. }
+        | CapturedCommandKind::ClearImageUint { .. }
+        | CapturedCommandKind::ClearImageInt { .. }
+        | CapturedCommandKind::ClearDepthStencilImage { .. } => true,
+        _ => false,
+    })
+}
+
+/// A constraint on the relative order of two sets of commands. See [Matcher::after].
+#[derive(Debug)]
+pub struct OrderConstraint {
+    first: Matcher,
+    then: Matcher,
+}
+
+/// Returns a constraint that all presentation commands are executed after all commands
+/// matched by `other`.
+pub fn present_after(other: Matcher) -> OrderConstraint {
+    present().after(other)
+}
+
+fn dump(capture: &CommandCapture) -> String {
+    capture
+        .iter()
+        .enumerate()
+        .map(|(i, cmd)| format!("    {:4} [{:#x}] {:?}\n", i, cmd.sortkey, cmd.kind))
+        .collect()
+}
+
+/// Panics if the number of draws in the capture is not `count`.
+pub fn assert_draws(capture: &CommandCapture, count: usize) {
+    let actual = capture.draw_count();
+    assert!(
+        actual == count,
+        "expected {} draws, found {}; commands:\n{}",
+        count,
+        actual,
+        dump(capture)
+    );
+}
+
+/// Panics if the number of dispatches in the capture is not `count`.
+pub fn assert_dispatches(capture: &CommandCapture, count: usize) {
+    let actual = capture.dispatch_count();
+    assert!(
+        actual == count,
+        "expected {} dispatches, found {}; commands:\n{}",
+        count,
+        actual,
+        dump(capture)
+    );
+}
+
+/// Panics if the constraint is not satisfied, or if no commands match one side of
+/// the constraint.
+pub fn assert_order(capture: &CommandCapture, constraint: OrderConstraint) {
+    let last_first = capture
+        .commands
+        .iter()
+        .rposition(|cmd| constraint.first.matches(cmd));
+    let first_then = capture.position(|cmd| constraint.then.matches(cmd));
+    match (last_first, first_then) {
+        (Some(last_first), Some(first_then)) => assert!(
+            last_first < first_then,
+            "expected {:?} after {:?}, but command {} is before command {}; commands:\n{}",
+            constraint.then,
+            constraint.first,
+            first_then,
+            last_first,
+            dump(capture)
+        ),
+        (None, _) => panic!(
+            "no command matches {:?}; commands:\n{}",
+            constraint.first,
+            dump(capture)
+        ),
+        (_, None) => panic!(
+            "no command matches {:?}; commands:\n{}",
+            constraint.then,
+            dump(capture)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use autograph_api::{
+        command::{
+            BarrierDesc, Command, CommandBuffer, CommandInner, PresentEncoding, SubmitOptions,
+        },
+        image::Image2d,
+        swapchain::Swapchain,
+        DummyBackend,
+    };
+    use std::iter;
+
+    static OBJECT: () = ();
+
+    fn draw_command(sortkey: u64) -> Command<'static, DummyBackend> {
+        Command {
+            sortkey,
+            seq: 0,
+            cmd: CommandInner::Draw {
+                vertex_count: 3,
+                instance_count: 1,
+                first_vertex: 0,
+                first_instance: 0,
+            },
+            call_site: None,
+        }
+    }
+
+    fn recorded_frame() -> CommandCapture {
+        let image: Image2d<DummyBackend> = unsafe { Image2d::from_raw(&OBJECT) };
+        let mut cmdbuf = CommandBuffer::new_detached();
+        cmdbuf.present_image(0x10, image, Swapchain(&OBJECT));
+        cmdbuf.clear_render_target(0x0, image, &[0.0; 4]);
+        CommandCapture::from_command_buffers(iter::once(cmdbuf), &SubmitOptions::DEFAULT)
+    }
+
+    #[test]
+    fn test_capture_sorted() {
+        let capture = recorded_frame();
+        assert_eq!(capture.len(), 2);
+        assert_order(&capture, present_after(clear()));
+        assert_draws(&capture, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "no command matches draw")]
+    fn test_order_without_match() {
+        assert_order(&recorded_frame(), present_after(draw()));
+    }
+
+    #[test]
+    fn test_barrier_order() {
+        let mut cmdbuf = CommandBuffer::new_detached();
+        cmdbuf.barrier(0x1, BarrierDesc::STORAGE_BUFFER_READ_AFTER_WRITE);
+        let mut commands = autograph_api::command::sort_command_buffers(iter::once(cmdbuf));
+        commands.insert(0, draw_command(0x0));
+        commands.push(draw_command(0x2));
+        let capture = CommandCapture::from_commands(&commands);
+        assert_order(&capture, barrier().after(draw().with_sortkey(0x0)));
+        assert_order(&capture, draw().with_sortkey(0x2).after(barrier()));
+    }
+
+    #[test]
+    fn test_base_key() {
+        let image: Image2d<DummyBackend> = unsafe { Image2d::from_raw(&OBJECT) };
+        let mut post = CommandBuffer::new_detached().with_base_key(0x200);
+        post.present_image(0x1, image, Swapchain(&OBJECT));
+        let mut main = CommandBuffer::new_detached().with_base_key(0x100);
+        main.clear_render_target(0x1, image, &[0.0; 4]);
+        assert_eq!(main.base_key(), 0x100);
+
+        // the command buffers are submitted in the opposite order of their base keys
+        let capture = CommandCapture::from_command_buffers(
+            vec![post, main].into_iter(),
+            &SubmitOptions::DEFAULT,
+        );
+        assert_order(
+            &capture,
+            present()
+                .with_sortkey(0x201)
+                .after(clear().with_sortkey(0x101)),
+        );
+    }
+
+    #[test]
+    fn test_present_encoding() {
+        let image: Image2d<DummyBackend> = unsafe { Image2d::from_raw(&OBJECT) };
+        let mut cmdbuf = CommandBuffer::new_detached();
+        cmdbuf.present_image(0x0, image, Swapchain(&OBJECT));
+        cmdbuf.present_image_with_encoding(0x1, image, Swapchain(&OBJECT), PresentEncoding::Raw);
+        let capture =
+            CommandCapture::from_command_buffers(iter::once(cmdbuf), &SubmitOptions::DEFAULT);
+        let encodings: Vec<_> = capture
+            .iter()
+            .filter_map(|cmd| match cmd.kind {
+                CapturedCommandKind::Present { encoding, .. } => Some(encoding),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(encodings, &[PresentEncoding::Default, PresentEncoding::Raw]);
+    }
+
+    #[test]
+    fn test_draws() {
+        let capture = CommandCapture::from_commands(&[draw_command(0x1), draw_command(0x2)]);
+        assert_draws(&capture, 2);
+        assert_order(
+            &capture,
+            draw().with_sortkey(0x2).after(draw().with_sortkey(0x1)),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected present after draw")]
+    fn test_order_violated() {
+        let image: Image2d<DummyBackend> = unsafe { Image2d::from_raw(&OBJECT) };
+        let mut cmdbuf = CommandBuffer::new_detached();
+        cmdbuf.present_image(0x0, image, Swapchain(&OBJECT));
+        let mut commands = autograph_api::command::sort_command_buffers(iter::once(cmdbuf));
+        commands.push(draw_command(0x1));
+        assert_order(
+            &CommandCapture::from_commands(&commands),
+            present_after(draw()),
+        );
+    }
+}
@@ -2,17 +2,10 @@
 //! Boilerplate code for creating a window and an OpenGL context with winit/glutin.
 use glutin::{Event, EventsLoop, WindowEvent};
 
-pub type Backend = autograph_api_gl::OpenGlBackend;
-pub type Renderer = autograph_api::Api<Backend>;
-pub type Arena<'a> = autograph_api::Arena<'a, Backend>;
-pub type Buffer<'a, T> = autograph_api::buffer::Buffer<'a, Backend, T>;
-pub type Image<'a> = autograph_api::image::Image<'a, Backend>;
-pub type SampledImage<'a> = autograph_api::image::TextureImageView<'a, Backend>;
-pub type TypedGraphicsPipeline<'a, T> =
-    autograph_api::pipeline::TypedGraphicsPipeline<'a, Backend, T>;
-pub type TypedArgumentBlock<'a, T> = autograph_api::pipeline::TypedArgumentBlock<'a, Backend, T>;
-pub type TextureImageView<'a> = autograph_api::image::TextureImageView<'a, Backend>;
-pub type RenderTargetView<'a> = autograph_api::image::RenderTargetView<'a, Backend>;
+pub mod assertions;
+
+autograph_api::aliases!(pub autograph_api_gl::OpenGlBackend);
+pub type Renderer = Api;
 
 pub struct InnerLoop<'a> {
     _renderer: &'a Renderer,
@@ -105,7 +98,7 @@ mod tests {
             innerloop.run(|| {
                 let arena = renderer.create_arena();
                 let mut cmdbuf = renderer.create_command_buffer();
-                cmdbuf.present(0, img, renderer.default_swapchain().unwrap());
+                cmdbuf.present_image(0, img, renderer.default_swapchain().unwrap());
                 renderer.submit_frame(iter::once(cmdbuf));
             })
         })
@@ -0,0 +1,635 @@
+use crate::{
+    buffer::{WgpuBuffer, WgpuBufferView, WgpuHostReadback, WgpuHostReference},
+    command::SubmissionContext,
+    format::format_properties,
+    image::WgpuImage,
+    pipeline::{
+        DummyResources, WgpuArgumentBlock, WgpuComputePipeline, WgpuGraphicsPipeline,
+        WgpuShaderModule, WgpuSignature,
+    },
+    query::WgpuQuery,
+    sampler::SamplerCache,
+    swapchain::WgpuSwapchain,
+};
+use autograph_api::{
+    allocator::{AllocationKind, AllocationRequest},
+    clip::{ClipSpaceInfo, DepthRange, YDirection},
+    command::{ClearValue, Command, PresentEncoding},
+    descriptor::Descriptor,
+    format::{ColorSpace, Format, FormatProperties},
+    image::{
+        DepthStencilView, Dimensions, ImageRegionUpdate, ImageUsageFlags, MipmapsOption,
+        RenderTargetView,
+    },
+    pipeline::{
+        BareArgumentBlock, GraphicsPipelineCreateInfo, PipelineRef, Scissor, ShaderStageFlags,
+        SignatureDescription, Viewport,
+    },
+    report::{PipelineCompileTime, PipelineKind, SubmitStats},
+    traits::Swapchain,
+    vertex::{IndexBufferView, VertexBufferView},
+    AliasScope, ApiProfile, ArenaAllocator, ArenaOptions, Backend, DeviceLimits, Instance,
+};
+use std::{cell::RefCell, time::Duration};
+use typed_arena::Arena;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct WgpuBackend;
+
+impl Backend for WgpuBackend {
+    type Instance = WgpuInstance;
+    type Arena = WgpuArena;
+    type Swapchain = WgpuSwapchain;
+    type Image = WgpuImage;
+    type Buffer = WgpuBuffer;
+    type BufferView = WgpuBufferView;
+    type ShaderModule = WgpuShaderModule;
+    type GraphicsPipeline = WgpuGraphicsPipeline;
+    type ComputePipeline = WgpuComputePipeline;
+    type Signature = WgpuSignature;
+    type ArgumentBlock = WgpuArgumentBlock;
+    type HostReference = WgpuHostReference;
+    type HostReadback = WgpuHostReadback;
+    type Query = WgpuQuery;
+}
+
+//--------------------------------------------------------------------------------------------------
+pub struct WgpuArena {
+    pub(crate) buffers: Arena<WgpuBuffer>,
+    pub(crate) images: Arena<WgpuImage>,
+    pub(crate) shader_modules: Arena<WgpuShaderModule>,
+    pub(crate) signatures: Arena<WgpuSignature>,
+    pub(crate) graphics_pipelines: Arena<WgpuGraphicsPipeline>,
+    pub(crate) compute_pipelines: Arena<WgpuComputePipeline>,
+    pub(crate) argument_blocks: Arena<WgpuArgumentBlock>,
+    pub(crate) host_references: Arena<WgpuHostReference>,
+    pub(crate) host_readbacks: Arena<WgpuHostReadback>,
+    pub(crate) queries: Arena<WgpuQuery>,
+    pub(crate) allocator: Box<dyn ArenaAllocator>,
+}
+
+impl WgpuArena {
+    fn new(options: ArenaOptions) -> WgpuArena {
+        WgpuArena {
+            buffers: Arena::new(),
+            images: Arena::new(),
+            shader_modules: Arena::new(),
+            signatures: Arena::new(),
+            graphics_pipelines: Arena::new(),
+            compute_pipelines: Arena::new(),
+            argument_blocks: Arena::new(),
+            host_references: Arena::new(),
+            host_readbacks: Arena::new(),
+            queries: Arena::new(),
+            allocator: options.allocator,
+        }
+    }
+
+    fn alloc_buffer(&self, buffer: WgpuBuffer) -> &WgpuBuffer {
+        let request = AllocationRequest {
+            kind: AllocationKind::Buffer,
+            size: buffer.size,
+        };
+        if !self.allocator.allocate(&request) {
+            panic!(
+                "the allocator of the arena refused a buffer allocation of {} bytes",
+                buffer.size
+            )
+        }
+        self.buffers.alloc(buffer)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone, Debug)]
+pub struct InstanceConfig {
+    /// Color space of the images of the swap chain. With `ColorSpace::Srgb`, the swap chain has
+    /// a sRGB format and linear images are encoded to sRGB when presented.
+    pub swapchain_color_space: ColorSpace,
+    /// Conversion of the presented images, unless overridden in the present command.
+    /// With `PresentEncoding::Default`, images are encoded to sRGB only if
+    /// `swapchain_color_space` is `ColorSpace::Srgb`.
+    pub present_encoding: PresentEncoding,
+    pub vsync: bool,
+}
+
+impl Default for InstanceConfig {
+    fn default() -> Self {
+        InstanceConfig {
+            swapchain_color_space: ColorSpace::Linear,
+            present_encoding: PresentEncoding::Default,
+            vsync: false,
+        }
+    }
+}
+
+/// Limits of the WebGPU backend: the minimum guaranteed by the implementations of wgpu.
+const LIMITS: DeviceLimits = DeviceLimits {
+    max_image_dimension_1d: 8192,
+    max_image_dimension_2d: 8192,
+    max_image_dimension_3d: 2048,
+    max_image_dimension_cube: 8192,
+    max_image_array_layers: 256,
+    max_color_attachments: 4,
+    max_samples: 4,
+    max_sampler_anisotropy: 1.0,
+    max_viewports: 1,
+    max_uniform_buffer_range: 16384,
+    min_uniform_buffer_offset_alignment: 256,
+    max_compute_work_group_count: [65535; 3],
+    line_width_range: [1.0, 1.0],
+    point_size_range: [1.0, 1.0],
+    max_multiview_view_count: 1,
+    native_multiview: false,
+    profile: ApiProfile::Embedded,
+    spirv_shaders: true,
+};
+
+/// Instance of the WebGPU backend, running on wgpu.
+///
+/// Fields are declared so that the objects created with the device are dropped before it.
+pub struct WgpuInstance {
+    def_swapchain: Option<WgpuSwapchain>,
+    sampler_cache: RefCell<SamplerCache>,
+    dummies: DummyResources,
+    device: RefCell<wgpu::Device>,
+    _adapter: wgpu::Adapter,
+    instance: wgpu::Instance,
+    cfg: InstanceConfig,
+    cleared_images: RefCell<Vec<ClearedImage>>,
+}
+
+/// An image cleared at the start of its alias scope in each frame
+/// (see `Arena::create_image_cleared`).
+struct ClearedImage {
+    arena: *const WgpuArena,
+    image: *const WgpuImage,
+    scope: AliasScope,
+    value: ClearValue,
+}
+
+impl WgpuInstance {
+    fn create_device(instance: &wgpu::Instance) -> (wgpu::Adapter, wgpu::Device) {
+        let adapter = instance.get_adapter(&wgpu::AdapterDescriptor {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+        });
+        let device = adapter.request_device(&wgpu::DeviceDescriptor {
+            extensions: wgpu::Extensions {
+                anisotropic_filtering: false,
+            },
+            limits: wgpu::Limits::default(),
+        });
+        (adapter, device)
+    }
+
+    /// Creates an instance with no default swapchain, for offscreen rendering.
+    pub fn new(cfg: &InstanceConfig) -> WgpuInstance {
+        let instance = wgpu::Instance::new();
+        let (adapter, device) = Self::create_device(&instance);
+        WgpuInstance {
+            def_swapchain: None,
+            sampler_cache: RefCell::new(SamplerCache::new()),
+            dummies: DummyResources::new(&device),
+            device: RefCell::new(device),
+            _adapter: adapter,
+            instance,
+            cfg: *cfg,
+            cleared_images: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Creates an instance with a default swapchain of the specified size presenting to the
+    /// surface returned by `create_surface` (e.g. `wgpu::Instance::create_surface` with a
+    /// window).
+    pub fn with_surface(
+        cfg: &InstanceConfig,
+        create_surface: impl FnOnce(&wgpu::Instance) -> wgpu::Surface,
+        size: (u32, u32),
+    ) -> WgpuInstance {
+        let instance = wgpu::Instance::new();
+        let surface = create_surface(&instance);
+        let (adapter, device) = Self::create_device(&instance);
+        let swapchain = WgpuSwapchain::new(
+            &device,
+            surface,
+            size,
+            cfg.swapchain_color_space,
+            cfg.present_encoding,
+            cfg.vsync,
+        );
+        WgpuInstance {
+            def_swapchain: Some(swapchain),
+            sampler_cache: RefCell::new(SamplerCache::new()),
+            dummies: DummyResources::new(&device),
+            device: RefCell::new(device),
+            _adapter: adapter,
+            instance,
+            cfg: *cfg,
+            cleared_images: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Returns the wgpu instance.
+    pub fn wgpu_instance(&self) -> &wgpu::Instance {
+        &self.instance
+    }
+
+    /// Returns the configuration of the instance.
+    pub fn config(&self) -> &InstanceConfig {
+        &self.cfg
+    }
+
+    /// Resizes the default swapchain (e.g. when the window is resized).
+    ///
+    /// Images with swapchain-relative dimensions that were already created keep their size:
+    /// they should be created in a per-frame arena.
+    pub fn resize_swapchain(&self, size: (u32, u32)) {
+        let swapchain = self
+            .def_swapchain
+            .as_ref()
+            .expect("the instance has no default swapchain");
+        swapchain.resize(&self.device.borrow(), size);
+    }
+
+    fn upload_image_regions(&self, image: &WgpuImage, regions: &[ImageRegionUpdate]) {
+        let mut device = self.device.borrow_mut();
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+        for region in regions.iter() {
+            let level_size = image.level_size(region.mip_level);
+            assert!(
+                region.mip_level < image.mip_levels
+                    && region.offset.0 + region.size.0 <= level_size.0
+                    && region.offset.1 + region.size.1 <= level_size.1
+                    && region.offset.2 + region.size.2 <= level_size.2,
+                "region out of the bounds of mip level {} of the image",
+                region.mip_level
+            );
+            image.upload_region(
+                &device,
+                &mut encoder,
+                region.mip_level,
+                region.offset,
+                region.size,
+                region.row_pitch,
+                region.data,
+            );
+        }
+        device.get_queue().submit(&[encoder.finish()]);
+    }
+}
+
+impl Instance<WgpuBackend> for WgpuInstance {
+    unsafe fn create_arena(&self, options: ArenaOptions) -> Box<WgpuArena> {
+        Box::new(WgpuArena::new(options))
+    }
+
+    unsafe fn drop_arena(&self, arena: Box<WgpuArena>) {
+        let arena_ptr = &*arena as *const WgpuArena;
+        self.cleared_images
+            .borrow_mut()
+            .retain(|c| c.arena != arena_ptr);
+        let arena = *arena;
+        for buffer in arena.buffers.into_vec() {
+            arena.allocator.free(&AllocationRequest {
+                kind: AllocationKind::Buffer,
+                size: buffer.size,
+            });
+        }
+    }
+
+    unsafe fn create_swapchain<'a>(&self, _arena: &'a WgpuArena) -> &'a WgpuSwapchain {
+        unimplemented!()
+    }
+
+    unsafe fn default_swapchain<'a>(&'a self) -> Option<&'a WgpuSwapchain> {
+        self.def_swapchain.as_ref()
+    }
+
+    unsafe fn acquire_swapchain_image<'a>(
+        &self,
+        arena: &'a WgpuArena,
+        swapchain: &'a WgpuSwapchain,
+    ) -> &'a WgpuImage {
+        // the images of the swap chain are only accessible during presentation: render into an
+        // intermediate image, drawn into the swap chain in `WgpuSwapchain::present`
+        let format = match swapchain.color_space() {
+            ColorSpace::Srgb => Format::R8G8B8A8_SRGB,
+            ColorSpace::Linear => Format::R8G8B8A8_UNORM,
+        };
+        self.create_image(
+            arena,
+            AliasScope::no_alias(),
+            format,
+            Dimensions::Dim2d {
+                width: swapchain.size().0,
+                height: swapchain.size().1,
+                array_layers: 1,
+            },
+            MipmapsOption::NoMipmap,
+            1,
+            ImageUsageFlags::COLOR_ATTACHMENT,
+            None,
+        )
+    }
+
+    fn limits(&self) -> DeviceLimits {
+        LIMITS
+    }
+
+    fn clip_space_info(&self) -> ClipSpaceInfo {
+        ClipSpaceInfo {
+            depth_range: DepthRange::ZeroToOne,
+            y_direction: YDirection::Up,
+        }
+    }
+
+    fn format_properties(&self, format: Format) -> FormatProperties {
+        format_properties(format)
+    }
+
+    unsafe fn create_image<'a>(
+        &self,
+        arena: &'a WgpuArena,
+        _scope: AliasScope,
+        format: Format,
+        dimensions: Dimensions,
+        mipcount: MipmapsOption,
+        samples: u32,
+        usage: ImageUsageFlags,
+        initial_data: Option<&[u8]>,
+    ) -> &'a WgpuImage {
+        // images are never aliased, and swapchain-relative images are not resized
+        // (see `resize_swapchain`)
+        let swapchain_size = self
+            .def_swapchain
+            .as_ref()
+            .map(|swapchain| swapchain.size())
+            .unwrap_or((1, 1));
+        let dimensions = dimensions.resolve(swapchain_size);
+        let (width, height, depth) = dimensions.width_height_depth();
+        match mipcount {
+            MipmapsOption::Generate | MipmapsOption::GenerateCount(_) => {
+                warn!("mipmap generation is not supported by the WebGPU backend")
+            }
+            _ => {}
+        }
+        let image = arena.images.alloc(WgpuImage::new(
+            &self.device.borrow(),
+            format,
+            dimensions,
+            mipcount.count(width, height, depth),
+            samples,
+            usage,
+        ));
+        if let Some(data) = initial_data {
+            let layers = dimensions.array_layers_with_cube();
+            let depth = if let Dimensions::Dim3d { .. } = dimensions {
+                depth
+            } else {
+                layers
+            };
+            self.upload_image_regions(
+                image,
+                &[ImageRegionUpdate {
+                    mip_level: 0,
+                    offset: (0, 0, 0),
+                    size: (width, height, depth),
+                    row_pitch: 0,
+                    data,
+                }],
+            );
+        }
+        image
+    }
+
+    unsafe fn create_image_cleared<'a>(
+        &self,
+        arena: &'a WgpuArena,
+        scope: AliasScope,
+        format: Format,
+        dimensions: Dimensions,
+        mipcount: MipmapsOption,
+        samples: u32,
+        usage: ImageUsageFlags,
+        clear_value: ClearValue,
+    ) -> &'a WgpuImage {
+        let image = self.create_image(
+            arena, scope, format, dimensions, mipcount, samples, usage, None,
+        );
+        self.cleared_images.borrow_mut().push(ClearedImage {
+            arena,
+            image,
+            scope,
+            value: clear_value,
+        });
+        image
+    }
+
+    unsafe fn update_image(
+        &self,
+        image: &WgpuImage,
+        mip_level: u32,
+        min_extent: (u32, u32, u32),
+        max_extent: (u32, u32, u32),
+        data: &[u8],
+    ) {
+        self.upload_image_regions(
+            image,
+            &[ImageRegionUpdate {
+                mip_level,
+                offset: min_extent,
+                size: (
+                    max_extent.0 - min_extent.0,
+                    max_extent.1 - min_extent.1,
+                    max_extent.2 - min_extent.2,
+                ),
+                row_pitch: 0,
+                data,
+            }],
+        );
+    }
+
+    unsafe fn update_image_regions(&self, image: &WgpuImage, regions: &[ImageRegionUpdate]) {
+        self.upload_image_regions(image, regions);
+    }
+
+    unsafe fn create_immutable_buffer<'a>(
+        &self,
+        arena: &'a WgpuArena,
+        size: u64,
+        data: &[u8],
+    ) -> &'a WgpuBuffer {
+        arena.alloc_buffer(WgpuBuffer::new_immutable(&self.device.borrow(), size, data))
+    }
+
+    unsafe fn create_buffer<'a>(&self, arena: &'a WgpuArena, size: u64) -> &'a WgpuBuffer {
+        arena.alloc_buffer(WgpuBuffer::new(&self.device.borrow(), size))
+    }
+
+    unsafe fn update_buffer(&self, buffer: &WgpuBuffer, offset: usize, data: &[u8]) {
+        let mut device = self.device.borrow_mut();
+        buffer.update(&mut device, offset as u64, data);
+    }
+
+    unsafe fn create_buffer_view<'a>(
+        &self,
+        _arena: &'a WgpuArena,
+        _buffer: &'a WgpuBuffer,
+        _format: Format,
+        _offset: usize,
+        _size: Option<usize>,
+    ) -> &'a WgpuBufferView {
+        panic!("texel buffers are not supported by the WebGPU backend")
+    }
+
+    unsafe fn create_shader_module<'a>(
+        &self,
+        arena: &'a WgpuArena,
+        spirv: &[u8],
+        stage: ShaderStageFlags,
+    ) -> &'a WgpuShaderModule {
+        arena
+            .shader_modules
+            .alloc(WgpuShaderModule::new(&self.device.borrow(), spirv, stage))
+    }
+
+    unsafe fn create_graphics_pipeline<'a>(
+        &self,
+        arena: &'a WgpuArena,
+        root_signature: &'a WgpuSignature,
+        root_signature_description: &SignatureDescription,
+        create_info: &GraphicsPipelineCreateInfo<'a, '_, WgpuBackend>,
+    ) -> &'a WgpuGraphicsPipeline {
+        WgpuGraphicsPipeline::new(
+            arena,
+            &self.device.borrow(),
+            root_signature,
+            root_signature_description,
+            create_info,
+        )
+    }
+
+    unsafe fn create_compute_pipeline<'a>(
+        &self,
+        arena: &'a WgpuArena,
+        root_signature: &'a WgpuSignature,
+        _root_signature_description: &SignatureDescription,
+        shader: &'a WgpuShaderModule,
+    ) -> &'a WgpuComputePipeline {
+        WgpuComputePipeline::new(arena, &self.device.borrow(), root_signature, shader)
+    }
+
+    unsafe fn warm_up_pipelines(
+        &self,
+        pipelines: &[PipelineRef<WgpuBackend>],
+    ) -> Vec<PipelineCompileTime> {
+        // render pipelines are only created on first use, when the formats of the attachments
+        // are known
+        pipelines
+            .iter()
+            .map(|pipeline| match *pipeline {
+                PipelineRef::Graphics(pipeline) => PipelineCompileTime {
+                    kind: PipelineKind::Graphics,
+                    create_time: pipeline.create_time,
+                    warm_up_time: Duration::from_secs(0),
+                },
+                PipelineRef::Compute(pipeline) => PipelineCompileTime {
+                    kind: PipelineKind::Compute,
+                    create_time: pipeline.create_time,
+                    warm_up_time: Duration::from_secs(0),
+                },
+            })
+            .collect()
+    }
+
+    unsafe fn create_signature<'a>(
+        &'a self,
+        arena: &'a WgpuArena,
+        inherited: &[&'a WgpuSignature],
+        description: &SignatureDescription,
+    ) -> &'a WgpuSignature {
+        WgpuSignature::new(arena, &self.device.borrow(), inherited, description)
+    }
+
+    unsafe fn create_argument_block<'a>(
+        &self,
+        arena: &'a WgpuArena,
+        signature: &'a WgpuSignature,
+        inherited: impl IntoIterator<Item = BareArgumentBlock<'a, WgpuBackend>>,
+        descriptors: impl IntoIterator<Item = Descriptor<'a, WgpuBackend>>,
+        vertex_buffers: impl IntoIterator<Item = VertexBufferView<'a, WgpuBackend>>,
+        index_buffer: Option<IndexBufferView<'a, WgpuBackend>>,
+        render_targets: impl IntoIterator<Item = RenderTargetView<'a, WgpuBackend>>,
+        depth_stencil_target: Option<DepthStencilView<'a, WgpuBackend>>,
+        viewports: impl IntoIterator<Item = Viewport>,
+        scissors: impl IntoIterator<Item = Scissor>,
+        stencil_reference: Option<u32>,
+    ) -> &'a WgpuArgumentBlock {
+        WgpuArgumentBlock::new(
+            arena,
+            &self.device.borrow(),
+            &mut self.sampler_cache.borrow_mut(),
+            &self.dummies,
+            signature,
+            inherited,
+            descriptors,
+            vertex_buffers,
+            index_buffer,
+            render_targets,
+            depth_stencil_target,
+            viewports,
+            scissors,
+            stencil_reference,
+        )
+    }
+
+    unsafe fn create_host_reference<'a>(
+        &self,
+        arena: &'a WgpuArena,
+        data: &'a [u8],
+    ) -> &'a WgpuHostReference {
+        arena.host_references.alloc(WgpuHostReference {
+            buffer: WgpuBuffer::new_immutable(&self.device.borrow(), data.len() as u64, data),
+        })
+    }
+
+    unsafe fn create_host_readback<'a>(
+        &self,
+        arena: &'a WgpuArena,
+        dest: &'a mut [u8],
+    ) -> &'a WgpuHostReadback {
+        arena.host_readbacks.alloc(WgpuHostReadback {
+            ptr: dest.as_mut_ptr(),
+            len: dest.len(),
+        })
+    }
+
+    unsafe fn create_occlusion_query<'a>(&self, arena: &'a WgpuArena) -> &'a WgpuQuery {
+        arena.queries.alloc(WgpuQuery)
+    }
+
+    unsafe fn submit_frame<'a>(&self, commands: &[Command<'a, WgpuBackend>]) -> SubmitStats {
+        let mut device = self.device.borrow_mut();
+        let mut context = SubmissionContext::new(&mut device);
+        let mut initial_clears = self
+            .cleared_images
+            .borrow()
+            .iter()
+            .map(|c| (c.image, c.scope, c.value))
+            .collect::<Vec<_>>();
+        for command in commands.iter() {
+            // commands are sorted: clear the images before the first command inside their scope
+            initial_clears.retain(|&(image, scope, value)| {
+                if scope.contains(command.sortkey) {
+                    context.clear_image(&*image, value);
+                    false
+                } else {
+                    true
+                }
+            });
+            context.submit_command(command);
+        }
+        context.finish()
+    }
+}
@@ -0,0 +1,113 @@
+/// Returns the usage of all buffers: buffers can be bound to any slot, and copied to and from.
+pub(crate) fn buffer_usage() -> wgpu::BufferUsage {
+    wgpu::BufferUsage::VERTEX
+        | wgpu::BufferUsage::INDEX
+        | wgpu::BufferUsage::UNIFORM
+        | wgpu::BufferUsage::STORAGE
+        | wgpu::BufferUsage::COPY_SRC
+        | wgpu::BufferUsage::COPY_DST
+}
+
+/// Rounds a buffer size to the next multiple of 4, as required by copies.
+pub(crate) fn align_buffer_size(size: u64) -> u64 {
+    (size + 3) & !3
+}
+
+/// Buffer of the WebGPU backend.
+pub struct WgpuBuffer {
+    pub(crate) buffer: wgpu::Buffer,
+    /// Size requested on creation. The size of the underlying buffer is rounded up to a multiple
+    /// of 4.
+    pub(crate) size: u64,
+}
+
+impl WgpuBuffer {
+    /// Creates a buffer initialized with `data`.
+    pub(crate) fn new_immutable(device: &wgpu::Device, size: u64, data: &[u8]) -> WgpuBuffer {
+        assert!(
+            data.len() as u64 <= size,
+            "initial data larger than the buffer ({} bytes, buffer size is {})",
+            data.len(),
+            size
+        );
+        let mut padded = data.to_vec();
+        padded.resize(align_buffer_size(size) as usize, 0);
+        let buffer = device
+            .create_buffer_mapped(padded.len(), buffer_usage())
+            .fill_from_slice(&padded);
+        WgpuBuffer { buffer, size }
+    }
+
+    /// Creates a buffer with unspecified contents.
+    pub(crate) fn new(device: &wgpu::Device, size: u64) -> WgpuBuffer {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            size: align_buffer_size(size),
+            usage: buffer_usage(),
+        });
+        WgpuBuffer { buffer, size }
+    }
+
+    /// Writes `data` at `offset` through a staging buffer, with a copy submitted immediately.
+    pub(crate) fn update(&self, device: &mut wgpu::Device, offset: u64, data: &[u8]) {
+        let len = data.len() as u64;
+        assert!(
+            offset + len <= self.size,
+            "update of {} bytes at offset {} outside of a buffer of {} bytes",
+            len,
+            offset,
+            self.size
+        );
+        assert!(
+            offset % 4 == 0 && len % 4 == 0,
+            "buffer updates must be aligned to 4 bytes (offset {}, size {})",
+            offset,
+            len
+        );
+        let staging = device
+            .create_buffer_mapped(data.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(data);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+        encoder.copy_buffer_to_buffer(&staging, 0, &self.buffer, offset, len);
+        device.get_queue().submit(&[encoder.finish()]);
+    }
+}
+
+impl std::fmt::Debug for WgpuBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "WgpuBuffer {{ size: {} }}", self.size)
+    }
+}
+
+/// Texel buffer view.
+///
+/// WebGPU has no texel buffers: views cannot be created, and this type has no values.
+#[derive(Debug)]
+pub enum WgpuBufferView {}
+
+/// Host data referenced by the commands of a frame.
+///
+/// The data is borrowed for the lifetime of the arena and cannot change: it is uploaded into a
+/// uniform buffer on creation.
+pub struct WgpuHostReference {
+    pub(crate) buffer: WgpuBuffer,
+}
+
+impl std::fmt::Debug for WgpuHostReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "WgpuHostReference {{ size: {} }}", self.buffer.size)
+    }
+}
+
+/// Destination of a copy to host memory.
+///
+/// Copies are made into a staging buffer, which is mapped once the frame has been submitted.
+#[derive(Debug)]
+pub struct WgpuHostReadback {
+    pub(crate) ptr: *mut u8,
+    pub(crate) len: usize,
+}
+
+// only written in `submit_frame`, while the destination is borrowed by the arena.
+unsafe impl Send for WgpuHostReadback {}
+unsafe impl Sync for WgpuHostReadback {}
@@ -0,0 +1,693 @@
+use crate::{
+    buffer::{align_buffer_size, WgpuBuffer, WgpuHostReadback},
+    image::{WgpuImage, ROW_PITCH_ALIGNMENT},
+    pipeline::{
+        Attachment, AttachmentFormats, WgpuArgumentBlock, WgpuComputePipeline, WgpuGraphicsPipeline,
+    },
+    swapchain::WgpuSwapchain,
+    WgpuBackend,
+};
+use autograph_api::{
+    command::{ClearColor, ClearValue, Command, CommandInner, PresentEncoding},
+    format::Format,
+    image::Dimensions,
+    pipeline::{LoadOp, Scissor, ScissorsOwned, StoreOp, Viewport, ViewportsOwned},
+    report::SubmitStats,
+};
+use fxhash::{FxBuildHasher, FxHashSet};
+use std::{
+    mem,
+    ops::Range,
+    slice,
+    time::{Duration, Instant},
+};
+
+/// Image subresource rendered into: image, mip level and array layer.
+type AttachmentKey = (*const WgpuImage, u32, u32);
+
+enum DrawKind {
+    Draw {
+        vertices: Range<u32>,
+        instances: Range<u32>,
+    },
+    Indexed {
+        indices: Range<u32>,
+        base_vertex: i32,
+        instances: Range<u32>,
+    },
+}
+
+/// A draw recorded in the current render pass.
+struct DrawCall<'a> {
+    pipeline: &'a WgpuGraphicsPipeline,
+    /// Flattened argument tree.
+    blocks: Vec<&'a WgpuArgumentBlock>,
+    kind: DrawKind,
+}
+
+/// Attachments of a render pass, found in the argument blocks of its draws.
+#[derive(Clone)]
+struct PassAttachments<'a> {
+    colors: &'a [Attachment],
+    depth_stencil: Option<&'a Attachment>,
+}
+
+impl<'a> PassAttachments<'a> {
+    /// Returns the attachments of the last blocks of the argument tree that specify them.
+    fn new(blocks: &[&'a WgpuArgumentBlock]) -> PassAttachments<'a> {
+        PassAttachments {
+            colors: blocks
+                .iter()
+                .rev()
+                .map(|b| &b.color_attachments[..])
+                .find(|c| !c.is_empty())
+                .unwrap_or(&[]),
+            depth_stencil: blocks
+                .iter()
+                .rev()
+                .filter_map(|b| b.depth_stencil_attachment.as_ref())
+                .next(),
+        }
+    }
+
+    fn keys(&self) -> Vec<AttachmentKey> {
+        self.colors
+            .iter()
+            .chain(self.depth_stencil)
+            .map(|a| a.key())
+            .collect()
+    }
+
+    fn formats(&self) -> AttachmentFormats {
+        AttachmentFormats {
+            colors: self
+                .colors
+                .iter()
+                .map(|a| a.image().texture_format)
+                .collect(),
+            depth_stencil: self.depth_stencil.map(|a| a.image().texture_format),
+            samples: self
+                .colors
+                .iter()
+                .chain(self.depth_stencil)
+                .map(|a| a.image().samples)
+                .next()
+                .unwrap_or(1),
+        }
+    }
+
+    /// Size of the render area.
+    fn size(&self) -> (u32, u32) {
+        self.colors
+            .iter()
+            .chain(self.depth_stencil)
+            .map(|a| {
+                let (w, h, _) = a.image().level_size(a.mip_level);
+                (w, h)
+            })
+            .next()
+            .unwrap_or((1, 1))
+    }
+}
+
+/// Layout of the data in the staging buffer of a readback.
+enum ReadbackLayout {
+    /// Same layout as the destination.
+    Linear,
+    /// Rows of 8-bit RGBA or BGRA texels, padded to the required alignment.
+    Rows {
+        row_size: usize,
+        row_pitch: usize,
+        height: usize,
+        bgra: bool,
+    },
+}
+
+/// A copy to host memory, read from the staging buffer once the frame has been submitted.
+struct PendingReadback {
+    staging: wgpu::Buffer,
+    size: u64,
+    dest: *mut u8,
+    len: usize,
+    layout: ReadbackLayout,
+}
+
+fn color(c: [f32; 4]) -> wgpu::Color {
+    wgpu::Color {
+        r: f64::from(c[0]),
+        g: f64::from(c[1]),
+        b: f64::from(c[2]),
+        a: f64::from(c[3]),
+    }
+}
+
+fn store_op(op: StoreOp) -> wgpu::StoreOp {
+    match op {
+        StoreOp::Store => wgpu::StoreOp::Store,
+        StoreOp::DontCare => wgpu::StoreOp::Clear,
+    }
+}
+
+/// State of the submission of a frame.
+pub(crate) struct SubmissionContext<'a, 'd> {
+    device: &'d mut wgpu::Device,
+    encoder: wgpu::CommandEncoder,
+    /// Subresources rendered into since the start of the frame. Only their first use applies
+    /// the load operation of the attachment.
+    used_attachments: FxHashSet<AttachmentKey>,
+    /// Draws of the current render pass.
+    draws: Vec<DrawCall<'a>>,
+    pass_attachments: Option<PassAttachments<'a>>,
+    readbacks: Vec<PendingReadback>,
+    graphics_pipeline: Option<&'a WgpuGraphicsPipeline>,
+    compute_pipeline: Option<&'a WgpuComputePipeline>,
+    arguments: Option<&'a WgpuArgumentBlock>,
+    /// Time spent waiting for the GPU or the swap chain.
+    cpu_wait: Duration,
+}
+
+impl<'a, 'd> SubmissionContext<'a, 'd> {
+    pub(crate) fn new(device: &'d mut wgpu::Device) -> SubmissionContext<'a, 'd> {
+        let encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+        SubmissionContext {
+            device,
+            encoder,
+            used_attachments: FxHashSet::with_hasher(FxBuildHasher::default()),
+            draws: Vec::new(),
+            pass_attachments: None,
+            readbacks: Vec::new(),
+            graphics_pipeline: None,
+            compute_pipeline: None,
+            arguments: None,
+            cpu_wait: Duration::from_secs(0),
+        }
+    }
+
+    /// Submits the commands recorded so far, and starts a new command encoder.
+    fn submit_encoder(&mut self) {
+        self.flush_render_pass();
+        let encoder = mem::replace(
+            &mut self.encoder,
+            self.device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 }),
+        );
+        self.device.get_queue().submit(&[encoder.finish()]);
+    }
+
+    /// Returns the current argument tree, flattened.
+    fn flattened_arguments(&self) -> Vec<&'a WgpuArgumentBlock> {
+        let mut blocks = Vec::new();
+        if let Some(arguments) = self.arguments {
+            arguments.flatten(&mut blocks);
+        }
+        blocks
+    }
+
+    fn cmd_draw(&mut self, kind: DrawKind) {
+        let pipeline = self
+            .graphics_pipeline
+            .expect("draw command without a graphics pipeline");
+        let blocks = self.flattened_arguments();
+        let attachments = PassAttachments::new(&blocks);
+        let same_pass = self
+            .pass_attachments
+            .as_ref()
+            .map(|a| a.keys() == attachments.keys())
+            .unwrap_or(false);
+        if !same_pass {
+            self.flush_render_pass();
+            self.pass_attachments = Some(attachments);
+        }
+        self.draws.push(DrawCall {
+            pipeline,
+            blocks,
+            kind,
+        });
+    }
+
+    /// Returns the load operation of an attachment, and marks it as used.
+    fn attachment_load_op(&mut self, attachment: &Attachment) -> (wgpu::LoadOp, [f32; 4]) {
+        if !self.used_attachments.insert(attachment.key()) {
+            return (wgpu::LoadOp::Load, [0.0; 4]);
+        }
+        match attachment.output.load_op {
+            LoadOp::Load => (wgpu::LoadOp::Load, [0.0; 4]),
+            LoadOp::Clear(value) => (wgpu::LoadOp::Clear, value),
+            // there is no "don't care" load operation in WebGPU
+            LoadOp::DontCare => (wgpu::LoadOp::Clear, [0.0; 4]),
+        }
+    }
+
+    /// Records the draws of the current render pass.
+    fn flush_render_pass(&mut self) {
+        if self.draws.is_empty() {
+            self.pass_attachments = None;
+            return;
+        }
+        let draws = mem::replace(&mut self.draws, Vec::new());
+        let attachments = self.pass_attachments.take().unwrap();
+
+        let color_ops = attachments
+            .colors
+            .iter()
+            .map(|a| self.attachment_load_op(a))
+            .collect::<Vec<_>>();
+        let depth_stencil_op = attachments
+            .depth_stencil
+            .map(|a| self.attachment_load_op(a));
+
+        let formats = attachments.formats();
+        let device: &wgpu::Device = self.device;
+        let pipelines = draws
+            .iter()
+            .map(|d| d.pipeline.instance(device, &formats))
+            .collect::<Vec<_>>();
+        let (width, height) = attachments.size();
+
+        let color_attachments = attachments
+            .colors
+            .iter()
+            .zip(color_ops.iter())
+            .map(
+                |(a, &(load_op, clear))| wgpu::RenderPassColorAttachmentDescriptor {
+                    attachment: &a.view,
+                    resolve_target: None,
+                    load_op,
+                    store_op: store_op(a.output.store_op),
+                    clear_color: color(clear),
+                },
+            )
+            .collect::<Vec<_>>();
+        let depth_stencil_attachment = attachments.depth_stencil.map(|a| {
+            let (load_op, clear) = depth_stencil_op.unwrap();
+            wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                attachment: &a.view,
+                depth_load_op: load_op,
+                depth_store_op: store_op(a.output.store_op),
+                clear_depth: clear[0],
+                stencil_load_op: load_op,
+                stencil_store_op: store_op(a.output.store_op),
+                clear_stencil: 0,
+            }
+        });
+
+        let mut pass = self.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &color_attachments,
+            depth_stencil_attachment,
+        });
+
+        for (draw, pipeline) in draws.iter().zip(pipelines) {
+            pass.set_pipeline(pipeline);
+            for (i, bind_group) in draw
+                .blocks
+                .iter()
+                .filter_map(|b| b.bind_group.as_ref())
+                .enumerate()
+            {
+                pass.set_bind_group(i as u32, bind_group, &[]);
+            }
+
+            // the buffers outlive the argument blocks
+            let vertex_buffers = draw
+                .blocks
+                .iter()
+                .flat_map(|b| b.vertex_buffers.iter())
+                .map(|&(buffer, offset)| (unsafe { &(*buffer).buffer }, offset))
+                .collect::<Vec<_>>();
+            if !vertex_buffers.is_empty() {
+                pass.set_vertex_buffers(&vertex_buffers);
+            }
+            if let Some(&(buffer, offset, _)) = draw
+                .blocks
+                .iter()
+                .rev()
+                .filter_map(|b| b.index_buffer.as_ref())
+                .next()
+            {
+                pass.set_index_buffer(unsafe { &(*buffer).buffer }, offset);
+            }
+
+            // only one viewport is supported
+            let viewport = match draw.pipeline.viewports {
+                ViewportsOwned::Static(ref viewports) => viewports.first().cloned(),
+                ViewportsOwned::Dynamic => draw
+                    .blocks
+                    .iter()
+                    .rev()
+                    .filter_map(|b| b.viewports.first().cloned())
+                    .next(),
+            }
+            .unwrap_or_else(|| Viewport::from((width, height)));
+            pass.set_viewport(
+                viewport.x.into_inner(),
+                viewport.y.into_inner(),
+                viewport.width.into_inner(),
+                viewport.height.into_inner(),
+                viewport.min_depth.into_inner(),
+                viewport.max_depth.into_inner(),
+            );
+            let scissor = match draw.pipeline.scissors {
+                ScissorsOwned::Static(ref scissors) => scissors.first().cloned(),
+                ScissorsOwned::Dynamic => draw
+                    .blocks
+                    .iter()
+                    .rev()
+                    .filter_map(|b| b.scissors.first().cloned())
+                    .next(),
+            };
+            match scissor {
+                Some(Scissor::Enabled(rect)) => {
+                    let x = rect.x.max(0) as u32;
+                    let y = rect.y.max(0) as u32;
+                    pass.set_scissor_rect(
+                        x.min(width),
+                        y.min(height),
+                        rect.width.min(width.saturating_sub(x)),
+                        rect.height.min(height.saturating_sub(y)),
+                    );
+                }
+                _ => pass.set_scissor_rect(0, 0, width, height),
+            }
+            let stencil_reference = draw.pipeline.stencil_reference.or_else(|| {
+                draw.blocks
+                    .iter()
+                    .rev()
+                    .filter_map(|b| b.stencil_reference)
+                    .next()
+            });
+            pass.set_stencil_reference(stencil_reference.unwrap_or(0));
+            pass.set_blend_color(draw.pipeline.blend_color);
+
+            match draw.kind {
+                DrawKind::Draw {
+                    ref vertices,
+                    ref instances,
+                } => pass.draw(vertices.clone(), instances.clone()),
+                DrawKind::Indexed {
+                    ref indices,
+                    base_vertex,
+                    ref instances,
+                } => pass.draw_indexed(indices.clone(), base_vertex, instances.clone()),
+            }
+        }
+    }
+
+    /// Clears all layers of the first mipmap level of an image.
+    fn cmd_clear_image(
+        &mut self,
+        image: &WgpuImage,
+        color_value: Option<[f32; 4]>,
+        depth: f32,
+        stencil: Option<u8>,
+    ) {
+        self.flush_render_pass();
+        if let Dimensions::Dim3d { .. } = image.dimensions {
+            panic!("clearing 3D images is not supported by the WebGPU backend")
+        }
+        for layer in 0..image.dimensions.array_layers_with_cube() {
+            self.used_attachments.insert((image as *const _, 0, layer));
+            let view = image.create_attachment_view(0, layer);
+            let color_attachments;
+            let depth_stencil_attachment;
+            if let Some(c) = color_value {
+                color_attachments = vec![wgpu::RenderPassColorAttachmentDescriptor {
+                    attachment: &view,
+                    resolve_target: None,
+                    load_op: wgpu::LoadOp::Clear,
+                    store_op: wgpu::StoreOp::Store,
+                    clear_color: color(c),
+                }];
+                depth_stencil_attachment = None;
+            } else {
+                color_attachments = Vec::new();
+                depth_stencil_attachment = Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                    attachment: &view,
+                    depth_load_op: wgpu::LoadOp::Clear,
+                    depth_store_op: wgpu::StoreOp::Store,
+                    clear_depth: depth,
+                    stencil_load_op: if stencil.is_some() {
+                        wgpu::LoadOp::Clear
+                    } else {
+                        wgpu::LoadOp::Load
+                    },
+                    stencil_store_op: wgpu::StoreOp::Store,
+                    clear_stencil: u32::from(stencil.unwrap_or(0)),
+                });
+            }
+            self.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &color_attachments,
+                depth_stencil_attachment,
+            });
+        }
+    }
+
+    fn cmd_dispatch(&mut self, x: u32, y: u32, z: u32) {
+        self.flush_render_pass();
+        let pipeline = self
+            .compute_pipeline
+            .expect("dispatch command without a compute pipeline");
+        let blocks = self.flattened_arguments();
+        let mut pass = self.encoder.begin_compute_pass();
+        pass.set_pipeline(&pipeline.pipeline);
+        for (i, bind_group) in blocks
+            .iter()
+            .filter_map(|b| b.bind_group.as_ref())
+            .enumerate()
+        {
+            pass.set_bind_group(i as u32, bind_group, &[]);
+        }
+        pass.dispatch(x, y, z);
+    }
+
+    fn create_staging_buffer(&self, size: u64) -> wgpu::Buffer {
+        self.device.create_buffer(&wgpu::BufferDescriptor {
+            size,
+            usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+        })
+    }
+
+    fn cmd_copy_buffer_to_host(&mut self, buffer: &WgpuBuffer, readback: &WgpuHostReadback) {
+        self.flush_render_pass();
+        assert!(
+            readback.len as u64 <= buffer.size,
+            "readback destination larger than the buffer ({} bytes, buffer size is {})",
+            readback.len,
+            buffer.size
+        );
+        let size = align_buffer_size(readback.len as u64);
+        let staging = self.create_staging_buffer(size);
+        self.encoder
+            .copy_buffer_to_buffer(&buffer.buffer, 0, &staging, 0, size);
+        self.readbacks.push(PendingReadback {
+            staging,
+            size,
+            dest: readback.ptr,
+            len: readback.len,
+            layout: ReadbackLayout::Linear,
+        });
+    }
+
+    fn cmd_copy_image_to_host(&mut self, image: &WgpuImage, readback: &WgpuHostReadback) {
+        self.flush_render_pass();
+        let bgra = match image.format {
+            Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB => false,
+            Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB => true,
+            other => panic!(
+                "copies to host memory of images with format {:?} are not supported by the \
+                 WebGPU backend",
+                other
+            ),
+        };
+        let (width, height, _) = image.level_size(0);
+        let row_size = width as usize * 4;
+        assert_eq!(
+            readback.len,
+            row_size * height as usize,
+            "the size of the readback destination must be the size of the image"
+        );
+        let row_pitch =
+            (row_size + ROW_PITCH_ALIGNMENT - 1) / ROW_PITCH_ALIGNMENT * ROW_PITCH_ALIGNMENT;
+        let size = (row_pitch * height as usize) as u64;
+        let staging = self.create_staging_buffer(size);
+        self.encoder.copy_texture_to_buffer(
+            wgpu::TextureCopyView {
+                texture: &image.texture,
+                mip_level: 0,
+                array_layer: 0,
+                origin: wgpu::Origin3d {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            },
+            wgpu::BufferCopyView {
+                buffer: &staging,
+                offset: 0,
+                row_pitch: row_pitch as u32,
+                image_height: height,
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+        );
+        self.readbacks.push(PendingReadback {
+            staging,
+            size,
+            dest: readback.ptr,
+            len: readback.len,
+            layout: ReadbackLayout::Rows {
+                row_size,
+                row_pitch,
+                height: height as usize,
+                bgra,
+            },
+        });
+    }
+
+    fn cmd_present(
+        &mut self,
+        image: &WgpuImage,
+        swapchain: &WgpuSwapchain,
+        encoding: PresentEncoding,
+    ) {
+        self.submit_encoder();
+        let start = Instant::now();
+        swapchain.present(self.device, image, encoding);
+        self.cpu_wait += start.elapsed();
+    }
+
+    /// Clears an image created with `Arena::create_image_cleared`.
+    pub(crate) fn clear_image(&mut self, image: &WgpuImage, value: ClearValue) {
+        match value {
+            ClearValue::Color(ClearColor::Float(color)) => {
+                self.cmd_clear_image(image, Some(color), 0.0, None)
+            }
+            ClearValue::Color(_) => {
+                panic!("clearing integer images is not supported by the WebGPU backend")
+            }
+            ClearValue::DepthStencil { depth, stencil } => {
+                self.cmd_clear_image(image, None, depth, stencil)
+            }
+        }
+    }
+
+    pub(crate) fn submit_command(&mut self, command: &Command<'a, WgpuBackend>) {
+        match command.cmd {
+            // wgpu tracks the usage of resources and inserts barriers automatically
+            CommandInner::PipelineBarrier { .. } => {}
+            CommandInner::ClearImageFloat { image, color } => {
+                self.cmd_clear_image(image, Some(color), 0.0, None)
+            }
+            CommandInner::ClearImageUint { .. } | CommandInner::ClearImageInt { .. } => {
+                panic!("clearing integer images is not supported by the WebGPU backend")
+            }
+            CommandInner::ClearDepthStencilImage {
+                image,
+                depth,
+                stencil,
+            } => self.cmd_clear_image(image, None, depth, stencil),
+            CommandInner::CopyBufferToHost { buffer, readback } => {
+                self.cmd_copy_buffer_to_host(buffer, readback)
+            }
+            CommandInner::CopyImageToHost { image, readback } => {
+                self.cmd_copy_image_to_host(image, readback)
+            }
+            CommandInner::Present {
+                image,
+                swapchain,
+                encoding,
+            } => self.cmd_present(image, swapchain, encoding),
+            CommandInner::DrawHeader { pipeline } => self.graphics_pipeline = Some(pipeline),
+            CommandInner::DispatchHeader { pipeline } => self.compute_pipeline = Some(pipeline),
+            // no queries: conditional draws are always executed
+            CommandInner::BeginQuery { .. }
+            | CommandInner::EndQuery { .. }
+            | CommandInner::BeginConditional { .. }
+            | CommandInner::EndConditional {} => {}
+            // debug groups cannot span render passes
+            CommandInner::BeginDebugGroup { .. } | CommandInner::EndDebugGroup {} => {}
+            CommandInner::SetPipelineArguments { arguments } => self.arguments = Some(arguments),
+            // lines are always one pixel wide
+            CommandInner::SetLineWidth { .. } => {}
+            CommandInner::Draw {
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            } => self.cmd_draw(DrawKind::Draw {
+                vertices: first_vertex..first_vertex + vertex_count,
+                instances: first_instance..first_instance + instance_count,
+            }),
+            CommandInner::DrawIndexed {
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            } => self.cmd_draw(DrawKind::Indexed {
+                indices: first_index..first_index + index_count,
+                base_vertex: vertex_offset,
+                instances: first_instance..first_instance + instance_count,
+            }),
+            CommandInner::Dispatch {
+                group_count_x,
+                group_count_y,
+                group_count_z,
+            } => self.cmd_dispatch(group_count_x, group_count_y, group_count_z),
+            CommandInner::DispatchIndirect { .. } => {
+                panic!("indirect dispatches are not supported by the WebGPU backend")
+            }
+        }
+    }
+
+    /// Submits the remaining commands, and waits for the copies to host memory.
+    pub(crate) fn finish(mut self) -> SubmitStats {
+        self.submit_encoder();
+        if !self.readbacks.is_empty() {
+            let start = Instant::now();
+            for readback in self.readbacks.drain(..) {
+                let PendingReadback {
+                    staging,
+                    size,
+                    dest,
+                    len,
+                    layout,
+                } = readback;
+                staging.map_read_async(
+                    0,
+                    size,
+                    move |result: wgpu::BufferMapAsyncResult<&[u8]>| {
+                        let data = result.expect("failed to map a readback buffer").data;
+                        // the destination is borrowed by the arena of the readback
+                        let dest = unsafe { slice::from_raw_parts_mut(dest, len) };
+                        match layout {
+                            ReadbackLayout::Linear => dest.copy_from_slice(&data[..len]),
+                            ReadbackLayout::Rows {
+                                row_size,
+                                row_pitch,
+                                height,
+                                bgra,
+                            } => {
+                                for row in 0..height {
+                                    let src = &data[row * row_pitch..row * row_pitch + row_size];
+                                    let dst = &mut dest[row * row_size..(row + 1) * row_size];
+                                    dst.copy_from_slice(src);
+                                    if bgra {
+                                        for texel in dst.chunks_mut(4) {
+                                            texel.swap(0, 2);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                );
+                self.device.poll(true);
+            }
+            self.cpu_wait += start.elapsed();
+        }
+        SubmitStats::from_durations(self.cpu_wait, Duration::from_secs(0))
+    }
+}
@@ -0,0 +1,152 @@
+use autograph_api::{
+    format::{ComponentLayout, FormatFeatureFlags, FormatProperties, NumericFormat},
+    Format,
+};
+
+/// Returns the WebGPU texture format equivalent to the specified format, or `None` if there is
+/// none.
+///
+/// Three-component formats have no equivalent: the API replaces them with their
+/// [fallback formats](autograph_api::Format::fallback_formats).
+pub fn texture_format(fmt: Format) -> Option<wgpu::TextureFormat> {
+    use wgpu::TextureFormat as T;
+    let format = match fmt {
+        Format::R8_UNORM => T::R8Unorm,
+        Format::R8_SNORM => T::R8Snorm,
+        Format::R8_UINT => T::R8Uint,
+        Format::R8_SINT => T::R8Sint,
+        Format::R16_UINT => T::R16Uint,
+        Format::R16_SINT => T::R16Sint,
+        Format::R16_SFLOAT => T::R16Float,
+        Format::R8G8_UNORM => T::Rg8Unorm,
+        Format::R8G8_SNORM => T::Rg8Snorm,
+        Format::R8G8_UINT => T::Rg8Uint,
+        Format::R8G8_SINT => T::Rg8Sint,
+        Format::R32_UINT => T::R32Uint,
+        Format::R32_SINT => T::R32Sint,
+        Format::R32_SFLOAT => T::R32Float,
+        Format::R16G16_UINT => T::Rg16Uint,
+        Format::R16G16_SINT => T::Rg16Sint,
+        Format::R16G16_SFLOAT => T::Rg16Float,
+        Format::R8G8B8A8_UNORM => T::Rgba8Unorm,
+        Format::R8G8B8A8_SRGB => T::Rgba8UnormSrgb,
+        Format::R8G8B8A8_SNORM => T::Rgba8Snorm,
+        Format::R8G8B8A8_UINT => T::Rgba8Uint,
+        Format::R8G8B8A8_SINT => T::Rgba8Sint,
+        Format::B8G8R8A8_UNORM => T::Bgra8Unorm,
+        Format::B8G8R8A8_SRGB => T::Bgra8UnormSrgb,
+        Format::A2B10G10R10_UNORM_PACK32 => T::Rgb10a2Unorm,
+        Format::B10G11R11_UFLOAT_PACK32 => T::Rg11b10Float,
+        Format::R32G32_UINT => T::Rg32Uint,
+        Format::R32G32_SINT => T::Rg32Sint,
+        Format::R32G32_SFLOAT => T::Rg32Float,
+        Format::R16G16B16A16_UINT => T::Rgba16Uint,
+        Format::R16G16B16A16_SINT => T::Rgba16Sint,
+        Format::R16G16B16A16_SFLOAT => T::Rgba16Float,
+        Format::R32G32B32A32_UINT => T::Rgba32Uint,
+        Format::R32G32B32A32_SINT => T::Rgba32Sint,
+        Format::R32G32B32A32_SFLOAT => T::Rgba32Float,
+        Format::D32_SFLOAT => T::Depth32Float,
+        Format::X8_D24_UNORM_PACK32 => T::Depth24Plus,
+        Format::D24_UNORM_S8_UINT => T::Depth24PlusStencil8,
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// Returns whether the format has a depth or stencil component.
+pub fn is_depth_stencil_format(fmt: Format) -> bool {
+    match fmt.get_format_info().component_layout {
+        ComponentLayout::D | ComponentLayout::DS | ComponentLayout::S | ComponentLayout::XD => true,
+        _ => false,
+    }
+}
+
+/// Returns whether images of the format can be bound as storage textures.
+fn is_storage_format(fmt: Format) -> bool {
+    match fmt {
+        Format::R32_UINT
+        | Format::R32_SINT
+        | Format::R32_SFLOAT
+        | Format::R32G32_UINT
+        | Format::R32G32_SINT
+        | Format::R32G32_SFLOAT
+        | Format::R8G8B8A8_UNORM
+        | Format::R8G8B8A8_SNORM
+        | Format::R8G8B8A8_UINT
+        | Format::R8G8B8A8_SINT
+        | Format::R16G16B16A16_UINT
+        | Format::R16G16B16A16_SINT
+        | Format::R16G16B16A16_SFLOAT
+        | Format::R32G32B32A32_UINT
+        | Format::R32G32B32A32_SINT
+        | Format::R32G32B32A32_SFLOAT => true,
+        _ => false,
+    }
+}
+
+/// Returns the operations supported on images of the specified format.
+///
+/// WebGPU does not provide a way to query them: these are the guarantees of the specification.
+pub fn format_properties(fmt: Format) -> FormatProperties {
+    let mut features = FormatFeatureFlags::empty();
+    if texture_format(fmt).is_some() {
+        features |= FormatFeatureFlags::SAMPLED_IMAGE;
+        if is_depth_stencil_format(fmt) {
+            features |= FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT;
+        } else {
+            // snorm formats are not renderable
+            if let NumericFormat::SNORM = fmt.get_format_info().format_type {
+            } else {
+                features |= FormatFeatureFlags::COLOR_ATTACHMENT;
+            }
+            if is_storage_format(fmt) {
+                features |= FormatFeatureFlags::STORAGE_IMAGE;
+            }
+        }
+    }
+    FormatProperties {
+        features,
+        emulated: fmt == Format::X8_D24_UNORM_PACK32 || fmt == Format::D24_UNORM_S8_UINT,
+    }
+}
+
+/// Returns the WebGPU format of vertex attributes of the specified format.
+///
+/// Panics if there is no equivalent format.
+pub fn vertex_format(fmt: Format) -> wgpu::VertexFormat {
+    use wgpu::VertexFormat as V;
+    match fmt {
+        Format::R8G8_UINT => V::Uchar2,
+        Format::R8G8B8A8_UINT => V::Uchar4,
+        Format::R8G8_SINT => V::Char2,
+        Format::R8G8B8A8_SINT => V::Char4,
+        Format::R8G8_UNORM => V::Uchar2Norm,
+        Format::R8G8B8A8_UNORM => V::Uchar4Norm,
+        Format::R8G8_SNORM => V::Char2Norm,
+        Format::R8G8B8A8_SNORM => V::Char4Norm,
+        Format::R16G16_UINT => V::Ushort2,
+        Format::R16G16B16A16_UINT => V::Ushort4,
+        Format::R16G16_SINT => V::Short2,
+        Format::R16G16B16A16_SINT => V::Short4,
+        Format::R16G16_UNORM => V::Ushort2Norm,
+        Format::R16G16B16A16_UNORM => V::Ushort4Norm,
+        Format::R16G16_SNORM => V::Short2Norm,
+        Format::R16G16B16A16_SNORM => V::Short4Norm,
+        Format::R16G16_SFLOAT => V::Half2,
+        Format::R16G16B16A16_SFLOAT => V::Half4,
+        Format::R32_SFLOAT => V::Float,
+        Format::R32G32_SFLOAT => V::Float2,
+        Format::R32G32B32_SFLOAT => V::Float3,
+        Format::R32G32B32A32_SFLOAT => V::Float4,
+        Format::R32_UINT => V::Uint,
+        Format::R32G32_UINT => V::Uint2,
+        Format::R32G32B32_UINT => V::Uint3,
+        Format::R32G32B32A32_UINT => V::Uint4,
+        Format::R32_SINT => V::Int,
+        Format::R32G32_SINT => V::Int2,
+        Format::R32G32B32_SINT => V::Int3,
+        Format::R32G32B32A32_SINT => V::Int4,
+        _ => panic!("Unsupported vertex attribute format: {:?}", fmt),
+    }
+}
@@ -0,0 +1,236 @@
+use crate::format::{format_properties, texture_format};
+use autograph_api::{
+    descriptor::{ResourceShape, SubresourceRange},
+    format::{Format, FormatFeatureFlags},
+    image::{Dimensions, ImageUsageFlags},
+    traits,
+};
+use std::fmt;
+
+/// Required alignment of the rows of image data in copies between buffers and textures.
+pub(crate) const ROW_PITCH_ALIGNMENT: usize = 256;
+
+/// Image of the WebGPU backend.
+pub struct WgpuImage {
+    pub(crate) texture: wgpu::Texture,
+    pub(crate) format: Format,
+    pub(crate) texture_format: wgpu::TextureFormat,
+    /// Dimensions, with swapchain-relative dimensions resolved.
+    pub(crate) dimensions: Dimensions,
+    pub(crate) mip_levels: u32,
+    pub(crate) samples: u32,
+}
+
+impl fmt::Debug for WgpuImage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "WgpuImage {{ format: {:?}, dimensions: {:?}, mip_levels: {}, samples: {} }}",
+            self.format, self.dimensions, self.mip_levels, self.samples
+        )
+    }
+}
+
+impl traits::Image for WgpuImage {
+    fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+}
+
+impl WgpuImage {
+    /// Creates an image. `dimensions` must not be swapchain-relative.
+    ///
+    /// All textures can be copied to and from and sampled. Textures of renderable formats can be
+    /// used as render targets, so that they can be cleared and presented.
+    ///
+    /// Panics if the format is not supported.
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        format: Format,
+        dimensions: Dimensions,
+        mip_levels: u32,
+        samples: u32,
+        usage: ImageUsageFlags,
+    ) -> WgpuImage {
+        let texture_format = texture_format(format)
+            .unwrap_or_else(|| panic!("unsupported image format: {:?}", format));
+        let features = format_properties(format).features;
+        let mut tex_usage = wgpu::TextureUsage::COPY_SRC
+            | wgpu::TextureUsage::COPY_DST
+            | wgpu::TextureUsage::SAMPLED;
+        if features.intersects(
+            FormatFeatureFlags::COLOR_ATTACHMENT | FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT,
+        ) {
+            tex_usage |= wgpu::TextureUsage::OUTPUT_ATTACHMENT;
+        }
+        if usage.contains(ImageUsageFlags::STORAGE) {
+            assert!(
+                features.contains(FormatFeatureFlags::STORAGE_IMAGE),
+                "format {:?} cannot be used for storage images",
+                format
+            );
+            tex_usage |= wgpu::TextureUsage::STORAGE;
+        }
+
+        let (width, height, depth) = dimensions.width_height_depth();
+        let (dimension, depth) = match dimensions {
+            Dimensions::Dim1d { .. } => (wgpu::TextureDimension::D1, 1),
+            Dimensions::Dim3d { .. } => (wgpu::TextureDimension::D3, depth),
+            Dimensions::Dim2d { .. } | Dimensions::Cubemap { .. } => {
+                (wgpu::TextureDimension::D2, 1)
+            }
+            Dimensions::SwapchainRelative { .. } => {
+                panic!("swapchain-relative dimensions must be resolved first")
+            }
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth,
+            },
+            array_layer_count: dimensions.array_layers_with_cube(),
+            mip_level_count: mip_levels,
+            sample_count: samples,
+            dimension,
+            format: texture_format,
+            usage: tex_usage,
+        });
+
+        WgpuImage {
+            texture,
+            format,
+            texture_format,
+            dimensions,
+            mip_levels,
+            samples,
+        }
+    }
+
+    /// Returns the size of a mipmap level.
+    pub(crate) fn level_size(&self, mip_level: u32) -> (u32, u32, u32) {
+        let (width, height, depth) = self.dimensions.width_height_depth();
+        (
+            (width >> mip_level).max(1),
+            (height >> mip_level).max(1),
+            (depth >> mip_level).max(1),
+        )
+    }
+
+    /// Creates a view of a subresource, bound as a texture or storage image of the specified
+    /// shape.
+    pub(crate) fn create_view(
+        &self,
+        subresource: &SubresourceRange,
+        shape: ResourceShape,
+    ) -> wgpu::TextureView {
+        let dimension = match shape {
+            ResourceShape::R1d => wgpu::TextureViewDimension::D1,
+            ResourceShape::R2d | ResourceShape::R2dMultisample => wgpu::TextureViewDimension::D2,
+            ResourceShape::R2dArray => wgpu::TextureViewDimension::D2Array,
+            ResourceShape::R3d => wgpu::TextureViewDimension::D3,
+            ResourceShape::RCube => wgpu::TextureViewDimension::Cube,
+            ResourceShape::R1dArray | ResourceShape::R2dMultisampleArray => {
+                panic!("unsupported texture shape: {:?}", shape)
+            }
+        };
+        let layers = self.dimensions.array_layers_with_cube();
+        self.texture.create_view(&wgpu::TextureViewDescriptor {
+            format: self.texture_format,
+            dimension,
+            aspect: wgpu::TextureAspect::All,
+            base_mip_level: subresource.base_mip_level,
+            level_count: subresource
+                .level_count
+                .unwrap_or(self.mip_levels - subresource.base_mip_level),
+            base_array_layer: subresource.base_array_layer,
+            array_layer_count: subresource
+                .layer_count
+                .unwrap_or(layers - subresource.base_array_layer),
+        })
+    }
+
+    /// Creates a 2D view of one layer of a mipmap level, to render into.
+    pub(crate) fn create_attachment_view(&self, mip_level: u32, layer: u32) -> wgpu::TextureView {
+        self.texture.create_view(&wgpu::TextureViewDescriptor {
+            format: self.texture_format,
+            dimension: wgpu::TextureViewDimension::D2,
+            aspect: wgpu::TextureAspect::All,
+            base_mip_level: mip_level,
+            level_count: 1,
+            base_array_layer: layer,
+            array_layer_count: 1,
+        })
+    }
+
+    /// Records the upload of a region of a mipmap level.
+    ///
+    /// `row_pitch` is the number of bytes between two rows in `data`, or 0 if rows are tightly
+    /// packed. The data is copied into a staging buffer, with rows padded to the alignment
+    /// required by WebGPU.
+    pub(crate) fn upload_region(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        mip_level: u32,
+        offset: (u32, u32, u32),
+        size: (u32, u32, u32),
+        row_pitch: usize,
+        data: &[u8],
+    ) {
+        let texel_size = self.format.byte_size();
+        let row_size = size.0 as usize * texel_size;
+        let row_pitch = if row_pitch == 0 { row_size } else { row_pitch };
+        let rows = size.1 as usize * size.2 as usize;
+        assert!(
+            rows == 0 || data.len() >= (rows - 1) * row_pitch + row_size,
+            "not enough data for an image region of size {:?} ({} bytes)",
+            size,
+            data.len()
+        );
+
+        let aligned_pitch =
+            (row_size + ROW_PITCH_ALIGNMENT - 1) / ROW_PITCH_ALIGNMENT * ROW_PITCH_ALIGNMENT;
+        let mut staging_data = vec![0u8; aligned_pitch * rows];
+        for row in 0..rows {
+            staging_data[row * aligned_pitch..row * aligned_pitch + row_size]
+                .copy_from_slice(&data[row * row_pitch..row * row_pitch + row_size]);
+        }
+        let staging = device
+            .create_buffer_mapped(staging_data.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(&staging_data);
+
+        // layers of 1D and 2D arrays and cubemaps are slices of the region
+        let (origin_z, array_layer, depth) = match self.dimensions {
+            Dimensions::Dim3d { .. } => (offset.2, 0, size.2),
+            _ => (0, offset.2, 1),
+        };
+        let layer_count = if depth == 1 { size.2 } else { 1 };
+        for layer in 0..layer_count {
+            encoder.copy_buffer_to_texture(
+                wgpu::BufferCopyView {
+                    buffer: &staging,
+                    offset: (layer as usize * size.1 as usize * aligned_pitch) as u64,
+                    row_pitch: aligned_pitch as u32,
+                    image_height: size.1,
+                },
+                wgpu::TextureCopyView {
+                    texture: &self.texture,
+                    mip_level,
+                    array_layer: array_layer + layer,
+                    origin: wgpu::Origin3d {
+                        x: offset.0 as f32,
+                        y: offset.1 as f32,
+                        z: origin_z as f32,
+                    },
+                },
+                wgpu::Extent3d {
+                    width: size.0,
+                    height: size.1,
+                    depth,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! WebGPU backend for autograph-api, running on wgpu.
+//!
+//! ### Signatures and argument blocks
+//!
+//! Each signature with descriptors maps to a bind group layout, and each argument block of
+//! such a signature to a bind group, created with the block. The bind groups of an argument tree
+//! are bound to consecutive sets, depth-first with inherited blocks first: the shaders of
+//! a pipeline must declare the descriptors of the n-th signature with descriptors in set n.
+//!
+//! Samplers and textures are separate in WebGPU: combined image-samplers and texel buffers are
+//! not supported. Empty descriptors are bound to dummy resources.
+//!
+//! ### Pipelines
+//!
+//! WebGPU render pipelines include the formats of their attachments, which are only known when
+//! the pipeline is used with an argument block. The wgpu pipelines are created on the first use
+//! of a pipeline with a set of attachment formats, and cached: `warm_up_pipelines` does nothing.
+//! Geometry and tessellation shaders, multiview, logic operations, depth bounds tests and
+//! non-fill polygon modes are not supported. The entry point of all shader modules is `main`.
+//!
+//! ### Render passes
+//!
+//! Consecutive draws with the same render targets are recorded in a single render pass.
+//! The load operation of a render target (see `FragmentOutputDescription`) is applied the first
+//! time it is rendered into in a frame; afterwards, its contents are preserved.
+//! Clears are render passes with no draws.
+//!
+//! ### Presentation
+//!
+//! The images of the swap chain are only accessible during presentation:
+//! `acquire_swapchain_image` returns an intermediate image, which is drawn into the next image
+//! of the swap chain by the "present" command.
+//!
+//! ### Limitations
+//!
+//! There are no occlusion queries (conditional draws are always executed), no indirect
+//! dispatches, no mipmap generation, and integer images cannot be cleared. Copies to host memory
+//! wait for the GPU at the end of the frame.
+//!
+//! ### Texture & viewport coordinates
+//!
+//! Same as the API: texcoord (0,0) samples the upper-left pixel, and the first scanline of
+//! texture data is the topmost row of pixels. No data is flipped.
+//!
+#[macro_use]
+extern crate log;
+
+mod backend;
+mod buffer;
+mod command;
+mod format;
+mod image;
+mod pipeline;
+pub mod prelude;
+mod query;
+mod sampler;
+mod swapchain;
+
+pub use self::backend::{InstanceConfig, WgpuBackend, WgpuInstance};
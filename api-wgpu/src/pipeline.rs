@@ -0,0 +1,990 @@
+use crate::{
+    backend::WgpuArena,
+    buffer::{WgpuBuffer, WgpuHostReference},
+    format::vertex_format,
+    image::WgpuImage,
+    sampler::SamplerCache,
+    WgpuBackend,
+};
+use autograph_api::{
+    descriptor::{Descriptor, ResourceBindingType, ResourceShape, SubresourceRange},
+    format::Format,
+    image::{DepthStencilView, Dimensions, ImageUsageFlags, RenderTargetView, SamplerDescription},
+    pipeline::{
+        validate::vertex_attribute_locations, BareArgumentBlock, BlendFactor, BlendOp,
+        ColorBlendAttachmentState, ColorComponentFlags, CompareOp, CullModeFlags, DepthBias,
+        DepthBoundTest, FragmentOutputDescription, FrontFace, GraphicsPipelineCreateInfo,
+        PolygonMode, PrimitiveTopology, Scissor, ScissorsOwned, ShaderStageFlags,
+        SignatureDescription, StencilOp, StencilOpState, StencilTest, Viewport, ViewportsOwned,
+    },
+    vertex::{IndexBufferView, IndexFormat, VertexBufferView, VertexInputRate},
+};
+use std::{
+    fmt,
+    ops::Range,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Magic number of SPIR-V modules.
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+//--------------------------------------------------------------------------------------------------
+
+/// Shader module of the WebGPU backend. The entry point of all shaders is `main`.
+pub struct WgpuShaderModule {
+    pub(crate) module: wgpu::ShaderModule,
+    pub(crate) stage: ShaderStageFlags,
+}
+
+impl fmt::Debug for WgpuShaderModule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "WgpuShaderModule {{ stage: {:?} }}", self.stage)
+    }
+}
+
+impl WgpuShaderModule {
+    /// Creates a shader module from SPIR-V bytecode.
+    ///
+    /// Panics if `spirv` is not a SPIR-V module.
+    pub(crate) fn new(device: &wgpu::Device, spirv: &[u8], stage: ShaderStageFlags) -> Self {
+        assert!(
+            spirv.len() % 4 == 0 && spirv.len() >= 4,
+            "invalid SPIR-V module: the size of the bytecode must be a multiple of 4"
+        );
+        let words: Vec<u32> = spirv
+            .chunks(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        assert_eq!(
+            words[0], SPIRV_MAGIC,
+            "invalid SPIR-V module: only SPIR-V shaders are supported by the WebGPU backend"
+        );
+        WgpuShaderModule {
+            module: device.create_shader_module(&words),
+            stage,
+        }
+    }
+
+    fn stage_descriptor(&self) -> wgpu::PipelineStageDescriptor {
+        wgpu::PipelineStageDescriptor {
+            module: &self.module,
+            entry_point: "main",
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Signature of the WebGPU backend.
+///
+/// The descriptors of a signature are described by a bind group layout, with the binding
+/// indices of the descriptors. The bind groups of a signature tree are numbered depth-first,
+/// inherited signatures first, skipping the signatures without descriptors: this is the order
+/// of the descriptor sets expected by shaders (see `validate::validate_descriptors`).
+pub struct WgpuSignature {
+    pub(crate) inherited: Vec<*const WgpuSignature>,
+    /// `None` if the signature has no descriptors.
+    pub(crate) bind_group_layout: Option<wgpu::BindGroupLayout>,
+    /// Binding index and type of each descriptor.
+    pub(crate) descriptors: Vec<(u32, ResourceBindingType)>,
+    pub(crate) fragment_outputs: Vec<FragmentOutputDescription>,
+    pub(crate) depth_stencil_fragment_output: Option<FragmentOutputDescription>,
+}
+
+// It's read-only so it should be safe?
+unsafe impl Sync for WgpuSignature {}
+
+impl fmt::Debug for WgpuSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("WgpuSignature")
+            .field("inherited", &self.inherited)
+            .field("descriptors", &self.descriptors)
+            .field("fragment_outputs", &self.fragment_outputs)
+            .finish()
+    }
+}
+
+fn binding_type(ty: ResourceBindingType) -> wgpu::BindingType {
+    match ty {
+        ResourceBindingType::Sampler => wgpu::BindingType::Sampler,
+        ResourceBindingType::Texture(_) => wgpu::BindingType::SampledTexture,
+        ResourceBindingType::RwImage(_) => wgpu::BindingType::StorageTexture,
+        ResourceBindingType::ConstantBuffer => wgpu::BindingType::UniformBuffer { dynamic: false },
+        ResourceBindingType::RwBuffer => wgpu::BindingType::StorageBuffer { dynamic: false },
+        ResourceBindingType::TextureSampler(_) => panic!(
+            "combined image samplers are not supported by the WebGPU backend: \
+             use separate texture and sampler descriptors"
+        ),
+        ResourceBindingType::TexelBuffer | ResourceBindingType::RwTexelBuffer => {
+            panic!("texel buffers are not supported by the WebGPU backend")
+        }
+    }
+}
+
+impl WgpuSignature {
+    pub(crate) fn new<'a>(
+        arena: &'a WgpuArena,
+        device: &wgpu::Device,
+        inherited: &[&'a WgpuSignature],
+        description: &SignatureDescription,
+    ) -> &'a WgpuSignature {
+        let inherited = inherited.iter().map(|&sig| sig as *const _).collect();
+        let descriptors = description
+            .descriptors
+            .iter()
+            .map(|d| (d.index, d.ty))
+            .collect::<Vec<_>>();
+
+        let bind_group_layout = if descriptors.is_empty() {
+            None
+        } else {
+            let bindings = description
+                .descriptors
+                .iter()
+                .map(|d| wgpu::BindGroupLayoutBinding {
+                    binding: d.index,
+                    // the same signature can be used by graphics and compute pipelines
+                    visibility: wgpu::ShaderStage::VERTEX
+                        | wgpu::ShaderStage::FRAGMENT
+                        | wgpu::ShaderStage::COMPUTE,
+                    ty: binding_type(d.ty),
+                })
+                .collect::<Vec<_>>();
+            Some(
+                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    bindings: &bindings,
+                }),
+            )
+        };
+
+        arena.signatures.alloc(WgpuSignature {
+            inherited,
+            bind_group_layout,
+            descriptors,
+            fragment_outputs: description.fragment_outputs.to_vec(),
+            depth_stencil_fragment_output: description.depth_stencil_fragment_output,
+        })
+    }
+
+    /// Collects the bind group layouts of the signature tree, in set order.
+    fn collect_bind_group_layouts<'a>(&'a self, out: &mut Vec<&'a wgpu::BindGroupLayout>) {
+        for &inherited in self.inherited.iter() {
+            // inherited signatures live in the arena of this signature, or an arena that
+            // outlives it
+            unsafe { &*inherited }.collect_bind_group_layouts(out);
+        }
+        if let Some(ref layout) = self.bind_group_layout {
+            out.push(layout);
+        }
+    }
+
+    /// Creates the pipeline layout of a pipeline with this root signature.
+    fn create_pipeline_layout(&self, device: &wgpu::Device) -> wgpu::PipelineLayout {
+        let mut layouts = Vec::new();
+        self.collect_bind_group_layouts(&mut layouts);
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &layouts,
+        })
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Resources bound to empty descriptors.
+pub(crate) struct DummyResources {
+    pub(crate) buffer: WgpuBuffer,
+    /// 2D image with 6 layers, so that it can be viewed as a cubemap.
+    pub(crate) image: WgpuImage,
+}
+
+impl DummyResources {
+    pub(crate) fn new(device: &wgpu::Device) -> DummyResources {
+        DummyResources {
+            buffer: WgpuBuffer::new_immutable(device, 256, &[0; 256]),
+            image: WgpuImage::new(
+                device,
+                Format::R8G8B8A8_UNORM,
+                Dimensions::Dim2d {
+                    width: 1,
+                    height: 1,
+                    array_layers: 6,
+                },
+                1,
+                1,
+                ImageUsageFlags::SAMPLED | ImageUsageFlags::STORAGE,
+            ),
+        }
+    }
+}
+
+/// An image attached to a render pass.
+pub(crate) struct Attachment {
+    pub(crate) image: *const WgpuImage,
+    pub(crate) view: wgpu::TextureView,
+    pub(crate) mip_level: u32,
+    pub(crate) layer: u32,
+    pub(crate) output: FragmentOutputDescription,
+}
+
+impl Attachment {
+    fn new(
+        image: &WgpuImage,
+        subresource: SubresourceRange,
+        output: FragmentOutputDescription,
+    ) -> Self {
+        assert!(
+            subresource.layer_count.unwrap_or(1) == 1,
+            "layered render targets are not supported by the WebGPU backend"
+        );
+        Attachment {
+            image,
+            view: image
+                .create_attachment_view(subresource.base_mip_level, subresource.base_array_layer),
+            mip_level: subresource.base_mip_level,
+            layer: subresource.base_array_layer,
+            output,
+        }
+    }
+
+    /// Returns the image of the attachment, and the subresource rendered into.
+    pub(crate) fn key(&self) -> (*const WgpuImage, u32, u32) {
+        (self.image, self.mip_level, self.layer)
+    }
+
+    pub(crate) fn image(&self) -> &WgpuImage {
+        // the image outlives the argument block
+        unsafe { &*self.image }
+    }
+}
+
+/// Argument block of the WebGPU backend.
+///
+/// The descriptors of the block are in a bind group, created with the block.
+pub struct WgpuArgumentBlock {
+    pub(crate) inherited: Vec<*const WgpuArgumentBlock>,
+    pub(crate) bind_group: Option<wgpu::BindGroup>,
+    /// Views of the images referenced by the bind group.
+    _views: Vec<wgpu::TextureView>,
+    pub(crate) vertex_buffers: Vec<(*const WgpuBuffer, u64)>,
+    pub(crate) index_buffer: Option<(*const WgpuBuffer, u64, IndexFormat)>,
+    pub(crate) color_attachments: Vec<Attachment>,
+    pub(crate) depth_stencil_attachment: Option<Attachment>,
+    pub(crate) viewports: Vec<Viewport>,
+    pub(crate) scissors: Vec<Scissor>,
+    pub(crate) stencil_reference: Option<u32>,
+}
+
+// It's read-only once created, and the referenced objects outlive it.
+unsafe impl Sync for WgpuArgumentBlock {}
+
+impl fmt::Debug for WgpuArgumentBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("WgpuArgumentBlock")
+            .field("inherited", &self.inherited)
+            .field("vertex_buffers", &self.vertex_buffers)
+            .field("color_attachments", &self.color_attachments.len())
+            .field(
+                "depth_stencil_attachment",
+                &self.depth_stencil_attachment.is_some(),
+            )
+            .field("viewports", &self.viewports)
+            .field("scissors", &self.scissors)
+            .field("stencil_reference", &self.stencil_reference)
+            .finish()
+    }
+}
+
+/// A resource bound in a bind group.
+enum BoundResource {
+    Buffer(*const wgpu::Buffer, Range<u64>),
+    /// Index of the view in the views of the block.
+    TextureView(usize),
+    Sampler(*const wgpu::Sampler),
+}
+
+fn buffer_range(buffer: &WgpuBuffer, offset: usize, size: Option<usize>) -> Range<u64> {
+    let offset = offset as u64;
+    let size = size.map(|s| s as u64).unwrap_or(buffer.size - offset);
+    assert!(
+        offset + size <= buffer.size,
+        "buffer range out of bounds (offset {}, size {}, buffer size {})",
+        offset,
+        size,
+        buffer.size
+    );
+    offset..offset + size
+}
+
+fn texture_shape(ty: ResourceBindingType) -> ResourceShape {
+    match ty {
+        ResourceBindingType::Texture(shape) | ResourceBindingType::RwImage(shape) => shape,
+        _ => panic!("unexpected image descriptor for a binding of type {:?}", ty),
+    }
+}
+
+impl WgpuArgumentBlock {
+    pub(crate) unsafe fn new<'a>(
+        arena: &'a WgpuArena,
+        device: &wgpu::Device,
+        sampler_cache: &mut SamplerCache,
+        dummies: &DummyResources,
+        signature: &'a WgpuSignature,
+        inherited: impl IntoIterator<Item = BareArgumentBlock<'a, WgpuBackend>>,
+        descriptors: impl IntoIterator<Item = Descriptor<'a, WgpuBackend>>,
+        vertex_buffers: impl IntoIterator<Item = VertexBufferView<'a, WgpuBackend>>,
+        index_buffer: Option<IndexBufferView<'a, WgpuBackend>>,
+        render_targets: impl IntoIterator<Item = RenderTargetView<'a, WgpuBackend>>,
+        depth_stencil_render_target: Option<DepthStencilView<'a, WgpuBackend>>,
+        viewports: impl IntoIterator<Item = Viewport>,
+        scissors: impl IntoIterator<Item = Scissor>,
+        stencil_reference: Option<u32>,
+    ) -> &'a WgpuArgumentBlock {
+        let inherited = inherited
+            .into_iter()
+            .map(|b| b.0 as *const _)
+            .collect::<Vec<_>>();
+
+        let mut views = Vec::new();
+        let mut resources = Vec::new();
+        for (d, &(binding, ty)) in descriptors.into_iter().zip(signature.descriptors.iter()) {
+            let resource = match d {
+                Descriptor::Sampler { desc } => {
+                    BoundResource::Sampler(sampler_cache.get_sampler(device, &desc))
+                }
+                Descriptor::Texture { image, subresource }
+                | Descriptor::RwImage { image, subresource } => {
+                    views.push(image.create_view(&subresource, texture_shape(ty)));
+                    BoundResource::TextureView(views.len() - 1)
+                }
+                Descriptor::ConstantBuffer {
+                    buffer,
+                    offset,
+                    size,
+                }
+                | Descriptor::RwBuffer {
+                    buffer,
+                    offset,
+                    size,
+                } => BoundResource::Buffer(&buffer.buffer, buffer_range(buffer, offset, size)),
+                Descriptor::HostConstantBuffer { reference } => {
+                    let reference: &WgpuHostReference = reference;
+                    BoundResource::Buffer(&reference.buffer.buffer, 0..reference.buffer.size)
+                }
+                Descriptor::TextureSampler { .. } | Descriptor::TextureSamplerLod { .. } => {
+                    panic!(
+                        "combined image samplers are not supported by the WebGPU backend: \
+                         use separate texture and sampler descriptors"
+                    )
+                }
+                Descriptor::TexelBuffer { .. } | Descriptor::RwTexelBuffer { .. } => {
+                    panic!("texel buffers are not supported by the WebGPU backend")
+                }
+                Descriptor::Empty => match ty {
+                    ResourceBindingType::Sampler => BoundResource::Sampler(
+                        sampler_cache
+                            .get_sampler(device, &SamplerDescription::LINEAR_MIPMAP_LINEAR),
+                    ),
+                    ResourceBindingType::Texture(shape) | ResourceBindingType::RwImage(shape) => {
+                        let subresource = SubresourceRange {
+                            base_mip_level: 0,
+                            level_count: None,
+                            base_array_layer: 0,
+                            layer_count: if shape == ResourceShape::RCube {
+                                None
+                            } else {
+                                Some(1)
+                            },
+                        };
+                        views.push(dummies.image.create_view(&subresource, shape));
+                        BoundResource::TextureView(views.len() - 1)
+                    }
+                    _ => BoundResource::Buffer(&dummies.buffer.buffer, 0..dummies.buffer.size),
+                },
+            };
+            resources.push((binding, resource));
+        }
+
+        let bind_group = signature.bind_group_layout.as_ref().map(|layout| {
+            let bindings = resources
+                .iter()
+                .map(|&(binding, ref resource)| wgpu::Binding {
+                    binding,
+                    resource: match *resource {
+                        BoundResource::Buffer(buffer, ref range) => wgpu::BindingResource::Buffer {
+                            buffer: &*buffer,
+                            range: range.clone(),
+                        },
+                        BoundResource::TextureView(i) => {
+                            wgpu::BindingResource::TextureView(&views[i])
+                        }
+                        BoundResource::Sampler(sampler) => {
+                            wgpu::BindingResource::Sampler(&*sampler)
+                        }
+                    },
+                })
+                .collect::<Vec<_>>();
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout,
+                bindings: &bindings,
+            })
+        });
+
+        let vertex_buffers = vertex_buffers
+            .into_iter()
+            .map(|vb| (vb.buffer() as *const _, vb.offset() as u64))
+            .collect();
+        let index_buffer =
+            index_buffer.map(|ib| (ib.buffer as *const _, ib.offset as u64, ib.format));
+
+        let color_attachments = render_targets
+            .into_iter()
+            .zip(signature.fragment_outputs.iter())
+            .map(|(rt, &output)| Attachment::new(rt.inner(), rt.subresource(), output))
+            .collect();
+        let depth_stencil_attachment = depth_stencil_render_target.map(|ds| {
+            Attachment::new(
+                ds.inner(),
+                ds.subresource(),
+                signature
+                    .depth_stencil_fragment_output
+                    .unwrap_or(FragmentOutputDescription::DEFAULT),
+            )
+        });
+
+        arena.argument_blocks.alloc(WgpuArgumentBlock {
+            inherited,
+            bind_group,
+            _views: views,
+            vertex_buffers,
+            index_buffer,
+            color_attachments,
+            depth_stencil_attachment,
+            viewports: viewports.into_iter().collect(),
+            scissors: scissors.into_iter().collect(),
+            stencil_reference,
+        })
+    }
+
+    /// Collects the blocks of the argument tree, depth-first with inherited blocks first.
+    pub(crate) fn flatten<'a>(&'a self, out: &mut Vec<&'a WgpuArgumentBlock>) {
+        for &inherited in self.inherited.iter() {
+            // inherited blocks outlive this block
+            unsafe { &*inherited }.flatten(out);
+        }
+        out.push(self);
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+fn compare_function(op: CompareOp) -> wgpu::CompareFunction {
+    match op {
+        CompareOp::Never => wgpu::CompareFunction::Never,
+        CompareOp::Less => wgpu::CompareFunction::Less,
+        CompareOp::Equal => wgpu::CompareFunction::Equal,
+        CompareOp::LessOrEqual => wgpu::CompareFunction::LessEqual,
+        CompareOp::Greater => wgpu::CompareFunction::Greater,
+        CompareOp::NotEqual => wgpu::CompareFunction::NotEqual,
+        CompareOp::GreaterOrEqual => wgpu::CompareFunction::GreaterEqual,
+        CompareOp::Always => wgpu::CompareFunction::Always,
+    }
+}
+
+fn stencil_operation(op: StencilOp) -> wgpu::StencilOperation {
+    match op {
+        StencilOp::Keep => wgpu::StencilOperation::Keep,
+        StencilOp::Zero => wgpu::StencilOperation::Zero,
+        StencilOp::Replace => wgpu::StencilOperation::Replace,
+        StencilOp::IncrementAndClamp => wgpu::StencilOperation::IncrementClamp,
+        StencilOp::DecrementAndClamp => wgpu::StencilOperation::DecrementClamp,
+        StencilOp::Invert => wgpu::StencilOperation::Invert,
+        StencilOp::IncrementAndWrap => wgpu::StencilOperation::IncrementWrap,
+        StencilOp::DecrementAndWrap => wgpu::StencilOperation::DecrementWrap,
+    }
+}
+
+fn stencil_face(state: &StencilOpState) -> wgpu::StencilStateFaceDescriptor {
+    wgpu::StencilStateFaceDescriptor {
+        compare: compare_function(state.compare_op),
+        fail_op: stencil_operation(state.fail_op),
+        depth_fail_op: stencil_operation(state.depth_fail_op),
+        pass_op: stencil_operation(state.pass_op),
+    }
+}
+
+fn blend_factor(factor: BlendFactor) -> wgpu::BlendFactor {
+    match factor {
+        BlendFactor::Zero => wgpu::BlendFactor::Zero,
+        BlendFactor::One => wgpu::BlendFactor::One,
+        BlendFactor::SrcColor => wgpu::BlendFactor::SrcColor,
+        BlendFactor::OneMinusSrcColor => wgpu::BlendFactor::OneMinusSrcColor,
+        BlendFactor::DstColor => wgpu::BlendFactor::DstColor,
+        BlendFactor::OneMinusDstColor => wgpu::BlendFactor::OneMinusDstColor,
+        BlendFactor::SrcAlpha => wgpu::BlendFactor::SrcAlpha,
+        BlendFactor::OneMinusSrcAlpha => wgpu::BlendFactor::OneMinusSrcAlpha,
+        BlendFactor::DstAlpha => wgpu::BlendFactor::DstAlpha,
+        BlendFactor::OneMinusDstAlpha => wgpu::BlendFactor::OneMinusDstAlpha,
+        BlendFactor::ConstantColor => wgpu::BlendFactor::BlendColor,
+        BlendFactor::OneMinusConstantColor => wgpu::BlendFactor::OneMinusBlendColor,
+        BlendFactor::SrcAlphaSaturate => wgpu::BlendFactor::SrcAlphaSaturated,
+        BlendFactor::ConstantAlpha
+        | BlendFactor::OneMinusConstantAlpha
+        | BlendFactor::Src1Color
+        | BlendFactor::OneMinusSrc1Color
+        | BlendFactor::Src1Alpha
+        | BlendFactor::OneMinusSrc1Alpha => panic!(
+            "unsupported blend factor for the WebGPU backend: {:?}",
+            factor
+        ),
+    }
+}
+
+fn blend_operation(op: BlendOp) -> wgpu::BlendOperation {
+    match op {
+        BlendOp::Add => wgpu::BlendOperation::Add,
+        BlendOp::Subtract => wgpu::BlendOperation::Subtract,
+        BlendOp::ReverseSubtract => wgpu::BlendOperation::ReverseSubtract,
+        BlendOp::Min => wgpu::BlendOperation::Min,
+        BlendOp::Max => wgpu::BlendOperation::Max,
+    }
+}
+
+fn color_state(
+    format: wgpu::TextureFormat,
+    blend: ColorBlendAttachmentState,
+) -> wgpu::ColorStateDescriptor {
+    match blend {
+        ColorBlendAttachmentState::Disabled => wgpu::ColorStateDescriptor {
+            format,
+            color_blend: wgpu::BlendDescriptor::REPLACE,
+            alpha_blend: wgpu::BlendDescriptor::REPLACE,
+            write_mask: wgpu::ColorWrite::ALL,
+        },
+        ColorBlendAttachmentState::Enabled {
+            src_color_blend_factor,
+            dst_color_blend_factor,
+            color_blend_op,
+            src_alpha_blend_factor,
+            dst_alpha_blend_factor,
+            alpha_blend_op,
+            color_write_mask,
+        } => {
+            let mut write_mask = wgpu::ColorWrite::empty();
+            if color_write_mask.contains(ColorComponentFlags::R) {
+                write_mask |= wgpu::ColorWrite::RED;
+            }
+            if color_write_mask.contains(ColorComponentFlags::G) {
+                write_mask |= wgpu::ColorWrite::GREEN;
+            }
+            if color_write_mask.contains(ColorComponentFlags::B) {
+                write_mask |= wgpu::ColorWrite::BLUE;
+            }
+            if color_write_mask.contains(ColorComponentFlags::A) {
+                write_mask |= wgpu::ColorWrite::ALPHA;
+            }
+            wgpu::ColorStateDescriptor {
+                format,
+                color_blend: wgpu::BlendDescriptor {
+                    src_factor: blend_factor(src_color_blend_factor),
+                    dst_factor: blend_factor(dst_color_blend_factor),
+                    operation: blend_operation(color_blend_op),
+                },
+                alpha_blend: wgpu::BlendDescriptor {
+                    src_factor: blend_factor(src_alpha_blend_factor),
+                    dst_factor: blend_factor(dst_alpha_blend_factor),
+                    operation: blend_operation(alpha_blend_op),
+                },
+                write_mask,
+            }
+        }
+    }
+}
+
+fn primitive_topology(topology: PrimitiveTopology) -> wgpu::PrimitiveTopology {
+    match topology {
+        PrimitiveTopology::PointList => wgpu::PrimitiveTopology::PointList,
+        PrimitiveTopology::LineList => wgpu::PrimitiveTopology::LineList,
+        PrimitiveTopology::LineStrip => wgpu::PrimitiveTopology::LineStrip,
+        PrimitiveTopology::TriangleList => wgpu::PrimitiveTopology::TriangleList,
+        PrimitiveTopology::TriangleStrip => wgpu::PrimitiveTopology::TriangleStrip,
+        PrimitiveTopology::TriangleFan => {
+            panic!("triangle fans are not supported by the WebGPU backend")
+        }
+    }
+}
+
+pub(crate) fn index_format(format: IndexFormat) -> wgpu::IndexFormat {
+    match format {
+        IndexFormat::U16 => wgpu::IndexFormat::Uint16,
+        IndexFormat::U32 => wgpu::IndexFormat::Uint32,
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Layout of a vertex buffer of a pipeline.
+struct VertexBufferLayout {
+    stride: u64,
+    step_mode: wgpu::InputStepMode,
+    attributes: Vec<wgpu::VertexAttributeDescriptor>,
+}
+
+fn collect_vertex_buffer_layouts(
+    sig: &SignatureDescription,
+    locations: &mut std::slice::Iter<Option<u32>>,
+    out: &mut Vec<VertexBufferLayout>,
+) {
+    for &inherited in sig.inherited {
+        collect_vertex_buffer_layouts(inherited, locations, out);
+    }
+    for binding in sig.vertex_inputs.iter() {
+        let attributes = binding
+            .layout
+            .elements
+            .iter()
+            .filter_map(|e| {
+                // attributes that match no input of the shader are not bound
+                let location = (*locations.next()?)?;
+                Some(wgpu::VertexAttributeDescriptor {
+                    offset: u64::from(e.offset),
+                    format: vertex_format(e.format),
+                    shader_location: location,
+                })
+            })
+            .collect();
+        out.push(VertexBufferLayout {
+            stride: binding.layout.stride as u64,
+            step_mode: match binding.rate {
+                VertexInputRate::Vertex => wgpu::InputStepMode::Vertex,
+                VertexInputRate::Instance => wgpu::InputStepMode::Instance,
+            },
+            attributes,
+        });
+    }
+}
+
+fn collect_fragment_outputs(sig: &SignatureDescription, out: &mut Vec<FragmentOutputDescription>) {
+    for &inherited in sig.inherited {
+        collect_fragment_outputs(inherited, out);
+    }
+    out.extend(sig.fragment_outputs.iter().cloned());
+}
+
+fn find_index_format(sig: &SignatureDescription) -> Option<IndexFormat> {
+    sig.index_format.or_else(|| {
+        sig.inherited
+            .iter()
+            .filter_map(|&i| find_index_format(i))
+            .next()
+    })
+}
+
+/// Formats of the attachments of a render pass, which select the instance of a graphics
+/// pipeline.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct AttachmentFormats {
+    pub(crate) colors: Vec<wgpu::TextureFormat>,
+    pub(crate) depth_stencil: Option<wgpu::TextureFormat>,
+    pub(crate) samples: u32,
+}
+
+/// Graphics pipeline of the WebGPU backend.
+///
+/// WebGPU pipelines include the formats of their attachments, which are only known once the
+/// pipeline is used with an argument block: the pipeline is created on its first use with
+/// each set of formats, and cached.
+pub struct WgpuGraphicsPipeline {
+    layout: wgpu::PipelineLayout,
+    vertex: *const WgpuShaderModule,
+    fragment: Option<*const WgpuShaderModule>,
+    rasterization_state: wgpu::RasterizationStateDescriptor,
+    primitive_topology: wgpu::PrimitiveTopology,
+    /// Blend state of each color attachment.
+    color_blend: Vec<ColorBlendAttachmentState>,
+    depth_write_enabled: bool,
+    depth_compare: wgpu::CompareFunction,
+    stencil: Option<(
+        wgpu::StencilStateFaceDescriptor,
+        wgpu::StencilStateFaceDescriptor,
+        u32,
+        u32,
+    )>,
+    /// Static stencil reference, or `None` if it is provided by the argument blocks.
+    pub(crate) stencil_reference: Option<u32>,
+    pub(crate) blend_color: wgpu::Color,
+    vertex_buffers: Vec<VertexBufferLayout>,
+    index_format: wgpu::IndexFormat,
+    pub(crate) viewports: ViewportsOwned,
+    pub(crate) scissors: ScissorsOwned,
+    /// Pipelines created for each set of attachment formats. They are never removed, so that
+    /// references to them stay valid for the lifetime of the pipeline.
+    instances: Mutex<Vec<(AttachmentFormats, Box<wgpu::RenderPipeline>)>>,
+    pub(crate) create_time: Duration,
+}
+
+// the shader modules outlive the pipeline
+unsafe impl Sync for WgpuGraphicsPipeline {}
+
+impl fmt::Debug for WgpuGraphicsPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "WgpuGraphicsPipeline {{ instances: {} }}",
+            self.instances.lock().unwrap().len()
+        )
+    }
+}
+
+impl WgpuGraphicsPipeline {
+    pub(crate) fn new<'a>(
+        arena: &'a WgpuArena,
+        device: &wgpu::Device,
+        root_signature: &'a WgpuSignature,
+        root_signature_description: &SignatureDescription,
+        ci: &GraphicsPipelineCreateInfo<'a, '_, WgpuBackend>,
+    ) -> &'a WgpuGraphicsPipeline {
+        let start = Instant::now();
+        assert!(
+            ci.shader_stages.geometry.is_none()
+                && ci.shader_stages.tess_control.is_none()
+                && ci.shader_stages.tess_eval.is_none(),
+            "geometry and tessellation shaders are not supported by the WebGPU backend"
+        );
+        assert!(
+            !ci.view_count.is_multiview(),
+            "multiview pipelines are not supported by the WebGPU backend"
+        );
+        assert!(
+            ci.color_blend_state.logic_op.is_none(),
+            "logic operations are not supported by the WebGPU backend"
+        );
+        if let DepthBoundTest::Enabled { .. } = ci.depth_stencil_state.depth_bounds_test {
+            panic!("the depth bounds test is not supported by the WebGPU backend")
+        }
+
+        let rs = &ci.rasterization_state;
+        assert_eq!(
+            rs.polygon_mode,
+            PolygonMode::Fill,
+            "only the Fill polygon mode is supported by the WebGPU backend"
+        );
+        let cull_mode = if rs.cull_mode == CullModeFlags::NONE {
+            wgpu::CullMode::None
+        } else if rs.cull_mode == CullModeFlags::FRONT {
+            wgpu::CullMode::Front
+        } else if rs.cull_mode == CullModeFlags::BACK {
+            wgpu::CullMode::Back
+        } else {
+            panic!("culling of both faces is not supported by the WebGPU backend")
+        };
+        let (depth_bias, depth_bias_clamp, depth_bias_slope_scale) = match rs.depth_bias {
+            DepthBias::Disabled => (0, 0.0, 0.0),
+            DepthBias::Enabled {
+                constant_factor,
+                clamp,
+                slope_factor,
+            } => (
+                constant_factor.into_inner() as i32,
+                clamp.into_inner(),
+                slope_factor.into_inner(),
+            ),
+        };
+        let rasterization_state = wgpu::RasterizationStateDescriptor {
+            front_face: match rs.front_face {
+                FrontFace::Clockwise => wgpu::FrontFace::Cw,
+                FrontFace::CounterClockwise => wgpu::FrontFace::Ccw,
+            },
+            cull_mode,
+            depth_bias,
+            depth_bias_slope_scale,
+            depth_bias_clamp,
+        };
+
+        let mut fragment_outputs = Vec::new();
+        collect_fragment_outputs(root_signature_description, &mut fragment_outputs);
+        let color_blend = if ci.shader_stages.fragment.is_some() {
+            fragment_outputs
+                .iter()
+                .enumerate()
+                .map(|(i, output)| {
+                    output
+                        .blend
+                        .or_else(|| ci.color_blend_state.attachment(i))
+                        .unwrap_or(ColorBlendAttachmentState::Disabled)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let ds = &ci.depth_stencil_state;
+        let (depth_write_enabled, depth_compare) = if ds.depth_test_enable {
+            (ds.depth_write_enable, compare_function(ds.depth_compare_op))
+        } else {
+            (false, wgpu::CompareFunction::Always)
+        };
+        let (stencil, stencil_reference) = match ds.stencil_test {
+            StencilTest::Disabled => (None, 0),
+            StencilTest::Enabled {
+                ref front,
+                ref back,
+            } => (
+                Some((
+                    stencil_face(front),
+                    stencil_face(back),
+                    front.compare_mask,
+                    front.write_mask,
+                )),
+                front.reference,
+            ),
+        };
+
+        let locations = vertex_attribute_locations(
+            root_signature_description,
+            ci.shader_stages.vertex.reflection().vertex_input_attributes,
+        );
+        let mut vertex_buffers = Vec::new();
+        collect_vertex_buffer_layouts(
+            root_signature_description,
+            &mut locations.iter(),
+            &mut vertex_buffers,
+        );
+
+        let [r, g, b, a] = ci.color_blend_state.blend_constants;
+        let pipeline = WgpuGraphicsPipeline {
+            layout: root_signature.create_pipeline_layout(device),
+            vertex: ci.shader_stages.vertex.inner(),
+            fragment: ci.shader_stages.fragment.map(|s| s.inner() as *const _),
+            rasterization_state,
+            primitive_topology: primitive_topology(ci.input_assembly_state.topology),
+            color_blend,
+            depth_write_enabled,
+            depth_compare,
+            stencil,
+            stencil_reference: if ds.dynamic_stencil_reference {
+                None
+            } else {
+                Some(stencil_reference)
+            },
+            blend_color: wgpu::Color {
+                r: f64::from(r.into_inner()),
+                g: f64::from(g.into_inner()),
+                b: f64::from(b.into_inner()),
+                a: f64::from(a.into_inner()),
+            },
+            vertex_buffers,
+            index_format: index_format(
+                find_index_format(root_signature_description).unwrap_or(IndexFormat::U16),
+            ),
+            viewports: ci.viewport_state.viewports.into(),
+            scissors: ci.viewport_state.scissors.into(),
+            instances: Mutex::new(Vec::new()),
+            create_time: Duration::from_secs(0),
+        };
+        arena.graphics_pipelines.alloc(WgpuGraphicsPipeline {
+            create_time: start.elapsed(),
+            ..pipeline
+        })
+    }
+
+    /// Returns the pipeline for the specified attachment formats, creating it on first use.
+    pub(crate) fn instance(
+        &self,
+        device: &wgpu::Device,
+        formats: &AttachmentFormats,
+    ) -> &wgpu::RenderPipeline {
+        let mut instances = self.instances.lock().unwrap();
+        if let Some(&(_, ref pipeline)) = instances.iter().find(|&&(ref f, _)| f == formats) {
+            let ptr: *const wgpu::RenderPipeline = &**pipeline;
+            // instances are never removed or moved out of their box
+            return unsafe { &*ptr };
+        }
+
+        let (vertex, fragment) = unsafe { (&*self.vertex, self.fragment.map(|f| &*f)) };
+        let color_states = formats
+            .colors
+            .iter()
+            .zip(self.color_blend.iter())
+            .map(|(&format, &blend)| color_state(format, blend))
+            .collect::<Vec<_>>();
+        let depth_stencil_state =
+            formats
+                .depth_stencil
+                .map(|format| wgpu::DepthStencilStateDescriptor {
+                    format,
+                    depth_write_enabled: self.depth_write_enabled,
+                    depth_compare: self.depth_compare,
+                    stencil_front: self
+                        .stencil
+                        .as_ref()
+                        .map(|s| s.0.clone())
+                        .unwrap_or(wgpu::StencilStateFaceDescriptor::IGNORE),
+                    stencil_back: self
+                        .stencil
+                        .as_ref()
+                        .map(|s| s.1.clone())
+                        .unwrap_or(wgpu::StencilStateFaceDescriptor::IGNORE),
+                    stencil_read_mask: self.stencil.as_ref().map(|s| s.2).unwrap_or(!0),
+                    stencil_write_mask: self.stencil.as_ref().map(|s| s.3).unwrap_or(!0),
+                });
+        let vertex_buffers = self
+            .vertex_buffers
+            .iter()
+            .map(|vb| wgpu::VertexBufferDescriptor {
+                stride: vb.stride,
+                step_mode: vb.step_mode,
+                attributes: &vb.attributes,
+            })
+            .collect::<Vec<_>>();
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            layout: &self.layout,
+            vertex_stage: vertex.stage_descriptor(),
+            fragment_stage: fragment.map(|f| f.stage_descriptor()),
+            rasterization_state: self.rasterization_state.clone(),
+            primitive_topology: self.primitive_topology,
+            color_states: &color_states,
+            depth_stencil_state,
+            index_format: self.index_format,
+            vertex_buffers: &vertex_buffers,
+            sample_count: formats.samples,
+        });
+        let pipeline = Box::new(pipeline);
+        let ptr: *const wgpu::RenderPipeline = &*pipeline;
+        instances.push((formats.clone(), pipeline));
+        unsafe { &*ptr }
+    }
+}
+
+/// Compute pipeline of the WebGPU backend.
+pub struct WgpuComputePipeline {
+    pub(crate) pipeline: wgpu::ComputePipeline,
+    pub(crate) create_time: Duration,
+}
+
+impl fmt::Debug for WgpuComputePipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "WgpuComputePipeline {{..}}")
+    }
+}
+
+impl WgpuComputePipeline {
+    pub(crate) fn new<'a>(
+        arena: &'a WgpuArena,
+        device: &wgpu::Device,
+        root_signature: &'a WgpuSignature,
+        shader: &'a WgpuShaderModule,
+    ) -> &'a WgpuComputePipeline {
+        let start = Instant::now();
+        let layout = root_signature.create_pipeline_layout(device);
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            layout: &layout,
+            compute_stage: shader.stage_descriptor(),
+        });
+        arena.compute_pipelines.alloc(WgpuComputePipeline {
+            pipeline,
+            create_time: start.elapsed(),
+        })
+    }
+}
@@ -0,0 +1,5 @@
+//! Aliases of the API types for the WebGPU backend.
+use crate::backend;
+
+//--------------------------------------------------------------------------------------------------
+autograph_api::aliases!(pub backend::WgpuBackend);
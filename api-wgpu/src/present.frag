@@ -0,0 +1,30 @@
+#version 450
+// Copies the presented image into the swapchain image.
+
+layout(set=0, binding=0) uniform texture2D u_image;
+layout(set=0, binding=1) uniform sampler u_sampler;
+layout(set=0, binding=2) uniform PresentParams {
+  // 0: copy the sampled values, 1: encode them to sRGB, 2: decode them from sRGB
+  uint u_mode;
+};
+
+layout(location=0) in vec2 v_texcoord;
+layout(location=0) out vec4 out_color;
+
+vec3 linear_to_srgb(vec3 c) {
+  return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+
+vec3 srgb_to_linear(vec3 c) {
+  return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+void main() {
+  vec4 color = texture(sampler2D(u_image, u_sampler), v_texcoord);
+  if (u_mode == 1) {
+    color.rgb = linear_to_srgb(color.rgb);
+  } else if (u_mode == 2) {
+    color.rgb = srgb_to_linear(color.rgb);
+  }
+  out_color = color;
+}
@@ -0,0 +1,11 @@
+#version 450
+// Fullscreen triangle, without vertex buffers.
+
+layout(location=0) out vec2 v_texcoord;
+
+void main() {
+  vec2 pos = vec2(float((gl_VertexIndex << 1) & 2), float(gl_VertexIndex & 2));
+  gl_Position = vec4(pos * 2.0 - 1.0, 0.0, 1.0);
+  // Y = 1 is the top row of the swapchain images, and texcoord 0 the first scanline
+  v_texcoord = vec2(pos.x, 1.0 - pos.y);
+}
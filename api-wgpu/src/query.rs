@@ -0,0 +1,6 @@
+/// Occlusion query of the WebGPU backend.
+///
+/// WebGPU has no occlusion queries: queries record nothing, and draws conditioned on them are
+/// always executed.
+#[derive(Debug)]
+pub struct WgpuQuery;
@@ -0,0 +1,59 @@
+use autograph_api::image::{Filter, SamplerAddressMode, SamplerDescription, SamplerMipmapMode};
+use fxhash::{FxBuildHasher, FxHashMap};
+
+pub struct SamplerCache {
+    // samplers are never deleted, and referenced by raw pointers in the bind groups of
+    // argument blocks
+    samplers: FxHashMap<SamplerDescription, Box<wgpu::Sampler>>,
+}
+
+impl SamplerCache {
+    pub fn new() -> SamplerCache {
+        SamplerCache {
+            samplers: FxHashMap::with_hasher(FxBuildHasher::default()),
+        }
+    }
+
+    pub fn get_sampler(
+        &mut self,
+        device: &wgpu::Device,
+        desc: &SamplerDescription,
+    ) -> *const wgpu::Sampler {
+        let sampler = self.samplers.entry(desc.clone()).or_insert_with(|| {
+            Box::new(device.create_sampler(&wgpu::SamplerDescriptor {
+                address_mode_u: address_mode(desc.addr_u),
+                address_mode_v: address_mode(desc.addr_v),
+                address_mode_w: address_mode(desc.addr_w),
+                mag_filter: filter_mode(desc.mag_filter),
+                min_filter: filter_mode(desc.min_filter),
+                mipmap_filter: mipmap_filter_mode(desc.mipmap_mode),
+                lod_min_clamp: 0.0,
+                lod_max_clamp: 1000.0,
+                compare_function: wgpu::CompareFunction::Always,
+            }))
+        });
+        &**sampler
+    }
+}
+
+fn filter_mode(filter: Filter) -> wgpu::FilterMode {
+    match filter {
+        Filter::Nearest => wgpu::FilterMode::Nearest,
+        Filter::Linear => wgpu::FilterMode::Linear,
+    }
+}
+
+fn mipmap_filter_mode(mode: SamplerMipmapMode) -> wgpu::FilterMode {
+    match mode {
+        SamplerMipmapMode::Nearest => wgpu::FilterMode::Nearest,
+        SamplerMipmapMode::Linear => wgpu::FilterMode::Linear,
+    }
+}
+
+fn address_mode(mode: SamplerAddressMode) -> wgpu::AddressMode {
+    match mode {
+        SamplerAddressMode::Clamp => wgpu::AddressMode::ClampToEdge,
+        SamplerAddressMode::Mirror => wgpu::AddressMode::MirrorRepeat,
+        SamplerAddressMode::Wrap => wgpu::AddressMode::Repeat,
+    }
+}
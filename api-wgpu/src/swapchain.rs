@@ -0,0 +1,278 @@
+use crate::{buffer::WgpuBuffer, image::WgpuImage};
+use autograph_api::{
+    command::PresentEncoding, descriptor::ResourceShape, descriptor::SubresourceRange,
+    format::ColorSpace, include_glsl_raw, traits,
+};
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+};
+
+static PRESENT_VERT: &[u8] = include_glsl_raw!("present.vert");
+static PRESENT_FRAG: &[u8] = include_glsl_raw!("present.frag");
+
+/// Swap chain of a surface (window).
+///
+/// The images of the swap chain cannot be used as images of the API: frames are rendered into
+/// intermediate images (see `Instance::acquire_swapchain_image`), drawn into the next image of
+/// the swap chain on presentation.
+pub struct WgpuSwapchain {
+    surface: wgpu::Surface,
+    swap_chain: RefCell<wgpu::SwapChain>,
+    size: Cell<(u32, u32)>,
+    format: wgpu::TextureFormat,
+    present_mode: wgpu::PresentMode,
+    pub(crate) color_space: ColorSpace,
+    /// Default conversion of the presented images (`InstanceConfig::present_encoding`).
+    pub(crate) present_encoding: PresentEncoding,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+}
+
+// only used on the thread of the instance
+unsafe impl Sync for WgpuSwapchain {}
+
+impl fmt::Debug for WgpuSwapchain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "WgpuSwapchain {{ size: {:?}, color_space: {:?} }}",
+            self.size.get(),
+            self.color_space
+        )
+    }
+}
+
+impl traits::Swapchain for WgpuSwapchain {
+    fn size(&self) -> (u32, u32) {
+        self.size.get()
+    }
+
+    fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+}
+
+fn shader_module(device: &wgpu::Device, spirv: &[u8]) -> wgpu::ShaderModule {
+    let words: Vec<u32> = spirv
+        .chunks(4)
+        .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+        .collect();
+    device.create_shader_module(&words)
+}
+
+impl WgpuSwapchain {
+    /// Creates a swap chain of the specified size for a surface.
+    ///
+    /// Images of swap chains with the sRGB color space have a sRGB format.
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        surface: wgpu::Surface,
+        size: (u32, u32),
+        color_space: ColorSpace,
+        present_encoding: PresentEncoding,
+        vsync: bool,
+    ) -> WgpuSwapchain {
+        let format = match color_space {
+            ColorSpace::Srgb => wgpu::TextureFormat::Bgra8UnormSrgb,
+            ColorSpace::Linear => wgpu::TextureFormat::Bgra8Unorm,
+        };
+        let present_mode = if vsync {
+            wgpu::PresentMode::Vsync
+        } else {
+            wgpu::PresentMode::NoVsync
+        };
+        let swap_chain = device.create_swap_chain(
+            &surface,
+            &wgpu::SwapChainDescriptor {
+                usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+                format,
+                width: size.0,
+                height: size.1,
+                present_mode,
+            },
+        );
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture,
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&bind_group_layout],
+        });
+        let vs = shader_module(device, PRESENT_VERT);
+        let fs = shader_module(device, PRESENT_FRAG);
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            layout: &layout,
+            vertex_stage: wgpu::PipelineStageDescriptor {
+                module: &vs,
+                entry_point: "main",
+            },
+            fragment_stage: Some(wgpu::PipelineStageDescriptor {
+                module: &fs,
+                entry_point: "main",
+            }),
+            rasterization_state: wgpu::RasterizationStateDescriptor {
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: wgpu::CullMode::None,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+            },
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format,
+                color_blend: wgpu::BlendDescriptor::REPLACE,
+                alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            depth_stencil_state: None,
+            index_format: wgpu::IndexFormat::Uint16,
+            vertex_buffers: &[],
+            sample_count: 1,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 0.0,
+            compare_function: wgpu::CompareFunction::Always,
+        });
+
+        WgpuSwapchain {
+            surface,
+            swap_chain: RefCell::new(swap_chain),
+            size: Cell::new(size),
+            format,
+            present_mode,
+            color_space,
+            present_encoding,
+            bind_group_layout,
+            pipeline,
+            sampler,
+        }
+    }
+
+    /// Recreates the swap chain with a new size (e.g. after the window was resized).
+    pub(crate) fn resize(&self, device: &wgpu::Device, size: (u32, u32)) {
+        if size == self.size.get() {
+            return;
+        }
+        let swap_chain = device.create_swap_chain(
+            &self.surface,
+            &wgpu::SwapChainDescriptor {
+                usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+                format: self.format,
+                width: size.0,
+                height: size.1,
+                present_mode: self.present_mode,
+            },
+        );
+        *self.swap_chain.borrow_mut() = swap_chain;
+        self.size.set(size);
+    }
+
+    /// Returns the conversion performed by the present shader (see `present.frag`).
+    fn present_shader_mode(&self, image: &WgpuImage, encoding: PresentEncoding) -> u32 {
+        let encoding = match encoding {
+            PresentEncoding::Default => self.present_encoding,
+            other => other,
+        };
+        // values of sRGB images are decoded when sampled, and encoded when written to sRGB
+        // swap chain images
+        let decoded = image.format.color_space() == ColorSpace::Srgb;
+        let encoded = self.color_space == ColorSpace::Srgb;
+        let raw = match encoding {
+            PresentEncoding::Raw => true,
+            PresentEncoding::Srgb => false,
+            PresentEncoding::Default => !encoded,
+        };
+        match (raw, decoded, encoded) {
+            (true, true, false) => 1,
+            (true, false, true) => 2,
+            _ => 0,
+        }
+    }
+
+    /// Draws the presented image into the next image of the swap chain, and presents it.
+    pub(crate) fn present(
+        &self,
+        device: &mut wgpu::Device,
+        image: &WgpuImage,
+        encoding: PresentEncoding,
+    ) {
+        let view = image.create_view(
+            &SubresourceRange {
+                base_mip_level: 0,
+                level_count: Some(1),
+                base_array_layer: 0,
+                layer_count: Some(1),
+            },
+            ResourceShape::R2d,
+        );
+        let mode = self.present_shader_mode(image, encoding);
+        let params = WgpuBuffer::new_immutable(device, 4, &mode.to_le_bytes());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            bindings: &[
+                wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::Binding {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::Binding {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: &params.buffer,
+                        range: 0..4,
+                    },
+                },
+            ],
+        });
+
+        let mut swap_chain = self.swap_chain.borrow_mut();
+        let frame = swap_chain.get_next_texture();
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                    attachment: &frame.view,
+                    resolve_target: None,
+                    load_op: wgpu::LoadOp::Clear,
+                    store_op: wgpu::StoreOp::Store,
+                    clear_color: wgpu::Color::BLACK,
+                }],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+        device.get_queue().submit(&[encoder.finish()]);
+        // the image is presented when the frame is dropped
+    }
+}
@@ -6,7 +6,8 @@ use autograph_api::{
     glm, include_shader,
     pipeline::{
         Arguments, ColorBlendState, DepthStencilState, GraphicsPipelineCreateInfo,
-        InputAssemblyState, MultisampleState, RasterisationState, Viewport, ViewportState,
+        InputAssemblyState, MultisampleState, RasterisationState, ViewCount, Viewport,
+        ViewportState,
     },
     vertex::VertexData,
 };
@@ -138,6 +139,7 @@ fn create_pipelines<'a>(arena: &'a Arena) -> Pipelines<'a> {
         depth_stencil_state: DepthStencilState::default(),
         input_assembly_state: InputAssemblyState::default(),
         color_blend_state: ColorBlendState::DISABLED,
+        view_count: ViewCount::default(),
     };
 
     let background = arena.create_graphics_pipeline(&background);
@@ -150,6 +152,7 @@ fn create_pipelines<'a>(arena: &'a Arena) -> Pipelines<'a> {
         depth_stencil_state: DepthStencilState::default(),
         input_assembly_state: InputAssemblyState::default(),
         color_blend_state: ColorBlendState::DISABLED,
+        view_count: ViewCount::default(),
     };
 
     let path = arena.create_graphics_pipeline(&path);
@@ -350,7 +353,7 @@ fn main() {
 
                 //----------------------------------------------------------------------------------
                 // Present
-                cmdbuf.present(0x0, color_buffer, default_swapchain);
+                cmdbuf.present_image(0x0, color_buffer, default_swapchain);
                 renderer.submit_frame(iter::once(cmdbuf));
             })
         },
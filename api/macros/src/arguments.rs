@@ -29,6 +29,15 @@ struct ArgumentsItem {
     inherit: Flag,
     #[darling(default)]
     render_target: Flag,
+    /// Blend state override for a render target.
+    #[darling(default)]
+    blend: Option<String>,
+    /// Load operation of a render target.
+    #[darling(default)]
+    load: Option<String>,
+    /// Store operation of a render target.
+    #[darling(default)]
+    store: Option<String>,
     #[darling(default)]
     depth_stencil_render_target: Flag,
     #[darling(default)]
@@ -39,6 +48,9 @@ struct ArgumentsItem {
     scissor: Flag,
     #[darling(default)]
     scissor_array: Flag,
+    /// Stencil reference value (`u32`) for pipelines with a dynamic stencil reference.
+    #[darling(default)]
+    stencil_reference: Flag,
     #[darling(default)]
     vertex_buffer: Flag,
     #[darling(default)]
@@ -48,6 +60,184 @@ struct ArgumentsItem {
     // Shader interfaces -----------------------
     #[darling(default)]
     descriptor: Flag,
+    /// Buffer view bound as a texel buffer (`samplerBuffer`).
+    #[darling(default)]
+    texel_buffer: Flag,
+    /// Built-in resource used when an optional descriptor is `None`.
+    #[darling(default)]
+    fallback: Option<String>,
+}
+
+/// Generates the blend state override of a render target from the `blend = "..."` option.
+///
+/// Accepts one of the predefined blend states (`alpha`, `additive`, `disabled`) or the path to a
+/// constant of type `ColorBlendAttachmentState`.
+fn blend_override(span: Span, blend: &Option<String>) -> Result<TokenStream, syn::Error> {
+    let blend = if let Some(blend) = blend {
+        blend
+    } else {
+        return Ok(quote!(None));
+    };
+
+    let state = match blend.as_str() {
+        "alpha" => quote!(#G::pipeline::ColorBlendAttachmentState::ALPHA_BLENDING),
+        "additive" => quote!(#G::pipeline::ColorBlendAttachmentState::ADDITIVE),
+        "disabled" => quote!(#G::pipeline::ColorBlendAttachmentState::DISABLED),
+        other => {
+            let path: syn::Path = syn::parse_str(other).map_err(|_| {
+                syn::Error::new(
+                    span,
+                    format!(
+                        "invalid blend state `{}`: expected `alpha`, `additive`, `disabled` or the path to a constant",
+                        other
+                    ),
+                )
+            })?;
+            quote!(#path)
+        }
+    };
+
+    Ok(quote!(Some(#state)))
+}
+
+/// Generates the load operation of a render target from the `load = "..."` option.
+///
+/// Accepts `load`, `clear` (to zero for color render targets, and to depth 1.0 for depth-stencil
+/// render targets), `dont_care`, or the path to a constant of type `LoadOp`.
+fn load_op(span: Span, load: &Option<String>, depth: bool) -> Result<TokenStream, syn::Error> {
+    let load = if let Some(load) = load {
+        load
+    } else {
+        return Ok(quote!(#G::pipeline::LoadOp::Load));
+    };
+
+    match load.as_str() {
+        "load" => Ok(quote!(#G::pipeline::LoadOp::Load)),
+        "clear" if depth => Ok(quote!(#G::pipeline::LoadOp::CLEAR_DEPTH_FAR)),
+        "clear" => Ok(quote!(#G::pipeline::LoadOp::CLEAR_ZERO)),
+        "dont_care" | "dont-care" => Ok(quote!(#G::pipeline::LoadOp::DontCare)),
+        other => {
+            let path: syn::Path = syn::parse_str(other).map_err(|_| {
+                syn::Error::new(
+                    span,
+                    format!(
+                        "invalid load operation `{}`: expected `load`, `clear`, `dont_care` or the path to a constant",
+                        other
+                    ),
+                )
+            })?;
+            Ok(quote!(#path))
+        }
+    }
+}
+
+/// Generates the store operation of a render target from the `store = "..."` option.
+fn store_op(span: Span, store: &Option<String>) -> Result<TokenStream, syn::Error> {
+    match store.as_ref().map(|s| s.as_str()) {
+        None | Some("store") => Ok(quote!(#G::pipeline::StoreOp::Store)),
+        Some("dont_care") | Some("dont-care") => Ok(quote!(#G::pipeline::StoreOp::DontCare)),
+        Some(other) => Err(syn::Error::new(
+            span,
+            format!(
+                "invalid store operation `{}`: expected `store` or `dont_care`",
+                other
+            ),
+        )),
+    }
+}
+
+/// Generates the fallback texture of an optional descriptor from the `fallback = "..."` option.
+fn fallback_texture(span: Span, fallback: &str) -> Result<TokenStream, syn::Error> {
+    match fallback {
+        "white" => Ok(quote!(#G::image::FallbackTexture::White)),
+        "black" => Ok(quote!(#G::image::FallbackTexture::Black)),
+        "normal-up" | "normal_up" => Ok(quote!(#G::image::FallbackTexture::NormalUp)),
+        other => Err(syn::Error::new(
+            span,
+            format!(
+                "invalid fallback `{}`: expected `white`, `black` or `normal-up`",
+                other
+            ),
+        )),
+    }
+}
+
+/// Returns the type `T` if `ty` is of the form `Option<T>`.
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let path = match *ty {
+        syn::Type::Path(ref ty) if ty.qself.is_none() => &ty.path,
+        _ => return None,
+    };
+    let last = path.segments.last()?.into_value();
+    if last.ident != "Option" {
+        return None;
+    }
+    match last.arguments {
+        syn::PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => {
+            match *args.args.iter().next().unwrap() {
+                syn::GenericArgument::Type(ref ty) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the length of a render target array field (`[RenderTarget2dView<'a, B>; 4]`), or
+/// `None` if the field is a single render target.
+fn render_target_array_len(ty: &syn::Type) -> Result<Option<usize>, syn::Error> {
+    let array = match *ty {
+        syn::Type::Array(ref array) => array,
+        _ => return Ok(None),
+    };
+    match array.len {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(ref len),
+            ..
+        }) => Ok(Some(len.value() as usize)),
+        ref len => Err(syn::Error::new(
+            len.span(),
+            "the length of a render target array must be an integer literal",
+        )),
+    }
+}
+
+/// Returns whether a type parameter is bounded by `Arguments`, either in its declaration or in
+/// the where clause of the struct.
+fn has_arguments_bound(generics: &syn::Generics, param: &syn::TypeParam) -> bool {
+    let is_arguments = |bound: &syn::TypeParamBound| match bound {
+        syn::TypeParamBound::Trait(t) => t
+            .path
+            .segments
+            .last()
+            .map_or(false, |s| s.value().ident == "Arguments"),
+        _ => false,
+    };
+
+    if param.bounds.iter().any(is_arguments) {
+        return true;
+    }
+
+    let where_clause = if let Some(ref where_clause) = generics.where_clause {
+        where_clause
+    } else {
+        return false;
+    };
+
+    where_clause.predicates.iter().any(|p| match p {
+        syn::WherePredicate::Type(p) => {
+            let is_param = match p.bounded_ty {
+                syn::Type::Path(ref ty) => {
+                    ty.qself.is_none()
+                        && ty.path.segments.len() == 1
+                        && ty.path.segments.iter().next().unwrap().ident == param.ident
+                }
+                _ => false,
+            };
+            is_param && p.bounds.iter().any(is_arguments)
+        }
+        _ => false,
+    })
 }
 
 pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
@@ -55,7 +245,6 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
     let struct_name = &s.ident;
 
     //----------------------------------------------------------------------------------------------
-    let (impl_generics, ty_generics, where_clause) = s.generics.split_for_impl();
     let first_lt = s.generics.lifetimes().next();
 
     let lt_arena = if let Some(lt) = first_lt {
@@ -105,8 +294,11 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
     let mut i_desc = Vec::new();
     let mut ib_format = None;
     let mut seen_dst = false;
+    let mut dst_load_op = quote!(#G::pipeline::LoadOp::Load);
+    let mut dst_store_op = quote!(#G::pipeline::StoreOp::Store);
     let mut n_viewports = 0usize;
     let mut n_scissors = 0usize;
+    let mut stencil_reference = None;
 
     for f in fields.iter() {
         let ty = &f.ty;
@@ -134,6 +326,9 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
                 if pitem.scissor_array.is_some() {
                     num_attrs += 1;
                 }
+                if pitem.stencil_reference.is_some() {
+                    num_attrs += 1;
+                }
                 if pitem.vertex_buffer.is_some() {
                     num_attrs += 1;
                 }
@@ -146,6 +341,9 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
                 if pitem.descriptor.is_some() {
                     num_attrs += 1;
                 }
+                if pitem.texel_buffer.is_some() {
+                    num_attrs += 1;
+                }
                 if pitem.depth_stencil_render_target.is_some() {
                     num_attrs += 1;
                 }
@@ -165,6 +363,42 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
                     continue;
                 }
 
+                if (pitem.load.is_some() || pitem.store.is_some())
+                    && !pitem.render_target.is_some()
+                    && !pitem.depth_stencil_render_target.is_some()
+                {
+                    stmts.push(
+                        syn::Error::new(
+                            name.span(),
+                            "the `load` and `store` options are only valid on render target fields",
+                        )
+                        .to_compile_error(),
+                    );
+                    continue;
+                }
+
+                if pitem.blend.is_some() && !pitem.render_target.is_some() {
+                    stmts.push(
+                        syn::Error::new(
+                            name.span(),
+                            "the `blend` option is only valid on `argument(render_target)` fields",
+                        )
+                        .to_compile_error(),
+                    );
+                    continue;
+                }
+
+                if pitem.fallback.is_some() && !pitem.descriptor.is_some() {
+                    stmts.push(
+                        syn::Error::new(
+                            name.span(),
+                            "the `fallback` option is only valid on `argument(descriptor)` fields",
+                        )
+                        .to_compile_error(),
+                    );
+                    continue;
+                }
+
                 // arguments --------------------------------------------
                 if pitem.inherit.is_some() {
                     //let index = i_subsig.len();
@@ -177,10 +411,49 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
                 }
                 // render target --------------------------------------------
                 else if pitem.render_target.is_some() {
-                    iter_render_targets.push(quote! {
-                        std::iter::once(self.#name.into())
-                    });
-                    i_fragout.push(quote! { #G::pipeline::FragmentOutputDescription{} });
+                    // arrays of render targets are bound to consecutive locations, and share
+                    // the same options
+                    let count = match render_target_array_len(ty) {
+                        Ok(Some(len)) => {
+                            iter_render_targets.push(quote! {
+                                {
+                                    let targets = self.#name;
+                                    (0..#len).map(move |i| targets[i].into())
+                                }
+                            });
+                            len
+                        }
+                        Ok(None) => {
+                            iter_render_targets.push(quote! {
+                                std::iter::once(self.#name.into())
+                            });
+                            1
+                        }
+                        Err(e) => {
+                            stmts.push(e.to_compile_error());
+                            continue;
+                        }
+                    };
+                    match (
+                        blend_override(name.span(), &pitem.blend),
+                        load_op(name.span(), &pitem.load, false),
+                        store_op(name.span(), &pitem.store),
+                    ) {
+                        (Ok(blend), Ok(load_op), Ok(store_op)) => {
+                            for _ in 0..count {
+                                i_fragout.push(quote! {
+                                    #G::pipeline::FragmentOutputDescription {
+                                        blend: #blend,
+                                        load_op: #load_op,
+                                        store_op: #store_op,
+                                    }
+                                })
+                            }
+                        }
+                        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                            stmts.push(e.to_compile_error())
+                        }
+                    }
                 }
                 // depth stencil render target --------------------------------------------
                 else if pitem.depth_stencil_render_target.is_some() {
@@ -189,6 +462,14 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
                             quote! { depth_stencil_render_target = Some(self.#name.into()); },
                         );
                         seen_dst = true;
+                        match load_op(name.span(), &pitem.load, true) {
+                            Ok(op) => dst_load_op = op,
+                            Err(e) => stmts.push(e.to_compile_error()),
+                        }
+                        match store_op(name.span(), &pitem.store) {
+                            Ok(op) => dst_store_op = op,
+                            Err(e) => stmts.push(e.to_compile_error()),
+                        }
                     } else {
                         stmts.push(
                             syn::Error::new(
@@ -201,10 +482,41 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
                 }
                 // descriptor --------------------------------------------
                 else if pitem.descriptor.is_some() {
-                    iter_descriptors.push(quote! {
-                       std::iter::once(self.#name.into_descriptor())
-                    });
+                    // optional descriptors are replaced by a built-in resource when `None`
+                    let ty = if let Some(ref fallback) = pitem.fallback {
+                        let inner = if let Some(inner) = option_inner_type(ty) {
+                            inner
+                        } else {
+                            stmts.push(
+                                syn::Error::new(
+                                    ty.span(),
+                                    "fields with a `fallback` option must be of type `Option<...>`",
+                                )
+                                .to_compile_error(),
+                            );
+                            continue;
+                        };
+                        match fallback_texture(name.span(), fallback) {
+                            Ok(kind) => iter_descriptors.push(quote! {
+                                std::iter::once(match self.#name {
+                                    Some(resource) => resource.into_descriptor(),
+                                    None => <#inner as #G::descriptor::FallbackResource<#lt_arena, #ty_backend>>::fallback(arena, #kind).into_descriptor(),
+                                })
+                            }),
+                            Err(e) => {
+                                stmts.push(e.to_compile_error());
+                                continue;
+                            }
+                        }
+                        inner
+                    } else {
+                        iter_descriptors.push(quote! {
+                           std::iter::once(self.#name.into_descriptor())
+                        });
+                        ty
+                    };
                     let index = i_desc.len() as u32;
+                    let field_name = name.to_string();
                     i_desc.push(quote!{
                         #G::descriptor::ResourceBinding {
                             set: None, // descriptor set is determined by the argument block layout
@@ -215,6 +527,29 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
                             data_ty: <#ty as #G::descriptor::ResourceInterface<#ty_backend>>::DATA_TYPE,
                             data_format: <#ty as #G::descriptor::ResourceInterface<#ty_backend>>::DATA_FORMAT,
                             data_layout: <#ty as #G::descriptor::ResourceInterface<#ty_backend>>::DATA_LAYOUT,
+                            name: Some(#field_name),
+                        }
+                    });
+                }
+                // texel buffer --------------------------------------------
+                else if pitem.texel_buffer.is_some() {
+                    iter_descriptors.push(quote! {
+                        std::iter::once(#G::buffer::BufferView::<#ty_backend>::from(self.#name).into_descriptor())
+                    });
+                    let index = i_desc.len() as u32;
+                    let field_name = name.to_string();
+                    // the format of the view is only known at runtime
+                    i_desc.push(quote! {
+                        #G::descriptor::ResourceBinding {
+                            set: None,
+                            index: #index,
+                            ty: #G::descriptor::ResourceBindingType::TexelBuffer,
+                            stage_flags: #G::pipeline::ShaderStageFlags::ALL_GRAPHICS,
+                            count: 1,
+                            data_ty: None,
+                            data_format: #G::format::Format::UNDEFINED,
+                            data_layout: None,
+                            name: Some(#field_name),
                         }
                     });
                 }
@@ -267,6 +602,20 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
                     });
 
                     n_scissors += 1;
+                }
+                // stencil reference --------------------------------------------
+                else if pitem.stencil_reference.is_some() {
+                    if stencil_reference.is_none() {
+                        stencil_reference = Some(quote!(Some(self.#name.into())));
+                    } else {
+                        stmts.push(
+                            syn::Error::new(
+                                name.span(),
+                                "duplicate `argument(stencil_reference)` attribute",
+                            )
+                            .to_compile_error(),
+                        );
+                    }
                 } else if pitem.viewport_array.is_some() {
                     unimplemented!()
                 } else if pitem.scissor_array.is_some() {
@@ -290,10 +639,16 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
     let is_root_fragment_output_signature = i_fragout.len() > 0;
     let is_root_vertex_input_signature = false;
     let depth_stencil_fragment_output = if seen_dst {
-        quote!(Some(#G::pipeline::FragmentOutputDescription{}))
+        quote!(Some(#G::pipeline::FragmentOutputDescription {
+            blend: None,
+            load_op: #dst_load_op,
+            store_op: #dst_store_op,
+        }))
     } else {
         quote!(None)
     };
+    let has_stencil_reference = stencil_reference.is_some();
+    let stencil_reference = stencil_reference.unwrap_or(quote!(None));
     let ib_format = match ib_format {
         Some(fmt) => fmt,
         None => quote!(None),
@@ -304,22 +659,46 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
         Span::call_site(),
     );
 
+    // The unique type is generic over the type parameters of the struct, so that each
+    // instantiation gets its own signature. Type parameters that are themselves arguments
+    // are replaced by their unique type (they usually borrow data for the arena lifetime),
+    // others must be 'static.
+    let mut generics = s.generics.clone();
+    let mut unique_params = Vec::new();
+    for param in s.generics.type_params() {
+        let ident = &param.ident;
+        if *ident != ty_backend && has_arguments_bound(&s.generics, param) {
+            unique_params.push(
+                quote!(<#ident as #G::pipeline::Arguments<#lt_arena, #ty_backend>>::UniqueType),
+            );
+        } else {
+            if *ident != ty_backend {
+                generics
+                    .make_where_clause()
+                    .predicates
+                    .push(syn::parse_quote!(#ident: 'static));
+            }
+            unique_params.push(quote!(#ident));
+        }
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = s.generics.split_for_impl();
+
     let ty_params = s.generics.type_params().map(|ty| &ty.ident);
     let ty_params2 = s.generics.type_params().map(|ty| &ty.ident);
-    let ty_params3 = s.generics.type_params().map(|ty| &ty.ident);
 
     let q = quote! {
 
         #[doc(hidden)]
         mod #privmod {
             // IMPORTANT must be generic if interface struct is generic
-            // but the generic parameters must also be 'static...
-            pub struct Dummy<#(#ty_params,)*>(std::marker::PhantomData<(#(#ty_params3),*)>);
+            // (parameters can be unsized: e.g. the element type of a buffer)
+            pub struct Dummy<#(#ty_params: ?Sized,)*>(std::marker::PhantomData<(#(*const #ty_params2,)*)>);
         }
 
         impl #impl_generics #G::pipeline::Arguments<#lt_arena, #ty_backend> for #struct_name #ty_generics #where_clause {
 
-            type UniqueType = #privmod::Dummy<#(#ty_params2,)*>;
+            type UniqueType = #privmod::Dummy<#(#unique_params,)*>;
             type IntoInterface = Self;
 
             const SIGNATURE: &'static #G::pipeline::SignatureDescription<'static> = &#G::pipeline::SignatureDescription {
@@ -333,6 +712,7 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
                 index_format                      : #ib_format,
                 num_viewports                     : #n_viewports,
                 num_scissors                      : #n_scissors,
+                has_stencil_reference             : #has_stencil_reference,
             };
 
             fn get_inherited_signatures(renderer: &#lt_arena #G::Api<#ty_backend>) -> Vec<&#lt_arena <#ty_backend as #G::Backend>::Signature> {
@@ -372,7 +752,8 @@ pub fn generate(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
                     render_targets,
                     depth_stencil_render_target,
                     viewports,
-                    scissors)
+                    scissors,
+                    #stencil_reference)
             }
         }
     };
@@ -1,7 +1,7 @@
 use crate::G;
 use proc_macro2::{Span, TokenStream};
-use quote::quote;
-use syn::Ident;
+use quote::{quote, quote_spanned};
+use syn::{spanned::Spanned, Ident};
 
 /// Checks that the derive input has a repr(C) attribute.
 fn has_repr_c_attr(ast: &syn::DeriveInput) -> bool {
@@ -22,6 +22,67 @@ fn has_repr_c_attr(ast: &syn::DeriveInput) -> bool {
     })
 }
 
+/// Scalar types that form vectors when put in a fixed-size array (`[f32; 3]` is a `vec3`).
+const SCALAR_TYPES: &[&str] = &["f32", "i32", "u32", "BoolU32"];
+
+fn is_scalar_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(ref p) => p.path.segments.last().map_or(false, |s| {
+            SCALAR_TYPES.contains(&&*s.value().ident.to_string())
+        }),
+        _ => false,
+    }
+}
+
+fn array_len_literal(expr: &syn::Expr) -> Option<u64> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(ref i),
+            ..
+        }) => Some(i.value()),
+        _ => None,
+    }
+}
+
+/// Returns whether `ty` is a vector type (`[f32; 3]`).
+fn is_vector_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Array(ref a) => is_scalar_type(&a.elem),
+        _ => false,
+    }
+}
+
+/// Returns the element type and length of a field of type `[T; N]` that should be described
+/// as an array of `T`, as opposed to a vector or matrix type implemented directly
+/// by `StructuredBufferData` (`[f32; 4]` or `[[f32; 4]; 4]`).
+///
+/// Only `[[T; 4]; 4]` is assumed to be a matrix: `[[f32; 2]; 2]` and `[[f32; 3]; 3]` do not have
+/// the layout of a `mat2` or `mat3` in uniform buffers (columns are aligned like a `vec4`), and
+/// are described as arrays of vectors.
+fn array_field_element(ty: &syn::Type) -> Option<(&syn::Type, &syn::Expr)> {
+    match ty {
+        syn::Type::Array(ref a) => {
+            let elem = &*a.elem;
+            if is_scalar_type(elem) {
+                // vector
+                return None;
+            }
+            if let syn::Type::Array(ref inner) = elem {
+                let mat4 = match (array_len_literal(&a.len), array_len_literal(&inner.len)) {
+                    (Some(4), Some(4)) => true,
+                    _ => false,
+                };
+                if mat4 && is_scalar_type(&inner.elem) {
+                    // matrix
+                    return None;
+                }
+            }
+            Some((elem, &a.len))
+        }
+        _ => None,
+    }
+}
+
 /// See [generate_struct_layout]
 struct StructLayout {
     offsets: Vec<syn::ItemConst>,
@@ -104,6 +165,7 @@ pub fn generate_structured_buffer_data(
     let mut field_tys = Vec::new();
     let mut layouts = Vec::new();
     let mut offsets = Vec::new();
+    let mut stride_checks = Vec::new();
 
     for (i, f) in fields.iter().enumerate() {
         let field_ty = &f.ty;
@@ -114,11 +176,48 @@ pub fn generate_structured_buffer_data(
             continue;
         }
 
-        field_tys.push(quote! { <#field_ty as #G::buffer::StructuredBufferData>::TYPE });
-
         offsets.push(quote! { #privmod::#offset });
 
-        layouts.push(quote! { <#field_ty as #G::buffer::StructuredBufferData>::LAYOUT });
+        if let Some((elem_ty, len)) = array_field_element(field_ty) {
+            // array of structs (or of vectors): the element type and layout come from the
+            // element, and the stride is the size of the element on the rust side.
+            field_tys.push(quote! {
+                #G::typedesc::TypeDesc::Array {
+                    elem_ty: &<#elem_ty as #G::buffer::StructuredBufferData>::TYPE,
+                    len: #len,
+                }
+            });
+            layouts.push(quote! {
+                #G::typedesc::Layout {
+                    align: ::std::mem::align_of::<#field_ty>(),
+                    size: ::std::mem::size_of::<#field_ty>(),
+                    details: #G::typedesc::LayoutDetails::Array(#G::typedesc::ArrayLayout {
+                        elem_layout: &<#elem_ty as #G::buffer::StructuredBufferData>::LAYOUT,
+                        stride: ::std::mem::size_of::<#elem_ty>(),
+                    })
+                }
+            });
+
+            // std140: the stride of an array is the size of the element rounded up to the
+            // alignment of a vec4. Arrays of scalars and vectors with a smaller stride are
+            // accepted (they have the std430 layout of storage buffers), but arrays of structs
+            // fail to compile (array length mismatch) if the stride does not match: add explicit
+            // padding fields to the element type.
+            if !is_vector_type(elem_ty) {
+                let check = Ident::new(
+                    &format!("STD140_ARRAY_STRIDE_CHECK_{}", i),
+                    Span::call_site(),
+                );
+                stride_checks.push(quote! {
+                    pub const #check: [(); 0] = [();
+                        (::std::mem::size_of::<#elem_ty>() + 15) / 16 * 16
+                            - ::std::mem::size_of::<#elem_ty>()];
+                });
+            }
+        } else {
+            field_tys.push(quote! { <#field_ty as #G::buffer::StructuredBufferData>::TYPE });
+            layouts.push(quote! { <#field_ty as #G::buffer::StructuredBufferData>::LAYOUT });
+        }
     }
 
     let offset_consts = &layout.offsets;
@@ -130,6 +229,7 @@ pub fn generate_structured_buffer_data(
             use super::*;
             #(#offset_consts)*
             #(#size_consts)*
+            #(#stride_checks)*
         }
 
         unsafe impl #G::buffer::StructuredBufferData for #struct_name {
@@ -148,6 +248,77 @@ pub fn generate_structured_buffer_data(
     }
 }
 
+/// Returns the innermost element type of a (possibly nested) array type.
+fn innermost_element(ty: &syn::Type) -> &syn::Type {
+    match ty {
+        syn::Type::Array(ref a) => innermost_element(&a.elem),
+        _ => ty,
+    }
+}
+
+pub fn generate_pod(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
+    if !ast.generics.params.is_empty() {
+        panic!("derive(Pod) cannot be used on generic structs");
+    }
+
+    let struct_name = &ast.ident;
+    // `Pod` is only implemented for some array lengths: require it of the elements instead.
+    // The bounds are on concrete types, so a field that is not `Pod` fails to compile.
+    let field_tys = fields.iter().map(|f| innermost_element(&f.ty));
+
+    quote! {
+        unsafe impl #G::buffer::Pod for #struct_name where #(#field_tys: #G::buffer::Pod),* {}
+    }
+}
+
+/// Parses the format override of a vertex attribute (`#[vertex(format = "R16G16_SFLOAT")]`).
+fn vertex_format_override(field: &syn::Field) -> Option<syn::Ident> {
+    let mut format = None;
+    for attr in field.attrs.iter() {
+        let meta = match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => list,
+            _ => continue,
+        };
+        if meta.ident != "vertex" {
+            continue;
+        }
+        for n in meta.nested.iter() {
+            match n {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(ref nv)) if nv.ident == "format" => {
+                    match nv.lit {
+                        syn::Lit::Str(ref s) => {
+                            format = Some(syn::Ident::new(&s.value(), s.span()));
+                        }
+                        _ => panic!("derive(VertexData): expected a string literal for `format`"),
+                    }
+                }
+                _ => panic!("derive(VertexData): unrecognized vertex attribute option"),
+            }
+        }
+    }
+    format
+}
+
+/// Returns the size in bytes of a vertex format from its name (`R16G16_SFLOAT` is 4 bytes),
+/// or `None` if it can't be inferred from the name (e.g. compressed formats).
+fn format_byte_size(format: &str) -> Option<usize> {
+    let mut parts = format.split('_');
+    let components = parts.next()?;
+    // packed formats: `A2B10G10R10_UNORM_PACK32`
+    if let Some(pack) = parts.find(|p| p.starts_with("PACK")) {
+        return pack[4..].parse::<usize>().ok().map(|bits| bits / 8);
+    }
+    // bits of each component: `R32G32B32A32`
+    let mut bits = 0;
+    for n in components.split(|c: char| c.is_ascii_alphabetic()).skip(1) {
+        bits += n.parse::<usize>().ok()?;
+    }
+    if bits == 0 || bits % 8 != 0 {
+        return None;
+    }
+    Some(bits / 8)
+}
+
 pub fn generate_vertex_data(ast: &syn::DeriveInput, fields: &syn::Fields) -> TokenStream {
     if !has_repr_c_attr(ast) {
         panic!("derive(VertexData) can only be used on repr(C) structs");
@@ -165,19 +336,44 @@ pub fn generate_vertex_data(ast: &syn::DeriveInput, fields: &syn::Fields) -> Tok
     };
 
     let mut attribs = Vec::new();
+    let mut size_checks = Vec::new();
 
     for (i, f) in fields.iter().enumerate() {
         let field_ty = &f.ty;
         let offset = &layout.offsets[i];
         let offset = &offset.ident;
+        let field_name = match f.ident {
+            Some(ref ident) => ident.to_string(),
+            None => i.to_string(),
+        };
+
+        // the format can be overriden to reinterpret the field data (e.g. `[u16; 2]` as half-floats)
+        let format = if let Some(format) = vertex_format_override(f) {
+            // This fails to compile (array length mismatch) if the size of the field is not
+            // the size of an element of the format.
+            if let Some(format_size) = format_byte_size(&format.to_string()) {
+                let check = Ident::new(
+                    &format!("VERTEX_FORMAT_SIZE_CHECK_{}", i),
+                    Span::call_site(),
+                );
+                size_checks.push(quote_spanned! { field_ty.span() =>
+                    pub const #check: [(); #format_size] =
+                        [(); ::std::mem::size_of::<#field_ty>()];
+                });
+            }
+            quote! { #G::format::Format::#format }
+        } else {
+            quote! { <#field_ty as #G::vertex::VertexAttributeType>::FORMAT }
+        };
 
         attribs.push(quote! {
             #G::vertex::VertexLayoutElement {
                 //ty: &<#field_ty as #gfx::vertex::VertexAttributeType>::EQUIVALENT_TYPE,
                 //location: #i as u32,
-                format: <#field_ty as #G::vertex::VertexAttributeType>::FORMAT,
+                format: #format,
                 offset: #privmod::#offset as u32,
-                semantic: None  // TODO
+                semantic: None,  // TODO
+                name: Some(#field_name)
             }
         });
     }
@@ -191,6 +387,7 @@ pub fn generate_vertex_data(ast: &syn::DeriveInput, fields: &syn::Fields) -> Tok
             use super::*;
             #(#offsets)*
             #(#sizes)*
+            #(#size_checks)*
         }
 
         unsafe impl #G::vertex::VertexData for #struct_name {
@@ -3,6 +3,8 @@
 //! - `BufferLayout` for verifying the layout of uniform buffer data with SPIR-V
 //! - `AttachmentGroup` for groups of attachments
 //! - `VertexLayout` for verifying the layout of vertex buffers
+//! - `SortKey` for sort keys made of packed fields
+//! - `Pod` for plain-old-data structs that can be read back from GPU buffers
 //!
 #![recursion_limit = "256"]
 #![feature(proc_macro_diagnostic)]
@@ -30,6 +32,7 @@ impl ToTokens for CrateName {
 
 mod arguments;
 mod layout;
+mod sort_key;
 
 #[proc_macro_derive(StructuredBufferData)]
 pub fn structured_buffer_data_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -43,7 +46,19 @@ pub fn structured_buffer_data_derive(input: proc_macro::TokenStream) -> proc_mac
     result.into()
 }
 
-#[proc_macro_derive(VertexData)]
+#[proc_macro_derive(Pod)]
+pub fn pod_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast: syn::DeriveInput = syn::parse(input).expect("Couldn't parse item");
+
+    let result = match ast.data {
+        syn::Data::Struct(ref s) => layout::generate_pod(&ast, &s.fields),
+        _ => panic!("Pod trait can only be automatically derived on structs."),
+    };
+
+    result.into()
+}
+
+#[proc_macro_derive(VertexData, attributes(vertex))]
 pub fn vertex_data_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast: syn::DeriveInput = syn::parse(input).expect("Couldn't parse item");
 
@@ -66,3 +81,9 @@ pub fn arguments_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStre
 
     result.into()
 }
+
+#[proc_macro_derive(SortKey, attributes(sort_key))]
+pub fn sort_key_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast: syn::DeriveInput = syn::parse(input).expect("Couldn't parse item");
+    sort_key::generate(&ast).into()
+}
@@ -0,0 +1,186 @@
+use crate::G;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::spanned::Spanned;
+
+/// A field of a sort key, from the `#[sort_key(name = bits, ...)]` attribute.
+struct SortKeyField {
+    name: syn::Ident,
+    bits: u32,
+}
+
+/// Returns whether the struct is a newtype around `u64`.
+fn is_u64_newtype(fields: &syn::Fields) -> bool {
+    match fields {
+        syn::Fields::Unnamed(f) if f.unnamed.len() == 1 => match f.unnamed[0].ty {
+            syn::Type::Path(ref p) => {
+                p.qself.is_none() && p.path.segments.len() == 1 && p.path.segments[0].ident == "u64"
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Parses the fields of the `#[sort_key(...)]` attribute, from the most significant to the least
+/// significant.
+fn parse_fields(ast: &syn::DeriveInput) -> Result<Vec<SortKeyField>, syn::Error> {
+    let attr = ast
+        .attrs
+        .iter()
+        .find(|attr| attr.path.segments.len() == 1 && attr.path.segments[0].ident == "sort_key")
+        .ok_or_else(|| {
+            syn::Error::new(
+                ast.ident.span(),
+                "missing `#[sort_key(...)]` attribute describing the fields of the key",
+            )
+        })?;
+
+    let list = match attr.parse_meta()? {
+        syn::Meta::List(list) => list,
+        meta => {
+            return Err(syn::Error::new(
+                meta.span(),
+                "expected a list of fields: `#[sort_key(name = bits, ...)]`",
+            ))
+        }
+    };
+
+    let mut fields: Vec<SortKeyField> = Vec::new();
+    for nested in list.nested.iter() {
+        let (name, bits) = match nested {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                ident,
+                lit: syn::Lit::Int(bits),
+                ..
+            })) => (ident, bits),
+            other => {
+                return Err(syn::Error::new(
+                    other.span(),
+                    "expected a field and its number of bits: `name = bits`",
+                ))
+            }
+        };
+        if bits.value() == 0 || bits.value() > 64 {
+            return Err(syn::Error::new(
+                bits.span(),
+                format!(
+                    "invalid number of bits for field `{}`: expected between 1 and 64",
+                    name
+                ),
+            ));
+        }
+        if fields.iter().any(|f| f.name == *name) {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("duplicate sort key field `{}`", name),
+            ));
+        }
+        fields.push(SortKeyField {
+            name: name.clone(),
+            bits: bits.value() as u32,
+        });
+    }
+
+    let total: u32 = fields.iter().map(|f| f.bits).sum();
+    if total > 64 {
+        return Err(syn::Error::new(
+            list.ident.span(),
+            format!(
+                "the fields of the sort key use {} bits, but sort keys have only 64 bits",
+                total
+            ),
+        ));
+    }
+    Ok(fields)
+}
+
+pub fn generate(ast: &syn::DeriveInput) -> TokenStream {
+    let struct_name = &ast.ident;
+
+    match ast.data {
+        syn::Data::Struct(ref s) if is_u64_newtype(&s.fields) => {}
+        _ => {
+            return syn::Error::new(
+                struct_name.span(),
+                "SortKey can only be derived on newtypes of `u64` (`struct Key(u64);`)",
+            )
+            .to_compile_error()
+        }
+    }
+
+    let fields = match parse_fields(ast) {
+        Ok(fields) => fields,
+        Err(e) => return e.to_compile_error(),
+    };
+    let total_bits: u32 = fields.iter().map(|f| f.bits).sum();
+
+    let mut shift = total_bits;
+    let mut items = Vec::new();
+    for f in fields.iter() {
+        shift -= f.bits;
+        let name = &f.name;
+        let bits = f.bits;
+        let mask = if f.bits == 64 {
+            !0u64
+        } else {
+            (1u64 << f.bits) - 1
+        };
+        let upper = name.to_string().to_uppercase();
+        let shift_const = syn::Ident::new(&format!("{}_SHIFT", upper), Span::call_site());
+        let bits_const = syn::Ident::new(&format!("{}_BITS", upper), Span::call_site());
+        let setter = syn::Ident::new(&format!("set_{}", name), Span::call_site());
+        let builder = syn::Ident::new(&format!("with_{}", name), Span::call_site());
+        let field_name = name.to_string();
+
+        items.push(quote! {
+            /// Position of the least significant bit of the field in the key.
+            pub const #shift_const: u32 = #shift;
+            /// Number of bits of the field.
+            pub const #bits_const: u32 = #bits;
+
+            /// Returns the value of the field.
+            pub fn #name(&self) -> u64 {
+                (self.0 >> #shift) & #mask
+            }
+
+            /// Sets the value of the field.
+            ///
+            /// Panics if the value does not fit in the bits of the field.
+            pub fn #setter(&mut self, value: u64) {
+                assert!(
+                    value <= #mask,
+                    "value {:#x} does not fit in the {} bits of sort key field `{}`",
+                    value,
+                    #bits,
+                    #field_name
+                );
+                self.0 = (self.0 & !(#mask << #shift)) | (value << #shift);
+            }
+
+            /// Returns a copy of the key with the field set to `value`.
+            ///
+            /// Panics if the value does not fit in the bits of the field.
+            pub fn #builder(mut self, value: u64) -> Self {
+                self.#setter(value);
+                self
+            }
+        });
+    }
+
+    quote! {
+        impl #struct_name {
+            #(#items)*
+        }
+
+        impl #G::sortkey::SortKey for #struct_name {
+            const BITS: u32 = #total_bits;
+        }
+
+        impl From<#struct_name> for u64 {
+            fn from(key: #struct_name) -> u64 {
+                key.0
+            }
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use autograph_api::{image::DepthStencilView, pipeline::Arguments, Backend};
+
+#[derive(Arguments)]
+struct Targets<'a, B: Backend> {
+    #[argument(depth_stencil_render_target)]
+    depth: DepthStencilView<'a, B>,
+    #[argument(depth_stencil_render_target)]
+    shadow: DepthStencilView<'a, B>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use autograph_api::{buffer::Buffer, pipeline::Arguments, Backend};
+
+#[derive(Arguments)]
+struct Mesh<'a, B: Backend> {
+    #[argument(index_buffer)]
+    indices: Buffer<'a, B, [u16]>,
+    #[argument(index_buffer)]
+    more_indices: Buffer<'a, B, [u16]>,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use autograph_api::{image::RenderTargetView, pipeline::Arguments, Backend};
+
+#[derive(Arguments)]
+struct Portal<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(stencil_reference)]
+    portal_id: u32,
+    #[argument(stencil_reference)]
+    outline_id: u32,
+}
+
+fn main() {}
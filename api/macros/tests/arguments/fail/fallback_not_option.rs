@@ -0,0 +1,9 @@
+use autograph_api::{image::TextureSampler2dView, pipeline::Arguments, Backend};
+
+#[derive(Arguments)]
+struct Material<'a, B: Backend> {
+    #[argument(descriptor, fallback = "white")]
+    albedo: TextureSampler2dView<'a, B>,
+}
+
+fn main() {}
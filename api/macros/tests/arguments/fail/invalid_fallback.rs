@@ -0,0 +1,9 @@
+use autograph_api::{image::TextureSampler2dView, pipeline::Arguments, Backend};
+
+#[derive(Arguments)]
+struct Material<'a, B: Backend> {
+    #[argument(descriptor, fallback = "pink")]
+    albedo: Option<TextureSampler2dView<'a, B>>,
+}
+
+fn main() {}
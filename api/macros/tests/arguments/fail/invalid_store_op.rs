@@ -0,0 +1,9 @@
+use autograph_api::{image::RenderTargetView, pipeline::Arguments, Backend};
+
+#[derive(Arguments)]
+struct Targets<'a, B: Backend> {
+    #[argument(render_target, store = "discard")]
+    color: RenderTargetView<'a, B>,
+}
+
+fn main() {}
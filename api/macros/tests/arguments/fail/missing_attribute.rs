@@ -0,0 +1,14 @@
+use autograph_api::{
+    image::RenderTargetView,
+    pipeline::{Arguments, Viewport},
+    Backend,
+};
+
+#[derive(Arguments)]
+struct Targets<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    viewport: Viewport,
+}
+
+fn main() {}
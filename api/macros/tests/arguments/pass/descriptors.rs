@@ -0,0 +1,71 @@
+use autograph_api::{
+    buffer::BufferView,
+    descriptor::{ResourceBinding, ResourceBindingType, ResourceShape},
+    format::Format,
+    image::{RwImage2dView, Texture3dView, TextureSampler2dView},
+    pipeline::{Arguments, ShaderStageFlags, SignatureDescription},
+    Backend, DummyBackend,
+};
+
+#[derive(Arguments)]
+struct Resources<'a, B: Backend> {
+    #[argument(descriptor)]
+    albedo: TextureSampler2dView<'a, B>,
+    #[argument(descriptor)]
+    volume: Texture3dView<'a, B>,
+    #[argument(descriptor)]
+    output: RwImage2dView<'a, B>,
+    #[argument(texel_buffer)]
+    bones: BufferView<'a, B>,
+    #[argument(descriptor, fallback = "black")]
+    emission: Option<TextureSampler2dView<'a, B>>,
+}
+
+fn binding(index: u32, ty: ResourceBindingType, name: &'static str) -> ResourceBinding<'static> {
+    ResourceBinding {
+        set: None,
+        index,
+        ty,
+        stage_flags: ShaderStageFlags::ALL_GRAPHICS,
+        count: 1,
+        data_ty: None,
+        data_layout: None,
+        data_format: Format::UNDEFINED,
+        name: Some(name),
+    }
+}
+
+fn main() {
+    let sig: &SignatureDescription =
+        <Resources<DummyBackend> as Arguments<DummyBackend>>::SIGNATURE;
+    // descriptors are numbered in declaration order
+    assert_eq!(
+        sig.descriptors,
+        &[
+            binding(
+                0,
+                ResourceBindingType::TextureSampler(ResourceShape::R2d),
+                "albedo"
+            ),
+            binding(
+                1,
+                ResourceBindingType::Texture(ResourceShape::R3d),
+                "volume"
+            ),
+            binding(
+                2,
+                ResourceBindingType::RwImage(ResourceShape::R2d),
+                "output"
+            ),
+            binding(3, ResourceBindingType::TexelBuffer, "bones"),
+            // optional descriptors have the type of the resource
+            binding(
+                4,
+                ResourceBindingType::TextureSampler(ResourceShape::R2d),
+                "emission"
+            ),
+        ][..]
+    );
+    assert!(!sig.is_root_fragment_output_signature);
+    assert!(sig.fragment_outputs.is_empty());
+}
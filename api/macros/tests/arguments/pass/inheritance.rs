@@ -0,0 +1,55 @@
+use autograph_api::{
+    image::{RenderTargetView, TextureSampler2dView},
+    pipeline::{Arguments, Scissor, SignatureDescription, TypedArgumentBlock, Viewport},
+    Backend, DummyBackend,
+};
+
+#[derive(Arguments)]
+struct Targets<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+    #[argument(scissor)]
+    scissor: Scissor,
+}
+
+#[derive(Arguments)]
+struct Textures<'a, B: Backend> {
+    #[argument(descriptor)]
+    albedo: TextureSampler2dView<'a, B>,
+}
+
+#[derive(Arguments)]
+struct Material<'a, B: Backend> {
+    #[argument(inherit)]
+    targets: TypedArgumentBlock<'a, B, Targets<'a, B>>,
+    #[argument(inherit)]
+    textures: TypedArgumentBlock<'a, B, Textures<'a, B>>,
+    #[argument(descriptor)]
+    normal_map: TextureSampler2dView<'a, B>,
+}
+
+fn signature<'a, A: Arguments<'a, DummyBackend>>() -> &'static SignatureDescription<'static> {
+    A::SIGNATURE
+}
+
+fn main() {
+    let targets = signature::<Targets<DummyBackend>>();
+    assert_eq!(targets.num_viewports, 1);
+    assert_eq!(targets.num_scissors, 1);
+    assert!(targets.inherited.is_empty());
+
+    let material = signature::<Material<DummyBackend>>();
+    // inherited signatures are listed in declaration order
+    assert_eq!(material.inherited.len(), 2);
+    assert_eq!(material.inherited[0], targets);
+    assert_eq!(material.inherited[1], signature::<Textures<DummyBackend>>());
+    // the descriptors of a block are numbered independently of the inherited blocks
+    assert_eq!(material.descriptors.len(), 1);
+    assert_eq!(material.descriptors[0].index, 0);
+    assert_eq!(material.descriptors[0].name, Some("normal_map"));
+    assert_eq!(material.num_viewports, 0);
+    assert_eq!(material.num_scissors, 0);
+    assert!(!material.is_root_fragment_output_signature);
+}
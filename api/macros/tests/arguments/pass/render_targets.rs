@@ -0,0 +1,68 @@
+use autograph_api::{
+    image::{DepthStencilView, RenderTargetView},
+    pipeline::{
+        Arguments, ColorBlendAttachmentState, FragmentOutputDescription, LoadOp,
+        SignatureDescription, StoreOp,
+    },
+    Backend, DummyBackend,
+};
+
+#[derive(Arguments)]
+struct GBuffer<'a, B: Backend> {
+    #[argument(render_target)]
+    albedo: RenderTargetView<'a, B>,
+    #[argument(render_target, blend = "additive", load = "clear")]
+    emission: RenderTargetView<'a, B>,
+    #[argument(render_target, load = "dont_care", store = "dont_care")]
+    scratch: RenderTargetView<'a, B>,
+    #[argument(depth_stencil_render_target, load = "clear")]
+    depth: DepthStencilView<'a, B>,
+}
+
+#[derive(Arguments)]
+struct Layers<'a, B: Backend> {
+    #[argument(render_target, blend = "alpha")]
+    layers: [RenderTargetView<'a, B>; 3],
+}
+
+fn main() {
+    let sig: &SignatureDescription = <GBuffer<DummyBackend> as Arguments<DummyBackend>>::SIGNATURE;
+    assert!(sig.is_root_fragment_output_signature);
+    assert_eq!(
+        sig.fragment_outputs,
+        &[
+            FragmentOutputDescription::DEFAULT,
+            FragmentOutputDescription {
+                blend: Some(ColorBlendAttachmentState::ADDITIVE),
+                load_op: LoadOp::CLEAR_ZERO,
+                store_op: StoreOp::Store,
+            },
+            FragmentOutputDescription {
+                blend: None,
+                load_op: LoadOp::DontCare,
+                store_op: StoreOp::DontCare,
+            },
+        ][..]
+    );
+    assert_eq!(
+        sig.depth_stencil_fragment_output,
+        Some(FragmentOutputDescription {
+            blend: None,
+            load_op: LoadOp::CLEAR_DEPTH_FAR,
+            store_op: StoreOp::Store,
+        })
+    );
+    assert!(sig.descriptors.is_empty());
+    assert!(sig.vertex_inputs.is_empty());
+
+    // arrays of render targets get one output per element, with the same options
+    let sig: &SignatureDescription = <Layers<DummyBackend> as Arguments<DummyBackend>>::SIGNATURE;
+    assert_eq!(sig.fragment_outputs.len(), 3);
+    for output in sig.fragment_outputs.iter() {
+        assert_eq!(
+            output.blend,
+            Some(ColorBlendAttachmentState::ALPHA_BLENDING)
+        );
+    }
+    assert_eq!(sig.depth_stencil_fragment_output, None);
+}
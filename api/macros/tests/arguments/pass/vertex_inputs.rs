@@ -0,0 +1,51 @@
+use autograph_api::{
+    buffer::Buffer,
+    pipeline::{Arguments, SignatureDescription, VertexInputBinding},
+    vertex::{IndexFormat, VertexData, VertexInputRate},
+    Backend, DummyBackend,
+};
+
+#[derive(VertexData, Copy, Clone)]
+#[repr(C)]
+struct Vertex {
+    position: [f32; 3],
+    texcoords: [f32; 2],
+}
+
+#[derive(VertexData, Copy, Clone)]
+#[repr(C)]
+struct Color {
+    color: [u8; 4],
+}
+
+#[derive(Arguments)]
+struct Mesh<'a, B: Backend> {
+    #[argument(vertex_buffer)]
+    vertices: Buffer<'a, B, [Vertex]>,
+    #[argument(vertex_buffer)]
+    colors: Buffer<'a, B, [Color]>,
+    #[argument(index_buffer)]
+    indices: Buffer<'a, B, [u32]>,
+}
+
+fn main() {
+    let sig: &SignatureDescription = <Mesh<DummyBackend> as Arguments<DummyBackend>>::SIGNATURE;
+    assert_eq!(
+        sig.vertex_inputs,
+        &[
+            VertexInputBinding {
+                layout: Vertex::LAYOUT,
+                rate: VertexInputRate::Vertex,
+                base_location: None,
+            },
+            VertexInputBinding {
+                layout: Color::LAYOUT,
+                rate: VertexInputRate::Vertex,
+                base_location: None,
+            },
+        ][..]
+    );
+    assert_eq!(sig.index_format, Some(IndexFormat::U32));
+    // vertex inputs can be extended by inheriting blocks
+    assert!(!sig.is_root_vertex_input_signature);
+}
@@ -0,0 +1,15 @@
+//! Fixtures for `#[derive(SortKey)]`.
+//!
+//! Each file in `tests/sort_key/pass` is compiled and run, and each file in
+//! `tests/sort_key/fail` must be rejected with the diagnostics in the `.stderr` file of the same
+//! name.
+//!
+//! After an intended change to the diagnostics of the derive, update the snapshots with
+//! `TRYBUILD=overwrite cargo test -p autograph-api-macros --test sort_key`.
+
+#[test]
+fn derive_sort_key() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/sort_key/pass/*.rs");
+    t.compile_fail("tests/sort_key/fail/*.rs");
+}
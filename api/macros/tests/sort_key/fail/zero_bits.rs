@@ -0,0 +1,7 @@
+use autograph_api::sortkey::SortKey;
+
+#[derive(Copy, Clone, SortKey)]
+#[sort_key(layer = 0, depth = 24)]
+struct Key(u64);
+
+fn main() {}
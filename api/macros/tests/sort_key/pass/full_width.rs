@@ -0,0 +1,21 @@
+use autograph_api::sortkey::SortKey;
+
+// fields using all 64 bits of the key
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, SortKey)]
+#[sort_key(layer = 8, material = 24, depth = 32)]
+struct Key(u64);
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, SortKey)]
+#[sort_key(index = 64)]
+struct Raw(u64);
+
+fn main() {
+    assert_eq!(Key::BITS, 64);
+    assert_eq!(Key::LAYER_SHIFT, 56);
+    let key = Key::default().with_layer(0xFF).with_depth(0xFFFF_FFFF);
+    assert_eq!(key.value(), 0xFF00_0000_FFFF_FFFF);
+    assert_eq!(key.material(), 0);
+
+    assert_eq!(Raw::BITS, 64);
+    assert_eq!(Raw::default().with_index(!0).value(), !0);
+}
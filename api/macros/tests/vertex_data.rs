@@ -0,0 +1,15 @@
+//! Fixtures for `#[derive(VertexData)]`.
+//!
+//! Each file in `tests/vertex_data/pass` is compiled and run, and each file in
+//! `tests/vertex_data/fail` must be rejected with the diagnostics in the `.stderr` file of the
+//! same name.
+//!
+//! After an intended change to the diagnostics of the derive, update the snapshots with
+//! `TRYBUILD=overwrite cargo test -p autograph-api-macros --test vertex_data`.
+
+#[test]
+fn derive_vertex_data() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/vertex_data/pass/*.rs");
+    t.compile_fail("tests/vertex_data/fail/*.rs");
+}
@@ -0,0 +1,11 @@
+use autograph_api::vertex::VertexData;
+
+#[derive(VertexData, Copy, Clone)]
+#[repr(C)]
+struct Vertex {
+    position: [f32; 3],
+    #[vertex(format = "R32G32B32A32_SFLOAT")]
+    texcoords: [u16; 2],
+}
+
+fn main() {}
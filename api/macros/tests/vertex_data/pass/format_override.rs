@@ -0,0 +1,23 @@
+use autograph_api::{format::Format, vertex::VertexData};
+
+#[derive(VertexData, Copy, Clone)]
+#[repr(C)]
+struct Vertex {
+    position: [f32; 3],
+    #[vertex(format = "R16G16_SFLOAT")]
+    texcoords: [u16; 2],
+    #[vertex(format = "A2B10G10R10_SNORM_PACK32")]
+    normal: u32,
+}
+
+fn main() {
+    let formats: Vec<_> = Vertex::LAYOUT.elements.iter().map(|e| e.format).collect();
+    assert_eq!(
+        formats,
+        &[
+            Format::R32G32B32_SFLOAT,
+            Format::R16G16_SFLOAT,
+            Format::A2B10G10R10_SNORM_PACK32
+        ]
+    );
+}
@@ -0,0 +1,76 @@
+/// Generates type aliases for the main API types, specialized for the given backend.
+///
+/// Most types of the API are generic over the backend, which leads to verbose signatures in
+/// applications that only ever use one backend. This macro defines a `Backend` alias
+/// and an alias without the backend parameter for each type (`Arena<'a>`, `Buffer<'a, T>`,
+/// `Image2d<'a>`, `TypedGraphicsPipeline<'a, T>`...) in the current module.
+///
+/// ```
+/// autograph_api::aliases!(pub autograph_api::DummyBackend);
+///
+/// fn uses_aliases<'a>(_arena: &'a Arena<'a>, _buffer: Buffer<'a, [f32]>) {}
+/// ```
+#[macro_export]
+macro_rules! aliases {
+    ($vis:vis $backend:ty) => {
+        $vis type Backend = $backend;
+        $vis type Api = $crate::Api<$backend>;
+        $vis type Arena<'a> = $crate::Arena<'a, $backend>;
+        $vis type Swapchain<'a> = $crate::swapchain::Swapchain<'a, $backend>;
+        $vis type SwapchainFrame<'a> = $crate::swapchain::SwapchainFrame<'a, $backend>;
+        $vis type CommandBuffer<'a> = $crate::command::CommandBuffer<'a, $backend>;
+        $vis type ResourceSemantics<'a> = $crate::semantics::ResourceSemantics<'a, $backend>;
+        $vis type OcclusionQuery<'a> = $crate::query::OcclusionQuery<'a, $backend>;
+
+        $vis type Buffer<'a, T> = $crate::buffer::Buffer<'a, $backend, T>;
+        $vis type BufferTypeless<'a> = $crate::buffer::BufferTypeless<'a, $backend>;
+        $vis type TypedConstantBufferView<'a, T> =
+            $crate::buffer::TypedConstantBufferView<'a, $backend, T>;
+        $vis type TypedRwBufferView<'a, T> = $crate::buffer::TypedRwBufferView<'a, $backend, T>;
+        $vis type HostReference<'a, T> = $crate::descriptor::HostReference<'a, $backend, T>;
+        $vis type HostReadback<'a, T> = $crate::buffer::HostReadback<'a, $backend, T>;
+
+        $vis type Image1d<'a> = $crate::image::Image1d<'a, $backend>;
+        $vis type Image2d<'a> = $crate::image::Image2d<'a, $backend>;
+        $vis type Image3d<'a> = $crate::image::Image3d<'a, $backend>;
+        $vis type RenderTargetImage2d<'a> = $crate::image::RenderTargetImage2d<'a, $backend>;
+        $vis type DepthStencilImage2d<'a> = $crate::image::DepthStencilImage2d<'a, $backend>;
+        $vis type Image2dView<'a> = $crate::image::Image2dView<'a, $backend>;
+        $vis type RenderTargetView<'a> = $crate::image::RenderTargetView<'a, $backend>;
+        $vis type RenderTarget2dView<'a> = $crate::image::RenderTarget2dView<'a, $backend>;
+        $vis type DepthStencilView<'a> = $crate::image::DepthStencilView<'a, $backend>;
+        $vis type DepthStencil2dView<'a> = $crate::image::DepthStencil2dView<'a, $backend>;
+        $vis type TextureSampler2dView<'a> = $crate::image::TextureSampler2dView<'a, $backend>;
+        $vis type TextureSampler2dLodView<'a> =
+            $crate::image::TextureSampler2dLodView<'a, $backend>;
+        $vis type Image3dView<'a> = $crate::image::Image3dView<'a, $backend>;
+        $vis type Texture3dView<'a> = $crate::image::Texture3dView<'a, $backend>;
+        $vis type TextureSampler3dView<'a> = $crate::image::TextureSampler3dView<'a, $backend>;
+        $vis type RwImage3dView<'a> = $crate::image::RwImage3dView<'a, $backend>;
+        $vis type RenderTarget3dSliceView<'a> =
+            $crate::image::RenderTarget3dSliceView<'a, $backend>;
+        $vis type RenderTarget2dArrayView<'a> =
+            $crate::image::RenderTarget2dArrayView<'a, $backend>;
+        $vis type DepthStencil2dArrayView<'a> =
+            $crate::image::DepthStencil2dArrayView<'a, $backend>;
+        $vis type RwImage2dView<'a> = $crate::image::RwImage2dView<'a, $backend>;
+
+        $vis type VertexBufferView<'a> = $crate::vertex::VertexBufferView<'a, $backend>;
+        $vis type TypedVertexBufferView<'a, V> =
+            $crate::vertex::TypedVertexBufferView<'a, $backend, V>;
+        $vis type IndexBufferView<'a> = $crate::vertex::IndexBufferView<'a, $backend>;
+        $vis type TypedIndexBufferView<'a, I> =
+            $crate::vertex::TypedIndexBufferView<'a, $backend, I>;
+
+        $vis type BareArgumentBlock<'a> = $crate::pipeline::BareArgumentBlock<'a, $backend>;
+        $vis type TypedArgumentBlock<'a, T> =
+            $crate::pipeline::TypedArgumentBlock<'a, $backend, T>;
+        $vis type TypedCachedArgumentBlock<'a, T> =
+            $crate::pipeline::TypedCachedArgumentBlock<'a, $backend, T>;
+        $vis type TypedGraphicsPipeline<'a, T> =
+            $crate::pipeline::TypedGraphicsPipeline<'a, $backend, T>;
+        $vis type TypedComputePipeline<'a, T> =
+            $crate::pipeline::TypedComputePipeline<'a, $backend, T>;
+        $vis type DynamicSignature<'a> = $crate::pipeline::DynamicSignature<'a, $backend>;
+    };
+}
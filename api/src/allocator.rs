@@ -0,0 +1,86 @@
+//! Hooks for the allocation of the device memory of arenas.
+//!
+//! Arenas are created with [ArenaOptions]. The [ArenaAllocator] of the options is called by the
+//! backend each time it allocates or frees device memory on behalf of the arena, which lets
+//! applications track memory usage per arena, or enforce a memory budget:
+//! ```ignore
+//! #[derive(Debug)]
+//! struct Budget(AtomicU64);
+//!
+//! impl ArenaAllocator for Budget {
+//!     fn allocate(&self, request: &AllocationRequest) -> Result<(), Error> { ... }
+//!     fn free(&self, request: &AllocationRequest) { ... }
+//! }
+//!
+//! let arena = api.create_arena_with_options(ArenaOptions {
+//!     allocator: Box::new(Budget(AtomicU64::new(64 << 20))),
+//!     ..ArenaOptions::default()
+//! });
+//! ```
+use crate::error::Error;
+use std::fmt::Debug;
+
+/// Default value of [ArenaOptions::block_size].
+pub const DEFAULT_ARENA_BLOCK_SIZE: u64 = 256 * 1024;
+
+/// The kind of object for which memory is allocated.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AllocationKind {
+    /// A dedicated buffer object.
+    Buffer,
+}
+
+/// Describes an allocation of device memory made by the backend.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct AllocationRequest {
+    pub kind: AllocationKind,
+    /// Size of the allocation in bytes.
+    pub size: u64,
+}
+
+/// Hook called by the backend when it allocates or frees device memory for an arena.
+///
+/// Allocators are shared between the thread that owns the arena and the backend, so methods
+/// take `&self`: use atomics or locks to keep state.
+pub trait ArenaAllocator: Debug + Send + Sync {
+    /// Called before the backend allocates memory for an object of the arena.
+    ///
+    /// Returning an error (usually `Error::OutOfMemory`) refuses the allocation: the backend
+    /// then panics.
+    fn allocate(&self, request: &AllocationRequest) -> Result<(), Error>;
+
+    /// Called after the backend has freed memory previously accepted by `allocate`,
+    /// with the same request.
+    fn free(&self, request: &AllocationRequest);
+}
+
+/// Allocator that accepts all allocations.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DefaultAllocator;
+
+impl ArenaAllocator for DefaultAllocator {
+    fn allocate(&self, _request: &AllocationRequest) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn free(&self, _request: &AllocationRequest) {}
+}
+
+/// Parameters for the creation of an arena.
+#[derive(Debug)]
+pub struct ArenaOptions {
+    /// Size in bytes of the blocks from which the backend sub-allocates small objects of the
+    /// arena (e.g. the upload blocks of the OpenGL backend).
+    pub block_size: u64,
+    /// Hook called on allocations of device memory.
+    pub allocator: Box<dyn ArenaAllocator>,
+}
+
+impl Default for ArenaOptions {
+    fn default() -> Self {
+        ArenaOptions {
+            block_size: DEFAULT_ARENA_BLOCK_SIZE,
+            allocator: Box::new(DefaultAllocator),
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! Per-frame budgets for uploads and transient memory.
+//!
+//! A single frame that uploads too much data (e.g. all the textures of a level streamed at once)
+//! or creates too many large resources can stall for seconds. Budgets catch these frames, and
+//! can spread queued uploads over several frames. They are set with
+//! [Api::set_budget](crate::Api::set_budget):
+//! ```ignore
+//! api.set_budget(Budget {
+//!     max_upload_bytes_per_frame: Some(32 << 20),
+//!     max_transient_mb: Some(256),
+//!     defer_uploads: true,
+//! });
+//! ```
+//!
+//! The following is counted between two calls to `submit_frame`:
+//! - uploads: the initial data of the images and buffers created in arenas, the host references,
+//!   and the updates of images and buffers made with the `update_*` methods of [Api](crate::Api);
+//! - transient memory: the estimated memory of the images and buffers created in arenas.
+//!
+//! When a budget is exceeded, a [BudgetWarning] is logged on submission, and stored in the frame
+//! report ([FrameReport::budget_warnings](crate::report::FrameReport::budget_warnings)) if frame
+//! reports are enabled. The usage of the last frame is returned by
+//! [Api::last_budget_usage](crate::Api::last_budget_usage).
+//!
+//! Uploads made by the methods above happen immediately. Updates that can wait are recorded in an
+//! [UploadQueue] instead, and applied with [Api::flush_uploads](crate::Api::flush_uploads) once
+//! per frame. With [Budget::defer_uploads], the updates that do not fit in the remaining upload
+//! budget of the frame stay in the queue for the next frames:
+//! ```ignore
+//! uploads.update_image(tile.texture, (0, 0, 0), (256, 256, 1), &tile.texels);
+//! ...
+//! api.flush_uploads(&mut uploads);
+//! api.submit_frame(cmdbufs);
+//! ```
+use crate::{
+    buffer::BufferTypeless,
+    format::Format,
+    image::{Dimensions, ImageView, MipmapsOption},
+    Backend,
+};
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+/// Limits on the work of a frame. `None` means no limit.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Budget {
+    /// Maximum number of bytes uploaded to the GPU in a frame.
+    pub max_upload_bytes_per_frame: Option<u64>,
+    /// Maximum memory of the images and buffers created in a frame, in mebibytes.
+    pub max_transient_mb: Option<u64>,
+    /// Whether [Api::flush_uploads](crate::Api::flush_uploads) leaves the updates that exceed the
+    /// upload budget in their queue, instead of applying them all.
+    pub defer_uploads: bool,
+}
+
+/// Work done in a frame, counted against the [Budget].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct BudgetUsage {
+    /// Number of bytes uploaded to the GPU.
+    pub upload_bytes: u64,
+    /// Estimated memory of the images and buffers created, in bytes.
+    pub transient_bytes: u64,
+    /// Number of updates left in their queues by `flush_uploads`, summed over the calls of the
+    /// frame.
+    pub deferred_updates: u32,
+    /// Number of bytes of the updates left in their queues.
+    pub deferred_bytes: u64,
+}
+
+/// A budget exceeded in a frame.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum BudgetWarning {
+    /// More bytes than `max_upload_bytes_per_frame` were uploaded.
+    Uploads { bytes: u64, budget: u64 },
+    /// More memory than `max_transient_mb` was allocated. Values are in bytes.
+    TransientMemory { bytes: u64, budget: u64 },
+}
+
+impl fmt::Display for BudgetWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BudgetWarning::Uploads { bytes, budget } => write!(
+                f,
+                "frame upload budget exceeded: {} bytes uploaded (budget: {} bytes)",
+                bytes, budget
+            ),
+            BudgetWarning::TransientMemory { bytes, budget } => write!(
+                f,
+                "frame transient memory budget exceeded: {} MiB allocated (budget: {} MiB)",
+                bytes >> 20,
+                budget >> 20
+            ),
+        }
+    }
+}
+
+/// Returns the estimated size in bytes of an image with resolved dimensions.
+///
+/// Formats with no known texel size (e.g. compressed formats) are counted as zero bytes.
+pub(crate) fn image_memory_estimate(
+    format: Format,
+    dimensions: Dimensions,
+    mipmaps: MipmapsOption,
+    samples: u32,
+) -> u64 {
+    let (width, height, depth) = dimensions.width_height_depth();
+    let texels: u64 = (0..mipmaps.count(width, height, depth))
+        .map(|level| {
+            let extent = |size: u32| u64::from(size.checked_shr(level).unwrap_or(0).max(1));
+            extent(width) * extent(height) * extent(depth)
+        })
+        .sum();
+    texels
+        * u64::from(dimensions.array_layers_with_cube())
+        * u64::from(samples.max(1))
+        * format.get_format_info().byte_size() as u64
+}
+
+/// An update recorded in an [UploadQueue].
+enum QueuedUpdate<'a, B: Backend> {
+    Image {
+        image: ImageView<'a, B>,
+        min_extent: (u32, u32, u32),
+        max_extent: (u32, u32, u32),
+        data: Vec<u8>,
+    },
+    Buffer {
+        buffer: BufferTypeless<'a, B>,
+        offset: usize,
+        data: Vec<u8>,
+    },
+}
+
+impl<'a, B: Backend> QueuedUpdate<'a, B> {
+    fn size(&self) -> u64 {
+        match *self {
+            QueuedUpdate::Image { ref data, .. } | QueuedUpdate::Buffer { ref data, .. } => {
+                data.len() as u64
+            }
+        }
+    }
+}
+
+/// Updates of images and buffers waiting to be applied with
+/// [Api::flush_uploads](crate::Api::flush_uploads), in the order in which they were recorded.
+///
+/// The data is copied in the queue.
+pub struct UploadQueue<'a, B: Backend> {
+    updates: VecDeque<QueuedUpdate<'a, B>>,
+}
+
+impl<'a, B: Backend> Default for UploadQueue<'a, B> {
+    fn default() -> Self {
+        UploadQueue::new()
+    }
+}
+
+impl<'a, B: Backend> UploadQueue<'a, B> {
+    /// Creates an empty queue.
+    pub fn new() -> UploadQueue<'a, B> {
+        UploadQueue {
+            updates: VecDeque::new(),
+        }
+    }
+
+    /// Queues an update of an image. See [Api::update_image](crate::Api::update_image).
+    pub fn update_image(
+        &mut self,
+        image: impl Into<ImageView<'a, B>>,
+        min_extent: (u32, u32, u32),
+        max_extent: (u32, u32, u32),
+        data: &[u8],
+    ) {
+        self.updates.push_back(QueuedUpdate::Image {
+            image: image.into(),
+            min_extent,
+            max_extent,
+            data: data.to_vec(),
+        });
+    }
+
+    /// Queues an update of a buffer. See [Api::update_buffer](crate::Api::update_buffer).
+    pub fn update_buffer(
+        &mut self,
+        buffer: impl Into<BufferTypeless<'a, B>>,
+        offset: usize,
+        data: &[u8],
+    ) {
+        self.updates.push_back(QueuedUpdate::Buffer {
+            buffer: buffer.into(),
+            offset,
+            data: data.to_vec(),
+        });
+    }
+
+    /// Returns the number of queued updates.
+    pub fn len(&self) -> usize {
+        self.updates.len()
+    }
+
+    /// Returns whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty()
+    }
+
+    /// Returns the number of bytes of the queued updates.
+    pub fn pending_bytes(&self) -> u64 {
+        self.updates.iter().map(QueuedUpdate::size).sum()
+    }
+}
+
+/// Usage of the current frame, and state of the last one.
+#[derive(Default)]
+struct BudgetState {
+    budget: Budget,
+    /// Usage of the current frame, except the uploaded bytes and the transient memory.
+    current: BudgetUsage,
+    /// Number of queued updates applied in the current frame.
+    flushed_updates: u32,
+    last: BudgetUsage,
+    last_warnings: Vec<BudgetWarning>,
+}
+
+pub(crate) struct BudgetTracker {
+    state: Mutex<BudgetState>,
+    // counted for each resource creation and update: not behind the lock
+    upload_bytes: AtomicUsize,
+    transient_bytes: AtomicUsize,
+}
+
+impl BudgetTracker {
+    pub(crate) fn new() -> BudgetTracker {
+        BudgetTracker {
+            state: Mutex::new(BudgetState::default()),
+            upload_bytes: AtomicUsize::new(0),
+            transient_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn budget(&self) -> Budget {
+        self.state.lock().unwrap().budget
+    }
+
+    pub(crate) fn set_budget(&self, budget: Budget) {
+        self.state.lock().unwrap().budget = budget;
+    }
+
+    pub(crate) fn record_upload(&self, bytes: u64) {
+        self.upload_bytes
+            .fetch_add(bytes as usize, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_transient(&self, bytes: u64) {
+        self.transient_bytes
+            .fetch_add(bytes as usize, Ordering::Relaxed);
+    }
+
+    pub(crate) fn last_usage(&self) -> BudgetUsage {
+        self.state.lock().unwrap().last
+    }
+
+    pub(crate) fn last_warnings(&self) -> Vec<BudgetWarning> {
+        self.state.lock().unwrap().last_warnings.clone()
+    }
+
+    /// Applies the updates of `queue` with `apply`, until one does not fit in the remaining
+    /// upload budget of the frame if uploads are deferred. The first queued update of each frame
+    /// is always applied, so that updates larger than the budget are eventually applied.
+    ///
+    /// Returns the number of applied updates.
+    pub(crate) fn flush<'a, B: Backend>(
+        &self,
+        queue: &mut UploadQueue<'a, B>,
+        mut apply_image: impl FnMut(ImageView<'a, B>, (u32, u32, u32), (u32, u32, u32), &[u8]),
+        mut apply_buffer: impl FnMut(BufferTypeless<'a, B>, usize, &[u8]),
+    ) -> usize {
+        let mut applied = 0;
+        while let Some(size) = queue.updates.front().map(QueuedUpdate::size) {
+            {
+                let state = self.state.lock().unwrap();
+                if state.budget.defer_uploads && state.flushed_updates > 0 {
+                    if let Some(max) = state.budget.max_upload_bytes_per_frame {
+                        let uploaded = self.upload_bytes.load(Ordering::Relaxed) as u64;
+                        if uploaded + size > max {
+                            break;
+                        }
+                    }
+                }
+            }
+            // the `update_*` methods of the API count the uploaded bytes
+            match queue.updates.pop_front().unwrap() {
+                QueuedUpdate::Image {
+                    image,
+                    min_extent,
+                    max_extent,
+                    data,
+                } => apply_image(image, min_extent, max_extent, &data),
+                QueuedUpdate::Buffer {
+                    buffer,
+                    offset,
+                    data,
+                } => apply_buffer(buffer, offset, &data),
+            }
+            self.state.lock().unwrap().flushed_updates += 1;
+            applied += 1;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.current.deferred_updates += queue.len() as u32;
+        state.current.deferred_bytes += queue.pending_bytes();
+        applied
+    }
+
+    /// Ends the usage of the current frame, and returns the exceeded budgets.
+    pub(crate) fn end_frame(&self) -> Vec<BudgetWarning> {
+        let mut state = self.state.lock().unwrap();
+        let usage = BudgetUsage {
+            upload_bytes: self.upload_bytes.swap(0, Ordering::Relaxed) as u64,
+            transient_bytes: self.transient_bytes.swap(0, Ordering::Relaxed) as u64,
+            ..state.current
+        };
+        let mut warnings = Vec::new();
+        if let Some(max) = state.budget.max_upload_bytes_per_frame {
+            if usage.upload_bytes > max {
+                warnings.push(BudgetWarning::Uploads {
+                    bytes: usage.upload_bytes,
+                    budget: max,
+                });
+            }
+        }
+        if let Some(max_mb) = state.budget.max_transient_mb {
+            let max = max_mb << 20;
+            if usage.transient_bytes > max {
+                warnings.push(BudgetWarning::TransientMemory {
+                    bytes: usage.transient_bytes,
+                    budget: max,
+                });
+            }
+        }
+        state.last = usage;
+        state.last_warnings = warnings.clone();
+        state.current = BudgetUsage::default();
+        state.flushed_updates = 0;
+        warnings
+    }
+}
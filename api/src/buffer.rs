@@ -1,10 +1,16 @@
 use crate::{
     descriptor::{Descriptor, ResourceBindingType, ResourceInterface},
-    typedesc::{Layout, PrimitiveType, TypeDesc},
+    format::Format,
+    typedesc::{ArrayLayout, Layout, LayoutDetails, PrimitiveType, TypeDesc},
+    util::{eq_address, hash_address},
     Backend,
 };
-pub use autograph_api_macros::StructuredBufferData;
-use std::marker::PhantomData;
+pub use autograph_api_macros::{Pod, StructuredBufferData};
+use std::{
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem,
+};
 
 //--------------------------------------------------------------------------------------------------
 
@@ -28,6 +34,36 @@ impl<U: BufferData> BufferData for [U] {
     }
 }
 
+/// Marker trait for plain-old-data types, for which any bit pattern is a valid value.
+///
+/// Data copied from the GPU into host memory (see [HostReadback]) can only be read as a `Pod`
+/// type. It is implemented for integer and floating-point types and for arrays of `Pod` types.
+/// Structs whose fields are all `Pod` can derive it with `#[derive(Pod)]`.
+///
+/// # Safety
+///
+/// Implementors must not have invalid bit patterns: this excludes `bool`, `char`, enums
+/// (including [BoolU32]), references and pointers.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(unsafe impl Pod for $t {})*
+    };
+}
+
+macro_rules! impl_pod_arrays {
+    ($($n:expr),*) => {
+        $(unsafe impl<T: Pod> Pod for [T; $n] {})*
+    };
+}
+
+impl_pod!(u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, f32, f64);
+impl_pod_arrays!(
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26,
+    27, 28, 29, 30, 31, 32, 64, 128, 256, 512, 1024
+);
+
 //--------------------------------------------------------------------------------------------------
 
 /// Buffer.
@@ -56,12 +92,41 @@ impl<'a, B: Backend, T: BufferData + ?Sized> Buffer<'a, B, T> {
     }
 }
 
+// buffers are hashed by identity
+impl<'a, B: Backend, T: BufferData + ?Sized> Hash for Buffer<'a, B, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.0, state)
+    }
+}
+
+impl<'a, B: Backend, T: BufferData + ?Sized> PartialEq for Buffer<'a, B, T> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.0, other.0)
+    }
+}
+
+impl<'a, B: Backend, T: BufferData + ?Sized> Eq for Buffer<'a, B, T> {}
+
 /// Buffer without type information.
 #[derive(derivative::Derivative)]
 #[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
 #[repr(transparent)]
 pub struct BufferTypeless<'a, B: Backend>(pub &'a B::Buffer);
 
+impl<'a, B: Backend> Hash for BufferTypeless<'a, B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.0, state)
+    }
+}
+
+impl<'a, B: Backend> PartialEq for BufferTypeless<'a, B> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.0, other.0)
+    }
+}
+
+impl<'a, B: Backend> Eq for BufferTypeless<'a, B> {}
+
 impl<'a, B: Backend, T: BufferData + ?Sized> From<Buffer<'a, B, T>> for BufferTypeless<'a, B> {
     fn from(from: Buffer<'a, B, T>) -> Self {
         from.into_typeless()
@@ -75,6 +140,94 @@ pub struct BufferSlice<'a, B: Backend> {
     pub size: usize,
 }
 
+/// A view of a buffer as an array of formatted texels (a.k.a. texel buffer).
+///
+/// Created with [Arena::create_buffer_view]. Bound to `samplerBuffer` variables in shaders
+/// with `#[argument(texel_buffer)]`.
+///
+/// [Arena::create_buffer_view]: crate::Arena::create_buffer_view
+#[derive(derivative::Derivative)]
+#[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
+pub struct BufferView<'a, B: Backend> {
+    pub(crate) view: &'a B::BufferView,
+    pub(crate) format: Format,
+}
+
+impl<'a, B: Backend> BufferView<'a, B> {
+    pub fn inner(&self) -> &'a B::BufferView {
+        self.view
+    }
+
+    /// Returns the format of the texels.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub unsafe fn from_raw(raw: &'a B::BufferView, format: Format) -> BufferView<'a, B> {
+        BufferView { view: raw, format }
+    }
+}
+
+// hashed by identity
+impl<'a, B: Backend> Hash for BufferView<'a, B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.view, state)
+    }
+}
+
+impl<'a, B: Backend> PartialEq for BufferView<'a, B> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.view, other.view)
+    }
+}
+
+impl<'a, B: Backend> Eq for BufferView<'a, B> {}
+
+impl<'a, B: Backend> ResourceInterface<'a, B> for BufferView<'a, B> {
+    const TYPE: ResourceBindingType = ResourceBindingType::TexelBuffer;
+    fn into_descriptor(self) -> Descriptor<'a, B> {
+        Descriptor::TexelBuffer { view: self.view }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// A destination in host memory for data copied from a GPU buffer.
+///
+/// The destination slice stays mutably borrowed as long as the readback can be referenced
+/// by commands. The data is written by the backend during `submit_frame`, and is guaranteed
+/// to be filled once `submit_frame` returns. See [CommandBuffer::copy_buffer_to_host].
+///
+/// [CommandBuffer::copy_buffer_to_host]: crate::command::CommandBuffer::copy_buffer_to_host
+#[derive(derivative::Derivative)]
+#[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
+#[repr(transparent)]
+pub struct HostReadback<'a, B: Backend, T: Pod>(
+    pub &'a B::HostReadback,
+    pub(crate) PhantomData<&'a mut [T]>,
+);
+
+impl<'a, B: Backend, T: Pod> HostReadback<'a, B, T> {
+    pub fn inner(&self) -> &'a B::HostReadback {
+        self.0
+    }
+}
+
+// hashed by identity
+impl<'a, B: Backend, T: Pod> Hash for HostReadback<'a, B, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.0, state)
+    }
+}
+
+impl<'a, B: Backend, T: Pod> PartialEq for HostReadback<'a, B, T> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.0, other.0)
+    }
+}
+
+impl<'a, B: Backend, T: Pod> Eq for HostReadback<'a, B, T> {}
+
 //--------------------------------------------------------------------------------------------------
 
 /// Trait implemented by types that are layout-compatible with an specific
@@ -115,6 +268,7 @@ impl Default for BoolU32 {
 
 impl_structured_type!(BoolU32, TypeDesc::Primitive(PrimitiveType::UnsignedInt));
 impl_structured_type!(f32, TypeDesc::Primitive(PrimitiveType::Float));
+impl_structured_type!(u32, TypeDesc::Primitive(PrimitiveType::UnsignedInt));
 impl_structured_type!(
     [f32; 2],
     TypeDesc::Vector {
@@ -183,6 +337,22 @@ impl_structured_type!(
     }
 );
 
+// runtime-sized arrays (e.g. `uint data[];` as the last member of a storage buffer)
+unsafe impl<T: StructuredBufferData + Copy> StructuredBufferData for [T] {
+    const TYPE: TypeDesc<'static> = TypeDesc::Array {
+        elem_ty: &T::TYPE,
+        len: 0,
+    };
+    const LAYOUT: Layout<'static> = Layout {
+        align: mem::align_of::<T>(),
+        size: 0,
+        details: LayoutDetails::Array(ArrayLayout {
+            elem_layout: &T::LAYOUT,
+            stride: mem::size_of::<T>(),
+        }),
+    };
+}
+
 /*
 // array impls
 unsafe impl<T: StructuredBufferData + Copy> StructuredBufferData for [T; 32] {
@@ -263,6 +433,24 @@ pub struct TypedConstantBufferView<'a, B: Backend, T: StructuredBufferData> {
     pub(crate) _phantom: PhantomData<&'a T>,
 }
 
+impl<'a, B: Backend, T: StructuredBufferData> Hash for TypedConstantBufferView<'a, B, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.buffer, state);
+        self.offset.hash(state);
+        self.size.hash(state);
+    }
+}
+
+impl<'a, B: Backend, T: StructuredBufferData> PartialEq for TypedConstantBufferView<'a, B, T> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.buffer, other.buffer)
+            && self.offset == other.offset
+            && self.size == other.size
+    }
+}
+
+impl<'a, B: Backend, T: StructuredBufferData> Eq for TypedConstantBufferView<'a, B, T> {}
+
 impl<'a, B: Backend, T: StructuredBufferData> ResourceInterface<'a, B>
     for TypedConstantBufferView<'a, B, T>
 {
@@ -290,3 +478,59 @@ impl<'a, B: Backend, T: StructuredBufferData> From<Buffer<'a, B, T>>
         }
     }
 }
+
+//--------------------------------------------------------------------------------------------------
+#[derive(derivative::Derivative)]
+#[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
+pub struct TypedRwBufferView<'a, B: Backend, T: StructuredBufferData + ?Sized> {
+    pub(crate) buffer: &'a B::Buffer,
+    pub(crate) offset: usize,
+    pub(crate) size: Option<usize>,
+    pub(crate) _phantom: PhantomData<&'a T>,
+}
+
+impl<'a, B: Backend, T: StructuredBufferData + ?Sized> Hash for TypedRwBufferView<'a, B, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.buffer, state);
+        self.offset.hash(state);
+        self.size.hash(state);
+    }
+}
+
+impl<'a, B: Backend, T: StructuredBufferData + ?Sized> PartialEq for TypedRwBufferView<'a, B, T> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.buffer, other.buffer)
+            && self.offset == other.offset
+            && self.size == other.size
+    }
+}
+
+impl<'a, B: Backend, T: StructuredBufferData + ?Sized> Eq for TypedRwBufferView<'a, B, T> {}
+
+impl<'a, B: Backend, T: StructuredBufferData + ?Sized> ResourceInterface<'a, B>
+    for TypedRwBufferView<'a, B, T>
+{
+    const TYPE: ResourceBindingType = ResourceBindingType::RwBuffer;
+    const DATA_TYPE: Option<&'static TypeDesc<'static>> = Some(&T::TYPE);
+    const DATA_LAYOUT: Option<&'static Layout<'static>> = Some(&T::LAYOUT);
+    fn into_descriptor(self) -> Descriptor<'a, B> {
+        Descriptor::RwBuffer {
+            buffer: self.buffer,
+            offset: self.offset,
+            size: self.size,
+        }
+    }
+}
+
+impl<'a, B: Backend, T: StructuredBufferData + ?Sized> From<Buffer<'a, B, T>>
+    for TypedRwBufferView<'a, B, T>
+{
+    fn from(buf: Buffer<'a, B, T>) -> Self {
+        TypedRwBufferView {
+            buffer: buf.0,
+            offset: 0,
+            size: None,
+            _phantom: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Caches of objects created on first use and kept until the renderer is dropped.
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    mem,
+    sync::{Arc, Condvar, Mutex},
+    thread::{self, ThreadId},
+};
+
+enum SlotState<T> {
+    Empty,
+    /// The object is being created by the thread.
+    Creating(ThreadId),
+    Ready(T),
+}
+
+/// A slot of a [ObjectCache], initialized once.
+struct Slot<T> {
+    state: Mutex<SlotState<T>>,
+    /// Notified when the slot leaves the `Creating` state.
+    created: Condvar,
+}
+
+/// Resets a slot to the `Empty` state if the creation of its object panics, so that it is not
+/// left in the `Creating` state forever.
+struct ResetOnUnwind<'a, T>(&'a Slot<T>);
+
+impl<'a, T> Drop for ResetOnUnwind<'a, T> {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.0.state.lock() {
+            *state = SlotState::Empty;
+        }
+        self.0.created.notify_all();
+    }
+}
+
+/// A map from keys to objects that are created once, on first access.
+///
+/// The objects are stored by value, and copied out of the cache. Objects with different keys can
+/// be created concurrently: no lock is held while an object is created. Concurrent accesses to a
+/// key that is being created wait for the creation to finish, so that each object is created
+/// once. If the creation panics, the next access to the key creates the object again.
+pub(crate) struct ObjectCache<K, T> {
+    slots: Mutex<HashMap<K, Arc<Slot<T>>>>,
+}
+
+impl<K: Eq + Hash, T: Copy> ObjectCache<K, T> {
+    pub(crate) fn new() -> ObjectCache<K, T> {
+        ObjectCache {
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the object associated to `key`, or creates it with `create`.
+    ///
+    /// `create` may access other keys of the cache. Panics if it accesses `key` itself.
+    pub(crate) fn get_or_create(&self, key: K, create: impl FnOnce() -> T) -> T {
+        let slot = self
+            .slots
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| {
+                Arc::new(Slot {
+                    state: Mutex::new(SlotState::Empty),
+                    created: Condvar::new(),
+                })
+            })
+            .clone();
+
+        {
+            let mut state = slot.state.lock().unwrap();
+            loop {
+                match *state {
+                    SlotState::Ready(object) => return object,
+                    SlotState::Creating(thread) => {
+                        assert!(
+                            thread != thread::current().id(),
+                            "cached object accessed during its own creation"
+                        );
+                        state = slot.created.wait(state).unwrap();
+                    }
+                    SlotState::Empty => {
+                        *state = SlotState::Creating(thread::current().id());
+                        break;
+                    }
+                }
+            }
+        }
+
+        let reset = ResetOnUnwind(&slot);
+        let object = create();
+        mem::forget(reset);
+        *slot.state.lock().unwrap() = SlotState::Ready(object);
+        slot.created.notify_all();
+        object
+    }
+}
@@ -0,0 +1,302 @@
+//! Inspection of the sorted command stream of a frame.
+//!
+//! Requires the `capture` feature. A [CommandCapture] is a copy of the sorted commands of a
+//! frame that does not borrow the resources referenced by the commands: resources are identified
+//! by their address ([ObjectId]). Captures are obtained either by sorting command buffers without
+//! submitting them ([CommandCapture::from_command_buffers]), or from an actual submission
+//! (see [Api::set_command_capture](crate::Api::set_command_capture)).
+//!
+//! This is intended for testing: see the assertions in `autograph-api-test`.
+use crate::{
+    command::{
+        sort_command_buffers_with_options, BarrierDesc, Command, CommandBuffer, CommandInner,
+        PresentEncoding, SubmitOptions,
+    },
+    Backend,
+};
+
+/// Identifies a backend object referenced by a command, by address.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct ObjectId(usize);
+
+impl ObjectId {
+    /// Returns the identifier of a backend object (e.g. `image.inner()`).
+    pub fn of<T>(object: &T) -> ObjectId {
+        ObjectId(object as *const T as usize)
+    }
+}
+
+/// Lifetime-free copy of a [CommandInner].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CapturedCommandKind {
+    PipelineBarrier {
+        desc: BarrierDesc,
+    },
+    ClearImageFloat {
+        image: ObjectId,
+        color: [f32; 4],
+    },
+    ClearImageUint {
+        image: ObjectId,
+        color: [u32; 4],
+    },
+    ClearImageInt {
+        image: ObjectId,
+        color: [i32; 4],
+    },
+    ClearDepthStencilImage {
+        image: ObjectId,
+        depth: f32,
+        stencil: Option<u8>,
+    },
+    CopyBufferToHost {
+        buffer: ObjectId,
+    },
+    CopyImageToHost {
+        image: ObjectId,
+    },
+    Present {
+        image: ObjectId,
+        swapchain: ObjectId,
+        encoding: PresentEncoding,
+    },
+    DrawHeader {
+        pipeline: ObjectId,
+    },
+    DispatchHeader {
+        pipeline: ObjectId,
+    },
+    BeginQuery {
+        query: ObjectId,
+    },
+    EndQuery {
+        query: ObjectId,
+    },
+    BeginConditional,
+    EndConditional,
+    BeginDebugGroup {
+        name: &'static str,
+    },
+    EndDebugGroup,
+    SetPipelineArguments {
+        arguments: ObjectId,
+    },
+    SetLineWidth {
+        width: f32,
+    },
+    Draw {
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    },
+    DrawIndexed {
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    },
+    Dispatch {
+        group_count: [u32; 3],
+    },
+    DispatchIndirect {
+        buffer: ObjectId,
+        offset: u64,
+    },
+}
+
+impl CapturedCommandKind {
+    /// Returns whether this is a draw or an indexed draw.
+    pub fn is_draw(&self) -> bool {
+        match self {
+            CapturedCommandKind::Draw { .. } | CapturedCommandKind::DrawIndexed { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Returns whether this is a direct or indirect dispatch.
+    pub fn is_dispatch(&self) -> bool {
+        match self {
+            CapturedCommandKind::Dispatch { .. } | CapturedCommandKind::DispatchIndirect { .. } => {
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'a, B: Backend> From<&CommandInner<'a, B>> for CapturedCommandKind {
+    fn from(cmd: &CommandInner<'a, B>) -> Self {
+        match *cmd {
+            CommandInner::PipelineBarrier { desc } => CapturedCommandKind::PipelineBarrier { desc },
+            CommandInner::ClearImageFloat { image, color } => {
+                CapturedCommandKind::ClearImageFloat {
+                    image: ObjectId::of(image),
+                    color,
+                }
+            }
+            CommandInner::ClearImageUint { image, color } => CapturedCommandKind::ClearImageUint {
+                image: ObjectId::of(image),
+                color,
+            },
+            CommandInner::ClearImageInt { image, color } => CapturedCommandKind::ClearImageInt {
+                image: ObjectId::of(image),
+                color,
+            },
+            CommandInner::ClearDepthStencilImage {
+                image,
+                depth,
+                stencil,
+            } => CapturedCommandKind::ClearDepthStencilImage {
+                image: ObjectId::of(image),
+                depth,
+                stencil,
+            },
+            CommandInner::CopyBufferToHost { buffer, .. } => {
+                CapturedCommandKind::CopyBufferToHost {
+                    buffer: ObjectId::of(buffer),
+                }
+            }
+            CommandInner::CopyImageToHost { image, .. } => CapturedCommandKind::CopyImageToHost {
+                image: ObjectId::of(image),
+            },
+            CommandInner::Present {
+                image,
+                swapchain,
+                encoding,
+            } => CapturedCommandKind::Present {
+                image: ObjectId::of(image),
+                swapchain: ObjectId::of(swapchain),
+                encoding,
+            },
+            CommandInner::DrawHeader { pipeline } => CapturedCommandKind::DrawHeader {
+                pipeline: ObjectId::of(pipeline),
+            },
+            CommandInner::DispatchHeader { pipeline } => CapturedCommandKind::DispatchHeader {
+                pipeline: ObjectId::of(pipeline),
+            },
+            CommandInner::BeginQuery { query } => CapturedCommandKind::BeginQuery {
+                query: ObjectId::of(query),
+            },
+            CommandInner::EndQuery { query } => CapturedCommandKind::EndQuery {
+                query: ObjectId::of(query),
+            },
+            CommandInner::BeginConditional { .. } => CapturedCommandKind::BeginConditional,
+            CommandInner::EndConditional {} => CapturedCommandKind::EndConditional,
+            CommandInner::BeginDebugGroup { name } => CapturedCommandKind::BeginDebugGroup { name },
+            CommandInner::EndDebugGroup {} => CapturedCommandKind::EndDebugGroup,
+            CommandInner::SetPipelineArguments { arguments } => {
+                CapturedCommandKind::SetPipelineArguments {
+                    arguments: ObjectId::of(arguments),
+                }
+            }
+            CommandInner::SetLineWidth { width } => CapturedCommandKind::SetLineWidth { width },
+            CommandInner::Draw {
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            } => CapturedCommandKind::Draw {
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            },
+            CommandInner::DrawIndexed {
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            } => CapturedCommandKind::DrawIndexed {
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            },
+            CommandInner::Dispatch {
+                group_count_x,
+                group_count_y,
+                group_count_z,
+            } => CapturedCommandKind::Dispatch {
+                group_count: [group_count_x, group_count_y, group_count_z],
+            },
+            CommandInner::DispatchIndirect { buffer, offset } => {
+                CapturedCommandKind::DispatchIndirect {
+                    buffer: ObjectId::of(buffer),
+                    offset,
+                }
+            }
+        }
+    }
+}
+
+/// A command of a [CommandCapture].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CapturedCommand {
+    pub sortkey: u64,
+    /// See [Command::seq].
+    pub seq: u64,
+    pub kind: CapturedCommandKind,
+}
+
+/// The sorted commands of a frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommandCapture {
+    /// Commands in execution order.
+    pub commands: Vec<CapturedCommand>,
+}
+
+impl CommandCapture {
+    /// Captures a list of sorted commands.
+    pub fn from_commands<'a, B: Backend>(commands: &[Command<'a, B>]) -> CommandCapture {
+        CommandCapture {
+            commands: commands
+                .iter()
+                .map(|cmd| CapturedCommand {
+                    sortkey: cmd.sortkey,
+                    seq: cmd.seq,
+                    kind: (&cmd.cmd).into(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Sorts the commands of the command buffers as they would be on submission,
+    /// without submitting them.
+    pub fn from_command_buffers<'a, B: Backend>(
+        cmdbufs: impl IntoIterator<Item = CommandBuffer<'a, B>>,
+        options: &SubmitOptions,
+    ) -> CommandCapture {
+        Self::from_commands(&sort_command_buffers_with_options(cmdbufs, options))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CapturedCommand> {
+        self.commands.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Returns the number of draws (indexed or not).
+    pub fn draw_count(&self) -> usize {
+        self.iter().filter(|cmd| cmd.kind.is_draw()).count()
+    }
+
+    /// Returns the number of dispatches (direct or indirect).
+    pub fn dispatch_count(&self) -> usize {
+        self.iter().filter(|cmd| cmd.kind.is_dispatch()).count()
+    }
+
+    /// Returns the position of the first command that satisfies `predicate`.
+    pub fn position(&self, predicate: impl Fn(&CapturedCommand) -> bool) -> Option<usize> {
+        self.commands.iter().position(predicate)
+    }
+}
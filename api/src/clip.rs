@@ -0,0 +1,95 @@
+//! Conventions of the clip space of backends.
+//!
+//! Backends may differ in the range of normalized device depth and in the direction of the Y
+//! axis relative to the rows of images. User code should not assume the conventions of a
+//! particular API: query them with [Api::clip_space_info](crate::Api::clip_space_info) and,
+//! with the `glm` feature, build projection matrices with [perspective_for] and [ortho_for].
+#[cfg(feature = "glm")]
+use crate::{Api, Backend};
+
+/// Range of depth values in normalized device coordinates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DepthRange {
+    /// Depth goes from -1 (near) to 1 (far), as in OpenGL.
+    NegativeOneToOne,
+    /// Depth goes from 0 (near) to 1 (far), as in Vulkan and D3D.
+    ZeroToOne,
+}
+
+/// Direction of the Y axis of normalized device coordinates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum YDirection {
+    /// Y = -1 is the bottom row of images and render targets.
+    Up,
+    /// Y = -1 is the top row (the first scanline) of images and render targets.
+    Down,
+}
+
+/// Clip-space conventions of a backend.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ClipSpaceInfo {
+    pub depth_range: DepthRange,
+    pub y_direction: YDirection,
+}
+
+impl ClipSpaceInfo {
+    /// Returns the Y coordinate of the top row of images in normalized device coordinates.
+    pub fn top(&self) -> f32 {
+        match self.y_direction {
+            YDirection::Up => 1.0,
+            YDirection::Down => -1.0,
+        }
+    }
+
+    /// Returns the matrix that transforms OpenGL clip coordinates (Y up, depth in -1..1)
+    /// into the clip coordinates of the backend.
+    #[cfg(feature = "glm")]
+    pub fn correction(&self) -> crate::glm::Mat4 {
+        let sy = match self.y_direction {
+            YDirection::Up => 1.0,
+            YDirection::Down => -1.0,
+        };
+        let (sz, tz) = match self.depth_range {
+            DepthRange::NegativeOneToOne => (1.0, 0.0),
+            DepthRange::ZeroToOne => (0.5, 0.5),
+        };
+        // arguments are in row-major order
+        crate::glm::mat4(
+            1.0, 0.0, 0.0, 0.0, //
+            0.0, sy, 0.0, 0.0, //
+            0.0, 0.0, sz, tz, //
+            0.0, 0.0, 0.0, 1.0,
+        )
+    }
+}
+
+/// Perspective projection matrix (right-handed) for the clip space of the backend of `api`.
+///
+/// Same parameters as `glm::perspective`.
+#[cfg(feature = "glm")]
+pub fn perspective_for<B: Backend>(
+    api: &Api<B>,
+    aspect: f32,
+    fovy: f32,
+    near: f32,
+    far: f32,
+) -> crate::glm::Mat4 {
+    api.clip_space_info().correction() * crate::glm::perspective(aspect, fovy, near, far)
+}
+
+/// Orthographic projection matrix (right-handed) for the clip space of the backend of `api`.
+///
+/// `bottom` and `top` refer to the bottom and top rows of the render target. Same parameters as
+/// `glm::ortho`.
+#[cfg(feature = "glm")]
+pub fn ortho_for<B: Backend>(
+    api: &Api<B>,
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+    znear: f32,
+    zfar: f32,
+) -> crate::glm::Mat4 {
+    api.clip_space_info().correction() * crate::glm::ortho(left, right, bottom, top, znear, zfar)
+}
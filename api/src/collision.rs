@@ -0,0 +1,305 @@
+//! Debug detection of commands with identical sort keys whose relative order affects the output.
+//!
+//! Commands with the same sortkey have no defined order relative to each other beyond what
+//! [SubmitOptions](crate::command::SubmitOptions) guarantees: commands from different command
+//! buffers are ordered by the position of their command buffer in the submission, which is
+//! often incidental (e.g. command buffers recorded in parallel), and commands of the same command
+//! buffer are ordered by insertion only with a stable sort or a tie break.
+//! When two such commands write to the same render target (clears, draws with overlapping
+//! scissors, presentation), a different order produces a different image.
+//!
+//! These checks are enabled with
+//! [Api::set_sort_key_collision_checks](crate::Api::set_sort_key_collision_checks). They only
+//! see the render targets and scissors provided by argument blocks created while the checks
+//! are enabled: static scissors of pipelines are assumed to cover the whole target.
+//!
+//! Recording call sites can be attached to the commands with
+//! [CommandBuffer::set_call_site](crate::command::CommandBuffer::set_call_site) and the
+//! [call_site!](crate::call_site) macro ([render_pass!](crate::render_pass) does it for each
+//! pass), and are reported alongside the colliding keys.
+use crate::{
+    command::{CommandBuffer, CommandInner, SubmitOptions, TieBreak},
+    pipeline::Scissor,
+    util::address,
+    Backend,
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+/// A location in the source code where commands were recorded. See [call_site!](crate::call_site).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CallSite {
+    pub file: &'static str,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for CallSite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Returns the [CallSite] of the macro invocation.
+#[macro_export]
+macro_rules! call_site {
+    () => {
+        $crate::collision::CallSite {
+            file: file!(),
+            line: line!(),
+            column: column!(),
+        }
+    };
+}
+
+/// Where a command was recorded.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CommandOrigin {
+    /// Index of the command buffer in the submission.
+    pub command_buffer: u32,
+    /// Position of the command in its command buffer.
+    pub position: u32,
+    pub call_site: Option<CallSite>,
+}
+
+impl fmt::Display for CommandOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "command #{} of command buffer #{}",
+            self.position, self.command_buffer
+        )?;
+        if let Some(site) = self.call_site {
+            write!(f, " (recorded at {})", site)?;
+        }
+        Ok(())
+    }
+}
+
+/// Two commands with the same sortkey that write to the same render target, and whose
+/// relative order is not guaranteed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SortKeyCollision {
+    pub sortkey: u64,
+    /// The command that comes first in the submission.
+    pub first: CommandOrigin,
+    pub second: CommandOrigin,
+}
+
+impl fmt::Display for SortKeyCollision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "commands with the same sortkey {:#x} write to the same render target in an unspecified order: {} and {}",
+            self.sortkey, self.first, self.second
+        )
+    }
+}
+
+/// Render targets and scissors of an argument block.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BlockTargets {
+    /// Addresses of the inherited argument blocks.
+    pub(crate) inherited: Vec<usize>,
+    /// Addresses of the color and depth-stencil render targets.
+    pub(crate) images: Vec<usize>,
+    pub(crate) scissors: Vec<Scissor>,
+}
+
+/// A command, or group of commands for draws, that writes to render targets.
+struct Operation {
+    sortkey: u64,
+    origin: CommandOrigin,
+    images: Vec<usize>,
+    /// Empty if it covers the whole render targets.
+    scissors: Vec<Scissor>,
+}
+
+pub(crate) struct CollisionChecker {
+    enabled: AtomicBool,
+    /// Targets of the argument blocks created while the checks are enabled, by address.
+    blocks: Mutex<HashMap<usize, BlockTargets>>,
+    /// Collisions of the last submitted frame.
+    last: Mutex<Vec<SortKeyCollision>>,
+}
+
+impl CollisionChecker {
+    pub(crate) fn new() -> CollisionChecker {
+        CollisionChecker {
+            enabled: AtomicBool::new(false),
+            blocks: Mutex::new(HashMap::new()),
+            last: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.blocks.lock().unwrap().clear();
+            self.last.lock().unwrap().clear();
+        }
+    }
+
+    pub(crate) fn register_block(&self, block: usize, targets: BlockTargets) {
+        self.blocks.lock().unwrap().insert(block, targets);
+    }
+
+    /// Forgets the specified argument blocks (when their arena is dropped).
+    pub(crate) fn forget(&self, blocks: &[usize]) {
+        let mut tracked = self.blocks.lock().unwrap();
+        for block in blocks {
+            tracked.remove(block);
+        }
+    }
+
+    pub(crate) fn last_collisions(&self) -> Vec<SortKeyCollision> {
+        self.last.lock().unwrap().clone()
+    }
+
+    /// Finds the collisions in the command buffers of a submission, before sorting,
+    /// and keeps them as the collisions of the last frame.
+    pub(crate) fn check<B: Backend>(
+        &self,
+        command_buffers: &[CommandBuffer<B>],
+        options: &SubmitOptions,
+    ) -> Vec<SortKeyCollision> {
+        let mut operations = Vec::new();
+        {
+            let blocks = self.blocks.lock().unwrap();
+            for (i, cmdbuf) in command_buffers.iter().enumerate() {
+                collect_operations(i as u32, cmdbuf, &blocks, &mut operations);
+            }
+        }
+
+        // without a stable sort or a tie break, even the commands of the same command buffer
+        // can be reordered
+        let same_buffer_ordered = options.stable_sort || options.tie_break != TieBreak::None;
+
+        operations.sort_by_key(|op| op.sortkey);
+        let mut collisions = Vec::new();
+        for (i, a) in operations.iter().enumerate() {
+            for b in operations[i + 1..]
+                .iter()
+                .take_while(|b| b.sortkey == a.sortkey)
+            {
+                if same_buffer_ordered && a.origin.command_buffer == b.origin.command_buffer {
+                    continue;
+                }
+                if a.images.iter().any(|image| b.images.contains(image))
+                    && scissors_overlap(&a.scissors, &b.scissors)
+                {
+                    collisions.push(SortKeyCollision {
+                        sortkey: a.sortkey,
+                        first: a.origin,
+                        second: b.origin,
+                    });
+                }
+            }
+        }
+
+        *self.last.lock().unwrap() = collisions.clone();
+        collisions
+    }
+}
+
+fn collect_operations<B: Backend>(
+    command_buffer: u32,
+    cmdbuf: &CommandBuffer<B>,
+    blocks: &HashMap<usize, BlockTargets>,
+    out: &mut Vec<Operation>,
+) {
+    // origin of the current draw, and its argument blocks
+    let mut draw_origin = None;
+    let mut arguments = Vec::new();
+
+    for (position, cmd) in cmdbuf.iter().enumerate() {
+        let origin = CommandOrigin {
+            command_buffer,
+            position: position as u32,
+            call_site: cmd.call_site,
+        };
+        match cmd.cmd {
+            CommandInner::ClearImageFloat { image, .. }
+            | CommandInner::ClearImageUint { image, .. }
+            | CommandInner::ClearImageInt { image, .. }
+            | CommandInner::ClearDepthStencilImage { image, .. }
+            | CommandInner::Present { image, .. } => out.push(Operation {
+                sortkey: cmd.sortkey,
+                origin,
+                images: vec![address(image)],
+                scissors: Vec::new(),
+            }),
+            CommandInner::DrawHeader { .. } => {
+                draw_origin = Some(origin);
+                arguments.clear();
+            }
+            CommandInner::SetPipelineArguments { arguments: block } => {
+                arguments.push(address(block))
+            }
+            CommandInner::Draw { .. } | CommandInner::DrawIndexed { .. } => {
+                let mut images = Vec::new();
+                let mut scissors = Vec::new();
+                for &block in arguments.iter() {
+                    collect_targets(block, blocks, &mut images, &mut scissors);
+                }
+                out.push(Operation {
+                    sortkey: cmd.sortkey,
+                    origin: draw_origin.unwrap_or(origin),
+                    images,
+                    scissors,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Collects the render targets of an argument block and of the blocks it inherits from.
+/// The scissors are the ones of the first block that provides some.
+fn collect_targets(
+    block: usize,
+    blocks: &HashMap<usize, BlockTargets>,
+    images: &mut Vec<usize>,
+    scissors: &mut Vec<Scissor>,
+) {
+    if let Some(targets) = blocks.get(&block) {
+        for &inherited in targets.inherited.iter() {
+            collect_targets(inherited, blocks, images, scissors);
+        }
+        images.extend(targets.images.iter().cloned());
+        if scissors.is_empty() {
+            scissors.extend(targets.scissors.iter().cloned());
+        }
+    }
+}
+
+/// Returns whether two sets of scissors may cover the same pixels. An empty set covers the
+/// whole render target.
+fn scissors_overlap(a: &[Scissor], b: &[Scissor]) -> bool {
+    if a.is_empty() || b.is_empty() {
+        return true;
+    }
+    a.iter().any(|sa| {
+        b.iter().any(|sb| match (sa, sb) {
+            (Scissor::Enabled(ra), Scissor::Enabled(rb)) => {
+                let (ax, ay) = (i64::from(ra.x), i64::from(ra.y));
+                let (bx, by) = (i64::from(rb.x), i64::from(rb.y));
+                ax < bx + i64::from(rb.width)
+                    && bx < ax + i64::from(ra.width)
+                    && ay < by + i64::from(rb.height)
+                    && by < ay + i64::from(ra.height)
+            }
+            _ => true,
+        })
+    })
+}
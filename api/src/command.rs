@@ -1,21 +1,104 @@
 use crate::{
+    buffer::{Buffer, BufferTypeless, HostReadback, Pod, StructuredBufferData},
+    collision::CallSite,
+    elision::FullscreenDraw,
     image::{DepthStencilView, Image2dView, RenderTargetView},
-    pipeline::{GraphicsPipeline, IntoArgumentBlock, Signature},
-    swapchain::Swapchain,
+    pass::PassInfo,
+    pipeline::{
+        validate::{validate_dynamic_state, validate_static_viewports},
+        ArgumentBlock, ComputePipeline, GraphicsPipeline, IntoArgumentBlock, Signature,
+    },
+    query::{OcclusionQuery, Predicate},
+    semantics::{ResourceSemantics, SemanticId},
+    swapchain::{Swapchain, SwapchainFrame},
+    typedesc::{FieldsLayout, Layout, LayoutDetails, PrimitiveType, TypeDesc},
+    util::address,
     Arena, Backend,
 };
+use bitflags::bitflags;
 
 /// Represents a command to be executed by the renderer backend.
 ///
 /// Before being sent to the backend, all commands are collected into a single array, and then
-/// sorted accorded to their `sortkey`. This sort is stable,
-/// so if two commands in a command buffer have the same sortkey, the order of insertion is kept.
-/// However, commands with the same sorting key from different command buffers
-/// can end up interleaved.
+/// sorted accorded to their `sortkey`. By default, this sort is stable: commands with the same
+/// sortkey are executed in the order of their command buffers in the submission, and then in
+/// their order of insertion. See [SubmitOptions] to change how ties are broken.
 #[derive(Clone)]
 pub struct Command<'a, B: Backend> {
     pub sortkey: u64,
+    /// Secondary sequence number, assigned when the command buffers are collected for
+    /// submission: the index of the command buffer in the submission in the upper 32 bits,
+    /// and the position of the command in its command buffer in the lower 32 bits.
+    ///
+    /// It is unique among the commands of a frame, and does not depend on the sorting
+    /// algorithm, so it can be used to replay a frame deterministically.
+    pub seq: u64,
     pub cmd: CommandInner<'a, B>,
+    /// Where the command was recorded, if specified (see [CommandBuffer::set_call_site]).
+    pub call_site: Option<CallSite>,
+}
+
+impl<'a, B: Backend> Command<'a, B> {
+    /// Index of the command buffer containing this command in the submission.
+    pub fn command_buffer_index(&self) -> u32 {
+        (self.seq >> 32) as u32
+    }
+
+    /// Position of the command in its command buffer.
+    pub fn position(&self) -> u32 {
+        self.seq as u32
+    }
+}
+
+/// Conversion of the color values of an image when it is presented to a swapchain.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PresentEncoding {
+    /// Conversion configured in the backend (usually, linear values are encoded to sRGB if the
+    /// swapchain has a sRGB color space).
+    Default,
+    /// The image contains linear values, which are encoded to sRGB if the swapchain images have
+    /// a sRGB format.
+    Srgb,
+    /// The values are copied as-is, e.g. for images that already contain sRGB-encoded values.
+    Raw,
+}
+
+/// How commands with the same sortkey are ordered relative to each other.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TieBreak {
+    /// Only the sortkey is compared. The order of commands with the same sortkey is the
+    /// order of submission if `SubmitOptions::stable_sort` is set, and unspecified otherwise.
+    None,
+    /// Commands with the same sortkey are ordered by command buffer (in the order of the
+    /// submission), then by order of insertion.
+    CommandBuffer,
+    /// Commands with the same sortkey are ordered by their position in their command buffer,
+    /// then by command buffer. This interleaves the commands of different command buffers.
+    Interleaved,
+}
+
+/// Options controlling the sorting of commands on frame submission.
+///
+/// See [Api::submit_frame_with_options](crate::Api::submit_frame_with_options).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SubmitOptions {
+    /// Whether to use a stable sort. An unstable sort may be faster, and is deterministic
+    /// anyway if `tie_break` is not `TieBreak::None`.
+    pub stable_sort: bool,
+    pub tie_break: TieBreak,
+}
+
+impl SubmitOptions {
+    pub const DEFAULT: SubmitOptions = SubmitOptions {
+        stable_sort: true,
+        tie_break: TieBreak::None,
+    };
+}
+
+impl Default for SubmitOptions {
+    fn default() -> Self {
+        SubmitOptions::DEFAULT
+    }
 }
 
 /// Parameters for non-indexed draw commands.
@@ -49,32 +132,196 @@ pub struct DrawIndexedParams {
     pub first_instance: u32,
 }
 
+/// Layout of the parameters of an indirect dispatch command in a buffer.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DispatchIndirectCommand {
+    pub group_count_x: u32,
+    pub group_count_y: u32,
+    pub group_count_z: u32,
+}
+
+// matches `uint group_count_x, group_count_y, group_count_z;` in a storage buffer
+unsafe impl StructuredBufferData for DispatchIndirectCommand {
+    const TYPE: TypeDesc<'static> = TypeDesc::Struct {
+        fields: &[
+            &TypeDesc::Primitive(PrimitiveType::UnsignedInt),
+            &TypeDesc::Primitive(PrimitiveType::UnsignedInt),
+            &TypeDesc::Primitive(PrimitiveType::UnsignedInt),
+        ],
+    };
+    const LAYOUT: Layout<'static> = Layout {
+        align: 4,
+        size: 12,
+        details: LayoutDetails::Struct(FieldsLayout {
+            offsets: &[0, 4, 8],
+            layouts: &[&u32::LAYOUT, &u32::LAYOUT, &u32::LAYOUT],
+        }),
+    };
+}
+
+bitflags! {
+    /// Kinds of memory accesses, for manual barriers (see [CommandBuffer::barrier]).
+    pub struct AccessFlags: u32 {
+        /// Reads of vertex attributes from vertex buffers.
+        const VERTEX_ATTRIBUTE_READ = (1 << 0);
+        /// Reads of indices from index buffers.
+        const INDEX_READ = (1 << 1);
+        /// Reads of uniform (constant) buffers by shaders.
+        const UNIFORM_READ = (1 << 2);
+        /// Reads of sampled images by shaders.
+        const TEXTURE_READ = (1 << 3);
+        /// Reads and writes of storage images by shaders.
+        const STORAGE_IMAGE = (1 << 4);
+        /// Reads and writes of storage buffers by shaders.
+        const STORAGE_BUFFER = (1 << 5);
+        /// Reads of indirect draw and dispatch parameters.
+        const INDIRECT_COMMAND_READ = (1 << 6);
+        /// Copies and host readbacks of buffers and images.
+        const TRANSFER = (1 << 7);
+        /// Reads and writes of render targets.
+        const RENDER_TARGET = (1 << 8);
+        const ALL = Self::VERTEX_ATTRIBUTE_READ.bits | Self::INDEX_READ.bits
+            | Self::UNIFORM_READ.bits | Self::TEXTURE_READ.bits | Self::STORAGE_IMAGE.bits
+            | Self::STORAGE_BUFFER.bits | Self::INDIRECT_COMMAND_READ.bits | Self::TRANSFER.bits
+            | Self::RENDER_TARGET.bits;
+    }
+}
+
+/// Describes a manual memory barrier. See [CommandBuffer::barrier].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BarrierDesc {
+    /// Accesses, by commands after the barrier, that must observe the writes made by shaders
+    /// (to storage buffers and images) in commands before the barrier.
+    pub dst_access: AccessFlags,
+}
+
+impl BarrierDesc {
+    /// Makes shader writes visible to all kinds of accesses.
+    pub const ALL: BarrierDesc = BarrierDesc {
+        dst_access: AccessFlags::ALL,
+    };
+
+    /// Makes writes to storage buffers visible to shaders that read them as storage buffers.
+    pub const STORAGE_BUFFER_READ_AFTER_WRITE: BarrierDesc = BarrierDesc {
+        dst_access: AccessFlags::STORAGE_BUFFER,
+    };
+}
+
+/// Value to clear a color render target with. See [CommandBuffer::clear_render_target].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClearColor {
+    Float([f32; 4]),
+    /// For images with an unsigned integer format.
+    Uint([u32; 4]),
+    /// For images with a signed integer format.
+    Int([i32; 4]),
+}
+
+impl<'a> From<&'a [f32; 4]> for ClearColor {
+    fn from(color: &'a [f32; 4]) -> Self {
+        ClearColor::Float(*color)
+    }
+}
+
+impl<'a> From<&'a [u32; 4]> for ClearColor {
+    fn from(color: &'a [u32; 4]) -> Self {
+        ClearColor::Uint(*color)
+    }
+}
+
+impl<'a> From<&'a [i32; 4]> for ClearColor {
+    fn from(color: &'a [i32; 4]) -> Self {
+        ClearColor::Int(*color)
+    }
+}
+
+/// Value to clear an image with at the start of its alias scope.
+/// See [Arena::create_image_cleared](crate::Arena::create_image_cleared).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClearValue {
+    /// For images with a color format.
+    Color(ClearColor),
+    /// For images with a depth or depth-stencil format. The stencil is left untouched
+    /// if `stencil` is `None`.
+    DepthStencil { depth: f32, stencil: Option<u8> },
+}
+
 #[derive(derivative::Derivative)]
 #[derivative(Clone(bound = ""))]
 pub enum CommandInner<'a, B: Backend> {
     // MAIN (LEAD-IN) COMMANDS ---------------------------------------------------------------------
-    PipelineBarrier {},
+    PipelineBarrier {
+        desc: BarrierDesc,
+    },
     ClearImageFloat {
         image: &'a B::Image,
         color: [f32; 4],
     },
+    /// Clears an image with an unsigned integer format.
+    ClearImageUint {
+        image: &'a B::Image,
+        color: [u32; 4],
+    },
+    /// Clears an image with a signed integer format.
+    ClearImageInt {
+        image: &'a B::Image,
+        color: [i32; 4],
+    },
     ClearDepthStencilImage {
         image: &'a B::Image,
         depth: f32,
         stencil: Option<u8>,
     },
+    /// Copies the contents of `buffer` into host memory. The size of the copy is the size of
+    /// the destination.
+    CopyBufferToHost {
+        buffer: &'a B::Buffer,
+        readback: &'a B::HostReadback,
+    },
+    /// Copies the first mipmap level of a 2D color image into host memory, as tightly packed
+    /// 8-bit RGBA texels, first scanline first. The size of the destination must be the size
+    /// of the image.
+    CopyImageToHost {
+        image: &'a B::Image,
+        readback: &'a B::HostReadback,
+    },
     Present {
         image: &'a B::Image,
         swapchain: &'a B::Swapchain,
+        encoding: PresentEncoding,
     },
     DrawHeader {
         pipeline: &'a B::GraphicsPipeline,
     },
+    DispatchHeader {
+        pipeline: &'a B::ComputePipeline,
+    },
+    BeginQuery {
+        query: &'a B::Query,
+    },
+    EndQuery {
+        query: &'a B::Query,
+    },
+    /// Subsequent draws are skipped if the predicate is false, until the next `EndConditional`.
+    BeginConditional {
+        predicate: Predicate<'a, B>,
+    },
+    EndConditional {},
+    /// Opens a debug group for graphics debuggers, closed by the next `EndDebugGroup`.
+    BeginDebugGroup {
+        name: &'static str,
+    },
+    EndDebugGroup {},
 
     // STATE CHANGE COMMANDS -----------------------------------------------------------------------
     SetPipelineArguments {
         arguments: &'a B::ArgumentBlock,
     },
+    /// Sets the line width of subsequent draws that use a pipeline with a dynamic line width.
+    SetLineWidth {
+        width: f32,
+    },
 
     // DRAW (LEAD-OUT) COMMANDS --------------------------------------------------------------------
     Draw {
@@ -90,11 +337,63 @@ pub enum CommandInner<'a, B: Backend> {
         vertex_offset: i32,
         first_instance: u32,
     },
+    Dispatch {
+        group_count_x: u32,
+        group_count_y: u32,
+        group_count_z: u32,
+    },
+    /// Dispatch with parameters read from a [DispatchIndirectCommand] in `buffer` at `offset`.
+    DispatchIndirect {
+        buffer: &'a B::Buffer,
+        offset: u64,
+    },
+}
+
+/// A command that references an image by semantic, resolved on submission.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""))]
+enum SemanticCommand<'a, B: Backend> {
+    ClearImageFloat {
+        image: SemanticId,
+        color: [f32; 4],
+    },
+    ClearDepthStencilImage {
+        image: SemanticId,
+        depth: f32,
+        stencil: Option<u8>,
+    },
+    Present {
+        image: SemanticId,
+        swapchain: &'a B::Swapchain,
+        encoding: PresentEncoding,
+    },
+}
+
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""))]
+struct DeferredCommand<'a, B: Backend> {
+    /// Index in the command list before which the resolved command is inserted.
+    position: usize,
+    sortkey: u64,
+    cmd: SemanticCommand<'a, B>,
+    call_site: Option<CallSite>,
 }
 
 /// Command buffers contain a list of commands.
 pub struct CommandBuffer<'a, B: Backend> {
     commands: Vec<Command<'a, B>>,
+    /// Commands referencing resources by semantic.
+    deferred: Vec<DeferredCommand<'a, B>>,
+    /// Combined with the sortkeys of all commands (see [CommandBuffer::with_base_key]).
+    base_key: u64,
+    /// Open passes, with the base key before each one (see [CommandBuffer::begin_pass]).
+    passes: Vec<(PassInfo, u64)>,
+    /// Call site attached to the commands (see [CommandBuffer::set_call_site]).
+    call_site: Option<CallSite>,
+    /// Draws recorded with [CommandBuffer::draw_fullscreen], for the elision of redundant clears.
+    fullscreen_draws: Vec<FullscreenDraw>,
+    /// Sort key of the open conditional rendering block (see [CommandBuffer::begin_conditional]).
+    conditional: Option<u64>,
 }
 
 /// API exposed by command buffers.
@@ -103,17 +402,176 @@ impl<'a, B: Backend> CommandBuffer<'a, B> {
     pub(super) fn new() -> CommandBuffer<'a, B> {
         CommandBuffer {
             commands: Vec::new(),
+            deferred: Vec::new(),
+            base_key: 0,
+            passes: Vec::new(),
+            call_site: None,
+            fullscreen_draws: Vec::new(),
+            conditional: None,
+        }
+    }
+
+    /// Attaches a call site to the commands recorded after this call, reported by the
+    /// [sort key collision checks](crate::collision):
+    /// ```ignore
+    /// cmdbuf.set_call_site(call_site!());
+    /// ```
+    /// The call site is recorded explicitly because `#[track_caller]` is not available.
+    pub fn set_call_site(&mut self, call_site: CallSite) {
+        self.call_site = Some(call_site);
+    }
+
+    /// Sets the base sortkey of the command buffer.
+    ///
+    /// The sortkey of each command recorded after this call is `base_key | sortkey`. This lets
+    /// subsystems record their commands with small local sortkeys, while the code that
+    /// orchestrates the frame decides where they are placed relative to each other:
+    /// ```ignore
+    /// let mut shadows = arena.create_command_buffer().with_base_key(0x1_0000);
+    /// let mut opaque = arena.create_command_buffer().with_base_key(0x2_0000);
+    /// shadow_renderer.render(&mut shadows); // sortkeys 0x0..0xFFFF
+    /// mesh_renderer.render(&mut opaque);    // sortkeys 0x0..0xFFFF
+    /// ```
+    /// The local sortkeys should not overlap the bits of the base key.
+    pub fn with_base_key(mut self, base_key: u64) -> CommandBuffer<'a, B> {
+        self.base_key = base_key;
+        self
+    }
+
+    /// Returns the base sortkey of the command buffer (0 by default).
+    pub fn base_key(&self) -> u64 {
+        self.base_key
+    }
+
+    /// Creates a command buffer without a renderer, for unit-testing code that records commands.
+    ///
+    /// See [CommandCapture::from_command_buffers](crate::capture::CommandCapture::from_command_buffers).
+    #[cfg(feature = "capture")]
+    pub fn new_detached() -> CommandBuffer<'a, B> {
+        CommandBuffer::new()
+    }
+
+    /// Checks that a local sort key is inside the range of the current pass, if any.
+    fn check_pass_key(&self, sortkey: u64) {
+        if let Some(&(ref pass, _)) = self.passes.last() {
+            debug_assert!(
+                sortkey < pass.key_count,
+                "sort key {:#x} outside of pass `{}` ({} keys)",
+                sortkey,
+                pass.name,
+                pass.key_count
+            );
         }
     }
 
     fn push_command(&mut self, sortkey: u64, cmd: CommandInner<'a, B>) {
-        self.commands.push(Command { cmd, sortkey })
+        self.check_pass_key(sortkey);
+        self.commands.push(Command {
+            cmd,
+            sortkey: self.base_key | sortkey,
+            seq: 0,
+            call_site: self.call_site,
+        })
+    }
+
+    fn push_deferred(&mut self, sortkey: u64, cmd: SemanticCommand<'a, B>) {
+        self.check_pass_key(sortkey);
+        self.deferred.push(DeferredCommand {
+            position: self.commands.len(),
+            sortkey: self.base_key | sortkey,
+            cmd,
+            call_site: self.call_site,
+        })
     }
 
+    /// Iterates over the commands in this buffer.
+    ///
+    /// Commands referencing resources by semantic are not returned until
+    /// [resolve_semantics](CommandBuffer::resolve_semantics) is called.
     pub fn iter(&self) -> impl Iterator<Item = &Command<'a, B>> {
         self.commands.iter()
     }
 
+    pub(crate) fn fullscreen_draws(&self) -> &[FullscreenDraw] {
+        &self.fullscreen_draws
+    }
+
+    /// Replaces all references to resources by semantic with the resources registered in
+    /// `semantics`.
+    ///
+    /// Returns the first semantic that could not be resolved, in which case the command buffer
+    /// is left unchanged.
+    pub fn resolve_semantics(
+        &mut self,
+        semantics: &ResourceSemantics<'a, B>,
+    ) -> Result<(), SemanticId> {
+        if self.deferred.is_empty() {
+            return Ok(());
+        }
+
+        let mut resolved = Vec::with_capacity(self.deferred.len());
+        for d in self.deferred.iter() {
+            let image = match d.cmd {
+                SemanticCommand::ClearImageFloat { image, .. }
+                | SemanticCommand::ClearDepthStencilImage { image, .. }
+                | SemanticCommand::Present { image, .. } => {
+                    semantics.image(image).ok_or(image)?.image
+                }
+            };
+            let cmd = match d.cmd {
+                SemanticCommand::ClearImageFloat { color, .. } => {
+                    CommandInner::ClearImageFloat { image, color }
+                }
+                SemanticCommand::ClearDepthStencilImage { depth, stencil, .. } => {
+                    CommandInner::ClearDepthStencilImage {
+                        image,
+                        depth,
+                        stencil,
+                    }
+                }
+                SemanticCommand::Present {
+                    swapchain,
+                    encoding,
+                    ..
+                } => CommandInner::Present {
+                    image,
+                    swapchain,
+                    encoding,
+                },
+            };
+            resolved.push((
+                d.position,
+                Command {
+                    sortkey: d.sortkey,
+                    seq: 0,
+                    cmd,
+                    call_site: d.call_site,
+                },
+            ));
+        }
+
+        let mut commands = Vec::with_capacity(self.commands.len() + resolved.len());
+        let mut resolved = resolved.into_iter().peekable();
+        let mut fullscreen_draws = self.fullscreen_draws.iter_mut().peekable();
+        for (i, cmd) in self.commands.drain(..).enumerate() {
+            while resolved.peek().map_or(false, |&(pos, _)| pos == i) {
+                commands.push(resolved.next().unwrap().1);
+            }
+            // the draws move with the inserted commands
+            while fullscreen_draws
+                .peek()
+                .map_or(false, |draw| draw.position == i)
+            {
+                fullscreen_draws.next().unwrap().position = commands.len();
+            }
+            commands.push(cmd);
+        }
+        commands.extend(resolved.map(|(_, c)| c));
+        self.commands = commands;
+        self.deferred.clear();
+        Ok(())
+    }
+
     //----------------------------------------------------------------------------------------------
     // Copy
 
@@ -129,25 +587,45 @@ impl<'a, B: Backend> CommandBuffer<'a, B> {
         unimplemented!()
     }*/
 
+    //----------------------------------------------------------------------------------------------
+    // Barriers
+
+    /// Inserts a memory barrier: shader writes to storage buffers and images made by commands
+    /// sorted before `sortkey` are visible to the accesses in `desc.dst_access` made by commands
+    /// sorted after it.
+    ///
+    /// Barriers are normally inserted automatically. This is an escape hatch for accesses that
+    /// the renderer does not track, such as compute shaders reading a storage buffer written by
+    /// a previous dispatch through a raw buffer binding. Commands with the same sortkey as the
+    /// barrier are ordered relative to it by their insertion order.
+    pub fn barrier(&mut self, sortkey: u64, desc: BarrierDesc) {
+        self.push_command(sortkey, CommandInner::PipelineBarrier { desc })
+    }
+
     //----------------------------------------------------------------------------------------------
     // Clear
 
     /// Clears an image.
     ///
+    /// `color` is either a `&[f32; 4]`, or a `&[u32; 4]` or `&[i32; 4]` for images with an
+    /// integer format (e.g. `R32_UINT` images of object IDs). Images with an integer format
+    /// cleared with floating-point values are cleared with the components of `color` converted
+    /// to integers.
+    ///
     /// Q: Should it be necessary for the image to be an RTV?
     pub fn clear_render_target(
         &mut self,
         sortkey: u64,
         image: impl Into<RenderTargetView<'a, B>>,
-        color: &[f32; 4],
+        color: impl Into<ClearColor>,
     ) {
-        self.push_command(
-            sortkey,
-            CommandInner::ClearImageFloat {
-                image: image.into().image,
-                color: *color,
-            },
-        )
+        let image = image.into().image;
+        let cmd = match color.into() {
+            ClearColor::Float(color) => CommandInner::ClearImageFloat { image, color },
+            ClearColor::Uint(color) => CommandInner::ClearImageUint { image, color },
+            ClearColor::Int(color) => CommandInner::ClearImageInt { image, color },
+        };
+        self.push_command(sortkey, cmd)
     }
 
     /// Clears an image.
@@ -168,6 +646,161 @@ impl<'a, B: Backend> CommandBuffer<'a, B> {
         )
     }
 
+    /// Clears the image registered under the given semantic.
+    ///
+    /// The image is looked up when the frame is submitted.
+    pub fn clear_semantic_render_target(
+        &mut self,
+        sortkey: u64,
+        semantic: impl Into<SemanticId>,
+        color: &[f32; 4],
+    ) {
+        self.push_deferred(
+            sortkey,
+            SemanticCommand::ClearImageFloat {
+                image: semantic.into(),
+                color: *color,
+            },
+        )
+    }
+
+    /// Clears the depth-stencil image registered under the given semantic.
+    ///
+    /// The image is looked up when the frame is submitted.
+    pub fn clear_semantic_depth_stencil(
+        &mut self,
+        sortkey: u64,
+        semantic: impl Into<SemanticId>,
+        depth: f32,
+        stencil: Option<u8>,
+    ) {
+        self.push_deferred(
+            sortkey,
+            SemanticCommand::ClearDepthStencilImage {
+                image: semantic.into(),
+                depth,
+                stencil,
+            },
+        )
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Queries & conditional rendering
+
+    /// Starts an occlusion query.
+    ///
+    /// The query records whether any sample of the draws with sortkeys between this command
+    /// and the matching [end_occlusion_query](CommandBuffer::end_occlusion_query)
+    /// passed the depth and stencil tests.
+    pub fn begin_occlusion_query(&mut self, sortkey: u64, query: OcclusionQuery<'a, B>) {
+        self.push_command(sortkey, CommandInner::BeginQuery { query: query.0 })
+    }
+
+    /// Ends an occlusion query.
+    pub fn end_occlusion_query(&mut self, sortkey: u64, query: OcclusionQuery<'a, B>) {
+        self.push_command(sortkey, CommandInner::EndQuery { query: query.0 })
+    }
+
+    /// Starts a conditional rendering block: the draws between this command and the matching
+    /// [end_conditional](CommandBuffer::end_conditional) are skipped by the GPU if the predicate
+    /// is false, without reading back the result on the CPU.
+    ///
+    /// If the backend does not support the kind of predicate, the draws are executed
+    /// unconditionally.
+    ///
+    /// Panics if a conditional block is already open: conditional blocks cannot be nested.
+    pub fn begin_conditional(&mut self, sortkey: u64, predicate: impl Into<Predicate<'a, B>>) {
+        if let Some(open) = self.conditional {
+            panic!(
+                "conditional rendering block at sort key {:#x} nested in the block opened at sort key {:#x}",
+                sortkey, open
+            );
+        }
+        self.conditional = Some(sortkey);
+        self.push_command(
+            sortkey,
+            CommandInner::BeginConditional {
+                predicate: predicate.into(),
+            },
+        )
+    }
+
+    /// Ends a conditional rendering block.
+    ///
+    /// Panics if no conditional block is open.
+    pub fn end_conditional(&mut self, sortkey: u64) {
+        if self.conditional.take().is_none() {
+            panic!(
+                "end_conditional at sort key {:#x} without a matching begin_conditional",
+                sortkey
+            );
+        }
+        self.push_command(sortkey, CommandInner::EndConditional {})
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Debug groups & passes
+
+    /// Opens a debug group, shown by graphics debuggers around the commands sorted between
+    /// this command and the matching [end_debug_group](CommandBuffer::end_debug_group).
+    ///
+    /// Backends without debug groups ignore it.
+    pub fn begin_debug_group(&mut self, sortkey: u64, name: &'static str) {
+        self.push_command(sortkey, CommandInner::BeginDebugGroup { name })
+    }
+
+    /// Closes a debug group.
+    pub fn end_debug_group(&mut self, sortkey: u64) {
+        self.push_command(sortkey, CommandInner::EndDebugGroup {})
+    }
+
+    /// Starts recording the commands of a pass: until the matching
+    /// [end_pass](CommandBuffer::end_pass), sort keys are local to the range of the pass
+    /// (the base key of the command buffer is combined with the first key of the pass), and
+    /// debug builds check that they are inside the range.
+    ///
+    /// Opens a debug group named after the pass at its first sort key. See also
+    /// [render_pass!](crate::render_pass).
+    pub fn begin_pass(&mut self, pass: PassInfo) {
+        let previous = self.base_key;
+        self.check_pass_key(pass.base_key + pass.key_count - 1);
+        self.base_key |= pass.base_key;
+        self.passes.push((pass, previous));
+        self.begin_debug_group(0, pass.name);
+    }
+
+    /// Ends the current pass, closing its debug group at the last sort key of the pass.
+    ///
+    /// Panics if there is no current pass.
+    pub fn end_pass(&mut self) {
+        let key_count = self
+            .passes
+            .last()
+            .expect("end_pass called without begin_pass")
+            .0
+            .key_count;
+        self.end_debug_group(key_count - 1);
+        let (_, previous) = self.passes.pop().unwrap();
+        self.base_key = previous;
+    }
+
+    /// Returns the current pass (see [begin_pass](CommandBuffer::begin_pass)).
+    pub fn current_pass(&self) -> Option<&PassInfo> {
+        self.passes.last().map(|p| &p.0)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Dynamic state
+
+    /// Sets the width of lines for subsequent draws (in sort order) with pipelines
+    /// created with [LineWidth::Dynamic](crate::pipeline::LineWidth::Dynamic).
+    ///
+    /// Pipelines with a static line width are not affected. The width stays in effect
+    /// until the next `set_line_width` command.
+    pub fn set_line_width(&mut self, sortkey: u64, width: f32) {
+        self.push_command(sortkey, CommandInner::SetLineWidth { width })
+    }
+
     //----------------------------------------------------------------------------------------------
     // Draw
     fn set_pipeline(
@@ -180,6 +813,44 @@ impl<'a, B: Backend> CommandBuffer<'a, B> {
         self.push_command(sortkey, CommandInner::SetPipelineArguments { arguments })
     }
 
+    /// Checks that the signature of the arguments provides the dynamic states expected by the
+    /// pipeline. Pipelines are validated against their signature on creation, so this only
+    /// catches signatures swapped afterwards: it is skipped in release builds.
+    fn check_dynamic_state<S: Signature<'a, B>>(
+        pipeline: &GraphicsPipeline<'a, B, S>,
+        signature: &S,
+    ) {
+        if cfg!(debug_assertions) {
+            let mut errors = Vec::new();
+            validate_dynamic_state(signature.description(), pipeline.dynamic_state, &mut errors);
+            if let Some(e) = errors.first() {
+                panic!("invalid arguments for draw: {}", e);
+            }
+        }
+    }
+
+    /// Checks that the static viewports of the pipeline are inside the render targets of the
+    /// arguments. Skipped in release builds, where the size of the render targets is not recorded.
+    fn check_viewports<S: Signature<'a, B>>(
+        arena: &Arena<B>,
+        pipeline: &GraphicsPipeline<'a, B, S>,
+        arguments: &ArgumentBlock<'a, B, S>,
+    ) {
+        if cfg!(debug_assertions) && !pipeline.viewports.is_empty() {
+            let size = arena
+                .renderer
+                .target_sizes
+                .size(address(arguments.arguments));
+            if let Some(size) = size {
+                let mut errors = Vec::new();
+                validate_static_viewports(pipeline.viewports, size, &mut errors);
+                if let Some(e) = errors.first() {
+                    panic!("invalid arguments for draw: {}", e);
+                }
+            }
+        }
+    }
+
     // S: Signature<'a,B> P: Into<ArgumentBlock<'a,B,S>>
     pub fn draw<S: Signature<'a, B>, P: IntoArgumentBlock<'a, B, S>>(
         &mut self,
@@ -190,6 +861,8 @@ impl<'a, B: Backend> CommandBuffer<'a, B> {
         params: DrawParams,
     ) {
         let arguments = arguments.into_block(pipeline.signature, arena);
+        Self::check_dynamic_state(&pipeline, &arguments.signature);
+        Self::check_viewports(arena, &pipeline, &arguments);
         self.set_pipeline(sortkey, pipeline.inner, arguments.arguments);
         self.push_command(
             sortkey,
@@ -202,6 +875,40 @@ impl<'a, B: Backend> CommandBuffer<'a, B> {
         );
     }
 
+    /// Same as [draw](CommandBuffer::draw), but declares that the draw covers the whole render
+    /// targets of the arguments: its primitives cover the viewports, and the viewports and
+    /// scissors cover the render targets (e.g. a fullscreen quad).
+    ///
+    /// With the [elision of redundant clears](crate::elision) enabled, clears of the render
+    /// targets that are overwritten by this draw are removed, if the pipeline overwrites its
+    /// render targets (see [GraphicsPipeline::overwrites_render_targets]).
+    pub fn draw_fullscreen<S: Signature<'a, B>, P: IntoArgumentBlock<'a, B, S>>(
+        &mut self,
+        sortkey: u64,
+        arena: &'a Arena<B>,
+        pipeline: GraphicsPipeline<'a, B, S>,
+        arguments: P,
+        params: DrawParams,
+    ) {
+        let overwrites_render_targets = pipeline.overwrites_render_targets;
+        self.draw(sortkey, arena, pipeline, arguments, params);
+
+        let elision = &arena.renderer.clear_elision;
+        if overwrites_render_targets && elision.is_enabled() {
+            // the draw command follows the arguments
+            let position = self.commands.len() - 1;
+            if let CommandInner::SetPipelineArguments { arguments } =
+                self.commands[position - 1].cmd
+            {
+                let images = elision.render_targets(address(arguments));
+                if !images.is_empty() {
+                    self.fullscreen_draws
+                        .push(FullscreenDraw { position, images });
+                }
+            }
+        }
+    }
+
     pub fn draw_indexed<S: Signature<'a, B>, P: IntoArgumentBlock<'a, B, S>>(
         &mut self,
         sortkey: u64,
@@ -211,6 +918,8 @@ impl<'a, B: Backend> CommandBuffer<'a, B> {
         params: DrawIndexedParams,
     ) {
         let arguments = arguments.into_block(pipeline.signature, arena);
+        Self::check_dynamic_state(&pipeline, &arguments.signature);
+        Self::check_viewports(arena, &pipeline, &arguments);
         self.set_pipeline(sortkey, pipeline.inner, arguments.arguments);
         self.push_command(
             sortkey,
@@ -224,24 +933,173 @@ impl<'a, B: Backend> CommandBuffer<'a, B> {
         );
     }
 
+    //----------------------------------------------------------------------------------------------
+    // Compute
+    fn set_compute_pipeline(
+        &mut self,
+        sortkey: u64,
+        pipeline: &'a B::ComputePipeline,
+        arguments: &'a B::ArgumentBlock,
+    ) {
+        self.push_command(sortkey, CommandInner::DispatchHeader { pipeline });
+        self.push_command(sortkey, CommandInner::SetPipelineArguments { arguments })
+    }
+
+    /// Dispatches a compute shader with the specified number of workgroups.
+    pub fn dispatch<S: Signature<'a, B>, P: IntoArgumentBlock<'a, B, S>>(
+        &mut self,
+        sortkey: u64,
+        arena: &'a Arena<B>,
+        pipeline: ComputePipeline<'a, B, S>,
+        arguments: P,
+        group_count: (u32, u32, u32),
+    ) {
+        let arguments = arguments.into_block(pipeline.signature, arena);
+        self.set_compute_pipeline(sortkey, pipeline.inner, arguments.arguments);
+        self.push_command(
+            sortkey,
+            CommandInner::Dispatch {
+                group_count_x: group_count.0,
+                group_count_y: group_count.1,
+                group_count_z: group_count.2,
+            },
+        );
+    }
+
+    /// Dispatches a compute shader with the number of workgroups read from a
+    /// [DispatchIndirectCommand] stored in `buffer` at byte offset `offset`.
+    ///
+    /// The parameters can be written by a previous compute command.
+    pub fn dispatch_indirect<S: Signature<'a, B>, P: IntoArgumentBlock<'a, B, S>>(
+        &mut self,
+        sortkey: u64,
+        arena: &'a Arena<B>,
+        pipeline: ComputePipeline<'a, B, S>,
+        arguments: P,
+        buffer: impl Into<BufferTypeless<'a, B>>,
+        offset: u64,
+    ) {
+        let arguments = arguments.into_block(pipeline.signature, arena);
+        self.set_compute_pipeline(sortkey, pipeline.inner, arguments.arguments);
+        self.push_command(
+            sortkey,
+            CommandInner::DispatchIndirect {
+                buffer: buffer.into().0,
+                offset,
+            },
+        );
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Readback
+
+    /// Copies the elements at the beginning of `buffer` into the host memory referenced by
+    /// `readback`, which should not be larger than the buffer.
+    ///
+    /// The copy is done during `submit_frame`: the destination slice can be read after
+    /// `submit_frame` returns, once the borrow has ended.
+    pub fn copy_buffer_to_host<T: Pod>(
+        &mut self,
+        sortkey: u64,
+        buffer: Buffer<'a, B, [T]>,
+        readback: HostReadback<'a, B, T>,
+    ) {
+        self.push_command(
+            sortkey,
+            CommandInner::CopyBufferToHost {
+                buffer: buffer.0,
+                readback: readback.0,
+            },
+        );
+    }
+
+    /// Copies the first mipmap level of a 2D color image into the host memory referenced by
+    /// `readback`, as tightly packed 8-bit RGBA texels, from the first scanline (the top row
+    /// of presented images) to the last.
+    ///
+    /// The destination must hold exactly `4 * width * height` bytes. The texels are converted
+    /// to 8-bit unsigned normalized values without color space conversion: the texels of
+    /// images in sRGB formats are copied as they are stored.
+    ///
+    /// As with [copy_buffer_to_host](CommandBuffer::copy_buffer_to_host), the copy is done
+    /// during `submit_frame`.
+    pub fn copy_image_to_host(
+        &mut self,
+        sortkey: u64,
+        image: impl Into<Image2dView<'a, B>>,
+        readback: HostReadback<'a, B, u8>,
+    ) {
+        self.push_command(
+            sortkey,
+            CommandInner::CopyImageToHost {
+                image: image.into().image,
+                readback: readback.0,
+            },
+        );
+    }
+
     //----------------------------------------------------------------------------------------------
     // Present
 
-    /// Presents the specified image to the swapchain.
+    /// Presents an image acquired with [Swapchain::acquire] to its swapchain.
+    pub fn present(&mut self, sortkey: u64, frame: SwapchainFrame<'a, B>) {
+        self.push_command(
+            sortkey,
+            CommandInner::Present {
+                image: frame.image,
+                swapchain: frame.swapchain,
+                encoding: PresentEncoding::Default,
+            },
+        )
+    }
+
+    /// Copies the specified image to the swapchain and presents it.
     /// Might incur a copy / blit or format conversion if necessary.
     ///
     /// Q: What type should `image` be? Need a 2D view of an image which supports transfer source.
-    pub fn present(
+    pub fn present_image(
         &mut self,
         sortkey: u64,
         image: impl Into<Image2dView<'a, B>>,
         swapchain: Swapchain<'a, B>,
+    ) {
+        self.present_image_with_encoding(sortkey, image, swapchain, PresentEncoding::Default)
+    }
+
+    /// Same as [present_image](CommandBuffer::present_image), but overrides the conversion
+    /// of the color values of the image configured in the backend.
+    pub fn present_image_with_encoding(
+        &mut self,
+        sortkey: u64,
+        image: impl Into<Image2dView<'a, B>>,
+        swapchain: Swapchain<'a, B>,
+        encoding: PresentEncoding,
     ) {
         self.push_command(
             sortkey,
             CommandInner::Present {
                 image: image.into().image,
                 swapchain: swapchain.0,
+                encoding,
+            },
+        )
+    }
+
+    /// Presents the image registered under the given semantic to the swapchain.
+    ///
+    /// The image is looked up when the frame is submitted.
+    pub fn present_semantic(
+        &mut self,
+        sortkey: u64,
+        semantic: impl Into<SemanticId>,
+        swapchain: Swapchain<'a, B>,
+    ) {
+        self.push_deferred(
+            sortkey,
+            SemanticCommand::Present {
+                image: semantic.into(),
+                swapchain: swapchain.0,
+                encoding: PresentEncoding::Default,
             },
         )
     }
@@ -250,17 +1108,45 @@ impl<'a, B: Backend> CommandBuffer<'a, B> {
 /// TODO optimize (radix sort, dense command buffer layout, separate index map)
 pub fn sort_command_buffers<'a, B: Backend>(
     cmdbufs: impl IntoIterator<Item = CommandBuffer<'a, B>>,
+) -> Vec<Command<'a, B>> {
+    sort_command_buffers_with_options(cmdbufs, &SubmitOptions::DEFAULT)
+}
+
+/// Collects the commands of all command buffers and sorts them according to `options`.
+///
+/// Assigns the sequence numbers of the commands (see [Command::seq]).
+pub fn sort_command_buffers_with_options<'a, B: Backend>(
+    cmdbufs: impl IntoIterator<Item = CommandBuffer<'a, B>>,
+    options: &SubmitOptions,
 ) -> Vec<Command<'a, B>> {
     let mut fused = Vec::new();
-    //let mut sortkeys = Vec::new();
-    //let mut i: usize = 0;
-    for cmdbuf in cmdbufs.into_iter() {
-        for cmd in cmdbuf.commands.iter() {
-            fused.push(cmd.clone());
-            //sortkeys.push(cmd.sortkey);
+    for (i_cmdbuf, cmdbuf) in cmdbufs.into_iter().enumerate() {
+        assert!(
+            cmdbuf.deferred.is_empty(),
+            "command buffer references resources by semantic: use `submit_frame_with_semantics`"
+        );
+        for (i_cmd, cmd) in cmdbuf.commands.iter().enumerate() {
+            let mut cmd = cmd.clone();
+            cmd.seq = (i_cmdbuf as u64) << 32 | i_cmd as u64;
+            fused.push(cmd);
         }
     }
 
-    fused.sort_by(|cmd_a, cmd_b| cmd_a.sortkey.cmp(&cmd_b.sortkey));
+    let compare = |a: &Command<'a, B>, b: &Command<'a, B>| {
+        let ord = a.sortkey.cmp(&b.sortkey);
+        match options.tie_break {
+            TieBreak::None => ord,
+            TieBreak::CommandBuffer => ord.then(a.seq.cmp(&b.seq)),
+            TieBreak::Interleaved => ord
+                .then(a.position().cmp(&b.position()))
+                .then(a.command_buffer_index().cmp(&b.command_buffer_index())),
+        }
+    };
+
+    if options.stable_sort {
+        fused.sort_by(compare);
+    } else {
+        fused.sort_unstable_by(compare);
+    }
     fused
 }
@@ -1,11 +1,23 @@
 //! Descriptors
 use crate::{
-    buffer::BufferData, format::Format, image::SamplerDescription, pipeline::ShaderStageFlags,
-    typedesc::TypeDesc, Backend,
+    buffer::{BufferData, StructuredBufferData},
+    format::Format,
+    image::{FallbackTexture, LodClamp, SamplerDescription},
+    pipeline::ShaderStageFlags,
+    typedesc::TypeDesc,
+    util::{eq_address, hash_address},
+    Arena, Backend,
 };
 use autograph_spirv::layout::Layout;
-use std::marker::PhantomData;
+use std::{
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
 
+/// A reference to host data that is uploaded to the GPU by the backend during frame submission.
+///
+/// It can be used as a constant buffer in argument blocks: this avoids an explicit upload
+/// for uniforms that change every frame.
 #[derive(Copy, Clone, Debug)]
 #[repr(transparent)]
 pub struct HostReference<'a, B: Backend, T: BufferData>(
@@ -13,6 +25,21 @@ pub struct HostReference<'a, B: Backend, T: BufferData>(
     pub(crate) PhantomData<&'a T>,
 );
 
+// hashed by identity: the contents are not hashed
+impl<'a, B: Backend, T: BufferData> Hash for HostReference<'a, B, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.0, state)
+    }
+}
+
+impl<'a, B: Backend, T: BufferData> PartialEq for HostReference<'a, B, T> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.0, other.0)
+    }
+}
+
+impl<'a, B: Backend, T: BufferData> Eq for HostReference<'a, B, T> {}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum ResourceShape {
     R1d,
@@ -61,9 +88,12 @@ pub struct ResourceBinding<'tcx> {
     /// Data format for r/w images & texel buffers.
     /// `Format::UNDEFINED` if not applicable (all other binding types)
     pub data_format: Format,
+    /// Name of the binding, for diagnostics: the name of the variable in the shader if debug
+    /// information is available, or the name of the field for derived `Arguments`.
+    pub name: Option<&'tcx str>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct SubresourceRange {
     pub base_mip_level: u32,
     pub level_count: Option<u32>,
@@ -87,6 +117,13 @@ pub enum Descriptor<'a, B: Backend> {
         subresource: SubresourceRange,
         sampler: SamplerDescription,
     },
+    /// Combined image-sampler, with a clamp of the levels of detail selected by the sampler.
+    TextureSamplerLod {
+        image: &'a B::Image,
+        subresource: SubresourceRange,
+        sampler: SamplerDescription,
+        lod: LodClamp,
+    },
     RwImage {
         image: &'a B::Image,
         subresource: SubresourceRange,
@@ -102,15 +139,17 @@ pub enum Descriptor<'a, B: Backend> {
         size: Option<usize>,
     },
     TexelBuffer {
-        buffer: &'a B::Buffer,
-        offset: usize,
-        size: Option<usize>,
+        view: &'a B::BufferView,
     },
     RwTexelBuffer {
         buffer: &'a B::Buffer,
         offset: usize,
         size: Option<usize>,
     },
+    /// Constant buffer whose contents are uploaded from host memory during frame submission.
+    HostConstantBuffer {
+        reference: &'a B::HostReference,
+    },
     Empty,
 }
 
@@ -126,3 +165,20 @@ pub trait ResourceInterface<'a, B: Backend> {
     const DATA_FORMAT: Format = Format::UNDEFINED;
     fn into_descriptor(self) -> Descriptor<'a, B>;
 }
+
+/// Resources that have a built-in replacement, used for optional descriptors in argument blocks.
+///
+/// See the `fallback` option of `#[derive(Arguments)]`.
+pub trait FallbackResource<'a, B: Backend>: ResourceInterface<'a, B> {
+    /// Returns the built-in resource of the specified kind.
+    fn fallback(arena: &'a Arena<B>, kind: FallbackTexture) -> Self;
+}
+
+impl<'a, B: Backend, T: StructuredBufferData> ResourceInterface<'a, B> for HostReference<'a, B, T> {
+    const TYPE: ResourceBindingType = ResourceBindingType::ConstantBuffer;
+    const DATA_TYPE: Option<&'static TypeDesc<'static>> = Some(&T::TYPE);
+    const DATA_LAYOUT: Option<&'static Layout<'static>> = Some(&T::LAYOUT);
+    fn into_descriptor(self) -> Descriptor<'a, B> {
+        Descriptor::HostConstantBuffer { reference: self.0 }
+    }
+}
@@ -0,0 +1,281 @@
+//! Elision of redundant clears.
+//!
+//! A clear is wasted if the cleared image is overwritten before anything reads it: when the
+//! image is cleared again, or when an opaque draw covering the whole image follows the clear
+//! (e.g. a post-processing pass or a blit into a render target). Subsystems often clear their
+//! targets defensively, without knowing what the rest of the frame does with them.
+//!
+//! When enabled with [Api::set_clear_elision](crate::Api::set_clear_elision), the sorted
+//! commands of each frame are scanned before they are sent to the backend, and these clears
+//! are removed. The number of removed clears is available with
+//! [Api::last_clear_elision_stats](crate::Api::last_clear_elision_stats).
+//!
+//! Draws are seen as covering their render targets only if they are recorded with
+//! [CommandBuffer::draw_fullscreen](crate::command::CommandBuffer::draw_fullscreen), which
+//! declares that the primitives, viewports and scissors of the draw cover the render targets,
+//! and if their pipeline overwrites the render targets according to its state (see
+//! [GraphicsPipelineCreateInfo::overwrites_render_targets](crate::pipeline::GraphicsPipelineCreateInfo::overwrites_render_targets)).
+//! Only the color render targets of argument blocks created while the elision is enabled are
+//! seen, and only if the view covers the first mipmap level and all the array layers of the
+//! image (the part of the image that clears write); depth-stencil clears are only elided by
+//! the clears that follow them.
+//!
+//! The analysis is conservative: the resources read by draws and dispatches are not known,
+//! so any draw or dispatch between a clear and the command that overwrites the image keeps the
+//! clear, as does a covering draw between a begin and an end of conditional rendering.
+//! Draws (including blits) are never removed, because their other effects (storage writes,
+//! queries) are not tracked.
+use crate::{
+    command::{Command, CommandBuffer, CommandInner},
+    descriptor::SubresourceRange,
+    image::Dimensions,
+    traits,
+    util::address,
+    Backend,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+/// Number of clears removed from the last submitted frame.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct ClearElisionStats {
+    /// Clears of color render targets.
+    pub render_target_clears: u32,
+    /// Clears of depth-stencil targets.
+    pub depth_stencil_clears: u32,
+}
+
+impl ClearElisionStats {
+    /// Total number of removed clears.
+    pub fn total(&self) -> u32 {
+        self.render_target_clears + self.depth_stencil_clears
+    }
+}
+
+/// Color render targets of an argument block that cover the part of the image written by clears.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BlockRenderTargets {
+    /// Addresses of the inherited argument blocks.
+    pub(crate) inherited: Vec<usize>,
+    /// Addresses of the images.
+    pub(crate) images: Vec<usize>,
+}
+
+/// A draw recorded with `draw_fullscreen`, whose pipeline overwrites the render targets.
+#[derive(Clone, Debug)]
+pub(crate) struct FullscreenDraw {
+    /// Position of the draw command in its command buffer.
+    pub(crate) position: usize,
+    /// Addresses of the images covered by the draw.
+    pub(crate) images: Vec<usize>,
+}
+
+/// Returns whether a view of an image covers the part of the image written by clears: the first
+/// mipmap level, and all the array layers.
+pub(crate) fn covers_cleared_image<B: Backend>(
+    image: &B::Image,
+    subresource: &SubresourceRange,
+) -> bool {
+    if subresource.base_mip_level != 0 || subresource.base_array_layer != 0 {
+        return false;
+    }
+    match traits::Image::dimensions(image) {
+        Dimensions::Dim2d { array_layers, .. } => subresource
+            .layer_count
+            .map_or(true, |count| count >= array_layers),
+        Dimensions::SwapchainRelative { .. } => true,
+        _ => false,
+    }
+}
+
+/// Returns the images covered by the fullscreen draws of the command buffers of a
+/// submission, by sequence number of the draw commands (see [Command::seq]).
+pub(crate) fn collect_fullscreen_draws<B: Backend>(
+    command_buffers: &[CommandBuffer<B>],
+) -> HashMap<u64, Vec<usize>> {
+    let mut draws = HashMap::new();
+    for (i, cmdbuf) in command_buffers.iter().enumerate() {
+        for draw in cmdbuf.fullscreen_draws() {
+            let seq = (i as u64) << 32 | draw.position as u64;
+            draws.insert(seq, draw.images.clone());
+        }
+    }
+    draws
+}
+
+/// A clear whose result has not been read or overwritten yet.
+#[derive(Copy, Clone, Debug)]
+enum PendingClear {
+    Color { index: usize },
+    DepthStencil { index: usize, stencil: bool },
+}
+
+pub(crate) struct ClearElision {
+    enabled: AtomicBool,
+    /// Render targets of the argument blocks created while the elision is enabled, by address.
+    blocks: Mutex<HashMap<usize, BlockRenderTargets>>,
+    /// Statistics of the last submitted frame.
+    last: Mutex<ClearElisionStats>,
+}
+
+impl ClearElision {
+    pub(crate) fn new() -> ClearElision {
+        ClearElision {
+            enabled: AtomicBool::new(false),
+            blocks: Mutex::new(HashMap::new()),
+            last: Mutex::new(ClearElisionStats::default()),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.blocks.lock().unwrap().clear();
+            *self.last.lock().unwrap() = ClearElisionStats::default();
+        }
+    }
+
+    pub(crate) fn register_block(&self, block: usize, targets: BlockRenderTargets) {
+        self.blocks.lock().unwrap().insert(block, targets);
+    }
+
+    /// Forgets the specified argument blocks (when their arena is dropped).
+    pub(crate) fn forget(&self, blocks: &[usize]) {
+        let mut tracked = self.blocks.lock().unwrap();
+        for block in blocks {
+            tracked.remove(block);
+        }
+    }
+
+    pub(crate) fn last_stats(&self) -> ClearElisionStats {
+        *self.last.lock().unwrap()
+    }
+
+    /// Returns the color render targets of an argument block and of the blocks it inherits from.
+    pub(crate) fn render_targets(&self, block: usize) -> Vec<usize> {
+        let blocks = self.blocks.lock().unwrap();
+        let mut images = Vec::new();
+        collect_render_targets(block, &blocks, &mut images);
+        images
+    }
+
+    /// Removes the redundant clears of the sorted commands of a frame, and keeps the statistics
+    /// as the ones of the last frame.
+    ///
+    /// `fullscreen_draws` are the images covered by the fullscreen draws of the frame
+    /// (see [collect_fullscreen_draws]).
+    pub(crate) fn elide<B: Backend>(
+        &self,
+        commands: &mut Vec<Command<B>>,
+        fullscreen_draws: &HashMap<u64, Vec<usize>>,
+    ) -> ClearElisionStats {
+        let mut stats = ClearElisionStats::default();
+        // indices of the elided commands
+        let mut elided = Vec::new();
+        let mut pending: HashMap<usize, PendingClear> = HashMap::new();
+        let mut conditional_depth = 0u32;
+        for (index, command) in commands.iter().enumerate() {
+            match command.cmd {
+                CommandInner::ClearImageFloat { image, .. }
+                | CommandInner::ClearImageUint { image, .. }
+                | CommandInner::ClearImageInt { image, .. } => {
+                    let clear = PendingClear::Color { index };
+                    let previous = pending.insert(address(image), clear);
+                    // a conditional clear may not be executed: it does not overwrite the
+                    // previous one
+                    if conditional_depth == 0 {
+                        if let Some(PendingClear::Color { index: previous }) = previous {
+                            elided.push(previous);
+                            stats.render_target_clears += 1;
+                        }
+                    }
+                }
+                CommandInner::ClearDepthStencilImage { image, stencil, .. } => {
+                    let clear = PendingClear::DepthStencil {
+                        index,
+                        stencil: stencil.is_some(),
+                    };
+                    let previous = pending.insert(address(image), clear);
+                    if conditional_depth == 0 {
+                        if let Some(PendingClear::DepthStencil {
+                            index: previous,
+                            stencil: previous_stencil,
+                        }) = previous
+                        {
+                            // a depth-only clear does not replace the stencil of the previous
+                            // clear
+                            if stencil.is_some() || !previous_stencil {
+                                elided.push(previous);
+                                stats.depth_stencil_clears += 1;
+                            }
+                        }
+                    }
+                }
+                CommandInner::Present { image, .. }
+                | CommandInner::CopyImageToHost { image, .. } => {
+                    pending.remove(&address(image));
+                }
+                CommandInner::BeginConditional { .. } => conditional_depth += 1,
+                CommandInner::EndConditional {} => {
+                    conditional_depth = conditional_depth.saturating_sub(1)
+                }
+                CommandInner::Draw { .. } | CommandInner::DrawIndexed { .. } => {
+                    if conditional_depth == 0 {
+                        if let Some(images) = fullscreen_draws.get(&command.seq) {
+                            for image in images.iter() {
+                                if let Some(PendingClear::Color { index: previous }) =
+                                    pending.remove(image)
+                                {
+                                    elided.push(previous);
+                                    stats.render_target_clears += 1;
+                                }
+                            }
+                        }
+                    }
+                    // the draw may read the images of the other clears
+                    pending.clear();
+                }
+                CommandInner::Dispatch { .. } | CommandInner::DispatchIndirect { .. } => {
+                    pending.clear();
+                }
+                _ => {}
+            }
+        }
+
+        if !elided.is_empty() {
+            let mut keep = vec![true; commands.len()];
+            for &index in elided.iter() {
+                keep[index] = false;
+            }
+            let mut i = 0;
+            commands.retain(|_| {
+                i += 1;
+                keep[i - 1]
+            });
+        }
+        *self.last.lock().unwrap() = stats;
+        stats
+    }
+}
+
+fn collect_render_targets(
+    block: usize,
+    blocks: &HashMap<usize, BlockRenderTargets>,
+    images: &mut Vec<usize>,
+) {
+    if let Some(targets) = blocks.get(&block) {
+        for &inherited in targets.inherited.iter() {
+            collect_render_targets(inherited, blocks, images);
+        }
+        images.extend(targets.images.iter().cloned());
+    }
+}
@@ -1,4 +1,6 @@
 #![allow(non_upper_case_globals)]
+use crate::image::ImageUsageFlags;
+use bitflags::bitflags;
 
 /// Storage formats for GPU data (texture, vertices, etc).
 ///
@@ -194,6 +196,95 @@ pub enum Format {
     ASTC_12x12_SRGB_BLOCK = 184,
 }
 
+/// Type of the channels of image data in host memory, as returned by image loading libraries.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ChannelType {
+    /// 8-bit unsigned integer, interpreted as a normalized value
+    U8,
+    /// 16-bit unsigned integer, interpreted as a normalized value
+    U16,
+    /// 16-bit floating-point value
+    Half,
+    /// 32-bit floating-point value
+    Float,
+}
+
+/// Host types that can be used as image channels.
+pub trait ChannelData: Copy + 'static {
+    const CHANNEL_TYPE: ChannelType;
+}
+
+impl ChannelData for u8 {
+    const CHANNEL_TYPE: ChannelType = ChannelType::U8;
+}
+impl ChannelData for u16 {
+    const CHANNEL_TYPE: ChannelType = ChannelType::U16;
+}
+impl ChannelData for f32 {
+    const CHANNEL_TYPE: ChannelType = ChannelType::Float;
+}
+
+/// Encoding of color values in images.
+///
+/// Images in sRGB formats are decoded to linear values when sampled, and values written into
+/// them are encoded from linear. No conversion is performed on images with linear formats.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ColorSpace {
+    /// Values are linear.
+    Linear,
+    /// Values are encoded with the sRGB transfer function.
+    Srgb,
+}
+
+impl Default for ColorSpace {
+    fn default() -> Self {
+        ColorSpace::Linear
+    }
+}
+
+bitflags! {
+    /// Operations supported on images of a given format.
+    pub struct FormatFeatureFlags: u32 {
+        const SAMPLED_IMAGE            = 0b0000_0001;
+        const STORAGE_IMAGE            = 0b0000_0010;
+        const COLOR_ATTACHMENT         = 0b0000_0100;
+        const DEPTH_STENCIL_ATTACHMENT = 0b0000_1000;
+    }
+}
+
+/// Support of a format by the device.
+///
+/// See [Api::format_properties](crate::Api::format_properties).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FormatProperties {
+    /// Operations supported on images of this format.
+    pub features: FormatFeatureFlags,
+    /// Whether the device stores images of this format in another internal format
+    /// (e.g. 3-component formats stored with 4 components), possibly with slow conversions on
+    /// uploads and reads.
+    pub emulated: bool,
+}
+
+impl FormatProperties {
+    /// Returns whether images of this format can be created with the specified usage.
+    pub fn supports(&self, usage: ImageUsageFlags) -> bool {
+        let mut features = FormatFeatureFlags::empty();
+        if usage.contains(ImageUsageFlags::SAMPLED) {
+            features |= FormatFeatureFlags::SAMPLED_IMAGE;
+        }
+        if usage.contains(ImageUsageFlags::STORAGE) {
+            features |= FormatFeatureFlags::STORAGE_IMAGE;
+        }
+        if usage.intersects(ImageUsageFlags::COLOR_ATTACHMENT | ImageUsageFlags::INPUT_ATTACHMENT) {
+            features |= FormatFeatureFlags::COLOR_ATTACHMENT;
+        }
+        if usage.contains(ImageUsageFlags::DEPTH_ATTACHMENT) {
+            features |= FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT;
+        }
+        self.features.contains(features)
+    }
+}
+
 /// Layout of color components in a given format.
 pub enum ComponentLayout {
     UNKNOWN,
@@ -263,6 +354,16 @@ impl FormatInfo {
         self.component_bits == [0, 0, 0, 0]
     }
 
+    /// Returns true if the format has a depth or stencil component.
+    pub fn is_depth_stencil(&self) -> bool {
+        match self.component_layout {
+            ComponentLayout::D | ComponentLayout::DS | ComponentLayout::S | ComponentLayout::XD => {
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Returns true if the components of the format are normalized in \[0.0;1.0\] or \[-1.0;1.0\].
     pub fn is_normalized(&self) -> bool {
         match self.format_type {
@@ -1228,6 +1329,113 @@ static TF_ASTC_12x12_SRGB_BLOCK: FormatInfo = FormatInfo {
 };
 
 impl Format {
+    /// Returns the format of tightly-packed image data with the specified number of channels
+    /// (between 1 and 4) of the specified type, or `None` if there is no such format.
+    ///
+    /// Integer channels are mapped to `UNORM` formats.
+    pub fn from_channels(ty: ChannelType, count: usize) -> Option<Format> {
+        let format = match (ty, count) {
+            (ChannelType::U8, 1) => Format::R8_UNORM,
+            (ChannelType::U8, 2) => Format::R8G8_UNORM,
+            (ChannelType::U8, 3) => Format::R8G8B8_UNORM,
+            (ChannelType::U8, 4) => Format::R8G8B8A8_UNORM,
+            (ChannelType::U16, 1) => Format::R16_UNORM,
+            (ChannelType::U16, 2) => Format::R16G16_UNORM,
+            (ChannelType::U16, 3) => Format::R16G16B16_UNORM,
+            (ChannelType::U16, 4) => Format::R16G16B16A16_UNORM,
+            (ChannelType::Half, 1) => Format::R16_SFLOAT,
+            (ChannelType::Half, 2) => Format::R16G16_SFLOAT,
+            (ChannelType::Half, 3) => Format::R16G16B16_SFLOAT,
+            (ChannelType::Half, 4) => Format::R16G16B16A16_SFLOAT,
+            (ChannelType::Float, 1) => Format::R32_SFLOAT,
+            (ChannelType::Float, 2) => Format::R32G32_SFLOAT,
+            (ChannelType::Float, 3) => Format::R32G32B32_SFLOAT,
+            (ChannelType::Float, 4) => Format::R32G32B32A32_SFLOAT,
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    /// Returns the formats that can replace this format when it's not supported by the device,
+    /// in order of preference.
+    ///
+    /// The replacements have the same component types, with more components or more precision.
+    pub fn fallback_formats(self) -> &'static [Format] {
+        match self {
+            Format::R8G8B8_UNORM => &[Format::R8G8B8A8_UNORM],
+            Format::R8G8B8_SNORM => &[Format::R8G8B8A8_SNORM],
+            Format::R8G8B8_UINT => &[Format::R8G8B8A8_UINT],
+            Format::R8G8B8_SINT => &[Format::R8G8B8A8_SINT],
+            Format::R8G8B8_SRGB => &[Format::R8G8B8A8_SRGB],
+            Format::B8G8R8_UNORM => &[Format::B8G8R8A8_UNORM],
+            Format::B8G8R8_SRGB => &[Format::B8G8R8A8_SRGB],
+            Format::R16G16B16_UNORM => &[Format::R16G16B16A16_UNORM],
+            Format::R16G16B16_SNORM => &[Format::R16G16B16A16_SNORM],
+            Format::R16G16B16_UINT => &[Format::R16G16B16A16_UINT],
+            Format::R16G16B16_SINT => &[Format::R16G16B16A16_SINT],
+            Format::R16G16B16_SFLOAT => &[Format::R16G16B16A16_SFLOAT],
+            Format::R32G32B32_UINT => &[Format::R32G32B32A32_UINT],
+            Format::R32G32B32_SINT => &[Format::R32G32B32A32_SINT],
+            Format::R32G32B32_SFLOAT => &[Format::R32G32B32A32_SFLOAT],
+            Format::D16_UNORM_S8_UINT => &[Format::D24_UNORM_S8_UINT, Format::D32_SFLOAT_S8_UINT],
+            Format::D24_UNORM_S8_UINT => &[Format::D32_SFLOAT_S8_UINT],
+            Format::X8_D24_UNORM_PACK32 => &[Format::D32_SFLOAT],
+            _ => &[],
+        }
+    }
+
+    /// Returns the color space of the values of this format.
+    pub fn color_space(self) -> ColorSpace {
+        if self.to_linear() != self {
+            ColorSpace::Srgb
+        } else {
+            ColorSpace::Linear
+        }
+    }
+
+    /// Returns the sRGB variant of this format, or `None` if there is none.
+    ///
+    /// Returns the format itself if it's already an sRGB format.
+    pub fn to_srgb(self) -> Option<Format> {
+        let format = match self {
+            Format::R8_UNORM => Format::R8_SRGB,
+            Format::R8G8_UNORM => Format::R8G8_SRGB,
+            Format::R8G8B8_UNORM => Format::R8G8B8_SRGB,
+            Format::B8G8R8_UNORM => Format::B8G8R8_SRGB,
+            Format::R8G8B8A8_UNORM => Format::R8G8B8A8_SRGB,
+            Format::B8G8R8A8_UNORM => Format::B8G8R8A8_SRGB,
+            Format::A8B8G8R8_UNORM_PACK32 => Format::A8B8G8R8_SRGB_PACK32,
+            Format::BC1_RGB_UNORM_BLOCK => Format::BC1_RGB_SRGB_BLOCK,
+            Format::BC1_RGBA_UNORM_BLOCK => Format::BC1_RGBA_SRGB_BLOCK,
+            Format::BC2_UNORM_BLOCK => Format::BC2_SRGB_BLOCK,
+            Format::BC3_UNORM_BLOCK => Format::BC3_SRGB_BLOCK,
+            Format::BC7_UNORM_BLOCK => Format::BC7_SRGB_BLOCK,
+            other if other.color_space() == ColorSpace::Srgb => other,
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    /// Returns the linear (`UNORM`) variant of an sRGB format, or the format itself
+    /// if it's not an sRGB format.
+    pub fn to_linear(self) -> Format {
+        match self {
+            Format::R8_SRGB => Format::R8_UNORM,
+            Format::R8G8_SRGB => Format::R8G8_UNORM,
+            Format::R8G8B8_SRGB => Format::R8G8B8_UNORM,
+            Format::B8G8R8_SRGB => Format::B8G8R8_UNORM,
+            Format::R8G8B8A8_SRGB => Format::R8G8B8A8_UNORM,
+            Format::B8G8R8A8_SRGB => Format::B8G8R8A8_UNORM,
+            Format::A8B8G8R8_SRGB_PACK32 => Format::A8B8G8R8_UNORM_PACK32,
+            Format::BC1_RGB_SRGB_BLOCK => Format::BC1_RGB_UNORM_BLOCK,
+            Format::BC1_RGBA_SRGB_BLOCK => Format::BC1_RGBA_UNORM_BLOCK,
+            Format::BC2_SRGB_BLOCK => Format::BC2_UNORM_BLOCK,
+            Format::BC3_SRGB_BLOCK => Format::BC3_UNORM_BLOCK,
+            Format::BC7_SRGB_BLOCK => Format::BC7_UNORM_BLOCK,
+            other => other,
+        }
+    }
+
     /// Returns information about the format.
     pub fn get_format_info(self) -> &'static FormatInfo {
         match self {
@@ -1419,3 +1627,62 @@ impl Format {
         }
     }
 }
+
+/// Converts tightly-packed texels of format `src` to format `dst` by appending the missing
+/// components. Missing alpha components are set to one, others to zero.
+///
+/// Panics if `dst` is not one of the [fallback formats](Format::fallback_formats) of `src`
+/// with the same component types and more components.
+pub fn expand_components(src: Format, dst: Format, data: &[u8]) -> Vec<u8> {
+    let src_info = src.get_format_info();
+    let dst_info = dst.get_format_info();
+    let src_n = src_info.num_components() as usize;
+    let dst_n = dst_info.num_components() as usize;
+    let bits = src_info.component_bits[0];
+    assert!(
+        src.fallback_formats().contains(&dst)
+            && src_n < dst_n
+            && bits % 8 == 0
+            && src_info.component_bits[..src_n] == dst_info.component_bits[..src_n]
+            && dst_info.component_bits[src_n..dst_n]
+                .iter()
+                .all(|&b| b == bits),
+        "cannot convert texels from {:?} to {:?}",
+        src,
+        dst
+    );
+
+    let size = bits as usize / 8;
+    // little-endian representation of one
+    let one: Vec<u8> = match (&src_info.format_type, bits) {
+        (NumericFormat::SFLOAT, 16) => vec![0x00, 0x3C],
+        (NumericFormat::SFLOAT, 32) => 1.0f32.to_bits().to_le_bytes().to_vec(),
+        (NumericFormat::SFLOAT, 64) => 1.0f64.to_bits().to_le_bytes().to_vec(),
+        (NumericFormat::UNORM, _) | (NumericFormat::SRGB, _) => vec![0xFF; size],
+        (NumericFormat::SNORM, _) => {
+            let mut v = vec![0xFF; size];
+            v[size - 1] = 0x7F;
+            v
+        }
+        _ => {
+            let mut v = vec![0; size];
+            v[0] = 1;
+            v
+        }
+    };
+
+    let texel_size = src_n * size;
+    assert_eq!(data.len() % texel_size, 0, "incomplete texel data");
+    let mut out = Vec::with_capacity(data.len() / src_n * dst_n);
+    for texel in data.chunks(texel_size) {
+        out.extend_from_slice(texel);
+        for i in src_n..dst_n {
+            if i == 3 {
+                out.extend_from_slice(&one);
+            } else {
+                out.extend(std::iter::repeat(0).take(size));
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,130 @@
+//! Generational handles to images.
+//!
+//! Images are normally referenced by borrows of the arena that owns them, which ties every
+//! structure that stores an image to the lifetime of the arena. Images can be inserted in a
+//! [HandleTable], which returns a copyable, `'static` [ImageHandle] that can be stored in
+//! long-lived data (e.g. the components of an ECS).
+//!
+//! Handles are validated with a generation counter: once an image is removed from the table,
+//! its handle becomes stale, even if its slot is reused for another image.
+//!
+//! Incomplete: commands and argument blocks do not accept handles. Resolve them with
+//! [HandleTable::get] when recording the commands, and rebuild the argument blocks
+//! if the handle may have been removed since. The table stores the images it contains, so it is
+//! still tied to the lifetime of the arena that owns them (see `design/TODO.md`).
+use crate::{image::Image2dView, Backend};
+use std::fmt;
+
+/// Handle to an image in a [HandleTable].
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ImageHandle {
+    index: u32,
+    generation: u32,
+}
+
+impl ImageHandle {
+    /// Index of the slot of the image in the table.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Generation of the slot when the handle was created.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+impl fmt::Debug for ImageHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ImageHandle({}v{})", self.index, self.generation)
+    }
+}
+
+struct Slot<'a, B: Backend> {
+    generation: u32,
+    image: Option<Image2dView<'a, B>>,
+}
+
+/// Table of images referenced by generational handles.
+///
+/// The table borrows the arena of its images: it cannot outlive it.
+pub struct HandleTable<'a, B: Backend> {
+    slots: Vec<Slot<'a, B>>,
+    free: Vec<u32>,
+}
+
+impl<'a, B: Backend> HandleTable<'a, B> {
+    pub fn new() -> HandleTable<'a, B> {
+        HandleTable {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Inserts an image in the table and returns a handle to it.
+    pub fn insert(&mut self, image: impl Into<Image2dView<'a, B>>) -> ImageHandle {
+        let image = Some(image.into());
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.image = image;
+            ImageHandle {
+                index,
+                generation: slot.generation,
+            }
+        } else {
+            let index = self.slots.len() as u32;
+            self.slots.push(Slot {
+                generation: 0,
+                image,
+            });
+            ImageHandle {
+                index,
+                generation: 0,
+            }
+        }
+    }
+
+    /// Removes the image referenced by the handle. The handle and all its copies become stale.
+    ///
+    /// Returns `None` if the handle is already stale.
+    pub fn remove(&mut self, handle: ImageHandle) -> Option<Image2dView<'a, B>> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let image = slot.image.take();
+        if image.is_some() {
+            slot.generation = slot.generation.wrapping_add(1);
+            self.free.push(handle.index);
+        }
+        image
+    }
+
+    /// Returns the image referenced by the handle, or `None` if the handle is stale.
+    pub fn get(&self, handle: ImageHandle) -> Option<Image2dView<'a, B>> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.image)
+    }
+
+    /// Returns whether the handle references an image of the table.
+    pub fn contains(&self, handle: ImageHandle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// Returns the number of images in the table.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a, B: Backend> Default for HandleTable<'a, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
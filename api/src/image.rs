@@ -1,15 +1,20 @@
 use crate::{
     descriptor::{
-        Descriptor, ResourceBindingType, ResourceInterface, ResourceShape, SubresourceRange,
+        Descriptor, FallbackResource, ResourceBindingType, ResourceInterface, ResourceShape,
+        SubresourceRange,
     },
-    format::Format,
+    format::{ColorSpace, Format},
+    traits,
     typedesc::*,
-    AliasScope, Backend,
+    util::{eq_address, hash_address},
+    AliasScope, Arena, Backend,
 };
 use bitflags::bitflags;
+use ordered_float::NotNan;
 use std::{
     cmp::max,
     fmt,
+    hash::{Hash, Hasher},
     ops::{Bound, RangeBounds},
 };
 
@@ -34,6 +39,21 @@ pub struct GenericImage<'a, B: Backend> {
     pub(crate) format: Format,
 }
 
+// images are hashed by identity
+impl<'a, B: Backend> Hash for GenericImage<'a, B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.inner, state)
+    }
+}
+
+impl<'a, B: Backend> PartialEq for GenericImage<'a, B> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.inner, other.inner)
+    }
+}
+
+impl<'a, B: Backend> Eq for GenericImage<'a, B> {}
+
 impl<'a, B: Backend> GenericImage<'a, B> {
     pub fn inner(&self) -> &'a B::Image {
         self.inner
@@ -70,9 +90,51 @@ pub enum Dimensions {
     Dim3d { width: u32, height: u32, depth: u32 },
     /// Cubemap image (6 2D images)
     Cubemap { size: u32, array_layers: u32 },
+    /// 2D image whose size is a fraction of the size of the default swapchain.
+    ///
+    /// The size is resolved by the backend when the image is created, and then again each time
+    /// a frame is submitted, so that the image follows the size of the window. See
+    /// [Arena::create_image](crate::Arena::create_image) for the restrictions that apply.
+    SwapchainRelative {
+        scale_x: NotNan<f32>,
+        scale_y: NotNan<f32>,
+    },
 }
 
 impl Dimensions {
+    /// Returns swapchain-relative dimensions with the given scale factors.
+    ///
+    /// Panics if one of the scale factors is NaN.
+    pub fn swapchain_relative(scale_x: f32, scale_y: f32) -> Dimensions {
+        Dimensions::SwapchainRelative {
+            scale_x: NotNan::new(scale_x).unwrap(),
+            scale_y: NotNan::new(scale_y).unwrap(),
+        }
+    }
+
+    /// Returns true if these dimensions are relative to the size of the swapchain.
+    #[inline]
+    pub fn is_swapchain_relative(&self) -> bool {
+        match *self {
+            Dimensions::SwapchainRelative { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Resolves swapchain-relative dimensions given the size of the swapchain.
+    ///
+    /// The resulting image is at least 1x1. Other dimensions are returned unchanged.
+    pub fn resolve(&self, (width, height): (u32, u32)) -> Dimensions {
+        match *self {
+            Dimensions::SwapchainRelative { scale_x, scale_y } => Dimensions::Dim2d {
+                width: ((width as f32 * scale_x.into_inner()).round() as u32).max(1),
+                height: ((height as f32 * scale_y.into_inner()).round() as u32).max(1),
+                array_layers: 1,
+            },
+            other => other,
+        }
+    }
+
     /// Returns the width in pixels.
     #[inline]
     pub fn width(&self) -> u32 {
@@ -81,6 +143,7 @@ impl Dimensions {
             Dimensions::Dim2d { width, .. } => width,
             Dimensions::Dim3d { width, .. } => width,
             Dimensions::Cubemap { size, .. } => size,
+            Dimensions::SwapchainRelative { .. } => unresolved(),
         }
     }
 
@@ -94,6 +157,7 @@ impl Dimensions {
             Dimensions::Dim2d { height, .. } => height,
             Dimensions::Dim3d { height, .. } => height,
             Dimensions::Cubemap { size, .. } => size,
+            Dimensions::SwapchainRelative { .. } => unresolved(),
         }
     }
 
@@ -115,6 +179,7 @@ impl Dimensions {
             Dimensions::Dim2d { .. } => 1,
             Dimensions::Dim3d { depth, .. } => depth,
             Dimensions::Cubemap { .. } => 1,
+            Dimensions::SwapchainRelative { .. } => 1,
         }
     }
 
@@ -133,6 +198,7 @@ impl Dimensions {
             Dimensions::Dim2d { array_layers, .. } => array_layers,
             Dimensions::Dim3d { .. } => 1,
             Dimensions::Cubemap { array_layers, .. } => array_layers,
+            Dimensions::SwapchainRelative { .. } => 1,
         }
     }
 
@@ -143,10 +209,16 @@ impl Dimensions {
             Dimensions::Dim2d { array_layers, .. } => array_layers,
             Dimensions::Dim3d { .. } => 1,
             Dimensions::Cubemap { array_layers, .. } => array_layers * 6,
+            Dimensions::SwapchainRelative { .. } => 1,
         }
     }
 }
 
+#[cold]
+fn unresolved() -> ! {
+    panic!("swapchain-relative dimensions must be resolved first")
+}
+
 impl From<(u32, u32)> for Dimensions {
     fn from((width, height): (u32, u32)) -> Dimensions {
         Dimensions::Dim2d {
@@ -193,6 +265,9 @@ impl fmt::Debug for Dimensions {
                     write!(f, "[Cubemap Array {}x{}(x{})]", size, size, array_layers)
                 }
             }
+            Dimensions::SwapchainRelative { scale_x, scale_y } => {
+                write!(f, "[2D Swapchain x{}, x{}]", scale_x, scale_y)
+            }
         }
     }
 }
@@ -223,6 +298,9 @@ bitflags! {
         const INPUT_ATTACHMENT = 0b0000_0100;
         const STORAGE          = 0b0000_1000;
         const SAMPLED          = 0b0001_0000;
+        /// The usage is inferred from the first frame in which the image is used.
+        /// See [Arena::create_image](crate::Arena::create_image).
+        const INFER            = 0b1000_0000;
     }
 }
 
@@ -313,6 +391,42 @@ impl SamplerDescription {
     };
 }
 
+/// Range of levels of detail that a sampler can select, applied when the texture is bound
+/// (see [TextureSampler2dLodView]).
+///
+/// Levels are relative to the most detailed mipmap level of the view, and `max_lod` is
+/// inclusive: `min_lod == max_lod` pins sampling to a single mipmap level.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub struct LodClamp {
+    pub min_lod: u32,
+    pub max_lod: u32,
+}
+
+impl LodClamp {
+    /// Clamp to a single mipmap level.
+    pub fn level(lod: u32) -> LodClamp {
+        LodClamp {
+            min_lod: lod,
+            max_lod: lod,
+        }
+    }
+
+    /// Panics if the clamp selects levels outside the `mip_levels` levels of an image,
+    /// when sampling the mipmap levels of `subresource`.
+    ///
+    /// Called by backends when creating argument blocks.
+    pub fn validate(&self, subresource: &SubresourceRange, mip_levels: u32) {
+        assert!(
+            subresource.base_mip_level + self.max_lod < mip_levels,
+            "LOD clamp {}..={} (from mip level {}) out of the bounds of an image with {} levels",
+            self.min_lod,
+            self.max_lod,
+            subresource.base_mip_level,
+            mip_levels
+        );
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum MipmapsOption {
@@ -324,11 +438,10 @@ pub enum MipmapsOption {
 }
 
 impl MipmapsOption {
-    pub fn count(&self, width: u32, height: u32, _depth: u32) -> u32 {
+    pub fn count(&self, width: u32, height: u32, depth: u32) -> u32 {
         match self {
             MipmapsOption::Allocate | MipmapsOption::Generate => {
-                // TODO mipcount for 3D textures?
-                get_texture_mip_map_count(max(width, height))
+                get_texture_mip_map_count(max(max(width, height), depth))
             }
             MipmapsOption::GenerateCount(n) | MipmapsOption::AllocateCount(n) => *n,
             MipmapsOption::NoMipmap => 1,
@@ -336,6 +449,18 @@ impl MipmapsOption {
     }
 }
 
+/// Removes the usages that are not supported by images of the specified format
+/// from the default usage of image builders.
+///
+/// sRGB images cannot be used as storage images (no conversion is performed on load and store).
+pub(crate) fn supported_usage(format: Format, usage: ImageUsageFlags) -> ImageUsageFlags {
+    if format.color_space() == ColorSpace::Srgb {
+        usage - ImageUsageFlags::STORAGE
+    } else {
+        usage
+    }
+}
+
 macro_rules! impl_image_builder {
     (@T size D1) => { u32 };
     (@T size D2) => { (u32,u32) };
@@ -439,6 +564,35 @@ macro_rules! impl_image_builder {
     (@E flags C) => { ImageUsageFlags::COLOR_ATTACHMENT };
     (@E flags DS) => { ImageUsageFlags::DEPTH_ATTACHMENT };
 
+    (@M color_space) => {
+        /// Sets the color space of the image. The format is replaced by its sRGB or linear
+        /// variant.
+        ///
+        /// Panics if the format has no sRGB variant.
+        pub fn color_space(&mut self, color_space: ColorSpace) -> &mut Self {
+            self.format = match color_space {
+                ColorSpace::Srgb => self.format.to_srgb().unwrap_or_else(|| {
+                    panic!("format {:?} has no sRGB variant", self.format)
+                }),
+                ColorSpace::Linear => self.format.to_linear(),
+            };
+            self.usage = supported_usage(self.format, self.usage);
+            self
+        }
+    };
+
+    (@M usage) => {
+        pub fn usage(&mut self, usage: ImageUsageFlags) -> &mut Self {
+            self.usage = usage;
+            self
+        }
+        /// Infers the usage of the image from the first frame in which it is used
+        /// (see [ImageUsageFlags::INFER]).
+        pub fn infer_usage(&mut self) -> &mut Self {
+            self.usage(ImageUsageFlags::INFER)
+        }
+    };
+
     (@M build) => {
         pub fn build(&mut self) -> O {
             let c = ImageCreateInfo {
@@ -507,7 +661,7 @@ macro_rules! impl_image_builder {
                     array_layers: 1,
                     mipmaps: MipmapsOption::NoMipmap,
                     samples: 1,
-                    usage: impl_image_builder!(@E flags $mode),
+                    usage: supported_usage(format, impl_image_builder!(@E flags $mode)),
                     aliasing: AliasScope::no_alias(),
                 }
             }
@@ -517,6 +671,8 @@ macro_rules! impl_image_builder {
             impl_image_builder!(@M samples $multisample);
             impl_image_builder!(@M size $shape);
             impl_image_builder!(@MM mipmap_methods $shape $multisample);
+            impl_image_builder!(@M color_space);
+            impl_image_builder!(@M usage);
             impl_image_builder!(@M build $mode);
             impl_image_builder!(@M with_data);
         }
@@ -563,6 +719,20 @@ macro_rules! impl_image {
                 $n { image: raw }
             }
         }
+
+        impl<'a, B: Backend> Hash for $n<'a, B> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                hash_address(self.image, state)
+            }
+        }
+
+        impl<'a, B: Backend> PartialEq for $n<'a, B> {
+            fn eq(&self, other: &Self) -> bool {
+                eq_address(self.image, other.image)
+            }
+        }
+
+        impl<'a, B: Backend> Eq for $n<'a, B> {}
     };
 }
 
@@ -575,6 +745,21 @@ macro_rules! impl_image_mipmap {
             pub(crate) miplevel: u32,
         }
 
+        impl<'a, B: Backend> Hash for $n_image_mipmap<'a, B> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                hash_address(self.image, state);
+                self.miplevel.hash(state);
+            }
+        }
+
+        impl<'a, B: Backend> PartialEq for $n_image_mipmap<'a, B> {
+            fn eq(&self, other: &Self) -> bool {
+                eq_address(self.image, other.image) && self.miplevel == other.miplevel
+            }
+        }
+
+        impl<'a, B: Backend> Eq for $n_image_mipmap<'a, B> {}
+
         impl<'a, B: Backend> $n_image<'a, B> {
             pub fn mipmap(&self, miplevel: u32) -> $n_image_mipmap<'a, B> {
                 $n_image_mipmap {
@@ -596,6 +781,24 @@ macro_rules! impl_image_mipmaps {
             pub(crate) mip_count: Option<u32>,
         }
 
+        impl<'a, B: Backend> Hash for $n_image_mipmaps<'a, B> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                hash_address(self.image, state);
+                self.most_detailed_miplevel.hash(state);
+                self.mip_count.hash(state);
+            }
+        }
+
+        impl<'a, B: Backend> PartialEq for $n_image_mipmaps<'a, B> {
+            fn eq(&self, other: &Self) -> bool {
+                eq_address(self.image, other.image)
+                    && self.most_detailed_miplevel == other.most_detailed_miplevel
+                    && self.mip_count == other.mip_count
+            }
+        }
+
+        impl<'a, B: Backend> Eq for $n_image_mipmaps<'a, B> {}
+
         impl<'a, B: Backend> $n_image_mipmaps<'a, B> {
             pub fn sampled(&self, sampler: SamplerDescription) -> $texture_sampler_view<'a, B> {
                 $texture_sampler_view {
@@ -683,6 +886,26 @@ impl<'a, B: Backend> Image2d<'a, B> {
             },
         }
     }
+
+    /// Returns a view of `count` array layers of the image starting at `base_layer`, usable as
+    /// a layered render target: view `i` of a multiview pipeline renders to layer `base_layer + i`.
+    pub fn render_target_layers_view(
+        &self,
+        base_layer: u32,
+        count: u32,
+    ) -> RenderTarget2dArrayView<'a, B> {
+        self.mipmap(0).render_target_layers_view(base_layer, count)
+    }
+
+    /// Returns a view of `count` array layers of the image starting at `base_layer`, usable as
+    /// a layered depth-stencil render target.
+    pub fn depth_stencil_layers_view(
+        &self,
+        base_layer: u32,
+        count: u32,
+    ) -> DepthStencil2dArrayView<'a, B> {
+        self.mipmap(0).depth_stencil_layers_view(base_layer, count)
+    }
 }
 
 impl<'a, B: Backend> Image2dMipmap<'a, B> {
@@ -697,6 +920,64 @@ impl<'a, B: Backend> Image2dMipmap<'a, B> {
             },
         }
     }
+
+    /// Returns a view of `count` array layers of this mipmap level starting at `base_layer`,
+    /// usable as a layered render target.
+    pub fn render_target_layers_view(
+        &self,
+        base_layer: u32,
+        count: u32,
+    ) -> RenderTarget2dArrayView<'a, B> {
+        RenderTarget2dArrayView {
+            image: self.image,
+            subresource: SubresourceRange {
+                base_mip_level: self.miplevel,
+                level_count: Some(1),
+                base_array_layer: base_layer,
+                layer_count: Some(count),
+            },
+        }
+    }
+
+    /// Returns a view of `count` array layers of this mipmap level starting at `base_layer`,
+    /// usable as a layered depth-stencil render target.
+    pub fn depth_stencil_layers_view(
+        &self,
+        base_layer: u32,
+        count: u32,
+    ) -> DepthStencil2dArrayView<'a, B> {
+        DepthStencil2dArrayView {
+            image: self.image,
+            subresource: SubresourceRange {
+                base_mip_level: self.miplevel,
+                level_count: Some(1),
+                base_array_layer: base_layer,
+                layer_count: Some(count),
+            },
+        }
+    }
+}
+
+impl<'a, B: Backend> Image3d<'a, B> {
+    /// Returns a view of the depth slice `z` of the image, usable as a render target.
+    pub fn render_target_slice_view(&self, z: u32) -> RenderTarget3dSliceView<'a, B> {
+        self.mipmap(0).render_target_slice_view(z)
+    }
+}
+
+impl<'a, B: Backend> Image3dMipmap<'a, B> {
+    /// Returns a view of the depth slice `z` of this mipmap level, usable as a render target.
+    pub fn render_target_slice_view(&self, z: u32) -> RenderTarget3dSliceView<'a, B> {
+        RenderTarget3dSliceView {
+            image: self.image,
+            subresource: SubresourceRange {
+                base_mip_level: self.miplevel,
+                level_count: Some(1),
+                base_array_layer: z,
+                layer_count: Some(1),
+            },
+        }
+    }
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -718,6 +999,20 @@ macro_rules! impl_view_type {
             pub fn inner(&self) -> &'a B::Image { self.image }
             pub fn subresource(&self) -> SubresourceRange { self.subresource }
         }
+        impl<'a, B: Backend> Hash for $nv<'a,B> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                hash_address(self.image, state);
+                self.subresource.hash(state);
+            }
+        }
+
+        impl<'a, B: Backend> PartialEq for $nv<'a,B> {
+            fn eq(&self, other: &Self) -> bool {
+                eq_address(self.image, other.image) && self.subresource == other.subresource
+            }
+        }
+
+        impl<'a, B: Backend> Eq for $nv<'a,B> {}
         $(impl<'a,B:Backend> From<$trivial_conv<'a,B>> for $nv<'a,B> {
             fn from(other: $trivial_conv<'a,B>) -> $nv<'a,B> {
                 $nv {
@@ -740,6 +1035,23 @@ macro_rules! impl_view_type {
             pub fn subresource(&self) -> SubresourceRange { self.subresource }
             pub fn sampler(&self) -> &SamplerDescription { &self.sampler }
         }
+        impl<'a, B: Backend> Hash for $nv<'a,B> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                hash_address(self.image, state);
+                self.subresource.hash(state);
+                self.sampler.hash(state);
+            }
+        }
+
+        impl<'a, B: Backend> PartialEq for $nv<'a,B> {
+            fn eq(&self, other: &Self) -> bool {
+                eq_address(self.image, other.image)
+                    && self.subresource == other.subresource
+                    && self.sampler == other.sampler
+            }
+        }
+
+        impl<'a, B: Backend> Eq for $nv<'a,B> {}
         $(impl<'a,B:Backend> From<$trivial_conv<'a,B>> for $nv<'a,B> {
             fn from(other: $trivial_conv<'a,B>) -> $nv<'a,B> {
                 $nv {
@@ -827,11 +1139,43 @@ impl_single_mipmap_view!(default DepthStencilImage2d => Image2dView);
 impl_single_mipmap_view!(Image1dMipmap => Image1dView);
 impl_single_mipmap_view!(Image2dMipmap => Image2dView);
 impl_single_mipmap_view!(Image3dMipmap => Image3dView);
+impl_single_mipmap_view!(default Image1d => ImageView);
+impl_single_mipmap_view!(default Image2d => ImageView);
+impl_single_mipmap_view!(default Image3d => ImageView);
 
-impl_view_type!(RenderTargetView from RenderTarget2dView);
-impl_view_type!(DepthStencilView from DepthStencil2dView);
+impl_view_type!(RenderTargetView from RenderTarget2dView, RenderTarget3dSliceView, RenderTarget2dArrayView);
+impl_view_type!(DepthStencilView from DepthStencil2dView, DepthStencil2dArrayView);
 impl_view_type!(RenderTarget2dView);
+// a single depth slice of a 3D image (`subresource.base_array_layer` is the slice index)
+impl_view_type!(RenderTarget3dSliceView);
 impl_view_type!(DepthStencil2dView);
+// a range of array layers of a 2D image, for multiview rendering (one view per layer)
+impl_view_type!(RenderTarget2dArrayView);
+impl_view_type!(DepthStencil2dArrayView);
+
+/// Returns the size in pixels of a mipmap level of an image.
+fn mip_level_size<B: Backend>(image: &B::Image, level: u32) -> (u32, u32) {
+    let (width, height) = traits::Image::dimensions(image).width_height();
+    (max(width >> level, 1), max(height >> level, 1))
+}
+
+impl<'a, B: Backend> RenderTargetView<'a, B> {
+    /// Returns the size in pixels of the mipmap level of the view.
+    ///
+    /// The size of swapchain-relative images is the one of the last frame submission.
+    pub fn size(&self) -> (u32, u32) {
+        mip_level_size::<B>(self.image, self.subresource.base_mip_level)
+    }
+}
+
+impl<'a, B: Backend> DepthStencilView<'a, B> {
+    /// Returns the size in pixels of the mipmap level of the view.
+    ///
+    /// The size of swapchain-relative images is the one of the last frame submission.
+    pub fn size(&self) -> (u32, u32) {
+        mip_level_size::<B>(self.image, self.subresource.base_mip_level)
+    }
+}
 // img2d, default level can be converted to RTV via into
 impl_single_mipmap_view!(default Image2d => RenderTargetView);
 impl_single_mipmap_view!(default Image2d => RenderTarget2dView);
@@ -869,6 +1213,16 @@ impl_resource_interface_view!(
     RwImage
 );
 
+// 3D image without a sampler, for `texelFetch`
+impl_view_type!(Texture3dView);
+impl_single_mipmap_view!(default Image3d => Texture3dView);
+impl_single_mipmap_view!(Image3dMipmap => Texture3dView);
+impl_resource_interface_view!(
+    Texture3dView,
+    ResourceBindingType::Texture(ResourceShape::R3d),
+    Texture
+);
+
 impl_view_type!(sampled TextureSamplerView from TextureSampler1dView,TextureSampler2dView,TextureSampler3dView);
 impl_view_type!(sampled TextureSampler1dView);
 impl_view_type!(sampled TextureSampler2dView);
@@ -877,3 +1231,216 @@ impl_view_type!(sampled TextureSampler3dView);
 impl_resource_interface_view!(sampled TextureSampler1dView, ResourceBindingType::TextureSampler(ResourceShape::R1d), TextureSampler);
 impl_resource_interface_view!(sampled TextureSampler2dView, ResourceBindingType::TextureSampler(ResourceShape::R2d), TextureSampler);
 impl_resource_interface_view!(sampled TextureSampler3dView, ResourceBindingType::TextureSampler(ResourceShape::R3d), TextureSampler);
+
+/// A 2D texture and sampler, with a clamp of the levels of detail selected by the sampler.
+///
+/// Bound as a regular combined image-sampler (`sampler2D` in GLSL): this is useful for effects
+/// that sample a specific mipmap level, e.g. the upsampling passes of a bloom filter.
+/// The clamp is validated against the number of mipmap levels of the image when the argument
+/// block is created.
+#[derive(derivative::Derivative)]
+#[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
+pub struct TextureSampler2dLodView<'a, B: Backend> {
+    pub(crate) image: &'a B::Image,
+    pub(crate) subresource: SubresourceRange,
+    pub(crate) sampler: SamplerDescription,
+    pub(crate) lod: LodClamp,
+}
+
+impl<'a, B: Backend> TextureSampler2dLodView<'a, B> {
+    pub fn inner(&self) -> &'a B::Image {
+        self.image
+    }
+    pub fn subresource(&self) -> SubresourceRange {
+        self.subresource
+    }
+    pub fn sampler(&self) -> &SamplerDescription {
+        &self.sampler
+    }
+    pub fn lod(&self) -> LodClamp {
+        self.lod
+    }
+}
+
+impl<'a, B: Backend> Hash for TextureSampler2dLodView<'a, B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.image, state);
+        self.subresource.hash(state);
+        self.sampler.hash(state);
+        self.lod.hash(state);
+    }
+}
+
+impl<'a, B: Backend> PartialEq for TextureSampler2dLodView<'a, B> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.image, other.image)
+            && self.subresource == other.subresource
+            && self.sampler == other.sampler
+            && self.lod == other.lod
+    }
+}
+
+impl<'a, B: Backend> Eq for TextureSampler2dLodView<'a, B> {}
+
+impl<'a, B: Backend> TextureSampler2dView<'a, B> {
+    /// Restricts the levels of detail selected by the sampler to `min_lod..=max_lod`.
+    pub fn lod_clamp(&self, min_lod: u32, max_lod: u32) -> TextureSampler2dLodView<'a, B> {
+        assert!(
+            min_lod <= max_lod,
+            "invalid LOD clamp {}..={}",
+            min_lod,
+            max_lod
+        );
+        TextureSampler2dLodView {
+            image: self.image,
+            subresource: self.subresource,
+            sampler: self.sampler,
+            lod: LodClamp { min_lod, max_lod },
+        }
+    }
+
+    /// Samples only the mipmap level `lod`.
+    pub fn pin_lod(&self, lod: u32) -> TextureSampler2dLodView<'a, B> {
+        self.lod_clamp(lod, lod)
+    }
+}
+
+impl<'a, B: Backend> ResourceInterface<'a, B> for TextureSampler2dLodView<'a, B> {
+    const TYPE: ResourceBindingType = ResourceBindingType::TextureSampler(ResourceShape::R2d);
+    const DATA_TYPE: Option<&'static TypeDesc<'static>> = None;
+    fn into_descriptor(self) -> Descriptor<'a, B> {
+        Descriptor::TextureSamplerLod {
+            image: self.image,
+            subresource: self.subresource,
+            sampler: self.sampler,
+            lod: self.lod,
+        }
+    }
+}
+
+/// Built-in 1x1 textures provided by the renderer, used as replacements for missing textures.
+///
+/// See [Api::fallback_texture](crate::Api::fallback_texture).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum FallbackTexture {
+    /// Opaque white (1,1,1,1).
+    White,
+    /// Opaque black (0,0,0,1).
+    Black,
+    /// Tangent-space normal pointing up (0,0,1), encoded as (0.5,0.5,1,1).
+    NormalUp,
+}
+
+impl FallbackTexture {
+    /// Returns the contents of the texture in the `R8G8B8A8_UNORM` format.
+    pub fn texel(&self) -> [u8; 4] {
+        match self {
+            FallbackTexture::White => [255, 255, 255, 255],
+            FallbackTexture::Black => [0, 0, 0, 255],
+            FallbackTexture::NormalUp => [128, 128, 255, 255],
+        }
+    }
+}
+
+impl<'a, B: Backend> FallbackResource<'a, B> for TextureSampler2dView<'a, B> {
+    fn fallback(arena: &'a Arena<B>, kind: FallbackTexture) -> Self {
+        arena.api().fallback_texture(kind).sampled_linear()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// A region of an image to update with data from host memory.
+///
+/// See [ImageUpdateBatch].
+#[derive(Copy, Clone, Debug)]
+pub struct ImageRegionUpdate<'d> {
+    pub mip_level: u32,
+    /// Offset of the region, in texels.
+    pub offset: (u32, u32, u32),
+    /// Size of the region, in texels.
+    pub size: (u32, u32, u32),
+    /// Number of bytes between the starts of two consecutive rows in `data`,
+    /// or 0 if rows are tightly packed.
+    ///
+    /// Must be a multiple of the size of a texel. Slices are made of `size.1` rows.
+    pub row_pitch: usize,
+    /// Data in the format of the image. No conversion is performed.
+    pub data: &'d [u8],
+}
+
+/// A set of updates of a mipmap level of an image, sent to the backend in one call
+/// to [Api::update_image_batch](crate::Api::update_image_batch).
+///
+/// The extents of all regions are validated against the dimensions of the mipmap level.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct ImageUpdateBatch<'a, 'd, B: Backend> {
+    pub(crate) image: &'a B::Image,
+    pub(crate) mip_level: u32,
+    pub(crate) regions: Vec<ImageRegionUpdate<'d>>,
+}
+
+impl<'a, 'd, B: Backend> ImageUpdateBatch<'a, 'd, B> {
+    /// Creates an empty batch of updates of the mipmap level of the view.
+    pub fn new(image: impl Into<ImageView<'a, B>>) -> ImageUpdateBatch<'a, 'd, B> {
+        let view = image.into();
+        ImageUpdateBatch {
+            image: view.image,
+            mip_level: view.subresource.base_mip_level,
+            regions: Vec::new(),
+        }
+    }
+
+    /// Adds an update of the region between `min_extent` (inclusive) and `max_extent`
+    /// (exclusive), with tightly packed data.
+    pub fn update(
+        &mut self,
+        min_extent: (u32, u32, u32),
+        max_extent: (u32, u32, u32),
+        data: &'d [u8],
+    ) -> &mut Self {
+        self.update_with_pitch(min_extent, max_extent, 0, data)
+    }
+
+    /// Adds an update of the region between `min_extent` (inclusive) and `max_extent`
+    /// (exclusive), with `row_pitch` bytes between the starts of two consecutive rows of `data`.
+    ///
+    /// This can be used to update a region from a sub-rectangle of a larger image in memory,
+    /// or from rows that are padded for alignment.
+    pub fn update_with_pitch(
+        &mut self,
+        min_extent: (u32, u32, u32),
+        max_extent: (u32, u32, u32),
+        row_pitch: usize,
+        data: &'d [u8],
+    ) -> &mut Self {
+        assert!(
+            min_extent.0 <= max_extent.0
+                && min_extent.1 <= max_extent.1
+                && min_extent.2 <= max_extent.2,
+            "invalid image region"
+        );
+        self.regions.push(ImageRegionUpdate {
+            mip_level: self.mip_level,
+            offset: min_extent,
+            size: (
+                max_extent.0 - min_extent.0,
+                max_extent.1 - min_extent.1,
+                max_extent.2 - min_extent.2,
+            ),
+            row_pitch,
+            data,
+        });
+        self
+    }
+
+    /// Returns the regions to update.
+    pub fn regions(&self) -> &[ImageRegionUpdate<'d>] {
+        &self.regions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+}
@@ -0,0 +1,481 @@
+//! Read-only description of the resources bound in argument blocks, for tooling.
+//!
+//! Requires the `introspection` feature. When enabled, the properties of images and buffers are
+//! recorded when they are created, and the contents of each argument block are recorded when
+//! the block is created. [BareArgumentBlock::introspect](crate::pipeline::BareArgumentBlock::introspect)
+//! then returns an [ArgumentBlockInfo], which does not borrow the resources and can be displayed
+//! in an inspector window (its `Display` implementation prints one line per binding).
+//!
+//! The descriptions are forgotten when the arena containing the objects is dropped.
+use crate::{
+    descriptor::{Descriptor, SubresourceRange},
+    format::Format,
+    image::{Dimensions, ImageUsageFlags, LodClamp, MipmapsOption, SamplerDescription},
+    pipeline::{Scissor, Viewport},
+    util::address,
+    vertex::{IndexBufferView, IndexFormat, VertexBufferView},
+    Backend,
+};
+use std::{collections::HashMap, fmt, sync::Mutex};
+
+/// Properties of an image, as specified on creation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ImageInfo {
+    pub format: Format,
+    pub dimensions: Dimensions,
+    pub mipmaps: MipmapsOption,
+    pub samples: u32,
+    /// Actual usage of the image (already expanded if created with [ImageUsageFlags::INFER]).
+    pub usage: ImageUsageFlags,
+}
+
+/// Properties of a buffer, as specified on creation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BufferInfo {
+    /// Size in bytes.
+    pub size: u64,
+}
+
+/// Properties of a texel buffer view, as specified on creation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BufferViewInfo {
+    pub buffer: Option<BufferInfo>,
+    pub format: Format,
+    pub offset: usize,
+    pub size: Option<usize>,
+}
+
+/// Description of a bound descriptor.
+///
+/// Resources that were not recorded (e.g. created by the backend directly) are `None`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DescriptorInfo {
+    Sampler {
+        sampler: SamplerDescription,
+    },
+    /// Sampled or storage image, with its sampler if it is a combined image-sampler.
+    Image {
+        image: Option<ImageInfo>,
+        subresource: SubresourceRange,
+        sampler: Option<SamplerDescription>,
+        lod: Option<LodClamp>,
+        storage: bool,
+    },
+    /// Constant or storage buffer.
+    Buffer {
+        buffer: Option<BufferInfo>,
+        offset: usize,
+        size: Option<usize>,
+        storage: bool,
+    },
+    TexelBuffer {
+        view: Option<BufferViewInfo>,
+    },
+    RwTexelBuffer {
+        buffer: Option<BufferInfo>,
+        offset: usize,
+        size: Option<usize>,
+    },
+    /// Constant buffer uploaded from host memory.
+    HostConstantBuffer,
+    Empty,
+}
+
+/// Description of a bound vertex buffer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct VertexBufferInfo {
+    pub buffer: Option<BufferInfo>,
+    pub offset: usize,
+    pub stride: usize,
+}
+
+/// Description of a bound index buffer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct IndexBufferInfo {
+    pub buffer: Option<BufferInfo>,
+    pub offset: usize,
+    pub format: IndexFormat,
+}
+
+/// Description of a bound render target or depth-stencil target.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AttachmentInfo {
+    pub image: Option<ImageInfo>,
+    pub subresource: SubresourceRange,
+}
+
+/// Description of the contents of an argument block, including its inherited blocks.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArgumentBlockInfo {
+    pub inherited: Vec<ArgumentBlockInfo>,
+    pub descriptors: Vec<DescriptorInfo>,
+    pub vertex_buffers: Vec<VertexBufferInfo>,
+    pub index_buffer: Option<IndexBufferInfo>,
+    pub render_targets: Vec<AttachmentInfo>,
+    pub depth_stencil_target: Option<AttachmentInfo>,
+    pub viewports: Vec<Viewport>,
+    pub scissors: Vec<Scissor>,
+    pub stencil_reference: Option<u32>,
+}
+
+struct Indent(usize);
+
+impl fmt::Display for Indent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for _ in 0..self.0 {
+            f.write_str("  ")?;
+        }
+        Ok(())
+    }
+}
+
+struct OptionalImage<'a>(&'a Option<ImageInfo>);
+
+impl<'a> fmt::Display for OptionalImage<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(image) => write!(
+                f,
+                "{:?} {:?}, {:?}, {} sample(s)",
+                image.format, image.dimensions, image.mipmaps, image.samples
+            ),
+            None => f.write_str("<unknown image>"),
+        }
+    }
+}
+
+struct OptionalBuffer<'a>(&'a Option<BufferInfo>);
+
+impl<'a> fmt::Display for OptionalBuffer<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(buffer) => write!(f, "buffer of {} bytes", buffer.size),
+            None => f.write_str("<unknown buffer>"),
+        }
+    }
+}
+
+struct Range(usize, Option<usize>);
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.1 {
+            Some(size) => write!(f, "[{}..{}]", self.0, self.0 + size),
+            None => write!(f, "[{}..]", self.0),
+        }
+    }
+}
+
+impl fmt::Display for DescriptorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DescriptorInfo::Sampler { sampler } => write!(f, "sampler {:?}", sampler),
+            DescriptorInfo::Image {
+                image,
+                subresource,
+                sampler,
+                lod,
+                storage,
+            } => {
+                let kind = if *storage { "storage image" } else { "texture" };
+                write!(f, "{} {}, {:?}", kind, OptionalImage(image), subresource)?;
+                if let Some(sampler) = sampler {
+                    write!(f, ", sampler {:?}", sampler)?;
+                }
+                if let Some(lod) = lod {
+                    write!(f, ", lod {}..={}", lod.min_lod, lod.max_lod)?;
+                }
+                Ok(())
+            }
+            DescriptorInfo::Buffer {
+                buffer,
+                offset,
+                size,
+                storage,
+            } => {
+                let kind = if *storage {
+                    "storage buffer"
+                } else {
+                    "constant buffer"
+                };
+                write!(
+                    f,
+                    "{} {}{}",
+                    kind,
+                    OptionalBuffer(buffer),
+                    Range(*offset, *size)
+                )
+            }
+            DescriptorInfo::TexelBuffer { view } => match view {
+                Some(view) => write!(
+                    f,
+                    "texel buffer {:?}, {}{}",
+                    view.format,
+                    OptionalBuffer(&view.buffer),
+                    Range(view.offset, view.size)
+                ),
+                None => f.write_str("texel buffer <unknown view>"),
+            },
+            DescriptorInfo::RwTexelBuffer {
+                buffer,
+                offset,
+                size,
+            } => write!(
+                f,
+                "storage texel buffer {}{}",
+                OptionalBuffer(buffer),
+                Range(*offset, *size)
+            ),
+            DescriptorInfo::HostConstantBuffer => f.write_str("host constant buffer"),
+            DescriptorInfo::Empty => f.write_str("empty"),
+        }
+    }
+}
+
+impl ArgumentBlockInfo {
+    fn fmt_indented(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        let i = Indent(indent);
+        for (index, inherited) in self.inherited.iter().enumerate() {
+            writeln!(f, "{}inherited block #{}:", i, index)?;
+            inherited.fmt_indented(f, indent + 1)?;
+        }
+        for (index, descriptor) in self.descriptors.iter().enumerate() {
+            writeln!(f, "{}descriptor #{}: {}", i, index, descriptor)?;
+        }
+        for (index, vb) in self.vertex_buffers.iter().enumerate() {
+            writeln!(
+                f,
+                "{}vertex buffer #{}: {}, offset {}, stride {}",
+                i,
+                index,
+                OptionalBuffer(&vb.buffer),
+                vb.offset,
+                vb.stride
+            )?;
+        }
+        if let Some(ref ib) = self.index_buffer {
+            writeln!(
+                f,
+                "{}index buffer: {}, offset {}, {:?}",
+                i,
+                OptionalBuffer(&ib.buffer),
+                ib.offset,
+                ib.format
+            )?;
+        }
+        for (index, rt) in self.render_targets.iter().enumerate() {
+            writeln!(
+                f,
+                "{}render target #{}: {}, {:?}",
+                i,
+                index,
+                OptionalImage(&rt.image),
+                rt.subresource
+            )?;
+        }
+        if let Some(ref ds) = self.depth_stencil_target {
+            writeln!(
+                f,
+                "{}depth-stencil target: {}, {:?}",
+                i,
+                OptionalImage(&ds.image),
+                ds.subresource
+            )?;
+        }
+        for (index, viewport) in self.viewports.iter().enumerate() {
+            writeln!(f, "{}viewport #{}: {:?}", i, index, viewport)?;
+        }
+        for (index, scissor) in self.scissors.iter().enumerate() {
+            writeln!(f, "{}scissor #{}: {:?}", i, index, scissor)?;
+        }
+        if let Some(reference) = self.stencil_reference {
+            writeln!(f, "{}stencil reference: {}", i, reference)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ArgumentBlockInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// Descriptions of images, buffers and argument blocks, by address of the backend object.
+pub(crate) struct Registry {
+    images: Mutex<HashMap<usize, ImageInfo>>,
+    buffers: Mutex<HashMap<usize, BufferInfo>>,
+    buffer_views: Mutex<HashMap<usize, BufferViewInfo>>,
+    blocks: Mutex<HashMap<usize, ArgumentBlockInfo>>,
+}
+
+impl Registry {
+    pub(crate) fn new() -> Registry {
+        Registry {
+            images: Mutex::new(HashMap::new()),
+            buffers: Mutex::new(HashMap::new()),
+            buffer_views: Mutex::new(HashMap::new()),
+            blocks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn register_image(&self, image: usize, info: ImageInfo) {
+        self.images.lock().unwrap().insert(image, info);
+    }
+
+    pub(crate) fn register_buffer(&self, buffer: usize, info: BufferInfo) {
+        self.buffers.lock().unwrap().insert(buffer, info);
+    }
+
+    pub(crate) fn register_buffer_view(&self, view: usize, info: BufferViewInfo) {
+        self.buffer_views.lock().unwrap().insert(view, info);
+    }
+
+    pub(crate) fn register_block(&self, block: usize, info: ArgumentBlockInfo) {
+        self.blocks.lock().unwrap().insert(block, info);
+    }
+
+    /// Forgets the descriptions of the specified objects (when their arena is dropped).
+    pub(crate) fn forget(&self, objects: &[usize]) {
+        let mut images = self.images.lock().unwrap();
+        let mut buffers = self.buffers.lock().unwrap();
+        let mut buffer_views = self.buffer_views.lock().unwrap();
+        let mut blocks = self.blocks.lock().unwrap();
+        for object in objects {
+            images.remove(object);
+            buffers.remove(object);
+            buffer_views.remove(object);
+            blocks.remove(object);
+        }
+    }
+
+    pub(crate) fn image<B: Backend>(&self, image: &B::Image) -> Option<ImageInfo> {
+        self.images.lock().unwrap().get(&address(image)).cloned()
+    }
+
+    pub(crate) fn buffer<B: Backend>(&self, buffer: &B::Buffer) -> Option<BufferInfo> {
+        self.buffers.lock().unwrap().get(&address(buffer)).cloned()
+    }
+
+    pub(crate) fn block<B: Backend>(&self, block: &B::ArgumentBlock) -> Option<ArgumentBlockInfo> {
+        self.blocks.lock().unwrap().get(&address(block)).cloned()
+    }
+
+    pub(crate) fn describe_descriptor<B: Backend>(
+        &self,
+        descriptor: &Descriptor<B>,
+    ) -> DescriptorInfo {
+        match *descriptor {
+            Descriptor::Sampler { desc } => DescriptorInfo::Sampler { sampler: desc },
+            Descriptor::Texture { image, subresource } => DescriptorInfo::Image {
+                image: self.image::<B>(image),
+                subresource,
+                sampler: None,
+                lod: None,
+                storage: false,
+            },
+            Descriptor::TextureSampler {
+                image,
+                subresource,
+                sampler,
+            } => DescriptorInfo::Image {
+                image: self.image::<B>(image),
+                subresource,
+                sampler: Some(sampler),
+                lod: None,
+                storage: false,
+            },
+            Descriptor::TextureSamplerLod {
+                image,
+                subresource,
+                sampler,
+                lod,
+            } => DescriptorInfo::Image {
+                image: self.image::<B>(image),
+                subresource,
+                sampler: Some(sampler),
+                lod: Some(lod),
+                storage: false,
+            },
+            Descriptor::RwImage { image, subresource } => DescriptorInfo::Image {
+                image: self.image::<B>(image),
+                subresource,
+                sampler: None,
+                lod: None,
+                storage: true,
+            },
+            Descriptor::ConstantBuffer {
+                buffer,
+                offset,
+                size,
+            } => DescriptorInfo::Buffer {
+                buffer: self.buffer::<B>(buffer),
+                offset,
+                size,
+                storage: false,
+            },
+            Descriptor::RwBuffer {
+                buffer,
+                offset,
+                size,
+            } => DescriptorInfo::Buffer {
+                buffer: self.buffer::<B>(buffer),
+                offset,
+                size,
+                storage: true,
+            },
+            Descriptor::TexelBuffer { view } => DescriptorInfo::TexelBuffer {
+                view: self
+                    .buffer_views
+                    .lock()
+                    .unwrap()
+                    .get(&address(view))
+                    .cloned(),
+            },
+            Descriptor::RwTexelBuffer {
+                buffer,
+                offset,
+                size,
+            } => DescriptorInfo::RwTexelBuffer {
+                buffer: self.buffer::<B>(buffer),
+                offset,
+                size,
+            },
+            Descriptor::HostConstantBuffer { .. } => DescriptorInfo::HostConstantBuffer,
+            Descriptor::Empty => DescriptorInfo::Empty,
+        }
+    }
+
+    pub(crate) fn describe_vertex_buffer<B: Backend>(
+        &self,
+        vertex_buffer: &VertexBufferView<B>,
+    ) -> VertexBufferInfo {
+        VertexBufferInfo {
+            buffer: self.buffer::<B>(vertex_buffer.buffer),
+            offset: vertex_buffer.offset,
+            stride: vertex_buffer.stride,
+        }
+    }
+
+    pub(crate) fn describe_index_buffer<B: Backend>(
+        &self,
+        index_buffer: &IndexBufferView<B>,
+    ) -> IndexBufferInfo {
+        IndexBufferInfo {
+            buffer: self.buffer::<B>(index_buffer.buffer),
+            offset: index_buffer.offset,
+            format: index_buffer.format,
+        }
+    }
+
+    pub(crate) fn describe_attachment<B: Backend>(
+        &self,
+        image: &B::Image,
+        subresource: SubresourceRange,
+    ) -> AttachmentInfo {
+        AttachmentInfo {
+            image: self.image::<B>(image),
+            subresource,
+        }
+    }
+}
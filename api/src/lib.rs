@@ -29,41 +29,98 @@ extern crate log;
 #[cfg(feature = "glm")]
 pub use nalgebra_glm as glm;
 
+mod aliases;
+pub mod allocator;
+pub mod budget;
 pub mod buffer;
+mod cache;
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod clip;
+pub mod collision;
 pub mod command;
 pub mod descriptor;
+pub mod elision;
 pub mod error;
 pub mod format;
+pub mod handle;
 pub mod image;
+#[cfg(feature = "introspection")]
+pub mod introspection;
+pub mod null;
+pub mod pass;
 pub mod pipeline;
 pub mod prelude;
+pub mod query;
+pub mod report;
+pub mod semantics;
+pub mod sink;
+pub mod sortkey;
 pub mod swapchain;
+mod target_size;
 pub mod traits;
 pub mod typedesc;
+mod usage;
 mod util;
 pub mod vertex;
-
-pub use crate::{buffer::*, command::*, descriptor::*, format::*, image::*, util::*};
+pub mod vertex_pool;
+
+pub use crate::{
+    allocator::{ArenaAllocator, ArenaOptions},
+    buffer::*,
+    command::*,
+    descriptor::*,
+    format::*,
+    image::*,
+    util::*,
+};
 
 // re-export macros
 pub use autograph_shader_macros::{
     glsl_compute, glsl_fragment, glsl_geometry, glsl_tess_control, glsl_tess_eval, glsl_vertex,
-    include_glsl, include_glsl_raw,
+    include_glsl, include_glsl_interface, include_glsl_raw, include_glsl_reflection,
 };
 
 use crate::{
+    budget::{
+        image_memory_estimate, Budget, BudgetTracker, BudgetUsage, BudgetWarning, UploadQueue,
+    },
+    cache::ObjectCache,
+    clip::ClipSpaceInfo,
+    collision::{BlockTargets, CollisionChecker, SortKeyCollision},
+    elision::{
+        collect_fullscreen_draws, covers_cleared_image, BlockRenderTargets, ClearElision,
+        ClearElisionStats,
+    },
     pipeline::{
-        ArgumentBlock, Arguments, BareArgumentBlock, GraphicsPipeline, GraphicsPipelineCreateInfo,
-        GraphicsShaderStages, ReflectedShader, Scissor, ShaderModule, ShaderStageFlags, Signature,
-        SignatureDescription, TypedSignature, Viewport,
+        validate::{validate_compute_pipeline, validate_graphics_pipeline, ValidationError},
+        ArgumentBlock, Arguments, BareArgumentBlock, ColorBlendAttachments, ComputePipeline,
+        GraphicsPipeline, GraphicsPipelineCreateInfo, GraphicsShaderStages, PipelineRef,
+        ReflectedShader, Scissor, ShaderModule, ShaderStageFlags, Signature, SignatureDescription,
+        TypedSignature, Viewport, Viewports,
     },
+    query::OcclusionQuery,
+    report::{FrameReport, LifetimeReport, PipelineCompileTime, SubmitStats, WarmUpReport},
+    semantics::ResourceSemantics,
     swapchain::Swapchain,
+    target_size::{min_size, TargetSizes},
+    usage::UsageTracker,
     vertex::{IndexBufferView, VertexBufferView},
 };
 use autograph_spirv::DroplessArena;
+use log::{info, warn};
 use std::{
-    any::TypeId, collections::HashMap, fmt::Debug, hash::Hash, marker::PhantomData, mem,
-    sync::Mutex,
+    any::TypeId,
+    collections::HashMap,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Instant,
 };
 
 //--------------------------------------------------------------------------------------------------
@@ -107,13 +164,87 @@ impl AliasScope {
         let m = self.mask & other.mask;
         (self.value & m) == (other.value & m)
     }
+
+    /// Returns true if the command with the specified sortkey is inside this scope.
+    pub fn contains(&self, sortkey: u64) -> bool {
+        (sortkey & self.mask) == self.value
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Class of the graphics API used by a backend, for backends that can run on several.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ApiProfile {
+    /// A desktop API (e.g. OpenGL 4.5+ core).
+    Desktop,
+    /// An API for embedded or web platforms (e.g. OpenGL ES 3 or WebGL 2, possibly through
+    /// ANGLE). Some features are missing or emulated: see the other fields of [DeviceLimits].
+    Embedded,
+}
+
+/// Capabilities and limits of the device, as reported by the backend.
+///
+/// See [Api::limits].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DeviceLimits {
+    /// Maximum width of 1D images.
+    pub max_image_dimension_1d: u32,
+    /// Maximum width and height of 2D images.
+    pub max_image_dimension_2d: u32,
+    /// Maximum width, height and depth of 3D images.
+    pub max_image_dimension_3d: u32,
+    /// Maximum size of the faces of cubemap images.
+    pub max_image_dimension_cube: u32,
+    /// Maximum number of layers in an array image.
+    pub max_image_array_layers: u32,
+    /// Maximum number of color render targets that can be bound at once.
+    pub max_color_attachments: u32,
+    /// Maximum number of samples of multisampled images and render targets.
+    pub max_samples: u32,
+    /// Maximum degree of anisotropy for sampling.
+    pub max_sampler_anisotropy: f32,
+    pub max_viewports: u32,
+    /// Maximum size in bytes of constant buffer ranges.
+    pub max_uniform_buffer_range: u32,
+    /// Required alignment of the offsets of constant buffers.
+    pub min_uniform_buffer_offset_alignment: u32,
+    /// Maximum number of work groups in a compute dispatch, for each dimension.
+    pub max_compute_work_group_count: [u32; 3],
+    /// Range (minimum, maximum) of supported line widths.
+    pub line_width_range: [f32; 2],
+    /// Range (minimum, maximum) of supported point sizes.
+    pub point_size_range: [f32; 2],
+    /// Maximum number of views of multiview pipelines (see [ViewCount](pipeline::ViewCount)).
+    pub max_multiview_view_count: u32,
+    /// Whether multiview rendering is supported natively. Otherwise, it is emulated by
+    /// issuing each draw once per view, and shaders cannot read the index of the view.
+    pub native_multiview: bool,
+    /// Class of the API the backend runs on.
+    pub profile: ApiProfile,
+    /// Whether shader modules can be created from SPIR-V bytecode.
+    pub spirv_shaders: bool,
+}
+
+impl DeviceLimits {
+    /// Returns the maximum extent in any direction of images of the given kind.
+    pub fn max_image_extent(&self, dimensions: &Dimensions) -> u32 {
+        match *dimensions {
+            Dimensions::Dim1d { .. } => self.max_image_dimension_1d,
+            Dimensions::Dim2d { .. } | Dimensions::SwapchainRelative { .. } => {
+                self.max_image_dimension_2d
+            }
+            Dimensions::Dim3d { .. } => self.max_image_dimension_3d,
+            Dimensions::Cubemap { .. } => self.max_image_dimension_cube,
+        }
+    }
 }
 
 //--------------------------------------------------------------------------------------------------
 
 pub trait Instance<B: Backend> {
     /// Creates a new empty Arena.
-    unsafe fn create_arena(&self) -> Box<B::Arena>;
+    unsafe fn create_arena(&self, options: ArenaOptions) -> Box<B::Arena>;
 
     /// Drops an arena and all the objects it owns.
     unsafe fn drop_arena(&self, arena: Box<B::Arena>);
@@ -124,6 +255,25 @@ pub trait Instance<B: Backend> {
     /// See [Renderer::default_swapchain](crate::Renderer::default_swapchain).
     unsafe fn default_swapchain<'a>(&'a self) -> Option<&'a B::Swapchain>;
 
+    /// See [Swapchain::acquire](crate::swapchain::Swapchain::acquire).
+    ///
+    /// Backends without access to the images of the swapchain can return an intermediate
+    /// image, copied to the swapchain on presentation.
+    unsafe fn acquire_swapchain_image<'a>(
+        &self,
+        arena: &'a B::Arena,
+        swapchain: &'a B::Swapchain,
+    ) -> &'a B::Image;
+
+    /// See [Api::limits].
+    fn limits(&self) -> DeviceLimits;
+
+    /// See [Api::clip_space_info].
+    fn clip_space_info(&self) -> ClipSpaceInfo;
+
+    /// See [Api::format_properties].
+    fn format_properties(&self, format: Format) -> FormatProperties;
+
     ///
     unsafe fn create_image<'a>(
         &self,
@@ -137,18 +287,41 @@ pub trait Instance<B: Backend> {
         initial_data: Option<&[u8]>,
     ) -> &'a B::Image;
 
-    /// Updates a region of an image.
+    /// Creates an image that is cleared with `clear_value` at the start of its alias scope,
+    /// in each frame. See [Arena::create_image_cleared].
+    ///
+    /// Backends should defer the clear until the first command of the frame that is inside
+    /// the scope, and skip it in frames without such commands.
+    unsafe fn create_image_cleared<'a>(
+        &self,
+        arena: &'a B::Arena,
+        scope: AliasScope,
+        format: Format,
+        dimensions: Dimensions,
+        mipcount: MipmapsOption,
+        samples: u32,
+        usage: ImageUsageFlags,
+        clear_value: ClearValue,
+    ) -> &'a B::Image;
+
+    /// Updates a region of a mipmap level of an image.
     ///
     /// This function assumes that the format of data matches the internal format of the image.
     /// No conversion is performed.
     unsafe fn update_image(
         &self,
         image: &B::Image,
+        mip_level: u32,
         min_extent: (u32, u32, u32),
         max_extent: (u32, u32, u32),
         data: &[u8],
     );
 
+    /// Updates several regions of an image. See [Api::update_image_batch].
+    ///
+    /// Panics if a region is outside the bounds of its mipmap level.
+    unsafe fn update_image_regions(&self, image: &B::Image, regions: &[ImageRegionUpdate]);
+
     /// TODO
     unsafe fn create_immutable_buffer<'a>(
         &self,
@@ -160,6 +333,23 @@ pub trait Instance<B: Backend> {
     /// TODO
     unsafe fn create_buffer<'a>(&self, arena: &'a B::Arena, size: u64) -> &'a B::Buffer;
 
+    /// Writes `data` at the byte offset `offset` of a buffer created with `create_buffer`.
+    /// See [Api::update_buffer].
+    ///
+    /// Panics if the range is outside the buffer.
+    unsafe fn update_buffer(&self, buffer: &B::Buffer, offset: usize, data: &[u8]);
+
+    /// Creates a view of a range of a buffer as an array of texels of the specified format.
+    /// See [Arena::create_buffer_view].
+    unsafe fn create_buffer_view<'a>(
+        &self,
+        arena: &'a B::Arena,
+        buffer: &'a B::Buffer,
+        format: Format,
+        offset: usize,
+        size: Option<usize>,
+    ) -> &'a B::BufferView;
+
     unsafe fn create_shader_module<'a>(
         &self,
         arena: &'a B::Arena,
@@ -175,6 +365,19 @@ pub trait Instance<B: Backend> {
         create_info: &GraphicsPipelineCreateInfo<'a, '_, B>,
     ) -> &'a B::GraphicsPipeline;
 
+    unsafe fn create_compute_pipeline<'a>(
+        &self,
+        arena: &'a B::Arena,
+        root_signature: &'a B::Signature,
+        root_signature_description: &SignatureDescription,
+        shader: &'a B::ShaderModule,
+    ) -> &'a B::ComputePipeline;
+
+    /// Completes the compilation of pipelines before their first use. See [Api::warm_up].
+    ///
+    /// Returns the compilation time of each pipeline, in the same order.
+    unsafe fn warm_up_pipelines(&self, pipelines: &[PipelineRef<B>]) -> Vec<PipelineCompileTime>;
+
     unsafe fn create_signature<'a>(
         &'a self,
         arena: &'a B::Arena,
@@ -194,6 +397,7 @@ pub trait Instance<B: Backend> {
         depth_stencil_target: Option<DepthStencilView<'a, B>>,
         viewports: impl IntoIterator<Item = Viewport>,
         scissors: impl IntoIterator<Item = Scissor>,
+        stencil_reference: Option<u32>,
     ) -> &'a B::ArgumentBlock;
 
     unsafe fn create_host_reference<'a>(
@@ -202,11 +406,38 @@ pub trait Instance<B: Backend> {
         data: &'a [u8],
     ) -> &'a B::HostReference;
 
+    /// See [Arena::host_readback].
+    unsafe fn create_host_readback<'a>(
+        &self,
+        arena: &'a B::Arena,
+        dest: &'a mut [u8],
+    ) -> &'a B::HostReadback;
+
+    /// See [Arena::create_occlusion_query].
+    unsafe fn create_occlusion_query<'a>(&self, arena: &'a B::Arena) -> &'a B::Query;
+
     /// Sends commands to the GPU for execution, and ends the current frame.
     /// Uploads all referenced host data to the GPU and releases the borrows.
     ///
     /// Precondition: the command list should be sorted by sortkey.
-    unsafe fn submit_frame<'a>(&self, commands: &[Command<'a, B>]);
+    ///
+    /// Returns the time spent waiting on the GPU. Backends that cannot measure it return
+    /// `SubmitStats::default()`.
+    unsafe fn submit_frame<'a>(&self, commands: &[Command<'a, B>]) -> SubmitStats;
+
+    /// Same as `submit_frame`, but also collects statistics about the submission in `report`.
+    ///
+    /// The default implementation only measures the time spent in `submit_frame`.
+    unsafe fn submit_frame_with_report<'a>(
+        &self,
+        commands: &[Command<'a, B>],
+        report: &mut FrameReport,
+    ) -> SubmitStats {
+        let start = Instant::now();
+        let stats = self.submit_frame(commands);
+        report.submit_time = start.elapsed();
+        stats
+    }
 }
 
 /// Trait implemented by renderer backends.
@@ -227,18 +458,25 @@ pub trait Backend:
     type Instance: Instance<Self>;
     type Arena;
     type Swapchain: Sync + Debug + traits::Swapchain;
-    type Image: Sync + Debug;
+    type Image: Sync + Debug + traits::Image;
     type Buffer: Sync + Debug;
+    type BufferView: Sync + Debug;
     type ShaderModule: Sync + Debug;
     type GraphicsPipeline: Sync + Debug;
+    type ComputePipeline: Sync + Debug;
     type Signature: Sync + Debug;
     type ArgumentBlock: Sync + Debug;
     type HostReference: Sync + Debug;
+    type HostReadback: Sync + Debug;
+    type Query: Sync + Debug;
 }
 
 /// Dummy backend for testing purposes.
 ///
 /// Should this be in render-test?
+///
+/// All functions of its instance panic: see [null::NullBackend] for a backend that accepts all
+/// calls.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct DummyBackend;
 
@@ -249,6 +487,16 @@ impl traits::Swapchain for DummySwapchain {
     fn size(&self) -> (u32, u32) {
         unimplemented!()
     }
+
+    fn color_space(&self) -> ColorSpace {
+        unimplemented!()
+    }
+}
+
+impl traits::Image for () {
+    fn dimensions(&self) -> Dimensions {
+        unimplemented!()
+    }
 }
 
 impl Backend for DummyBackend {
@@ -257,11 +505,15 @@ impl Backend for DummyBackend {
     type Swapchain = DummySwapchain;
     type Image = ();
     type Buffer = ();
+    type BufferView = ();
     type ShaderModule = ();
     type GraphicsPipeline = ();
+    type ComputePipeline = ();
     type Signature = ();
     type ArgumentBlock = ();
     type HostReference = ();
+    type HostReadback = ();
+    type Query = ();
 }
 
 /// Dummy instance for testing purposes.
@@ -270,7 +522,7 @@ impl Backend for DummyBackend {
 pub struct DummyInstance;
 
 impl Instance<DummyBackend> for DummyInstance {
-    unsafe fn create_arena(&self) -> Box<()> {
+    unsafe fn create_arena(&self, _options: ArenaOptions) -> Box<()> {
         unimplemented!()
     }
 
@@ -286,6 +538,26 @@ impl Instance<DummyBackend> for DummyInstance {
         unimplemented!()
     }
 
+    unsafe fn acquire_swapchain_image<'a>(
+        &self,
+        _arena: &'a (),
+        _swapchain: &'a DummySwapchain,
+    ) -> &'a () {
+        unimplemented!()
+    }
+
+    fn limits(&self) -> DeviceLimits {
+        unimplemented!()
+    }
+
+    fn clip_space_info(&self) -> ClipSpaceInfo {
+        unimplemented!()
+    }
+
+    fn format_properties(&self, _format: Format) -> FormatProperties {
+        unimplemented!()
+    }
+
     unsafe fn create_image<'a>(
         &self,
         _arena: &'a (),
@@ -300,9 +572,24 @@ impl Instance<DummyBackend> for DummyInstance {
         unimplemented!()
     }
 
+    unsafe fn create_image_cleared<'a>(
+        &self,
+        _arena: &'a (),
+        _scope: AliasScope,
+        _format: Format,
+        _dimensions: Dimensions,
+        _mipcount: MipmapsOption,
+        _samples: u32,
+        _usage: ImageUsageFlags,
+        _clear_value: ClearValue,
+    ) -> &'a () {
+        unimplemented!()
+    }
+
     unsafe fn update_image(
         &self,
         _image: &(),
+        _mip_level: u32,
         _min_extent: (u32, u32, u32),
         _max_extent: (u32, u32, u32),
         _data: &[u8],
@@ -310,6 +597,10 @@ impl Instance<DummyBackend> for DummyInstance {
         unimplemented!()
     }
 
+    unsafe fn update_image_regions(&self, _image: &(), _regions: &[ImageRegionUpdate]) {
+        unimplemented!()
+    }
+
     unsafe fn create_immutable_buffer<'a>(
         &self,
         _arena: &'a (),
@@ -323,6 +614,21 @@ impl Instance<DummyBackend> for DummyInstance {
         unimplemented!()
     }
 
+    unsafe fn update_buffer(&self, _buffer: &(), _offset: usize, _data: &[u8]) {
+        unimplemented!()
+    }
+
+    unsafe fn create_buffer_view<'a>(
+        &self,
+        _arena: &'a (),
+        _buffer: &'a (),
+        _format: Format,
+        _offset: usize,
+        _size: Option<usize>,
+    ) -> &'a () {
+        unimplemented!()
+    }
+
     unsafe fn create_shader_module<'a>(
         &self,
         _arena: &'a (),
@@ -342,6 +648,23 @@ impl Instance<DummyBackend> for DummyInstance {
         unimplemented!()
     }
 
+    unsafe fn create_compute_pipeline<'a>(
+        &self,
+        _arena: &'a (),
+        _root_signature: &'a (),
+        _root_signature_description: &SignatureDescription,
+        _shader: &'a (),
+    ) -> &'a () {
+        unimplemented!()
+    }
+
+    unsafe fn warm_up_pipelines(
+        &self,
+        _pipelines: &[PipelineRef<DummyBackend>],
+    ) -> Vec<PipelineCompileTime> {
+        unimplemented!()
+    }
+
     unsafe fn create_signature<'a>(
         &'a self,
         _arena: &'a (),
@@ -363,6 +686,7 @@ impl Instance<DummyBackend> for DummyInstance {
         _depth_stencil_render_target: Option<DepthStencilView<'a, DummyBackend>>,
         _viewports: impl IntoIterator<Item = Viewport>,
         _scissors: impl IntoIterator<Item = Scissor>,
+        _stencil_reference: Option<u32>,
     ) -> &'a () {
         unimplemented!()
     }
@@ -371,7 +695,15 @@ impl Instance<DummyBackend> for DummyInstance {
         unimplemented!()
     }
 
-    unsafe fn submit_frame<'a>(&self, _commands: &[Command<'a, DummyBackend>]) {
+    unsafe fn create_host_readback<'a>(&self, _arena: &'a (), _dest: &'a mut [u8]) -> &'a () {
+        unimplemented!()
+    }
+
+    unsafe fn create_occlusion_query<'a>(&self, _arena: &'a ()) -> &'a () {
+        unimplemented!()
+    }
+
+    unsafe fn submit_frame<'a>(&self, _commands: &[Command<'a, DummyBackend>]) -> SubmitStats {
         unimplemented!()
     }
 }
@@ -404,10 +736,37 @@ pub struct Arena<'r, B: Backend> {
     /// so there is some duplication between frontend and backend...
     /// Maybe pass a reference to the dropless arena to the backend at the same time?
     pub(crate) misc: DroplessArena,
+    /// Images created with `ImageUsageFlags::INFER` in this arena
+    inferred_images: Mutex<Vec<usize>>,
+    /// Argument blocks of this arena registered for the sort key collision checks
+    collision_blocks: Mutex<Vec<usize>>,
+    /// Argument blocks of this arena with render targets, for the validation of static viewports
+    target_size_blocks: Mutex<Vec<usize>>,
+    /// Argument blocks of this arena registered for the elision of redundant clears
+    elision_blocks: Mutex<Vec<usize>>,
+    /// Images, buffers and argument blocks of this arena recorded for introspection
+    #[cfg(feature = "introspection")]
+    introspected: Mutex<Vec<usize>>,
 }
 
 impl<'r, B: Backend> Drop for Arena<'r, B> {
     fn drop(&mut self) {
+        self.renderer
+            .usage_tracker
+            .forget(&self.inferred_images.lock().unwrap());
+        self.renderer
+            .collision_checker
+            .forget(&self.collision_blocks.lock().unwrap());
+        self.renderer
+            .target_sizes
+            .forget(&self.target_size_blocks.lock().unwrap());
+        self.renderer
+            .clear_elision
+            .forget(&self.elision_blocks.lock().unwrap());
+        #[cfg(feature = "introspection")]
+        self.renderer
+            .introspection
+            .forget(&self.introspected.lock().unwrap());
         unsafe { self.instance.drop_arena(self.inner.take().unwrap()) }
     }
 }
@@ -417,6 +776,11 @@ impl<'r, B: Backend> Arena<'r, B> {
         self.inner.as_ref().unwrap()
     }
 
+    /// Returns the API instance that created this arena.
+    pub fn api(&self) -> &'r Api<B> {
+        self.renderer
+    }
+
     /// Creates a swapchain.
     #[inline]
     pub fn create_swapchain(&self) -> Swapchain<B> {
@@ -469,25 +833,73 @@ impl<'r, B: Backend> Arena<'r, B> {
         }
     }
 
+    /// Shorthand to create a `GraphicsShaderStages` object with only a vertex shader, for
+    /// depth-only pipelines (e.g. shadow maps).
+    #[inline]
+    pub fn create_vertex_shader_stages<'a, 're>(
+        &'a self,
+        vertex_shader: ReflectedShader<'_, 're>,
+    ) -> GraphicsShaderStages<'a, 're, B> {
+        assert_eq!(
+            vertex_shader.reflection.stage,
+            ShaderStageFlags::VERTEX,
+            "invalid shader stage"
+        );
+        GraphicsShaderStages::new_vertex_only(self.create_shader_module(vertex_shader))
+    }
+
     /// Creates a graphics pipeline given the pipeline description passed in create_info
     /// and information derived from the pipeline interface type.
+    ///
+    /// Panics if the pipeline fails validation against the signature of `P`. See
+    /// [try_create_graphics_pipeline] for a non-panicking version.
     pub fn create_graphics_pipeline<'a, P: Arguments<'a, B>>(
         &'a self,
         create_info: &GraphicsPipelineCreateInfo<'a, '_, B>,
     ) -> GraphicsPipeline<'a, B, TypedSignature<'a, B, P>> {
+        match self.try_create_graphics_pipeline::<P>(create_info) {
+            Ok(pipeline) => pipeline,
+            Err(errors) => {
+                let messages: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+                panic!(
+                    "graphics pipeline validation failed:\n{}",
+                    messages.join("\n")
+                )
+            }
+        }
+    }
+
+    /// Creates a graphics pipeline, or returns the list of validation errors if the shader
+    /// interfaces do not match the signature of `P`.
+    pub fn try_create_graphics_pipeline<'a, P: Arguments<'a, B>>(
+        &'a self,
+        create_info: &GraphicsPipelineCreateInfo<'a, '_, B>,
+    ) -> Result<GraphicsPipeline<'a, B, TypedSignature<'a, B, P>>, Vec<ValidationError>> {
+        validate_graphics_pipeline(P::SIGNATURE, &create_info, &self.renderer.limits())?;
         let root_signature = self.renderer.get_cached_signature::<P>();
 
-        // validate the pipeline
-        /*let validation_result =
-            validate_spirv_graphics_pipeline(root_signature.description(), &create_info);
-        if let Err(es) = validation_result {
-            for e in es {
-                log::error!("validation error: {}", e);
-            }
-            panic!("graphics pipeline validation failed");
-        }*/
+        // apply per-render-target blend state overrides specified in the signature
+        let mut create_info = *create_info;
+        let mut fragment_outputs = Vec::new();
+        P::SIGNATURE.collect_fragment_outputs(&mut fragment_outputs);
+        if fragment_outputs.iter().any(|output| output.blend.is_some()) {
+            let color_blend_state = create_info.color_blend_state;
+            let attachments = self
+                .misc
+                .alloc_extend(fragment_outputs.iter().enumerate().map(|(i, output)| {
+                    output
+                        .blend
+                        .unwrap_or(color_blend_state.attachment(i).unwrap_or_default())
+                }));
+            create_info.color_blend_state.attachments =
+                ColorBlendAttachments::Separate(attachments);
+        }
+        let viewports: &[Viewport] = match create_info.viewport_state.viewports {
+            Viewports::Static(viewports) => self.misc.alloc_extend(viewports.iter().cloned()),
+            Viewports::Dynamic => &[],
+        };
 
-        GraphicsPipeline {
+        Ok(GraphicsPipeline {
             inner: unsafe {
                 self.instance.create_graphics_pipeline(
                     self.inner(),
@@ -497,9 +909,59 @@ impl<'r, B: Backend> Arena<'r, B> {
                 )
             },
             signature: root_signature,
+            dynamic_state: create_info.dynamic_state(),
+            viewports,
+            overwrites_render_targets: create_info.overwrites_render_targets(),
+        })
+    }
+
+    /// Creates a compute pipeline from a compute shader and information derived from the
+    /// pipeline interface type.
+    ///
+    /// Panics if the shader fails validation against the signature of `P`. See
+    /// [try_create_compute_pipeline] for a non-panicking version.
+    pub fn create_compute_pipeline<'a, P: Arguments<'a, B>>(
+        &'a self,
+        shader: ShaderModule<'a, '_, B>,
+    ) -> ComputePipeline<'a, B, TypedSignature<'a, B, P>> {
+        match self.try_create_compute_pipeline::<P>(shader) {
+            Ok(pipeline) => pipeline,
+            Err(errors) => {
+                let messages: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+                panic!(
+                    "compute pipeline validation failed:\n{}",
+                    messages.join("\n")
+                )
+            }
         }
     }
 
+    /// Creates a compute pipeline, or returns the list of validation errors if the shader
+    /// interface does not match the signature of `P`.
+    pub fn try_create_compute_pipeline<'a, P: Arguments<'a, B>>(
+        &'a self,
+        shader: ShaderModule<'a, '_, B>,
+    ) -> Result<ComputePipeline<'a, B, TypedSignature<'a, B, P>>, Vec<ValidationError>> {
+        assert_eq!(
+            shader.reflection.stage,
+            ShaderStageFlags::COMPUTE,
+            "invalid shader stage"
+        );
+        validate_compute_pipeline(P::SIGNATURE, shader.reflection)?;
+        let root_signature = self.renderer.get_cached_signature::<P>();
+        Ok(ComputePipeline {
+            inner: unsafe {
+                self.instance.create_compute_pipeline(
+                    self.inner(),
+                    root_signature.0,
+                    P::SIGNATURE,
+                    shader.module,
+                )
+            },
+            signature: root_signature,
+        })
+    }
+
     /// Creates an image.
     ///
     /// If `scope` is not `AliasScope::no_alias()`, the image is considered _aliasable_, meaning
@@ -513,6 +975,24 @@ impl<'r, B: Backend> Arena<'r, B> {
     /// The first operation that depends on the image will block until the initial data upload
     /// is complete.
     ///
+    /// If `dimensions` is [Dimensions::SwapchainRelative], the size of the image is computed
+    /// from the size of the default swapchain, and is updated on each frame submission
+    /// if the swapchain was resized (aliasable images included). The contents of the image are
+    /// lost when that happens, so `initial_data` must be `None`. Argument blocks and framebuffers
+    /// that refer to the image are not updated: they should be created in a per-frame arena.
+    /// Backends that cannot resize images (WebGPU) keep the size at creation: create
+    /// swapchain-relative images in a per-frame arena with these backends.
+    ///
+    /// If `usage` contains [ImageUsageFlags::INFER], the image is created with all the usages
+    /// supported by its format, and its actual usage is collected from the argument blocks and
+    /// commands that refer to it until the end of the first frame in which it is used.
+    /// Uses that were not seen during that frame panic afterwards.
+    /// Intended for prototypes: specifying the usage allows the backend to pick a more efficient
+    /// representation of the image.
+    ///
+    /// Panics if the dimensions or the number of samples exceed the [DeviceLimits], or if
+    /// `usage` contains `STORAGE` and `format` is a sRGB format.
+    ///
     /// See also [AliasScope].
     #[inline]
     pub fn create_image(
@@ -525,22 +1005,284 @@ impl<'r, B: Backend> Arena<'r, B> {
         usage: ImageUsageFlags,
         initial_data: Option<&[u8]>,
     ) -> UnsafeImage<B> {
-        UnsafeImage {
-            image: unsafe {
-                self.instance.create_image(
-                    self.inner(),
-                    scope,
-                    format,
-                    dimensions,
-                    mipcount,
-                    samples,
-                    usage,
-                    initial_data,
-                )
-            },
+        let infer = usage.contains(ImageUsageFlags::INFER);
+        let usage = self.check_image_create_info(format, &dimensions, samples, usage);
+        let image = unsafe {
+            self.instance.create_image(
+                self.inner(),
+                scope,
+                format,
+                dimensions,
+                mipcount,
+                samples,
+                usage,
+                initial_data,
+            )
+        };
+        if infer {
+            self.register_inferred_image(image, format, dimensions);
+        }
+        self.introspect_image(image, format, dimensions, mipcount, samples, usage);
+        self.record_image_budget(format, dimensions, mipcount, samples, initial_data);
+        UnsafeImage { image }
+    }
+
+    /// Same as [create_image](Arena::create_image), but the image is cleared with `clear_value`
+    /// at the start of its alias scope, in each frame in which a command is inside the scope.
+    ///
+    /// This avoids reading uninitialized memory from transient images that share memory with
+    /// other images. The clear is performed by the backend before the first command of the
+    /// frame inside the scope.
+    ///
+    /// Panics if `clear_value` is a color value for an image with a depth or stencil format,
+    /// or the reverse, and in the same cases as `create_image`.
+    pub fn create_image_cleared(
+        &self,
+        scope: AliasScope,
+        format: Format,
+        dimensions: Dimensions,
+        mipcount: MipmapsOption,
+        samples: u32,
+        usage: ImageUsageFlags,
+        clear_value: ClearValue,
+    ) -> UnsafeImage<B> {
+        let is_depth_stencil = format.get_format_info().is_depth_stencil();
+        match clear_value {
+            ClearValue::Color(_) => assert!(
+                !is_depth_stencil,
+                "cannot clear an image with a depth or stencil format ({:?}) with a color",
+                format
+            ),
+            ClearValue::DepthStencil { .. } => assert!(
+                is_depth_stencil,
+                "cannot clear an image with a color format ({:?}) with a depth value",
+                format
+            ),
+        }
+        let infer = usage.contains(ImageUsageFlags::INFER);
+        let usage = self.check_image_create_info(format, &dimensions, samples, usage);
+        let image = unsafe {
+            self.instance.create_image_cleared(
+                self.inner(),
+                scope,
+                format,
+                dimensions,
+                mipcount,
+                samples,
+                usage,
+                clear_value,
+            )
+        };
+        self.introspect_image(image, format, dimensions, mipcount, samples, usage);
+        self.record_image_budget(format, dimensions, mipcount, samples, None);
+        if infer {
+            self.register_inferred_image(image, format, dimensions);
+            // cleared like a render target
+            let usage = if is_depth_stencil {
+                ImageUsageFlags::DEPTH_ATTACHMENT
+            } else {
+                ImageUsageFlags::COLOR_ATTACHMENT
+            };
+            self.renderer.usage_tracker.record(address(image), usage);
+        }
+        UnsafeImage { image }
+    }
+
+    /// Validates the parameters of an image, and returns its actual usage
+    /// (see [ImageUsageFlags::INFER]).
+    fn check_image_create_info(
+        &self,
+        format: Format,
+        dimensions: &Dimensions,
+        samples: u32,
+        usage: ImageUsageFlags,
+    ) -> ImageUsageFlags {
+        assert!(
+            !(format.color_space() == ColorSpace::Srgb && usage.contains(ImageUsageFlags::STORAGE)),
+            "images with a sRGB format ({:?}) cannot be used as storage images",
+            format
+        );
+        let limits = self.renderer.limits();
+        assert!(
+            samples <= limits.max_samples,
+            "unsupported sample count: {} (maximum is {})",
+            samples,
+            limits.max_samples
+        );
+        if !dimensions.is_swapchain_relative() {
+            let (w, h, d) = dimensions.width_height_depth();
+            let max_extent = limits.max_image_extent(dimensions);
+            assert!(
+                w.max(h).max(d) <= max_extent,
+                "image dimensions {:?} exceed the maximum image size ({})",
+                dimensions,
+                max_extent
+            );
+            assert!(
+                dimensions.array_layers() <= limits.max_image_array_layers,
+                "image dimensions {:?} exceed the maximum number of array layers ({})",
+                dimensions,
+                limits.max_image_array_layers
+            );
+        }
+        if usage.contains(ImageUsageFlags::INFER) {
+            image::supported_usage(format, ImageUsageFlags::default())
+        } else {
+            usage
+        }
+    }
+
+    /// Starts the inference of the usage of an image created with [ImageUsageFlags::INFER].
+    fn register_inferred_image(&self, image: &B::Image, format: Format, dimensions: Dimensions) {
+        let addr = address(image);
+        self.renderer
+            .usage_tracker
+            .register(addr, format, dimensions);
+        self.inferred_images.lock().unwrap().push(addr);
+    }
+
+    /// Counts the memory and the initial data of an image against the budget of the frame.
+    fn record_image_budget(
+        &self,
+        format: Format,
+        dimensions: Dimensions,
+        mipmaps: MipmapsOption,
+        samples: u32,
+        initial_data: Option<&[u8]>,
+    ) {
+        let dimensions = if dimensions.is_swapchain_relative() {
+            let size = unsafe { self.instance.default_swapchain() }
+                .map_or((1, 1), traits::Swapchain::size);
+            dimensions.resolve(size)
+        } else {
+            dimensions
+        };
+        let budget = &self.renderer.budget;
+        budget.record_transient(image_memory_estimate(format, dimensions, mipmaps, samples));
+        if let Some(data) = initial_data {
+            budget.record_upload(data.len() as u64);
         }
     }
 
+    /// Counts the memory and the initial data of a buffer against the budget of the frame.
+    fn record_buffer_budget(&self, size: u64, upload_bytes: u64) {
+        let budget = &self.renderer.budget;
+        budget.record_transient(size);
+        budget.record_upload(upload_bytes);
+    }
+
+    /// Records the properties of an image for introspection.
+    #[cfg(feature = "introspection")]
+    fn introspect_image(
+        &self,
+        image: &B::Image,
+        format: Format,
+        dimensions: Dimensions,
+        mipmaps: MipmapsOption,
+        samples: u32,
+        usage: ImageUsageFlags,
+    ) {
+        let addr = address(image);
+        self.renderer.introspection.register_image(
+            addr,
+            introspection::ImageInfo {
+                format,
+                dimensions,
+                mipmaps,
+                samples,
+                usage,
+            },
+        );
+        self.introspected.lock().unwrap().push(addr);
+    }
+
+    #[cfg(not(feature = "introspection"))]
+    fn introspect_image(
+        &self,
+        _image: &B::Image,
+        _format: Format,
+        _dimensions: Dimensions,
+        _mipmaps: MipmapsOption,
+        _samples: u32,
+        _usage: ImageUsageFlags,
+    ) {
+    }
+
+    /// Records the size of a buffer for introspection.
+    #[cfg(feature = "introspection")]
+    fn introspect_buffer(&self, buffer: &B::Buffer, size: u64) {
+        let addr = address(buffer);
+        self.renderer
+            .introspection
+            .register_buffer(addr, introspection::BufferInfo { size });
+        self.introspected.lock().unwrap().push(addr);
+    }
+
+    #[cfg(not(feature = "introspection"))]
+    fn introspect_buffer(&self, _buffer: &B::Buffer, _size: u64) {}
+
+    /// Same as [create_image](Arena::create_image), but replaces the format with one of its
+    /// [fallback formats](Format::fallback_formats) if the device does not support it natively
+    /// for the specified usage. Returns the image and its actual format.
+    ///
+    /// The substitution is logged. Initial data is converted to the new format.
+    /// If no fallback format is supported, an emulated format is used as-is.
+    ///
+    /// Panics if neither the format nor any of its fallbacks can be used.
+    pub fn create_image_compatible(
+        &self,
+        scope: AliasScope,
+        format: Format,
+        dimensions: Dimensions,
+        mipcount: MipmapsOption,
+        samples: u32,
+        usage: ImageUsageFlags,
+        initial_data: Option<&[u8]>,
+    ) -> (UnsafeImage<B>, Format) {
+        let is_native = |format| {
+            let properties = self.renderer.format_properties(format);
+            properties.supports(usage) && !properties.emulated
+        };
+
+        let actual_format = if is_native(format) {
+            format
+        } else if let Some(&fallback) = format.fallback_formats().iter().find(|&&f| is_native(f)) {
+            info!(
+                "format {:?} is not supported natively for {:?}, using {:?} instead",
+                format, usage, fallback
+            );
+            fallback
+        } else if self.renderer.format_properties(format).supports(usage) {
+            warn!(
+                "format {:?} is emulated and no fallback format is supported for {:?}",
+                format, usage
+            );
+            format
+        } else {
+            panic!("unsupported image format {:?} for {:?}", format, usage)
+        };
+
+        let converted_data;
+        let initial_data = if actual_format != format {
+            converted_data =
+                initial_data.map(|data| expand_components(format, actual_format, data));
+            converted_data.as_ref().map(|data| &data[..])
+        } else {
+            initial_data
+        };
+
+        let image = self.create_image(
+            scope,
+            actual_format,
+            dimensions,
+            mipcount,
+            samples,
+            usage,
+            initial_data,
+        );
+        (image, actual_format)
+    }
+
     #[inline]
     pub fn image_1d<'a>(
         &'a self,
@@ -609,19 +1351,66 @@ impl<'r, B: Backend> Arena<'r, B> {
         })
     }
 
+    /// Creates an occlusion query, to be used as a predicate for conditional rendering.
+    pub fn create_occlusion_query(&self) -> OcclusionQuery<B> {
+        OcclusionQuery(unsafe { self.instance.create_occlusion_query(self.inner()) })
+    }
+
     /// Creates a GPU (device local) buffer.
     #[inline]
     pub fn create_buffer_typeless(&self, size: u64) -> BufferTypeless<B> {
-        BufferTypeless(unsafe { self.instance.create_buffer(&self.inner(), size) })
+        let buffer = unsafe { self.instance.create_buffer(&self.inner(), size) };
+        self.introspect_buffer(buffer, size);
+        self.record_buffer_budget(size, 0);
+        BufferTypeless(buffer)
     }
 
     /// Creates a GPU (device local) buffer.
     #[inline]
     pub fn create_immutable_buffer_typeless(&self, size: u64, data: &[u8]) -> BufferTypeless<B> {
-        BufferTypeless(unsafe {
+        let buffer = unsafe {
             self.instance
                 .create_immutable_buffer(self.inner(), size, data)
-        })
+        };
+        self.introspect_buffer(buffer, size);
+        self.record_buffer_budget(size, data.len() as u64);
+        BufferTypeless(buffer)
+    }
+
+    /// Creates a view of a buffer as an array of texels of the specified format (a texel buffer),
+    /// which can be bound with `#[argument(texel_buffer)]`.
+    ///
+    /// `offset` and `size` are in bytes: `None` means up to the end of the buffer.
+    /// Unlike storage buffers, the elements are read with format conversion through
+    /// `texelFetch` in shaders.
+    #[inline]
+    pub fn create_buffer_view<'a, T: BufferData + ?Sized>(
+        &'a self,
+        buffer: Buffer<'a, B, T>,
+        format: Format,
+        offset: usize,
+        size: Option<usize>,
+    ) -> BufferView<'a, B> {
+        let view = unsafe {
+            self.instance
+                .create_buffer_view(self.inner(), buffer.0, format, offset, size)
+        };
+        #[cfg(feature = "introspection")]
+        {
+            let addr = address(view);
+            let registry = &self.renderer.introspection;
+            registry.register_buffer_view(
+                addr,
+                introspection::BufferViewInfo {
+                    buffer: registry.buffer::<B>(buffer.0),
+                    format,
+                    offset,
+                    size,
+                },
+            );
+            self.introspected.lock().unwrap().push(addr);
+        }
+        BufferView { view, format }
     }
 
     /// Creates an immutable, device-local GPU buffer containing an object of type T.
@@ -630,13 +1419,13 @@ impl<'r, B: Backend> Arena<'r, B> {
         let size = mem::size_of::<T>();
         let bytes = unsafe { ::std::slice::from_raw_parts(data as *const T as *const u8, size) };
 
-        Buffer(
-            unsafe {
-                self.instance
-                    .create_immutable_buffer(self.inner(), size as u64, bytes)
-            },
-            PhantomData,
-        )
+        let buffer = unsafe {
+            self.instance
+                .create_immutable_buffer(self.inner(), size as u64, bytes)
+        };
+        self.introspect_buffer(buffer, size as u64);
+        self.record_buffer_budget(size as u64, size as u64);
+        Buffer(buffer, PhantomData)
     }
 
     /// Creates an immutable, device-local GPU buffer containing an array of objects of type T.
@@ -645,20 +1434,61 @@ impl<'r, B: Backend> Arena<'r, B> {
         let size = mem::size_of_val(data);
         let bytes = unsafe { ::std::slice::from_raw_parts(data.as_ptr() as *const u8, size) };
 
-        Buffer(
-            unsafe {
-                self.instance
-                    .create_immutable_buffer(&self.inner(), size as u64, bytes)
-            },
-            PhantomData,
-        )
+        let buffer = unsafe {
+            self.instance
+                .create_immutable_buffer(&self.inner(), size as u64, bytes)
+        };
+        self.introspect_buffer(buffer, size as u64);
+        self.record_buffer_budget(size as u64, size as u64);
+        Buffer(buffer, PhantomData)
     }
 
-    /// Creates an immutable, device-local GPU buffer containing an array of objects of type T.
+    /// Uploads an array of objects in a single immutable GPU buffer, and returns a constant
+    /// buffer view of each element.
+    ///
+    /// The elements are padded to the minimum alignment of constant buffer offsets of the device
+    /// ([DeviceLimits::min_uniform_buffer_offset_alignment]), so that each view can be bound
+    /// independently. This is cheaper than one buffer per element when drawing many objects
+    /// with per-object parameters.
+    pub fn upload_slice_views<'a, T: StructuredBufferData + Copy>(
+        &'a self,
+        data: &[T],
+    ) -> impl Iterator<Item = TypedConstantBufferView<'a, B, T>> + Clone {
+        let elem_size = mem::size_of::<T>();
+        let align = (self.renderer.limits().min_uniform_buffer_offset_alignment as usize).max(1);
+        let stride = (elem_size + align - 1) / align * align;
+
+        let mut bytes = vec![0u8; stride * data.len()];
+        for (i, elem) in data.iter().enumerate() {
+            let elem_bytes =
+                unsafe { ::std::slice::from_raw_parts(elem as *const T as *const u8, elem_size) };
+            bytes[i * stride..i * stride + elem_size].copy_from_slice(elem_bytes);
+        }
+        let buffer = unsafe {
+            self.instance
+                .create_immutable_buffer(self.inner(), bytes.len() as u64, &bytes)
+        };
+        self.introspect_buffer(buffer, bytes.len() as u64);
+        self.record_buffer_budget(bytes.len() as u64, bytes.len() as u64);
+
+        (0..data.len()).map(move |i| TypedConstantBufferView {
+            buffer,
+            offset: i * stride,
+            size: Some(elem_size),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Creates a reference to an object in host memory, which is uploaded to the GPU
+    /// when the frame is submitted.
+    ///
+    /// The returned reference can be used as a constant buffer descriptor
+    /// (`#[argument(descriptor)]`).
     #[inline]
     pub fn host_reference<'a, T: Copy + 'static>(&'a self, data: &'a T) -> HostReference<'a, B, T> {
         let size = mem::size_of::<T>();
         let bytes = unsafe { ::std::slice::from_raw_parts(data as *const T as *const u8, size) };
+        self.renderer.budget.record_upload(size as u64);
 
         HostReference(
             unsafe { self.instance.create_host_reference(self.inner(), bytes) },
@@ -666,11 +1496,13 @@ impl<'r, B: Backend> Arena<'r, B> {
         )
     }
 
-    /// Creates an immutable, device-local GPU buffer containing an array of objects of type T.
+    /// Creates a reference to an array of objects in host memory, which is uploaded to the GPU
+    /// when the frame is submitted.
     #[inline]
     pub fn host_slice<'a, T: Copy + 'static>(&'a self, data: &'a [T]) -> HostReference<'a, B, T> {
         let size = mem::size_of_val(data);
         let bytes = unsafe { ::std::slice::from_raw_parts(data.as_ptr() as *const u8, size) };
+        self.renderer.budget.record_upload(size as u64);
 
         HostReference(
             unsafe { self.instance.create_host_reference(self.inner(), bytes) },
@@ -678,7 +1510,28 @@ impl<'r, B: Backend> Arena<'r, B> {
         )
     }
 
+    /// Creates a destination in host memory for a copy from a GPU buffer
+    /// (see [CommandBuffer::copy_buffer_to_host]).
+    ///
+    /// `dest` is filled during the call to `submit_frame` that executes the copy. It stays
+    /// borrowed as long as the returned object (and the command buffers that refer to it)
+    /// are alive, so that it cannot be read before the copy has happened. The elements must be
+    /// plain-old-data ([Pod]), since they are overwritten with raw bytes from the GPU.
+    #[inline]
+    pub fn host_readback<'a, T: Pod>(&'a self, dest: &'a mut [T]) -> HostReadback<'a, B, T> {
+        let size = mem::size_of_val(dest);
+        let bytes = unsafe { ::std::slice::from_raw_parts_mut(dest.as_mut_ptr() as *mut u8, size) };
+
+        HostReadback(
+            unsafe { self.instance.create_host_readback(self.inner(), bytes) },
+            PhantomData,
+        )
+    }
+
     /// Creates an _argument block_.
+    ///
+    /// `stencil_reference` must be `Some` if and only if the signature has a stencil reference
+    /// (see [SignatureDescription::has_stencil_reference](crate::pipeline::SignatureDescription::has_stencil_reference)).
     pub fn create_argument_block<'a, S: Signature<'a, B>>(
         &'a self,
         signature: S,
@@ -690,22 +1543,180 @@ impl<'r, B: Backend> Arena<'r, B> {
         depth_stencil_target: Option<DepthStencilView<'a, B>>,
         viewports: impl IntoIterator<Item = Viewport>,
         scissors: impl IntoIterator<Item = Scissor>,
+        stencil_reference: Option<u32>,
     ) -> ArgumentBlock<'a, B, S> {
+        // usage of the images created with `ImageUsageFlags::INFER`
+        let tracker = &self.renderer.usage_tracker;
+        let infer = tracker.is_enabled();
+        let descriptors = descriptors.into_iter().inspect(move |d| {
+            if !infer {
+                return;
+            }
+            match *d {
+                Descriptor::Texture { image, .. }
+                | Descriptor::TextureSampler { image, .. }
+                | Descriptor::TextureSamplerLod { image, .. } => {
+                    tracker.record(address(image), ImageUsageFlags::SAMPLED)
+                }
+                Descriptor::RwImage { image, .. } => {
+                    tracker.record(address(image), ImageUsageFlags::STORAGE)
+                }
+                _ => {}
+            }
+        });
+        // render targets and scissors, for the sort key collision checks
+        let collision_checks = self.renderer.collision_checker.is_enabled();
+        let mut targets = BlockTargets::default();
+        if let Some(ref dsv) = depth_stencil_target {
+            if collision_checks {
+                targets.images.push(address(dsv.image));
+            }
+        }
+        // smallest size of the render targets, for the validation of static viewports on draw
+        let size_checks = cfg!(debug_assertions);
+        let target_sizes = &self.renderer.target_sizes;
+        let mut inherited_size = None;
+        let mut target_size = match depth_stencil_target {
+            Some(ref dsv) if size_checks => Some(dsv.size()),
+            _ => None,
+        };
+        // render targets overwritten by fullscreen draws, for the elision of redundant clears
+        let elision = self.renderer.clear_elision.is_enabled();
+        let mut elision_targets = BlockRenderTargets::default();
+        let inherited_blocks = &mut targets.inherited;
+        let elision_inherited = &mut elision_targets.inherited;
+        let inherited_size_ref = &mut inherited_size;
+        let inherited = inherited.into_iter().inspect(move |block| {
+            if collision_checks {
+                inherited_blocks.push(address(block.0))
+            }
+            if elision {
+                elision_inherited.push(address(block.0))
+            }
+            if size_checks {
+                if let Some(size) = target_sizes.size(address(block.0)) {
+                    *inherited_size_ref = Some(min_size(*inherited_size_ref, size));
+                }
+            }
+        });
+        let images = &mut targets.images;
+        let elision_images = &mut elision_targets.images;
+        let target_size_ref = &mut target_size;
+        let render_targets = render_targets.into_iter().inspect(move |rtv| {
+            if infer {
+                tracker.record(address(rtv.image), ImageUsageFlags::COLOR_ATTACHMENT);
+            }
+            if collision_checks {
+                images.push(address(rtv.image))
+            }
+            if elision && covers_cleared_image::<B>(rtv.image, &rtv.subresource) {
+                elision_images.push(address(rtv.image))
+            }
+            if size_checks {
+                *target_size_ref = Some(min_size(*target_size_ref, rtv.size()));
+            }
+        });
+        let scissor_rects = &mut targets.scissors;
+        let scissors = scissors.into_iter().inspect(move |&scissor| {
+            if collision_checks {
+                scissor_rects.push(scissor)
+            }
+        });
+        if let Some(ref dsv) = depth_stencil_target {
+            if infer {
+                tracker.record(address(dsv.image), ImageUsageFlags::DEPTH_ATTACHMENT);
+            }
+        }
+
+        // the arguments are collected to be recorded before they are passed to the backend
+        #[cfg(feature = "introspection")]
+        let (inherited, descriptors, vertex_buffers, render_targets, viewports, scissors) = (
+            inherited.collect::<Vec<_>>(),
+            descriptors.collect::<Vec<_>>(),
+            vertex_buffers.into_iter().collect::<Vec<_>>(),
+            render_targets.collect::<Vec<_>>(),
+            viewports.into_iter().collect::<Vec<_>>(),
+            scissors.collect::<Vec<_>>(),
+        );
+        #[cfg(feature = "introspection")]
+        let introspected = {
+            let registry = &self.renderer.introspection;
+            introspection::ArgumentBlockInfo {
+                inherited: inherited
+                    .iter()
+                    .filter_map(|block| registry.block::<B>(block.0))
+                    .collect(),
+                descriptors: descriptors
+                    .iter()
+                    .map(|d| registry.describe_descriptor(d))
+                    .collect(),
+                vertex_buffers: vertex_buffers
+                    .iter()
+                    .map(|vb| registry.describe_vertex_buffer(vb))
+                    .collect(),
+                index_buffer: index_buffer
+                    .as_ref()
+                    .map(|ib| registry.describe_index_buffer(ib)),
+                render_targets: render_targets
+                    .iter()
+                    .map(|rtv| registry.describe_attachment::<B>(rtv.image, rtv.subresource))
+                    .collect(),
+                depth_stencil_target: depth_stencil_target
+                    .as_ref()
+                    .map(|dsv| registry.describe_attachment::<B>(dsv.image, dsv.subresource)),
+                viewports: viewports.clone(),
+                scissors: scissors.clone(),
+                stencil_reference,
+            }
+        };
+
+        let arguments = unsafe {
+            self.instance.create_argument_block(
+                self.inner(),
+                signature.inner(),
+                inherited,
+                descriptors,
+                vertex_buffers,
+                index_buffer,
+                render_targets,
+                depth_stencil_target,
+                viewports,
+                scissors,
+                stencil_reference,
+            )
+        };
+        if collision_checks {
+            let addr = address(arguments);
+            self.renderer
+                .collision_checker
+                .register_block(addr, targets);
+            self.collision_blocks.lock().unwrap().push(addr);
+        }
+        if elision {
+            let addr = address(arguments);
+            self.renderer
+                .clear_elision
+                .register_block(addr, elision_targets);
+            self.elision_blocks.lock().unwrap().push(addr);
+        }
+        if let Some(size) = inherited_size
+            .map(|size| min_size(target_size, size))
+            .or(target_size)
+        {
+            let addr = address(arguments);
+            self.renderer.target_sizes.register_block(addr, size);
+            self.target_size_blocks.lock().unwrap().push(addr);
+        }
+        #[cfg(feature = "introspection")]
+        {
+            let addr = address(arguments);
+            self.renderer
+                .introspection
+                .register_block(addr, introspected);
+            self.introspected.lock().unwrap().push(addr);
+        }
         ArgumentBlock {
-            arguments: unsafe {
-                self.instance.create_argument_block(
-                    self.inner(),
-                    signature.inner(),
-                    inherited,
-                    descriptors,
-                    vertex_buffers,
-                    index_buffer,
-                    render_targets,
-                    depth_stencil_target,
-                    viewports,
-                    scissors,
-                )
-            },
+            arguments,
             signature,
         }
     }
@@ -732,6 +1743,31 @@ impl<'r, B: Backend> Arena<'r, B> {
 
 //--------------------------------------------------------------------------------------------------
 
+/// Key of a signature in the cache of signatures by description.
+///
+/// Descriptions are compared ignoring the names of descriptors and vertex attributes.
+#[derive(Clone)]
+struct SignatureKey {
+    description: &'static SignatureDescription<'static>,
+    /// Addresses of the inherited signatures.
+    inherited: Vec<usize>,
+}
+
+impl PartialEq for SignatureKey {
+    fn eq(&self, other: &SignatureKey) -> bool {
+        self.inherited == other.inherited && self.description.eq_ignoring_names(other.description)
+    }
+}
+
+impl Eq for SignatureKey {}
+
+impl Hash for SignatureKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.description.hash_ignoring_names(state);
+        self.inherited.hash(state);
+    }
+}
+
 /// Graphics API trait.
 ///
 /// This is the main interface for interacting with a backend.
@@ -746,57 +1782,370 @@ impl<'r, B: Backend> Arena<'r, B> {
 ///
 /// Note that the final submission order of commands to the GPU is defined by their associated
 /// _sort key_. See (TODO) for more info.
+///
+/// `Api` is `Sync` if the backend instance and arenas are: in this case, pipelines and
+/// signatures can be created concurrently from worker threads, each with its own arena.
 pub struct Api<B: Backend> {
     /// Backend instance
     instance: B::Instance,
     /// Arena for long-lived or cached objects, such as pipeline signatures
     default_arena: Option<Box<B::Arena>>,
-    /// Cache of pipeline signatures
-    signature_cache: Mutex<HashMap<TypeId, *const B::Signature>>,
+    /// Cache of pipeline signatures, allocated in the default arena
+    signature_cache: ObjectCache<TypeId, &'static B::Signature>,
+    /// Signatures by description and inherited signatures, shared by the types with identical
+    /// descriptions
+    signature_description_cache: ObjectCache<SignatureKey, &'static B::Signature>,
+    /// Built-in fallback textures, created on first use in the default arena
+    fallback_textures: ObjectCache<FallbackTexture, &'static B::Image>,
+    /// Cache of utility graphics pipelines, allocated in the default arena
+    pipeline_cache: ObjectCache<(TypeId, TypeId), &'static B::GraphicsPipeline>,
+    /// Whether to collect a `FrameReport` on each submission
+    frame_reports: AtomicBool,
+    /// Report of the last submitted frame
+    last_frame_report: Mutex<Option<FrameReport>>,
+    /// Whether to analyze the lifetimes of transient resources on each submission
+    lifetime_reports: AtomicBool,
+    /// Usage of the images created with `ImageUsageFlags::INFER`
+    usage_tracker: UsageTracker,
+    /// Detection of commands with the same sortkey writing to the same render targets
+    collision_checker: CollisionChecker,
+    /// Sizes of the render targets of argument blocks, in debug builds
+    target_sizes: TargetSizes,
+    /// Elision of the clears overwritten before being read
+    clear_elision: ClearElision,
+    /// Per-frame budgets, and usage of the current frame
+    budget: BudgetTracker,
+    /// Whether the cached graphics pipelines overwrite their render targets (see
+    /// [GraphicsPipelineCreateInfo::overwrites_render_targets]), by key in the pipeline cache
+    cached_pipeline_overwrites: Mutex<HashMap<(TypeId, TypeId), bool>>,
+    /// Descriptions of the resources and argument blocks, for introspection
+    #[cfg(feature = "introspection")]
+    introspection: introspection::Registry,
+    /// Whether to capture the sorted commands on each submission
+    #[cfg(feature = "capture")]
+    command_capture: AtomicBool,
+    /// Commands of the last submitted frame
+    #[cfg(feature = "capture")]
+    last_command_capture: Mutex<Option<capture::CommandCapture>>,
+}
+
+/// Extends the lifetime of an object allocated in the default arena of the renderer, to store it
+/// in one of its caches.
+///
+/// # Safety
+///
+/// The object must not be accessed through the returned reference after the default arena is
+/// dropped. The caches are only accessed through `&Api`, and the default arena is dropped with
+/// the renderer.
+unsafe fn cached<T>(object: &T) -> &'static T {
+    &*(object as *const T)
 }
 
 impl<B: Backend> Api<B> {
     /// Creates a new renderer with the specified backend.
     pub fn new(instance: B::Instance) -> Api<B> {
-        let default_arena = unsafe { instance.create_arena() };
+        let default_arena = unsafe { instance.create_arena(ArenaOptions::default()) };
         Api {
             instance,
             default_arena: Some(default_arena),
-            signature_cache: Mutex::new(HashMap::new()),
+            signature_cache: ObjectCache::new(),
+            signature_description_cache: ObjectCache::new(),
+            fallback_textures: ObjectCache::new(),
+            pipeline_cache: ObjectCache::new(),
+            frame_reports: AtomicBool::new(false),
+            last_frame_report: Mutex::new(None),
+            lifetime_reports: AtomicBool::new(false),
+            usage_tracker: UsageTracker::new(),
+            collision_checker: CollisionChecker::new(),
+            target_sizes: TargetSizes::new(),
+            clear_elision: ClearElision::new(),
+            budget: BudgetTracker::new(),
+            cached_pipeline_overwrites: Mutex::new(HashMap::new()),
+            #[cfg(feature = "introspection")]
+            introspection: introspection::Registry::new(),
+            #[cfg(feature = "capture")]
+            command_capture: AtomicBool::new(false),
+            #[cfg(feature = "capture")]
+            last_command_capture: Mutex::new(None),
         }
     }
 
+    /// Creates an arena with the default options.
     pub fn create_arena(&self) -> Arena<B> {
+        self.create_arena_with_options(ArenaOptions::default())
+    }
+
+    /// Creates an arena with a custom block size or allocator hook.
+    pub fn create_arena_with_options(&self, options: ArenaOptions) -> Arena<B> {
         Arena {
             renderer: self,
             instance: &self.instance,
-            inner: Some(unsafe { self.instance.create_arena() }),
+            inner: Some(unsafe { self.instance.create_arena(options) }),
             misc: DroplessArena::new(),
+            inferred_images: Mutex::new(Vec::new()),
+            collision_blocks: Mutex::new(Vec::new()),
+            target_size_blocks: Mutex::new(Vec::new()),
+            elision_blocks: Mutex::new(Vec::new()),
+            #[cfg(feature = "introspection")]
+            introspected: Mutex::new(Vec::new()),
         }
     }
 
     /// Returns or creates the pipeline signature associated to the pipeline interface type.
+    ///
+    /// Types with identical descriptions (compared by value, ignoring the names of descriptors
+    /// and vertex attributes) and identical inherited signatures share the same backend
+    /// signature.
     pub fn get_cached_signature<'r, P: Arguments<'r, B>>(&'r self) -> TypedSignature<'r, B, P> {
         let typeid = TypeId::of::<P::UniqueType>();
-        let cached = self.signature_cache.lock().unwrap().get(&typeid).cloned();
-        if let Some(cached) = cached {
-            unsafe { TypedSignature(&*cached, PhantomData) }
-        } else {
-            // signature not created yet
+        let sig = self.signature_cache.get_or_create(typeid, || {
+            // inherited signatures have different keys
             let inherited = P::get_inherited_signatures(self);
-            let sig = unsafe {
-                self.instance.create_signature(
-                    self.default_arena.as_ref().unwrap(),
-                    &inherited,
-                    P::SIGNATURE,
-                )
+            let key = SignatureKey {
+                description: P::SIGNATURE,
+                inherited: inherited
+                    .iter()
+                    .map(|&s| s as *const B::Signature as usize)
+                    .collect(),
+            };
+            self.signature_description_cache
+                .get_or_create(key, || unsafe {
+                    cached(self.instance.create_signature(
+                        self.default_arena.as_ref().unwrap(),
+                        &inherited,
+                        P::SIGNATURE,
+                    ))
+                })
+        });
+        TypedSignature(sig, PhantomData)
+    }
+
+    /// Returns or creates the built-in 1x1 texture of the specified kind.
+    ///
+    /// These are used in place of missing optional textures in argument blocks
+    /// (see the `fallback` option of `#[derive(Arguments)]`).
+    pub fn fallback_texture<'r>(&'r self, kind: FallbackTexture) -> Image2d<'r, B> {
+        let image = self.fallback_textures.get_or_create(kind, || unsafe {
+            let image = cached(self.instance.create_image(
+                self.default_arena.as_ref().unwrap(),
+                AliasScope::no_alias(),
+                Format::R8G8B8A8_UNORM,
+                (1, 1).into(),
+                MipmapsOption::NoMipmap,
+                1,
+                ImageUsageFlags::SAMPLED,
+                Some(&kind.texel()),
+            ));
+            // lives as long as the renderer: never forgotten
+            #[cfg(feature = "introspection")]
+            self.introspection.register_image(
+                address(image),
+                introspection::ImageInfo {
+                    format: Format::R8G8B8A8_UNORM,
+                    dimensions: (1, 1).into(),
+                    mipmaps: MipmapsOption::NoMipmap,
+                    samples: 1,
+                    usage: ImageUsageFlags::SAMPLED,
+                },
+            );
+            image
+        });
+        Image2d { image }
+    }
+
+    /// Returns or creates a graphics pipeline that lives as long as the renderer.
+    ///
+    /// This is meant for utility passes that do not belong to a particular arena, such as
+    /// blits. The pipeline is identified by the marker type `K` and the arguments type `P`:
+    /// `create_info` is only called on first use, with the shader stages created from
+    /// `vertex_shader` and `fragment_shader`.
+    ///
+    /// Panics if the pipeline fails validation against the signature of `P`.
+    pub fn get_cached_graphics_pipeline<'r, K: 'static, P: Arguments<'r, B>>(
+        &'r self,
+        vertex_shader: ReflectedShader<'_, 'static>,
+        fragment_shader: ReflectedShader<'_, 'static>,
+        create_info: impl FnOnce(
+            GraphicsShaderStages<'r, 'static, B>,
+        ) -> GraphicsPipelineCreateInfo<'r, 'static, B>,
+    ) -> GraphicsPipeline<'r, B, TypedSignature<'r, B, P>> {
+        let root_signature = self.get_cached_signature::<P>();
+        let key = (TypeId::of::<K>(), TypeId::of::<P::UniqueType>());
+        let inner = self.pipeline_cache.get_or_create(key, || unsafe {
+            let arena = self.default_arena.as_ref().unwrap();
+            let create_shader_module = |shader: ReflectedShader<'_, 'static>| ShaderModule {
+                module: self.instance.create_shader_module(
+                    arena,
+                    shader.bytecode,
+                    shader.reflection.stage,
+                ),
+                reflection: shader.reflection,
             };
-            self.signature_cache
+            let create_info = create_info(GraphicsShaderStages::new_vertex_fragment(
+                create_shader_module(vertex_shader),
+                create_shader_module(fragment_shader),
+            ));
+
+            self.cached_pipeline_overwrites
                 .lock()
                 .unwrap()
-                .insert(typeid, sig as *const _);
-            TypedSignature(sig, PhantomData)
+                .insert(key, create_info.overwrites_render_targets());
+            if let Err(errors) =
+                validate_graphics_pipeline(P::SIGNATURE, &create_info, &self.limits())
+            {
+                let messages: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+                panic!(
+                    "graphics pipeline validation failed:\n{}",
+                    messages.join("\n")
+                )
+            }
+
+            // apply per-render-target blend state overrides specified in the signature
+            let mut create_info = create_info;
+            let mut fragment_outputs = Vec::new();
+            P::SIGNATURE.collect_fragment_outputs(&mut fragment_outputs);
+            let attachments: Vec<_> = fragment_outputs
+                .iter()
+                .enumerate()
+                .map(|(i, output)| {
+                    output.blend.unwrap_or(
+                        create_info
+                            .color_blend_state
+                            .attachment(i)
+                            .unwrap_or_default(),
+                    )
+                })
+                .collect();
+            if fragment_outputs.iter().any(|output| output.blend.is_some()) {
+                create_info.color_blend_state.attachments =
+                    ColorBlendAttachments::Separate(&attachments);
+            }
+
+            cached(self.instance.create_graphics_pipeline(
+                arena,
+                root_signature.0,
+                P::SIGNATURE,
+                &create_info,
+            ))
+        });
+        GraphicsPipeline {
+            inner,
+            signature: root_signature,
+            // validation ensures that the pipeline and the signature agree on the dynamic states
+            dynamic_state: P::SIGNATURE.dynamic_state(),
+            // the create info is not kept after the first use: static viewports are not checked
+            viewports: &[],
+            overwrites_render_targets: self.cached_pipeline_overwrites.lock().unwrap()[&key],
+        }
+    }
+
+    /// Returns the capabilities and limits of the device.
+    pub fn limits(&self) -> DeviceLimits {
+        self.instance.limits()
+    }
+
+    /// Returns the clip-space conventions of the backend (depth range and Y direction).
+    pub fn clip_space_info(&self) -> ClipSpaceInfo {
+        self.instance.clip_space_info()
+    }
+
+    /// Returns the operations supported by the device on images of the specified format.
+    pub fn format_properties(&self, format: Format) -> FormatProperties {
+        self.instance.format_properties(format)
+    }
+
+    /// Updates the region of an image between `min_extent` (inclusive) and `max_extent`
+    /// (exclusive), in the mipmap level of the view. The update is visible to all commands
+    /// submitted after this call.
+    ///
+    /// The data must be tightly packed, in the format of the image. No conversion is performed.
+    ///
+    /// Panics if the region is outside the mipmap level, like
+    /// [update_image_batch](Api::update_image_batch).
+    pub fn update_image<'a>(
+        &self,
+        image: impl Into<ImageView<'a, B>>,
+        min_extent: (u32, u32, u32),
+        max_extent: (u32, u32, u32),
+        data: &[u8],
+    ) {
+        let view = image.into();
+        assert!(
+            min_extent.0 <= max_extent.0
+                && min_extent.1 <= max_extent.1
+                && min_extent.2 <= max_extent.2,
+            "invalid image region"
+        );
+        self.budget.record_upload(data.len() as u64);
+        unsafe {
+            self.instance.update_image(
+                view.inner(),
+                view.subresource().base_mip_level,
+                min_extent,
+                max_extent,
+                data,
+            )
+        }
+    }
+
+    /// Writes `data` in a buffer, starting at the byte offset `offset`. The update is visible to
+    /// all commands submitted after this call, and does not affect the commands submitted
+    /// before.
+    ///
+    /// The buffer must have been created with
+    /// [Arena::create_buffer_typeless](crate::Arena::create_buffer_typeless): immutable buffers
+    /// and buffers created by uploads cannot be updated. Panics if the range is outside the
+    /// buffer.
+    pub fn update_buffer<'a>(
+        &self,
+        buffer: impl Into<BufferTypeless<'a, B>>,
+        offset: usize,
+        data: &[u8],
+    ) {
+        if data.is_empty() {
+            return;
+        }
+        self.budget.record_upload(data.len() as u64);
+        unsafe { self.instance.update_buffer(buffer.into().0, offset, data) }
+    }
+
+    /// Applies the updates of `queue`, in order, and removes them from the queue.
+    ///
+    /// If the budget of the frame defers uploads (see [Budget::defer_uploads]), stops at the
+    /// first update that does not fit in the remaining upload budget: the remaining updates are
+    /// applied by the calls of the next frames. The first queued update of each frame is always
+    /// applied, even if it is larger than the budget. See the [budget] module.
+    ///
+    /// Returns the number of applied updates.
+    pub fn flush_uploads<'a>(&self, queue: &mut UploadQueue<'a, B>) -> usize {
+        self.budget.flush(
+            queue,
+            |image, min_extent, max_extent, data| {
+                self.update_image(image, min_extent, max_extent, data)
+            },
+            |buffer, offset, data| self.update_buffer(buffer, offset, data),
+        )
+    }
+
+    /// Applies all updates of `batch` to its image. The updates are visible to all commands
+    /// submitted after this call.
+    ///
+    /// Panics if a region is outside the bounds of the mipmap level of the image.
+    pub fn update_image_batch(&self, batch: &ImageUpdateBatch<B>) {
+        if batch.is_empty() {
+            return;
         }
+        self.budget
+            .record_upload(batch.regions.iter().map(|r| r.data.len() as u64).sum());
+        unsafe {
+            self.instance
+                .update_image_regions(batch.image, &batch.regions)
+        }
+    }
+
+    /// Returns the backend instance, for backend-specific functionality.
+    pub fn instance(&self) -> &B::Instance {
+        &self.instance
     }
 
     /// Returns the default swapchain if there is one.
@@ -813,11 +2162,262 @@ impl<B: Backend> Api<B> {
     ///
     /// Frame-granularity synchronization points happen in this call.
     /// A new frame is implicitly started after this call.
+    ///
+    /// Returns the time spent waiting on the GPU (see [SubmitStats]).
     pub fn submit_frame<'a>(
         &self,
         command_buffers: impl IntoIterator<Item = CommandBuffer<'a, B>>,
-    ) {
-        let commands = sort_command_buffers(command_buffers);
-        unsafe { self.instance.submit_frame(&commands) }
+    ) -> SubmitStats {
+        self.sort_and_submit(command_buffers, &SubmitOptions::DEFAULT)
+    }
+
+    /// Same as [submit_frame], but with control over the order of execution of commands
+    /// with the same sortkey.
+    pub fn submit_frame_with_options<'a>(
+        &self,
+        command_buffers: impl IntoIterator<Item = CommandBuffer<'a, B>>,
+        options: &SubmitOptions,
+    ) -> SubmitStats {
+        self.sort_and_submit(command_buffers, options)
+    }
+
+    /// Same as [submit_frame], but first resolves all references to resources by semantic
+    /// in the command buffers with the resources registered in `semantics`.
+    ///
+    /// Panics if a semantic is not registered.
+    pub fn submit_frame_with_semantics<'a>(
+        &self,
+        command_buffers: impl IntoIterator<Item = CommandBuffer<'a, B>>,
+        semantics: &ResourceSemantics<'a, B>,
+    ) -> SubmitStats {
+        self.sort_and_submit(
+            command_buffers.into_iter().map(|mut cmdbuf| {
+                if let Err(semantic) = cmdbuf.resolve_semantics(semantics) {
+                    panic!("no resource registered for semantic {:?}", semantic)
+                }
+                cmdbuf
+            }),
+            &SubmitOptions::DEFAULT,
+        )
+    }
+
+    /// Forces the backend to compile the specified pipelines ahead of their first use in a frame.
+    ///
+    /// Drivers usually defer part of the compilation of pipelines (e.g. the variants of a GL
+    /// program for the current state) until the first draw, causing a hitch in the first frame
+    /// that uses them. Call this during loading screens with the pipelines of the next scene:
+    /// ```ignore
+    /// let report = api.warm_up(&[opaque.into(), transparent.into(), culling.into()]);
+    /// info!("pipeline warm-up:\n{}", report);
+    /// ```
+    ///
+    /// Pipelines are compiled on the calling thread. With backends whose instance is `Sync`, this
+    /// can be called from a worker thread while the main thread keeps rendering. The OpenGL
+    /// backend is not `Sync`: warm-up must happen on the rendering thread.
+    pub fn warm_up<'a>(&self, pipelines: &[PipelineRef<'a, B>]) -> WarmUpReport {
+        let pipelines = unsafe { self.instance.warm_up_pipelines(pipelines) };
+        WarmUpReport { pipelines }
+    }
+
+    /// Enables or disables the collection of statistics on each call to `submit_frame`.
+    ///
+    /// See [last_frame_report].
+    pub fn set_frame_reports(&self, enabled: bool) {
+        self.frame_reports.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            *self.last_frame_report.lock().unwrap() = None;
+        }
+    }
+
+    /// Returns the statistics collected during the last call to `submit_frame`,
+    /// or `None` if neither frame reports nor lifetime reports are enabled
+    /// (see [set_frame_reports] and [set_lifetime_reports]).
+    pub fn last_frame_report(&self) -> Option<FrameReport> {
+        self.last_frame_report.lock().unwrap().clone()
+    }
+
+    /// Enables or disables the analysis of the lifetimes of transient resources on each call
+    /// to `submit_frame`. This is a diagnostic mode, slower than plain frame reports.
+    ///
+    /// The analysis is stored in the `lifetimes` field of the frame report
+    /// (see [last_frame_report]).
+    pub fn set_lifetime_reports(&self, enabled: bool) {
+        self.lifetime_reports.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            if let Some(ref mut report) = *self.last_frame_report.lock().unwrap() {
+                report.lifetimes = None;
+            }
+        }
+    }
+
+    /// Enables or disables the detection of sort key collisions on each call to `submit_frame`:
+    /// commands with the same sortkey writing to the same render target, whose relative order
+    /// is not guaranteed by the submit options. This is a diagnostic mode, and each collision is
+    /// logged as a warning.
+    ///
+    /// Only the render targets and scissors of argument blocks created while the detection
+    /// is enabled are seen. See [last_sort_key_collisions] and the [collision] module.
+    pub fn set_sort_key_collision_checks(&self, enabled: bool) {
+        self.collision_checker.set_enabled(enabled);
+    }
+
+    /// Returns the sort key collisions detected during the last call to `submit_frame`
+    /// (see [set_sort_key_collision_checks]).
+    pub fn last_sort_key_collisions(&self) -> Vec<SortKeyCollision> {
+        self.collision_checker.last_collisions()
+    }
+
+    /// Enables or disables the elision of redundant clears on each call to `submit_frame`:
+    /// clears overwritten by another clear or by a fullscreen draw before being read are removed
+    /// from the frame. See the [elision] module for the conditions.
+    ///
+    /// Only the render targets of argument blocks created while the elision is enabled are seen.
+    /// See [last_clear_elision_stats].
+    pub fn set_clear_elision(&self, enabled: bool) {
+        self.clear_elision.set_enabled(enabled);
+    }
+
+    /// Returns the number of clears removed from the frame of the last call to `submit_frame`
+    /// (see [set_clear_elision]).
+    pub fn last_clear_elision_stats(&self) -> ClearElisionStats {
+        self.clear_elision.last_stats()
+    }
+
+    /// Sets the limits on the uploads and the transient memory of each frame, checked on each
+    /// call to `submit_frame`. Exceeded budgets are logged as warnings. See the [budget] module.
+    pub fn set_budget(&self, budget: Budget) {
+        self.budget.set_budget(budget);
+    }
+
+    /// Returns the current budget (see [set_budget]).
+    pub fn budget(&self) -> Budget {
+        self.budget.budget()
+    }
+
+    /// Returns the uploads and the transient memory of the frame of the last call to
+    /// `submit_frame`, counted against the budget (see [set_budget]).
+    pub fn last_budget_usage(&self) -> BudgetUsage {
+        self.budget.last_usage()
+    }
+
+    /// Returns the budgets exceeded in the frame of the last call to `submit_frame`.
+    pub fn last_budget_warnings(&self) -> Vec<BudgetWarning> {
+        self.budget.last_warnings()
+    }
+
+    /// Enables or disables the capture of the sorted commands on each call to `submit_frame`.
+    ///
+    /// See [last_command_capture].
+    #[cfg(feature = "capture")]
+    pub fn set_command_capture(&self, enabled: bool) {
+        self.command_capture.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            *self.last_command_capture.lock().unwrap() = None;
+        }
+    }
+
+    /// Returns the sorted commands of the last call to `submit_frame`,
+    /// or `None` if command capture is not enabled (see [set_command_capture]).
+    #[cfg(feature = "capture")]
+    pub fn last_command_capture(&self) -> Option<capture::CommandCapture> {
+        self.last_command_capture.lock().unwrap().clone()
+    }
+
+    #[cfg(feature = "capture")]
+    fn capture_commands(&self, commands: &[Command<B>]) {
+        if self.command_capture.load(Ordering::Relaxed) {
+            *self.last_command_capture.lock().unwrap() =
+                Some(capture::CommandCapture::from_commands(commands));
+        }
+    }
+
+    #[cfg(not(feature = "capture"))]
+    fn capture_commands(&self, _commands: &[Command<B>]) {}
+
+    /// Records the uses of images by commands, and freezes the usage inferred during the frame.
+    fn infer_image_usage(&self, commands: &[Command<B>]) {
+        if !self.usage_tracker.is_enabled() {
+            return;
+        }
+        for command in commands {
+            match command.cmd {
+                CommandInner::ClearImageFloat { image, .. }
+                | CommandInner::ClearImageUint { image, .. }
+                | CommandInner::ClearImageInt { image, .. } => self
+                    .usage_tracker
+                    .record(address(image), ImageUsageFlags::COLOR_ATTACHMENT),
+                CommandInner::ClearDepthStencilImage { image, .. } => self
+                    .usage_tracker
+                    .record(address(image), ImageUsageFlags::DEPTH_ATTACHMENT),
+                _ => {}
+            }
+        }
+        self.usage_tracker.end_frame();
+    }
+
+    /// Returns the usage inferred for an image created with [ImageUsageFlags::INFER], or `None`
+    /// if the image was created with an explicit usage or was not used in a submitted frame yet.
+    pub fn inferred_usage(&self, image: &B::Image) -> Option<ImageUsageFlags> {
+        match self.usage_tracker.usage(address(image)) {
+            Some((usage, true)) => Some(usage),
+            _ => None,
+        }
+    }
+
+    fn sort_and_submit<'a>(
+        &self,
+        command_buffers: impl IntoIterator<Item = CommandBuffer<'a, B>>,
+        options: &SubmitOptions,
+    ) -> SubmitStats {
+        let command_buffers = command_buffers.into_iter().collect::<Vec<_>>();
+        if self.collision_checker.is_enabled() {
+            for collision in self.collision_checker.check(&command_buffers, options) {
+                warn!("{}", collision);
+            }
+        }
+        let fullscreen_draws = if self.clear_elision.is_enabled() {
+            Some(collect_fullscreen_draws(&command_buffers))
+        } else {
+            None
+        };
+        let elide_clears = |commands: &mut Vec<Command<'a, B>>| {
+            if let Some(ref fullscreen_draws) = fullscreen_draws {
+                self.clear_elision.elide(commands, fullscreen_draws);
+            }
+        };
+        let budget_warnings = self.budget.end_frame();
+        for warning in budget_warnings.iter() {
+            warn!("{}", warning);
+        }
+        let lifetime_reports = self.lifetime_reports.load(Ordering::Relaxed);
+        if self.frame_reports.load(Ordering::Relaxed) || lifetime_reports {
+            let mut report = FrameReport::default();
+            if lifetime_reports {
+                report.lifetimes = Some(LifetimeReport::default());
+            }
+            report.budget_warnings = budget_warnings;
+            let start = Instant::now();
+            let mut commands = sort_command_buffers_with_options(command_buffers, options);
+            report.sort_time = start.elapsed();
+            elide_clears(&mut commands);
+            self.infer_image_usage(&commands);
+            self.capture_commands(&commands);
+            let stats = unsafe {
+                self.instance
+                    .submit_frame_with_report(&commands, &mut report)
+            };
+            if let Some(ref mut lifetimes) = report.lifetimes {
+                let sortkeys = commands.iter().map(|c| c.sortkey).collect::<Vec<_>>();
+                lifetimes.finish(&sortkeys);
+            }
+            *self.last_frame_report.lock().unwrap() = Some(report);
+            stats
+        } else {
+            let mut commands = sort_command_buffers_with_options(command_buffers, options);
+            elide_clears(&mut commands);
+            self.infer_image_usage(&commands);
+            self.capture_commands(&commands);
+            unsafe { self.instance.submit_frame(&commands) }
+        }
     }
 }
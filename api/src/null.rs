@@ -0,0 +1,858 @@
+//! A backend that renders nothing, for testing without a GPU.
+//!
+//! Unlike [DummyBackend](crate::DummyBackend), whose functions panic, the [NullBackend] accepts
+//! all calls and returns plausible values: images and buffers are created with the requested
+//! descriptions, formats support all usages that make sense for them, and readbacks of buffers
+//! return the data they were created with. This lets crates that depend on the API exercise their
+//! logic (resource creation, pipeline setup, command recording and submission) on machines with
+//! no GPU at all:
+//! ```ignore
+//! let api = Api::new(NullInstance::new());
+//! let arena = api.create_arena();
+//! let mut renderer = MyRenderer::new(&arena);
+//! api.submit_frame(vec![renderer.render(&arena)]);
+//!
+//! let instance = api.instance();
+//! assert_eq!(instance.last_frame().draws, 3);
+//! assert_eq!(instance.live_images().len(), 2);
+//! ```
+//!
+//! Arena allocators are called for all buffers (see [ArenaAllocator](crate::ArenaAllocator)), so
+//! memory budgets can also be tested.
+use crate::{
+    allocator::{AllocationKind, AllocationRequest, ArenaAllocator, ArenaOptions},
+    clip::{ClipSpaceInfo, DepthRange, YDirection},
+    command::{ClearValue, Command, CommandInner},
+    descriptor::Descriptor,
+    format::{ColorSpace, ComponentLayout, Format, FormatFeatureFlags, FormatProperties},
+    image::{
+        DepthStencilView, Dimensions, ImageRegionUpdate, ImageUsageFlags, MipmapsOption,
+        RenderTargetView,
+    },
+    pipeline::{
+        BareArgumentBlock, GraphicsPipelineCreateInfo, PipelineRef, Scissor, ShaderStageFlags,
+        SignatureDescription, Viewport,
+    },
+    report::{PipelineCompileTime, SubmitStats},
+    traits,
+    vertex::{IndexBufferView, VertexBufferView},
+    AliasScope, ApiProfile, Backend, DeviceLimits, Instance,
+};
+use std::{
+    any::Any,
+    slice,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// Backend that accepts all calls and renders nothing. See the module documentation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct NullBackend;
+
+impl Backend for NullBackend {
+    type Instance = NullInstance;
+    type Arena = NullArena;
+    type Swapchain = NullSwapchain;
+    type Image = NullImage;
+    type Buffer = NullBuffer;
+    type BufferView = NullBufferView;
+    type ShaderModule = NullShaderModule;
+    type GraphicsPipeline = NullPipeline;
+    type ComputePipeline = NullPipeline;
+    type Signature = NullSignature;
+    type ArgumentBlock = NullArgumentBlock;
+    type HostReference = NullHostReference;
+    type HostReadback = NullHostReadback;
+    type Query = NullQuery;
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Arena of the null backend.
+///
+/// Objects are boxed so that their address does not change, and dropped with the arena.
+pub struct NullArena {
+    id: usize,
+    allocator: Box<dyn ArenaAllocator>,
+    objects: Mutex<Vec<Box<dyn Any + Send>>>,
+}
+
+impl NullArena {
+    fn alloc<T: Any + Send>(&self, object: T) -> &T {
+        let object = Box::new(object);
+        let ptr = &*object as *const T;
+        self.objects.lock().unwrap().push(object);
+        // the box is only dropped with the arena
+        unsafe { &*ptr }
+    }
+}
+
+/// Swapchain of the null backend, of a fixed size.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct NullSwapchain {
+    pub size: (u32, u32),
+    pub color_space: ColorSpace,
+}
+
+impl traits::Swapchain for NullSwapchain {
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+}
+
+/// Description of an image created with the null backend.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NullImage {
+    /// Unique identifier, in creation order.
+    pub id: usize,
+    pub format: Format,
+    /// Dimensions of the image. Swapchain-relative dimensions are resolved with the size of the
+    /// default swapchain at creation.
+    pub dimensions: Dimensions,
+    pub mip_levels: u32,
+    pub samples: u32,
+    pub usage: ImageUsageFlags,
+    pub scope: AliasScope,
+    /// Value the image is cleared with at the start of its scope
+    /// (see `Arena::create_image_cleared`).
+    pub clear_value: Option<ClearValue>,
+}
+
+impl traits::Image for NullImage {
+    fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+}
+
+/// Buffer of the null backend, which keeps a copy of its initial data.
+#[derive(Clone, Debug)]
+pub struct NullBuffer {
+    /// Unique identifier, in creation order.
+    pub id: usize,
+    pub size: u64,
+    /// Initial data, or `None` for buffers created without data (read back as zeros).
+    pub data: Option<Vec<u8>>,
+}
+
+/// Description of a buffer created with the null backend.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct NullBufferDesc {
+    pub id: usize,
+    pub size: u64,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct NullBufferView {
+    pub buffer: usize,
+    pub format: Format,
+    pub offset: usize,
+    pub size: Option<usize>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct NullShaderModule {
+    pub stage: ShaderStageFlags,
+    /// Size of the bytecode in bytes.
+    pub size: usize,
+}
+
+/// Graphics or compute pipeline of the null backend.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct NullPipeline {
+    pub id: usize,
+}
+
+/// Signature of the null backend, with the number of items of its description.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct NullSignature {
+    pub inherited: usize,
+    pub descriptors: usize,
+    pub vertex_inputs: usize,
+    pub fragment_outputs: usize,
+}
+
+/// Argument block of the null backend, with the number of arguments of each kind.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct NullArgumentBlock {
+    pub inherited: usize,
+    pub descriptors: usize,
+    pub vertex_buffers: usize,
+    pub index_buffer: bool,
+    pub render_targets: usize,
+    pub depth_stencil_target: bool,
+    pub viewports: usize,
+    pub scissors: usize,
+    pub stencil_reference: Option<u32>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct NullHostReference {
+    pub size: usize,
+}
+
+/// Destination of a buffer-to-host copy.
+#[derive(Debug)]
+pub struct NullHostReadback {
+    ptr: *mut u8,
+    len: usize,
+}
+
+// only written in `submit_frame`, while the destination is borrowed by the arena.
+unsafe impl Send for NullHostReadback {}
+unsafe impl Sync for NullHostReadback {}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct NullQuery {
+    pub id: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Number of objects created since the creation of the instance.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct NullObjectCounts {
+    pub arenas: usize,
+    pub images: usize,
+    pub buffers: usize,
+    pub buffer_views: usize,
+    pub shader_modules: usize,
+    pub graphics_pipelines: usize,
+    pub compute_pipelines: usize,
+    pub signatures: usize,
+    pub argument_blocks: usize,
+    pub host_references: usize,
+    pub host_readbacks: usize,
+    pub queries: usize,
+    /// Number of calls to `update_image` and `update_image_regions`.
+    pub image_updates: usize,
+    /// Number of calls to `update_buffer`. The data is not kept: readbacks of the buffer still
+    /// return its initial data.
+    pub buffer_updates: usize,
+}
+
+/// Number of commands of each kind in a submitted frame.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct NullCommandCounts {
+    pub barriers: usize,
+    pub clears: usize,
+    pub copies_to_host: usize,
+    pub presents: usize,
+    pub graphics_pipelines: usize,
+    pub compute_pipelines: usize,
+    pub queries: usize,
+    pub conditionals: usize,
+    pub pipeline_arguments: usize,
+    pub line_widths: usize,
+    /// Debug groups opened and closed.
+    pub debug_groups: usize,
+    /// Non-indexed and indexed draws.
+    pub draws: usize,
+    /// Direct and indirect dispatches.
+    pub dispatches: usize,
+    /// Clears of images created with `Arena::create_image_cleared`, performed before the first
+    /// command inside their scope.
+    pub initial_clears: usize,
+}
+
+impl NullCommandCounts {
+    fn count(&mut self, command: &CommandInner<NullBackend>) {
+        match *command {
+            CommandInner::PipelineBarrier { .. } => self.barriers += 1,
+            CommandInner::ClearImageFloat { .. }
+            | CommandInner::ClearImageUint { .. }
+            | CommandInner::ClearImageInt { .. }
+            | CommandInner::ClearDepthStencilImage { .. } => self.clears += 1,
+            CommandInner::CopyBufferToHost { .. } | CommandInner::CopyImageToHost { .. } => {
+                self.copies_to_host += 1
+            }
+            CommandInner::Present { .. } => self.presents += 1,
+            CommandInner::DrawHeader { .. } => self.graphics_pipelines += 1,
+            CommandInner::DispatchHeader { .. } => self.compute_pipelines += 1,
+            CommandInner::BeginQuery { .. } | CommandInner::EndQuery { .. } => self.queries += 1,
+            CommandInner::BeginConditional { .. } | CommandInner::EndConditional {} => {
+                self.conditionals += 1
+            }
+            CommandInner::BeginDebugGroup { .. } | CommandInner::EndDebugGroup {} => {
+                self.debug_groups += 1
+            }
+            CommandInner::SetPipelineArguments { .. } => self.pipeline_arguments += 1,
+            CommandInner::SetLineWidth { .. } => self.line_widths += 1,
+            CommandInner::Draw { .. } | CommandInner::DrawIndexed { .. } => self.draws += 1,
+            CommandInner::Dispatch { .. } | CommandInner::DispatchIndirect { .. } => {
+                self.dispatches += 1
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    objects: NullObjectCounts,
+    /// Images and buffers of the live arenas, with the ID of their arena.
+    images: Vec<(usize, NullImage)>,
+    buffers: Vec<(usize, NullBufferDesc)>,
+    frames: u64,
+    last_frame: NullCommandCounts,
+}
+
+/// Instance of the null backend.
+pub struct NullInstance {
+    limits: DeviceLimits,
+    default_swapchain: Option<NullSwapchain>,
+    next_id: AtomicUsize,
+    state: Mutex<State>,
+}
+
+/// Limits of the null backend: the minimum values required by OpenGL 4.5.
+pub const NULL_DEVICE_LIMITS: DeviceLimits = DeviceLimits {
+    max_image_dimension_1d: 16384,
+    max_image_dimension_2d: 16384,
+    max_image_dimension_3d: 2048,
+    max_image_dimension_cube: 16384,
+    max_image_array_layers: 2048,
+    max_color_attachments: 8,
+    max_samples: 4,
+    max_sampler_anisotropy: 16.0,
+    max_viewports: 16,
+    max_uniform_buffer_range: 16384,
+    min_uniform_buffer_offset_alignment: 256,
+    max_compute_work_group_count: [65535, 65535, 65535],
+    line_width_range: [1.0, 1.0],
+    point_size_range: [1.0, 64.0],
+    max_multiview_view_count: 16,
+    native_multiview: false,
+    profile: ApiProfile::Desktop,
+    spirv_shaders: true,
+};
+
+const DEFAULT_SWAPCHAIN: NullSwapchain = NullSwapchain {
+    size: (1280, 720),
+    color_space: ColorSpace::Srgb,
+};
+
+impl NullInstance {
+    /// Creates an instance with a default swapchain of 1280x720 pixels and the limits of
+    /// [NULL_DEVICE_LIMITS].
+    pub fn new() -> NullInstance {
+        NullInstance::with_limits(NULL_DEVICE_LIMITS)
+    }
+
+    /// Creates an instance that reports the specified limits.
+    pub fn with_limits(limits: DeviceLimits) -> NullInstance {
+        NullInstance {
+            limits,
+            default_swapchain: Some(DEFAULT_SWAPCHAIN),
+            next_id: AtomicUsize::new(0),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Replaces the default swapchain, or removes it if `size` is `None`.
+    pub fn with_default_swapchain(mut self, size: Option<(u32, u32)>) -> NullInstance {
+        self.default_swapchain = size.map(|size| NullSwapchain {
+            size,
+            color_space: ColorSpace::Srgb,
+        });
+        self
+    }
+
+    /// Returns the number of objects created since the creation of the instance.
+    pub fn object_counts(&self) -> NullObjectCounts {
+        self.state.lock().unwrap().objects
+    }
+
+    /// Returns the descriptions of the images of all arenas that are not dropped, in creation
+    /// order.
+    pub fn live_images(&self) -> Vec<NullImage> {
+        let state = self.state.lock().unwrap();
+        state.images.iter().map(|&(_, image)| image).collect()
+    }
+
+    /// Returns the descriptions of the buffers of all arenas that are not dropped, in creation
+    /// order.
+    pub fn live_buffers(&self) -> Vec<NullBufferDesc> {
+        let state = self.state.lock().unwrap();
+        state.buffers.iter().map(|&(_, buffer)| buffer).collect()
+    }
+
+    /// Returns the number of submitted frames.
+    pub fn frame_count(&self) -> u64 {
+        self.state.lock().unwrap().frames
+    }
+
+    /// Returns the commands of the last submitted frame.
+    pub fn last_frame(&self) -> NullCommandCounts {
+        self.state.lock().unwrap().last_frame
+    }
+
+    fn next_id(&self) -> usize {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn count(&self, f: impl FnOnce(&mut NullObjectCounts)) {
+        f(&mut self.state.lock().unwrap().objects)
+    }
+
+    fn alloc_image<'a>(
+        &self,
+        arena: &'a NullArena,
+        scope: AliasScope,
+        format: Format,
+        dimensions: Dimensions,
+        mipcount: MipmapsOption,
+        samples: u32,
+        usage: ImageUsageFlags,
+        clear_value: Option<ClearValue>,
+    ) -> &'a NullImage {
+        let swapchain_size = self
+            .default_swapchain
+            .map(|swapchain| swapchain.size)
+            .unwrap_or((1, 1));
+        let dimensions = dimensions.resolve(swapchain_size);
+        let (width, height, depth) = dimensions.width_height_depth();
+        let image = arena.alloc(NullImage {
+            id: self.next_id(),
+            format,
+            dimensions,
+            mip_levels: mipcount.count(width, height, depth),
+            samples,
+            usage,
+            scope,
+            clear_value,
+        });
+        let mut state = self.state.lock().unwrap();
+        state.objects.images += 1;
+        state.images.push((arena.id, *image));
+        image
+    }
+
+    fn alloc_buffer<'a>(
+        &self,
+        arena: &'a NullArena,
+        size: u64,
+        data: Option<&[u8]>,
+    ) -> &'a NullBuffer {
+        let request = AllocationRequest {
+            kind: AllocationKind::Buffer,
+            size,
+        };
+        if let Err(e) = arena.allocator.allocate(&request) {
+            panic!(
+                "allocation of a buffer of {} bytes refused by the arena allocator: {:?}",
+                size, e
+            );
+        }
+        let buffer = arena.alloc(NullBuffer {
+            id: self.next_id(),
+            size,
+            data: data.map(|data| data.to_vec()),
+        });
+        let mut state = self.state.lock().unwrap();
+        state.objects.buffers += 1;
+        state.buffers.push((
+            arena.id,
+            NullBufferDesc {
+                id: buffer.id,
+                size,
+            },
+        ));
+        buffer
+    }
+}
+
+impl Default for NullInstance {
+    fn default() -> Self {
+        NullInstance::new()
+    }
+}
+
+impl Instance<NullBackend> for NullInstance {
+    unsafe fn create_arena(&self, options: ArenaOptions) -> Box<NullArena> {
+        self.count(|c| c.arenas += 1);
+        Box::new(NullArena {
+            id: self.next_id(),
+            allocator: options.allocator,
+            objects: Mutex::new(Vec::new()),
+        })
+    }
+
+    unsafe fn drop_arena(&self, arena: Box<NullArena>) {
+        let mut state = self.state.lock().unwrap();
+        state.images.retain(|&(id, _)| id != arena.id);
+        for &(_, buffer) in state.buffers.iter().filter(|&&(id, _)| id == arena.id) {
+            arena.allocator.free(&AllocationRequest {
+                kind: AllocationKind::Buffer,
+                size: buffer.size,
+            });
+        }
+        state.buffers.retain(|&(id, _)| id != arena.id);
+    }
+
+    unsafe fn create_swapchain<'a>(&self, arena: &'a NullArena) -> &'a NullSwapchain {
+        arena.alloc(self.default_swapchain.unwrap_or(DEFAULT_SWAPCHAIN))
+    }
+
+    unsafe fn default_swapchain<'a>(&'a self) -> Option<&'a NullSwapchain> {
+        self.default_swapchain.as_ref()
+    }
+
+    unsafe fn acquire_swapchain_image<'a>(
+        &self,
+        arena: &'a NullArena,
+        swapchain: &'a NullSwapchain,
+    ) -> &'a NullImage {
+        let format = match swapchain.color_space {
+            ColorSpace::Srgb => Format::R8G8B8A8_SRGB,
+            ColorSpace::Linear => Format::R8G8B8A8_UNORM,
+        };
+        self.create_image(
+            arena,
+            AliasScope::no_alias(),
+            format,
+            Dimensions::Dim2d {
+                width: swapchain.size.0,
+                height: swapchain.size.1,
+                array_layers: 1,
+            },
+            MipmapsOption::NoMipmap,
+            1,
+            ImageUsageFlags::COLOR_ATTACHMENT,
+            None,
+        )
+    }
+
+    fn limits(&self) -> DeviceLimits {
+        self.limits
+    }
+
+    fn clip_space_info(&self) -> ClipSpaceInfo {
+        // same as the OpenGL backend
+        ClipSpaceInfo {
+            depth_range: DepthRange::NegativeOneToOne,
+            y_direction: YDirection::Up,
+        }
+    }
+
+    fn format_properties(&self, format: Format) -> FormatProperties {
+        let info = format.get_format_info();
+        let features = match info.component_layout {
+            ComponentLayout::D | ComponentLayout::DS | ComponentLayout::S | ComponentLayout::XD => {
+                FormatFeatureFlags::SAMPLED_IMAGE | FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT
+            }
+            _ if info.is_compressed() => FormatFeatureFlags::SAMPLED_IMAGE,
+            _ => {
+                FormatFeatureFlags::SAMPLED_IMAGE
+                    | FormatFeatureFlags::STORAGE_IMAGE
+                    | FormatFeatureFlags::COLOR_ATTACHMENT
+            }
+        };
+        FormatProperties {
+            features,
+            emulated: false,
+        }
+    }
+
+    unsafe fn create_image<'a>(
+        &self,
+        arena: &'a NullArena,
+        scope: AliasScope,
+        format: Format,
+        dimensions: Dimensions,
+        mipcount: MipmapsOption,
+        samples: u32,
+        usage: ImageUsageFlags,
+        _initial_data: Option<&[u8]>,
+    ) -> &'a NullImage {
+        self.alloc_image(
+            arena, scope, format, dimensions, mipcount, samples, usage, None,
+        )
+    }
+
+    unsafe fn create_image_cleared<'a>(
+        &self,
+        arena: &'a NullArena,
+        scope: AliasScope,
+        format: Format,
+        dimensions: Dimensions,
+        mipcount: MipmapsOption,
+        samples: u32,
+        usage: ImageUsageFlags,
+        clear_value: ClearValue,
+    ) -> &'a NullImage {
+        self.alloc_image(
+            arena,
+            scope,
+            format,
+            dimensions,
+            mipcount,
+            samples,
+            usage,
+            Some(clear_value),
+        )
+    }
+
+    unsafe fn update_image(
+        &self,
+        image: &NullImage,
+        mip_level: u32,
+        min_extent: (u32, u32, u32),
+        max_extent: (u32, u32, u32),
+        data: &[u8],
+    ) {
+        assert!(
+            mip_level < image.mip_levels,
+            "invalid mip level {} for an image with {} levels",
+            mip_level,
+            image.mip_levels
+        );
+        self.update_image_regions(
+            image,
+            &[ImageRegionUpdate {
+                mip_level,
+                offset: min_extent,
+                size: (
+                    max_extent.0 - min_extent.0,
+                    max_extent.1 - min_extent.1,
+                    max_extent.2 - min_extent.2,
+                ),
+                row_pitch: 0,
+                data,
+            }],
+        );
+    }
+
+    unsafe fn update_image_regions(&self, image: &NullImage, regions: &[ImageRegionUpdate]) {
+        for region in regions.iter() {
+            let (width, height, depth) = image.dimensions.width_height_depth();
+            let level_size = (
+                (width >> region.mip_level).max(1),
+                (height >> region.mip_level).max(1),
+                (depth >> region.mip_level).max(1),
+            );
+            assert!(
+                region.mip_level < image.mip_levels
+                    && region.offset.0 + region.size.0 <= level_size.0
+                    && region.offset.1 + region.size.1 <= level_size.1
+                    && region.offset.2 + region.size.2 <= level_size.2,
+                "region out of the bounds of mip level {} of the image",
+                region.mip_level
+            );
+        }
+        self.count(|c| c.image_updates += 1);
+    }
+
+    unsafe fn create_immutable_buffer<'a>(
+        &self,
+        arena: &'a NullArena,
+        size: u64,
+        data: &[u8],
+    ) -> &'a NullBuffer {
+        self.alloc_buffer(arena, size, Some(data))
+    }
+
+    unsafe fn create_buffer<'a>(&self, arena: &'a NullArena, size: u64) -> &'a NullBuffer {
+        self.alloc_buffer(arena, size, None)
+    }
+
+    unsafe fn update_buffer(&self, buffer: &NullBuffer, offset: usize, data: &[u8]) {
+        assert!(
+            (offset + data.len()) as u64 <= buffer.size,
+            "update of {} bytes at offset {} outside of a buffer of {} bytes",
+            data.len(),
+            offset,
+            buffer.size
+        );
+        self.count(|c| c.buffer_updates += 1);
+    }
+
+    unsafe fn create_buffer_view<'a>(
+        &self,
+        arena: &'a NullArena,
+        buffer: &'a NullBuffer,
+        format: Format,
+        offset: usize,
+        size: Option<usize>,
+    ) -> &'a NullBufferView {
+        self.count(|c| c.buffer_views += 1);
+        arena.alloc(NullBufferView {
+            buffer: buffer.id,
+            format,
+            offset,
+            size,
+        })
+    }
+
+    unsafe fn create_shader_module<'a>(
+        &self,
+        arena: &'a NullArena,
+        spirv: &[u8],
+        stage: ShaderStageFlags,
+    ) -> &'a NullShaderModule {
+        self.count(|c| c.shader_modules += 1);
+        arena.alloc(NullShaderModule {
+            stage,
+            size: spirv.len(),
+        })
+    }
+
+    unsafe fn create_graphics_pipeline<'a>(
+        &self,
+        arena: &'a NullArena,
+        _root_signature: &'a NullSignature,
+        _root_signature_description: &SignatureDescription,
+        _create_info: &GraphicsPipelineCreateInfo<NullBackend>,
+    ) -> &'a NullPipeline {
+        self.count(|c| c.graphics_pipelines += 1);
+        arena.alloc(NullPipeline { id: self.next_id() })
+    }
+
+    unsafe fn create_compute_pipeline<'a>(
+        &self,
+        arena: &'a NullArena,
+        _root_signature: &'a NullSignature,
+        _root_signature_description: &SignatureDescription,
+        _shader: &'a NullShaderModule,
+    ) -> &'a NullPipeline {
+        self.count(|c| c.compute_pipelines += 1);
+        arena.alloc(NullPipeline { id: self.next_id() })
+    }
+
+    unsafe fn warm_up_pipelines(
+        &self,
+        pipelines: &[PipelineRef<NullBackend>],
+    ) -> Vec<PipelineCompileTime> {
+        pipelines
+            .iter()
+            .map(|p| PipelineCompileTime {
+                kind: p.kind(),
+                create_time: Duration::from_secs(0),
+                warm_up_time: Duration::from_secs(0),
+            })
+            .collect()
+    }
+
+    unsafe fn create_signature<'a>(
+        &'a self,
+        arena: &'a NullArena,
+        inherited: &[&NullSignature],
+        description: &SignatureDescription,
+    ) -> &'a NullSignature {
+        self.count(|c| c.signatures += 1);
+        arena.alloc(NullSignature {
+            inherited: inherited.len(),
+            descriptors: description.descriptors.len(),
+            vertex_inputs: description.vertex_inputs.len(),
+            fragment_outputs: description.fragment_outputs.len(),
+        })
+    }
+
+    unsafe fn create_argument_block<'a>(
+        &self,
+        arena: &'a NullArena,
+        _signature: &'a NullSignature,
+        arguments: impl IntoIterator<Item = BareArgumentBlock<'a, NullBackend>>,
+        descriptors: impl IntoIterator<Item = Descriptor<'a, NullBackend>>,
+        vertex_buffers: impl IntoIterator<Item = VertexBufferView<'a, NullBackend>>,
+        index_buffer: Option<IndexBufferView<'a, NullBackend>>,
+        render_targets: impl IntoIterator<Item = RenderTargetView<'a, NullBackend>>,
+        depth_stencil_target: Option<DepthStencilView<'a, NullBackend>>,
+        viewports: impl IntoIterator<Item = Viewport>,
+        scissors: impl IntoIterator<Item = Scissor>,
+        stencil_reference: Option<u32>,
+    ) -> &'a NullArgumentBlock {
+        self.count(|c| c.argument_blocks += 1);
+        let descriptors = descriptors.into_iter().inspect(|d| {
+            if let Descriptor::TextureSamplerLod {
+                image,
+                subresource,
+                lod,
+                ..
+            } = *d
+            {
+                lod.validate(&subresource, image.mip_levels);
+            }
+        });
+        arena.alloc(NullArgumentBlock {
+            inherited: arguments.into_iter().count(),
+            descriptors: descriptors.count(),
+            vertex_buffers: vertex_buffers.into_iter().count(),
+            index_buffer: index_buffer.is_some(),
+            render_targets: render_targets.into_iter().count(),
+            depth_stencil_target: depth_stencil_target.is_some(),
+            viewports: viewports.into_iter().count(),
+            scissors: scissors.into_iter().count(),
+            stencil_reference,
+        })
+    }
+
+    unsafe fn create_host_reference<'a>(
+        &self,
+        arena: &'a NullArena,
+        data: &'a [u8],
+    ) -> &'a NullHostReference {
+        self.count(|c| c.host_references += 1);
+        arena.alloc(NullHostReference { size: data.len() })
+    }
+
+    unsafe fn create_host_readback<'a>(
+        &self,
+        arena: &'a NullArena,
+        dest: &'a mut [u8],
+    ) -> &'a NullHostReadback {
+        self.count(|c| c.host_readbacks += 1);
+        arena.alloc(NullHostReadback {
+            ptr: dest.as_mut_ptr(),
+            len: dest.len(),
+        })
+    }
+
+    unsafe fn create_occlusion_query<'a>(&self, arena: &'a NullArena) -> &'a NullQuery {
+        self.count(|c| c.queries += 1);
+        arena.alloc(NullQuery { id: self.next_id() })
+    }
+
+    unsafe fn submit_frame<'a>(&self, commands: &[Command<'a, NullBackend>]) -> SubmitStats {
+        let mut counts = NullCommandCounts::default();
+        for command in commands.iter() {
+            counts.count(&command.cmd);
+            if let CommandInner::CopyBufferToHost { buffer, readback } = command.cmd {
+                // copy the initial data of the buffer, or zeros
+                let dest = slice::from_raw_parts_mut(readback.ptr, readback.len);
+                let data = match buffer.data {
+                    Some(ref data) => &data[..],
+                    None => &[],
+                };
+                let n = data.len().min(dest.len());
+                dest[..n].copy_from_slice(&data[..n]);
+                for b in dest[n..].iter_mut() {
+                    *b = 0;
+                }
+            }
+            if let CommandInner::CopyImageToHost { readback, .. } = command.cmd {
+                // images have no contents
+                for b in slice::from_raw_parts_mut(readback.ptr, readback.len) {
+                    *b = 0;
+                }
+            }
+        }
+        let mut state = self.state.lock().unwrap();
+        counts.initial_clears = state
+            .images
+            .iter()
+            .filter(|&&(_, ref image)| {
+                image.clear_value.is_some()
+                    && commands.iter().any(|c| image.scope.contains(c.sortkey))
+            })
+            .count();
+        state.frames += 1;
+        state.last_frame = counts;
+        SubmitStats::default()
+    }
+}
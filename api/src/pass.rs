@@ -0,0 +1,181 @@
+//! Named passes, and the assignment of their sort keys.
+//!
+//! The commands of a frame are sorted by sort key, so each pass of the frame (shadows, G-buffer,
+//! lighting, post-processing...) must use a range of sort keys that does not overlap the ranges
+//! of the other passes. A [SortKeyLayout] hands out these ranges in order, and the
+//! [render_pass!](crate::render_pass) macro records the commands of a pass with sort keys local
+//! to its range:
+//! ```ignore
+//! let mut layout = SortKeyLayout::new(16);
+//! render_pass!(cmdbuf, layout, "shadows", {
+//!     cmdbuf.draw(0x10, &arena, shadow_pipeline, shadow_args, DrawParams::quad());
+//! });
+//! render_pass!(cmdbuf, layout, "lighting", {
+//!     // sort key 0x1_0010
+//!     cmdbuf.draw(0x10, &arena, lighting_pipeline, lighting_args, DrawParams::quad());
+//! });
+//! ```
+//!
+//! The commands of a pass are enclosed in a debug group named after the pass (visible in
+//! graphics debuggers such as RenderDoc), and the statistics of the pass in frame reports
+//! carry its name (see [PassReport::name](crate::report::PassReport::name)).
+use crate::AliasScope;
+use std::ops::Range;
+
+/// A pass and its range of sort keys.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PassInfo {
+    pub name: &'static str,
+    /// Index of the pass in its layout.
+    pub index: u32,
+    /// First sort key of the pass.
+    pub base_key: u64,
+    /// Number of sort keys of the pass.
+    pub key_count: u64,
+}
+
+impl PassInfo {
+    /// Returns the range of sort keys of the pass.
+    pub fn key_range(&self) -> Range<u64> {
+        self.base_key..self.base_key + self.key_count
+    }
+
+    /// Returns the sort key of the pass for the specified local sort key.
+    ///
+    /// Panics if `local` is outside of the range of the pass.
+    pub fn key(&self, local: u64) -> u64 {
+        assert!(
+            local < self.key_count,
+            "sort key {:#x} outside of pass `{}` ({} keys)",
+            local,
+            self.name,
+            self.key_count
+        );
+        self.base_key | local
+    }
+
+    /// Returns the scope containing the commands of this pass, for transient resources
+    /// that are only used in it.
+    pub fn alias_scope(&self) -> AliasScope {
+        AliasScope {
+            value: self.base_key,
+            mask: !(self.key_count - 1),
+        }
+    }
+}
+
+/// Divides the sort keys into consecutive ranges of `2^local_bits` keys, one for each pass,
+/// assigned in order by [next_pass](SortKeyLayout::next_pass).
+#[derive(Clone, Debug)]
+pub struct SortKeyLayout {
+    base_key: u64,
+    local_bits: u32,
+    passes: Vec<PassInfo>,
+}
+
+impl SortKeyLayout {
+    /// Creates a layout whose passes have `2^local_bits` sort keys each, starting at sort key 0.
+    pub fn new(local_bits: u32) -> SortKeyLayout {
+        SortKeyLayout::with_base_key(0, local_bits)
+    }
+
+    /// Creates a layout whose first pass starts at `base_key`.
+    ///
+    /// Panics if `local_bits` is 0 or 64 or more, or if `base_key` is not a multiple of
+    /// `2^local_bits`.
+    pub fn with_base_key(base_key: u64, local_bits: u32) -> SortKeyLayout {
+        assert!(
+            local_bits > 0 && local_bits < 64,
+            "invalid number of local sort key bits"
+        );
+        assert_eq!(
+            base_key & ((1 << local_bits) - 1),
+            0,
+            "the base key overlaps the local sort keys"
+        );
+        SortKeyLayout {
+            base_key,
+            local_bits,
+            passes: Vec::new(),
+        }
+    }
+
+    /// Number of sort keys of each pass.
+    pub fn keys_per_pass(&self) -> u64 {
+        1 << self.local_bits
+    }
+
+    /// Assigns the range of sort keys that follows the range of the previous pass to a new
+    /// pass.
+    ///
+    /// Panics if there are no more sort keys.
+    pub fn next_pass(&mut self, name: &'static str) -> PassInfo {
+        let index = self.passes.len() as u32;
+        let base_key = u64::from(index)
+            .checked_mul(self.keys_per_pass())
+            .and_then(|offset| offset.checked_add(self.base_key))
+            .unwrap_or_else(|| panic!("no more sort keys for pass `{}`", name));
+        let pass = PassInfo {
+            name,
+            index,
+            base_key,
+            key_count: self.keys_per_pass(),
+        };
+        self.passes.push(pass);
+        pass
+    }
+
+    /// Returns the passes assigned since the creation of the layout or the last
+    /// [reset](SortKeyLayout::reset), in order.
+    pub fn passes(&self) -> &[PassInfo] {
+        &self.passes
+    }
+
+    /// Returns the first pass with the specified name.
+    pub fn pass(&self, name: &str) -> Option<&PassInfo> {
+        self.passes.iter().find(|p| p.name == name)
+    }
+
+    /// Returns the pass that contains the specified sort key.
+    pub fn pass_of_key(&self, sortkey: u64) -> Option<&PassInfo> {
+        self.passes
+            .iter()
+            .find(|p| sortkey >= p.base_key && sortkey - p.base_key < p.key_count)
+    }
+
+    /// Forgets the passes, so that the next pass starts at the base key again (e.g. at the
+    /// beginning of a frame).
+    pub fn reset(&mut self) {
+        self.passes.clear();
+    }
+}
+
+/// Records commands in a named pass.
+///
+/// `render_pass!(cmdbuf, layout, name, { ... })` assigns the next range of sort keys of
+/// `layout` (a [SortKeyLayout](crate::pass::SortKeyLayout)) to a pass named `name` (a
+/// `&'static str`), and evaluates the block with the sort keys of `cmdbuf` local to this range
+/// (see [CommandBuffer::begin_pass](crate::command::CommandBuffer::begin_pass)). The commands of
+/// the block are enclosed in a debug group named after the pass, and the invocation of the
+/// macro is attached to them as their call site (see [collision](crate::collision)).
+///
+/// The value of the macro is the [PassInfo](crate::pass::PassInfo) of the pass. Passes can be
+/// nested, with a layout whose range fits in the range of the enclosing pass:
+/// ```ignore
+/// let post = render_pass!(cmdbuf, frame_layout, "post-processing", {
+///     let mut post_layout = SortKeyLayout::new(8);
+///     render_pass!(cmdbuf, post_layout, "bloom", { bloom.record(&mut cmdbuf, 0x0) });
+///     render_pass!(cmdbuf, post_layout, "tonemap", { tonemap.record(&mut cmdbuf, 0x0) });
+/// });
+/// ```
+#[macro_export]
+macro_rules! render_pass {
+    ($cmdbuf:expr, $layout:expr, $name:expr, $body:block) => {{
+        let __pass = $layout.next_pass($name);
+        $cmdbuf.set_call_site($crate::call_site!());
+        $cmdbuf.begin_pass(__pass);
+        $body;
+        $cmdbuf.end_pass();
+        __pass
+    }};
+}
@@ -0,0 +1,95 @@
+use crate::{
+    pipeline::{ArgumentBlock, Arguments, IntoArgumentBlock, Signature, TypedSignature},
+    Arena, Backend,
+};
+use std::{
+    cell::Cell,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// An argument block that is recreated only when its arguments change.
+///
+/// Argument blocks are immutable and allocated in an arena, so they are usually rebuilt each frame
+/// in a per-frame arena even if the resources they reference did not change.
+/// A `CachedArgumentBlock` keeps the last block it created (in a long-lived arena), along with
+/// the arguments used to create it and their hash. On each call to
+/// [get](CachedArgumentBlock::get), a new block is created only if the new arguments are
+/// different: their hash is compared first, then the arguments themselves.
+///
+/// Resources are hashed and compared by identity (the address of the backend object) and views
+/// also compare their subresource range and sampler. Plain values are compared by value. Note that
+/// a buffer does not compare its contents, so updating the contents of a buffer does not
+/// invalidate the block (which is usually what you want).
+///
+/// Outdated blocks are not freed until the arena is dropped: this saves work only if the
+/// arguments change less often than once per frame.
+/// Use [invalidate](CachedArgumentBlock::invalidate) to force the creation of a new block
+/// (e.g. when a referenced resource has been recreated at the same address).
+pub struct CachedArgumentBlock<'a, B: Backend, S: Signature<'a, B>, A> {
+    arena: &'a Arena<'a, B>,
+    signature: S,
+    cached: Cell<Option<(u64, A, ArgumentBlock<'a, B, S>)>>,
+}
+
+/// Cached argument block with a statically known signature, created from arguments of type `T`.
+pub type TypedCachedArgumentBlock<'a, B, T> =
+    CachedArgumentBlock<'a, B, TypedSignature<'a, B, T>, T>;
+
+impl<'a, B: Backend, S: Signature<'a, B>, A> CachedArgumentBlock<'a, B, S, A> {
+    /// Creates a new cache for argument blocks of the given signature.
+    /// Argument blocks will be allocated in `arena`.
+    pub fn new(arena: &'a Arena<'a, B>, signature: S) -> CachedArgumentBlock<'a, B, S, A> {
+        CachedArgumentBlock {
+            arena,
+            signature,
+            cached: Cell::new(None),
+        }
+    }
+
+    /// Forgets the cached block: the next call to `get` will create a new argument block.
+    pub fn invalidate(&self) {
+        self.cached.set(None);
+    }
+
+    /// Returns the signature of the argument blocks.
+    pub fn signature(&self) -> S {
+        self.signature
+    }
+}
+
+impl<'a, B, S, A> CachedArgumentBlock<'a, B, S, A>
+where
+    B: Backend,
+    S: Signature<'a, B>,
+    A: IntoArgumentBlock<'a, B, S> + Hash + Eq + Copy,
+{
+    /// Returns an argument block for the given arguments. The cached block is returned
+    /// if the arguments are equal to the arguments it was created with.
+    pub fn get(&self, args: A) -> ArgumentBlock<'a, B, S> {
+        let mut hasher = DefaultHasher::new();
+        args.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        match self.cached.get() {
+            Some((cached_hash, cached_args, block))
+                if cached_hash == hash && cached_args == args =>
+            {
+                block
+            }
+            _ => {
+                let block = args.into_block(self.signature, self.arena);
+                self.cached.set(Some((hash, args, block)));
+                block
+            }
+        }
+    }
+}
+
+impl<'a, B: Backend, T: Arguments<'a, B>> TypedCachedArgumentBlock<'a, B, T> {
+    /// Creates a new cache for argument blocks of type `T`.
+    pub fn typed(arena: &'a Arena<'a, B>) -> TypedCachedArgumentBlock<'a, B, T> {
+        let signature = arena.renderer.get_cached_signature::<T>();
+        CachedArgumentBlock::new(arena, signature)
+    }
+}
@@ -0,0 +1,251 @@
+//! Human-readable listings of shader interfaces and signature trees.
+//!
+//! Descriptors are listed with the `(set, binding)` pair that the shaders must use: comparing
+//! the output of [ShaderModule::dump_interface] with the one of
+//! [GraphicsPipeline::dump_signature] usually shows where a shader and an `Arguments` struct
+//! disagree. See also [validate](crate::pipeline::validate).
+//!
+//! [ShaderModule::dump_interface]: crate::pipeline::ShaderModule::dump_interface
+//! [GraphicsPipeline::dump_signature]: crate::pipeline::GraphicsPipeline::dump_signature
+use crate::{
+    descriptor::ResourceBinding,
+    format::Format,
+    pipeline::{ShaderStageReflection, SignatureDescription},
+    typedesc::{PrimitiveType, TypeDesc},
+};
+use std::fmt::Write;
+
+fn primitive_type_name(ty: PrimitiveType) -> &'static str {
+    match ty {
+        PrimitiveType::Int => "int",
+        PrimitiveType::UnsignedInt => "uint",
+        PrimitiveType::Half => "half",
+        PrimitiveType::Float => "float",
+        PrimitiveType::Double => "double",
+        PrimitiveType::Bool => "bool",
+    }
+}
+
+fn vector_prefix(ty: PrimitiveType) -> &'static str {
+    match ty {
+        PrimitiveType::Int => "i",
+        PrimitiveType::UnsignedInt => "u",
+        PrimitiveType::Double => "d",
+        PrimitiveType::Bool => "b",
+        PrimitiveType::Half | PrimitiveType::Float => "",
+    }
+}
+
+/// Returns the GLSL-like name of a type (e.g. `vec3`, `mat4x3`, `struct { float, uint[] }`).
+pub fn type_name(ty: &TypeDesc) -> String {
+    match *ty {
+        TypeDesc::Primitive(p) => primitive_type_name(p).to_string(),
+        TypeDesc::Vector { elem_ty, len } => format!("{}vec{}", vector_prefix(elem_ty), len),
+        TypeDesc::Matrix {
+            elem_ty,
+            rows,
+            columns,
+        } => {
+            if rows == columns {
+                format!("{}mat{}", vector_prefix(elem_ty), columns)
+            } else {
+                format!("{}mat{}x{}", vector_prefix(elem_ty), columns, rows)
+            }
+        }
+        TypeDesc::Array { elem_ty, len: 0 } => format!("{}[]", type_name(elem_ty)),
+        TypeDesc::Array { elem_ty, len } => format!("{}[{}]", type_name(elem_ty), len),
+        TypeDesc::Struct { fields } => format!(
+            "struct {{ {} }}",
+            fields
+                .iter()
+                .map(|&f| type_name(f))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TypeDesc::Pointer(pointee) => type_name(pointee),
+        ref other => format!("{:?}", other),
+    }
+}
+
+fn quoted_name(name: Option<&str>) -> String {
+    name.map(|n| format!(" \"{}\"", n)).unwrap_or_default()
+}
+
+fn write_binding(out: &mut String, indent: &str, set: u32, d: &ResourceBinding) {
+    write!(
+        out,
+        "{}set {} binding {}: {:?}{}",
+        indent,
+        set,
+        d.index,
+        d.ty,
+        quoted_name(d.name)
+    )
+    .unwrap();
+    if let Some(ty) = d.data_ty {
+        write!(out, " of {}", type_name(ty)).unwrap();
+    }
+    if d.data_format != Format::UNDEFINED {
+        write!(out, " format {:?}", d.data_format).unwrap();
+    }
+    out.push('\n');
+}
+
+/// Lists the descriptors, vertex inputs and fragment outputs of a shader stage.
+pub fn dump_shader_interface(reflection: &ShaderStageReflection) -> String {
+    let mut out = String::new();
+    writeln!(out, "{:?} shader", reflection.stage).unwrap();
+
+    writeln!(out, "  descriptors:").unwrap();
+    for d in reflection.descriptors.iter() {
+        write_binding(&mut out, "    ", d.set.unwrap_or(0), d);
+    }
+
+    if !reflection.vertex_input_attributes.is_empty() {
+        writeln!(out, "  vertex inputs:").unwrap();
+        for a in reflection.vertex_input_attributes.iter() {
+            let location = a
+                .location
+                .map(|l| l.to_string())
+                .unwrap_or_else(|| "?".to_string());
+            writeln!(
+                out,
+                "    location {}: {}{}",
+                location,
+                type_name(a.ty),
+                quoted_name(a.name)
+            )
+            .unwrap();
+        }
+    }
+
+    if !reflection.fragment_outputs.is_empty() {
+        writeln!(
+            out,
+            "  fragment outputs: {}",
+            reflection.fragment_outputs.len()
+        )
+        .unwrap();
+        for a in reflection.fragment_output_attributes.iter() {
+            writeln!(
+                out,
+                "    location {}: {}{}",
+                a.location,
+                type_name(a.ty),
+                quoted_name(a.name)
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+/// State threaded through the traversal of a signature tree.
+struct SignatureDumper {
+    out: String,
+    /// Next descriptor set number.
+    set: u32,
+    /// Next vertex attribute location.
+    location: u32,
+    /// Next vertex buffer index.
+    vertex_buffer: usize,
+    /// Next render target index.
+    render_target: usize,
+}
+
+impl SignatureDumper {
+    // Sets, locations and render targets are numbered in the same order as in validation:
+    // inherited blocks first, then the block itself.
+    fn dump(&mut self, signature: &SignatureDescription, depth: usize) {
+        let indent = "  ".repeat(depth);
+        writeln!(self.out, "{}block", indent).unwrap();
+        for &inherited in signature.inherited {
+            self.dump(inherited, depth + 1);
+        }
+
+        if !signature.descriptors.is_empty() {
+            let item_indent = format!("{}  ", indent);
+            for d in signature.descriptors.iter() {
+                write_binding(&mut self.out, &item_indent, self.set, d);
+            }
+            self.set += 1;
+        }
+
+        for binding in signature.vertex_inputs.iter() {
+            if let Some(base_location) = binding.base_location {
+                self.location = base_location;
+            }
+            writeln!(
+                self.out,
+                "{}  vertex buffer #{} ({:?}, stride {})",
+                indent, self.vertex_buffer, binding.rate, binding.layout.stride
+            )
+            .unwrap();
+            self.vertex_buffer += 1;
+            for e in binding.layout.elements.iter() {
+                write!(
+                    self.out,
+                    "{}    location {}: {:?} at offset {}{}",
+                    indent,
+                    self.location,
+                    e.format,
+                    e.offset,
+                    quoted_name(e.name)
+                )
+                .unwrap();
+                if let Some(semantic) = e.semantic {
+                    write!(self.out, " semantic {}{}", semantic.name, semantic.index).unwrap();
+                }
+                self.out.push('\n');
+                self.location += 1;
+            }
+        }
+
+        if let Some(format) = signature.index_format {
+            writeln!(self.out, "{}  index buffer ({:?})", indent, format).unwrap();
+        }
+
+        for output in signature.fragment_outputs.iter() {
+            writeln!(
+                self.out,
+                "{}  render target #{} (load {:?}, store {:?})",
+                indent, self.render_target, output.load_op, output.store_op
+            )
+            .unwrap();
+            self.render_target += 1;
+        }
+        if let Some(output) = signature.depth_stencil_fragment_output {
+            writeln!(
+                self.out,
+                "{}  depth-stencil render target (load {:?}, store {:?})",
+                indent, output.load_op, output.store_op
+            )
+            .unwrap();
+        }
+
+        if signature.num_viewports > 0 || signature.num_scissors > 0 {
+            writeln!(
+                self.out,
+                "{}  viewports: {}, scissors: {}",
+                indent, signature.num_viewports, signature.num_scissors
+            )
+            .unwrap();
+        }
+        if signature.has_stencil_reference {
+            writeln!(self.out, "{}  stencil reference", indent).unwrap();
+        }
+    }
+}
+
+/// Lists the contents of a signature and of its inherited signatures, as a tree.
+pub fn dump_signature(signature: &SignatureDescription) -> String {
+    let mut dumper = SignatureDumper {
+        out: String::new(),
+        set: 0,
+        location: 0,
+        vertex_buffer: 0,
+        render_target: 0,
+    };
+    dumper.dump(signature, 0);
+    dumper.out
+}
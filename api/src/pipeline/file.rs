@@ -0,0 +1,757 @@
+//! Pipeline state files: the fixed-function states of a graphics pipeline, loaded at runtime.
+//!
+//! A pipeline state file is a TOML document with one (optional) table per state. Each table can
+//! start from a preset (e.g. `RasterisationState::CULL_BACK_CCW`) and override individual fields.
+//! Omitted tables and fields keep their defaults:
+//! ```toml
+//! view_count = 1
+//!
+//! [rasterization]
+//! preset = "cull_back_ccw"           # default, cull_back_ccw, cull_back_cw, cull_front_ccw, wireframe
+//! polygon_mode = "fill"              # point, line, fill
+//! cull_mode = "back"                 # none, front, back, front_and_back
+//! front_face = "counter_clockwise"   # clockwise, counter_clockwise
+//! depth_clamp = false
+//! depth_bias = { constant_factor = 1.0, clamp = 0.0, slope_factor = 1.5 }
+//! line_width = 1.0                   # or "dynamic"
+//! line_smooth = false
+//! point_size = 1.0                   # or "program"
+//!
+//! [depth_stencil]
+//! preset = "less_write"              # disabled, less_write, less_equal_write, less_equal_read_only, ...
+//! depth_test = true
+//! depth_write = true
+//! depth_compare_op = "less"          # never, less, equal, less_or_equal, greater, ...
+//! depth_bounds = [0.0, 1.0]
+//! stencil = { compare_op = "equal", pass_op = "keep", reference = 1 }   # or front/back tables
+//! dynamic_stencil_reference = false
+//!
+//! [input_assembly]
+//! topology = "triangle_list"         # point_list, line_list, line_strip, triangle_list, ...
+//! primitive_restart = false
+//!
+//! [multisample]
+//! samples = 4
+//! min_sample_shading = 0.5
+//! alpha_to_coverage = false
+//!
+//! [color_blend]
+//! attachments = "alpha_blending"     # one state for all attachments, or an array of states
+//! logic_op = "xor"
+//! blend_constants = [0.0, 0.0, 0.0, 0.0]
+//! ```
+//! Blend states of attachments are either preset names (`disabled`, `alpha_blending`, `additive`,
+//! `premultiplied`, `multiply`) or tables with an optional `preset` and the `src_color`,
+//! `dst_color`, `color_op`, `src_alpha`, `dst_alpha`, `alpha_op` and `write_mask` (e.g. `"rgb"`)
+//! fields. Since TOML arrays cannot mix strings and tables, separate attachments that need a
+//! table are all specified as tables (`[[color_blend.attachments]]`).
+//!
+//! Unknown keys are errors, so that typos are not silently ignored.
+//!
+//! [PipelineStateFile] keeps track of the modification time of the file, so that pipelines can be
+//! recreated when the file changes (see [PipelineStateFile::reload_if_modified]).
+use super::*;
+use config::Value;
+use std::{
+    collections::HashMap,
+    error, fmt, fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Error while reading a pipeline state file.
+#[derive(Debug)]
+pub enum PipelineFileError {
+    Io(io::Error),
+    /// The file is not a valid TOML document.
+    Syntax(config::ConfigError),
+    /// A key that does not correspond to a state.
+    UnknownKey {
+        key: String,
+    },
+    /// A value with the wrong type, or an unknown name.
+    InvalidValue {
+        key: String,
+        expected: String,
+    },
+}
+
+impl fmt::Display for PipelineFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PipelineFileError::Io(err) => write!(f, "could not read pipeline state file: {}", err),
+            PipelineFileError::Syntax(err) => write!(f, "invalid pipeline state file: {}", err),
+            PipelineFileError::UnknownKey { key } => write!(f, "unknown key `{}`", key),
+            PipelineFileError::InvalidValue { key, expected } => {
+                write!(f, "invalid value for `{}`: expected {}", key, expected)
+            }
+        }
+    }
+}
+
+impl error::Error for PipelineFileError {}
+
+impl From<io::Error> for PipelineFileError {
+    fn from(err: io::Error) -> Self {
+        PipelineFileError::Io(err)
+    }
+}
+
+type Result<T> = ::std::result::Result<T, PipelineFileError>;
+
+/// The fixed-function states described by a pipeline state file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineStates {
+    pub rasterization_state: RasterisationState,
+    pub multisample_state: MultisampleState,
+    pub depth_stencil_state: DepthStencilState,
+    pub input_assembly_state: InputAssemblyState,
+    pub color_blend_attachments: ColorBlendAttachmentsOwned,
+    pub logic_op: Option<LogicOp>,
+    pub blend_constants: [NotNan<f32>; 4],
+    pub view_count: ViewCount,
+}
+
+impl Default for PipelineStates {
+    fn default() -> Self {
+        PipelineStates {
+            rasterization_state: RasterisationState::default(),
+            multisample_state: MultisampleState::default(),
+            depth_stencil_state: DepthStencilState::default(),
+            input_assembly_state: InputAssemblyState::default(),
+            color_blend_attachments: ColorBlendState::DISABLED.attachments.into(),
+            logic_op: None,
+            blend_constants: ColorBlendState::DISABLED.blend_constants,
+            view_count: ViewCount::default(),
+        }
+    }
+}
+
+impl PipelineStates {
+    /// Parses the contents of a pipeline state file.
+    pub fn parse(text: &str) -> Result<PipelineStates> {
+        let mut cfg = config::Config::new();
+        cfg.merge(config::File::from_str(text, config::FileFormat::Toml))
+            .map_err(PipelineFileError::Syntax)?;
+        let root = cfg
+            .try_into::<HashMap<String, Value>>()
+            .map_err(PipelineFileError::Syntax)?;
+        let mut root = Table::new(String::new(), root);
+
+        let mut states = PipelineStates::default();
+        if let Some(table) = root.table("rasterization")? {
+            states.rasterization_state = parse_rasterization_state(table)?;
+        }
+        if let Some(table) = root.table("multisample")? {
+            states.multisample_state = parse_multisample_state(table)?;
+        }
+        if let Some(table) = root.table("depth_stencil")? {
+            states.depth_stencil_state = parse_depth_stencil_state(table)?;
+        }
+        if let Some(table) = root.table("input_assembly")? {
+            states.input_assembly_state = parse_input_assembly_state(table)?;
+        }
+        if let Some(mut table) = root.table("color_blend")? {
+            if let Some(value) = table.take("attachments") {
+                let key = table.key("attachments");
+                states.color_blend_attachments = match value.clone().into_array() {
+                    Ok(array) => ColorBlendAttachmentsOwned::Separate(
+                        array
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, v)| {
+                                parse_color_blend_attachment(format!("{}[{}]", key, i), v)
+                            })
+                            .collect::<Result<_>>()?,
+                    ),
+                    Err(_) => {
+                        ColorBlendAttachmentsOwned::All(parse_color_blend_attachment(key, value)?)
+                    }
+                };
+            }
+            if let Some(op) = table.name("logic_op", LOGIC_OPS)? {
+                states.logic_op = Some(op);
+            }
+            if let Some(constants) = table.floats("blend_constants", 4)? {
+                states.blend_constants = [constants[0], constants[1], constants[2], constants[3]];
+            }
+            table.finish()?;
+        }
+        if let Some(count) = root.uint("view_count")? {
+            states.view_count = ViewCount(count);
+        }
+        root.finish()?;
+        Ok(states)
+    }
+
+    /// Returns the creation parameters of a pipeline with these states.
+    ///
+    /// The viewport state is not described by the file: it is `ViewportState::default()`
+    /// (viewports provided by the argument blocks, no scissors), replace it if necessary.
+    pub fn create_info<'a, 'b, B: Backend>(
+        &'b self,
+        shader_stages: GraphicsShaderStages<'a, 'b, B>,
+    ) -> GraphicsPipelineCreateInfo<'a, 'b, B> {
+        GraphicsPipelineCreateInfo {
+            shader_stages,
+            viewport_state: ViewportState::default(),
+            rasterization_state: self.rasterization_state,
+            multisample_state: self.multisample_state,
+            depth_stencil_state: self.depth_stencil_state,
+            input_assembly_state: self.input_assembly_state,
+            color_blend_state: ColorBlendState {
+                logic_op: self.logic_op,
+                attachments: self.color_blend_attachments.as_attachments(),
+                blend_constants: self.blend_constants,
+            },
+            view_count: self.view_count,
+        }
+    }
+}
+
+/// A pipeline state file loaded from disk, which can be reloaded when it is modified.
+#[derive(Clone, Debug)]
+pub struct PipelineStateFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    states: PipelineStates,
+}
+
+impl PipelineStateFile {
+    /// Loads and parses a pipeline state file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<PipelineStateFile> {
+        let path = path.as_ref().to_path_buf();
+        let modified = modification_time(&path);
+        let states = PipelineStates::parse(&fs::read_to_string(&path)?)?;
+        Ok(PipelineStateFile {
+            path,
+            modified,
+            states,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the states loaded from the file.
+    pub fn states(&self) -> &PipelineStates {
+        &self.states
+    }
+
+    /// Reloads the file if it has been modified since it was last loaded, and returns whether the
+    /// states were reloaded (in which case pipelines created from the file should be recreated).
+    ///
+    /// If the new contents are invalid, the previous states are kept, and the error is returned
+    /// only once for each modification of the file.
+    pub fn reload_if_modified(&mut self) -> Result<bool> {
+        let modified = modification_time(&self.path);
+        if modified == self.modified {
+            return Ok(false);
+        }
+        self.modified = modified;
+        self.states = PipelineStates::parse(&fs::read_to_string(&self.path)?)?;
+        Ok(true)
+    }
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl<'a, 'b, B: Backend> GraphicsPipelineCreateInfo<'a, 'b, B> {
+    /// Returns the creation parameters of a pipeline with the states of a pipeline state file.
+    ///
+    /// See [PipelineStates::create_info].
+    pub fn from_file(
+        file: &'b PipelineStateFile,
+        shader_stages: GraphicsShaderStages<'a, 'b, B>,
+    ) -> GraphicsPipelineCreateInfo<'a, 'b, B> {
+        file.states.create_info(shader_stages)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Entries of a table of the file, removed as they are read.
+struct Table {
+    path: String,
+    entries: HashMap<String, Value>,
+}
+
+impl Table {
+    fn new(path: String, entries: HashMap<String, Value>) -> Table {
+        Table { path, entries }
+    }
+
+    fn key(&self, name: &str) -> String {
+        if self.path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.path, name)
+        }
+    }
+
+    fn take(&mut self, name: &str) -> Option<Value> {
+        self.entries.remove(name)
+    }
+
+    fn invalid(&self, name: &str, expected: &str) -> PipelineFileError {
+        PipelineFileError::InvalidValue {
+            key: self.key(name),
+            expected: expected.to_string(),
+        }
+    }
+
+    fn bool(&mut self, name: &str) -> Result<Option<bool>> {
+        match self.take(name) {
+            Some(v) => v
+                .into_bool()
+                .map(Some)
+                .map_err(|_| self.invalid(name, "a boolean")),
+            None => Ok(None),
+        }
+    }
+
+    fn uint(&mut self, name: &str) -> Result<Option<u32>> {
+        match self.take(name) {
+            Some(v) => match v.into_int() {
+                Ok(i) if i >= 0 && i <= i64::from(u32::max_value()) => Ok(Some(i as u32)),
+                _ => Err(self.invalid(name, "a positive integer")),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn float(&mut self, name: &str) -> Result<Option<NotNan<f32>>> {
+        match self.take(name) {
+            Some(v) => to_float(v)
+                .map(Some)
+                .ok_or_else(|| self.invalid(name, "a number")),
+            None => Ok(None),
+        }
+    }
+
+    fn floats(&mut self, name: &str, count: usize) -> Result<Option<Vec<NotNan<f32>>>> {
+        match self.take(name) {
+            Some(v) => {
+                let expected = format!("an array of {} numbers", count);
+                let floats = v
+                    .into_array()
+                    .ok()
+                    .and_then(|a| a.into_iter().map(to_float).collect::<Option<Vec<_>>>())
+                    .filter(|floats| floats.len() == count)
+                    .ok_or_else(|| self.invalid(name, &expected))?;
+                Ok(Some(floats))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Reads a value among a list of names.
+    fn name<T: Copy>(&mut self, name: &str, names: &[(&str, T)]) -> Result<Option<T>> {
+        match self.take(name) {
+            Some(v) => {
+                let s = v.into_str().ok().map(|s| s.to_lowercase());
+                match s.and_then(|s| names.iter().find(|n| n.0 == s)) {
+                    Some(&(_, value)) => Ok(Some(value)),
+                    None => Err(self.invalid(name, &expected_names(names))),
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn table(&mut self, name: &str) -> Result<Option<Table>> {
+        match self.take(name) {
+            Some(v) => {
+                let entries = v.into_table().map_err(|_| self.invalid(name, "a table"))?;
+                Ok(Some(Table::new(self.key(name), entries)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Checks that all the entries of the table have been read.
+    fn finish(self) -> Result<()> {
+        match self.entries.keys().min() {
+            Some(name) => Err(PipelineFileError::UnknownKey {
+                key: self.key(name),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn to_float(v: Value) -> Option<NotNan<f32>> {
+    v.into_float().ok().and_then(|f| NotNan::new(f as f32).ok())
+}
+
+fn expected_names<T>(names: &[(&str, T)]) -> String {
+    let names: Vec<_> = names.iter().map(|n| format!("`{}`", n.0)).collect();
+    format!("one of {}", names.join(", "))
+}
+
+const RASTERIZATION_PRESETS: &[(&str, RasterisationState)] = &[
+    ("default", RasterisationState::DEFAULT),
+    ("cull_back_ccw", RasterisationState::CULL_BACK_CCW),
+    ("cull_back_cw", RasterisationState::CULL_BACK_CW),
+    ("cull_front_ccw", RasterisationState::CULL_FRONT_CCW),
+    ("wireframe", RasterisationState::WIREFRAME),
+];
+
+const POLYGON_MODES: &[(&str, PolygonMode)] = &[
+    ("point", PolygonMode::Point),
+    ("line", PolygonMode::Line),
+    ("fill", PolygonMode::Fill),
+];
+
+const CULL_MODES: &[(&str, CullModeFlags)] = &[
+    ("none", CullModeFlags::NONE),
+    ("front", CullModeFlags::FRONT),
+    ("back", CullModeFlags::BACK),
+    ("front_and_back", CullModeFlags::FRONT_AND_BACK),
+];
+
+const FRONT_FACES: &[(&str, FrontFace)] = &[
+    ("clockwise", FrontFace::Clockwise),
+    ("counter_clockwise", FrontFace::CounterClockwise),
+];
+
+const DEPTH_STENCIL_PRESETS: &[(&str, DepthStencilState)] = &[
+    ("disabled", DepthStencilState::DISABLED),
+    ("less_write", DepthStencilState::LESS_WRITE),
+    ("less_equal_write", DepthStencilState::LESS_EQUAL_WRITE),
+    (
+        "less_equal_read_only",
+        DepthStencilState::LESS_EQUAL_READ_ONLY,
+    ),
+    ("greater_write", DepthStencilState::GREATER_WRITE),
+    (
+        "greater_equal_write",
+        DepthStencilState::GREATER_EQUAL_WRITE,
+    ),
+];
+
+const COMPARE_OPS: &[(&str, CompareOp)] = &[
+    ("never", CompareOp::Never),
+    ("less", CompareOp::Less),
+    ("equal", CompareOp::Equal),
+    ("less_or_equal", CompareOp::LessOrEqual),
+    ("greater", CompareOp::Greater),
+    ("not_equal", CompareOp::NotEqual),
+    ("greater_or_equal", CompareOp::GreaterOrEqual),
+    ("always", CompareOp::Always),
+];
+
+const STENCIL_OPS: &[(&str, StencilOp)] = &[
+    ("keep", StencilOp::Keep),
+    ("zero", StencilOp::Zero),
+    ("replace", StencilOp::Replace),
+    ("increment_and_clamp", StencilOp::IncrementAndClamp),
+    ("decrement_and_clamp", StencilOp::DecrementAndClamp),
+    ("invert", StencilOp::Invert),
+    ("increment_and_wrap", StencilOp::IncrementAndWrap),
+    ("decrement_and_wrap", StencilOp::DecrementAndWrap),
+];
+
+const TOPOLOGIES: &[(&str, PrimitiveTopology)] = &[
+    ("point_list", PrimitiveTopology::PointList),
+    ("line_list", PrimitiveTopology::LineList),
+    ("line_strip", PrimitiveTopology::LineStrip),
+    ("triangle_list", PrimitiveTopology::TriangleList),
+    ("triangle_strip", PrimitiveTopology::TriangleStrip),
+    ("triangle_fan", PrimitiveTopology::TriangleFan),
+];
+
+const BLEND_PRESETS: &[(&str, ColorBlendAttachmentState)] = &[
+    ("disabled", ColorBlendAttachmentState::DISABLED),
+    ("alpha_blending", ColorBlendAttachmentState::ALPHA_BLENDING),
+    ("additive", ColorBlendAttachmentState::ADDITIVE),
+    ("premultiplied", ColorBlendAttachmentState::PREMULTIPLIED),
+    ("multiply", ColorBlendAttachmentState::MULTIPLY),
+];
+
+const BLEND_FACTORS: &[(&str, BlendFactor)] = &[
+    ("zero", BlendFactor::Zero),
+    ("one", BlendFactor::One),
+    ("src_color", BlendFactor::SrcColor),
+    ("one_minus_src_color", BlendFactor::OneMinusSrcColor),
+    ("dst_color", BlendFactor::DstColor),
+    ("one_minus_dst_color", BlendFactor::OneMinusDstColor),
+    ("src_alpha", BlendFactor::SrcAlpha),
+    ("one_minus_src_alpha", BlendFactor::OneMinusSrcAlpha),
+    ("dst_alpha", BlendFactor::DstAlpha),
+    ("one_minus_dst_alpha", BlendFactor::OneMinusDstAlpha),
+    ("constant_color", BlendFactor::ConstantColor),
+    (
+        "one_minus_constant_color",
+        BlendFactor::OneMinusConstantColor,
+    ),
+    ("constant_alpha", BlendFactor::ConstantAlpha),
+    (
+        "one_minus_constant_alpha",
+        BlendFactor::OneMinusConstantAlpha,
+    ),
+    ("src_alpha_saturate", BlendFactor::SrcAlphaSaturate),
+    ("src1_color", BlendFactor::Src1Color),
+    ("one_minus_src1_color", BlendFactor::OneMinusSrc1Color),
+    ("src1_alpha", BlendFactor::Src1Alpha),
+    ("one_minus_src1_alpha", BlendFactor::OneMinusSrc1Alpha),
+];
+
+const BLEND_OPS: &[(&str, BlendOp)] = &[
+    ("add", BlendOp::Add),
+    ("subtract", BlendOp::Subtract),
+    ("reverse_subtract", BlendOp::ReverseSubtract),
+    ("min", BlendOp::Min),
+    ("max", BlendOp::Max),
+];
+
+const LOGIC_OPS: &[(&str, LogicOp)] = &[
+    ("clear", LogicOp::Clear),
+    ("and", LogicOp::And),
+    ("and_reverse", LogicOp::AndReverse),
+    ("copy", LogicOp::Copy),
+    ("and_inverted", LogicOp::AndInverted),
+    ("no_op", LogicOp::NoOp),
+    ("xor", LogicOp::Xor),
+    ("or", LogicOp::Or),
+    ("nor", LogicOp::Nor),
+    ("equivalent", LogicOp::Equivalent),
+    ("invert", LogicOp::Invert),
+    ("or_reverse", LogicOp::OrReverse),
+    ("copy_inverted", LogicOp::CopyInverted),
+    ("or_inverted", LogicOp::OrInverted),
+    ("nand", LogicOp::Nand),
+    ("set", LogicOp::Set),
+];
+
+fn parse_rasterization_state(mut t: Table) -> Result<RasterisationState> {
+    let mut state = t
+        .name("preset", RASTERIZATION_PRESETS)?
+        .unwrap_or(RasterisationState::DEFAULT);
+    if let Some(polygon_mode) = t.name("polygon_mode", POLYGON_MODES)? {
+        state.polygon_mode = polygon_mode;
+    }
+    if let Some(cull_mode) = t.name("cull_mode", CULL_MODES)? {
+        state.cull_mode = cull_mode;
+    }
+    if let Some(front_face) = t.name("front_face", FRONT_FACES)? {
+        state.front_face = front_face;
+    }
+    if let Some(enable) = t.bool("depth_clamp")? {
+        state.depth_clamp_enable = enable;
+    }
+    if let Some(enable) = t.bool("rasterizer_discard")? {
+        state.rasterizer_discard_enable = enable;
+    }
+    if let Some(mut bias) = t.table("depth_bias")? {
+        state.depth_bias = DepthBias::Enabled {
+            constant_factor: bias
+                .float("constant_factor")?
+                .unwrap_or_else(|| 0.0f32.into()),
+            clamp: bias.float("clamp")?.unwrap_or_else(|| 0.0f32.into()),
+            slope_factor: bias.float("slope_factor")?.unwrap_or_else(|| 0.0f32.into()),
+        };
+        bias.finish()?;
+    }
+    if let Some(v) = t.take("line_width") {
+        state.line_width = match v.clone().into_str() {
+            Ok(ref s) if s.to_lowercase() == "dynamic" => LineWidth::Dynamic,
+            _ => LineWidth::Static(
+                to_float(v).ok_or_else(|| t.invalid("line_width", "a number or `dynamic`"))?,
+            ),
+        };
+    }
+    if let Some(enable) = t.bool("line_smooth")? {
+        state.line_smooth_enable = enable;
+    }
+    if let Some(v) = t.take("point_size") {
+        state.point_size = match v.clone().into_str() {
+            Ok(ref s) if s.to_lowercase() == "program" => PointSize::Program,
+            _ => PointSize::Static(
+                to_float(v).ok_or_else(|| t.invalid("point_size", "a number or `program`"))?,
+            ),
+        };
+    }
+    t.finish()?;
+    Ok(state)
+}
+
+fn parse_multisample_state(mut t: Table) -> Result<MultisampleState> {
+    let mut state = MultisampleState::default();
+    if let Some(samples) = t.uint("samples")? {
+        state.rasterization_samples = samples;
+    }
+    if let Some(min_sample_shading) = t.float("min_sample_shading")? {
+        state.sample_shading = SampleShading::Enabled { min_sample_shading };
+    }
+    if let Some(v) = t.take("sample_mask") {
+        state.sample_mask = match v.into_int() {
+            // TOML integers are signed: -1 enables all samples
+            Ok(mask) => mask as u64,
+            Err(_) => return Err(t.invalid("sample_mask", "an integer")),
+        };
+    }
+    if let Some(enable) = t.bool("alpha_to_coverage")? {
+        state.alpha_to_coverage_enable = enable;
+    }
+    if let Some(enable) = t.bool("alpha_to_one")? {
+        state.alpha_to_one_enable = enable;
+    }
+    t.finish()?;
+    Ok(state)
+}
+
+fn parse_stencil_op_state(mut t: Table) -> Result<StencilOpState> {
+    let state = StencilOpState {
+        fail_op: t.name("fail_op", STENCIL_OPS)?.unwrap_or(StencilOp::Keep),
+        pass_op: t.name("pass_op", STENCIL_OPS)?.unwrap_or(StencilOp::Keep),
+        depth_fail_op: t
+            .name("depth_fail_op", STENCIL_OPS)?
+            .unwrap_or(StencilOp::Keep),
+        compare_op: t
+            .name("compare_op", COMPARE_OPS)?
+            .unwrap_or(CompareOp::Always),
+        compare_mask: t.uint("compare_mask")?.unwrap_or(0xff),
+        write_mask: t.uint("write_mask")?.unwrap_or(0xff),
+        reference: t.uint("reference")?.unwrap_or(0),
+    };
+    t.finish()?;
+    Ok(state)
+}
+
+fn parse_depth_stencil_state(mut t: Table) -> Result<DepthStencilState> {
+    let mut state = t
+        .name("preset", DEPTH_STENCIL_PRESETS)?
+        .unwrap_or(DepthStencilState::DISABLED);
+    if let Some(enable) = t.bool("depth_test")? {
+        state.depth_test_enable = enable;
+    }
+    if let Some(enable) = t.bool("depth_write")? {
+        state.depth_write_enable = enable;
+    }
+    if let Some(op) = t.name("depth_compare_op", COMPARE_OPS)? {
+        state.depth_compare_op = op;
+    }
+    if let Some(bounds) = t.floats("depth_bounds", 2)? {
+        state.depth_bounds_test = DepthBoundTest::Enabled {
+            min_depth_bounds: bounds[0],
+            max_depth_bounds: bounds[1],
+        };
+    }
+    if let Some(stencil) = t.table("stencil")? {
+        let ops = parse_stencil_op_state(stencil)?;
+        state.stencil_test = StencilTest::Enabled {
+            front: ops,
+            back: ops,
+        };
+    } else if let Some(front) = t.table("stencil_front")? {
+        let front = parse_stencil_op_state(front)?;
+        let back = match t.table("stencil_back")? {
+            Some(back) => parse_stencil_op_state(back)?,
+            None => front,
+        };
+        state.stencil_test = StencilTest::Enabled { front, back };
+    }
+    if let Some(dynamic) = t.bool("dynamic_stencil_reference")? {
+        state.dynamic_stencil_reference = dynamic;
+    }
+    t.finish()?;
+    Ok(state)
+}
+
+fn parse_input_assembly_state(mut t: Table) -> Result<InputAssemblyState> {
+    let mut state = InputAssemblyState::default();
+    if let Some(topology) = t.name("topology", TOPOLOGIES)? {
+        state.topology = topology;
+    }
+    if let Some(enable) = t.bool("primitive_restart")? {
+        state.primitive_restart_enable = enable;
+    }
+    t.finish()?;
+    Ok(state)
+}
+
+fn parse_write_mask(t: &mut Table) -> Result<Option<ColorComponentFlags>> {
+    match t.take("write_mask") {
+        Some(v) => {
+            let mask = v.into_str().ok().and_then(|s| {
+                s.to_lowercase()
+                    .chars()
+                    .try_fold(ColorComponentFlags::empty(), |mask, c| match c {
+                        'r' => Some(mask | ColorComponentFlags::R),
+                        'g' => Some(mask | ColorComponentFlags::G),
+                        'b' => Some(mask | ColorComponentFlags::B),
+                        'a' => Some(mask | ColorComponentFlags::A),
+                        _ => None,
+                    })
+            });
+            mask.map(Some)
+                .ok_or_else(|| t.invalid("write_mask", "a combination of `r`, `g`, `b` and `a`"))
+        }
+        None => Ok(None),
+    }
+}
+
+fn parse_color_blend_attachment(key: String, value: Value) -> Result<ColorBlendAttachmentState> {
+    if let Ok(name) = value.clone().into_str() {
+        let name = name.to_lowercase();
+        return BLEND_PRESETS
+            .iter()
+            .find(|p| p.0 == name)
+            .map(|p| p.1)
+            .ok_or_else(|| PipelineFileError::InvalidValue {
+                key,
+                expected: format!("{} or a table", expected_names(BLEND_PRESETS)),
+            });
+    }
+    let entries = value
+        .into_table()
+        .map_err(|_| PipelineFileError::InvalidValue {
+            key: key.clone(),
+            expected: "the name of a preset or a table".to_string(),
+        })?;
+    let mut t = Table::new(key, entries);
+    let preset = t.name("preset", BLEND_PRESETS)?;
+    if preset == Some(ColorBlendAttachmentState::Disabled) && t.entries.is_empty() {
+        return Ok(ColorBlendAttachmentState::Disabled);
+    }
+    // the fields override the preset, or blending that replaces the destination
+    let base = match preset {
+        Some(state @ ColorBlendAttachmentState::Enabled { .. }) => state,
+        _ => ColorBlendAttachmentState::Disabled.with_color_write_mask(ColorComponentFlags::ALL),
+    };
+    let (src_color, dst_color, color_op, src_alpha, dst_alpha, alpha_op, write_mask) = match base {
+        ColorBlendAttachmentState::Enabled {
+            src_color_blend_factor,
+            dst_color_blend_factor,
+            color_blend_op,
+            src_alpha_blend_factor,
+            dst_alpha_blend_factor,
+            alpha_blend_op,
+            color_write_mask,
+        } => (
+            src_color_blend_factor,
+            dst_color_blend_factor,
+            color_blend_op,
+            src_alpha_blend_factor,
+            dst_alpha_blend_factor,
+            alpha_blend_op,
+            color_write_mask,
+        ),
+        ColorBlendAttachmentState::Disabled => unreachable!(),
+    };
+    let state = ColorBlendAttachmentState::Enabled {
+        src_color_blend_factor: t.name("src_color", BLEND_FACTORS)?.unwrap_or(src_color),
+        dst_color_blend_factor: t.name("dst_color", BLEND_FACTORS)?.unwrap_or(dst_color),
+        color_blend_op: t.name("color_op", BLEND_OPS)?.unwrap_or(color_op),
+        src_alpha_blend_factor: t.name("src_alpha", BLEND_FACTORS)?.unwrap_or(src_alpha),
+        dst_alpha_blend_factor: t.name("dst_alpha", BLEND_FACTORS)?.unwrap_or(dst_alpha),
+        alpha_blend_op: t.name("alpha_op", BLEND_OPS)?.unwrap_or(alpha_op),
+        color_write_mask: parse_write_mask(&mut t)?.unwrap_or(write_mask),
+    };
+    t.finish()?;
+    Ok(state)
+}
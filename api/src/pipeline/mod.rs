@@ -3,20 +3,33 @@ use crate::{
     descriptor::{Descriptor, ResourceBinding},
     format::Format,
     image::{DepthStencilView, RenderTargetView},
+    report::PipelineKind,
     vertex::{
         IndexBufferView, IndexData, IndexFormat, Semantic, VertexBufferView, VertexData,
-        VertexInputRate, VertexLayout,
+        VertexInputRate, VertexLayout, VertexLayoutElement,
     },
+    util::{eq_address, hash_address},
     Arena, Backend, Api,
 };
 pub use autograph_api_macros::Arguments;
 use autograph_spirv::{TypeDesc};
 use bitflags::bitflags;
 use ordered_float::NotNan;
-use std::{fmt::Debug, marker::PhantomData, mem};
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem,
+};
 
+mod cached;
+pub mod dump;
+pub mod file;
+mod states;
 pub mod validate;
 
+pub use self::cached::{CachedArgumentBlock, TypedCachedArgumentBlock};
+
 bitflags! {
     #[derive(Default)]
     pub struct ShaderStageFlags: u32 {
@@ -34,7 +47,10 @@ bitflags! {
 pub enum PrimitiveTopology {
     PointList,
     LineList,
+    LineStrip,
     TriangleList,
+    TriangleStrip,
+    TriangleFan,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -48,6 +64,8 @@ pub struct GraphicsShaderStages<'a, 're, B: Backend> {
     //pub format: ShaderFormat,
     pub vertex: ShaderModule<'a, 're, B>,
     pub geometry: Option<ShaderModule<'a, 're, B>>,
+    /// Optional: without a fragment shader, only the depth and stencil attachments are written
+    /// (e.g. for shadow maps or depth pre-passes).
     pub fragment: Option<ShaderModule<'a, 're, B>>,
     pub tess_eval: Option<ShaderModule<'a, 're, B>>,
     pub tess_control: Option<ShaderModule<'a, 're, B>>,
@@ -66,6 +84,17 @@ impl<'a, 're, B: Backend> GraphicsShaderStages<'a, 're, B> {
             tess_eval: None,
         }
     }
+
+    /// Shader stages of a depth-only pipeline, without a fragment shader.
+    pub fn new_vertex_only(vertex: ShaderModule<'a, 're, B>) -> GraphicsShaderStages<'a, 're, B> {
+        GraphicsShaderStages {
+            vertex,
+            fragment: None,
+            geometry: None,
+            tess_control: None,
+            tess_eval: None,
+        }
+    }
 }
 
 bitflags! {
@@ -95,6 +124,7 @@ bitflags! {
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum PolygonMode {
+    Point,
     Line,
     Fill,
 }
@@ -115,6 +145,26 @@ pub enum DepthBias {
     },
 }
 
+/// Width of rasterized lines.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum LineWidth {
+    /// Line width set when the pipeline is bound.
+    Static(NotNan<f32>),
+    /// Line width set in the command buffer
+    /// with [set_line_width](crate::command::CommandBuffer::set_line_width).
+    Dynamic,
+}
+
+/// Size of rasterized points.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PointSize {
+    /// Point size set when the pipeline is bound.
+    Static(NotNan<f32>),
+    /// Point size written by the last vertex processing stage in `gl_PointSize`.
+    /// The size is undefined if the shader does not write `gl_PointSize`.
+    Program,
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct RasterisationState {
     pub depth_clamp_enable: bool,
@@ -123,7 +173,11 @@ pub struct RasterisationState {
     pub cull_mode: CullModeFlags,
     pub depth_bias: DepthBias,
     pub front_face: FrontFace,
-    pub line_width: NotNan<f32>,
+    pub line_width: LineWidth,
+    /// Enables antialiasing of lines. Usually requires blending to have any visible effect.
+    pub line_smooth_enable: bool,
+    /// Size of points, for the `PointList` topology and the `Point` polygon mode.
+    pub point_size: PointSize,
 }
 
 impl RasterisationState {
@@ -134,7 +188,9 @@ impl RasterisationState {
         cull_mode: CullModeFlags::NONE,
         depth_bias: DepthBias::Disabled,
         front_face: FrontFace::Clockwise,
-        line_width: unsafe { mem::transmute(1.0f32) },
+        line_width: LineWidth::Static(unsafe { mem::transmute(1.0f32) }),
+        line_smooth_enable: false,
+        point_size: PointSize::Static(unsafe { mem::transmute(1.0f32) }),
     };
 }
 
@@ -168,6 +224,83 @@ impl From<(u32, u32)> for Viewport {
     }
 }
 
+impl Viewport {
+    /// Returns a viewport covering the whole render target.
+    ///
+    /// The size is read from the image at the time of the call: viewports of swapchain-relative
+    /// render targets must be created again after the swapchain is resized, e.g. per frame.
+    pub fn full<'a, B: Backend>(view: impl Into<RenderTargetView<'a, B>>) -> Viewport {
+        Viewport::from(view.into().size())
+    }
+
+    /// Returns a viewport covering an area of the specified size, mapping depth to `depth_range`.
+    pub fn with_depth_range((w, h): (u32, u32), depth_range: DepthRange) -> Viewport {
+        let (min_depth, max_depth) = match depth_range {
+            DepthRange::ZeroToOne => (0.0, 1.0),
+            DepthRange::OneToZero => (1.0, 0.0),
+        };
+        Viewport {
+            min_depth: min_depth.into(),
+            max_depth: max_depth.into(),
+            ..Viewport::from((w, h))
+        }
+    }
+
+    /// Returns the depth range of the viewport (`OneToZero` if `min_depth > max_depth`).
+    pub fn depth_range(&self) -> DepthRange {
+        if self.min_depth > self.max_depth {
+            DepthRange::OneToZero
+        } else {
+            DepthRange::ZeroToOne
+        }
+    }
+}
+
+/// Mapping of normalized device depth to the values stored in depth buffers.
+///
+/// Not to be confused with [clip::DepthRange](crate::clip::DepthRange), which describes the
+/// depth range of normalized device coordinates of a backend.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DepthRange {
+    /// The near plane maps to depth 0 and the far plane to depth 1.
+    ZeroToOne,
+    /// Reverse-Z: the near plane maps to depth 1 and the far plane to depth 0.
+    ///
+    /// With a floating-point depth buffer, this distributes depth precision much more evenly
+    /// over the view distance. Depth tests must use reversed comparisons
+    /// (see [DepthRange::compare_op]) and depth buffers must be cleared to 0.
+    OneToZero,
+}
+
+impl DepthRange {
+    pub fn is_reversed(&self) -> bool {
+        *self == DepthRange::OneToZero
+    }
+
+    /// Returns the comparison that has the same meaning in this depth range as `op` in the
+    /// `ZeroToOne` range (e.g. `Greater` instead of `Less` for reverse-Z).
+    pub fn compare_op(&self, op: CompareOp) -> CompareOp {
+        match *self {
+            DepthRange::ZeroToOne => op,
+            DepthRange::OneToZero => op.reversed(),
+        }
+    }
+
+    /// Returns the depth of the far plane. Depth buffers should be cleared to this value.
+    pub fn far_depth(&self) -> f32 {
+        match *self {
+            DepthRange::ZeroToOne => 1.0,
+            DepthRange::OneToZero => 0.0,
+        }
+    }
+}
+
+impl Default for DepthRange {
+    fn default() -> Self {
+        DepthRange::ZeroToOne
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub struct ScissorRect {
@@ -183,6 +316,21 @@ pub enum Scissor {
     Disabled,
 }
 
+impl Scissor {
+    /// Returns a scissor rectangle covering the whole render target.
+    ///
+    /// As with [Viewport::full], the size is read from the image at the time of the call.
+    pub fn full<'a, B: Backend>(view: impl Into<RenderTargetView<'a, B>>) -> Scissor {
+        let (width, height) = view.into().size();
+        Scissor::Enabled(ScissorRect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        })
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Viewports<'a> {
     Static(&'a [Viewport]),
@@ -245,6 +393,18 @@ impl<'a> ViewportState<'a> {
         viewports: Viewports::Dynamic,
         scissors: Scissors::Dynamic,
     };
+
+    /// Returns the states that must be provided by the argument blocks of the pipeline.
+    pub fn dynamic_state(&self) -> DynamicStateFlags {
+        let mut flags = DynamicStateFlags::empty();
+        if let Viewports::Dynamic = self.viewports {
+            flags |= DynamicStateFlags::VIEWPORT;
+        }
+        if let Scissors::Dynamic = self.scissors {
+            flags |= DynamicStateFlags::SCISSOR;
+        }
+        flags
+    }
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -272,21 +432,72 @@ pub enum SampleShading {
 pub struct MultisampleState {
     pub rasterization_samples: u32,
     pub sample_shading: SampleShading,
+    /// Bit _i_ enables the coverage of sample _i_. Bits above `rasterization_samples` must be
+    /// zero, unless the mask is [MultisampleState::ALL_SAMPLES].
+    pub sample_mask: u64,
     pub alpha_to_coverage_enable: bool,
     pub alpha_to_one_enable: bool,
 }
 
+impl MultisampleState {
+    /// Sample mask that enables all samples, regardless of the sample count.
+    pub const ALL_SAMPLES: u64 = !0;
+
+    /// Returns the bits of the sample mask that correspond to actual samples.
+    pub fn effective_sample_mask(&self) -> u64 {
+        if self.rasterization_samples >= 64 {
+            self.sample_mask
+        } else {
+            self.sample_mask & ((1u64 << self.rasterization_samples) - 1)
+        }
+    }
+}
+
 impl Default for MultisampleState {
     fn default() -> Self {
         MultisampleState {
             rasterization_samples: 1,
             sample_shading: SampleShading::Disabled,
+            sample_mask: MultisampleState::ALL_SAMPLES,
             alpha_to_coverage_enable: false,
             alpha_to_one_enable: false,
         }
     }
 }
 
+/// Number of views that the draws of a graphics pipeline are broadcast to (multiview).
+///
+/// With more than one view (e.g. [ViewCount::STEREO] for the left and right eyes of VR
+/// rendering), the render targets must be layered views with one layer per view (see
+/// [Image2d::render_target_layers_view](crate::image::Image2d::render_target_layers_view)), and
+/// each draw renders into all the layers. Shaders can read the index of the view with
+/// `gl_ViewIndex` (`GL_EXT_multiview`).
+///
+/// Backends without native support emulate multiview by issuing each draw once per view, into
+/// one layer at a time and with viewport _i_ for view _i_ if the arguments provide one viewport
+/// per view. The index of the view is not available to shaders in this case: check
+/// [DeviceLimits::native_multiview](crate::DeviceLimits::native_multiview).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ViewCount(pub u32);
+
+impl ViewCount {
+    /// A single view: regular rendering.
+    pub const MONO: ViewCount = ViewCount(1);
+    /// Two views: the left (0) and right (1) eyes.
+    pub const STEREO: ViewCount = ViewCount(2);
+
+    /// Returns whether draws are broadcast to more than one view.
+    pub fn is_multiview(self) -> bool {
+        self.0 > 1
+    }
+}
+
+impl Default for ViewCount {
+    fn default() -> Self {
+        ViewCount::MONO
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct AttachmentDescription {
     pub format: Format,
@@ -313,6 +524,19 @@ pub enum CompareOp {
     Always = 7,
 }
 
+impl CompareOp {
+    /// Returns the comparison with swapped operands (`Less` becomes `Greater`, etc.).
+    pub fn reversed(self) -> CompareOp {
+        match self {
+            CompareOp::Less => CompareOp::Greater,
+            CompareOp::LessOrEqual => CompareOp::GreaterOrEqual,
+            CompareOp::Greater => CompareOp::Less,
+            CompareOp::GreaterOrEqual => CompareOp::LessOrEqual,
+            op => op,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum StencilOp {
     Keep = 0,
@@ -361,16 +585,36 @@ pub struct DepthStencilState {
     pub depth_compare_op: CompareOp,
     pub depth_bounds_test: DepthBoundTest,
     pub stencil_test: StencilTest,
+    /// Whether the stencil reference value is provided by the argument blocks (see
+    /// [SignatureDescription::has_stencil_reference]) instead of the `reference` members of
+    /// `stencil_test`.
+    pub dynamic_stencil_reference: bool,
 }
 
 impl Default for DepthStencilState {
     fn default() -> Self {
+        DepthStencilState::DISABLED
+    }
+}
+
+impl DepthStencilState {
+    /// Depth test and write enabled, keeping the fragments nearest to the camera in the
+    /// specified depth range (`Less` with `ZeroToOne`, `Greater` with `OneToZero`).
+    pub fn depth_test(depth_range: DepthRange) -> DepthStencilState {
         DepthStencilState {
-            depth_test_enable: false,
-            depth_write_enable: false,
-            depth_compare_op: CompareOp::Less,
-            depth_bounds_test: DepthBoundTest::Disabled,
-            stencil_test: StencilTest::Disabled,
+            depth_test_enable: true,
+            depth_write_enable: true,
+            depth_compare_op: depth_range.compare_op(CompareOp::Less),
+            ..DepthStencilState::default()
+        }
+    }
+
+    /// Returns the states that must be provided by the argument blocks of the pipeline.
+    pub fn dynamic_state(&self) -> DynamicStateFlags {
+        if self.dynamic_stencil_reference {
+            DynamicStateFlags::STENCIL_REFERENCE
+        } else {
+            DynamicStateFlags::empty()
         }
     }
 }
@@ -463,6 +707,15 @@ impl ColorBlendAttachmentState {
         dst_alpha_blend_factor: BlendFactor::Zero,
         color_write_mask: ColorComponentFlags::ALL,
     };
+    pub const ADDITIVE: ColorBlendAttachmentState = ColorBlendAttachmentState::Enabled {
+        color_blend_op: BlendOp::Add,
+        src_color_blend_factor: BlendFactor::One,
+        dst_color_blend_factor: BlendFactor::One,
+        alpha_blend_op: BlendOp::Add,
+        src_alpha_blend_factor: BlendFactor::One,
+        dst_alpha_blend_factor: BlendFactor::One,
+        color_write_mask: ColorComponentFlags::ALL,
+    };
 }
 
 impl Default for ColorBlendAttachmentState {
@@ -477,6 +730,30 @@ pub enum ColorBlendAttachments<'a> {
     Separate(&'a [ColorBlendAttachmentState]),
 }
 
+impl<'a> From<ColorBlendAttachments<'a>> for ColorBlendAttachmentsOwned {
+    fn from(a: ColorBlendAttachments) -> Self {
+        match a {
+            ColorBlendAttachments::All(a) => ColorBlendAttachmentsOwned::All(*a),
+            ColorBlendAttachments::Separate(a) => ColorBlendAttachmentsOwned::Separate(a.to_vec()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ColorBlendAttachmentsOwned {
+    All(ColorBlendAttachmentState),
+    Separate(Vec<ColorBlendAttachmentState>),
+}
+
+impl ColorBlendAttachmentsOwned {
+    pub fn as_attachments(&self) -> ColorBlendAttachments {
+        match self {
+            ColorBlendAttachmentsOwned::All(a) => ColorBlendAttachments::All(a),
+            ColorBlendAttachmentsOwned::Separate(a) => ColorBlendAttachments::Separate(a),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct ColorBlendState<'a> {
     pub logic_op: Option<LogicOp>,
@@ -485,6 +762,17 @@ pub struct ColorBlendState<'a> {
 }
 
 impl<'a> ColorBlendState<'a> {
+    /// Returns the blend state of the color attachment at the specified index.
+    ///
+    /// Returns `None` if the attachments are specified separately and there are not enough
+    /// entries.
+    pub fn attachment(&self, index: usize) -> Option<ColorBlendAttachmentState> {
+        match self.attachments {
+            ColorBlendAttachments::All(state) => Some(*state),
+            ColorBlendAttachments::Separate(states) => states.get(index).cloned(),
+        }
+    }
+
     pub const DISABLED: ColorBlendState<'static> = ColorBlendState {
         attachments: ColorBlendAttachments::All(&ColorBlendAttachmentState::Disabled),
         blend_constants: [unsafe { mem::transmute(0.0f32) }; 4],
@@ -508,9 +796,56 @@ pub struct GraphicsPipelineCreateInfo<'a, 'b, B: Backend> {
     pub depth_stencil_state: DepthStencilState,
     pub input_assembly_state: InputAssemblyState,
     pub color_blend_state: ColorBlendState<'b>,
+    /// Number of views of the render targets. See [ViewCount].
+    pub view_count: ViewCount,
     //pub dynamic_state: DynamicStateFlags,
 }
 
+impl<'a, 'b, B: Backend> GraphicsPipelineCreateInfo<'a, 'b, B> {
+    /// Returns the states that must be provided by the argument blocks of the pipeline
+    /// (see [ViewportState::dynamic_state] and [DepthStencilState::dynamic_state]).
+    pub fn dynamic_state(&self) -> DynamicStateFlags {
+        self.viewport_state.dynamic_state() | self.depth_stencil_state.dynamic_state()
+    }
+
+    /// Returns whether the fragments of the pipeline replace the contents of the color render
+    /// targets: blending and logic operations are disabled, all fragments pass the depth,
+    /// stencil and depth bounds tests, and all samples are covered.
+    ///
+    /// Draws of such pipelines that cover the whole render targets can replace preceding
+    /// clears (see [elision](crate::elision)). The fragment shader is assumed not to discard
+    /// fragments.
+    pub fn overwrites_render_targets(&self) -> bool {
+        let blend = &self.color_blend_state;
+        let no_blending = match blend.attachments {
+            ColorBlendAttachments::All(state) => *state == ColorBlendAttachmentState::Disabled,
+            ColorBlendAttachments::Separate(states) => states
+                .iter()
+                .all(|state| *state == ColorBlendAttachmentState::Disabled),
+        };
+        let depth_stencil = &self.depth_stencil_state;
+        let all_fragments_pass = (!depth_stencil.depth_test_enable
+            || depth_stencil.depth_compare_op == CompareOp::Always)
+            && depth_stencil.stencil_test == StencilTest::Disabled
+            && depth_stencil.depth_bounds_test == DepthBoundTest::Disabled;
+        let multisample = &self.multisample_state;
+        let samples_mask = if multisample.rasterization_samples >= 64 {
+            !0
+        } else {
+            (1u64 << multisample.rasterization_samples.max(1)) - 1
+        };
+        let all_samples = (multisample.sample_mask & samples_mask) == samples_mask
+            && !multisample.alpha_to_coverage_enable;
+        let rasterization = &self.rasterization_state;
+        no_blending
+            && blend.logic_op.is_none()
+            && all_fragments_pass
+            && all_samples
+            && !rasterization.rasterizer_discard_enable
+            && rasterization.polygon_mode == PolygonMode::Fill
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 
 /// Shader module.
@@ -531,6 +866,12 @@ impl<'a, 're, B: Backend> ShaderModule<'a, 're, B> {
     pub fn reflection(&self) -> &'re ShaderStageReflection<'re> {
         self.reflection
     }
+
+    /// Returns a listing of the descriptors, vertex inputs and fragment outputs of the shader,
+    /// as extracted from its SPIR-V bytecode. See [dump].
+    pub fn dump_interface(&self) -> String {
+        dump::dump_shader_interface(self.reflection)
+    }
 }
 
 /*
@@ -560,7 +901,7 @@ pub struct VertexInputBinding<'a> {
 /// Describes the contents (all arguments) of an argument block.
 ///
 /// This can be seen as the 'layout' or 'format' of an argument block.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct SignatureDescription<'a> {
     /// Signatures of inherited argument blocks.
     ///
@@ -604,6 +945,12 @@ pub struct SignatureDescription<'a> {
     /// This follows the same rule as `num_viewports`.
     pub num_scissors: usize,
 
+    /// Indicates that the block contains the stencil reference value of pipelines with a dynamic
+    /// stencil reference (see [DepthStencilState::dynamic_stencil_reference]).
+    ///
+    /// At most one signature in a signature tree can contain a stencil reference.
+    pub has_stencil_reference: bool,
+
     /// Indicates that this block and its inherited blocks fully define the outputs of a fragment shader.
     ///
     /// An inheriting block must not define additional fragment outputs in the `fragment_outputs`
@@ -630,6 +977,7 @@ impl<'a> SignatureDescription<'a> {
         index_format: None,
         num_viewports: 0,
         num_scissors: 0,
+        has_stencil_reference: false,
         is_root_fragment_output_signature: false,
         is_root_vertex_input_signature: false,
     };
@@ -657,6 +1005,127 @@ impl<'a> SignatureDescription<'a> {
                 .map(|&s| s.count_scissors())
                 .sum::<usize>()
     }
+
+    /// Count the number of stencil reference values.
+    pub fn count_stencil_references(&self) -> usize {
+        self.has_stencil_reference as usize
+            + self
+                .inherited
+                .iter()
+                .map(|&s| s.count_stencil_references())
+                .sum::<usize>()
+    }
+
+    /// Returns the dynamic states (viewports, scissors and stencil reference) provided by this
+    /// signature and its inherited signatures.
+    pub fn dynamic_state(&self) -> DynamicStateFlags {
+        let mut flags = DynamicStateFlags::empty();
+        if self.count_viewports() > 0 {
+            flags |= DynamicStateFlags::VIEWPORT;
+        }
+        if self.count_scissors() > 0 {
+            flags |= DynamicStateFlags::SCISSOR;
+        }
+        if self.count_stencil_references() > 0 {
+            flags |= DynamicStateFlags::STENCIL_REFERENCE;
+        }
+        flags
+    }
+
+    /// Collects the color fragment outputs of this signature and its inherited signatures,
+    /// in render target order.
+    pub fn collect_fragment_outputs(&self, out: &mut Vec<FragmentOutputDescription>) {
+        for &s in self.inherited {
+            s.collect_fragment_outputs(out);
+        }
+        out.extend(self.fragment_outputs.iter().cloned());
+    }
+
+    /// Compares two descriptions, ignoring the names of the descriptors and vertex attributes
+    /// (which are only used for diagnostics).
+    pub fn eq_ignoring_names(&self, other: &SignatureDescription) -> bool {
+        self.inherited.len() == other.inherited.len()
+            && self
+                .inherited
+                .iter()
+                .zip(other.inherited.iter())
+                .all(|(a, b)| a.eq_ignoring_names(b))
+            && self.descriptors.len() == other.descriptors.len()
+            && self
+                .descriptors
+                .iter()
+                .zip(other.descriptors.iter())
+                .all(|(a, b)| unnamed_binding(a) == unnamed_binding(b))
+            && self.vertex_inputs.len() == other.vertex_inputs.len()
+            && self
+                .vertex_inputs
+                .iter()
+                .zip(other.vertex_inputs.iter())
+                .all(|(a, b)| {
+                    a.rate == b.rate
+                        && a.base_location == b.base_location
+                        && a.layout.stride == b.layout.stride
+                        && a.layout.elements.len() == b.layout.elements.len()
+                        && a.layout
+                            .elements
+                            .iter()
+                            .zip(b.layout.elements.iter())
+                            .all(|(a, b)| unnamed_element(a) == unnamed_element(b))
+                })
+            && self.fragment_outputs == other.fragment_outputs
+            && self.depth_stencil_fragment_output == other.depth_stencil_fragment_output
+            && self.index_format == other.index_format
+            && self.num_viewports == other.num_viewports
+            && self.num_scissors == other.num_scissors
+            && self.has_stencil_reference == other.has_stencil_reference
+            && self.is_root_fragment_output_signature == other.is_root_fragment_output_signature
+            && self.is_root_vertex_input_signature == other.is_root_vertex_input_signature
+    }
+
+    /// Hashes the description, ignoring the same names as
+    /// [eq_ignoring_names](SignatureDescription::eq_ignoring_names).
+    pub fn hash_ignoring_names<H: Hasher>(&self, state: &mut H) {
+        self.inherited.len().hash(state);
+        for inherited in self.inherited {
+            inherited.hash_ignoring_names(state);
+        }
+        self.descriptors.len().hash(state);
+        for d in self.descriptors {
+            unnamed_binding(d).hash(state);
+        }
+        self.vertex_inputs.len().hash(state);
+        for v in self.vertex_inputs {
+            v.rate.hash(state);
+            v.base_location.hash(state);
+            v.layout.stride.hash(state);
+            v.layout.elements.len().hash(state);
+            for e in v.layout.elements {
+                unnamed_element(e).hash(state);
+            }
+        }
+        self.fragment_outputs.hash(state);
+        self.depth_stencil_fragment_output.hash(state);
+        self.index_format.hash(state);
+        self.num_viewports.hash(state);
+        self.num_scissors.hash(state);
+        self.has_stencil_reference.hash(state);
+        self.is_root_fragment_output_signature.hash(state);
+        self.is_root_vertex_input_signature.hash(state);
+    }
+}
+
+fn unnamed_binding<'a>(binding: &ResourceBinding<'a>) -> ResourceBinding<'a> {
+    ResourceBinding {
+        name: None,
+        ..*binding
+    }
+}
+
+fn unnamed_element<'a>(element: &VertexLayoutElement<'a>) -> VertexLayoutElement<'a> {
+    VertexLayoutElement {
+        name: None,
+        ..*element
+    }
 }
 
 pub trait Signature<'a, B: Backend>: Copy + Clone + Debug {
@@ -704,6 +1173,35 @@ pub type TypedArgumentBlock<'a, B, T> = ArgumentBlock<'a, B, TypedSignature<'a,
 #[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
 pub struct BareArgumentBlock<'a, B: Backend>(pub &'a B::ArgumentBlock);
 
+// argument blocks are hashed by identity
+impl<'a, B: Backend> Hash for BareArgumentBlock<'a, B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.0, state)
+    }
+}
+
+impl<'a, B: Backend> PartialEq for BareArgumentBlock<'a, B> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.0, other.0)
+    }
+}
+
+impl<'a, B: Backend> Eq for BareArgumentBlock<'a, B> {}
+
+impl<'a, B: Backend, S: Signature<'a, B>> Hash for ArgumentBlock<'a, B, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.arguments, state)
+    }
+}
+
+impl<'a, B: Backend, S: Signature<'a, B>> PartialEq for ArgumentBlock<'a, B, S> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.arguments, other.arguments)
+    }
+}
+
+impl<'a, B: Backend, S: Signature<'a, B>> Eq for ArgumentBlock<'a, B, S> {}
+
 impl<'a, B: Backend, S: Signature<'a, B>> From<ArgumentBlock<'a, B, S>>
     for BareArgumentBlock<'a, B>
 {
@@ -712,12 +1210,37 @@ impl<'a, B: Backend, S: Signature<'a, B>> From<ArgumentBlock<'a, B, S>>
     }
 }
 
+#[cfg(feature = "introspection")]
+impl<'a, B: Backend> BareArgumentBlock<'a, B> {
+    /// Returns a description of the resources bound in this argument block and in its inherited
+    /// blocks, or `None` if the block was not created in an arena of `api`.
+    ///
+    /// Requires the `introspection` feature.
+    pub fn introspect(&self, api: &Api<B>) -> Option<crate::introspection::ArgumentBlockInfo> {
+        api.introspection.block::<B>(self.0)
+    }
+}
+
+#[cfg(feature = "introspection")]
+impl<'a, B: Backend, S: Signature<'a, B>> ArgumentBlock<'a, B, S> {
+    /// See [BareArgumentBlock::introspect].
+    pub fn introspect(&self, api: &Api<B>) -> Option<crate::introspection::ArgumentBlockInfo> {
+        BareArgumentBlock(self.arguments).introspect(api)
+    }
+}
+
 /// Graphics pipeline.
 #[derive(derivative::Derivative)]
 #[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
 pub struct GraphicsPipeline<'a, B: Backend, S: Signature<'a, B>> {
     pub(crate) inner: &'a B::GraphicsPipeline,
     pub(crate) signature: S,
+    /// States provided by the argument blocks (see [GraphicsPipelineCreateInfo::dynamic_state]).
+    pub(crate) dynamic_state: DynamicStateFlags,
+    /// Static viewports, checked against the render targets on draw (empty if dynamic).
+    pub(crate) viewports: &'a [Viewport],
+    /// See [GraphicsPipelineCreateInfo::overwrites_render_targets].
+    pub(crate) overwrites_render_targets: bool,
 }
 
 /// Graphics pipeline without an associated signature.
@@ -728,6 +1251,110 @@ pub struct GraphicsPipelineTypeless<'a, B: Backend>(pub(crate) &'a B::GraphicsPi
 /// Type alias for argument blocks with a statically known signature.
 pub type TypedGraphicsPipeline<'a, B, T> = GraphicsPipeline<'a, B, TypedSignature<'a, B, T>>;
 
+impl<'a, B: Backend, S: Signature<'a, B>> GraphicsPipeline<'a, B, S> {
+    /// Reinterprets the pipeline as a pipeline taking arguments of type `P`.
+    ///
+    /// This succeeds only if the signature of the pipeline has the same description as the
+    /// signature of `P`, ignoring the names of descriptors and vertex attributes, and returns
+    /// `None` otherwise. It allows pipelines created from different
+    /// shaders (e.g. quality permutations) with equivalent, but distinct, argument types to be
+    /// stored in the same collection and swapped per draw.
+    pub fn cast<P: Arguments<'a, B>>(
+        self,
+        api: &'a Api<B>,
+    ) -> Option<TypedGraphicsPipeline<'a, B, P>> {
+        if !self.signature.description().eq_ignoring_names(P::SIGNATURE) {
+            return None;
+        }
+        Some(GraphicsPipeline {
+            inner: self.inner,
+            signature: api.get_cached_signature::<P>(),
+            dynamic_state: self.dynamic_state,
+            viewports: self.viewports,
+            overwrites_render_targets: self.overwrites_render_targets,
+        })
+    }
+
+    /// Returns the states that the pipeline expects from its argument blocks.
+    pub fn dynamic_state(&self) -> DynamicStateFlags {
+        self.dynamic_state
+    }
+
+    /// Returns whether the fragments of the pipeline replace the contents of the color render
+    /// targets (see [GraphicsPipelineCreateInfo::overwrites_render_targets]).
+    pub fn overwrites_render_targets(&self) -> bool {
+        self.overwrites_render_targets
+    }
+
+    /// Returns a listing of the signature tree of the pipeline, with the descriptor sets and
+    /// vertex attribute locations expected from the shaders. See [dump].
+    pub fn dump_signature(&self) -> String {
+        dump::dump_signature(self.signature.description())
+    }
+
+    /// Same as [cast](GraphicsPipeline::cast), but panics if the signatures are not compatible.
+    pub fn with_signature_of<P: Arguments<'a, B>>(
+        self,
+        api: &'a Api<B>,
+    ) -> TypedGraphicsPipeline<'a, B, P> {
+        self.cast(api).expect("incompatible pipeline signatures")
+    }
+}
+
+/// Compute pipeline.
+#[derive(derivative::Derivative)]
+#[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
+pub struct ComputePipeline<'a, B: Backend, S: Signature<'a, B>> {
+    pub(crate) inner: &'a B::ComputePipeline,
+    pub(crate) signature: S,
+}
+
+/// Type alias for compute pipelines with a statically known signature.
+pub type TypedComputePipeline<'a, B, T> = ComputePipeline<'a, B, TypedSignature<'a, B, T>>;
+
+impl<'a, B: Backend, S: Signature<'a, B>> ComputePipeline<'a, B, S> {
+    /// Returns a listing of the signature tree of the pipeline. See [dump].
+    pub fn dump_signature(&self) -> String {
+        dump::dump_signature(self.signature.description())
+    }
+}
+
+/// Reference to a graphics or compute pipeline, regardless of its signature.
+/// See [Api::warm_up](crate::Api::warm_up).
+#[derive(derivative::Derivative)]
+#[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
+pub enum PipelineRef<'a, B: Backend> {
+    Graphics(&'a B::GraphicsPipeline),
+    Compute(&'a B::ComputePipeline),
+}
+
+impl<'a, B: Backend> PipelineRef<'a, B> {
+    pub fn kind(&self) -> PipelineKind {
+        match self {
+            PipelineRef::Graphics(_) => PipelineKind::Graphics,
+            PipelineRef::Compute(_) => PipelineKind::Compute,
+        }
+    }
+}
+
+impl<'a, B: Backend, S: Signature<'a, B>> From<GraphicsPipeline<'a, B, S>> for PipelineRef<'a, B> {
+    fn from(pipeline: GraphicsPipeline<'a, B, S>) -> Self {
+        PipelineRef::Graphics(pipeline.inner)
+    }
+}
+
+impl<'a, B: Backend> From<GraphicsPipelineTypeless<'a, B>> for PipelineRef<'a, B> {
+    fn from(pipeline: GraphicsPipelineTypeless<'a, B>) -> Self {
+        PipelineRef::Graphics(pipeline.0)
+    }
+}
+
+impl<'a, B: Backend, S: Signature<'a, B>> From<ComputePipeline<'a, B, S>> for PipelineRef<'a, B> {
+    fn from(pipeline: ComputePipeline<'a, B, S>) -> Self {
+        PipelineRef::Compute(pipeline.inner)
+    }
+}
+
 /// Trait for types that can be converted into an argument block.
 pub trait IntoArgumentBlock<'a, B: Backend, S: Signature<'a, B>> {
     fn into_block(self, signature: S, arena: &'a Arena<B>) -> ArgumentBlock<'a, B, S>;
@@ -807,6 +1434,106 @@ pub trait IntoArgumentBlock<'a, B: Backend, S: Signature<'a, B>> {
 /// }
 /// ```
 ///
+/// The blend state of a render target can be overridden with the `blend` option, which accepts
+/// `alpha`, `additive`, `disabled`, or the path to a `ColorBlendAttachmentState` constant.
+/// This takes precedence over the blend state specified in the pipeline:
+///
+/// ```
+/// #[derive(Arguments)]
+/// #[argument(backend="B")]
+/// pub struct GBuffers<'a> {
+///    #[argument(render_target)]
+///    pub albedo: Image<'a>,
+///    #[argument(render_target, blend = "additive")]
+///    pub lighting: Image<'a>,
+/// }
+/// ```
+///
+/// The `load` and `store` options of render targets specify what happens to their contents the
+/// first time they are used in a frame (`load`, `clear`, `dont_care`, or the path to a `LoadOp`
+/// constant) and at the end of the frame (`store` or `dont_care`). `clear` clears color targets
+/// to zero and depth targets to 1.0:
+///
+/// ```
+/// #[derive(Arguments)]
+/// #[argument(backend="B")]
+/// pub struct Targets<'a> {
+///    #[argument(render_target, load = "clear")]
+///    pub color: Image<'a>,
+///    #[argument(depth_stencil_render_target, load = "clear", store = "dont_care")]
+///    pub depth: Image<'a>,
+/// }
+/// ```
+///
+/// A field can also hold a fixed-size array of render targets, which are bound to consecutive
+/// locations and share the same options. The elements must be `Copy`, and the length must be an
+/// integer literal:
+///
+/// ```
+/// #[derive(Arguments)]
+/// #[argument(backend="B")]
+/// pub struct GBufferTargets<'a> {
+///    #[argument(render_target, load = "clear")]
+///    pub gbuffers: [RenderTarget2dView<'a>; 4],
+///    #[argument(render_target)]
+///    pub lighting: RenderTarget2dView<'a>,
+/// }
+/// ```
+///
+/// Structs can have type parameters in addition to the backend, e.g. for materials generic over
+/// the type of their parameters. Each instantiation has its own signature. Type parameters bounded
+/// by `Arguments` (typically, inherited arguments) are identified by their `UniqueType`;
+/// other type parameters must be `'static`:
+///
+/// ```ignore
+/// #[derive(Arguments)]
+/// pub struct Material<'a, B: Backend, T: StructuredBufferData, A: Arguments<'a, B>> {
+///    #[argument(inherit)]
+///    pub common: TypedArgumentBlock<'a, B, A>,
+///    #[argument(descriptor)]
+///    pub params: TypedConstantBufferView<'a, B, T>,
+/// }
+/// ```
+///
+/// Optional textures are declared as `Option<...>` descriptors with a `fallback` option
+/// (`white`, `black` or `normal-up`). When the field is `None`, the corresponding built-in 1x1
+/// texture of the renderer is bound instead (see [Api::fallback_texture](crate::Api::fallback_texture)):
+///
+/// ```ignore
+/// #[derive(Arguments)]
+/// pub struct MaterialTextures<'a, B: Backend> {
+///    #[argument(descriptor, fallback = "white")]
+///    pub albedo: Option<TextureSampler2dView<'a, B>>,
+///    #[argument(descriptor, fallback = "normal-up")]
+///    pub normal_map: Option<TextureSampler2dView<'a, B>>,
+/// }
+/// ```
+///
+/// Large read-only arrays can be bound as texel buffers (`samplerBuffer` in GLSL), with
+/// a buffer view created by [Arena::create_buffer_view](crate::Arena::create_buffer_view):
+///
+/// ```ignore
+/// #[derive(Arguments)]
+/// pub struct Skinning<'a, B: Backend> {
+///    #[argument(texel_buffer)]
+///    pub bone_matrices: BufferView<'a, B>,
+/// }
+/// ```
+///
+/// The stencil reference value of pipelines with a dynamic stencil reference (see
+/// [DepthStencilState::dynamic_stencil_reference]) is provided by a `u32` field with the
+/// `stencil_reference` attribute. This avoids creating one pipeline per reference value:
+///
+/// ```ignore
+/// #[derive(Arguments)]
+/// pub struct Portal<'a, B: Backend> {
+///    #[argument(inherit)]
+///    pub targets: TypedArgumentBlock<'a, B, Targets<'a, B>>,
+///    #[argument(stencil_reference)]
+///    pub portal_id: u32,
+/// }
+/// ```
+///
 /// TODO document more
 pub trait Arguments<'a, B: Backend>: Sized {
     const SIGNATURE: &'static SignatureDescription<'static>;
@@ -865,11 +1592,129 @@ pub struct VertexInputAttributeDescription<'tcx> {
     pub location: Option<u32>,
     pub ty: &'tcx TypeDesc<'tcx>,
     pub semantic: Option<Semantic<'tcx>>,
+    /// Name of the input variable in the shader, if debug information is available.
+    pub name: Option<&'tcx str>,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// Color output of a fragment shader.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct FragmentOutputAttributeDescription<'tcx> {
+    /// Index of the render target written by the output.
+    pub location: u32,
+    pub ty: &'tcx TypeDesc<'tcx>,
+    /// Name of the output variable in the shader, if debug information is available.
+    pub name: Option<&'tcx str>,
+}
+
+/// Input or output variable passed between two consecutive shader stages (e.g. the outputs of a
+/// vertex shader read by the fragment shader).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct InterfaceVariableDescription<'tcx> {
+    pub location: u32,
+    /// Type of the variable, including the per-vertex array level of the inputs of
+    /// tessellation and geometry shaders (and of the outputs of tessellation control shaders).
+    pub ty: &'tcx TypeDesc<'tcx>,
+    /// Name of the variable in the shader, if debug information is available.
+    pub name: Option<&'tcx str>,
+}
+
+/// What happens to the contents of a render target the first time it is used in a frame.
+///
+/// This lets backends avoid loading the previous contents of render targets that are
+/// overwritten anyway (which is expensive on tiled GPUs).
+#[derive(Copy, Clone, Debug)]
+pub enum LoadOp {
+    /// The previous contents are preserved.
+    Load,
+    /// The render target is cleared to the specified value. For depth-stencil render targets,
+    /// the first component is the depth value, and the stencil is cleared to zero.
+    Clear([f32; 4]),
+    /// The previous contents are undefined.
+    DontCare,
+}
+
+impl LoadOp {
+    /// Clears color render targets to transparent black.
+    pub const CLEAR_ZERO: LoadOp = LoadOp::Clear([0.0; 4]);
+    /// Clears depth render targets to the far plane of the default depth range.
+    pub const CLEAR_DEPTH_FAR: LoadOp = LoadOp::Clear([1.0, 0.0, 0.0, 0.0]);
+    /// Clears depth render targets to zero: the far plane for reverse-Z.
+    pub const CLEAR_DEPTH_ZERO: LoadOp = LoadOp::Clear([0.0; 4]);
+
+    /// Returns the operation that clears a depth render target to the far plane of `range`.
+    pub fn clear_depth_far(range: DepthRange) -> LoadOp {
+        LoadOp::Clear([range.far_depth(), 0.0, 0.0, 0.0])
+    }
+}
+
+impl Default for LoadOp {
+    fn default() -> Self {
+        LoadOp::Load
+    }
+}
+
+// clear values are compared bitwise so that descriptions can be compared and hashed
+impl PartialEq for LoadOp {
+    fn eq(&self, other: &LoadOp) -> bool {
+        match (self, other) {
+            (LoadOp::Load, LoadOp::Load) => true,
+            (LoadOp::DontCare, LoadOp::DontCare) => true,
+            (LoadOp::Clear(a), LoadOp::Clear(b)) => a
+                .iter()
+                .zip(b.iter())
+                .all(|(a, b)| a.to_bits() == b.to_bits()),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for LoadOp {}
+
+impl Hash for LoadOp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        if let LoadOp::Clear(value) = self {
+            for v in value.iter() {
+                v.to_bits().hash(state);
+            }
+        }
+    }
+}
+
+/// What happens to the contents of a render target at the end of a frame.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum StoreOp {
+    /// The contents are kept after the frame.
+    Store,
+    /// The contents are not needed after the frame and may be discarded by the backend (e.g.
+    /// transient depth buffers).
+    DontCare,
+}
+
+impl Default for StoreOp {
+    fn default() -> Self {
+        StoreOp::Store
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
 pub struct FragmentOutputDescription {
-    // nothing yet, we just care about the count
+    /// Blend state for this output, overriding the one specified in the pipeline
+    /// (`ColorBlendState::attachments`).
+    pub blend: Option<ColorBlendAttachmentState>,
+    /// Operation performed on the render target the first time it is used in a frame.
+    pub load_op: LoadOp,
+    /// Operation performed on the render target at the end of the frame.
+    pub store_op: StoreOp,
+}
+
+impl FragmentOutputDescription {
+    /// Fragment output with no particular requirements.
+    pub const DEFAULT: FragmentOutputDescription = FragmentOutputDescription {
+        blend: None,
+        load_op: LoadOp::Load,
+        store_op: StoreOp::Store,
+    };
 }
 
 /// Shader reflection information for one stage.
@@ -879,6 +1724,14 @@ pub struct ShaderStageReflection<'a> {
     pub descriptors: &'a [ResourceBinding<'a>],
     pub vertex_input_attributes: &'a [VertexInputAttributeDescription<'a>],
     pub fragment_outputs: &'a [FragmentOutputDescription],
+    /// Locations and types of the color outputs of a fragment shader.
+    pub fragment_output_attributes: &'a [FragmentOutputAttributeDescription<'a>],
+    /// Inputs read from the previous stage. Empty for vertex shaders, whose inputs are in
+    /// `vertex_input_attributes`. Built-in variables are not included.
+    pub stage_inputs: &'a [InterfaceVariableDescription<'a>],
+    /// Outputs passed to the next stage. Empty for fragment shaders, whose outputs are in
+    /// `fragment_output_attributes`. Built-in variables are not included.
+    pub stage_outputs: &'a [InterfaceVariableDescription<'a>],
 }
 
 /// Shader bytecode and reflection information.
@@ -908,6 +1761,7 @@ pub struct DynamicSignatureBuilder<'a, B: Backend> {
     index_format: Option<IndexFormat>,
     num_viewports: usize,
     num_scissors: usize,
+    has_stencil_reference: bool,
     is_root_fragment_output_signature: bool,
     is_root_vertex_input_signature: bool,
 }
@@ -924,6 +1778,7 @@ impl<'a, B: Backend> DynamicSignatureBuilder<'a, B> {
             index_format: None,
             num_viewports: 1,
             num_scissors: 0,
+            has_stencil_reference: false,
             is_root_fragment_output_signature: false,
             is_root_vertex_input_signature: false,
         }
@@ -950,6 +1805,10 @@ impl<'a, B: Backend> DynamicSignatureBuilder<'a, B> {
         self.num_scissors = count;
         self
     }
+    pub fn stencil_reference(&mut self, has: bool) -> &mut Self {
+        self.has_stencil_reference = has;
+        self
+    }
     pub fn index_format(&mut self, format: IndexFormat) -> &mut Self {
         self.is_root_vertex_input_signature = true;
         self.index_format = Some(format);
@@ -993,6 +1852,7 @@ impl<'a, B: Backend> DynamicSignatureBuilder<'a, B> {
             index_format: self.index_format,
             num_viewports: self.num_viewports,
             num_scissors: self.num_scissors,
+            has_stencil_reference: self.has_stencil_reference,
             is_root_fragment_output_signature: self.is_root_fragment_output_signature,
             is_root_vertex_input_signature: self.is_root_vertex_input_signature,
         });
@@ -1029,6 +1889,7 @@ pub struct DynamicArgumentBlockBuilder<'a, B: Backend> {
     depth_stencil_target: Option<DepthStencilView<'a, B>>,
     viewports: Vec<Viewport>,
     scissors: Vec<Scissor>,
+    stencil_reference: Option<u32>,
 }
 
 impl<'a, B: Backend> DynamicArgumentBlockBuilder<'a, B> {
@@ -1043,6 +1904,7 @@ impl<'a, B: Backend> DynamicArgumentBlockBuilder<'a, B> {
             depth_stencil_target: None,
             viewports: Vec::new(),
             scissors: Vec::new(),
+            stencil_reference: None,
         }
     }
 
@@ -1066,6 +1928,10 @@ impl<'a, B: Backend> DynamicArgumentBlockBuilder<'a, B> {
         self.scissors.push(s);
         self
     }
+    pub fn stencil_reference(&mut self, reference: u32) -> &mut Self {
+        self.stencil_reference = Some(reference);
+        self
+    }
     pub fn index_buffer<I: IndexData>(&mut self, ib: Buffer<'a, B, [I]>) -> &mut Self {
         self.index_buffer = Some(ib.into());
         self
@@ -1090,6 +1956,23 @@ impl<'a, 'b, B: Backend> IntoArgumentBlock<'a, B, DynamicSignature<'a, B>>
     ) -> ArgumentBlock<'a, B, DynamicSignature<'a, B>> {
         // comparing the signatures would also work, but this is faster
         assert_eq!(signature.raw as *const _, self.signature.raw as *const _);
+        // the backend reads as many viewports and scissors as declared in the signature
+        assert!(
+            self.viewports.len() == signature.description.num_viewports,
+            "the signature of the argument block declares {} viewports, but {} were specified",
+            signature.description.num_viewports,
+            self.viewports.len()
+        );
+        assert!(
+            self.scissors.len() == signature.description.num_scissors,
+            "the signature of the argument block declares {} scissors, but {} were specified",
+            signature.description.num_scissors,
+            self.scissors.len()
+        );
+        assert!(
+            self.stencil_reference.is_some() == signature.description.has_stencil_reference,
+            "the stencil reference of the argument block does not match its signature"
+        );
         arena.create_argument_block(
             signature,
             self.inherited.into_iter(),
@@ -1100,6 +1983,7 @@ impl<'a, 'b, B: Backend> IntoArgumentBlock<'a, B, DynamicSignature<'a, B>>
             self.depth_stencil_target,
             self.viewports.into_iter(),
             self.scissors.into_iter(),
+            self.stencil_reference,
         )
     }
 }
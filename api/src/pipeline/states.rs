@@ -0,0 +1,282 @@
+//! Presets and builder methods for the fixed-function states of graphics pipelines.
+//!
+//! Instead of spelling out every field of a state, start from a preset and adjust it:
+//! ```ignore
+//! let create_info = GraphicsPipelineCreateInfo {
+//!     rasterization_state: RasterisationState::CULL_BACK_CCW.with_polygon_mode(PolygonMode::Line),
+//!     depth_stencil_state: DepthStencilState::LESS_EQUAL_WRITE,
+//!     color_blend_state: ColorBlendState::PREMULTIPLIED,
+//!     ..
+//! };
+//! ```
+use super::*;
+
+impl RasterisationState {
+    /// Culls back faces, with counter-clockwise front faces (the OpenGL convention).
+    pub const CULL_BACK_CCW: RasterisationState = RasterisationState {
+        cull_mode: CullModeFlags::BACK,
+        front_face: FrontFace::CounterClockwise,
+        ..RasterisationState::DEFAULT
+    };
+    /// Culls back faces, with clockwise front faces.
+    pub const CULL_BACK_CW: RasterisationState = RasterisationState {
+        cull_mode: CullModeFlags::BACK,
+        front_face: FrontFace::Clockwise,
+        ..RasterisationState::DEFAULT
+    };
+    /// Culls front faces, with counter-clockwise front faces (e.g. for drawing the inside of
+    /// light volumes).
+    pub const CULL_FRONT_CCW: RasterisationState = RasterisationState {
+        cull_mode: CullModeFlags::FRONT,
+        front_face: FrontFace::CounterClockwise,
+        ..RasterisationState::DEFAULT
+    };
+    /// Draws the edges of polygons, without culling.
+    pub const WIREFRAME: RasterisationState = RasterisationState {
+        polygon_mode: PolygonMode::Line,
+        ..RasterisationState::DEFAULT
+    };
+
+    pub fn with_cull_mode(self, cull_mode: CullModeFlags) -> Self {
+        RasterisationState { cull_mode, ..self }
+    }
+
+    pub fn with_front_face(self, front_face: FrontFace) -> Self {
+        RasterisationState { front_face, ..self }
+    }
+
+    pub fn with_polygon_mode(self, polygon_mode: PolygonMode) -> Self {
+        RasterisationState {
+            polygon_mode,
+            ..self
+        }
+    }
+
+    pub fn with_depth_clamp(self, depth_clamp_enable: bool) -> Self {
+        RasterisationState {
+            depth_clamp_enable,
+            ..self
+        }
+    }
+
+    /// Enables depth bias (e.g. for shadow maps).
+    ///
+    /// Panics if one of the factors is NaN.
+    pub fn with_depth_bias(self, constant_factor: f32, clamp: f32, slope_factor: f32) -> Self {
+        RasterisationState {
+            depth_bias: DepthBias::Enabled {
+                constant_factor: constant_factor.into(),
+                clamp: clamp.into(),
+                slope_factor: slope_factor.into(),
+            },
+            ..self
+        }
+    }
+
+    pub fn with_line_width(self, line_width: LineWidth) -> Self {
+        RasterisationState { line_width, ..self }
+    }
+
+    pub fn with_point_size(self, point_size: PointSize) -> Self {
+        RasterisationState { point_size, ..self }
+    }
+}
+
+impl DepthStencilState {
+    /// Depth test and depth writes disabled. Same as `DepthStencilState::default()`.
+    pub const DISABLED: DepthStencilState = DepthStencilState {
+        depth_test_enable: false,
+        depth_write_enable: false,
+        depth_compare_op: CompareOp::Less,
+        depth_bounds_test: DepthBoundTest::Disabled,
+        stencil_test: StencilTest::Disabled,
+        dynamic_stencil_reference: false,
+    };
+    /// Keeps the nearest fragments with a `ZeroToOne` depth range, and writes their depth.
+    pub const LESS_WRITE: DepthStencilState = DepthStencilState {
+        depth_test_enable: true,
+        depth_write_enable: true,
+        depth_compare_op: CompareOp::Less,
+        ..DepthStencilState::DISABLED
+    };
+    /// Same as [LESS_WRITE](DepthStencilState::LESS_WRITE), but also passes fragments at the
+    /// same depth (e.g. for multi-pass rendering of the same geometry).
+    pub const LESS_EQUAL_WRITE: DepthStencilState = DepthStencilState {
+        depth_compare_op: CompareOp::LessOrEqual,
+        ..DepthStencilState::LESS_WRITE
+    };
+    /// Tests against the depth buffer without writing to it (e.g. for transparent geometry,
+    /// or after a depth pre-pass).
+    pub const LESS_EQUAL_READ_ONLY: DepthStencilState = DepthStencilState {
+        depth_write_enable: false,
+        ..DepthStencilState::LESS_EQUAL_WRITE
+    };
+    /// Keeps the nearest fragments with a reversed (`OneToZero`) depth range, and writes
+    /// their depth.
+    pub const GREATER_WRITE: DepthStencilState = DepthStencilState {
+        depth_compare_op: CompareOp::Greater,
+        ..DepthStencilState::LESS_WRITE
+    };
+    /// Reversed-depth counterpart of
+    /// [LESS_EQUAL_WRITE](DepthStencilState::LESS_EQUAL_WRITE).
+    pub const GREATER_EQUAL_WRITE: DepthStencilState = DepthStencilState {
+        depth_compare_op: CompareOp::GreaterOrEqual,
+        ..DepthStencilState::LESS_WRITE
+    };
+
+    pub fn with_depth_test(self, depth_test_enable: bool) -> Self {
+        DepthStencilState {
+            depth_test_enable,
+            ..self
+        }
+    }
+
+    pub fn with_depth_write(self, depth_write_enable: bool) -> Self {
+        DepthStencilState {
+            depth_write_enable,
+            ..self
+        }
+    }
+
+    pub fn with_depth_compare_op(self, depth_compare_op: CompareOp) -> Self {
+        DepthStencilState {
+            depth_compare_op,
+            ..self
+        }
+    }
+
+    /// Enables the depth bounds test.
+    ///
+    /// Panics if one of the bounds is NaN.
+    pub fn with_depth_bounds(self, min_depth_bounds: f32, max_depth_bounds: f32) -> Self {
+        DepthStencilState {
+            depth_bounds_test: DepthBoundTest::Enabled {
+                min_depth_bounds: min_depth_bounds.into(),
+                max_depth_bounds: max_depth_bounds.into(),
+            },
+            ..self
+        }
+    }
+
+    /// Enables the stencil test, with the same operations for front and back faces.
+    pub fn with_stencil_test(self, ops: StencilOpState) -> Self {
+        self.with_separate_stencil_test(ops, ops)
+    }
+
+    pub fn with_separate_stencil_test(self, front: StencilOpState, back: StencilOpState) -> Self {
+        DepthStencilState {
+            stencil_test: StencilTest::Enabled { front, back },
+            ..self
+        }
+    }
+
+    /// Takes the stencil reference value from the argument blocks of the pipeline instead of
+    /// the stencil operations (see [DepthStencilState::dynamic_stencil_reference]).
+    pub fn with_dynamic_stencil_reference(self) -> Self {
+        DepthStencilState {
+            dynamic_stencil_reference: true,
+            ..self
+        }
+    }
+}
+
+impl ColorBlendAttachmentState {
+    /// Blending of colors with premultiplied alpha (`src + (1 - src.a) * dst`).
+    pub const PREMULTIPLIED: ColorBlendAttachmentState = ColorBlendAttachmentState::Enabled {
+        color_blend_op: BlendOp::Add,
+        src_color_blend_factor: BlendFactor::One,
+        dst_color_blend_factor: BlendFactor::OneMinusSrcAlpha,
+        alpha_blend_op: BlendOp::Add,
+        src_alpha_blend_factor: BlendFactor::One,
+        dst_alpha_blend_factor: BlendFactor::OneMinusSrcAlpha,
+        color_write_mask: ColorComponentFlags::ALL,
+    };
+    /// Multiplies the destination by the source color (`src * dst`).
+    pub const MULTIPLY: ColorBlendAttachmentState = ColorBlendAttachmentState::Enabled {
+        color_blend_op: BlendOp::Add,
+        src_color_blend_factor: BlendFactor::DstColor,
+        dst_color_blend_factor: BlendFactor::Zero,
+        alpha_blend_op: BlendOp::Add,
+        src_alpha_blend_factor: BlendFactor::DstAlpha,
+        dst_alpha_blend_factor: BlendFactor::Zero,
+        color_write_mask: ColorComponentFlags::ALL,
+    };
+
+    /// Returns this state with the specified color write mask.
+    ///
+    /// Blending is enabled (with `src` replacing `dst`) if it was disabled.
+    pub fn with_color_write_mask(self, mask: ColorComponentFlags) -> Self {
+        match self {
+            ColorBlendAttachmentState::Disabled => ColorBlendAttachmentState::Enabled {
+                color_blend_op: BlendOp::Add,
+                src_color_blend_factor: BlendFactor::One,
+                dst_color_blend_factor: BlendFactor::Zero,
+                alpha_blend_op: BlendOp::Add,
+                src_alpha_blend_factor: BlendFactor::One,
+                dst_alpha_blend_factor: BlendFactor::Zero,
+                color_write_mask: mask,
+            },
+            ColorBlendAttachmentState::Enabled {
+                src_color_blend_factor,
+                dst_color_blend_factor,
+                color_blend_op,
+                src_alpha_blend_factor,
+                dst_alpha_blend_factor,
+                alpha_blend_op,
+                ..
+            } => ColorBlendAttachmentState::Enabled {
+                src_color_blend_factor,
+                dst_color_blend_factor,
+                color_blend_op,
+                src_alpha_blend_factor,
+                dst_alpha_blend_factor,
+                alpha_blend_op,
+                color_write_mask: mask,
+            },
+        }
+    }
+}
+
+impl<'a> ColorBlendState<'a> {
+    pub const PREMULTIPLIED: ColorBlendState<'static> = ColorBlendState {
+        attachments: ColorBlendAttachments::All(&ColorBlendAttachmentState::PREMULTIPLIED),
+        ..ColorBlendState::DISABLED
+    };
+
+    pub const ADDITIVE: ColorBlendState<'static> = ColorBlendState {
+        attachments: ColorBlendAttachments::All(&ColorBlendAttachmentState::ADDITIVE),
+        ..ColorBlendState::DISABLED
+    };
+
+    pub const MULTIPLY: ColorBlendState<'static> = ColorBlendState {
+        attachments: ColorBlendAttachments::All(&ColorBlendAttachmentState::MULTIPLY),
+        ..ColorBlendState::DISABLED
+    };
+
+    /// Returns this state with the specified attachment states, one for each color attachment.
+    pub fn with_separate_attachments<'b>(
+        self,
+        attachments: &'b [ColorBlendAttachmentState],
+    ) -> ColorBlendState<'b>
+    where
+        'a: 'b,
+    {
+        ColorBlendState {
+            attachments: ColorBlendAttachments::Separate(attachments),
+            ..self
+        }
+    }
+
+    /// Panics if one of the constants is NaN.
+    pub fn with_blend_constants(self, blend_constants: [f32; 4]) -> Self {
+        let [r, g, b, a] = blend_constants;
+        ColorBlendState {
+            blend_constants: [r.into(), g.into(), b.into(), a.into()],
+            ..self
+        }
+    }
+
+    pub fn with_logic_op(self, logic_op: Option<LogicOp>) -> Self {
+        ColorBlendState { logic_op, ..self }
+    }
+}
@@ -1,15 +1,990 @@
+//! Validation of graphics pipelines against the reflection information of shader stages.
 use crate::{
     descriptor::{ResourceBinding, ResourceBindingType},
+    format::{Format, NumericFormat},
     pipeline::{
-        FragmentOutputDescription, GraphicsPipelineCreateInfo, Scissors, SignatureDescription,
-        Viewports,
+        ColorBlendAttachmentState, ColorBlendAttachments, ColorBlendState, DynamicStateFlags,
+        GraphicsPipelineCreateInfo, InterfaceVariableDescription, LineWidth, MultisampleState,
+        PointSize, RasterisationState, SampleShading, ShaderStageFlags, ShaderStageReflection,
+        SignatureDescription, VertexInputAttributeDescription, ViewCount, Viewport,
     },
-    vertex::{IndexFormat, VertexLayout, VertexLayoutElement},
-    Backend,
+    typedesc::{PrimitiveType, TypeDesc},
+    vertex::VertexLayoutElement,
+    Backend, DeviceLimits,
 };
-use autograph_spirv::{headers::StorageClass, TypeDesc};
 use log::warn;
 use std::{error, fmt};
+
+/// Errors detected during the validation of a graphics pipeline.
+///
+/// Where available, errors carry the name of the variable in the shader (`shader_name`, from the
+/// debug information of the SPIR-V module) and the name of the field on the host side
+/// (`field_name`, from the `Arguments` or `VertexData` derives).
+#[derive(Clone, Debug)]
+pub enum ValidationError {
+    /// A shader uses a descriptor that is not provided by the signature.
+    DescriptorNotFound {
+        stage: ShaderStageFlags,
+        set: u32,
+        binding: u32,
+        shader_name: Option<String>,
+    },
+    /// The type of a descriptor in the signature is different from the one expected by a shader.
+    DescriptorTypeMismatch {
+        stage: ShaderStageFlags,
+        set: u32,
+        binding: u32,
+        shader_ty: ResourceBindingType,
+        host_ty: ResourceBindingType,
+        shader_name: Option<String>,
+        field_name: Option<String>,
+    },
+    /// The vertex shader has an input at a location that is not provided by any vertex buffer.
+    VertexInputNotFound {
+        location: u32,
+        shader_name: Option<String>,
+    },
+    /// The format of a vertex attribute is not compatible with the type of the vertex shader input.
+    VertexInputFormatMismatch {
+        location: u32,
+        format: Format,
+        shader_ty: String,
+        shader_name: Option<String>,
+        field_name: Option<String>,
+    },
+    /// A shader stage has an input at a location that is not written by the previous stage.
+    StageInputNotProduced {
+        stage: ShaderStageFlags,
+        previous_stage: ShaderStageFlags,
+        location: u32,
+        shader_name: Option<String>,
+    },
+    /// The type of an output of a shader stage is different from the type of the input of the
+    /// next stage at the same location.
+    StageInterfaceTypeMismatch {
+        location: u32,
+        output_stage: ShaderStageFlags,
+        input_stage: ShaderStageFlags,
+        output_ty: String,
+        input_ty: String,
+        shader_name: Option<String>,
+    },
+    /// The fragment shader writes to more color outputs than there are render targets in the
+    /// signature.
+    MissingRenderTarget {
+        num_shader_outputs: usize,
+        num_render_targets: usize,
+    },
+    /// The number of separate blend attachment states does not match the number of color
+    /// fragment outputs of the signature.
+    BlendAttachmentCountMismatch {
+        num_blend_attachments: usize,
+        num_fragment_outputs: usize,
+    },
+    /// Blending is enabled for a fragment output of integer type. Integer render targets cannot
+    /// be blended.
+    BlendingOnIntegerOutput {
+        location: u32,
+        shader_name: Option<String>,
+    },
+    /// The signature has more color render targets than the device supports.
+    TooManyRenderTargets {
+        num_render_targets: usize,
+        max_color_attachments: u32,
+    },
+    /// The view count of a multiview pipeline is zero, or greater than what the device
+    /// supports.
+    UnsupportedViewCount {
+        view_count: u32,
+        max_view_count: u32,
+    },
+    /// The rasterization sample count is not supported by the device.
+    UnsupportedSampleCount { samples: u32, max_samples: u32 },
+    /// The rasterization sample count is not a power of two between 1 and 64.
+    InvalidSampleCount { samples: u32 },
+    /// The sample mask enables samples beyond the rasterization sample count.
+    SampleMaskOutOfRange { sample_mask: u64, samples: u32 },
+    /// The sample mask disables all samples: nothing would be rendered.
+    EmptySampleMask { sample_mask: u64, samples: u32 },
+    /// The minimum fraction of samples to shade is not between 0 and 1.
+    InvalidMinSampleShading { min_sample_shading: f32 },
+    /// The static line width is outside the range supported by the device.
+    UnsupportedLineWidth { width: f32, range: [f32; 2] },
+    /// The static point size is outside the range supported by the device.
+    UnsupportedPointSize { size: f32, range: [f32; 2] },
+    /// The pipeline expects a dynamic state (viewports, scissors or stencil reference) from its
+    /// argument blocks, but the signature does not provide it.
+    MissingDynamicState { state: DynamicStateFlags },
+    /// The signature provides a dynamic state (viewports, scissors or stencil reference) that
+    /// the pipeline declares as static.
+    UnexpectedDynamicState { state: DynamicStateFlags },
+    /// More than one argument block of the signature tree provides a stencil reference.
+    MultipleStencilReferences { count: usize },
+    /// A static viewport of the pipeline extends beyond the render targets it is drawn to.
+    ViewportOutOfBounds {
+        index: usize,
+        viewport: Viewport,
+        attachment_size: (u32, u32),
+    },
+}
+
+/// Formats the shader and host names of an interface, e.g. "`color` (field `albedo`)".
+fn fmt_names(
+    f: &mut fmt::Formatter,
+    shader_name: &Option<String>,
+    field_name: &Option<String>,
+) -> fmt::Result {
+    match (shader_name, field_name) {
+        (Some(s), Some(h)) => write!(f, " `{}` (field `{}`)", s, h),
+        (Some(s), None) => write!(f, " `{}`", s),
+        (None, Some(h)) => write!(f, " (field `{}`)", h),
+        (None, None) => Ok(()),
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::DescriptorNotFound {
+                stage,
+                set,
+                binding,
+                shader_name,
+            } => {
+                write!(f, "descriptor")?;
+                fmt_names(f, shader_name, &None)?;
+                write!(
+                    f,
+                    " (set {}, binding {}) used in stage {:?} is not provided by the signature",
+                    set, binding, stage
+                )
+            }
+            ValidationError::DescriptorTypeMismatch {
+                stage,
+                set,
+                binding,
+                shader_ty,
+                host_ty,
+                shader_name,
+                field_name,
+            } => {
+                write!(f, "descriptor")?;
+                fmt_names(f, shader_name, field_name)?;
+                write!(
+                    f,
+                    " (set {}, binding {}) has type {:?} in the signature but {:?} in stage {:?}",
+                    set, binding, host_ty, shader_ty, stage
+                )
+            }
+            ValidationError::VertexInputNotFound {
+                location,
+                shader_name,
+            } => {
+                write!(f, "vertex input")?;
+                fmt_names(f, shader_name, &None)?;
+                write!(
+                    f,
+                    " at location {} is not provided by any vertex buffer",
+                    location
+                )
+            }
+            ValidationError::VertexInputFormatMismatch {
+                location,
+                format,
+                shader_ty,
+                shader_name,
+                field_name,
+            } => {
+                write!(f, "vertex input")?;
+                fmt_names(f, shader_name, field_name)?;
+                write!(
+                    f,
+                    " at location {}: attribute format {:?} is not compatible with shader input type {}",
+                    location, format, shader_ty
+                )
+            }
+            ValidationError::StageInputNotProduced {
+                stage,
+                previous_stage,
+                location,
+                shader_name,
+            } => {
+                write!(f, "input")?;
+                fmt_names(f, shader_name, &None)?;
+                write!(
+                    f,
+                    " at location {} of stage {:?} is not written by stage {:?}",
+                    location, stage, previous_stage
+                )
+            }
+            ValidationError::StageInterfaceTypeMismatch {
+                location,
+                output_stage,
+                input_stage,
+                output_ty,
+                input_ty,
+                shader_name,
+            } => {
+                write!(f, "input")?;
+                fmt_names(f, shader_name, &None)?;
+                write!(
+                    f,
+                    " at location {} has type {} in stage {:?} but the output of stage {:?} has type {}",
+                    location, input_ty, input_stage, output_stage, output_ty
+                )
+            }
+            ValidationError::MissingRenderTarget {
+                num_shader_outputs,
+                num_render_targets,
+            } => write!(
+                f,
+                "the fragment shader has {} color outputs but the signature has only {} render targets",
+                num_shader_outputs, num_render_targets
+            ),
+            ValidationError::BlendAttachmentCountMismatch {
+                num_blend_attachments,
+                num_fragment_outputs,
+            } => write!(
+                f,
+                "the number of color blend attachment states ({}) does not match the number of fragment outputs ({})",
+                num_blend_attachments, num_fragment_outputs
+            ),
+            ValidationError::BlendingOnIntegerOutput {
+                location,
+                shader_name,
+            } => {
+                write!(f, "fragment output")?;
+                fmt_names(f, shader_name, &None)?;
+                write!(
+                    f,
+                    " at location {} has an integer type, but blending is enabled for it",
+                    location
+                )
+            }
+            ValidationError::TooManyRenderTargets {
+                num_render_targets,
+                max_color_attachments,
+            } => write!(
+                f,
+                "the signature has {} render targets but the device supports at most {}",
+                num_render_targets, max_color_attachments
+            ),
+            ValidationError::UnsupportedViewCount {
+                view_count,
+                max_view_count,
+            } => write!(
+                f,
+                "unsupported view count {} (the device supports between 1 and {} views)",
+                view_count, max_view_count
+            ),
+            ValidationError::UnsupportedSampleCount {
+                samples,
+                max_samples,
+            } => write!(
+                f,
+                "unsupported rasterization sample count {} (the device supports at most {})",
+                samples, max_samples
+            ),
+            ValidationError::InvalidSampleCount { samples } => write!(
+                f,
+                "invalid rasterization sample count {} (must be a power of two between 1 and 64)",
+                samples
+            ),
+            ValidationError::SampleMaskOutOfRange {
+                sample_mask,
+                samples,
+            } => write!(
+                f,
+                "the sample mask {:#x} enables samples beyond the sample count ({})",
+                sample_mask, samples
+            ),
+            ValidationError::EmptySampleMask {
+                sample_mask,
+                samples,
+            } => write!(
+                f,
+                "the sample mask {:#x} disables all {} samples",
+                sample_mask, samples
+            ),
+            ValidationError::InvalidMinSampleShading { min_sample_shading } => write!(
+                f,
+                "invalid minimum sample shading {} (must be between 0 and 1)",
+                min_sample_shading
+            ),
+            ValidationError::UnsupportedLineWidth { width, range } => write!(
+                f,
+                "unsupported line width {} (the device supports widths between {} and {})",
+                width, range[0], range[1]
+            ),
+            ValidationError::UnsupportedPointSize { size, range } => write!(
+                f,
+                "unsupported point size {} (the device supports sizes between {} and {})",
+                size, range[0], range[1]
+            ),
+            ValidationError::MissingDynamicState { state } => write!(
+                f,
+                "the pipeline expects dynamic {:?} state but the signature does not provide any",
+                state
+            ),
+            ValidationError::UnexpectedDynamicState { state } => write!(
+                f,
+                "the signature provides {:?} state but the pipeline declares it as static",
+                state
+            ),
+            ValidationError::MultipleStencilReferences { count } => write!(
+                f,
+                "the signature tree provides {} stencil references, expected at most one",
+                count
+            ),
+            ValidationError::ViewportOutOfBounds {
+                index,
+                viewport,
+                attachment_size,
+            } => write!(
+                f,
+                "static viewport #{} ({}x{} at ({}, {})) extends beyond the render targets ({}x{})",
+                index,
+                viewport.width,
+                viewport.height,
+                viewport.x,
+                viewport.y,
+                attachment_size.0,
+                attachment_size.1
+            ),
+        }
+    }
+}
+
+impl error::Error for ValidationError {}
+
+/// Returns whether vertex data of the given format can be read by a vertex shader input of
+/// type `ty`.
+///
+/// Normalized, scaled and floating-point formats are read as `float` vectors, and integer formats
+/// as `int` or `uint` vectors depending on their signedness.
+/// The shader can read fewer components than what the format provides, but not more.
+pub fn is_vertex_format_compatible(format: Format, ty: &TypeDesc) -> bool {
+    is_vertex_format_class_compatible(format, ty)
+        && vertex_input_components(ty) <= format.get_format_info().num_components()
+}
+
+/// Number of components of a vertex shader input of type `ty`.
+fn vertex_input_components(ty: &TypeDesc) -> u32 {
+    match *ty {
+        TypeDesc::Vector { len, .. } => len as u32,
+        _ => 1,
+    }
+}
+
+/// Same as [is_vertex_format_compatible], ignoring the number of components.
+fn is_vertex_format_class_compatible(format: Format, ty: &TypeDesc) -> bool {
+    let prim_ty = match *ty {
+        TypeDesc::Primitive(p) => p,
+        TypeDesc::Vector { elem_ty, .. } => elem_ty,
+        _ => return false,
+    };
+
+    match format.get_format_info().format_type {
+        NumericFormat::UNORM
+        | NumericFormat::SNORM
+        | NumericFormat::USCALED
+        | NumericFormat::SSCALED
+        | NumericFormat::UFLOAT
+        | NumericFormat::SFLOAT => prim_ty == PrimitiveType::Float,
+        NumericFormat::UINT => prim_ty == PrimitiveType::UnsignedInt,
+        NumericFormat::SINT => prim_ty == PrimitiveType::Int,
+        _ => false,
+    }
+}
+
+/// Collects the vertex attributes provided by the vertex buffers of a signature (and its
+/// inherited signatures), indexed by location.
+///
+/// Locations are assigned sequentially, starting from the base location of each buffer if specified.
+fn collect_vertex_attributes<'a>(
+    signature: &SignatureDescription<'a>,
+    location: &mut u32,
+    out: &mut Vec<(u32, VertexLayoutElement<'a>)>,
+) {
+    for &inherited in signature.inherited {
+        collect_vertex_attributes(inherited, location, out);
+    }
+    for binding in signature.vertex_inputs.iter() {
+        if let Some(base_location) = binding.base_location {
+            *location = base_location;
+        }
+        for &e in binding.layout.elements.iter() {
+            out.push((*location, e));
+            *location += 1;
+        }
+    }
+}
+
+/// Strips the usual prefixes of vertex shader input names (`a_position`, `in_position`).
+fn vertex_input_base_name(name: &str) -> &str {
+    for prefix in &["a_", "in_"] {
+        if name.starts_with(prefix) {
+            return &name[prefix.len()..];
+        }
+    }
+    name
+}
+
+/// Returns whether a shader input and a vertex attribute have the same semantic or, failing
+/// that, the same name.
+fn vertex_input_matches(
+    input: &VertexInputAttributeDescription,
+    element: &VertexLayoutElement,
+) -> bool {
+    if let (Some(a), Some(b)) = (input.semantic, element.semantic) {
+        return a == b;
+    }
+    match (input.name, element.name) {
+        (Some(a), Some(b)) => vertex_input_base_name(a) == vertex_input_base_name(b),
+        _ => false,
+    }
+}
+
+/// Checks the shader inputs against vertex attributes at the specified locations.
+fn check_vertex_inputs(
+    attributes: &[(u32, VertexLayoutElement)],
+    shader_inputs: &[VertexInputAttributeDescription],
+    errors: &mut Vec<ValidationError>,
+) {
+    for input in shader_inputs.iter() {
+        let location = if let Some(location) = input.location {
+            location
+        } else {
+            // not matched by location: nothing to check for now
+            continue;
+        };
+        let shader_name = input.name.map(str::to_owned);
+
+        match attributes.iter().find(|(l, _)| *l == location) {
+            Some((_, attr)) => {
+                if !is_vertex_format_class_compatible(attr.format, input.ty) {
+                    errors.push(ValidationError::VertexInputFormatMismatch {
+                        location,
+                        format: attr.format,
+                        shader_ty: format!("{:?}", input.ty),
+                        shader_name,
+                        field_name: attr.name.map(str::to_owned),
+                    });
+                } else if !is_vertex_format_compatible(attr.format, input.ty) {
+                    // allowed by GL: the missing components are read as (0, 0, 0, 1)
+                    warn!(
+                        "vertex shader input at location {} reads {} components from attribute `{}` with format {:?}",
+                        location,
+                        vertex_input_components(input.ty),
+                        attr.name.unwrap_or("<unnamed>"),
+                        attr.format
+                    );
+                }
+            }
+            None => errors.push(ValidationError::VertexInputNotFound {
+                location,
+                shader_name,
+            }),
+        }
+    }
+}
+
+/// Returns the location of each vertex attribute provided by the vertex buffers of a signature,
+/// in the order of declaration of the attributes.
+///
+/// If every input of the shader can be matched to an attribute by semantic or by name (ignoring
+/// `a_` and `in_` prefixes), the attributes are bound at the locations of the corresponding
+/// inputs, and the attributes that match no input are not bound (`None`). This allows vertex
+/// structs to declare their fields in a different order than the shader. Otherwise, locations
+/// are assigned sequentially.
+pub fn vertex_attribute_locations(
+    signature: &SignatureDescription,
+    shader_inputs: &[VertexInputAttributeDescription],
+) -> Vec<Option<u32>> {
+    let mut attributes = Vec::new();
+    collect_vertex_attributes(signature, &mut 0, &mut attributes);
+    let sequential = attributes.iter().map(|&(l, _)| Some(l)).collect();
+
+    let mut remapped = vec![None; attributes.len()];
+    for input in shader_inputs.iter() {
+        let location = if let Some(location) = input.location {
+            location
+        } else {
+            continue;
+        };
+        let matched = (0..attributes.len())
+            .find(|&i| remapped[i].is_none() && vertex_input_matches(input, &attributes[i].1));
+        match matched {
+            Some(i) => remapped[i] = Some(location),
+            None => return sequential,
+        }
+    }
+    remapped
+}
+
+/// Checks that all inputs of the vertex shader are provided by vertex buffers of the signature
+/// with a compatible format, at the locations given by [vertex_attribute_locations].
+pub fn validate_vertex_inputs(
+    signature: &SignatureDescription,
+    shader_inputs: &[VertexInputAttributeDescription],
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut attributes = Vec::new();
+    collect_vertex_attributes(signature, &mut 0, &mut attributes);
+    let attributes: Vec<_> = attributes
+        .into_iter()
+        .zip(vertex_attribute_locations(signature, shader_inputs))
+        .filter_map(|((_, e), location)| location.map(|l| (l, e)))
+        .collect();
+    check_vertex_inputs(&attributes, shader_inputs, errors);
+}
+
+/// Returns the type of a variable passed between stages, without the per-vertex array level.
+///
+/// The inputs of tessellation and geometry shaders, and the outputs of tessellation control
+/// shaders, are arrays with one element per vertex of the patch or primitive. Per-patch
+/// variables are not arrayed, but are not distinguished in the reflection information: they
+/// are assumed to be declared as non-array variables.
+fn interface_element_type<'a>(
+    stage: ShaderStageFlags,
+    is_input: bool,
+    variable: &InterfaceVariableDescription<'a>,
+) -> &'a TypeDesc<'a> {
+    let arrayed = if is_input {
+        stage.intersects(
+            ShaderStageFlags::TESS_CONTROL
+                | ShaderStageFlags::TESS_EVAL
+                | ShaderStageFlags::GEOMETRY,
+        )
+    } else {
+        stage.contains(ShaderStageFlags::TESS_CONTROL)
+    };
+    match *variable.ty {
+        TypeDesc::Array { elem_ty, .. } if arrayed => elem_ty,
+        _ => variable.ty,
+    }
+}
+
+/// Checks that every input of a shader stage is written by the previous stage (`producer`), at
+/// the same location and with the same type.
+///
+/// Outputs of the producer that are not read by the consumer are allowed, but a warning is logged
+/// since they are likely a mistake (e.g. a typo in a location).
+pub fn validate_stage_interface(
+    producer: &ShaderStageReflection,
+    consumer: &ShaderStageReflection,
+    errors: &mut Vec<ValidationError>,
+) {
+    for input in consumer.stage_inputs.iter() {
+        let shader_name = input.name.map(str::to_owned);
+        let output = producer
+            .stage_outputs
+            .iter()
+            .find(|o| o.location == input.location);
+
+        match output {
+            Some(output) => {
+                let output_ty = interface_element_type(producer.stage, false, output);
+                let input_ty = interface_element_type(consumer.stage, true, input);
+                if output_ty != input_ty {
+                    errors.push(ValidationError::StageInterfaceTypeMismatch {
+                        location: input.location,
+                        output_stage: producer.stage,
+                        input_stage: consumer.stage,
+                        output_ty: format!("{:?}", output_ty),
+                        input_ty: format!("{:?}", input_ty),
+                        shader_name,
+                    });
+                }
+            }
+            None => errors.push(ValidationError::StageInputNotProduced {
+                stage: consumer.stage,
+                previous_stage: producer.stage,
+                location: input.location,
+                shader_name,
+            }),
+        }
+    }
+
+    for output in producer.stage_outputs.iter() {
+        if !consumer
+            .stage_inputs
+            .iter()
+            .any(|i| i.location == output.location)
+        {
+            warn!(
+                "output{} at location {} of stage {:?} is not read by stage {:?}",
+                output.name.map(|n| format!(" `{}`", n)).unwrap_or_default(),
+                output.location,
+                producer.stage,
+                consumer.stage
+            );
+        }
+    }
+}
+
+/// Collects the descriptors defined by a signature and its inherited signatures, with the index
+/// of their descriptor set.
+///
+/// Descriptors with an explicit set (`ResourceBinding::set`) are in this set. Otherwise, each
+/// argument block that contains descriptors defines a new descriptor set, in declaration order.
+fn collect_descriptors<'a>(
+    signature: &SignatureDescription<'a>,
+    next_set: &mut u32,
+    out: &mut Vec<(u32, &'a ResourceBinding<'a>)>,
+) {
+    for &inherited in signature.inherited {
+        collect_descriptors(inherited, next_set, out);
+    }
+    if !signature.descriptors.is_empty() {
+        for d in signature.descriptors.iter() {
+            out.push((d.set.unwrap_or(*next_set), d));
+        }
+        *next_set += 1;
+    }
+}
+
+/// Checks that all descriptors used by a shader stage are provided by the signature with the
+/// expected type.
+///
+/// Descriptors are matched by set and binding number (`ResourceBinding::index`).
+pub fn validate_descriptors(
+    signature: &SignatureDescription,
+    reflection: &ShaderStageReflection,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut descriptors = Vec::new();
+    collect_descriptors(signature, &mut 0, &mut descriptors);
+
+    for d in reflection.descriptors.iter() {
+        let set = d.set.unwrap_or(0);
+        let shader_name = d.name.map(str::to_owned);
+        let host = descriptors
+            .iter()
+            .find(|(s, h)| *s == set && h.index == d.index)
+            .map(|&(_, h)| h);
+
+        match host {
+            Some(host) => {
+                if host.ty != d.ty {
+                    errors.push(ValidationError::DescriptorTypeMismatch {
+                        stage: reflection.stage,
+                        set,
+                        binding: d.index,
+                        shader_ty: d.ty,
+                        host_ty: host.ty,
+                        shader_name,
+                        field_name: host.name.map(str::to_owned),
+                    });
+                }
+            }
+            None => errors.push(ValidationError::DescriptorNotFound {
+                stage: reflection.stage,
+                set,
+                binding: d.index,
+                shader_name,
+            }),
+        }
+    }
+}
+
+/// Checks that the signature has a render target for each color output of the fragment shader.
+pub fn validate_fragment_outputs(
+    signature: &SignatureDescription,
+    reflection: &ShaderStageReflection,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut outputs = Vec::new();
+    signature.collect_fragment_outputs(&mut outputs);
+    if reflection.fragment_outputs.len() > outputs.len() {
+        errors.push(ValidationError::MissingRenderTarget {
+            num_shader_outputs: reflection.fragment_outputs.len(),
+            num_render_targets: outputs.len(),
+        });
+    }
+}
+
+/// Checks that there is exactly one blend attachment state for each color fragment output of the
+/// signature, if they are specified separately.
+pub fn validate_color_blend_state(
+    signature: &SignatureDescription,
+    color_blend_state: &ColorBlendState,
+    errors: &mut Vec<ValidationError>,
+) {
+    if let ColorBlendAttachments::Separate(states) = color_blend_state.attachments {
+        let mut outputs = Vec::new();
+        signature.collect_fragment_outputs(&mut outputs);
+        if states.len() != outputs.len() {
+            errors.push(ValidationError::BlendAttachmentCountMismatch {
+                num_blend_attachments: states.len(),
+                num_fragment_outputs: outputs.len(),
+            });
+        }
+    }
+}
+
+/// Checks that blending is disabled for the color outputs of integer type of a fragment shader
+/// (e.g. object IDs written to `R32_UINT` render targets).
+///
+/// The blend state of an output is the one of its render target in the signature if specified,
+/// or the one of the pipeline otherwise.
+pub fn validate_integer_output_blending(
+    signature: &SignatureDescription,
+    reflection: &ShaderStageReflection,
+    color_blend_state: &ColorBlendState,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut outputs = Vec::new();
+    signature.collect_fragment_outputs(&mut outputs);
+    for a in reflection.fragment_output_attributes.iter() {
+        let prim_ty = match *a.ty {
+            TypeDesc::Primitive(p) => p,
+            TypeDesc::Vector { elem_ty, .. } => elem_ty,
+            _ => continue,
+        };
+        if prim_ty != PrimitiveType::Int && prim_ty != PrimitiveType::UnsignedInt {
+            continue;
+        }
+        let blend = outputs
+            .get(a.location as usize)
+            .and_then(|output| output.blend)
+            .or_else(|| color_blend_state.attachment(a.location as usize));
+        if let Some(ColorBlendAttachmentState::Enabled { .. }) = blend {
+            errors.push(ValidationError::BlendingOnIntegerOutput {
+                location: a.location,
+                shader_name: a.name.map(str::to_owned),
+            });
+        }
+    }
+}
+
+/// Checks the number of render targets, the sample count and the view count of a graphics
+/// pipeline against the limits of the device.
+pub fn validate_device_limits<B: Backend>(
+    signature: &SignatureDescription,
+    create_info: &GraphicsPipelineCreateInfo<B>,
+    limits: &DeviceLimits,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut outputs = Vec::new();
+    signature.collect_fragment_outputs(&mut outputs);
+    if outputs.len() > limits.max_color_attachments as usize {
+        errors.push(ValidationError::TooManyRenderTargets {
+            num_render_targets: outputs.len(),
+            max_color_attachments: limits.max_color_attachments,
+        });
+    }
+
+    let samples = create_info.multisample_state.rasterization_samples;
+    if samples > limits.max_samples {
+        errors.push(ValidationError::UnsupportedSampleCount {
+            samples,
+            max_samples: limits.max_samples,
+        });
+    }
+
+    validate_view_count(create_info.view_count, limits, errors);
+}
+
+/// Checks the view count of a multiview pipeline against the limits of the device.
+pub fn validate_view_count(
+    view_count: ViewCount,
+    limits: &DeviceLimits,
+    errors: &mut Vec<ValidationError>,
+) {
+    if view_count.0 == 0 || view_count.0 > limits.max_multiview_view_count {
+        errors.push(ValidationError::UnsupportedViewCount {
+            view_count: view_count.0,
+            max_view_count: limits.max_multiview_view_count,
+        });
+    }
+}
+
+/// Checks the static line width and point size of the rasterization state against the limits
+/// of the device.
+///
+/// Dynamic line widths and point sizes written by shaders (`gl_PointSize`) cannot be checked
+/// here: they are clamped to the supported range by the device.
+pub fn validate_rasterization_state(
+    state: &RasterisationState,
+    limits: &DeviceLimits,
+    errors: &mut Vec<ValidationError>,
+) {
+    let in_range = |v: f32, range: [f32; 2]| v >= range[0] && v <= range[1];
+
+    if let LineWidth::Static(width) = state.line_width {
+        let width = width.into_inner();
+        if !in_range(width, limits.line_width_range) {
+            errors.push(ValidationError::UnsupportedLineWidth {
+                width,
+                range: limits.line_width_range,
+            });
+        }
+    }
+
+    if let PointSize::Static(size) = state.point_size {
+        let size = size.into_inner();
+        if !in_range(size, limits.point_size_range) {
+            errors.push(ValidationError::UnsupportedPointSize {
+                size,
+                range: limits.point_size_range,
+            });
+        }
+    }
+}
+
+/// Checks the sample count, the sample mask and the sample shading rate of the multisample
+/// state.
+pub fn validate_multisample_state(state: &MultisampleState, errors: &mut Vec<ValidationError>) {
+    let samples = state.rasterization_samples;
+    if !samples.is_power_of_two() || samples > 64 {
+        errors.push(ValidationError::InvalidSampleCount { samples });
+        return;
+    }
+
+    if state.sample_mask != MultisampleState::ALL_SAMPLES
+        && state.effective_sample_mask() != state.sample_mask
+    {
+        errors.push(ValidationError::SampleMaskOutOfRange {
+            sample_mask: state.sample_mask,
+            samples,
+        });
+    }
+    if state.effective_sample_mask() == 0 {
+        errors.push(ValidationError::EmptySampleMask {
+            sample_mask: state.sample_mask,
+            samples,
+        });
+    }
+
+    if let SampleShading::Enabled { min_sample_shading } = state.sample_shading {
+        let min_sample_shading = min_sample_shading.into_inner();
+        if min_sample_shading < 0.0 || min_sample_shading > 1.0 {
+            errors.push(ValidationError::InvalidMinSampleShading { min_sample_shading });
+        }
+    }
+}
+
+/// Checks that the dynamic states expected by a pipeline (`expected`, see
+/// [GraphicsPipelineCreateInfo::dynamic_state](crate::pipeline::GraphicsPipelineCreateInfo::dynamic_state))
+/// are exactly the ones provided by the signature tree.
+///
+/// A mismatch would leave the backend either with no state to set, or with two conflicting ones.
+pub fn validate_dynamic_state(
+    signature: &SignatureDescription,
+    expected: DynamicStateFlags,
+    errors: &mut Vec<ValidationError>,
+) {
+    let provided = signature.dynamic_state();
+    for &state in &[
+        DynamicStateFlags::VIEWPORT,
+        DynamicStateFlags::SCISSOR,
+        DynamicStateFlags::STENCIL_REFERENCE,
+    ] {
+        if expected.contains(state) && !provided.contains(state) {
+            errors.push(ValidationError::MissingDynamicState { state });
+        }
+        if !expected.contains(state) && provided.contains(state) {
+            errors.push(ValidationError::UnexpectedDynamicState { state });
+        }
+    }
+    let count = signature.count_stencil_references();
+    if count > 1 {
+        errors.push(ValidationError::MultipleStencilReferences { count });
+    }
+}
+
+/// Checks that the static viewports of a pipeline do not extend beyond render targets of the
+/// specified size (the smallest size of the color and depth-stencil render targets).
+///
+/// The size of the render targets is only known when the pipeline is drawn: this is checked
+/// on each draw in debug builds. It catches pipelines created with the size of the window
+/// before a resize.
+pub fn validate_static_viewports(
+    viewports: &[Viewport],
+    attachment_size: (u32, u32),
+    errors: &mut Vec<ValidationError>,
+) {
+    let (width, height) = attachment_size;
+    for (index, viewport) in viewports.iter().enumerate() {
+        let right = viewport.x.into_inner() + viewport.width.into_inner();
+        let bottom = viewport.y.into_inner() + viewport.height.into_inner();
+        if right > width as f32 || bottom > height as f32 {
+            errors.push(ValidationError::ViewportOutOfBounds {
+                index,
+                viewport: *viewport,
+                attachment_size,
+            });
+        }
+    }
+}
+
+/// Validates a graphics pipeline against the root signature of its arguments and the limits of
+/// the device.
+pub fn validate_graphics_pipeline<B: Backend>(
+    signature: &SignatureDescription,
+    create_info: &GraphicsPipelineCreateInfo<B>,
+    limits: &DeviceLimits,
+) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+    let stages = &create_info.shader_stages;
+
+    validate_vertex_inputs(
+        signature,
+        stages.vertex.reflection().vertex_input_attributes,
+        &mut errors,
+    );
+
+    let all_stages = Some(stages.vertex)
+        .into_iter()
+        .chain(stages.tess_control)
+        .chain(stages.tess_eval)
+        .chain(stages.geometry)
+        .chain(stages.fragment)
+        .collect::<Vec<_>>();
+    for stage in all_stages.iter() {
+        validate_descriptors(signature, stage.reflection(), &mut errors);
+    }
+    for pair in all_stages.windows(2) {
+        validate_stage_interface(pair[0].reflection(), pair[1].reflection(), &mut errors);
+    }
+
+    if let Some(fragment) = stages.fragment {
+        validate_fragment_outputs(signature, fragment.reflection(), &mut errors);
+        validate_integer_output_blending(
+            signature,
+            fragment.reflection(),
+            &create_info.color_blend_state,
+            &mut errors,
+        );
+    }
+    validate_color_blend_state(signature, &create_info.color_blend_state, &mut errors);
+    validate_device_limits(signature, create_info, limits, &mut errors);
+    validate_rasterization_state(&create_info.rasterization_state, limits, &mut errors);
+    validate_multisample_state(&create_info.multisample_state, &mut errors);
+    validate_dynamic_state(signature, create_info.dynamic_state(), &mut errors);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validates a compute shader against a pipeline signature.
+pub fn validate_compute_pipeline(
+    signature: &SignatureDescription,
+    shader: &ShaderStageReflection,
+) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+    validate_descriptors(signature, shader, &mut errors);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 /*
 #[derive(Copy, Clone, Debug)]
 pub enum Interface {
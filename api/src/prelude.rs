@@ -7,9 +7,10 @@ pub use crate::{
     pipeline::{
         Arguments, ColorBlendAttachmentState, ColorBlendAttachments, ColorBlendState,
         DepthStencilState, GraphicsPipelineCreateInfo, GraphicsShaderStages,
-        InputAssemblyState, MultisampleState, PrimitiveTopology, RasterisationState,
-        ReflectedShader, Viewport, ViewportState, Viewports,
+        InputAssemblyState, LineWidth, MultisampleState, PointSize, PrimitiveTopology,
+        RasterisationState, ReflectedShader, ViewCount, Viewport, ViewportState, Viewports,
     },
+    sortkey::SortKey,
     vertex::VertexData,
     AliasScope,
 };
@@ -0,0 +1,38 @@
+//! Queries and predicates for conditional rendering.
+use crate::{buffer::BufferTypeless, Backend};
+
+/// An occlusion query: records whether any sample passed the depth and stencil tests
+/// between the corresponding begin and end commands.
+///
+/// See [CommandBuffer::begin_occlusion_query](crate::command::CommandBuffer::begin_occlusion_query).
+#[derive(derivative::Derivative)]
+#[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
+pub struct OcclusionQuery<'a, B: Backend>(pub &'a B::Query);
+
+/// The source of the condition of a conditional rendering block.
+///
+/// See [CommandBuffer::begin_conditional](crate::command::CommandBuffer::begin_conditional).
+#[derive(derivative::Derivative)]
+#[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
+pub enum Predicate<'a, B: Backend> {
+    /// Draw if any sample passed during the occlusion query.
+    Query(&'a B::Query),
+    /// Draw if the 32-bit value at `offset` in the buffer is not zero.
+    Buffer { buffer: &'a B::Buffer, offset: u64 },
+}
+
+impl<'a, B: Backend> Predicate<'a, B> {
+    /// Returns a predicate reading the 32-bit value at byte offset `offset` in the buffer.
+    pub fn buffer(buffer: impl Into<BufferTypeless<'a, B>>, offset: u64) -> Predicate<'a, B> {
+        Predicate::Buffer {
+            buffer: buffer.into().0,
+            offset,
+        }
+    }
+}
+
+impl<'a, B: Backend> From<OcclusionQuery<'a, B>> for Predicate<'a, B> {
+    fn from(query: OcclusionQuery<'a, B>) -> Self {
+        Predicate::Query(query.0)
+    }
+}
@@ -0,0 +1,597 @@
+//! Statistics about the submission of a frame, for diagnosing slow frames without
+//! an external profiler.
+//!
+//! Reports are opt-in (see [Api::set_frame_reports](crate::Api::set_frame_reports)).
+//! Once enabled, each call to `submit_frame` produces a [FrameReport] that can be retrieved
+//! with [Api::last_frame_report](crate::Api::last_frame_report).
+//!
+//! The lifetimes of transient resources can also be analyzed in each frame
+//! (see [Api::set_lifetime_reports](crate::Api::set_lifetime_reports)): the resulting
+//! [LifetimeReport] shows, for each aliasable resource, the range of sort keys in which it is
+//! actually used compared to its [AliasScope], which helps tuning the scopes.
+//! [FrameReport::to_dot] draws the passes and resources of a frame as a Graphviz graph, which
+//! shows how the commands were reordered and which resources alias each other.
+//!
+//! Independently of reports, `submit_frame` returns [SubmitStats], which tell whether the
+//! application is CPU- or GPU-bound.
+//!
+//! Finally, [Api::warm_up](crate::Api::warm_up) returns a [WarmUpReport] with the compilation
+//! time of each pipeline.
+use crate::{budget::BudgetWarning, AliasScope};
+use std::{
+    fmt::{self, Write},
+    time::Duration,
+};
+
+/// Statistics about a pass.
+///
+/// A pass is a sequence of consecutive commands (after sorting) that render into the same
+/// set of render targets, or the commands of a pass recorded with
+/// [render_pass!](crate::render_pass). Compute dispatches are counted in the pass they are
+/// sorted into.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PassReport {
+    /// Sort key of the first command of the pass.
+    pub sortkey: u64,
+    /// Name of the pass, for passes recorded with [render_pass!](crate::render_pass).
+    pub name: Option<&'static str>,
+    /// Number of draw calls.
+    pub draws: u32,
+    /// Number of compute dispatches.
+    pub dispatches: u32,
+    /// Number of state changes issued to the backend API
+    /// (pipelines, buffers, textures, render targets...).
+    pub state_changes: u32,
+}
+
+/// Statistics about the submission of a frame.
+#[derive(Clone, Debug, Default)]
+pub struct FrameReport {
+    /// Statistics per pass, in submission order.
+    pub passes: Vec<PassReport>,
+    /// Time spent sorting the commands.
+    pub sort_time: Duration,
+    /// Time spent in the backend to translate and submit the commands.
+    pub submit_time: Duration,
+    /// Number of bytes of host data uploaded to the GPU during submission.
+    pub upload_bytes: u64,
+    /// Lifetimes of the transient resources of the frame. Backends fill the report if it is
+    /// `Some` when the frame is submitted.
+    pub lifetimes: Option<LifetimeReport>,
+    /// Budgets exceeded in the frame (see [Api::set_budget](crate::Api::set_budget)).
+    pub budget_warnings: Vec<BudgetWarning>,
+}
+
+impl FrameReport {
+    /// Starts a new pass with the given sort key. Called by backends.
+    pub fn begin_pass(&mut self, sortkey: u64) {
+        self.passes.push(PassReport {
+            sortkey,
+            ..PassReport::default()
+        })
+    }
+
+    /// Starts a named pass with the given sort key. Called by backends on the debug groups
+    /// of passes.
+    ///
+    /// Replaces the current pass if it is unnamed and empty.
+    pub fn begin_named_pass(&mut self, sortkey: u64, name: &'static str) {
+        if let Some(&PassReport {
+            name: None,
+            draws: 0,
+            dispatches: 0,
+            state_changes: 0,
+            ..
+        }) = self.passes.last()
+        {
+            self.passes.pop();
+        }
+        self.passes.push(PassReport {
+            sortkey,
+            name: Some(name),
+            ..PassReport::default()
+        })
+    }
+
+    /// Returns the current pass, or starts one if there is none. Called by backends.
+    pub fn current_pass(&mut self, sortkey: u64) -> &mut PassReport {
+        if self.passes.is_empty() {
+            self.begin_pass(sortkey);
+        }
+        self.passes.last_mut().unwrap()
+    }
+
+    /// Sums the statistics of all passes. The sort key of the result is the one of the first pass.
+    pub fn total(&self) -> PassReport {
+        self.passes.iter().fold(
+            PassReport {
+                sortkey: self.passes.first().map(|p| p.sortkey).unwrap_or(0),
+                ..PassReport::default()
+            },
+            |acc, p| PassReport {
+                sortkey: acc.sortkey,
+                name: None,
+                draws: acc.draws + p.draws,
+                dispatches: acc.dispatches + p.dispatches,
+                state_changes: acc.state_changes + p.state_changes,
+            },
+        )
+    }
+
+    /// Returns the index of the pass containing the command with the specified sort key.
+    fn pass_index(&self, sortkey: u64) -> Option<usize> {
+        self.passes.iter().rposition(|p| p.sortkey <= sortkey)
+    }
+
+    /// Exports the passes of the frame, and the transient resources of the lifetime report
+    /// if there is one, as a Graphviz graph (dot language).
+    ///
+    /// Passes are chained in submission order. Each transient resource is linked to the pass of
+    /// its first use, considered as the one that writes it, and to the pass of its last use.
+    /// Resources allocated in the same memory (aliases) have the same color, and their
+    /// label shows their alias scope.
+    pub fn to_dot(&self) -> String {
+        const COLORS: &[&str] = &[
+            "lightblue",
+            "palegreen",
+            "lightsalmon",
+            "khaki",
+            "plum",
+            "lightpink",
+            "aquamarine",
+            "wheat",
+        ];
+
+        let mut out = String::new();
+        writeln!(out, "digraph frame {{").unwrap();
+        writeln!(out, "  rankdir=LR;").unwrap();
+        writeln!(out, "  node [fontname=\"monospace\"];").unwrap();
+        for (i, p) in self.passes.iter().enumerate() {
+            let name = p
+                .name
+                .map_or(format!("{:#x}", p.sortkey), |n| n.to_string());
+            writeln!(
+                out,
+                "  pass{} [shape=box, label={}];",
+                i,
+                json_string(&format!(
+                    "{}\nsortkey: {:#x}\ndraws: {}, dispatches: {}",
+                    name, p.sortkey, p.draws, p.dispatches
+                ))
+            )
+            .unwrap();
+            if i > 0 {
+                writeln!(out, "  pass{} -> pass{} [style=bold];", i - 1, i).unwrap();
+            }
+        }
+
+        if let Some(ref lifetimes) = self.lifetimes {
+            let mut memory = Vec::new();
+            for (i, r) in lifetimes.resources.iter().enumerate() {
+                let color = match memory.iter().position(|&m| m == r.memory) {
+                    Some(c) => c,
+                    None => {
+                        memory.push(r.memory);
+                        memory.len() - 1
+                    }
+                };
+                writeln!(
+                    out,
+                    "  res{} [shape=ellipse, style=filled, fillcolor={}, label={}];",
+                    i,
+                    COLORS[color % COLORS.len()],
+                    json_string(&format!(
+                        "{}\nmemory {}, scope {:#x}/{:#x}",
+                        r.description, r.memory, r.scope.value, r.scope.mask
+                    ))
+                )
+                .unwrap();
+
+                let first = r.first_use.and_then(|s| self.pass_index(s));
+                let last = r.last_use.and_then(|s| self.pass_index(s));
+                if let Some(first) = first {
+                    writeln!(out, "  pass{} -> res{};", first, i).unwrap();
+                }
+                if let Some(last) = last {
+                    if Some(last) != first {
+                        writeln!(out, "  res{} -> pass{};", i, last).unwrap();
+                    }
+                }
+            }
+        }
+        writeln!(out, "}}").unwrap();
+        out
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs() as f64 * 1000.0 + f64::from(d.subsec_nanos()) / 1_000_000.0
+}
+
+/// Prints the report as a table, with one row per pass.
+impl fmt::Display for FrameReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5} | {:>18} | {:>8} | {:>10} | {:>13} | name",
+            "pass", "sortkey", "draws", "dispatches", "state changes"
+        )?;
+        writeln!(f, "{:-<80}", "")?;
+        for (i, p) in self.passes.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>5} | {:#018x} | {:>8} | {:>10} | {:>13} | {}",
+                i,
+                p.sortkey,
+                p.draws,
+                p.dispatches,
+                p.state_changes,
+                p.name.unwrap_or("-")
+            )?;
+        }
+        writeln!(f, "{:-<80}", "")?;
+        let total = self.total();
+        writeln!(
+            f,
+            "{:>5} | {:>18} | {:>8} | {:>10} | {:>13}",
+            "total", "", total.draws, total.dispatches, total.state_changes
+        )?;
+        write!(
+            f,
+            "sort: {:.3}ms, submit: {:.3}ms, uploaded: {} bytes",
+            millis(self.sort_time),
+            millis(self.submit_time),
+            self.upload_bytes
+        )?;
+        for warning in self.budget_warnings.iter() {
+            write!(f, "\nwarning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+/// Synchronization between the CPU and the GPU during a call to `submit_frame`.
+///
+/// Returned by all `submit_frame` methods, whether frame reports are enabled or not.
+/// A frame is GPU-bound if the CPU regularly waits for the GPU, and CPU-bound if the GPU
+/// regularly runs out of work:
+/// ```ignore
+/// let stats = api.submit_frame(cmdbufs);
+/// if stats.is_gpu_bound() {
+///     // lower the resolution
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SubmitStats {
+    /// Time spent by the CPU blocked on the GPU, in milliseconds: waiting for a previous frame
+    /// to finish when too many frames are in flight, or in the swapchain when presenting.
+    pub cpu_wait_ms: f64,
+    /// Estimate of the time during which the GPU had nothing to execute before this frame was
+    /// submitted, in milliseconds.
+    ///
+    /// This is an upper bound: the time since the end of the previous submission, if the GPU had
+    /// already finished all previous frames when this one was submitted, and zero otherwise.
+    pub gpu_idle_estimate: f64,
+}
+
+impl SubmitStats {
+    /// Creates statistics from durations. Called by backends.
+    pub fn from_durations(cpu_wait: Duration, gpu_idle_estimate: Duration) -> SubmitStats {
+        SubmitStats {
+            cpu_wait_ms: millis(cpu_wait),
+            gpu_idle_estimate: millis(gpu_idle_estimate),
+        }
+    }
+
+    /// Returns whether the CPU waited on the GPU longer than the GPU was idle.
+    pub fn is_gpu_bound(&self) -> bool {
+        self.cpu_wait_ms > self.gpu_idle_estimate
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// The kind of a transient resource.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ResourceKind {
+    Image,
+    /// Aliasable buffers (not reported by the OpenGL backend, which does not alias buffers).
+    Buffer,
+}
+
+/// Lifetime of a transient (aliasable) resource in the sorted command stream of a frame.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResourceLifetime {
+    pub kind: ResourceKind,
+    /// Backend-specific identifier of the memory of the resource (e.g. the name of the OpenGL
+    /// texture object). Resources with the same memory alias each other.
+    pub memory: u64,
+    /// Scope in which the resource can be used.
+    pub scope: AliasScope,
+    /// Description of the resource (format, dimensions...).
+    pub description: String,
+    /// Sort key of the first command that uses the resource, or `None` if it is not used in
+    /// the frame.
+    pub first_use: Option<u64>,
+    /// Sort key of the last command that uses the resource.
+    pub last_use: Option<u64>,
+    /// Number of commands of the frame inside the scope of the resource.
+    pub scope_commands: usize,
+    /// Number of commands of the frame inside the scope, between the first and the last use of
+    /// the resource.
+    pub used_commands: usize,
+    /// Indices in the report of the other resources that share the memory of this one.
+    pub aliases: Vec<usize>,
+}
+
+impl ResourceLifetime {
+    /// Number of commands inside the scope of the resource but outside of its actual lifetime:
+    /// a scope reduced by that much would leave more room for aliasing.
+    pub fn wasted_commands(&self) -> usize {
+        self.scope_commands - self.used_commands
+    }
+}
+
+/// Lifetimes of the transient resources of a frame, and the aliasing decisions of the backend.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LifetimeReport {
+    /// Transient resources, in the order they were added by the backend.
+    pub resources: Vec<ResourceLifetime>,
+    /// Number of uses of transient memory outside of the scopes of all the resources allocated
+    /// in it. Such uses may overwrite the contents of an aliased resource.
+    pub out_of_scope_uses: usize,
+}
+
+impl LifetimeReport {
+    /// Adds a transient resource allocated in `memory`. Called by backends.
+    pub fn add_resource(
+        &mut self,
+        kind: ResourceKind,
+        memory: u64,
+        scope: AliasScope,
+        description: impl Into<String>,
+    ) {
+        self.resources.push(ResourceLifetime {
+            kind,
+            memory,
+            scope,
+            description: description.into(),
+            first_use: None,
+            last_use: None,
+            scope_commands: 0,
+            used_commands: 0,
+            aliases: Vec::new(),
+        })
+    }
+
+    /// Records a use of `memory` by a command with the specified sort key. Called by backends.
+    ///
+    /// The use is attributed to the resource allocated in `memory` whose scope contains the
+    /// sort key. Uses of memory that is not transient are ignored.
+    pub fn record_use(&mut self, memory: u64, sortkey: u64) {
+        if let Some(r) = self
+            .resources
+            .iter_mut()
+            .find(|r| r.memory == memory && r.scope.contains(sortkey))
+        {
+            r.first_use = Some(r.first_use.map_or(sortkey, |s| s.min(sortkey)));
+            r.last_use = Some(r.last_use.map_or(sortkey, |s| s.max(sortkey)));
+        } else if self.resources.iter().any(|r| r.memory == memory) {
+            self.out_of_scope_uses += 1;
+        }
+    }
+
+    /// Computes the command counts and aliases of the resources, given the sort keys of all
+    /// the commands of the frame. Called by the renderer after submission.
+    pub fn finish(&mut self, sortkeys: &[u64]) {
+        for r in self.resources.iter_mut() {
+            let scope = r.scope;
+            let in_scope = sortkeys.iter().filter(|&&s| scope.contains(s));
+            r.scope_commands = in_scope.clone().count();
+            r.used_commands = match (r.first_use, r.last_use) {
+                (Some(first), Some(last)) => in_scope.filter(|&&s| s >= first && s <= last).count(),
+                _ => 0,
+            };
+        }
+
+        let memory = self.resources.iter().map(|r| r.memory).collect::<Vec<_>>();
+        for (i, r) in self.resources.iter_mut().enumerate() {
+            r.aliases = memory
+                .iter()
+                .enumerate()
+                .filter(|&(j, &m)| j != i && m == r.memory)
+                .map(|(j, _)| j)
+                .collect();
+        }
+    }
+
+    /// Exports the report as JSON, for external tools.
+    ///
+    /// Sort keys and scopes are exported as hexadecimal strings, because they may not be
+    /// representable as JSON numbers.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        out.push_str("{\"resources\":[");
+        for (i, r) in self.resources.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write!(
+                out,
+                "{{\"index\":{},\"kind\":\"{:?}\",\"memory\":{},\"description\":{},\
+                 \"scope\":{{\"value\":\"{:#x}\",\"mask\":\"{:#x}\"}},\
+                 \"first_use\":{},\"last_use\":{},\"scope_commands\":{},\
+                 \"used_commands\":{},\"wasted_commands\":{},\"aliases\":{:?}}}",
+                i,
+                r.kind,
+                r.memory,
+                json_string(&r.description),
+                r.scope.value,
+                r.scope.mask,
+                json_sortkey(r.first_use),
+                json_sortkey(r.last_use),
+                r.scope_commands,
+                r.used_commands,
+                r.wasted_commands(),
+                r.aliases
+            )
+            .unwrap();
+        }
+        write!(out, "],\"out_of_scope_uses\":{}}}", self.out_of_scope_uses).unwrap();
+        out
+    }
+}
+
+fn json_sortkey(sortkey: Option<u64>) -> String {
+    match sortkey {
+        Some(s) => format!("\"{:#x}\"", s),
+        None => "null".to_string(),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn sortkey_or_dash(sortkey: Option<u64>) -> String {
+    sortkey.map_or("-".to_string(), |s| format!("{:#x}", s))
+}
+
+/// Prints the report as a table, with one row per resource.
+impl fmt::Display for LifetimeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4} | {:>6} | {:>8} | {:>18} | {:>18} | {:>18} | {:>18} | {:>6} | {:>6} | {:>7} | {}",
+            "#",
+            "kind",
+            "memory",
+            "scope value",
+            "scope mask",
+            "first use",
+            "last use",
+            "scope",
+            "used",
+            "aliases",
+            "description"
+        )?;
+        writeln!(f, "{:-<160}", "")?;
+        for (i, r) in self.resources.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4} | {:>6} | {:>8} | {:#018x} | {:#018x} | {:>18} | {:>18} | {:>6} | {:>6} | {:>7} | {}",
+                i,
+                format!("{:?}", r.kind),
+                r.memory,
+                r.scope.value,
+                r.scope.mask,
+                sortkey_or_dash(r.first_use),
+                sortkey_or_dash(r.last_use),
+                r.scope_commands,
+                r.used_commands,
+                r.aliases.len(),
+                r.description
+            )?;
+        }
+        writeln!(f, "{:-<160}", "")?;
+        write!(
+            f,
+            "wasted: {} commands, out-of-scope uses: {}",
+            self.resources
+                .iter()
+                .map(|r| r.wasted_commands())
+                .sum::<usize>(),
+            self.out_of_scope_uses
+        )
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// The kind of a pipeline passed to [Api::warm_up](crate::Api::warm_up).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PipelineKind {
+    Graphics,
+    Compute,
+}
+
+/// Compilation time of a pipeline.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PipelineCompileTime {
+    pub kind: PipelineKind,
+    /// Time spent compiling the pipeline when it was created (e.g. linking the GL program).
+    pub create_time: Duration,
+    /// Time spent in the backend to complete the compilation during the warm-up
+    /// (e.g. compilation of the driver-specific variants of the program).
+    pub warm_up_time: Duration,
+}
+
+impl PipelineCompileTime {
+    /// Total compilation time of the pipeline.
+    pub fn total(&self) -> Duration {
+        self.create_time + self.warm_up_time
+    }
+}
+
+/// Compilation times of the pipelines passed to [Api::warm_up](crate::Api::warm_up),
+/// in the same order.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct WarmUpReport {
+    pub pipelines: Vec<PipelineCompileTime>,
+}
+
+impl WarmUpReport {
+    /// Total time spent warming up the pipelines.
+    pub fn warm_up_time(&self) -> Duration {
+        self.pipelines.iter().map(|p| p.warm_up_time).sum()
+    }
+
+    /// Returns the index and compilation time of the pipeline that took the longest to compile.
+    pub fn slowest(&self) -> Option<(usize, &PipelineCompileTime)> {
+        self.pipelines
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, p)| p.total())
+    }
+}
+
+impl fmt::Display for WarmUpReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4} | {:>8} | {:>12} | {:>12} | {:>12}",
+            "#", "kind", "create (ms)", "warm-up (ms)", "total (ms)"
+        )?;
+        writeln!(f, "{:-<60}", "")?;
+        for (i, p) in self.pipelines.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4} | {:>8} | {:>12.3} | {:>12.3} | {:>12.3}",
+                i,
+                format!("{:?}", p.kind),
+                millis(p.create_time),
+                millis(p.warm_up_time),
+                millis(p.total())
+            )?;
+        }
+        writeln!(f, "{:-<60}", "")?;
+        write!(
+            f,
+            "{} pipelines, warm-up: {:.3}ms",
+            self.pipelines.len(),
+            millis(self.warm_up_time())
+        )
+    }
+}
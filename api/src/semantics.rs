@@ -0,0 +1,100 @@
+//! Named resources.
+//!
+//! Resources can be registered under a well-known _semantic_ (e.g. `gbuffer.normal`) in a
+//! [ResourceSemantics] registry. Command buffers can then reference them by [SemanticId] instead
+//! of by handle: the references are resolved when the frame is submitted
+//! (see [Api::submit_frame_with_semantics](crate::Api::submit_frame_with_semantics)).
+//!
+//! This way, a post-processing effect only needs to know the name of the buffer it operates on
+//! (convention over configuration), and not how to get a handle to it.
+//!
+//! Only images can be registered, and only clear and present commands can reference them by
+//! semantic: argument blocks are built from the resources themselves.
+use crate::{image::Image2dView, Backend};
+use std::{collections::HashMap, fmt};
+
+/// 64-bit identifier of a resource semantic.
+///
+/// Usually derived from a name with [SemanticId::from_name].
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct SemanticId(pub u64);
+
+impl SemanticId {
+    /// Returns the ID corresponding to the given name (64-bit FNV-1a hash of the name).
+    pub fn from_name(name: &str) -> SemanticId {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for &b in name.as_bytes() {
+            hash ^= u64::from(b);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        SemanticId(hash)
+    }
+}
+
+impl<'a> From<&'a str> for SemanticId {
+    fn from(name: &'a str) -> SemanticId {
+        SemanticId::from_name(name)
+    }
+}
+
+impl fmt::Debug for SemanticId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SemanticId({:#018x})", self.0)
+    }
+}
+
+/// A resource registered under a semantic.
+#[derive(derivative::Derivative)]
+#[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
+pub enum SemanticResource<'a, B: Backend> {
+    Image(Image2dView<'a, B>),
+}
+
+/// Registry of resources indexed by semantic.
+pub struct ResourceSemantics<'a, B: Backend> {
+    resources: HashMap<SemanticId, SemanticResource<'a, B>>,
+}
+
+impl<'a, B: Backend> ResourceSemantics<'a, B> {
+    pub fn new() -> ResourceSemantics<'a, B> {
+        ResourceSemantics {
+            resources: HashMap::new(),
+        }
+    }
+
+    /// Registers an image under the given semantic, replacing any previously registered resource.
+    pub fn register_image(
+        &mut self,
+        semantic: impl Into<SemanticId>,
+        image: impl Into<Image2dView<'a, B>>,
+    ) {
+        self.resources
+            .insert(semantic.into(), SemanticResource::Image(image.into()));
+    }
+
+    /// Removes the resource registered under the given semantic.
+    pub fn unregister(&mut self, semantic: impl Into<SemanticId>) {
+        self.resources.remove(&semantic.into());
+    }
+
+    /// Returns the resource registered under the given semantic, if any.
+    pub fn get(&self, semantic: impl Into<SemanticId>) -> Option<SemanticResource<'a, B>> {
+        self.resources.get(&semantic.into()).cloned()
+    }
+
+    /// Returns the image registered under the given semantic.
+    ///
+    /// Returns `None` if there is no resource with this semantic.
+    pub fn image(&self, semantic: impl Into<SemanticId>) -> Option<Image2dView<'a, B>> {
+        match self.get(semantic) {
+            Some(SemanticResource::Image(img)) => Some(img),
+            None => None,
+        }
+    }
+}
+
+impl<'a, B: Backend> Default for ResourceSemantics<'a, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,109 @@
+//! Destinations of rendered frames.
+//!
+//! A [FrameSink] provides the image to render each frame to, and outputs it at the end of the
+//! frame. Frame code written against a `FrameSink` runs unchanged whether the frames are
+//! presented to a window (a [Swapchain]) or copied to host memory, e.g. to write the frames of
+//! an animation to files in a batch render.
+use crate::{
+    buffer::HostReadback,
+    command::CommandBuffer,
+    image::{Image2d, RenderTarget2dView, RenderTargetImage2d},
+    swapchain::{Swapchain, SwapchainFrame},
+    Arena, Backend,
+};
+
+/// A destination for rendered frames.
+pub trait FrameSink<B: Backend> {
+    /// Returns the size of the frames.
+    fn size(&self) -> (u32, u32);
+
+    /// Begins a frame: returns the image to render to, valid for the lifetime of `arena`.
+    ///
+    /// The frame must be output with [SinkFrame::present] in a command buffer of the frame.
+    fn begin_frame<'a, 'r>(&'a mut self, arena: &'a Arena<'r, B>) -> SinkFrame<'a, B>;
+}
+
+impl<'s, B: Backend> FrameSink<B> for Swapchain<'s, B> {
+    fn size(&self) -> (u32, u32) {
+        Swapchain::size(self)
+    }
+
+    fn begin_frame<'a, 'r>(&'a mut self, arena: &'a Arena<'r, B>) -> SinkFrame<'a, B> {
+        let swapchain: Swapchain<'a, B> = *self;
+        SinkFrame::from(swapchain.acquire(arena))
+    }
+}
+
+enum FrameOutput<'a, B: Backend> {
+    Swapchain(&'a B::Swapchain),
+    Readback(HostReadback<'a, B, u8>),
+}
+
+/// A frame begun with [FrameSink::begin_frame].
+///
+/// Presenting consumes the frame, so that each frame is output at most once.
+pub struct SinkFrame<'a, B: Backend> {
+    image: &'a B::Image,
+    size: (u32, u32),
+    output: FrameOutput<'a, B>,
+}
+
+impl<'a, B: Backend> From<SwapchainFrame<'a, B>> for SinkFrame<'a, B> {
+    fn from(frame: SwapchainFrame<'a, B>) -> Self {
+        SinkFrame {
+            image: frame.image,
+            size: frame.size(),
+            output: FrameOutput::Swapchain(frame.swapchain),
+        }
+    }
+}
+
+impl<'a, B: Backend> SinkFrame<'a, B> {
+    /// Creates a frame that renders to `image`, of the specified size, and is copied to the
+    /// host memory referenced by `readback` on presentation
+    /// (see [CommandBuffer::copy_image_to_host]).
+    pub fn readback(
+        image: Image2d<'a, B>,
+        size: (u32, u32),
+        readback: HostReadback<'a, B, u8>,
+    ) -> Self {
+        SinkFrame {
+            image: image.image,
+            size,
+            output: FrameOutput::Readback(readback),
+        }
+    }
+
+    /// Returns the size of the frame.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the image of the frame.
+    pub fn image(&self) -> RenderTargetImage2d<'a, B> {
+        RenderTargetImage2d { image: self.image }
+    }
+
+    /// Returns a render target view of the image of the frame.
+    pub fn render_target_view(&self) -> RenderTarget2dView<'a, B> {
+        self.image().render_target_view()
+    }
+
+    /// Outputs the frame to its sink: presents it to the swapchain, or copies it to host memory.
+    pub fn present(self, cmdbuf: &mut CommandBuffer<'a, B>, sortkey: u64) {
+        match self.output {
+            FrameOutput::Swapchain(swapchain) => cmdbuf.present(
+                sortkey,
+                SwapchainFrame {
+                    swapchain,
+                    image: self.image,
+                },
+            ),
+            FrameOutput::Readback(readback) => cmdbuf.copy_image_to_host(
+                sortkey,
+                RenderTargetImage2d { image: self.image },
+                readback,
+            ),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! Typed sort keys.
+//!
+//! Commands are sorted by a `u64` sort key, usually packed from several fields (layer, pipeline,
+//! depth...) with shifts and masks. `#[derive(SortKey)]` generates the packing code for a newtype
+//! of `u64` from a description of its fields, from the most significant to the least
+//! significant:
+//! ```ignore
+//! #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, SortKey)]
+//! #[sort_key(layer = 4, pipeline = 12, depth = 24)]
+//! struct OpaqueKey(u64);
+//!
+//! let key = OpaqueKey::default().with_layer(1).with_pipeline(pipeline_index);
+//! cmdbuf.draw(key.with_depth(depth).into(), &arena, pipeline, args, DrawParams::quad());
+//! ```
+//!
+//! For each field, the derive generates a getter (`key.depth()`), a setter (`key.set_depth(d)`),
+//! a builder-style setter (`key.with_depth(d)`), and the `DEPTH_SHIFT` and `DEPTH_BITS`
+//! constants. The fields occupy the low [SortKey::BITS] bits of the key, so that the key can be
+//! used as the local sort key of a pass whose layout has at least as many local bits
+//! (see [SortKeyLayout](crate::pass::SortKeyLayout)).
+//!
+//! Layouts whose fields do not fit in 64 bits are rejected at compile time. The setters panic if
+//! the value does not fit in the bits of the field, instead of silently overwriting the other
+//! fields.
+pub use autograph_api_macros::SortKey;
+
+/// A sort key made of fields packed in a `u64`. See the [module-level documentation](self).
+///
+/// Should be derived with `#[derive(SortKey)]`.
+pub trait SortKey: Copy + Into<u64> {
+    /// Total number of bits used by the fields of the key.
+    const BITS: u32;
+
+    /// Returns the raw value of the key.
+    fn value(self) -> u64 {
+        self.into()
+    }
+}
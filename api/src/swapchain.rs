@@ -1,4 +1,8 @@
-use crate::Backend;
+use crate::{
+    format::ColorSpace,
+    image::{RenderTarget2dView, RenderTargetImage2d},
+    Arena, Backend,
+};
 
 //--------------------------------------------------------------------------------------------------
 /// Swapchains.
@@ -11,4 +15,67 @@ impl<'a, B: Backend> Swapchain<'a, B> {
     pub fn size(&self) -> (u32, u32) {
         crate::traits::Swapchain::size(self.0)
     }
+
+    /// Returns the color space of the images presented to the swapchain.
+    ///
+    /// With [ColorSpace::Srgb], linear images are encoded to sRGB on presentation, and
+    /// images in sRGB formats are presented as-is.
+    pub fn color_space(&self) -> ColorSpace {
+        crate::traits::Swapchain::color_space(self.0)
+    }
+
+    /// Acquires the image that will be presented next to the swapchain.
+    ///
+    /// The returned frame can be used as a render target, and must be passed to
+    /// [CommandBuffer::present](crate::command::CommandBuffer::present) to be displayed.
+    /// The image is valid for the lifetime of `arena`: acquire a new one for each frame
+    /// with a per-frame arena.
+    ///
+    /// Depending on the backend, the image is either an image of the swapchain, or an
+    /// intermediate image that is copied to the swapchain on presentation.
+    pub fn acquire<'r>(&self, arena: &'a Arena<'r, B>) -> SwapchainFrame<'a, B> {
+        let image = unsafe {
+            arena
+                .instance
+                .acquire_swapchain_image(arena.inner(), self.0)
+        };
+        SwapchainFrame {
+            swapchain: self.0,
+            image,
+        }
+    }
+}
+
+/// An image acquired from a swapchain with [Swapchain::acquire], to be presented with
+/// [CommandBuffer::present](crate::command::CommandBuffer::present).
+///
+/// Presenting consumes the frame, so that each acquired image is presented at most once.
+#[derive(derivative::Derivative)]
+#[derivative(Debug(bound = ""))]
+pub struct SwapchainFrame<'a, B: Backend> {
+    pub(crate) swapchain: &'a B::Swapchain,
+    pub(crate) image: &'a B::Image,
+}
+
+impl<'a, B: Backend> SwapchainFrame<'a, B> {
+    /// Returns the swapchain this frame was acquired from.
+    pub fn swapchain(&self) -> Swapchain<'a, B> {
+        Swapchain(self.swapchain)
+    }
+
+    /// Returns the size of the image, which is the size of the swapchain at the time of
+    /// acquisition.
+    pub fn size(&self) -> (u32, u32) {
+        crate::traits::Swapchain::size(self.swapchain)
+    }
+
+    /// Returns the acquired image.
+    pub fn image(&self) -> RenderTargetImage2d<'a, B> {
+        RenderTargetImage2d { image: self.image }
+    }
+
+    /// Returns a render target view of the acquired image.
+    pub fn render_target_view(&self) -> RenderTarget2dView<'a, B> {
+        self.image().render_target_view()
+    }
 }
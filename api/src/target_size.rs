@@ -0,0 +1,46 @@
+//! Sizes of the render targets of argument blocks, for the validation of the static viewports of
+//! pipelines at draw time.
+//!
+//! Only recorded in debug builds: the checks are skipped in release builds, like the other
+//! draw-time checks of [CommandBuffer](crate::command::CommandBuffer).
+use std::{collections::HashMap, sync::Mutex};
+
+/// Returns the smallest of two render target sizes, in each dimension.
+pub(crate) fn min_size(a: Option<(u32, u32)>, b: (u32, u32)) -> (u32, u32) {
+    match a {
+        Some(a) => (a.0.min(b.0), a.1.min(b.1)),
+        None => b,
+    }
+}
+
+/// Smallest size of the render targets of argument blocks (including the render targets of
+/// inherited blocks), by address of the backend argument block.
+pub(crate) struct TargetSizes {
+    blocks: Mutex<HashMap<usize, (u32, u32)>>,
+}
+
+impl TargetSizes {
+    pub(crate) fn new() -> TargetSizes {
+        TargetSizes {
+            blocks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn register_block(&self, block: usize, size: (u32, u32)) {
+        self.blocks.lock().unwrap().insert(block, size);
+    }
+
+    /// Forgets the specified argument blocks (when their arena is dropped).
+    pub(crate) fn forget(&self, blocks: &[usize]) {
+        let mut tracked = self.blocks.lock().unwrap();
+        for block in blocks {
+            tracked.remove(block);
+        }
+    }
+
+    /// Returns the size of the render targets of an argument block, or `None` if the block
+    /// has no render targets.
+    pub(crate) fn size(&self, block: usize) -> Option<(u32, u32)> {
+        self.blocks.lock().unwrap().get(&block).cloned()
+    }
+}
@@ -1,5 +1,14 @@
+use crate::{format::ColorSpace, image::Dimensions};
+
 pub trait Swapchain {
     fn size(&self) -> (u32, u32);
+    fn color_space(&self) -> ColorSpace;
+}
+
+pub trait Image {
+    /// Returns the current dimensions of the image. Swapchain-relative dimensions are resolved
+    /// with the size of the swapchain at the last frame submission.
+    fn dimensions(&self) -> Dimensions;
 }
 
 /*
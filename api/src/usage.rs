@@ -0,0 +1,117 @@
+//! Inference of the usage of images created with [ImageUsageFlags::INFER].
+//!
+//! The usage of an image is collected from the argument blocks and commands that refer to it,
+//! until the end of the first frame submission in which it was used. After that, the usage is
+//! frozen: any use that was not seen during that frame is a programming error, and panics.
+use crate::{format::Format, image::Dimensions, image::ImageUsageFlags};
+use log::info;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+/// The usage inferred so far for an image.
+#[derive(Copy, Clone, Debug)]
+struct InferredUsage {
+    format: Format,
+    dimensions: Dimensions,
+    usage: ImageUsageFlags,
+    frozen: bool,
+}
+
+/// Usage of images created with [ImageUsageFlags::INFER], by address of the backend image.
+pub(crate) struct UsageTracker {
+    images: Mutex<HashMap<usize, InferredUsage>>,
+    /// Number of tracked images, so that uses are recorded without locking when there are none.
+    count: AtomicUsize,
+}
+
+impl UsageTracker {
+    pub(crate) fn new() -> UsageTracker {
+        UsageTracker {
+            images: Mutex::new(HashMap::new()),
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns whether images are tracked.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.count.load(Ordering::Relaxed) != 0
+    }
+
+    /// Starts tracking the usage of an image.
+    pub(crate) fn register(&self, image: usize, format: Format, dimensions: Dimensions) {
+        let mut tracked = self.images.lock().unwrap();
+        tracked.insert(
+            image,
+            InferredUsage {
+                format,
+                dimensions,
+                usage: ImageUsageFlags::empty(),
+                frozen: false,
+            },
+        );
+        self.count.store(tracked.len(), Ordering::Relaxed);
+    }
+
+    /// Stops tracking the usage of the specified images (when their arena is dropped).
+    pub(crate) fn forget(&self, images: &[usize]) {
+        let mut tracked = self.images.lock().unwrap();
+        for image in images {
+            tracked.remove(image);
+        }
+        self.count.store(tracked.len(), Ordering::Relaxed);
+    }
+
+    /// Records a use of an image. Does nothing if the image is not tracked.
+    ///
+    /// Panics if the usage of the image is frozen and does not contain `usage`.
+    pub(crate) fn record(&self, image: usize, usage: ImageUsageFlags) {
+        if !self.is_enabled() {
+            return;
+        }
+        // don't panic with the lock held: arenas dropped while unwinding still need it
+        let mut violation = None;
+        if let Some(inferred) = self.images.lock().unwrap().get_mut(&image) {
+            if !inferred.frozen {
+                inferred.usage |= usage;
+            } else if !inferred.usage.contains(usage) {
+                violation = Some(*inferred);
+            }
+        }
+        if let Some(inferred) = violation {
+            panic!(
+                "image ({:?}, {:?}) used as {:?}, but its usage inferred from the first frame is {:?}",
+                inferred.format, inferred.dimensions, usage, inferred.usage
+            );
+        }
+    }
+
+    /// Returns the usage inferred so far for an image, and whether it is frozen.
+    pub(crate) fn usage(&self, image: usize) -> Option<(ImageUsageFlags, bool)> {
+        self.images
+            .lock()
+            .unwrap()
+            .get(&image)
+            .map(|inferred| (inferred.usage, inferred.frozen))
+    }
+
+    /// Freezes the usage of the images that were used since they were created.
+    ///
+    /// Called once per frame submission.
+    pub(crate) fn end_frame(&self) {
+        let mut tracked = self.images.lock().unwrap();
+        for inferred in tracked.values_mut() {
+            if !inferred.frozen && !inferred.usage.is_empty() {
+                info!(
+                    "inferred usage of image ({:?}, {:?}): {:?}",
+                    inferred.format, inferred.dimensions, inferred.usage
+                );
+                inferred.frozen = true;
+            }
+        }
+    }
+}
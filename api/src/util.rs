@@ -1,4 +1,8 @@
-use std::ops::Range;
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+    ptr,
+};
 
 pub fn align_offset(size: u64, align: u64, space: Range<u64>) -> Option<u64> {
     assert!(align.is_power_of_two(), "alignment must be a power of two");
@@ -12,3 +16,21 @@ pub fn align_offset(size: u64, align: u64, space: Range<u64>) -> Option<u64> {
         Some(space.start + off)
     }
 }
+
+/// Hashes a reference by address.
+///
+/// Used to hash wrappers around backend objects by identity (the backend objects themselves
+/// are not required to implement `Hash`).
+pub(crate) fn hash_address<T, H: Hasher>(r: &T, state: &mut H) {
+    (r as *const T as usize).hash(state)
+}
+
+/// Compares two references by address, consistently with [hash_address].
+pub(crate) fn eq_address<T>(a: &T, b: &T) -> bool {
+    ptr::eq(a, b)
+}
+
+/// Returns the address of a backend object, used as a key to identify it.
+pub(crate) fn address<T>(r: &T) -> usize {
+    r as *const T as usize
+}
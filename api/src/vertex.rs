@@ -4,8 +4,12 @@ use crate::{
     typedesc::{PrimitiveType, TypeDesc},
 };
 
-use crate::Backend;
+use crate::{
+    util::{eq_address, hash_address},
+    Backend,
+};
 pub use autograph_api_macros::VertexData;
+use std::hash::{Hash, Hasher};
 
 /// Describes the type of indices contained in an index buffer.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -35,6 +39,8 @@ pub struct VertexLayoutElement<'tcx> {
     pub semantic: Option<Semantic<'tcx>>,
     pub format: Format,
     pub offset: u32,
+    /// Name of the attribute, for diagnostics (the field name for derived `VertexData`).
+    pub name: Option<&'tcx str>,
 }
 
 /// Describes the layout of vertex data inside a single vertex buffer.
@@ -59,6 +65,24 @@ pub struct VertexBufferView<'a, B: Backend> {
     pub(crate) offset: usize,
 }
 
+impl<'a, B: Backend> Hash for VertexBufferView<'a, B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.buffer, state);
+        self.stride.hash(state);
+        self.offset.hash(state);
+    }
+}
+
+impl<'a, B: Backend> PartialEq for VertexBufferView<'a, B> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.buffer, other.buffer)
+            && self.stride == other.stride
+            && self.offset == other.offset
+    }
+}
+
+impl<'a, B: Backend> Eq for VertexBufferView<'a, B> {}
+
 impl<'a, B: Backend> VertexBufferView<'a, B> {
     pub fn buffer(&self) -> &'a B::Buffer {
         self.buffer
@@ -120,6 +144,21 @@ impl<'a, B: Backend, V: VertexData> From<Buffer<'a, B, [V]>> for TypedVertexBuff
 ///     texcoords: Vec2,
 /// }
 /// ```
+///
+/// The format of an attribute can be overriden with `#[vertex(format = "...")]`, in which case
+/// the field type does not need to implement [VertexAttributeType]. The size of the field
+/// must match the size of the format, otherwise the derive fails to compile:
+///
+/// ```rust
+/// #[derive(VertexData)]
+/// #[repr(C)]
+/// struct PackedVertex {
+///     position: [f32; 3],
+///     normals: Normalized<[i16; 4]>,
+///     #[vertex(format = "R16G16_SFLOAT")]
+///     texcoords: [u16; 2],
+/// }
+/// ```
 pub unsafe trait VertexData: BufferData {
     const LAYOUT: VertexLayout<'static>;
 }
@@ -136,6 +175,24 @@ pub struct IndexBufferView<'a, B: Backend> {
     pub offset: usize,
 }
 
+impl<'a, B: Backend> Hash for IndexBufferView<'a, B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_address(self.buffer, state);
+        self.format.hash(state);
+        self.offset.hash(state);
+    }
+}
+
+impl<'a, B: Backend> PartialEq for IndexBufferView<'a, B> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_address(self.buffer, other.buffer)
+            && self.format == other.format
+            && self.offset == other.offset
+    }
+}
+
+impl<'a, B: Backend> Eq for IndexBufferView<'a, B> {}
+
 /// Trait implemented by types that can serve as indices.
 pub unsafe trait IndexData: BufferData {
     /// Index type.
@@ -221,6 +278,83 @@ pub unsafe trait VertexAttributeType {
     const FORMAT: Format;
 }
 
+/// Wrapper for integer vertex attributes that are read as normalized floating-point values
+/// by the shader: in \[0.0;1.0\] for unsigned types and in \[-1.0;1.0\] for signed types.
+///
+/// E.g. `Normalized<[i16; 4]>` has the format `R16G16B16A16_SNORM` and is seen as a `vec4`.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Normalized<T>(pub T);
+
+/// Half-precision (16-bit) floating-point value, stored as raw bits.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Half(pub u16);
+
+impl Half {
+    /// Converts a single-precision value to half precision (rounding to nearest).
+    ///
+    /// Values that are too large are converted to infinity.
+    pub fn from_f32(value: f32) -> Half {
+        let bits = value.to_bits();
+        let sign = ((bits >> 16) & 0x8000) as u16;
+        let exp = ((bits >> 23) & 0xff) as i32;
+        let mant = bits & 0x007f_ffff;
+
+        if exp == 0xff {
+            // infinity or NaN
+            return Half(sign | 0x7c00 | if mant != 0 { 0x200 } else { 0 });
+        }
+
+        let e = exp - 127 + 15;
+        if e >= 0x1f {
+            // overflow
+            Half(sign | 0x7c00)
+        } else if e <= 0 {
+            // subnormal or zero
+            if e < -10 {
+                return Half(sign);
+            }
+            let mant = mant | 0x0080_0000;
+            let shift = (14 - e) as u32;
+            let half_mant = (mant >> shift) as u16;
+            let round = ((mant >> (shift - 1)) & 1) as u16;
+            Half(sign | (half_mant + round))
+        } else {
+            let half = sign | ((e as u16) << 10) | (mant >> 13) as u16;
+            // may carry into the exponent, which is the correct behavior
+            let round = ((mant >> 12) & 1) as u16;
+            Half(half + round)
+        }
+    }
+
+    /// Converts the value to single precision.
+    pub fn to_f32(self) -> f32 {
+        let sign = u32::from(self.0 & 0x8000) << 16;
+        let exp = u32::from((self.0 >> 10) & 0x1f);
+        let mant = u32::from(self.0 & 0x3ff);
+
+        match exp {
+            0 => {
+                let value = mant as f32 / (1 << 24) as f32;
+                if sign != 0 {
+                    -value
+                } else {
+                    value
+                }
+            }
+            0x1f => f32::from_bits(sign | 0x7f80_0000 | (mant << 13)),
+            _ => f32::from_bits(sign | ((exp + 112) << 23) | (mant << 13)),
+        }
+    }
+}
+
+impl From<f32> for Half {
+    fn from(value: f32) -> Half {
+        Half::from_f32(value)
+    }
+}
+
 // Vertex attribute types --------------------------------------------------------------------------
 macro_rules! impl_attrib_type {
     ($t:ty, $equiv:expr, $fmt:ident) => {
@@ -250,6 +384,15 @@ macro_rules! impl_attrib_array_type {
             const FORMAT: Format = Format::$fmt;
         }
     };
+    (Normalized<[$t:ty; $len:expr]>, $prim:ident, $fmt:ident) => {
+        unsafe impl VertexAttributeType for Normalized<[$t; $len]> {
+            const EQUIVALENT_TYPE: TypeDesc<'static> = TypeDesc::Vector {
+                elem_ty: PrimitiveType::$prim,
+                len: $len,
+            };
+            const FORMAT: Format = Format::$fmt;
+        }
+    };
 }
 
 // F32
@@ -291,6 +434,33 @@ impl_attrib_array_type!([i8; 2], Int, R8G8_SINT);
 impl_attrib_array_type!([i8; 3], Int, R8G8B8_SINT);
 impl_attrib_array_type!([i8; 4], Int, R8G8B8A8_SINT);
 
+// Half-floats
+impl_attrib_prim_type!(Half, Float, R16_SFLOAT);
+impl_attrib_array_type!([Half; 2], Float, R16G16_SFLOAT);
+impl_attrib_array_type!([Half; 3], Float, R16G16B16_SFLOAT);
+impl_attrib_array_type!([Half; 4], Float, R16G16B16A16_SFLOAT);
+
+// Normalized integers
+impl_attrib_prim_type!(Normalized<u16>, Float, R16_UNORM);
+impl_attrib_array_type!(Normalized<[u16; 2]>, Float, R16G16_UNORM);
+impl_attrib_array_type!(Normalized<[u16; 3]>, Float, R16G16B16_UNORM);
+impl_attrib_array_type!(Normalized<[u16; 4]>, Float, R16G16B16A16_UNORM);
+
+impl_attrib_prim_type!(Normalized<i16>, Float, R16_SNORM);
+impl_attrib_array_type!(Normalized<[i16; 2]>, Float, R16G16_SNORM);
+impl_attrib_array_type!(Normalized<[i16; 3]>, Float, R16G16B16_SNORM);
+impl_attrib_array_type!(Normalized<[i16; 4]>, Float, R16G16B16A16_SNORM);
+
+impl_attrib_prim_type!(Normalized<u8>, Float, R8_UNORM);
+impl_attrib_array_type!(Normalized<[u8; 2]>, Float, R8G8_UNORM);
+impl_attrib_array_type!(Normalized<[u8; 3]>, Float, R8G8B8_UNORM);
+impl_attrib_array_type!(Normalized<[u8; 4]>, Float, R8G8B8A8_UNORM);
+
+impl_attrib_prim_type!(Normalized<i8>, Float, R8_SNORM);
+impl_attrib_array_type!(Normalized<[i8; 2]>, Float, R8G8_SNORM);
+impl_attrib_array_type!(Normalized<[i8; 3]>, Float, R8G8B8_SNORM);
+impl_attrib_array_type!(Normalized<[i8; 4]>, Float, R8G8B8A8_SNORM);
+
 // Index data types --------------------------------------------------------------------------------
 macro_rules! impl_index_data {
     ($t:ty, $fmt:ident) => {
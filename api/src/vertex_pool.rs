@@ -0,0 +1,313 @@
+//! Long-lived vertex buffers shared by many meshes.
+//!
+//! Systems that regenerate geometry often (terrain chunks, UI, decals) would otherwise create
+//! thousands of small vertex buffers, each with its own allocation. A [VertexPool] creates one
+//! large buffer, and sub-allocates ranges of vertices from it with a free list:
+//! ```ignore
+//! let mut pool = VertexPool::<_, TerrainVertex>::new(&persistent_arena, 1 << 20);
+//! let chunk = pool.insert(&chunk_vertices).expect("terrain vertex pool is full");
+//!
+//! // every frame
+//! let args = TerrainArguments { vertices: pool.slice(chunk), .. };
+//! cmdbuf.draw(0, &frame_arena, pipeline, args, chunk.draw_params());
+//!
+//! // when the chunk is unloaded
+//! pool.free(chunk);
+//! ```
+//!
+//! A [VertexPoolSlice] can be bound with `#[argument(vertex_buffer)]`: its vertices start at
+//! vertex 0. Alternatively, the whole buffer of the pool can be bound once, and ranges drawn with
+//! `first_vertex: range.start`.
+//!
+//! Ranges are allocated with a first-fit strategy, and freed ranges are merged with the adjacent
+//! free ranges. Allocations are never moved, so the free space can become fragmented into ranges
+//! that are too small for new allocations: [VertexPoolStats] measures the fragmentation, so that
+//! the application can decide when to rebuild the pool.
+//!
+//! Vertices are written with [Api::update_buffer](crate::Api::update_buffer): writes are seen by
+//! the commands submitted after them, so a range can be freed and reused while the previous
+//! frames still draw from it. Commands are only submitted by `submit_frame`, however: a write
+//! after a draw is recorded, but before the frame is submitted, is seen by the draw.
+use crate::{
+    buffer::Buffer,
+    command::DrawParams,
+    vertex::{VertexBufferInterface, VertexBufferView, VertexData},
+    Api, Arena, Backend,
+};
+use std::{mem, slice};
+
+/// A range of vertices allocated in a [VertexPool].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct VertexRange {
+    /// Index of the first vertex in the buffer of the pool.
+    pub start: u32,
+    /// Number of vertices.
+    pub count: u32,
+}
+
+impl VertexRange {
+    /// Returns the index after the last vertex of the range.
+    pub fn end(&self) -> u32 {
+        self.start + self.count
+    }
+
+    /// Returns the parameters of a draw of all the vertices of a [slice](VertexPool::slice) of
+    /// the range.
+    pub fn draw_params(&self) -> DrawParams {
+        DrawParams {
+            vertex_count: self.count,
+            instance_count: 1,
+            first_vertex: 0,
+            first_instance: 0,
+        }
+    }
+}
+
+/// Usage and fragmentation of the free space of a [VertexPool].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct VertexPoolStats {
+    /// Number of vertices of the buffer of the pool.
+    pub capacity: u32,
+    /// Number of allocated vertices.
+    pub allocated: u32,
+    /// Number of live allocations.
+    pub allocations: u32,
+    /// Number of disjoint free ranges.
+    pub free_ranges: u32,
+    /// Number of vertices of the largest free range: the largest allocation that can succeed.
+    pub largest_free_range: u32,
+    /// Number of allocations that failed even though there were enough free vertices in total,
+    /// since the creation of the pool.
+    pub fragmentation_failures: u32,
+}
+
+impl VertexPoolStats {
+    /// Number of free vertices.
+    pub fn free(&self) -> u32 {
+        self.capacity - self.allocated
+    }
+
+    /// Returns the fraction of the free vertices that are outside the largest free range, between
+    /// 0.0 (all the free space is contiguous) and 1.0.
+    pub fn fragmentation(&self) -> f32 {
+        let free = self.free();
+        if free == 0 {
+            0.0
+        } else {
+            1.0 - self.largest_free_range as f32 / free as f32
+        }
+    }
+}
+
+/// Vertices of a range of a [VertexPool], bound with `#[argument(vertex_buffer)]`.
+#[derive(derivative::Derivative)]
+#[derivative(Copy(bound = ""), Clone(bound = ""), Debug(bound = ""))]
+pub struct VertexPoolSlice<'a, B: Backend, V: VertexData> {
+    buffer: Buffer<'a, B, [V]>,
+    range: VertexRange,
+}
+
+impl<'a, B: Backend, V: VertexData> VertexPoolSlice<'a, B, V> {
+    /// Returns the buffer of the pool.
+    pub fn buffer(&self) -> Buffer<'a, B, [V]> {
+        self.buffer
+    }
+
+    /// Returns the range of vertices of the slice.
+    pub fn range(&self) -> VertexRange {
+        self.range
+    }
+}
+
+impl<'a, B: Backend, V: VertexData> From<VertexPoolSlice<'a, B, V>> for VertexBufferView<'a, B> {
+    fn from(slice: VertexPoolSlice<'a, B, V>) -> Self {
+        VertexBufferView {
+            buffer: slice.buffer.0,
+            stride: V::LAYOUT.stride,
+            offset: slice.range.start as usize * mem::size_of::<V>(),
+        }
+    }
+}
+
+impl<'a, B: Backend, V: VertexData> VertexBufferInterface<'a, B> for VertexPoolSlice<'a, B, V> {
+    type Vertex = V;
+}
+
+/// A vertex buffer whose ranges are allocated and freed independently.
+/// See the [module-level documentation](self).
+pub struct VertexPool<'a, B: Backend, V: VertexData> {
+    api: &'a Api<B>,
+    buffer: Buffer<'a, B, [V]>,
+    capacity: u32,
+    /// Free ranges, sorted by start index, never adjacent.
+    free: Vec<VertexRange>,
+    allocated: u32,
+    allocations: u32,
+    fragmentation_failures: u32,
+}
+
+impl<'a, B: Backend, V: VertexData + Copy> VertexPool<'a, B, V> {
+    /// Creates a pool of `capacity` vertices, with a buffer allocated in `arena`.
+    ///
+    /// The pool is meant to live as long as the arena: its ranges stay valid until the arena is
+    /// dropped.
+    pub fn new(arena: &'a Arena<B>, capacity: u32) -> VertexPool<'a, B, V> {
+        let size = capacity as u64 * mem::size_of::<V>() as u64;
+        let buffer = arena.create_buffer_typeless(size);
+        VertexPool {
+            api: arena.api(),
+            buffer: unsafe { Buffer::from_raw(buffer.0) },
+            capacity,
+            free: if capacity == 0 {
+                Vec::new()
+            } else {
+                vec![VertexRange {
+                    start: 0,
+                    count: capacity,
+                }]
+            },
+            allocated: 0,
+            allocations: 0,
+            fragmentation_failures: 0,
+        }
+    }
+
+    /// Returns the buffer of the pool.
+    pub fn buffer(&self) -> Buffer<'a, B, [V]> {
+        self.buffer
+    }
+
+    /// Returns the number of vertices of the buffer of the pool.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Allocates a range of `count` vertices, with unspecified contents.
+    ///
+    /// Returns `None` if no free range is large enough.
+    pub fn allocate(&mut self, count: u32) -> Option<VertexRange> {
+        if count == 0 {
+            return Some(VertexRange { start: 0, count: 0 });
+        }
+        let index = match self.free.iter().position(|r| r.count >= count) {
+            Some(index) => index,
+            None => {
+                if self.capacity - self.allocated >= count {
+                    self.fragmentation_failures += 1;
+                }
+                return None;
+            }
+        };
+        let start = self.free[index].start;
+        if self.free[index].count == count {
+            self.free.remove(index);
+        } else {
+            self.free[index].start += count;
+            self.free[index].count -= count;
+        }
+        self.allocated += count;
+        self.allocations += 1;
+        Some(VertexRange { start, count })
+    }
+
+    /// Allocates a range for `vertices`, and writes them into it.
+    ///
+    /// Returns `None` if no free range is large enough.
+    pub fn insert(&mut self, vertices: &[V]) -> Option<VertexRange> {
+        let range = self.allocate(vertices.len() as u32)?;
+        self.write(range, 0, vertices);
+        Some(range)
+    }
+
+    /// Writes `vertices` in `range`, starting at the vertex `offset` of the range.
+    ///
+    /// Panics if the vertices do not fit in the range.
+    pub fn write(&self, range: VertexRange, offset: u32, vertices: &[V]) {
+        assert!(
+            offset as usize + vertices.len() <= range.count as usize,
+            "{} vertices written at offset {} of a range of {} vertices",
+            vertices.len(),
+            offset,
+            range.count
+        );
+        let size = mem::size_of_val(vertices);
+        let bytes = unsafe { slice::from_raw_parts(vertices.as_ptr() as *const u8, size) };
+        let byte_offset = (range.start + offset) as usize * mem::size_of::<V>();
+        self.api.update_buffer(self.buffer, byte_offset, bytes);
+    }
+
+    /// Returns the vertices of `range`, to be bound with `#[argument(vertex_buffer)]`.
+    pub fn slice(&self, range: VertexRange) -> VertexPoolSlice<'a, B, V> {
+        assert!(range.end() <= self.capacity, "range outside of the pool");
+        VertexPoolSlice {
+            buffer: self.buffer,
+            range,
+        }
+    }
+
+    /// Returns a range to the pool.
+    ///
+    /// Panics if the range is outside of the pool or overlaps a free range (e.g. if it has
+    /// already been freed).
+    pub fn free(&mut self, range: VertexRange) {
+        if range.count == 0 {
+            return;
+        }
+        assert!(range.end() <= self.capacity, "range outside of the pool");
+        // first free range after the freed one
+        let index = self
+            .free
+            .iter()
+            .position(|r| r.start >= range.start)
+            .unwrap_or(self.free.len());
+        let overlaps_prev = index > 0 && self.free[index - 1].end() > range.start;
+        let overlaps_next = index < self.free.len() && self.free[index].start < range.end();
+        assert!(
+            !overlaps_prev && !overlaps_next,
+            "vertex range {:?} is already free",
+            range
+        );
+
+        let merge_prev = index > 0 && self.free[index - 1].end() == range.start;
+        let merge_next = index < self.free.len() && self.free[index].start == range.end();
+        match (merge_prev, merge_next) {
+            (true, true) => {
+                let next = self.free.remove(index);
+                self.free[index - 1].count += range.count + next.count;
+            }
+            (true, false) => self.free[index - 1].count += range.count,
+            (false, true) => {
+                self.free[index].start = range.start;
+                self.free[index].count += range.count;
+            }
+            (false, false) => self.free.insert(index, range),
+        }
+        self.allocated -= range.count;
+        self.allocations = self.allocations.saturating_sub(1);
+    }
+
+    /// Frees all the ranges of the pool.
+    pub fn clear(&mut self) {
+        self.free.clear();
+        if self.capacity != 0 {
+            self.free.push(VertexRange {
+                start: 0,
+                count: self.capacity,
+            });
+        }
+        self.allocated = 0;
+        self.allocations = 0;
+    }
+
+    /// Returns statistics about the usage of the pool.
+    pub fn stats(&self) -> VertexPoolStats {
+        VertexPoolStats {
+            capacity: self.capacity,
+            allocated: self.allocated,
+            allocations: self.allocations,
+            free_ranges: self.free.len() as u32,
+            largest_free_range: self.free.iter().map(|r| r.count).max().unwrap_or(0),
+            fragmentation_failures: self.fragmentation_failures,
+        }
+    }
+}
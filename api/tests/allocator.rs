@@ -0,0 +1,53 @@
+use autograph_api::{
+    allocator::{AllocationKind, AllocationRequest, DEFAULT_ARENA_BLOCK_SIZE},
+    error::Error,
+    ArenaAllocator, ArenaOptions,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Allocator with a fixed budget.
+#[derive(Debug)]
+struct Budget(AtomicUsize);
+
+impl ArenaAllocator for Budget {
+    fn allocate(&self, request: &AllocationRequest) -> Result<(), Error> {
+        let size = request.size as usize;
+        let remaining = self.0.load(Ordering::SeqCst);
+        if size > remaining {
+            return Err(Error::OutOfMemory);
+        }
+        self.0.store(remaining - size, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn free(&self, request: &AllocationRequest) {
+        self.0.fetch_add(request.size as usize, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_default_options() {
+    let options = ArenaOptions::default();
+    assert_eq!(options.block_size, DEFAULT_ARENA_BLOCK_SIZE);
+    let request = AllocationRequest {
+        kind: AllocationKind::Buffer,
+        size: 1 << 40,
+    };
+    assert!(options.allocator.allocate(&request).is_ok());
+}
+
+#[test]
+fn test_custom_allocator() {
+    let options = ArenaOptions {
+        allocator: Box::new(Budget(AtomicUsize::new(1024))),
+        ..ArenaOptions::default()
+    };
+    let request = AllocationRequest {
+        kind: AllocationKind::Buffer,
+        size: 768,
+    };
+    assert!(options.allocator.allocate(&request).is_ok());
+    assert!(options.allocator.allocate(&request).is_err());
+    options.allocator.free(&request);
+    assert!(options.allocator.allocate(&request).is_ok());
+}
@@ -0,0 +1,126 @@
+use autograph_api::{
+    pipeline::{
+        validate::{validate_color_blend_state, validate_integer_output_blending, ValidationError},
+        ColorBlendAttachmentState, ColorBlendAttachments, ColorBlendState,
+        FragmentOutputAttributeDescription, FragmentOutputDescription, ShaderStageFlags,
+        ShaderStageReflection, SignatureDescription,
+    },
+    typedesc::{PrimitiveType, TypeDesc},
+};
+
+const RENDER_TARGETS: SignatureDescription<'static> = SignatureDescription {
+    fragment_outputs: &[
+        FragmentOutputDescription::DEFAULT,
+        FragmentOutputDescription {
+            blend: Some(ColorBlendAttachmentState::ADDITIVE),
+            ..FragmentOutputDescription::DEFAULT
+        },
+    ],
+    is_root_fragment_output_signature: true,
+    ..SignatureDescription::EMPTY
+};
+
+const ARGUMENTS: SignatureDescription<'static> = SignatureDescription {
+    inherited: &[&RENDER_TARGETS],
+    fragment_outputs: &[FragmentOutputDescription::DEFAULT],
+    ..SignatureDescription::EMPTY
+};
+
+#[test]
+fn test_collect_fragment_outputs() {
+    let mut outputs = Vec::new();
+    ARGUMENTS.collect_fragment_outputs(&mut outputs);
+    assert_eq!(
+        outputs,
+        &[
+            FragmentOutputDescription::DEFAULT,
+            FragmentOutputDescription {
+                blend: Some(ColorBlendAttachmentState::ADDITIVE),
+                ..FragmentOutputDescription::DEFAULT
+            },
+            FragmentOutputDescription::DEFAULT,
+        ]
+    );
+}
+
+#[test]
+fn test_blend_attachment_count() {
+    let mut errors = Vec::new();
+    validate_color_blend_state(&ARGUMENTS, &ColorBlendState::ALPHA_BLENDING, &mut errors);
+    assert!(errors.is_empty());
+
+    let separate = ColorBlendState {
+        attachments: ColorBlendAttachments::Separate(&[
+            ColorBlendAttachmentState::DISABLED,
+            ColorBlendAttachmentState::ALPHA_BLENDING,
+        ]),
+        ..ColorBlendState::DISABLED
+    };
+    validate_color_blend_state(&ARGUMENTS, &separate, &mut errors);
+    match errors.as_slice() {
+        [ValidationError::BlendAttachmentCountMismatch {
+            num_blend_attachments: 2,
+            num_fragment_outputs: 3,
+        }] => {}
+        _ => panic!("expected a blend attachment count mismatch"),
+    }
+}
+
+#[test]
+fn test_integer_output_blending_validation() {
+    const UINT: TypeDesc<'static> = TypeDesc::Primitive(PrimitiveType::UnsignedInt);
+    const VEC4: TypeDesc<'static> = TypeDesc::Vector {
+        elem_ty: PrimitiveType::Float,
+        len: 4,
+    };
+    let reflection = ShaderStageReflection {
+        stage: ShaderStageFlags::FRAGMENT,
+        descriptors: &[],
+        vertex_input_attributes: &[],
+        fragment_outputs: &[FragmentOutputDescription::DEFAULT; 3],
+        fragment_output_attributes: &[
+            FragmentOutputAttributeDescription {
+                location: 0,
+                ty: &VEC4,
+                name: Some("color"),
+            },
+            FragmentOutputAttributeDescription {
+                location: 1,
+                ty: &UINT,
+                name: Some("objectId"),
+            },
+            FragmentOutputAttributeDescription {
+                location: 2,
+                ty: &UINT,
+                name: Some("materialId"),
+            },
+        ],
+        stage_inputs: &[],
+        stage_outputs: &[],
+    };
+
+    let mut errors = Vec::new();
+    validate_integer_output_blending(
+        &ARGUMENTS,
+        &reflection,
+        &ColorBlendState::DISABLED,
+        &mut errors,
+    );
+    // the render target of `objectId` overrides the blend state of the pipeline
+    match errors.as_slice() {
+        [ValidationError::BlendingOnIntegerOutput {
+            location: 1,
+            shader_name: Some(name),
+        }] => assert_eq!(name, "objectId"),
+        _ => panic!("expected blending on an integer output"),
+    }
+
+    let mut errors = Vec::new();
+    validate_integer_output_blending(
+        &ARGUMENTS,
+        &reflection,
+        &ColorBlendState::ALPHA_BLENDING,
+        &mut errors,
+    );
+    assert_eq!(errors.len(), 2);
+}
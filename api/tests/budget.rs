@@ -0,0 +1,124 @@
+use autograph_api::{
+    budget::{Budget, BudgetUsage, BudgetWarning, UploadQueue},
+    format::Format,
+    null::{NullBackend, NullInstance},
+    Api,
+};
+
+#[test]
+fn test_frame_usage() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let texels = vec![0u8; 64 * 64 * 4];
+    let image = arena
+        .image_2d(Format::R8G8B8A8_UNORM, 64, 64)
+        .with_data(&texels);
+    arena.upload_slice(&[0u32; 16]);
+    api.update_image(image, (0, 0, 0), (16, 16, 1), &texels[..16 * 16 * 4]);
+    api.submit_frame(vec![]);
+    assert_eq!(
+        api.last_budget_usage(),
+        BudgetUsage {
+            upload_bytes: 64 * 64 * 4 + 64 + 16 * 16 * 4,
+            transient_bytes: 64 * 64 * 4 + 64,
+            deferred_updates: 0,
+            deferred_bytes: 0,
+        }
+    );
+    // no budget
+    assert!(api.last_budget_warnings().is_empty());
+
+    // counted per frame
+    api.submit_frame(vec![]);
+    assert_eq!(api.last_budget_usage(), BudgetUsage::default());
+}
+
+#[test]
+fn test_exceeded_budget() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    api.set_budget(Budget {
+        max_upload_bytes_per_frame: Some(1000),
+        max_transient_mb: Some(1),
+        ..Budget::default()
+    });
+    api.set_frame_reports(true);
+    let arena = api.create_arena();
+    arena.upload_slice(&[0u8; 2000]);
+    arena
+        .image_2d(Format::R32G32B32A32_SFLOAT, 512, 512)
+        .build();
+    api.submit_frame(vec![]);
+
+    let warnings = vec![
+        BudgetWarning::Uploads {
+            bytes: 2000,
+            budget: 1000,
+        },
+        BudgetWarning::TransientMemory {
+            bytes: 512 * 512 * 16 + 2000,
+            budget: 1 << 20,
+        },
+    ];
+    assert_eq!(api.last_budget_warnings(), warnings);
+    let report = api.last_frame_report().unwrap();
+    assert_eq!(report.budget_warnings, warnings);
+    assert!(report
+        .to_string()
+        .contains("warning: frame upload budget exceeded"));
+}
+
+#[test]
+fn test_deferred_uploads() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    api.set_budget(Budget {
+        max_upload_bytes_per_frame: Some(1000),
+        defer_uploads: true,
+        ..Budget::default()
+    });
+    let arena = api.create_arena();
+    let buffer = arena.create_buffer_typeless(4096);
+    let mut uploads = UploadQueue::new();
+    for i in 0..4 {
+        uploads.update_buffer(buffer, i * 600, &[0; 600]);
+    }
+    // larger than the budget, but the first update of the frame
+    uploads.update_buffer(buffer, 0, &[0; 2000]);
+    assert_eq!(uploads.pending_bytes(), 4400);
+
+    assert_eq!(api.flush_uploads(&mut uploads), 1);
+    api.submit_frame(vec![]);
+    let usage = api.last_budget_usage();
+    assert_eq!(usage.upload_bytes, 600);
+    assert_eq!((usage.deferred_updates, usage.deferred_bytes), (4, 3800));
+    assert!(api.last_budget_warnings().is_empty());
+
+    assert_eq!(api.flush_uploads(&mut uploads), 1);
+    api.submit_frame(vec![]);
+    assert_eq!(api.flush_uploads(&mut uploads), 1);
+    api.submit_frame(vec![]);
+    assert_eq!(api.flush_uploads(&mut uploads), 1);
+    api.submit_frame(vec![]);
+    assert_eq!(api.flush_uploads(&mut uploads), 1);
+    assert!(uploads.is_empty());
+    api.submit_frame(vec![]);
+    assert_eq!(api.last_budget_usage().upload_bytes, 2000);
+    assert_eq!(api.instance().object_counts().buffer_updates, 5);
+
+    // applied at once without deferral
+    api.set_budget(Budget {
+        max_upload_bytes_per_frame: Some(1000),
+        ..Budget::default()
+    });
+    for i in 0..4 {
+        uploads.update_buffer(buffer, i * 600, &[0; 600]);
+    }
+    assert_eq!(api.flush_uploads(&mut uploads), 4);
+    api.submit_frame(vec![]);
+    assert_eq!(
+        api.last_budget_warnings(),
+        vec![BudgetWarning::Uploads {
+            bytes: 2400,
+            budget: 1000,
+        }]
+    );
+}
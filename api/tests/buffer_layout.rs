@@ -0,0 +1,130 @@
+use autograph_api::{
+    buffer::StructuredBufferData,
+    command::DispatchIndirectCommand,
+    typedesc::{LayoutDetails, PrimitiveType, TypeDesc},
+};
+
+#[repr(C)]
+#[derive(StructuredBufferData, Copy, Clone)]
+struct Light {
+    position: [f32; 4],
+    color: [f32; 3],
+    intensity: f32,
+}
+
+#[repr(C)]
+#[derive(StructuredBufferData, Copy, Clone)]
+struct Lights {
+    lights: [Light; 8],
+    num_lights: i32,
+    _pad: [i32; 3],
+}
+
+#[repr(C)]
+#[derive(StructuredBufferData, Copy, Clone)]
+struct Material {
+    transform: [[f32; 4]; 4],
+    light: Light,
+}
+
+#[repr(C)]
+#[derive(StructuredBufferData, Copy, Clone)]
+struct Samples {
+    offsets: [[f32; 2]; 4],
+    basis: [[f32; 3]; 3],
+}
+
+#[test]
+fn test_nested_struct() {
+    assert_eq!(
+        <Material as StructuredBufferData>::TYPE,
+        TypeDesc::Struct {
+            fields: &[
+                &TypeDesc::Matrix {
+                    elem_ty: PrimitiveType::Float,
+                    rows: 4,
+                    columns: 4
+                },
+                &<Light as StructuredBufferData>::TYPE,
+            ]
+        }
+    );
+}
+
+#[test]
+fn test_array_of_structs() {
+    assert_eq!(
+        <Lights as StructuredBufferData>::TYPE,
+        TypeDesc::Struct {
+            fields: &[
+                &TypeDesc::Array {
+                    elem_ty: &<Light as StructuredBufferData>::TYPE,
+                    len: 8
+                },
+                &TypeDesc::Primitive(PrimitiveType::Int),
+            ]
+        }
+    );
+
+    match <Lights as StructuredBufferData>::LAYOUT.details {
+        LayoutDetails::Struct(fields) => {
+            assert_eq!(fields.offsets, &[0, 256]);
+            match fields.layouts[0].details {
+                LayoutDetails::Array(a) => assert_eq!(a.stride, 32),
+                _ => panic!("expected an array layout"),
+            }
+        }
+        _ => panic!("expected a struct layout"),
+    }
+}
+
+#[test]
+fn test_array_of_vectors() {
+    let vec = |len| TypeDesc::Vector {
+        elem_ty: PrimitiveType::Float,
+        len,
+    };
+    // `[[f32; 3]; 3]` is an array of vectors, not a matrix
+    assert_eq!(
+        <Samples as StructuredBufferData>::TYPE,
+        TypeDesc::Struct {
+            fields: &[
+                &TypeDesc::Array {
+                    elem_ty: &vec(2),
+                    len: 4
+                },
+                &TypeDesc::Array {
+                    elem_ty: &vec(3),
+                    len: 3
+                },
+            ]
+        }
+    );
+
+    // the strides are the ones of the rust arrays
+    match <Samples as StructuredBufferData>::LAYOUT.details {
+        LayoutDetails::Struct(fields) => {
+            assert_eq!(fields.offsets, &[0, 32]);
+            let strides: Vec<_> = fields
+                .layouts
+                .iter()
+                .map(|l| match l.details {
+                    LayoutDetails::Array(a) => a.stride,
+                    _ => panic!("expected an array layout"),
+                })
+                .collect();
+            assert_eq!(strides, [8, 12]);
+        }
+        _ => panic!("expected a struct layout"),
+    }
+}
+
+#[test]
+fn test_dispatch_indirect_layout() {
+    let layout = <DispatchIndirectCommand as StructuredBufferData>::LAYOUT;
+    assert_eq!(layout.size, std::mem::size_of::<DispatchIndirectCommand>());
+    match <[u32] as StructuredBufferData>::LAYOUT.details {
+        LayoutDetails::Array(a) => assert_eq!(a.stride, 4),
+        _ => panic!("expected an array layout"),
+    }
+}
@@ -0,0 +1,53 @@
+use autograph_api::{
+    format::Format,
+    image::RenderTargetView,
+    null::{NullBackend, NullInstance},
+    pipeline::{Arguments, TypedCachedArgumentBlock, Viewport},
+    Api, Backend,
+};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Arguments)]
+struct ColorPass<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+}
+
+#[test]
+fn test_cached_argument_block() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let a = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let b = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let cache: TypedCachedArgumentBlock<NullBackend, ColorPass<NullBackend>> =
+        TypedCachedArgumentBlock::typed(&arena);
+    let blocks = || api.instance().object_counts().argument_blocks;
+
+    let args = ColorPass {
+        color: a.into(),
+        viewport: Viewport::full(a),
+    };
+    let block = cache.get(args);
+    assert_eq!(blocks(), 1);
+
+    // same arguments
+    assert_eq!(cache.get(args), block);
+    assert_eq!(blocks(), 1);
+
+    // different image, same viewport
+    let other = cache.get(ColorPass {
+        color: b.into(),
+        ..args
+    });
+    assert_ne!(other, block);
+    assert_eq!(blocks(), 2);
+
+    // back to the first arguments: only the last block is kept
+    assert_ne!(cache.get(args), block);
+    assert_eq!(blocks(), 3);
+
+    cache.invalidate();
+    cache.get(args);
+    assert_eq!(blocks(), 4);
+}
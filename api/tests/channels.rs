@@ -0,0 +1,75 @@
+use autograph_api::{
+    format::{
+        expand_components, ChannelData, ChannelType, ColorSpace, Format, FormatFeatureFlags,
+        FormatProperties,
+    },
+    image::ImageUsageFlags,
+};
+
+#[test]
+fn test_format_from_channels() {
+    assert_eq!(
+        Format::from_channels(ChannelType::U16, 3),
+        Some(Format::R16G16B16_UNORM)
+    );
+    assert_eq!(
+        Format::from_channels(f32::CHANNEL_TYPE, 1),
+        Some(Format::R32_SFLOAT)
+    );
+    assert_eq!(
+        Format::from_channels(u8::CHANNEL_TYPE, 4),
+        Some(Format::R8G8B8A8_UNORM)
+    );
+    assert_eq!(Format::from_channels(ChannelType::Half, 0), None);
+    assert_eq!(Format::from_channels(ChannelType::Float, 5), None);
+}
+
+#[test]
+fn test_srgb_formats() {
+    assert_eq!(Format::R8G8B8A8_SRGB.color_space(), ColorSpace::Srgb);
+    assert_eq!(Format::R8G8B8A8_UNORM.color_space(), ColorSpace::Linear);
+    assert_eq!(
+        Format::R8G8B8A8_UNORM.to_srgb(),
+        Some(Format::R8G8B8A8_SRGB)
+    );
+    assert_eq!(
+        Format::BC7_SRGB_BLOCK.to_srgb(),
+        Some(Format::BC7_SRGB_BLOCK)
+    );
+    assert_eq!(Format::R32G32B32A32_SFLOAT.to_srgb(), None);
+    assert_eq!(Format::B8G8R8A8_SRGB.to_linear(), Format::B8G8R8A8_UNORM);
+    assert_eq!(Format::R16_UNORM.to_linear(), Format::R16_UNORM);
+}
+
+#[test]
+fn test_expand_components() {
+    assert_eq!(
+        Format::R16G16B16_UNORM.fallback_formats(),
+        &[Format::R16G16B16A16_UNORM]
+    );
+    let rgb = [1u8, 2, 3, 4, 5, 6];
+    assert_eq!(
+        expand_components(Format::R8G8B8_UNORM, Format::R8G8B8A8_UNORM, &rgb),
+        vec![1, 2, 3, 255, 4, 5, 6, 255]
+    );
+    let rgb16 = [1u8, 0, 2, 0, 3, 0];
+    assert_eq!(
+        expand_components(
+            Format::R16G16B16_SFLOAT,
+            Format::R16G16B16A16_SFLOAT,
+            &rgb16
+        ),
+        vec![1, 0, 2, 0, 3, 0, 0x00, 0x3C]
+    );
+}
+
+#[test]
+fn test_format_properties_supports() {
+    let properties = FormatProperties {
+        features: FormatFeatureFlags::SAMPLED_IMAGE | FormatFeatureFlags::COLOR_ATTACHMENT,
+        emulated: false,
+    };
+    assert!(properties.supports(ImageUsageFlags::SAMPLED));
+    assert!(properties.supports(ImageUsageFlags::SAMPLED | ImageUsageFlags::COLOR_ATTACHMENT));
+    assert!(!properties.supports(ImageUsageFlags::SAMPLED | ImageUsageFlags::STORAGE));
+}
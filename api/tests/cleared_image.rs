@@ -0,0 +1,76 @@
+use autograph_api::{
+    command::{ClearColor, ClearValue},
+    format::Format,
+    image::{Dimensions, Image2d, ImageUsageFlags, MipmapsOption},
+    null::{NullBackend, NullInstance},
+    AliasScope, Api,
+};
+
+fn scope(value: u64) -> AliasScope {
+    AliasScope {
+        value,
+        mask: 0xFF00,
+    }
+}
+
+#[test]
+fn test_cleared_image_is_cleared_in_its_scope() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let clear_value = ClearValue::Color(ClearColor::Float([0.0, 0.0, 0.0, 1.0]));
+    let image = arena.create_image_cleared(
+        scope(0x0100),
+        Format::R16G16B16A16_SFLOAT,
+        Dimensions::Dim2d {
+            width: 64,
+            height: 64,
+            array_layers: 1,
+        },
+        MipmapsOption::NoMipmap,
+        1,
+        ImageUsageFlags::COLOR_ATTACHMENT | ImageUsageFlags::SAMPLED,
+        clear_value,
+    );
+    assert_eq!(
+        api.instance().live_images()[0].clear_value,
+        Some(clear_value)
+    );
+    let image = unsafe { Image2d::from_raw(image.inner()) };
+    let other = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+
+    // a command inside the scope
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0x0000, other, &[0.0; 4]);
+    cmdbuf.clear_render_target(0x0142, image, &[1.0; 4]);
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(api.instance().last_frame().initial_clears, 1);
+
+    // no command inside the scope: the clear is skipped
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0x0200, other, &[0.0; 4]);
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(api.instance().last_frame().initial_clears, 0);
+}
+
+#[test]
+#[should_panic(expected = "with a depth value")]
+fn test_cleared_image_clear_value_is_validated() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    arena.create_image_cleared(
+        scope(0x0100),
+        Format::R8G8B8A8_UNORM,
+        Dimensions::Dim2d {
+            width: 64,
+            height: 64,
+            array_layers: 1,
+        },
+        MipmapsOption::NoMipmap,
+        1,
+        ImageUsageFlags::COLOR_ATTACHMENT,
+        ClearValue::DepthStencil {
+            depth: 1.0,
+            stencil: None,
+        },
+    );
+}
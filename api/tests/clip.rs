@@ -0,0 +1,30 @@
+use autograph_api::clip::{ClipSpaceInfo, DepthRange, YDirection};
+
+const VULKAN: ClipSpaceInfo = ClipSpaceInfo {
+    depth_range: DepthRange::ZeroToOne,
+    y_direction: YDirection::Down,
+};
+
+#[test]
+fn test_clip_space_top() {
+    assert_eq!(VULKAN.top(), -1.0);
+    let gl = ClipSpaceInfo {
+        depth_range: DepthRange::NegativeOneToOne,
+        y_direction: YDirection::Up,
+    };
+    assert_eq!(gl.top(), 1.0);
+}
+
+#[cfg(feature = "glm")]
+#[test]
+fn test_clip_space_correction() {
+    use autograph_api::glm;
+
+    let m = VULKAN.correction();
+    // near plane, top of the image in OpenGL conventions
+    let p = m * glm::vec4(0.0, 1.0, -1.0, 1.0);
+    assert_eq!(p, glm::vec4(0.0, -1.0, 0.0, 1.0));
+    // far plane, bottom of the image
+    let p = m * glm::vec4(0.0, -1.0, 1.0, 1.0);
+    assert_eq!(p, glm::vec4(0.0, 1.0, 1.0, 1.0));
+}
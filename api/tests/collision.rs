@@ -0,0 +1,142 @@
+use autograph_api::{
+    call_site,
+    command::{DrawParams, SubmitOptions, TieBreak},
+    format::Format,
+    image::RenderTargetView,
+    null::{NullBackend, NullInstance},
+    pipeline::{
+        Arguments, ColorBlendState, DepthStencilState, GraphicsPipelineCreateInfo,
+        InputAssemblyState, MultisampleState, RasterisationState, ViewCount, Viewport,
+        ViewportState,
+    },
+    Api, Backend,
+};
+
+mod common;
+use crate::common::{FRAGMENT, VERTEX};
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct ColorPass<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+}
+
+#[test]
+fn test_clear_collisions() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    api.set_sort_key_collision_checks(true);
+    let arena = api.create_arena();
+    let a = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let b = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+
+    let mut first = api.create_command_buffer();
+    first.clear_render_target(0, a, &[0.0; 4]);
+    first.clear_render_target(1, b, &[0.0; 4]);
+    let mut second = api.create_command_buffer();
+    second.set_call_site(call_site!());
+    second.clear_render_target(0, a, &[1.0; 4]);
+    second.clear_render_target(2, b, &[1.0; 4]);
+    api.submit_frame(vec![first, second]);
+
+    // only the clears of `a` have the same sortkey
+    let collisions = api.last_sort_key_collisions();
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].sortkey, 0);
+    assert_eq!(collisions[0].first.command_buffer, 0);
+    assert_eq!(collisions[0].first.call_site, None);
+    assert_eq!(collisions[0].second.command_buffer, 1);
+    assert_eq!(
+        collisions[0].second.call_site.map(|site| site.file),
+        Some(file!())
+    );
+
+    // same sortkey, different images
+    let mut first = api.create_command_buffer();
+    first.clear_render_target(0, a, &[0.0; 4]);
+    let mut second = api.create_command_buffer();
+    second.clear_render_target(0, b, &[0.0; 4]);
+    api.submit_frame(vec![first, second]);
+    assert!(api.last_sort_key_collisions().is_empty());
+}
+
+#[test]
+fn test_same_command_buffer_collisions() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    api.set_sort_key_collision_checks(true);
+    let arena = api.create_arena();
+    let image = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+
+    let record = || {
+        let mut cmdbuf = api.create_command_buffer();
+        cmdbuf.clear_render_target(0, image, &[0.0; 4]);
+        cmdbuf.clear_render_target(0, image, &[1.0; 4]);
+        cmdbuf
+    };
+
+    // ordered by insertion with a stable sort
+    api.submit_frame(vec![record()]);
+    assert!(api.last_sort_key_collisions().is_empty());
+
+    // can be reordered by an unstable sort without tie break...
+    let unstable = SubmitOptions {
+        stable_sort: false,
+        tie_break: TieBreak::None,
+    };
+    api.submit_frame_with_options(vec![record()], &unstable);
+    assert_eq!(api.last_sort_key_collisions().len(), 1);
+
+    // ...but not with one
+    let tie_break = SubmitOptions {
+        stable_sort: false,
+        tie_break: TieBreak::CommandBuffer,
+    };
+    api.submit_frame_with_options(vec![record()], &tie_break);
+    assert!(api.last_sort_key_collisions().is_empty());
+}
+
+#[test]
+fn test_draw_collisions() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    api.set_sort_key_collision_checks(true);
+    let arena = api.create_arena();
+    let a = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let b = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let pipeline =
+        arena.create_graphics_pipeline::<ColorPass<NullBackend>>(&GraphicsPipelineCreateInfo {
+            shader_stages: arena.create_vertex_fragment_shader_stages(VERTEX, FRAGMENT),
+            viewport_state: ViewportState::default(),
+            rasterization_state: RasterisationState::default(),
+            multisample_state: MultisampleState::default(),
+            depth_stencil_state: DepthStencilState::default(),
+            input_assembly_state: InputAssemblyState::default(),
+            color_blend_state: ColorBlendState::DISABLED,
+            view_count: ViewCount::default(),
+        });
+    let pass_a = arena.create_typed_argument_block(ColorPass {
+        color: a.into(),
+        viewport: (64, 64).into(),
+    });
+    let pass_b = arena.create_typed_argument_block(ColorPass {
+        color: b.into(),
+        viewport: (64, 64).into(),
+    });
+
+    let mut first = api.create_command_buffer();
+    first.draw(0, &arena, pipeline, pass_a, DrawParams::quad());
+    first.draw(1, &arena, pipeline, pass_b, DrawParams::quad());
+    let mut second = api.create_command_buffer();
+    second.draw(0, &arena, pipeline, pass_a, DrawParams::quad());
+    second.draw(1, &arena, pipeline, pass_a, DrawParams::quad());
+    api.submit_frame(vec![first, second]);
+
+    // the draws to `a` with sortkey 0 collide, the draws with sortkey 1 target different images
+    let collisions = api.last_sort_key_collisions();
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].sortkey, 0);
+
+    // disabling the checks forgets the collisions
+    api.set_sort_key_collision_checks(false);
+    assert!(api.last_sort_key_collisions().is_empty());
+}
@@ -0,0 +1,72 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+use autograph_api::{
+    descriptor::{ResourceBinding, ResourceBindingType},
+    format::Format,
+    pipeline::{
+        Arguments, FragmentOutputDescription, ReflectedShader, ShaderStageFlags,
+        ShaderStageReflection, SignatureDescription,
+    },
+    Backend,
+};
+
+/// Reflection data of a shader stage with no interface other than its fragment outputs.
+pub const fn reflection(
+    stage: ShaderStageFlags,
+    fragment_outputs: &'static [FragmentOutputDescription],
+) -> ShaderStageReflection<'static> {
+    ShaderStageReflection {
+        stage,
+        descriptors: &[],
+        vertex_input_attributes: &[],
+        fragment_outputs,
+        fragment_output_attributes: &[],
+        stage_inputs: &[],
+        stage_outputs: &[],
+    }
+}
+
+/// A vertex shader with no inputs.
+pub static VERTEX: ReflectedShader = ReflectedShader {
+    bytecode: &[],
+    reflection: &reflection(ShaderStageFlags::VERTEX, &[]),
+};
+
+/// A fragment shader writing to one color target.
+pub static FRAGMENT: ReflectedShader = ReflectedShader {
+    bytecode: &[],
+    reflection: &reflection(
+        ShaderStageFlags::FRAGMENT,
+        &[FragmentOutputDescription::DEFAULT],
+    ),
+};
+
+/// A compute shader with no interface.
+pub static COMPUTE: ReflectedShader = ReflectedShader {
+    bytecode: &[],
+    reflection: &reflection(ShaderStageFlags::COMPUTE, &[]),
+};
+
+/// A named resource binding visible to all graphics stages.
+pub const fn binding(
+    index: u32,
+    ty: ResourceBindingType,
+    name: &'static str,
+) -> ResourceBinding<'static> {
+    ResourceBinding {
+        set: None,
+        index,
+        ty,
+        stage_flags: ShaderStageFlags::ALL_GRAPHICS,
+        count: 1,
+        data_ty: None,
+        data_layout: None,
+        data_format: Format::UNDEFINED,
+        name: Some(name),
+    }
+}
+
+/// Returns the signature description of an arguments type.
+pub fn signature<'a, A: Arguments<'a, B>, B: Backend>() -> &'static SignatureDescription<'static> {
+    A::SIGNATURE
+}
@@ -0,0 +1,66 @@
+use autograph_api::{
+    image::DepthStencilView,
+    null::{NullBackend, NullInstance},
+    pipeline::{
+        validate::ValidationError, Arguments, ColorBlendState, DepthStencilState,
+        GraphicsPipelineCreateInfo, GraphicsShaderStages, InputAssemblyState, MultisampleState,
+        RasterisationState, ViewCount, Viewport, ViewportState,
+    },
+    Api, Backend,
+};
+
+mod common;
+use crate::common::{signature, FRAGMENT, VERTEX};
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct ShadowPass<'a, B: Backend> {
+    #[argument(depth_stencil_render_target)]
+    depth: DepthStencilView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+}
+
+fn create_info<'a, 're>(
+    shader_stages: GraphicsShaderStages<'a, 're, NullBackend>,
+) -> GraphicsPipelineCreateInfo<'a, 're, NullBackend> {
+    GraphicsPipelineCreateInfo {
+        shader_stages,
+        viewport_state: ViewportState::default(),
+        rasterization_state: RasterisationState::CULL_FRONT_CCW.with_depth_bias(1.0, 0.0, 2.0),
+        multisample_state: MultisampleState::default(),
+        depth_stencil_state: DepthStencilState::LESS_WRITE,
+        input_assembly_state: InputAssemblyState::default(),
+        color_blend_state: ColorBlendState::DISABLED,
+        view_count: ViewCount::default(),
+    }
+}
+
+#[test]
+fn test_depth_only_pipeline() {
+    // the signature has no color fragment outputs
+    let signature = signature::<ShadowPass<NullBackend>, _>();
+    assert!(signature.fragment_outputs.is_empty());
+    assert!(signature.depth_stencil_fragment_output.is_some());
+
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let stages = arena.create_vertex_shader_stages(VERTEX);
+    assert!(stages.fragment.is_none());
+    assert!(arena
+        .try_create_graphics_pipeline::<ShadowPass<NullBackend>>(&create_info(stages))
+        .is_ok());
+
+    // a fragment shader writing colors still needs render targets
+    let stages = arena.create_vertex_fragment_shader_stages(VERTEX, FRAGMENT);
+    let errors = arena
+        .try_create_graphics_pipeline::<ShadowPass<NullBackend>>(&create_info(stages))
+        .err()
+        .expect("expected validation errors");
+    match errors[..] {
+        [ValidationError::MissingRenderTarget {
+            num_shader_outputs: 1,
+            num_render_targets: 0,
+        }] => {}
+        _ => panic!("unexpected errors: {:?}", errors),
+    }
+}
@@ -0,0 +1,34 @@
+use autograph_api::pipeline::{CompareOp, DepthRange, DepthStencilState, Viewport};
+
+#[test]
+fn test_viewport_depth_range() {
+    let vp = Viewport::with_depth_range((640, 480), DepthRange::OneToZero);
+    assert_eq!(vp.min_depth.into_inner(), 1.0);
+    assert_eq!(vp.max_depth.into_inner(), 0.0);
+    assert_eq!(vp.width.into_inner(), 640.0);
+    assert_eq!(vp.depth_range(), DepthRange::OneToZero);
+    assert_eq!(
+        Viewport::from((640, 480)).depth_range(),
+        DepthRange::ZeroToOne
+    );
+}
+
+#[test]
+fn test_reversed_compare_op() {
+    let reverse_z = DepthRange::OneToZero;
+    assert_eq!(reverse_z.compare_op(CompareOp::Less), CompareOp::Greater);
+    assert_eq!(
+        reverse_z.compare_op(CompareOp::LessOrEqual),
+        CompareOp::GreaterOrEqual
+    );
+    assert_eq!(reverse_z.compare_op(CompareOp::Equal), CompareOp::Equal);
+    assert_eq!(
+        DepthRange::ZeroToOne.compare_op(CompareOp::Less),
+        CompareOp::Less
+    );
+    assert_eq!(reverse_z.far_depth(), 0.0);
+
+    let state = DepthStencilState::depth_test(reverse_z);
+    assert!(state.depth_test_enable && state.depth_write_enable);
+    assert_eq!(state.depth_compare_op, CompareOp::Greater);
+}
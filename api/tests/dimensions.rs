@@ -0,0 +1,66 @@
+use autograph_api::{
+    image::{Dimensions, MipmapsOption},
+    ApiProfile, DeviceLimits,
+};
+
+#[test]
+fn test_resolve_swapchain_relative() {
+    let half = Dimensions::swapchain_relative(0.5, 0.5);
+    assert!(half.is_swapchain_relative());
+    assert_eq!(
+        half.resolve((1280, 719)),
+        Dimensions::Dim2d {
+            width: 640,
+            height: 360,
+            array_layers: 1
+        }
+    );
+    // never resolves to an empty image
+    assert_eq!(half.resolve((0, 1)).width_height(), (1, 1));
+
+    let fixed: Dimensions = (256, 128).into();
+    assert!(!fixed.is_swapchain_relative());
+    assert_eq!(fixed.resolve((1280, 720)), fixed);
+}
+
+#[test]
+fn test_max_image_extent() {
+    let limits = DeviceLimits {
+        max_image_dimension_1d: 16384,
+        max_image_dimension_2d: 16384,
+        max_image_dimension_3d: 2048,
+        max_image_dimension_cube: 16384,
+        max_image_array_layers: 2048,
+        max_color_attachments: 8,
+        max_samples: 8,
+        max_sampler_anisotropy: 16.0,
+        max_viewports: 16,
+        max_uniform_buffer_range: 65536,
+        min_uniform_buffer_offset_alignment: 256,
+        max_compute_work_group_count: [65535; 3],
+        line_width_range: [1.0, 1.0],
+        point_size_range: [1.0, 64.0],
+        max_multiview_view_count: 16,
+        native_multiview: false,
+        profile: ApiProfile::Desktop,
+        spirv_shaders: true,
+    };
+    let volume = Dimensions::Dim3d {
+        width: 64,
+        height: 64,
+        depth: 64,
+    };
+    assert_eq!(limits.max_image_extent(&volume), 2048);
+    assert_eq!(
+        limits.max_image_extent(&Dimensions::swapchain_relative(1.0, 1.0)),
+        16384
+    );
+}
+
+#[test]
+fn test_mipmap_count_3d() {
+    // the depth of a volume counts towards the number of levels
+    assert_eq!(MipmapsOption::Allocate.count(16, 16, 64), 7);
+    assert_eq!(MipmapsOption::Generate.count(64, 16, 1), 7);
+    assert_eq!(MipmapsOption::NoMipmap.count(64, 64, 64), 1);
+}
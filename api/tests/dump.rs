@@ -0,0 +1,130 @@
+use autograph_api::{
+    descriptor::{ResourceBinding, ResourceBindingType},
+    format::Format,
+    pipeline::{
+        dump::{dump_shader_interface, dump_signature, type_name},
+        FragmentOutputDescription, ShaderStageFlags, ShaderStageReflection, SignatureDescription,
+        VertexInputAttributeDescription, VertexInputBinding,
+    },
+    typedesc::{PrimitiveType, TypeDesc},
+    vertex::{VertexInputRate, VertexLayout, VertexLayoutElement},
+};
+
+mod common;
+use crate::common::binding;
+
+const VEC3: TypeDesc<'static> = TypeDesc::Vector {
+    elem_ty: PrimitiveType::Float,
+    len: 3,
+};
+
+const GLOBALS: SignatureDescription<'static> = SignatureDescription {
+    descriptors: &[binding(0, ResourceBindingType::ConstantBuffer, "globals")],
+    fragment_outputs: &[FragmentOutputDescription::DEFAULT],
+    ..SignatureDescription::EMPTY
+};
+
+const MESH: SignatureDescription<'static> = SignatureDescription {
+    inherited: &[&GLOBALS],
+    descriptors: &[binding(0, ResourceBindingType::TexelBuffer, "bones")],
+    vertex_inputs: &[VertexInputBinding {
+        layout: VertexLayout {
+            elements: &[
+                VertexLayoutElement {
+                    semantic: None,
+                    format: Format::R32G32B32_SFLOAT,
+                    offset: 0,
+                    name: Some("position"),
+                },
+                VertexLayoutElement {
+                    semantic: None,
+                    format: Format::R32G32B32_SFLOAT,
+                    offset: 12,
+                    name: Some("normal"),
+                },
+            ],
+            stride: 24,
+        },
+        rate: VertexInputRate::Vertex,
+        base_location: None,
+    }],
+    ..SignatureDescription::EMPTY
+};
+
+#[test]
+fn test_type_names() {
+    assert_eq!(type_name(&VEC3), "vec3");
+    assert_eq!(
+        type_name(&TypeDesc::Matrix {
+            elem_ty: PrimitiveType::Float,
+            rows: 3,
+            columns: 4
+        }),
+        "mat4x3"
+    );
+    assert_eq!(
+        type_name(&TypeDesc::Struct {
+            fields: &[
+                &TypeDesc::Primitive(PrimitiveType::Float),
+                &TypeDesc::Array {
+                    elem_ty: &TypeDesc::Primitive(PrimitiveType::UnsignedInt),
+                    len: 0
+                }
+            ]
+        }),
+        "struct { float, uint[] }"
+    );
+}
+
+#[test]
+fn test_dump_shader_interface() {
+    let reflection = ShaderStageReflection {
+        stage: ShaderStageFlags::VERTEX,
+        descriptors: &[ResourceBinding {
+            set: Some(1),
+            data_ty: Some(&VEC3),
+            ..binding(0, ResourceBindingType::TexelBuffer, "boneMatrices")
+        }],
+        vertex_input_attributes: &[VertexInputAttributeDescription {
+            location: Some(1),
+            ty: &VEC3,
+            semantic: None,
+            name: Some("a_normal"),
+        }],
+        fragment_outputs: &[],
+        fragment_output_attributes: &[],
+        stage_inputs: &[],
+        stage_outputs: &[],
+    };
+    let dump = dump_shader_interface(&reflection);
+    let lines: Vec<_> = dump.lines().collect();
+    assert_eq!(
+        lines,
+        &[
+            "VERTEX shader",
+            "  descriptors:",
+            "    set 1 binding 0: TexelBuffer \"boneMatrices\" of vec3",
+            "  vertex inputs:",
+            "    location 1: vec3 \"a_normal\"",
+        ]
+    );
+}
+
+#[test]
+fn test_dump_signature() {
+    let dump = dump_signature(&MESH);
+    let lines: Vec<_> = dump.lines().collect();
+    assert_eq!(
+        lines,
+        &[
+            "block",
+            "  block",
+            "    set 0 binding 0: ConstantBuffer \"globals\"",
+            "    render target #0 (load Load, store Store)",
+            "  set 1 binding 0: TexelBuffer \"bones\"",
+            "  vertex buffer #0 (Vertex, stride 24)",
+            "    location 0: R32G32B32_SFLOAT at offset 0 \"position\"",
+            "    location 1: R32G32B32_SFLOAT at offset 12 \"normal\"",
+        ]
+    );
+}
@@ -0,0 +1,180 @@
+use autograph_api::{
+    command::DrawParams,
+    elision::ClearElisionStats,
+    format::Format,
+    image::RenderTargetView,
+    null::{NullBackend, NullInstance},
+    pipeline::{
+        Arguments, ColorBlendState, DepthStencilState, GraphicsPipelineCreateInfo,
+        InputAssemblyState, MultisampleState, RasterisationState, TypedGraphicsPipeline, ViewCount,
+        Viewport, ViewportState,
+    },
+    Api, Arena, Backend,
+};
+
+mod common;
+use crate::common::{FRAGMENT, VERTEX};
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct ColorPass<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+}
+
+fn create_pipeline<'a>(
+    arena: &'a Arena<NullBackend>,
+    color_blend_state: ColorBlendState<'static>,
+) -> TypedGraphicsPipeline<'a, NullBackend, ColorPass<'a, NullBackend>> {
+    arena.create_graphics_pipeline(&GraphicsPipelineCreateInfo {
+        shader_stages: arena.create_vertex_fragment_shader_stages(VERTEX, FRAGMENT),
+        viewport_state: ViewportState::default(),
+        rasterization_state: RasterisationState::default(),
+        multisample_state: MultisampleState::default(),
+        depth_stencil_state: DepthStencilState::default(),
+        input_assembly_state: InputAssemblyState::default(),
+        color_blend_state,
+        view_count: ViewCount::default(),
+    })
+}
+
+#[test]
+fn test_clear_before_fullscreen_draw() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    api.set_clear_elision(true);
+    let arena = api.create_arena();
+    let image = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let opaque = create_pipeline(&arena, ColorBlendState::DISABLED);
+    let blended = create_pipeline(&arena, ColorBlendState::ALPHA_BLENDING);
+    assert!(opaque.overwrites_render_targets());
+    assert!(!blended.overwrites_render_targets());
+
+    let args = ColorPass {
+        color: image.into(),
+        viewport: Viewport::full(image),
+    };
+
+    // overwritten by an opaque fullscreen draw
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, image, &[0.0; 4]);
+    cmdbuf.draw_fullscreen(1, &arena, opaque, args, DrawParams::quad());
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(
+        api.last_clear_elision_stats(),
+        ClearElisionStats {
+            render_target_clears: 1,
+            depth_stencil_clears: 0,
+        }
+    );
+
+    // blended with the cleared contents
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, image, &[0.0; 4]);
+    cmdbuf.draw_fullscreen(1, &arena, blended, args, DrawParams::quad());
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(api.last_clear_elision_stats().total(), 0);
+
+    // not declared as covering the render target
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, image, &[0.0; 4]);
+    cmdbuf.draw(1, &arena, opaque, args, DrawParams::quad());
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(api.last_clear_elision_stats().total(), 0);
+
+    // the draw in between may read the cleared image
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, image, &[0.0; 4]);
+    cmdbuf.draw(1, &arena, blended, args, DrawParams::quad());
+    cmdbuf.draw_fullscreen(2, &arena, opaque, args, DrawParams::quad());
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(api.last_clear_elision_stats().total(), 0);
+
+    // the order of execution is the order of the sort keys
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.draw_fullscreen(0, &arena, opaque, args, DrawParams::quad());
+    cmdbuf.clear_render_target(1, image, &[0.0; 4]);
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(api.last_clear_elision_stats().total(), 0);
+}
+
+#[test]
+fn test_consecutive_clears() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    api.set_clear_elision(true);
+    let arena = api.create_arena();
+    let color = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let other = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let depth = arena.image_2d(Format::D24_UNORM_S8_UINT, 64, 64).build();
+
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, color, &[0.0; 4]);
+    cmdbuf.clear_depth_stencil(0, depth.depth_stencil_layers_view(0, 1), 1.0, Some(0));
+    cmdbuf.clear_render_target(1, other, &[0.0; 4]);
+    cmdbuf.clear_render_target(2, color, &[1.0; 4]);
+    // the stencil of the first clear is kept
+    cmdbuf.clear_depth_stencil(3, depth.depth_stencil_layers_view(0, 1), 0.0, None);
+    cmdbuf.clear_depth_stencil(4, depth.depth_stencil_layers_view(0, 1), 1.0, Some(1));
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(
+        api.last_clear_elision_stats(),
+        ClearElisionStats {
+            render_target_clears: 1,
+            depth_stencil_clears: 1,
+        }
+    );
+
+    // a conditional clear may not be executed
+    let query = arena.create_occlusion_query();
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, color, &[0.0; 4]);
+    cmdbuf.clear_depth_stencil(0, depth.depth_stencil_layers_view(0, 1), 1.0, Some(0));
+    cmdbuf.begin_conditional(1, query);
+    cmdbuf.clear_render_target(2, color, &[1.0; 4]);
+    cmdbuf.clear_depth_stencil(2, depth.depth_stencil_layers_view(0, 1), 0.0, Some(1));
+    cmdbuf.end_conditional(3);
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(api.last_clear_elision_stats().total(), 0);
+
+    // but it is overwritten by an unconditional clear
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.begin_conditional(0, query);
+    cmdbuf.clear_render_target(1, color, &[0.0; 4]);
+    cmdbuf.end_conditional(2);
+    cmdbuf.clear_render_target(3, color, &[1.0; 4]);
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(
+        api.last_clear_elision_stats(),
+        ClearElisionStats {
+            render_target_clears: 1,
+            depth_stencil_clears: 0,
+        }
+    );
+
+    // disabled
+    api.set_clear_elision(false);
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, color, &[0.0; 4]);
+    cmdbuf.clear_render_target(1, color, &[1.0; 4]);
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(api.last_clear_elision_stats().total(), 0);
+}
+
+#[test]
+#[should_panic(expected = "block opened at sort key 0x1")]
+fn test_nested_conditional_blocks() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let query = arena.create_occlusion_query();
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.begin_conditional(1, query);
+    cmdbuf.begin_conditional(2, query);
+}
+
+#[test]
+#[should_panic(expected = "end_conditional at sort key 0x3 without a matching begin_conditional")]
+fn test_unmatched_end_conditional() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.end_conditional(3);
+}
@@ -0,0 +1,41 @@
+use autograph_api::{
+    descriptor::{ResourceBindingType, ResourceShape},
+    image::{FallbackTexture, TextureSampler2dView},
+    pipeline::Arguments,
+    Backend, DummyBackend,
+};
+
+mod common;
+use crate::common::signature;
+
+#[derive(Arguments)]
+struct MaterialTextures<'a, B: Backend> {
+    #[argument(descriptor)]
+    base: TextureSampler2dView<'a, B>,
+    #[argument(descriptor, fallback = "white")]
+    albedo: Option<TextureSampler2dView<'a, B>>,
+    #[argument(descriptor, fallback = "normal-up")]
+    normal_map: Option<TextureSampler2dView<'a, B>>,
+}
+
+#[test]
+fn test_optional_descriptor_signature() {
+    let sig = signature::<MaterialTextures<DummyBackend>, _>();
+    assert_eq!(sig.descriptors.len(), 3);
+    for (i, d) in sig.descriptors.iter().enumerate() {
+        assert_eq!(d.index, i as u32);
+        assert_eq!(
+            d.ty,
+            ResourceBindingType::TextureSampler(ResourceShape::R2d)
+        );
+    }
+    assert_eq!(sig.descriptors[1].name, Some("albedo"));
+    assert_eq!(sig.descriptors[2].name, Some("normal_map"));
+}
+
+#[test]
+fn test_fallback_texels() {
+    assert_eq!(FallbackTexture::White.texel(), [255, 255, 255, 255]);
+    assert_eq!(FallbackTexture::Black.texel(), [0, 0, 0, 255]);
+    assert_eq!(FallbackTexture::NormalUp.texel(), [128, 128, 255, 255]);
+}
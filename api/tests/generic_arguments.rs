@@ -0,0 +1,61 @@
+use autograph_api::{
+    buffer::{StructuredBufferData, TypedConstantBufferView},
+    image::RenderTargetView,
+    pipeline::{Arguments, TypedArgumentBlock, Viewport},
+    Backend, DummyBackend,
+};
+use std::any::TypeId;
+
+mod common;
+use crate::common::signature;
+
+#[derive(Arguments)]
+struct Targets<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+}
+
+#[derive(Arguments)]
+struct Material<'a, B: Backend, T: StructuredBufferData, A>
+where
+    A: Arguments<'a, B>,
+{
+    #[argument(inherit)]
+    common: TypedArgumentBlock<'a, B, A>,
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, T>,
+}
+
+fn unique_type_id<'a, A: Arguments<'a, DummyBackend>>() -> TypeId {
+    TypeId::of::<A::UniqueType>()
+}
+
+#[test]
+fn test_generic_arguments_signature() {
+    let sig = signature::<Material<DummyBackend, [f32; 4], Targets<DummyBackend>>, _>();
+    assert_eq!(sig.inherited.len(), 1);
+    assert_eq!(sig.inherited[0], signature::<Targets<DummyBackend>, _>());
+    assert_eq!(sig.descriptors.len(), 1);
+    assert_eq!(sig.descriptors[0].name, Some("params"));
+
+    let sig_f32 = signature::<Material<DummyBackend, f32, Targets<DummyBackend>>, _>();
+    assert_ne!(sig.descriptors[0].data_ty, sig_f32.descriptors[0].data_ty);
+}
+
+#[test]
+fn test_generic_arguments_unique_type() {
+    let a = unique_type_id::<Material<DummyBackend, [f32; 4], Targets<DummyBackend>>>();
+    let b = unique_type_id::<Material<DummyBackend, f32, Targets<DummyBackend>>>();
+    assert_ne!(a, b);
+    assert_eq!(
+        a,
+        unique_type_id::<Material<DummyBackend, [f32; 4], Targets<DummyBackend>>>()
+    );
+    // argument blocks have the same unique type as their arguments
+    assert_eq!(
+        unique_type_id::<Targets<DummyBackend>>(),
+        unique_type_id::<TypedArgumentBlock<DummyBackend, Targets<DummyBackend>>>()
+    );
+}
@@ -0,0 +1,44 @@
+use autograph_api::{
+    handle::HandleTable,
+    image::{Image2d, Image2dView},
+    DummyBackend,
+};
+
+static IMAGE_A: () = ();
+static IMAGE_B: () = ();
+
+fn image(raw: &'static ()) -> Image2d<'static, DummyBackend> {
+    unsafe { Image2d::from_raw(raw) }
+}
+
+fn is_image(view: Option<Image2dView<DummyBackend>>, raw: &'static ()) -> bool {
+    view.map_or(false, |view| view.inner() as *const () == raw as *const ())
+}
+
+#[test]
+fn test_handle_table() {
+    let mut table = HandleTable::new();
+    let a = table.insert(image(&IMAGE_A));
+    let b = table.insert(image(&IMAGE_B));
+    assert_eq!(table.len(), 2);
+    assert!(is_image(table.get(a), &IMAGE_A));
+    assert!(is_image(table.get(b), &IMAGE_B));
+
+    assert!(table.remove(a).is_some());
+    assert!(!table.contains(a));
+    assert!(table.remove(a).is_none());
+    assert_eq!(table.len(), 1);
+}
+
+#[test]
+fn test_stale_handle_after_reuse() {
+    let mut table = HandleTable::new();
+    let a = table.insert(image(&IMAGE_A));
+    table.remove(a);
+    // the slot is reused, but with a new generation
+    let b = table.insert(image(&IMAGE_B));
+    assert_eq!(a.index(), b.index());
+    assert_ne!(a.generation(), b.generation());
+    assert!(table.get(a).is_none());
+    assert!(is_image(table.get(b), &IMAGE_B));
+}
@@ -0,0 +1,89 @@
+use autograph_api::{
+    buffer::StructuredBufferData,
+    command::DrawParams,
+    descriptor::{Descriptor, HostReference, ResourceBindingType, ResourceInterface},
+    format::Format,
+    image::RenderTargetView,
+    null::{NullBackend, NullInstance},
+    pipeline::{
+        Arguments, ColorBlendState, DepthStencilState, GraphicsPipelineCreateInfo,
+        InputAssemblyState, MultisampleState, RasterisationState, ViewCount, Viewport,
+        ViewportState,
+    },
+    Api, Backend,
+};
+
+mod common;
+use crate::common::{signature, FRAGMENT, VERTEX};
+
+#[repr(C)]
+#[derive(StructuredBufferData, Copy, Clone)]
+struct Uniforms {
+    transform: [[f32; 4]; 4],
+    color: [f32; 4],
+}
+
+#[derive(Copy, Clone, Arguments)]
+struct Pass<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+    #[argument(descriptor)]
+    uniforms: HostReference<'a, B, Uniforms>,
+}
+
+#[test]
+fn test_host_reference_signature() {
+    let sig = signature::<Pass<NullBackend>, _>();
+    assert_eq!(sig.descriptors.len(), 1);
+    assert_eq!(sig.descriptors[0].ty, ResourceBindingType::ConstantBuffer);
+    assert_eq!(sig.descriptors[0].name, Some("uniforms"));
+}
+
+#[test]
+fn test_host_reference_descriptor() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let pipeline =
+        arena.create_graphics_pipeline::<Pass<NullBackend>>(&GraphicsPipelineCreateInfo {
+            shader_stages: arena.create_vertex_fragment_shader_stages(VERTEX, FRAGMENT),
+            viewport_state: ViewportState::default(),
+            rasterization_state: RasterisationState::default(),
+            multisample_state: MultisampleState::default(),
+            depth_stencil_state: DepthStencilState::default(),
+            input_assembly_state: InputAssemblyState::default(),
+            color_blend_state: ColorBlendState::DISABLED,
+            view_count: ViewCount::default(),
+        });
+
+    let uniforms = Uniforms {
+        transform: [[0.0; 4]; 4],
+        color: [1.0; 4],
+    };
+    let reference = arena.host_reference(&uniforms);
+    match reference.into_descriptor() {
+        Descriptor::HostConstantBuffer { reference } => assert_eq!(reference.size, 80),
+        d => panic!("unexpected descriptor: {:?}", d),
+    }
+
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.draw(
+        0,
+        &arena,
+        pipeline,
+        Pass {
+            color: image.into(),
+            viewport: Viewport::full(image),
+            uniforms: reference,
+        },
+        DrawParams::quad(),
+    );
+    api.submit_frame(vec![cmdbuf]);
+
+    let counts = api.instance().object_counts();
+    assert_eq!(counts.host_references, 1);
+    assert_eq!(counts.argument_blocks, 1);
+    assert_eq!(api.instance().last_frame().draws, 1);
+}
@@ -0,0 +1,67 @@
+use autograph_api::{
+    format::Format,
+    image::{Image2d, Image2dView, ImageUpdateBatch, MipmapsOption},
+    null::{NullBackend, NullInstance},
+    Api, DummyBackend,
+};
+
+static IMAGE: () = ();
+
+fn image() -> Image2d<'static, DummyBackend> {
+    unsafe { Image2d::from_raw(&IMAGE) }
+}
+
+#[test]
+fn test_update_batch_regions() {
+    let data = [0u8; 64];
+    let mut batch = ImageUpdateBatch::new(image());
+    assert!(batch.is_empty());
+    batch
+        .update((0, 0, 0), (4, 4, 1), &data)
+        .update_with_pitch((4, 2, 0), (6, 4, 1), 32, &data);
+
+    let regions = batch.regions();
+    assert_eq!(regions.len(), 2);
+    assert_eq!(regions[0].offset, (0, 0, 0));
+    assert_eq!(regions[0].size, (4, 4, 1));
+    assert_eq!(regions[0].row_pitch, 0);
+    assert_eq!(regions[1].offset, (4, 2, 0));
+    assert_eq!(regions[1].size, (2, 2, 1));
+    assert_eq!(regions[1].row_pitch, 32);
+    assert!(regions.iter().all(|r| r.mip_level == 0));
+}
+
+#[test]
+fn test_update_batch_mip_level() {
+    let data = [0u8; 16];
+    let mut batch = ImageUpdateBatch::new(Image2dView::from(image().mipmap(2)));
+    batch.update((0, 0, 0), (2, 2, 1), &data);
+    assert_eq!(batch.regions()[0].mip_level, 2);
+}
+
+#[test]
+#[should_panic(expected = "invalid image region")]
+fn test_update_batch_invalid_region() {
+    let data = [0u8; 16];
+    ImageUpdateBatch::new(image()).update((2, 0, 0), (1, 1, 1), &data);
+}
+
+#[test]
+#[should_panic(expected = "region out of the bounds of mip level 1 of the image")]
+fn test_update_image_out_of_bounds() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena
+        .image_2d(Format::R8G8B8A8_UNORM, 16, 16)
+        .mipmaps(MipmapsOption::AllocateCount(2))
+        .build();
+    let data = [0u8; 16 * 16 * 4];
+    api.update_image(image, (0, 0, 0), (16, 16, 1), &data);
+    // level 1 is 8x8
+    api.update_image(
+        Image2dView::from(image.mipmap(1)),
+        (0, 0, 0),
+        (16, 16, 1),
+        &data,
+    );
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "introspection")]
+use autograph_api::{
+    buffer::TypedConstantBufferView,
+    format::Format,
+    image::{Dimensions, RenderTargetView, SamplerDescription, TextureSampler2dView},
+    introspection::DescriptorInfo,
+    null::{NullBackend, NullInstance},
+    pipeline::{Arguments, BareArgumentBlock, TypedArgumentBlock, Viewport},
+    Api, Backend,
+};
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct Targets<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+}
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct Material<'a, B: Backend> {
+    #[argument(inherit)]
+    targets: TypedArgumentBlock<'a, B, Targets<'a, B>>,
+    #[argument(descriptor)]
+    albedo: TextureSampler2dView<'a, B>,
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, [f32; 4]>,
+}
+
+#[test]
+fn test_introspect_argument_block() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let color = arena
+        .image_2d(Format::R16G16B16A16_SFLOAT, 640, 480)
+        .build();
+    let albedo = arena.image_2d(Format::R8G8B8A8_UNORM, 256, 128).build();
+    let params = arena.upload_slice_views(&[[1.0f32; 4]]).next().unwrap();
+
+    let targets = arena.create_typed_argument_block(Targets {
+        color: color.into(),
+        viewport: (640, 480).into(),
+    });
+    let material = arena.create_typed_argument_block(Material {
+        targets,
+        albedo: albedo.sampled_linear(),
+        params,
+    });
+
+    let info = material.introspect(&api).unwrap();
+    assert_eq!(info.inherited.len(), 1);
+    assert_eq!(
+        Some(info.clone()),
+        BareArgumentBlock::from(material).introspect(&api)
+    );
+
+    let targets_info = &info.inherited[0];
+    assert_eq!(targets_info.viewports, vec![Viewport::from((640, 480))]);
+    let rt = targets_info.render_targets[0].image.unwrap();
+    assert_eq!(rt.format, Format::R16G16B16A16_SFLOAT);
+    assert_eq!(
+        rt.dimensions,
+        Dimensions::Dim2d {
+            width: 640,
+            height: 480,
+            array_layers: 1
+        }
+    );
+
+    assert_eq!(info.descriptors.len(), 2);
+    match info.descriptors[0] {
+        DescriptorInfo::Image {
+            image: Some(image),
+            sampler,
+            storage: false,
+            ..
+        } => {
+            assert_eq!(image.format, Format::R8G8B8A8_UNORM);
+            assert_eq!(sampler, Some(SamplerDescription::LINEAR_MIPMAP_LINEAR));
+        }
+        ref d => panic!("unexpected descriptor: {:?}", d),
+    }
+    match info.descriptors[1] {
+        DescriptorInfo::Buffer {
+            buffer: Some(buffer),
+            offset: 0,
+            size: Some(16),
+            storage: false,
+        } => assert!(buffer.size >= 16),
+        ref d => panic!("unexpected descriptor: {:?}", d),
+    }
+
+    let text = info.to_string();
+    assert!(text.contains("inherited block #0:"));
+    assert!(text.contains("  render target #0: R16G16B16A16_SFLOAT"));
+    assert!(text.contains("descriptor #1: constant buffer"));
+}
@@ -0,0 +1,105 @@
+use autograph_api::{
+    descriptor::{Descriptor, ResourceBindingType, ResourceInterface, ResourceShape},
+    format::Format,
+    image::{LodClamp, MipmapsOption, TextureSampler2dLodView},
+    null::{NullBackend, NullInstance},
+    pipeline::Arguments,
+    Api, Backend,
+};
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct Upsample<'a, B: Backend> {
+    #[argument(descriptor)]
+    src: TextureSampler2dLodView<'a, B>,
+}
+
+#[test]
+fn test_lod_view_descriptor() {
+    assert_eq!(
+        <TextureSampler2dLodView<NullBackend> as ResourceInterface<NullBackend>>::TYPE,
+        ResourceBindingType::TextureSampler(ResourceShape::R2d)
+    );
+
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena
+        .image_2d(Format::R16G16B16A16_SFLOAT, 256, 256)
+        .mipmaps(MipmapsOption::AllocateCount(4))
+        .build();
+    let view = image.sampled_linear().pin_lod(2);
+    assert_eq!(view.lod(), LodClamp::level(2));
+    match view.into_descriptor() {
+        Descriptor::TextureSamplerLod { lod, .. } => assert_eq!(
+            lod,
+            LodClamp {
+                min_lod: 2,
+                max_lod: 2
+            }
+        ),
+        _ => panic!("unexpected descriptor"),
+    }
+
+    // the last level of the image
+    arena.create_typed_argument_block(Upsample {
+        src: image.sampled_linear().lod_clamp(1, 3),
+    });
+}
+
+#[test]
+fn test_lod_clamp_relative_to_base_level() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena
+        .image_2d(Format::R16G16B16A16_SFLOAT, 256, 256)
+        .mipmaps(MipmapsOption::AllocateCount(4))
+        .build();
+    // samples only level 3
+    let view = image.mipmaps(3..).sampled_linear().pin_lod(0);
+    match view.into_descriptor() {
+        Descriptor::TextureSamplerLod {
+            subresource, lod, ..
+        } => {
+            assert_eq!(subresource.base_mip_level, 3);
+            assert_eq!(lod, LodClamp::level(0));
+        }
+        _ => panic!("unexpected descriptor"),
+    }
+    arena.create_typed_argument_block(Upsample { src: view });
+}
+
+#[test]
+#[should_panic(expected = "(from mip level 3) out of the bounds of an image with 4 levels")]
+fn test_lod_clamp_from_base_level_is_validated() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena
+        .image_2d(Format::R16G16B16A16_SFLOAT, 256, 256)
+        .mipmaps(MipmapsOption::AllocateCount(4))
+        .build();
+    arena.create_typed_argument_block(Upsample {
+        src: image.mipmaps(3..).sampled_linear().pin_lod(1),
+    });
+}
+
+#[test]
+#[should_panic(expected = "out of the bounds of an image with 4 levels")]
+fn test_lod_clamp_is_validated() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena
+        .image_2d(Format::R16G16B16A16_SFLOAT, 256, 256)
+        .mipmaps(MipmapsOption::AllocateCount(4))
+        .build();
+    arena.create_typed_argument_block(Upsample {
+        src: image.sampled_linear().pin_lod(4),
+    });
+}
+
+#[test]
+#[should_panic(expected = "invalid LOD clamp")]
+fn test_inverted_lod_clamp() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    image.sampled_linear().lod_clamp(2, 1);
+}
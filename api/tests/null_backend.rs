@@ -0,0 +1,220 @@
+use autograph_api::{
+    allocator::AllocationRequest,
+    command::CommandInner,
+    descriptor::{Descriptor, ResourceInterface},
+    error::Error,
+    format::Format,
+    image::{Dimensions, ImageUsageFlags, MipmapsOption},
+    null::{NullBackend, NullInstance},
+    AliasScope, Api, ArenaAllocator, ArenaOptions,
+};
+use std::{
+    slice,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+#[test]
+fn test_resources_are_tracked() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    {
+        let arena = api.create_arena();
+        let _color = arena.image_2d(Format::R8G8B8A8_UNORM, 640, 480).build();
+        let _half = arena.create_image(
+            AliasScope::no_alias(),
+            Format::R16G16B16A16_SFLOAT,
+            Dimensions::swapchain_relative(0.5, 0.5),
+            MipmapsOption::NoMipmap,
+            1,
+            ImageUsageFlags::COLOR_ATTACHMENT | ImageUsageFlags::SAMPLED,
+            None,
+        );
+        let _vertices = arena.upload_slice(&[0.0f32; 12]);
+
+        let images = api.instance().live_images();
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].format, Format::R8G8B8A8_UNORM);
+        assert_eq!(images[0].dimensions.width_height(), (640, 480));
+        // resolved with the size of the default swapchain
+        assert_eq!(images[1].dimensions.width_height(), (640, 360));
+        assert_eq!(api.instance().live_buffers()[0].size, 48);
+    }
+    // the images and buffers of the arena are released with it
+    assert!(api.instance().live_images().is_empty());
+    assert!(api.instance().live_buffers().is_empty());
+    assert_eq!(api.instance().object_counts().images, 2);
+}
+
+#[test]
+fn test_format_properties() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    assert!(api
+        .format_properties(Format::R8G8B8A8_UNORM)
+        .supports(ImageUsageFlags::COLOR_ATTACHMENT | ImageUsageFlags::STORAGE));
+    let depth = api.format_properties(Format::D32_SFLOAT);
+    assert!(depth.supports(ImageUsageFlags::DEPTH_ATTACHMENT | ImageUsageFlags::SAMPLED));
+    assert!(!depth.supports(ImageUsageFlags::COLOR_ATTACHMENT));
+}
+
+#[test]
+fn test_submit_counts_commands_and_reads_back() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let mut result = [0u32; 4];
+    {
+        let arena = api.create_arena();
+        let swapchain = api.default_swapchain().unwrap();
+        let frame = swapchain.acquire(&arena);
+        let data = arena.upload_slice(&[1u32, 2, 3]);
+
+        let mut cmdbuf = api.create_command_buffer();
+        cmdbuf.clear_render_target(0, frame.render_target_view(), &[0.0; 4]);
+        cmdbuf.copy_buffer_to_host(1, data, arena.host_readback(&mut result[..]));
+        cmdbuf.present(2, frame);
+        api.submit_frame(vec![cmdbuf]);
+    }
+    // elements past the end of the buffer are zeros
+    assert_eq!(result, [1, 2, 3, 0]);
+
+    let counts = api.instance().last_frame();
+    assert_eq!(counts.clears, 1);
+    assert_eq!(counts.copies_to_host, 1);
+    assert_eq!(counts.presents, 1);
+    assert_eq!(counts.draws, 0);
+    assert_eq!(api.instance().frame_count(), 1);
+}
+
+#[test]
+fn test_integer_clears() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let ids = arena.image_2d(Format::R32_UINT, 64, 64).build();
+
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, ids, &[0u32; 4]);
+    cmdbuf.clear_render_target(1, ids, &[-1i32, 0, 0, 0]);
+    cmdbuf.clear_render_target(2, ids, &[0.0; 4]);
+    match cmdbuf.iter().next().unwrap().cmd {
+        CommandInner::ClearImageUint { color, .. } => assert_eq!(color, [0; 4]),
+        _ => panic!("expected an unsigned integer clear"),
+    }
+    match cmdbuf.iter().nth(1).unwrap().cmd {
+        CommandInner::ClearImageInt { color, .. } => assert_eq!(color[0], -1),
+        _ => panic!("expected a signed integer clear"),
+    }
+
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(api.instance().last_frame().clears, 3);
+}
+
+#[test]
+fn test_upload_slice_views() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let transforms: Vec<[[f32; 4]; 4]> = (0..3).map(|i| [[i as f32; 4]; 4]).collect();
+    let views: Vec<_> = arena.upload_slice_views(&transforms).collect();
+    assert_eq!(views.len(), 3);
+
+    // a single buffer, with elements padded to the constant buffer offset alignment (256)
+    let buffers = api.instance().live_buffers();
+    assert_eq!(buffers.len(), 1);
+    assert_eq!(buffers[0].size, 3 * 256);
+    for (i, view) in views.into_iter().enumerate() {
+        match view.into_descriptor() {
+            Descriptor::ConstantBuffer {
+                buffer,
+                offset,
+                size,
+            } => {
+                assert_eq!(offset, i * 256);
+                assert_eq!(size, Some(64));
+                let data = buffer.data.as_ref().unwrap();
+                let expected =
+                    unsafe { slice::from_raw_parts(transforms[i].as_ptr() as *const u8, 64) };
+                assert_eq!(&data[offset..offset + 64], expected);
+            }
+            d => panic!("unexpected descriptor: {:?}", d),
+        }
+    }
+}
+
+#[test]
+fn test_inferred_image_usage() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let color = arena
+        .image_2d(Format::R8G8B8A8_SRGB, 64, 64)
+        .infer_usage()
+        .build();
+    let explicit = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+
+    // not used in the first frame: nothing inferred yet
+    api.submit_frame(vec![]);
+    assert_eq!(api.inferred_usage(color.inner()), None);
+
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, color, &[0.0; 4]);
+    cmdbuf.clear_render_target(1, explicit, &[0.0; 4]);
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(
+        api.inferred_usage(color.inner()),
+        Some(ImageUsageFlags::COLOR_ATTACHMENT)
+    );
+    assert_eq!(api.inferred_usage(explicit.inner()), None);
+
+    // uses seen in the first frame are still allowed
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, color, &[1.0; 4]);
+    api.submit_frame(vec![cmdbuf]);
+}
+
+#[test]
+#[should_panic(expected = "inferred from the first frame")]
+fn test_inferred_image_usage_is_validated() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena
+        .image_2d(Format::D32_SFLOAT, 64, 64)
+        .infer_usage()
+        .build();
+
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, image, &[0.0; 4]);
+    api.submit_frame(vec![cmdbuf]);
+
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_depth_stencil(0, image.depth_stencil_layers_view(0, 1), 1.0, None);
+    api.submit_frame(vec![cmdbuf]);
+}
+
+/// Counts the bytes allocated for an arena.
+#[derive(Debug)]
+struct Usage(Arc<AtomicUsize>);
+
+impl ArenaAllocator for Usage {
+    fn allocate(&self, request: &AllocationRequest) -> Result<(), Error> {
+        self.0.fetch_add(request.size as usize, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn free(&self, request: &AllocationRequest) {
+        self.0.fetch_sub(request.size as usize, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_arena_allocator_is_called() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let usage = Arc::new(AtomicUsize::new(0));
+    {
+        let arena = api.create_arena_with_options(ArenaOptions {
+            allocator: Box::new(Usage(usage.clone())),
+            ..ArenaOptions::default()
+        });
+        arena.upload(&[0u8; 100]);
+        arena.upload(&0u32);
+        assert_eq!(usage.load(Ordering::SeqCst), 104);
+    }
+    assert_eq!(usage.load(Ordering::SeqCst), 0);
+}
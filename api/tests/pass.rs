@@ -0,0 +1,86 @@
+use autograph_api::{
+    command::CommandInner,
+    null::{NullBackend, NullInstance},
+    pass::SortKeyLayout,
+    render_pass,
+    report::FrameReport,
+    AliasScope, Api,
+};
+
+#[test]
+fn test_sort_key_layout() {
+    let mut layout = SortKeyLayout::with_base_key(0x10_0000, 16);
+    let shadows = layout.next_pass("shadows");
+    let lighting = layout.next_pass("lighting");
+    assert_eq!(shadows.key_range(), 0x10_0000..0x11_0000);
+    assert_eq!(lighting.index, 1);
+    assert_eq!(lighting.key(0x20), 0x11_0020);
+    assert_eq!(
+        lighting.alias_scope(),
+        AliasScope {
+            value: 0x11_0000,
+            mask: !0xFFFF,
+        }
+    );
+    assert_eq!(layout.pass("lighting"), Some(&lighting));
+    assert_eq!(layout.pass_of_key(0x10_FFFF), Some(&shadows));
+    assert_eq!(layout.pass_of_key(0x12_0000), None);
+
+    layout.reset();
+    assert_eq!(layout.next_pass("shadows"), shadows);
+}
+
+#[test]
+fn test_render_pass_macro() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let swapchain = api.default_swapchain().unwrap();
+    let frame = swapchain.acquire(&arena);
+    let target = frame.render_target_view();
+
+    let mut layout = SortKeyLayout::new(8);
+    let mut cmdbuf = api.create_command_buffer();
+    render_pass!(cmdbuf, layout, "clear", {
+        cmdbuf.clear_render_target(0x10, target, &[0.0; 4]);
+    });
+    let post = render_pass!(cmdbuf, layout, "post", {
+        assert_eq!(cmdbuf.current_pass().unwrap().name, "post");
+        cmdbuf.clear_render_target(0x20, target, &[1.0; 4]);
+    });
+    assert!(cmdbuf.current_pass().is_none());
+    cmdbuf.present(0x300, frame);
+
+    assert_eq!(post.base_key, 0x100);
+    let keys: Vec<_> = cmdbuf.iter().map(|c| c.sortkey).collect();
+    assert_eq!(keys, [0x00, 0x10, 0xFF, 0x100, 0x120, 0x1FF, 0x300]);
+    match cmdbuf.iter().nth(3).unwrap().cmd {
+        CommandInner::BeginDebugGroup { name } => assert_eq!(name, "post"),
+        _ => panic!("expected a debug group"),
+    }
+
+    api.submit_frame(vec![cmdbuf]);
+    let counts = api.instance().last_frame();
+    assert_eq!(counts.debug_groups, 4);
+    assert_eq!(counts.clears, 2);
+}
+
+#[test]
+fn test_named_passes_in_report() {
+    let mut report = FrameReport::default();
+    report.begin_pass(0x0);
+    report.current_pass(0x0).dispatches += 1;
+    report.begin_named_pass(0x100, "shadows");
+    report.current_pass(0x110).draws += 3;
+    report.begin_pass(0x1FF);
+    report.begin_named_pass(0x200, "lighting");
+
+    // the empty pass between the named passes is dropped
+    let names: Vec<_> = report.passes.iter().map(|p| p.name).collect();
+    assert_eq!(names, [None, Some("shadows"), Some("lighting")]);
+    assert!(report
+        .to_string()
+        .lines()
+        .nth(3)
+        .unwrap()
+        .ends_with("shadows"));
+}
@@ -0,0 +1,97 @@
+use autograph_api::{
+    buffer::TypedConstantBufferView,
+    command::DrawParams,
+    format::Format,
+    image::RenderTargetView,
+    null::{NullBackend, NullInstance},
+    pipeline::{
+        Arguments, ColorBlendState, DepthStencilState, GraphicsPipelineCreateInfo,
+        InputAssemblyState, MultisampleState, RasterisationState, TypedGraphicsPipeline, ViewCount,
+        Viewport, ViewportState,
+    },
+    Api, Arena, Backend,
+};
+
+mod common;
+use crate::common::{FRAGMENT, VERTEX};
+
+#[derive(Copy, Clone, Arguments)]
+struct LowQuality<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, [f32; 4]>,
+}
+
+/// Same signature as `LowQuality`, with another descriptor name.
+#[derive(Copy, Clone, Arguments)]
+struct HighQuality<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+    #[argument(descriptor)]
+    parameters: TypedConstantBufferView<'a, B, [f32; 4]>,
+}
+
+#[derive(Copy, Clone, Arguments)]
+struct TwoTargets<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(render_target)]
+    normals: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+}
+
+fn low_quality_pipeline<'a>(
+    arena: &'a Arena<NullBackend>,
+) -> TypedGraphicsPipeline<'a, NullBackend, LowQuality<'a, NullBackend>> {
+    arena.create_graphics_pipeline(&GraphicsPipelineCreateInfo {
+        shader_stages: arena.create_vertex_fragment_shader_stages(VERTEX, FRAGMENT),
+        viewport_state: ViewportState::default(),
+        rasterization_state: RasterisationState::default(),
+        multisample_state: MultisampleState::default(),
+        depth_stencil_state: DepthStencilState::default(),
+        input_assembly_state: InputAssemblyState::default(),
+        color_blend_state: ColorBlendState::DISABLED,
+        view_count: ViewCount::default(),
+    })
+}
+
+#[test]
+fn test_cast_pipeline() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let parameters = arena.upload_slice_views(&[[1.0f32; 4]]).next().unwrap();
+    let pipeline = low_quality_pipeline(&arena);
+
+    assert!(pipeline.cast::<TwoTargets<NullBackend>>(&api).is_none());
+    let pipeline = pipeline.cast::<HighQuality<NullBackend>>(&api).unwrap();
+
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.draw(
+        0,
+        &arena,
+        pipeline,
+        HighQuality {
+            color: image.into(),
+            viewport: Viewport::full(image),
+            parameters,
+        },
+        DrawParams::quad(),
+    );
+    api.submit_frame(vec![cmdbuf]);
+    assert_eq!(api.instance().last_frame().draws, 1);
+}
+
+#[test]
+#[should_panic(expected = "incompatible pipeline signatures")]
+fn test_with_signature_of_mismatch() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    low_quality_pipeline(&arena).with_signature_of::<TwoTargets<NullBackend>>(&api);
+}
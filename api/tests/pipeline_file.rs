@@ -0,0 +1,175 @@
+use autograph_api::{
+    null::{NullBackend, NullInstance},
+    pipeline::{
+        file::{PipelineFileError, PipelineStateFile, PipelineStates},
+        BlendFactor, ColorBlendAttachmentState, ColorBlendAttachmentsOwned, ColorComponentFlags,
+        CompareOp, CullModeFlags, DepthStencilState, FrontFace, GraphicsPipelineCreateInfo,
+        LineWidth, PolygonMode, PrimitiveTopology, RasterisationState, StencilOp, StencilTest,
+        ViewCount,
+    },
+    Api,
+};
+use std::fs;
+
+mod common;
+use crate::common::{FRAGMENT, VERTEX};
+
+#[test]
+fn test_parse_pipeline_states() {
+    let states = PipelineStates::parse(
+        r#"
+        view_count = 2
+
+        [rasterization]
+        preset = "cull_back_ccw"
+        polygon_mode = "line"
+        line_width = "dynamic"
+
+        [depth_stencil]
+        preset = "less_equal_read_only"
+        stencil = { compare_op = "equal", pass_op = "replace", reference = 3 }
+
+        [input_assembly]
+        topology = "line_strip"
+
+        [color_blend]
+        blend_constants = [1.0, 0.5, 0.0, 1.0]
+
+        [[color_blend.attachments]]
+        preset = "premultiplied"
+
+        [[color_blend.attachments]]
+        src_color = "one"
+        dst_color = "one"
+        write_mask = "rgb"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(states.view_count, ViewCount::STEREO);
+    assert_eq!(
+        states.rasterization_state,
+        RasterisationState::CULL_BACK_CCW
+            .with_polygon_mode(PolygonMode::Line)
+            .with_line_width(LineWidth::Dynamic)
+    );
+    assert_eq!(states.rasterization_state.cull_mode, CullModeFlags::BACK);
+    assert_eq!(
+        states.rasterization_state.front_face,
+        FrontFace::CounterClockwise
+    );
+
+    let depth_stencil = states.depth_stencil_state;
+    assert!(!depth_stencil.depth_write_enable);
+    assert_eq!(
+        depth_stencil.depth_compare_op,
+        DepthStencilState::LESS_EQUAL_READ_ONLY.depth_compare_op
+    );
+    match depth_stencil.stencil_test {
+        StencilTest::Enabled { front, back } => {
+            assert_eq!(front, back);
+            assert_eq!(front.compare_op, CompareOp::Equal);
+            assert_eq!(front.pass_op, StencilOp::Replace);
+            assert_eq!(front.fail_op, StencilOp::Keep);
+            assert_eq!(front.reference, 3);
+        }
+        StencilTest::Disabled => panic!("stencil test not enabled"),
+    }
+
+    assert_eq!(
+        states.input_assembly_state.topology,
+        PrimitiveTopology::LineStrip
+    );
+    match states.color_blend_attachments {
+        ColorBlendAttachmentsOwned::Separate(ref attachments) => {
+            assert_eq!(attachments.len(), 2);
+            assert_eq!(attachments[0], ColorBlendAttachmentState::PREMULTIPLIED);
+            match attachments[1] {
+                ColorBlendAttachmentState::Enabled {
+                    dst_color_blend_factor,
+                    color_write_mask,
+                    ..
+                } => {
+                    assert_eq!(dst_color_blend_factor, BlendFactor::One);
+                    assert_eq!(
+                        color_write_mask,
+                        ColorComponentFlags::RGBA - ColorComponentFlags::A
+                    );
+                }
+                ColorBlendAttachmentState::Disabled => panic!("blending not enabled"),
+            }
+        }
+        ColorBlendAttachmentsOwned::All(_) => panic!("expected separate attachments"),
+    }
+    assert_eq!(*states.blend_constants[1], 0.5);
+}
+
+#[test]
+fn test_parse_pipeline_states_defaults() {
+    assert_eq!(
+        PipelineStates::parse("").unwrap(),
+        PipelineStates::default()
+    );
+    let states = PipelineStates::parse("[color_blend]\nattachments = \"alpha_blending\"").unwrap();
+    assert_eq!(
+        states.color_blend_attachments,
+        ColorBlendAttachmentsOwned::All(ColorBlendAttachmentState::ALPHA_BLENDING)
+    );
+}
+
+#[test]
+fn test_parse_pipeline_states_errors() {
+    match PipelineStates::parse("[rasterization]\ncul_mode = \"back\"") {
+        Err(PipelineFileError::UnknownKey { key }) => assert_eq!(key, "rasterization.cul_mode"),
+        r => panic!("unexpected result: {:?}", r),
+    }
+    match PipelineStates::parse("[depth_stencil]\ndepth_compare_op = \"lesser\"") {
+        Err(PipelineFileError::InvalidValue { key, expected }) => {
+            assert_eq!(key, "depth_stencil.depth_compare_op");
+            assert!(expected.contains("`less_or_equal`"));
+        }
+        r => panic!("unexpected result: {:?}", r),
+    }
+    match PipelineStates::parse("[color_blend]\nattachments = [\"disabled\", \"screen\"]") {
+        Err(PipelineFileError::InvalidValue { key, .. }) => {
+            assert_eq!(key, "color_blend.attachments[1]")
+        }
+        r => panic!("unexpected result: {:?}", r),
+    }
+    match PipelineStates::parse("view_count = -1") {
+        Err(PipelineFileError::InvalidValue { key, .. }) => assert_eq!(key, "view_count"),
+        r => panic!("unexpected result: {:?}", r),
+    }
+    match PipelineStates::parse("[rasterization") {
+        Err(PipelineFileError::Syntax(_)) => {}
+        r => panic!("unexpected result: {:?}", r),
+    }
+}
+
+#[test]
+fn test_pipeline_state_file() {
+    let path = std::env::temp_dir().join("autograph_test_pipeline_state_file.toml");
+    fs::write(&path, "[depth_stencil]\npreset = \"greater_write\"\n").unwrap();
+
+    let mut file = PipelineStateFile::load(&path).unwrap();
+    assert_eq!(
+        file.states().depth_stencil_state,
+        DepthStencilState::GREATER_WRITE
+    );
+    // not modified
+    assert!(!file.reload_if_modified().unwrap());
+
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let create_info = GraphicsPipelineCreateInfo::from_file(
+        &file,
+        arena.create_vertex_fragment_shader_stages(VERTEX, FRAGMENT),
+    );
+    assert_eq!(
+        create_info.depth_stencil_state,
+        DepthStencilState::GREATER_WRITE
+    );
+
+    fs::remove_file(&path).unwrap();
+    assert!(PipelineStateFile::load(&path).is_err());
+}
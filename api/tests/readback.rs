@@ -0,0 +1,67 @@
+use autograph_api::{
+    buffer::{Pod, StructuredBufferData, TypedRwBufferView},
+    null::{NullBackend, NullInstance},
+    pipeline::Arguments,
+    Api, Backend,
+};
+
+mod common;
+use crate::common::COMPUTE;
+
+#[repr(C)]
+#[derive(StructuredBufferData, Pod, Copy, Clone, Debug, Default, PartialEq)]
+struct Particle {
+    position: [f32; 4],
+    velocity: [f32; 4],
+}
+
+#[derive(Copy, Clone, Arguments)]
+struct Simulate<'a, B: Backend> {
+    #[argument(descriptor)]
+    particles: TypedRwBufferView<'a, B, [Particle]>,
+}
+
+#[test]
+fn test_readback_after_dispatch() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let particles = [
+        Particle {
+            position: [1.0, 2.0, 3.0, 1.0],
+            velocity: [0.0, -1.0, 0.0, 0.0],
+        },
+        Particle {
+            position: [4.0, 5.0, 6.0, 1.0],
+            velocity: [0.5, 0.0, 0.0, 0.0],
+        },
+    ];
+    let mut result = [Particle::default(); 3];
+    {
+        let arena = api.create_arena();
+        let pipeline = arena
+            .create_compute_pipeline::<Simulate<NullBackend>>(arena.create_shader_module(COMPUTE));
+        let buffer = arena.upload_slice(&particles);
+
+        let mut cmdbuf = api.create_command_buffer();
+        cmdbuf.dispatch(
+            0,
+            &arena,
+            pipeline,
+            Simulate {
+                particles: buffer.into(),
+            },
+            (1, 1, 1),
+        );
+        // same sort key: executed after the dispatch
+        cmdbuf.copy_buffer_to_host(0, buffer, arena.host_readback(&mut result[..]));
+        api.submit_frame(vec![cmdbuf]);
+    }
+
+    // the null backend does not run shaders: the buffer keeps its initial data,
+    // and elements past its end are zeros
+    assert_eq!(&result[..2], &particles[..]);
+    assert_eq!(result[2], Particle::default());
+
+    let counts = api.instance().last_frame();
+    assert_eq!(counts.dispatches, 1);
+    assert_eq!(counts.copies_to_host, 1);
+}
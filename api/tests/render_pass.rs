@@ -0,0 +1,62 @@
+use autograph_api::pipeline::{
+    DepthRange, FragmentOutputDescription, LoadOp, SignatureDescription, StoreOp,
+};
+
+const RENDER_TARGETS: SignatureDescription<'static> = SignatureDescription {
+    fragment_outputs: &[
+        FragmentOutputDescription {
+            load_op: LoadOp::CLEAR_ZERO,
+            ..FragmentOutputDescription::DEFAULT
+        },
+        FragmentOutputDescription {
+            load_op: LoadOp::DontCare,
+            store_op: StoreOp::DontCare,
+            ..FragmentOutputDescription::DEFAULT
+        },
+    ],
+    depth_stencil_fragment_output: Some(FragmentOutputDescription {
+        load_op: LoadOp::CLEAR_DEPTH_FAR,
+        store_op: StoreOp::DontCare,
+        ..FragmentOutputDescription::DEFAULT
+    }),
+    is_root_fragment_output_signature: true,
+    ..SignatureDescription::EMPTY
+};
+
+#[test]
+fn test_default_ops() {
+    let output = FragmentOutputDescription::default();
+    assert_eq!(output, FragmentOutputDescription::DEFAULT);
+    assert_eq!(output.load_op, LoadOp::Load);
+    assert_eq!(output.store_op, StoreOp::Store);
+}
+
+#[test]
+fn test_collect_ops() {
+    let mut outputs = Vec::new();
+    RENDER_TARGETS.collect_fragment_outputs(&mut outputs);
+    let ops = outputs
+        .iter()
+        .map(|o| (o.load_op, o.store_op))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ops,
+        &[
+            (LoadOp::Clear([0.0; 4]), StoreOp::Store),
+            (LoadOp::DontCare, StoreOp::DontCare),
+        ]
+    );
+}
+
+#[test]
+fn test_clear_values() {
+    assert_ne!(LoadOp::CLEAR_ZERO, LoadOp::CLEAR_DEPTH_FAR);
+    assert_eq!(
+        LoadOp::clear_depth_far(DepthRange::ZeroToOne),
+        LoadOp::CLEAR_DEPTH_FAR
+    );
+    assert_eq!(
+        LoadOp::clear_depth_far(DepthRange::OneToZero),
+        LoadOp::CLEAR_DEPTH_ZERO
+    );
+}
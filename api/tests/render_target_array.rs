@@ -0,0 +1,69 @@
+use autograph_api::{
+    image::{DepthStencilView, RenderTarget2dView},
+    pipeline::{
+        validate::{validate_fragment_outputs, ValidationError},
+        Arguments, ColorBlendAttachmentState, FragmentOutputDescription, LoadOp, ShaderStageFlags,
+        ShaderStageReflection, StoreOp,
+    },
+    Backend, DummyBackend,
+};
+
+mod common;
+use crate::common::signature;
+
+#[derive(Arguments)]
+struct GBuffers<'a, B: Backend> {
+    #[argument(render_target, load = "clear")]
+    gbuffers: [RenderTarget2dView<'a, B>; 4],
+    #[argument(render_target, blend = "additive")]
+    lighting: RenderTarget2dView<'a, B>,
+    #[argument(depth_stencil_render_target)]
+    depth: DepthStencilView<'a, B>,
+}
+
+fn fragment_shader(num_outputs: usize) -> ShaderStageReflection<'static> {
+    const OUTPUTS: &[FragmentOutputDescription] = &[FragmentOutputDescription::DEFAULT; 8];
+    ShaderStageReflection {
+        stage: ShaderStageFlags::FRAGMENT,
+        descriptors: &[],
+        vertex_input_attributes: &[],
+        fragment_outputs: &OUTPUTS[0..num_outputs],
+        fragment_output_attributes: &[],
+        stage_inputs: &[],
+        stage_outputs: &[],
+    }
+}
+
+#[test]
+fn test_render_target_array_signature() {
+    let sig = signature::<GBuffers<DummyBackend>, _>();
+    assert_eq!(sig.fragment_outputs.len(), 5);
+    for output in &sig.fragment_outputs[0..4] {
+        assert_eq!(output.load_op, LoadOp::CLEAR_ZERO);
+        assert_eq!(output.store_op, StoreOp::Store);
+        assert_eq!(output.blend, None);
+    }
+    // the field after the array gets the next location
+    assert_eq!(
+        sig.fragment_outputs[4].blend,
+        Some(ColorBlendAttachmentState::ADDITIVE)
+    );
+    assert!(sig.depth_stencil_fragment_output.is_some());
+}
+
+#[test]
+fn test_render_target_array_validation() {
+    let sig = signature::<GBuffers<DummyBackend>, _>();
+    let mut errors = Vec::new();
+    validate_fragment_outputs(sig, &fragment_shader(5), &mut errors);
+    assert!(errors.is_empty());
+
+    validate_fragment_outputs(sig, &fragment_shader(6), &mut errors);
+    match errors.as_slice() {
+        [ValidationError::MissingRenderTarget {
+            num_shader_outputs: 6,
+            num_render_targets: 5,
+        }] => {}
+        other => panic!("unexpected errors: {:?}", other),
+    }
+}
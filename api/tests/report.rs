@@ -0,0 +1,184 @@
+use autograph_api::{
+    report::{
+        FrameReport, LifetimeReport, PassReport, PipelineCompileTime, PipelineKind, ResourceKind,
+        SubmitStats, WarmUpReport,
+    },
+    AliasScope,
+};
+use std::time::Duration;
+
+#[test]
+fn test_frame_report_table() {
+    let mut report = FrameReport::default();
+    report.begin_pass(0x10);
+    report.current_pass(0x10).draws += 12;
+    report.current_pass(0x10).state_changes += 30;
+    report.begin_pass(0x20);
+    report.current_pass(0x20).dispatches += 2;
+    report.sort_time = Duration::from_micros(120);
+    report.upload_bytes = 256;
+
+    assert_eq!(
+        report.total(),
+        PassReport {
+            sortkey: 0x10,
+            name: None,
+            draws: 12,
+            dispatches: 2,
+            state_changes: 30
+        }
+    );
+
+    let table = report.to_string();
+    let lines: Vec<_> = table.lines().collect();
+    // header, separator, two passes, separator, total, timings
+    assert_eq!(lines.len(), 7);
+    assert!(lines[2].contains("0x0000000000000010"));
+    assert!(lines[5].starts_with("total"));
+    assert!(lines[6].contains("sort: 0.120ms"));
+    assert!(lines[6].contains("uploaded: 256 bytes"));
+}
+
+#[test]
+fn test_lifetime_report() {
+    let blur = AliasScope {
+        value: 0x1000,
+        mask: 0xF000,
+    };
+    let tonemap = AliasScope {
+        value: 0x2000,
+        mask: 0xF000,
+    };
+    let mut report = LifetimeReport::default();
+    report.add_resource(ResourceKind::Image, 1, blur, "blur");
+    report.add_resource(ResourceKind::Image, 1, tonemap, "tonemap \"final\"");
+    report.add_resource(ResourceKind::Image, 2, AliasScope::no_alias(), "unused");
+
+    report.record_use(1, 0x1001);
+    report.record_use(1, 0x1002);
+    report.record_use(1, 0x2003);
+    // outside of both scopes
+    report.record_use(1, 0x3000);
+    // not a transient resource
+    report.record_use(3, 0x1001);
+
+    report.finish(&[0x1000, 0x1001, 0x1002, 0x1003, 0x2000, 0x2003, 0x3000]);
+
+    let blur = &report.resources[0];
+    assert_eq!(
+        (blur.first_use, blur.last_use),
+        (Some(0x1001), Some(0x1002))
+    );
+    assert_eq!((blur.scope_commands, blur.used_commands), (4, 2));
+    assert_eq!(blur.wasted_commands(), 2);
+    assert_eq!(blur.aliases, &[1]);
+
+    let tonemap = &report.resources[1];
+    assert_eq!(
+        (tonemap.first_use, tonemap.last_use),
+        (Some(0x2003), Some(0x2003))
+    );
+    assert_eq!(tonemap.wasted_commands(), 1);
+
+    let unused = &report.resources[2];
+    assert_eq!(unused.first_use, None);
+    assert_eq!(unused.wasted_commands(), 7);
+    assert!(unused.aliases.is_empty());
+
+    assert_eq!(report.out_of_scope_uses, 1);
+
+    let json = report.to_json();
+    assert!(json.starts_with("{\"resources\":[{\"index\":0,\"kind\":\"Image\""));
+    assert!(json.contains("\"description\":\"tonemap \\\"final\\\"\""));
+    assert!(json.contains("\"first_use\":\"0x1001\""));
+    assert!(json.contains("\"first_use\":null"));
+    assert!(json.ends_with("],\"out_of_scope_uses\":1}"));
+
+    // header, separator, three resources, separator, summary
+    assert_eq!(report.to_string().lines().count(), 7);
+}
+
+#[test]
+fn test_warm_up_report() {
+    let report = WarmUpReport {
+        pipelines: vec![
+            PipelineCompileTime {
+                kind: PipelineKind::Graphics,
+                create_time: Duration::from_millis(12),
+                warm_up_time: Duration::from_millis(30),
+            },
+            PipelineCompileTime {
+                kind: PipelineKind::Compute,
+                create_time: Duration::from_millis(25),
+                warm_up_time: Duration::from_millis(1),
+            },
+        ],
+    };
+    assert_eq!(report.warm_up_time(), Duration::from_millis(31));
+    let (index, slowest) = report.slowest().unwrap();
+    assert_eq!(index, 0);
+    assert_eq!(slowest.total(), Duration::from_millis(42));
+    assert!(WarmUpReport::default().slowest().is_none());
+
+    let table = report.to_string();
+    let lines: Vec<_> = table.lines().collect();
+    // header, separator, two pipelines, separator, summary
+    assert_eq!(lines.len(), 6);
+    assert!(lines[3].contains("Compute"));
+    assert_eq!(lines[5], "2 pipelines, warm-up: 31.000ms");
+}
+
+#[test]
+fn test_submit_stats() {
+    let stats = SubmitStats::from_durations(Duration::from_micros(2500), Duration::from_millis(0));
+    assert_eq!(stats.cpu_wait_ms, 2.5);
+    assert_eq!(stats.gpu_idle_estimate, 0.0);
+    assert!(stats.is_gpu_bound());
+
+    let stats = SubmitStats::from_durations(Duration::from_millis(0), Duration::from_millis(4));
+    assert_eq!(stats.gpu_idle_estimate, 4.0);
+    assert!(!stats.is_gpu_bound());
+    assert!(!SubmitStats::default().is_gpu_bound());
+}
+
+#[test]
+fn test_frame_report_dot() {
+    let mut report = FrameReport::default();
+    report.begin_named_pass(0x1000, "blur");
+    report.current_pass(0x1000).draws += 2;
+    report.begin_pass(0x2000);
+    report.current_pass(0x2000).draws += 1;
+    report.begin_pass(0x3000);
+
+    let mut lifetimes = LifetimeReport::default();
+    let scope = |value| AliasScope {
+        value,
+        mask: 0xF000,
+    };
+    lifetimes.add_resource(ResourceKind::Image, 1, scope(0x1000), "blur");
+    lifetimes.add_resource(ResourceKind::Image, 1, scope(0x2000), "tonemap");
+    lifetimes.add_resource(ResourceKind::Image, 2, AliasScope::no_alias(), "\"depth\"");
+    lifetimes.record_use(1, 0x1001);
+    lifetimes.record_use(1, 0x2001);
+    lifetimes.record_use(2, 0x1002);
+    lifetimes.record_use(2, 0x3005);
+    report.lifetimes = Some(lifetimes);
+
+    let dot = report.to_dot();
+    assert!(dot.starts_with("digraph frame {"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("pass0 [shape=box, label=\"blur\\nsortkey: 0x1000\\ndraws: 2"));
+    assert!(dot.contains("pass1 [shape=box, label=\"0x2000\\n"));
+    assert!(dot.contains("pass0 -> pass1"));
+    assert!(dot.contains("pass1 -> pass2"));
+    // aliases share a color
+    assert!(dot.contains("res0 [shape=ellipse, style=filled, fillcolor=lightblue"));
+    assert!(dot.contains("res1 [shape=ellipse, style=filled, fillcolor=lightblue"));
+    assert!(dot.contains("res2 [shape=ellipse, style=filled, fillcolor=palegreen"));
+    assert!(dot.contains("label=\"\\\"depth\\\"\\nmemory 2, scope 0x0/0x0\""));
+    assert!(dot.contains("pass0 -> res0;"));
+    assert!(dot.contains("pass1 -> res1;"));
+    assert!(!dot.contains("res1 -> pass"));
+    assert!(dot.contains("pass0 -> res2;"));
+    assert!(dot.contains("res2 -> pass2;"));
+}
@@ -0,0 +1,21 @@
+use autograph_api::{image::Image2d, DummyBackend};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+fn hash_of<T: Hash>(v: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    v.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_image_hash_identity_and_subresource() {
+    let raw = &();
+    let image: Image2d<DummyBackend> = unsafe { Image2d::from_raw(raw) };
+    let same: Image2d<DummyBackend> = unsafe { Image2d::from_raw(raw) };
+    assert_eq!(hash_of(&image), hash_of(&same));
+    assert_eq!(hash_of(&image.mipmap(1)), hash_of(&same.mipmap(1)));
+    assert_ne!(hash_of(&image.mipmap(0)), hash_of(&image.mipmap(1)));
+}
@@ -0,0 +1,93 @@
+use autograph_api::{
+    command::{CommandBuffer, CommandInner},
+    format::Format,
+    null::{NullBackend, NullInstance},
+    semantics::{ResourceSemantics, SemanticId},
+    Api,
+};
+
+#[test]
+fn test_semantic_id() {
+    // FNV-1a test vectors
+    assert_eq!(SemanticId::from_name(""), SemanticId(0xcbf2_9ce4_8422_2325));
+    assert_eq!(
+        SemanticId::from_name("a"),
+        SemanticId(0xaf63_dc4c_8601_ec8c)
+    );
+    assert_eq!(
+        SemanticId::from("gbuffer.normal"),
+        SemanticId::from_name("gbuffer.normal")
+    );
+    assert_ne!(
+        SemanticId::from_name("gbuffer.normal"),
+        SemanticId::from_name("gbuffer.albedo")
+    );
+}
+
+/// Sortkeys and clear colors of the commands of the buffer, in order.
+fn clears(cmdbuf: &CommandBuffer<NullBackend>) -> Vec<(u64, f32)> {
+    cmdbuf
+        .iter()
+        .map(|cmd| match cmd.cmd {
+            CommandInner::ClearImageFloat { color, .. } => (cmd.sortkey, color[0]),
+            _ => panic!("unexpected command"),
+        })
+        .collect()
+}
+
+#[test]
+fn test_resolve_semantics_order() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let color = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let normal = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let mut semantics = ResourceSemantics::new();
+    semantics.register_image("gbuffer.normal", normal);
+
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, color, &[0.0; 4]);
+    cmdbuf.clear_semantic_render_target(1, "gbuffer.normal", &[1.0; 4]);
+    cmdbuf.clear_render_target(2, color, &[2.0; 4]);
+    cmdbuf.clear_semantic_render_target(3, "gbuffer.normal", &[3.0; 4]);
+    // deferred until resolved
+    assert_eq!(clears(&cmdbuf), vec![(0, 0.0), (2, 2.0)]);
+
+    cmdbuf.resolve_semantics(&semantics).unwrap();
+    // resolved commands are inserted where they were recorded
+    assert_eq!(
+        clears(&cmdbuf),
+        vec![(0, 0.0), (1, 1.0), (2, 2.0), (3, 3.0)]
+    );
+    match cmdbuf.iter().nth(1).unwrap().cmd {
+        CommandInner::ClearImageFloat { image, .. } => {
+            assert!(std::ptr::eq(image, normal.inner()))
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_resolve_semantics_missing() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let color = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let normal = arena.image_2d(Format::R8G8B8A8_UNORM, 64, 64).build();
+    let mut semantics = ResourceSemantics::new();
+    semantics.register_image("gbuffer.normal", normal);
+
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, color, &[0.0; 4]);
+    cmdbuf.clear_semantic_render_target(1, "gbuffer.normal", &[1.0; 4]);
+    cmdbuf.clear_semantic_render_target(2, "gbuffer.albedo", &[2.0; 4]);
+
+    assert_eq!(
+        cmdbuf.resolve_semantics(&semantics).unwrap_err(),
+        SemanticId::from("gbuffer.albedo")
+    );
+    // unchanged: the resolvable command is still deferred
+    assert_eq!(clears(&cmdbuf), vec![(0, 0.0)]);
+
+    semantics.register_image("gbuffer.albedo", color);
+    cmdbuf.resolve_semantics(&semantics).unwrap();
+    assert_eq!(clears(&cmdbuf), vec![(0, 0.0), (1, 1.0), (2, 2.0)]);
+}
@@ -0,0 +1,132 @@
+use autograph_api::{
+    buffer::{StructuredBufferData, TypedConstantBufferView},
+    image::RenderTargetView,
+    null::{NullBackend, NullInstance},
+    pipeline::{Arguments, Signature, SignatureDescription, TypedArgumentBlock, Viewport},
+    vertex::{TypedVertexBufferView, VertexData},
+    Api, Backend, DummyBackend,
+};
+use std::{
+    any::TypeId,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+mod common;
+use crate::common::signature;
+
+#[derive(Arguments)]
+struct Targets<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+}
+
+// same fields as `Targets`
+#[derive(Arguments)]
+struct OtherTargets<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+}
+
+#[derive(Arguments)]
+struct Material<'a, B: Backend, T: StructuredBufferData, A>
+where
+    A: Arguments<'a, B>,
+{
+    #[argument(inherit)]
+    common: TypedArgumentBlock<'a, B, A>,
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, T>,
+}
+
+#[repr(C)]
+#[derive(VertexData, Copy, Clone)]
+struct Vertex {
+    position: [f32; 3],
+    texcoords: [f32; 2],
+}
+
+// same layout as `Vertex`, with other attribute names
+#[repr(C)]
+#[derive(VertexData, Copy, Clone)]
+struct RenamedVertex {
+    pos: [f32; 3],
+    uv: [f32; 2],
+}
+
+#[derive(Arguments)]
+struct Mesh<'a, B: Backend> {
+    #[argument(descriptor)]
+    params: TypedConstantBufferView<'a, B, [f32; 4]>,
+    #[argument(vertex_buffer)]
+    vertices: TypedVertexBufferView<'a, B, Vertex>,
+}
+
+// same signature as `Mesh`, with other descriptor and attribute names
+#[derive(Arguments)]
+struct RenamedMesh<'a, B: Backend> {
+    #[argument(descriptor)]
+    parameters: TypedConstantBufferView<'a, B, [f32; 4]>,
+    #[argument(vertex_buffer)]
+    vertices: TypedVertexBufferView<'a, B, RenamedVertex>,
+}
+
+fn unique_type_id<'a, A: Arguments<'a, DummyBackend>>() -> TypeId {
+    TypeId::of::<A::UniqueType>()
+}
+
+fn hash(description: &SignatureDescription) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    description.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn hash_ignoring_names(description: &SignatureDescription) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    description.hash_ignoring_names(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_identical_signatures_compare_equal() {
+    let a = signature::<Targets<DummyBackend>, _>();
+    let b = signature::<OtherTargets<DummyBackend>, _>();
+    assert_ne!(
+        unique_type_id::<Targets<DummyBackend>>(),
+        unique_type_id::<OtherTargets<DummyBackend>>()
+    );
+    assert_eq!(a, b);
+    assert_eq!(hash(a), hash(b));
+}
+
+#[test]
+fn test_identical_nested_signatures_compare_equal() {
+    let a = signature::<Material<DummyBackend, f32, Targets<DummyBackend>>, _>();
+    let b = signature::<Material<DummyBackend, f32, OtherTargets<DummyBackend>>, _>();
+    assert_eq!(a, b);
+    assert_eq!(hash(a), hash(b));
+
+    let c = signature::<Material<DummyBackend, [f32; 4], Targets<DummyBackend>>, _>();
+    assert_ne!(a, c);
+}
+
+#[test]
+fn test_renamed_signatures_share_backend_signature() {
+    let a = signature::<Mesh<DummyBackend>, _>();
+    let b = signature::<RenamedMesh<DummyBackend>, _>();
+    // the names are kept for diagnostics
+    assert_ne!(a, b);
+    assert!(a.eq_ignoring_names(b));
+    assert_eq!(hash_ignoring_names(a), hash_ignoring_names(b));
+    assert!(!a.eq_ignoring_names(signature::<Targets<DummyBackend>, _>()));
+
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let a = api.get_cached_signature::<Mesh<NullBackend>>();
+    let b = api.get_cached_signature::<RenamedMesh<NullBackend>>();
+    assert!(std::ptr::eq(a.inner(), b.inner()));
+    assert_eq!(api.instance().object_counts().signatures, 1);
+}
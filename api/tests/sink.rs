@@ -0,0 +1,60 @@
+use autograph_api::{
+    format::Format,
+    null::{NullBackend, NullInstance},
+    sink::{FrameSink, SinkFrame},
+    Api, Arena,
+};
+
+/// Copies frames to host memory.
+struct Readback {
+    size: (u32, u32),
+    pixels: Vec<u8>,
+}
+
+impl FrameSink<NullBackend> for Readback {
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn begin_frame<'a, 'r>(
+        &'a mut self,
+        arena: &'a Arena<'r, NullBackend>,
+    ) -> SinkFrame<'a, NullBackend> {
+        let image = arena
+            .image_2d(Format::R8G8B8A8_SRGB, self.size.0, self.size.1)
+            .build();
+        SinkFrame::readback(image, self.size, arena.host_readback(&mut self.pixels[..]))
+    }
+}
+
+/// The same frame code for all sinks.
+fn render_frame(api: &Api<NullBackend>, sink: &mut dyn FrameSink<NullBackend>) {
+    let arena = api.create_arena();
+    let frame = sink.begin_frame(&arena);
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.clear_render_target(0, frame.render_target_view(), &[1.0; 4]);
+    frame.present(&mut cmdbuf, 1);
+    api.submit_frame(vec![cmdbuf]);
+}
+
+#[test]
+fn test_frame_sinks() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+
+    let mut swapchain = api.default_swapchain().unwrap();
+    render_frame(&api, &mut swapchain);
+    let counts = api.instance().last_frame();
+    assert_eq!(counts.presents, 1);
+    assert_eq!(counts.copies_to_host, 0);
+
+    let mut readback = Readback {
+        size: (4, 2),
+        pixels: vec![0xff; 4 * 4 * 2],
+    };
+    render_frame(&api, &mut readback);
+    let counts = api.instance().last_frame();
+    assert_eq!(counts.presents, 0);
+    assert_eq!(counts.copies_to_host, 1);
+    // the null backend reads back zeros
+    assert!(readback.pixels.iter().all(|&b| b == 0));
+}
@@ -0,0 +1,56 @@
+use autograph_api::{pass::SortKeyLayout, sortkey::SortKey};
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, SortKey)]
+#[sort_key(layer = 4, pipeline = 12, depth = 24)]
+struct OpaqueKey(u64);
+
+#[test]
+fn test_sort_key_fields() {
+    assert_eq!(OpaqueKey::BITS, 40);
+    assert_eq!(OpaqueKey::LAYER_SHIFT, 36);
+    assert_eq!(OpaqueKey::PIPELINE_SHIFT, 24);
+    assert_eq!(OpaqueKey::DEPTH_SHIFT, 0);
+    assert_eq!(OpaqueKey::PIPELINE_BITS, 12);
+
+    let mut key = OpaqueKey::default()
+        .with_layer(0x3)
+        .with_pipeline(0xABC)
+        .with_depth(0x12_3456);
+    assert_eq!(key.value(), (0x3 << 36) | (0xABC << 24) | 0x12_3456);
+    assert_eq!(u64::from(key), (0x3 << 36) | (0xABC << 24) | 0x12_3456);
+    assert_eq!(key.layer(), 0x3);
+    assert_eq!(key.pipeline(), 0xABC);
+    assert_eq!(key.depth(), 0x12_3456);
+
+    // setting a field leaves the others untouched
+    key.set_pipeline(0x001);
+    assert_eq!(key.value(), (0x3 << 36) | (0x001 << 24) | 0x12_3456);
+    key.set_depth(0xFF_FFFF);
+    assert_eq!(key.layer(), 0x3);
+    assert_eq!(key.pipeline(), 0x001);
+    assert_eq!(key.depth(), 0xFF_FFFF);
+}
+
+#[test]
+fn test_sort_key_order() {
+    // the first field is the most significant
+    let a = OpaqueKey::default().with_layer(0).with_depth(0xFF_FFFF);
+    let b = OpaqueKey::default().with_layer(1);
+    assert!(a < b);
+    assert!(a.value() < b.value());
+}
+
+#[test]
+fn test_sort_key_in_pass() {
+    let mut layout = SortKeyLayout::new(OpaqueKey::BITS);
+    let _ = layout.next_pass("shadows");
+    let pass = layout.next_pass("opaque");
+    let key = OpaqueKey::default().with_layer(0xF).with_depth(1);
+    assert_eq!(pass.key(key.value()), (1 << 40) | 0xF0_0000_0001);
+}
+
+#[test]
+#[should_panic(expected = "does not fit in the 12 bits of sort key field `pipeline`")]
+fn test_sort_key_field_overflow() {
+    let _ = OpaqueKey::default().with_pipeline(0x1000);
+}
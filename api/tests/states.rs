@@ -0,0 +1,79 @@
+use autograph_api::pipeline::{
+    BlendFactor, ColorBlendAttachmentState, ColorBlendState, ColorComponentFlags, CompareOp,
+    CullModeFlags, DepthBias, DepthStencilState, FrontFace, PolygonMode, RasterisationState,
+};
+
+#[test]
+fn test_rasterization_presets() {
+    let state = RasterisationState::CULL_BACK_CCW;
+    assert_eq!(state.cull_mode, CullModeFlags::BACK);
+    assert_eq!(state.front_face, FrontFace::CounterClockwise);
+    assert_eq!(state.polygon_mode, PolygonMode::Fill);
+
+    let state = state
+        .with_cull_mode(CullModeFlags::NONE)
+        .with_polygon_mode(PolygonMode::Line);
+    assert_eq!(
+        state,
+        RasterisationState {
+            front_face: FrontFace::CounterClockwise,
+            ..RasterisationState::WIREFRAME
+        }
+    );
+    match state.with_depth_bias(1.0, 0.0, 2.0).depth_bias {
+        DepthBias::Enabled { slope_factor, .. } => assert_eq!(slope_factor.into_inner(), 2.0),
+        DepthBias::Disabled => panic!("depth bias not enabled"),
+    }
+}
+
+#[test]
+fn test_depth_stencil_presets() {
+    assert_eq!(DepthStencilState::default(), DepthStencilState::DISABLED);
+    let state = DepthStencilState::LESS_EQUAL_WRITE;
+    assert!(state.depth_test_enable && state.depth_write_enable);
+    assert_eq!(state.depth_compare_op, CompareOp::LessOrEqual);
+    assert_eq!(
+        DepthStencilState::LESS_EQUAL_WRITE.with_depth_write(false),
+        DepthStencilState::LESS_EQUAL_READ_ONLY
+    );
+    assert_eq!(
+        DepthStencilState::LESS_WRITE.with_depth_compare_op(CompareOp::Less.reversed()),
+        DepthStencilState::GREATER_WRITE
+    );
+}
+
+#[test]
+fn test_color_blend_presets() {
+    match ColorBlendState::PREMULTIPLIED.attachment(3).unwrap() {
+        ColorBlendAttachmentState::Enabled {
+            src_color_blend_factor,
+            dst_color_blend_factor,
+            ..
+        } => {
+            assert_eq!(src_color_blend_factor, BlendFactor::One);
+            assert_eq!(dst_color_blend_factor, BlendFactor::OneMinusSrcAlpha);
+        }
+        ColorBlendAttachmentState::Disabled => panic!("blending not enabled"),
+    }
+
+    let masked = ColorBlendAttachmentState::ADDITIVE.with_color_write_mask(ColorComponentFlags::A);
+    match masked {
+        ColorBlendAttachmentState::Enabled {
+            dst_color_blend_factor,
+            color_write_mask,
+            ..
+        } => {
+            assert_eq!(dst_color_blend_factor, BlendFactor::One);
+            assert_eq!(color_write_mask, ColorComponentFlags::A);
+        }
+        ColorBlendAttachmentState::Disabled => panic!("blending not enabled"),
+    }
+
+    let attachments = [ColorBlendAttachmentState::DISABLED, masked];
+    let state = ColorBlendState::DISABLED
+        .with_separate_attachments(&attachments)
+        .with_blend_constants([0.5; 4]);
+    assert_eq!(state.attachment(1), Some(masked));
+    assert_eq!(state.attachment(2), None);
+    assert_eq!(state.blend_constants[0].into_inner(), 0.5);
+}
@@ -0,0 +1,108 @@
+use autograph_api::{
+    format::Format,
+    image::TextureSampler2dView,
+    null::{NullBackend, NullInstance},
+    pipeline::{
+        validate::{validate_dynamic_state, ValidationError},
+        Arguments, BareArgumentBlock, DepthStencilState, DynamicArgumentBlockBuilder,
+        DynamicSignatureBuilder, DynamicStateFlags, IntoArgumentBlock, SignatureDescription,
+        Viewport,
+    },
+    Api, Backend,
+};
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct Portal<'a, B: Backend> {
+    #[argument(viewport)]
+    viewport: Viewport,
+    #[argument(stencil_reference)]
+    portal_id: u32,
+    #[argument(descriptor)]
+    outline: TextureSampler2dView<'a, B>,
+}
+
+#[test]
+fn test_stencil_reference_argument() {
+    let sig = <Portal<NullBackend> as Arguments<NullBackend>>::SIGNATURE;
+    assert!(sig.has_stencil_reference);
+    assert_eq!(
+        sig.dynamic_state(),
+        DynamicStateFlags::VIEWPORT | DynamicStateFlags::STENCIL_REFERENCE
+    );
+
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let outline = arena.image_2d(Format::R8_UNORM, 64, 64).build();
+    let block = arena.create_typed_argument_block(Portal {
+        viewport: (64, 64).into(),
+        portal_id: 3,
+        outline: outline.sampled_linear(),
+    });
+    assert_eq!(BareArgumentBlock::from(block).0.stencil_reference, Some(3));
+}
+
+#[test]
+fn test_dynamic_stencil_reference_validation() {
+    let state = DepthStencilState::LESS_WRITE.with_dynamic_stencil_reference();
+    assert_eq!(state.dynamic_state(), DynamicStateFlags::STENCIL_REFERENCE);
+    assert_eq!(
+        DepthStencilState::LESS_WRITE.dynamic_state(),
+        DynamicStateFlags::empty()
+    );
+
+    const REFERENCE: SignatureDescription<'static> = SignatureDescription {
+        has_stencil_reference: true,
+        ..SignatureDescription::EMPTY
+    };
+    const ROOT: SignatureDescription<'static> = SignatureDescription {
+        inherited: &[&REFERENCE],
+        ..SignatureDescription::EMPTY
+    };
+    const TWICE: SignatureDescription<'static> = SignatureDescription {
+        inherited: &[&REFERENCE, &REFERENCE],
+        ..SignatureDescription::EMPTY
+    };
+
+    let mut errors = Vec::new();
+    validate_dynamic_state(&ROOT, state.dynamic_state(), &mut errors);
+    assert!(errors.is_empty());
+
+    // static reference, but the signature provides one
+    validate_dynamic_state(&ROOT, DynamicStateFlags::empty(), &mut errors);
+    // dynamic reference, but the signature has none
+    validate_dynamic_state(
+        &SignatureDescription::EMPTY,
+        state.dynamic_state(),
+        &mut errors,
+    );
+    validate_dynamic_state(&TWICE, state.dynamic_state(), &mut errors);
+    assert_eq!(errors.len(), 3);
+    match errors[0] {
+        ValidationError::UnexpectedDynamicState { state } => {
+            assert_eq!(state, DynamicStateFlags::STENCIL_REFERENCE)
+        }
+        ref e => panic!("unexpected error: {}", e),
+    }
+    match errors[1] {
+        ValidationError::MissingDynamicState { state } => {
+            assert_eq!(state, DynamicStateFlags::STENCIL_REFERENCE)
+        }
+        ref e => panic!("unexpected error: {}", e),
+    }
+    match errors[2] {
+        ValidationError::MultipleStencilReferences { count } => assert_eq!(count, 2),
+        ref e => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+#[should_panic(expected = "stencil reference of the argument block does not match")]
+fn test_dynamic_block_without_stencil_reference() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let signature = DynamicSignatureBuilder::new()
+        .viewport_count(0)
+        .stencil_reference(true)
+        .build(&arena);
+    DynamicArgumentBlockBuilder::new(signature).into_block(signature, &arena);
+}
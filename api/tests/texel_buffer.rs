@@ -0,0 +1,43 @@
+use autograph_api::{
+    buffer::BufferView,
+    descriptor::{Descriptor, ResourceBindingType, ResourceInterface},
+    format::Format,
+    pipeline::Arguments,
+    Backend, DummyBackend,
+};
+
+mod common;
+use crate::common::signature;
+
+#[derive(Arguments)]
+struct Skinning<'a, B: Backend> {
+    #[argument(texel_buffer)]
+    bone_matrices: BufferView<'a, B>,
+    #[argument(texel_buffer)]
+    bone_weights: BufferView<'a, B>,
+}
+
+#[test]
+fn test_texel_buffer_signature() {
+    let sig = signature::<Skinning<DummyBackend>, _>();
+    assert_eq!(sig.descriptors.len(), 2);
+    for (i, d) in sig.descriptors.iter().enumerate() {
+        assert_eq!(d.index, i as u32);
+        assert_eq!(d.ty, ResourceBindingType::TexelBuffer);
+        assert_eq!(d.data_format, Format::UNDEFINED);
+    }
+    assert_eq!(sig.descriptors[0].name, Some("bone_matrices"));
+    assert_eq!(sig.descriptors[1].name, Some("bone_weights"));
+}
+
+#[test]
+fn test_buffer_view_descriptor() {
+    let raw = &();
+    let view: BufferView<DummyBackend> =
+        unsafe { BufferView::from_raw(raw, Format::R32G32B32A32_SFLOAT) };
+    assert_eq!(view.format(), Format::R32G32B32A32_SFLOAT);
+    match view.into_descriptor() {
+        Descriptor::TexelBuffer { view } => assert_eq!(view as *const (), raw as *const ()),
+        d => panic!("unexpected descriptor: {:?}", d),
+    }
+}
@@ -0,0 +1,47 @@
+use autograph_api::{
+    image::RenderTargetView,
+    null::{NullBackend, NullInstance},
+    pipeline::{Arguments, Signature, Viewport},
+    Api, Backend, DummyBackend,
+};
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[derive(Arguments)]
+struct Targets<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+    #[argument(viewport)]
+    viewport: Viewport,
+}
+
+#[test]
+fn test_api_is_send_sync() {
+    assert_send_sync::<Api<DummyBackend>>();
+}
+
+#[test]
+fn test_concurrent_cached_signature() {
+    const THREADS: usize = 8;
+    let api: Arc<Api<NullBackend>> = Arc::new(Api::new(NullInstance::new()));
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let threads: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let api = api.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                let signature = api.get_cached_signature::<Targets<NullBackend>>();
+                signature.inner() as *const _ as usize
+            })
+        })
+        .collect();
+    let signatures: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+
+    assert!(signatures.iter().all(|&s| s == signatures[0]));
+    assert_eq!(api.instance().object_counts().signatures, 1);
+}
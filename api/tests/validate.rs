@@ -0,0 +1,441 @@
+use autograph_api::{
+    descriptor::{ResourceBinding, ResourceBindingType},
+    pipeline::{
+        validate::{
+            validate_descriptors, validate_dynamic_state, validate_multisample_state,
+            validate_rasterization_state, validate_stage_interface, validate_view_count,
+            ValidationError,
+        },
+        DynamicStateFlags, InterfaceVariableDescription, LineWidth, MultisampleState, PointSize,
+        RasterisationState, SampleShading, ShaderStageFlags, ShaderStageReflection,
+        SignatureDescription, ViewCount, ViewportState,
+    },
+    typedesc::{PrimitiveType, TypeDesc},
+    ApiProfile, DeviceLimits,
+};
+
+mod common;
+use crate::common::binding;
+
+const GLOBALS: SignatureDescription<'static> = SignatureDescription {
+    descriptors: &[binding(0, ResourceBindingType::ConstantBuffer, "globals")],
+    ..SignatureDescription::EMPTY
+};
+
+const MATERIAL: SignatureDescription<'static> = SignatureDescription {
+    inherited: &[&GLOBALS],
+    descriptors: &[binding(0, ResourceBindingType::Sampler, "sampler")],
+    ..SignatureDescription::EMPTY
+};
+
+#[test]
+fn test_descriptor_validation() {
+    let reflection = ShaderStageReflection {
+        stage: ShaderStageFlags::FRAGMENT,
+        descriptors: &[
+            ResourceBinding {
+                set: Some(0),
+                ..binding(0, ResourceBindingType::ConstantBuffer, "Globals")
+            },
+            ResourceBinding {
+                set: Some(1),
+                ..binding(0, ResourceBindingType::RwBuffer, "Lights")
+            },
+            ResourceBinding {
+                set: Some(1),
+                ..binding(1, ResourceBindingType::Sampler, "shadowSampler")
+            },
+        ],
+        vertex_input_attributes: &[],
+        fragment_outputs: &[],
+        fragment_output_attributes: &[],
+        stage_inputs: &[],
+        stage_outputs: &[],
+    };
+
+    let mut errors = Vec::new();
+    validate_descriptors(&MATERIAL, &reflection, &mut errors);
+    assert_eq!(errors.len(), 2);
+
+    match &errors[0] {
+        ValidationError::DescriptorTypeMismatch {
+            set: 1,
+            binding: 0,
+            shader_name,
+            field_name,
+            ..
+        } => {
+            assert_eq!(shader_name.as_ref().map(String::as_str), Some("Lights"));
+            assert_eq!(field_name.as_ref().map(String::as_str), Some("sampler"));
+        }
+        e => panic!("unexpected error: {}", e),
+    }
+    match &errors[1] {
+        ValidationError::DescriptorNotFound {
+            set: 1, binding: 1, ..
+        } => {}
+        e => panic!("unexpected error: {}", e),
+    }
+    assert!(errors[1].to_string().contains("shadowSampler"));
+}
+
+#[test]
+fn test_explicit_descriptor_bindings() {
+    // set 2, bindings 3 and 5: not the position of the descriptors in their block
+    const EXPLICIT: SignatureDescription<'static> = SignatureDescription {
+        inherited: &[&GLOBALS],
+        descriptors: &[
+            ResourceBinding {
+                set: Some(2),
+                ..binding(3, ResourceBindingType::ConstantBuffer, "params")
+            },
+            ResourceBinding {
+                set: Some(2),
+                ..binding(5, ResourceBindingType::RwBuffer, "output")
+            },
+        ],
+        ..SignatureDescription::EMPTY
+    };
+    let reflection = ShaderStageReflection {
+        stage: ShaderStageFlags::COMPUTE,
+        descriptors: &[
+            ResourceBinding {
+                set: Some(0),
+                ..binding(0, ResourceBindingType::ConstantBuffer, "Globals")
+            },
+            ResourceBinding {
+                set: Some(2),
+                ..binding(5, ResourceBindingType::RwBuffer, "Output")
+            },
+            ResourceBinding {
+                set: Some(2),
+                ..binding(3, ResourceBindingType::ConstantBuffer, "Params")
+            },
+        ],
+        vertex_input_attributes: &[],
+        fragment_outputs: &[],
+        fragment_output_attributes: &[],
+        stage_inputs: &[],
+        stage_outputs: &[],
+    };
+
+    let mut errors = Vec::new();
+    validate_descriptors(&EXPLICIT, &reflection, &mut errors);
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+}
+
+#[test]
+fn test_rasterization_state_validation() {
+    let limits = DeviceLimits {
+        max_image_dimension_1d: 16384,
+        max_image_dimension_2d: 16384,
+        max_image_dimension_3d: 2048,
+        max_image_dimension_cube: 16384,
+        max_image_array_layers: 2048,
+        max_color_attachments: 8,
+        max_samples: 8,
+        max_sampler_anisotropy: 16.0,
+        max_viewports: 16,
+        max_uniform_buffer_range: 65536,
+        min_uniform_buffer_offset_alignment: 256,
+        max_compute_work_group_count: [65535; 3],
+        line_width_range: [1.0, 8.0],
+        point_size_range: [1.0, 64.0],
+        max_multiview_view_count: 16,
+        native_multiview: false,
+        profile: ApiProfile::Desktop,
+        spirv_shaders: true,
+    };
+
+    let mut errors = Vec::new();
+    validate_rasterization_state(&RasterisationState::DEFAULT, &limits, &mut errors);
+    assert!(errors.is_empty());
+
+    // dynamic line widths and shader point sizes are not checked
+    let dynamic = RasterisationState {
+        line_width: LineWidth::Dynamic,
+        point_size: PointSize::Program,
+        ..RasterisationState::DEFAULT
+    };
+    validate_rasterization_state(&dynamic, &limits, &mut errors);
+    assert!(errors.is_empty());
+
+    let wide = RasterisationState {
+        line_width: LineWidth::Static(16.0.into()),
+        point_size: PointSize::Static(0.5.into()),
+        ..RasterisationState::DEFAULT
+    };
+    validate_rasterization_state(&wide, &limits, &mut errors);
+    assert_eq!(errors.len(), 2);
+    match errors[0] {
+        ValidationError::UnsupportedLineWidth { width, .. } => assert_eq!(width, 16.0),
+        ref e => panic!("unexpected error: {}", e),
+    }
+    match errors[1] {
+        ValidationError::UnsupportedPointSize { size, .. } => assert_eq!(size, 0.5),
+        ref e => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn test_dynamic_state_validation() {
+    // viewport provided by an inherited signature
+    const VIEWPORT: SignatureDescription<'static> = SignatureDescription {
+        num_viewports: 1,
+        ..SignatureDescription::EMPTY
+    };
+    const ROOT: SignatureDescription<'static> = SignatureDescription {
+        inherited: &[&VIEWPORT],
+        ..SignatureDescription::EMPTY
+    };
+
+    let mut errors = Vec::new();
+    validate_dynamic_state(&ROOT, ViewportState::default().dynamic_state(), &mut errors);
+    assert!(errors.is_empty());
+
+    // dynamic scissors, but the signature has none
+    validate_dynamic_state(
+        &ROOT,
+        ViewportState::DYNAMIC_VIEWPORT_SCISSOR.dynamic_state(),
+        &mut errors,
+    );
+    // static viewports, but the signature has one
+    validate_dynamic_state(&ROOT, DynamicStateFlags::empty(), &mut errors);
+    assert_eq!(errors.len(), 2);
+    match errors[0] {
+        ValidationError::MissingDynamicState { state } => {
+            assert_eq!(state, DynamicStateFlags::SCISSOR)
+        }
+        ref e => panic!("unexpected error: {}", e),
+    }
+    match errors[1] {
+        ValidationError::UnexpectedDynamicState { state } => {
+            assert_eq!(state, DynamicStateFlags::VIEWPORT)
+        }
+        ref e => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn test_multisample_state_validation() {
+    let mut errors = Vec::new();
+    validate_multisample_state(&MultisampleState::default(), &mut errors);
+    assert!(errors.is_empty());
+
+    // alpha-tested foliage: 4x MSAA, shading every sample, first two samples only
+    let msaa = MultisampleState {
+        rasterization_samples: 4,
+        sample_shading: SampleShading::Enabled {
+            min_sample_shading: 1.0.into(),
+        },
+        sample_mask: 0b0011,
+        ..MultisampleState::default()
+    };
+    validate_multisample_state(&msaa, &mut errors);
+    assert!(errors.is_empty());
+    assert_eq!(
+        MultisampleState {
+            sample_mask: MultisampleState::ALL_SAMPLES,
+            ..msaa
+        }
+        .effective_sample_mask(),
+        0b1111
+    );
+
+    validate_multisample_state(
+        &MultisampleState {
+            sample_mask: 0b1_0000,
+            ..msaa
+        },
+        &mut errors,
+    );
+    match errors.as_slice() {
+        [ValidationError::SampleMaskOutOfRange {
+            sample_mask: 0b1_0000,
+            samples: 4,
+        }, ValidationError::EmptySampleMask { .. }] => {}
+        _ => panic!("expected an out-of-range and empty sample mask"),
+    }
+
+    errors.clear();
+    validate_multisample_state(
+        &MultisampleState {
+            rasterization_samples: 3,
+            ..msaa
+        },
+        &mut errors,
+    );
+    validate_multisample_state(
+        &MultisampleState {
+            sample_shading: SampleShading::Enabled {
+                min_sample_shading: 1.5.into(),
+            },
+            ..msaa
+        },
+        &mut errors,
+    );
+    assert_eq!(errors.len(), 2);
+    match errors[0] {
+        ValidationError::InvalidSampleCount { samples: 3 } => {}
+        _ => panic!("expected an invalid sample count"),
+    }
+    match errors[1] {
+        ValidationError::InvalidMinSampleShading { .. } => {}
+        _ => panic!("expected an invalid sample shading rate"),
+    }
+}
+
+#[test]
+fn test_view_count_validation() {
+    let limits = DeviceLimits {
+        max_image_dimension_1d: 16384,
+        max_image_dimension_2d: 16384,
+        max_image_dimension_3d: 2048,
+        max_image_dimension_cube: 16384,
+        max_image_array_layers: 2048,
+        max_color_attachments: 8,
+        max_samples: 8,
+        max_sampler_anisotropy: 16.0,
+        max_viewports: 16,
+        max_uniform_buffer_range: 65536,
+        min_uniform_buffer_offset_alignment: 256,
+        max_compute_work_group_count: [65535; 3],
+        line_width_range: [1.0, 8.0],
+        point_size_range: [1.0, 64.0],
+        max_multiview_view_count: 2,
+        native_multiview: false,
+        profile: ApiProfile::Desktop,
+        spirv_shaders: true,
+    };
+
+    let mut errors = Vec::new();
+    validate_view_count(ViewCount::default(), &limits, &mut errors);
+    validate_view_count(ViewCount::STEREO, &limits, &mut errors);
+    assert!(errors.is_empty());
+    assert!(!ViewCount::MONO.is_multiview());
+    assert!(ViewCount::STEREO.is_multiview());
+
+    validate_view_count(ViewCount(0), &limits, &mut errors);
+    validate_view_count(ViewCount(4), &limits, &mut errors);
+    assert_eq!(errors.len(), 2);
+    match errors[0] {
+        ValidationError::UnsupportedViewCount { view_count: 0, .. } => {}
+        ref e => panic!("unexpected error: {}", e),
+    }
+    match errors[1] {
+        ValidationError::UnsupportedViewCount {
+            view_count: 4,
+            max_view_count: 2,
+        } => {}
+        ref e => panic!("unexpected error: {}", e),
+    }
+}
+
+const VEC2: TypeDesc<'static> = TypeDesc::Vector {
+    elem_ty: PrimitiveType::Float,
+    len: 2,
+};
+const VEC3: TypeDesc<'static> = TypeDesc::Vector {
+    elem_ty: PrimitiveType::Float,
+    len: 3,
+};
+const VEC4: TypeDesc<'static> = TypeDesc::Vector {
+    elem_ty: PrimitiveType::Float,
+    len: 4,
+};
+const VEC2_TRIANGLE: TypeDesc<'static> = TypeDesc::Array {
+    elem_ty: &VEC2,
+    len: 3,
+};
+const VEC3_TRIANGLE: TypeDesc<'static> = TypeDesc::Array {
+    elem_ty: &VEC3,
+    len: 3,
+};
+
+const fn interface(
+    location: u32,
+    ty: &'static TypeDesc<'static>,
+    name: &'static str,
+) -> InterfaceVariableDescription<'static> {
+    InterfaceVariableDescription {
+        location,
+        ty,
+        name: Some(name),
+    }
+}
+
+const fn stage_reflection(
+    stage: ShaderStageFlags,
+    stage_inputs: &'static [InterfaceVariableDescription<'static>],
+    stage_outputs: &'static [InterfaceVariableDescription<'static>],
+) -> ShaderStageReflection<'static> {
+    ShaderStageReflection {
+        stage,
+        descriptors: &[],
+        vertex_input_attributes: &[],
+        fragment_outputs: &[],
+        fragment_output_attributes: &[],
+        stage_inputs,
+        stage_outputs,
+    }
+}
+
+#[test]
+fn test_stage_interface_validation() {
+    const VERTEX: ShaderStageReflection<'static> = stage_reflection(
+        ShaderStageFlags::VERTEX,
+        &[],
+        &[
+            interface(0, &VEC3, "v_normal"),
+            interface(1, &VEC2, "v_texcoord"),
+        ],
+    );
+    // per-vertex inputs of a geometry shader are arrays
+    const GEOMETRY: ShaderStageReflection<'static> = stage_reflection(
+        ShaderStageFlags::GEOMETRY,
+        &[
+            interface(0, &VEC3_TRIANGLE, "v_normal"),
+            interface(1, &VEC2_TRIANGLE, "v_texcoord"),
+        ],
+        &[],
+    );
+    const FRAGMENT: ShaderStageReflection<'static> = stage_reflection(
+        ShaderStageFlags::FRAGMENT,
+        &[
+            interface(0, &VEC4, "v_normal"),
+            interface(1, &VEC2, "v_texcoord"),
+            interface(2, &VEC4, "v_color"),
+        ],
+        &[],
+    );
+
+    let mut errors = Vec::new();
+    validate_stage_interface(&VERTEX, &GEOMETRY, &mut errors);
+    assert!(errors.is_empty());
+
+    validate_stage_interface(&VERTEX, &FRAGMENT, &mut errors);
+    assert_eq!(errors.len(), 2);
+    match &errors[0] {
+        ValidationError::StageInterfaceTypeMismatch {
+            location: 0,
+            output_stage,
+            input_stage,
+            shader_name,
+            ..
+        } => {
+            assert_eq!(*output_stage, ShaderStageFlags::VERTEX);
+            assert_eq!(*input_stage, ShaderStageFlags::FRAGMENT);
+            assert_eq!(shader_name.as_ref().map(String::as_str), Some("v_normal"));
+        }
+        e => panic!("unexpected error: {}", e),
+    }
+    match &errors[1] {
+        ValidationError::StageInputNotProduced {
+            location: 2,
+            previous_stage,
+            ..
+        } => assert_eq!(*previous_stage, ShaderStageFlags::VERTEX),
+        e => panic!("unexpected error: {}", e),
+    }
+    assert!(errors[1].to_string().contains("`v_color`"));
+}
@@ -0,0 +1,72 @@
+use autograph_api::{
+    format::Format,
+    pipeline::validate::is_vertex_format_compatible,
+    typedesc::{PrimitiveType, TypeDesc},
+    vertex::{Half, Normalized, VertexData},
+};
+
+#[repr(C)]
+#[derive(VertexData, Copy, Clone)]
+struct PackedVertex {
+    position: [f32; 3],
+    normal: Normalized<[i16; 4]>,
+    #[vertex(format = "R16G16_SFLOAT")]
+    texcoords: [u16; 2],
+    color: Normalized<[u8; 4]>,
+}
+
+#[test]
+fn test_packed_vertex_layout() {
+    let layout = <PackedVertex as VertexData>::LAYOUT;
+    let formats: Vec<_> = layout.elements.iter().map(|e| e.format).collect();
+    assert_eq!(
+        formats,
+        &[
+            Format::R32G32B32_SFLOAT,
+            Format::R16G16B16A16_SNORM,
+            Format::R16G16_SFLOAT,
+            Format::R8G8B8A8_UNORM
+        ]
+    );
+    let offsets: Vec<_> = layout.elements.iter().map(|e| e.offset).collect();
+    assert_eq!(offsets, &[0, 12, 20, 24]);
+    assert_eq!(layout.stride, 28);
+}
+
+#[test]
+fn test_vertex_format_compatibility() {
+    let vec4 = TypeDesc::Vector {
+        elem_ty: PrimitiveType::Float,
+        len: 4,
+    };
+    let ivec2 = TypeDesc::Vector {
+        elem_ty: PrimitiveType::Int,
+        len: 2,
+    };
+    assert!(is_vertex_format_compatible(
+        Format::R16G16B16A16_SNORM,
+        &vec4
+    ));
+    assert!(is_vertex_format_compatible(
+        Format::R16G16B16A16_SFLOAT,
+        &vec4
+    ));
+    assert!(!is_vertex_format_compatible(
+        Format::R16G16B16A16_SINT,
+        &vec4
+    ));
+    assert!(!is_vertex_format_compatible(Format::R16G16_SFLOAT, &vec4));
+    assert!(is_vertex_format_compatible(Format::R16G16_SINT, &ivec2));
+    assert!(!is_vertex_format_compatible(Format::R16G16_UINT, &ivec2));
+}
+
+#[test]
+fn test_half_conversion() {
+    for &v in &[0.0f32, 1.0, -2.5, 0.333_251_95, 65504.0, 6.103_515_6e-5] {
+        assert_eq!(Half::from_f32(v).to_f32(), v);
+    }
+    assert_eq!(Half::from_f32(1.0).0, 0x3c00);
+    assert_eq!(Half::from_f32(1.0e6).to_f32(), std::f32::INFINITY);
+    // smallest subnormal
+    assert_eq!(Half::from_f32(5.960_464_5e-8).0, 0x0001);
+}
@@ -0,0 +1,130 @@
+use autograph_api::{
+    null::{NullBackend, NullInstance},
+    pipeline::Arguments,
+    vertex::{VertexBufferView, VertexData},
+    vertex_pool::{VertexPool, VertexPoolSlice, VertexPoolStats, VertexRange},
+    Api, Backend,
+};
+
+#[repr(C)]
+#[derive(VertexData, Copy, Clone, Debug)]
+struct Vertex {
+    position: [f32; 3],
+    texcoords: [f32; 2],
+}
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct Chunk<'a, B: Backend> {
+    #[argument(vertex_buffer)]
+    vertices: VertexPoolSlice<'a, B, Vertex>,
+}
+
+const V: Vertex = Vertex {
+    position: [0.0; 3],
+    texcoords: [0.0; 2],
+};
+
+#[test]
+fn test_allocate_and_free() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let mut pool = VertexPool::<_, Vertex>::new(&arena, 100);
+    assert_eq!(api.instance().live_buffers()[0].size, 100 * 20);
+
+    let a = pool.insert(&[V; 30]).unwrap();
+    let b = pool.insert(&[V; 30]).unwrap();
+    let c = pool.insert(&[V; 30]).unwrap();
+    assert_eq!(
+        a,
+        VertexRange {
+            start: 0,
+            count: 30
+        }
+    );
+    assert_eq!(b.start, 30);
+    assert_eq!(c.start, 60);
+    assert_eq!(api.instance().object_counts().buffer_updates, 3);
+    assert!(pool.allocate(20).is_none());
+
+    // freed ranges are reused first-fit, and merged with their neighbours
+    pool.free(a);
+    pool.free(c);
+    assert_eq!(
+        pool.stats(),
+        VertexPoolStats {
+            capacity: 100,
+            allocated: 30,
+            allocations: 1,
+            free_ranges: 2,
+            largest_free_range: 40,
+            fragmentation_failures: 0,
+        }
+    );
+    assert!(pool.allocate(50).is_none());
+    assert_eq!(pool.stats().fragmentation_failures, 1);
+    assert_eq!(pool.allocate(10).unwrap().start, 0);
+    pool.free(VertexRange {
+        start: 0,
+        count: 10,
+    });
+    pool.free(b);
+    let stats = pool.stats();
+    assert_eq!(stats.free_ranges, 1);
+    assert_eq!(stats.largest_free_range, 100);
+    assert_eq!(stats.fragmentation(), 0.0);
+    assert_eq!(pool.allocate(100).unwrap().start, 0);
+
+    pool.clear();
+    assert_eq!(pool.stats().allocated, 0);
+}
+
+#[test]
+fn test_fragmentation() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let mut pool = VertexPool::<_, Vertex>::new(&arena, 40);
+    let ranges: Vec<_> = (0..4).map(|_| pool.allocate(10).unwrap()).collect();
+    pool.free(ranges[0]);
+    pool.free(ranges[2]);
+    let stats = pool.stats();
+    assert_eq!(stats.free(), 20);
+    assert_eq!(stats.fragmentation(), 0.5);
+}
+
+#[test]
+#[should_panic(expected = "already free")]
+fn test_double_free() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let mut pool = VertexPool::<_, Vertex>::new(&arena, 40);
+    let range = pool.allocate(10).unwrap();
+    pool.free(range);
+    pool.free(range);
+}
+
+#[test]
+#[should_panic(expected = "outside of a buffer")]
+fn test_write_outside_buffer() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let pool = VertexPool::<_, Vertex>::new(&arena, 4);
+    api.update_buffer(pool.buffer(), 60, &[0; 40]);
+}
+
+#[test]
+fn test_slice_vertex_buffer() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let mut pool = VertexPool::<_, Vertex>::new(&arena, 100);
+    pool.allocate(8).unwrap();
+    let range = pool.insert(&[V; 3]).unwrap();
+    let params = range.draw_params();
+    assert_eq!((params.vertex_count, params.first_vertex), (3, 0));
+
+    let chunk = Chunk {
+        vertices: pool.slice(range),
+    };
+    let view: VertexBufferView<_> = chunk.vertices.into();
+    assert_eq!(view.offset(), 8 * 20);
+    assert_eq!(view.stride(), 20);
+}
@@ -0,0 +1,99 @@
+use autograph_api::{
+    format::Format,
+    pipeline::{
+        validate::{validate_vertex_inputs, vertex_attribute_locations, ValidationError},
+        SignatureDescription, VertexInputAttributeDescription, VertexInputBinding,
+    },
+    typedesc::{PrimitiveType, TypeDesc},
+    vertex::{VertexInputRate, VertexLayout, VertexLayoutElement},
+};
+
+const VEC2: TypeDesc<'static> = TypeDesc::Vector {
+    elem_ty: PrimitiveType::Float,
+    len: 2,
+};
+
+const VEC3: TypeDesc<'static> = TypeDesc::Vector {
+    elem_ty: PrimitiveType::Float,
+    len: 3,
+};
+
+const fn element(format: Format, offset: u32, name: &'static str) -> VertexLayoutElement<'static> {
+    VertexLayoutElement {
+        semantic: None,
+        format,
+        offset,
+        name: Some(name),
+    }
+}
+
+const fn input(
+    location: u32,
+    ty: &'static TypeDesc<'static>,
+    name: &'static str,
+) -> VertexInputAttributeDescription<'static> {
+    VertexInputAttributeDescription {
+        location: Some(location),
+        ty,
+        semantic: None,
+        name: Some(name),
+    }
+}
+
+// position, normal, texcoord
+const MESH: SignatureDescription<'static> = SignatureDescription {
+    vertex_inputs: &[VertexInputBinding {
+        layout: VertexLayout {
+            elements: &[
+                element(Format::R32G32B32_SFLOAT, 0, "position"),
+                element(Format::R32G32B32_SFLOAT, 12, "normal"),
+                element(Format::R32G32_SFLOAT, 24, "texcoord"),
+            ],
+            stride: 32,
+        },
+        rate: VertexInputRate::Vertex,
+        base_location: None,
+    }],
+    ..SignatureDescription::EMPTY
+};
+
+#[test]
+fn test_locations_matched_by_name() {
+    let inputs = [input(0, &VEC3, "a_position"), input(1, &VEC3, "a_normal")];
+    assert_eq!(
+        vertex_attribute_locations(&MESH, &inputs),
+        &[Some(0), Some(1), None]
+    );
+}
+
+#[test]
+fn test_remapped_locations() {
+    // texcoord first, normal unused
+    let inputs = [
+        input(0, &VEC2, "a_texcoord"),
+        input(1, &VEC3, "in_position"),
+    ];
+    assert_eq!(
+        vertex_attribute_locations(&MESH, &inputs),
+        &[Some(1), None, Some(0)]
+    );
+    let mut errors = Vec::new();
+    validate_vertex_inputs(&MESH, &inputs, &mut errors);
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_sequential_locations() {
+    // no match by name: locations follow the order of the fields
+    let inputs = [input(0, &VEC3, "a_pos"), input(3, &VEC3, "a_tangent")];
+    assert_eq!(
+        vertex_attribute_locations(&MESH, &inputs),
+        &[Some(0), Some(1), Some(2)]
+    );
+    let mut errors = Vec::new();
+    validate_vertex_inputs(&MESH, &inputs, &mut errors);
+    match errors.as_slice() {
+        [ValidationError::VertexInputNotFound { location: 3, .. }] => {}
+        other => panic!("unexpected errors: {:?}", other),
+    }
+}
@@ -0,0 +1,113 @@
+use autograph_api::{
+    command::DrawParams,
+    format::Format,
+    image::{MipmapsOption, RenderTargetView},
+    null::{NullBackend, NullInstance},
+    pipeline::{
+        validate::{validate_static_viewports, ValidationError},
+        Arguments, ColorBlendState, DepthStencilState, GraphicsPipelineCreateInfo,
+        InputAssemblyState, MultisampleState, RasterisationState, Scissor, ScissorRect, Scissors,
+        TypedArgumentBlock, ViewCount, Viewport, ViewportState, Viewports,
+    },
+    Api, Backend,
+};
+
+mod common;
+use crate::common::{FRAGMENT, VERTEX};
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct Target<'a, B: Backend> {
+    #[argument(render_target)]
+    color: RenderTargetView<'a, B>,
+}
+
+#[derive(Copy, Clone, Debug, Arguments)]
+struct Pass<'a, B: Backend> {
+    #[argument(inherit)]
+    target: TypedArgumentBlock<'a, B, Target<'a, B>>,
+}
+
+#[test]
+fn test_full_viewport_and_scissor() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena.image_2d(Format::R8G8B8A8_UNORM, 640, 480).build();
+
+    assert_eq!(Viewport::full(image), Viewport::from((640, 480)));
+    assert_eq!(
+        Scissor::full(image),
+        Scissor::Enabled(ScissorRect {
+            x: 0,
+            y: 0,
+            width: 640,
+            height: 480
+        })
+    );
+
+    // size of the mipmap level of the view
+    let mip = arena
+        .image_2d(Format::R8G8B8A8_UNORM, 640, 480)
+        .mipmaps(MipmapsOption::AllocateCount(4))
+        .build();
+    assert_eq!(RenderTargetView::from(mip.mipmap(3)).size(), (80, 60));
+}
+
+#[test]
+fn test_static_viewport_validation() {
+    let mut errors = Vec::new();
+    validate_static_viewports(&[Viewport::from((640, 480))], (640, 480), &mut errors);
+    assert!(errors.is_empty());
+
+    // viewports created before a resize of the window
+    validate_static_viewports(
+        &[Viewport::from((320, 240)), Viewport::from((1280, 720))],
+        (640, 480),
+        &mut errors,
+    );
+    assert_eq!(errors.len(), 1);
+    match errors[0] {
+        ValidationError::ViewportOutOfBounds {
+            index,
+            attachment_size,
+            ..
+        } => {
+            assert_eq!(index, 1);
+            assert_eq!(attachment_size, (640, 480));
+        }
+        ref e => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "static viewport #0 (1280x720 at (0, 0)) extends beyond")
+)]
+fn test_static_viewport_out_of_bounds_on_draw() {
+    let api: Api<NullBackend> = Api::new(NullInstance::new());
+    let arena = api.create_arena();
+    let image = arena.image_2d(Format::R8G8B8A8_UNORM, 640, 480).build();
+
+    let viewports = [Viewport::from((1280, 720))];
+    let pipeline =
+        arena.create_graphics_pipeline::<Pass<NullBackend>>(&GraphicsPipelineCreateInfo {
+            shader_stages: arena.create_vertex_fragment_shader_stages(VERTEX, FRAGMENT),
+            viewport_state: ViewportState {
+                viewports: Viewports::Static(&viewports),
+                scissors: Scissors::Static(&[Scissor::Disabled]),
+            },
+            rasterization_state: RasterisationState::default(),
+            multisample_state: MultisampleState::default(),
+            depth_stencil_state: DepthStencilState::default(),
+            input_assembly_state: InputAssemblyState::default(),
+            color_blend_state: ColorBlendState::DISABLED,
+            view_count: ViewCount::default(),
+        });
+
+    // the render target is provided by an inherited block
+    let target = arena.create_typed_argument_block(Target {
+        color: image.into(),
+    });
+    let mut cmdbuf = api.create_command_buffer();
+    cmdbuf.draw(0, &arena, pipeline, Pass { target }, DrawParams::quad());
+}
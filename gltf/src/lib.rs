@@ -0,0 +1,559 @@
+//! Loader for glTF 2.0 scenes (https://www.khronos.org/gltf/).
+//!
+//! [load] parses a glTF file (and its external or embedded buffers and images), uploads
+//! vertex data, indices and textures into an [Arena], and returns a [Scene]: a flattened node
+//! hierarchy with world transforms, meshes, and materials.
+//!
+//! Each [Primitive] carries an argument block with its vertex and index buffers and its material
+//! (constant parameters and textures), which can be inherited by the arguments of a pipeline:
+//! ```ignore
+//! #[derive(Arguments)]
+//! struct MeshArguments<'a, B: Backend> {
+//!     #[argument(inherit)]
+//!     targets: TypedArgumentBlock<'a, B, RenderTargets<'a, B>>,
+//!     #[argument(inherit)]
+//!     primitive: TypedArgumentBlock<'a, B, PrimitiveArguments<'a, B>>,
+//!     #[argument(descriptor)]
+//!     per_object: TypedConstantBufferView<'a, B, PerObject>,
+//! }
+//!
+//! let scene = autograph_gltf::load(&arena, "models/sponza/Sponza.gltf")?;
+//! for (world, primitive) in scene.primitives() {
+//!     // upload `world` in `per_object`...
+//!     cmdbuf.draw_indexed(0x100, &arena, pipeline, MeshArguments {
+//!         targets, primitive: primitive.arguments, per_object,
+//!     }, primitive.draw_params());
+//! }
+//! ```
+//!
+//! Restrictions:
+//! - only triangle lists are loaded, other primitives are skipped with a warning;
+//! - only the first texture coordinate set (`TEXCOORD_0`) is loaded;
+//! - animations, skins, morph targets and cameras are ignored.
+use autograph_api::{
+    buffer::{Buffer, StructuredBufferData, TypedConstantBufferView},
+    command::DrawIndexedParams,
+    format::Format,
+    glm,
+    image::{
+        Dimensions, Filter, Image2d, ImageUsageFlags, MipmapsOption, SamplerAddressMode,
+        SamplerDescription, SamplerMipmapMode, TextureSampler2dView,
+    },
+    pipeline::{Arguments, TypedArgumentBlock},
+    vertex::VertexData,
+    AliasScope, Arena, Backend,
+};
+use log::warn;
+use std::{collections::HashMap, path::Path};
+
+pub use gltf::material::AlphaMode;
+
+/// Vertex of a glTF mesh.
+///
+/// Missing attributes are replaced by default values: the normal is `+Z`,
+/// the tangent is `+X` (with a positive handedness) and the texture coordinates are zero.
+#[derive(Copy, Clone, Debug, VertexData)]
+#[repr(C)]
+pub struct GltfVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    /// Tangent in XYZ, handedness of the bitangent in W.
+    pub tangent: [f32; 4],
+    pub texcoord: [f32; 2],
+}
+
+/// Constant parameters of a metallic-roughness material.
+#[derive(Copy, Clone, Debug, StructuredBufferData)]
+#[repr(C)]
+pub struct MaterialParams {
+    pub base_color_factor: [f32; 4],
+    /// Emissive color in XYZ, W unused.
+    pub emissive_factor: [f32; 4],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub normal_scale: f32,
+    pub occlusion_strength: f32,
+}
+
+/// Arguments of a material.
+///
+/// Textures that are not specified by the material are replaced by the fallback textures of the
+/// renderer, so that shaders can always sample all textures and multiply with the factors.
+#[derive(Copy, Clone, Debug, Arguments)]
+pub struct MaterialArguments<'a, B: Backend> {
+    #[argument(descriptor)]
+    pub params: TypedConstantBufferView<'a, B, MaterialParams>,
+    /// Base color (sRGB) in RGB, alpha in A.
+    #[argument(descriptor, fallback = "white")]
+    pub base_color: Option<TextureSampler2dView<'a, B>>,
+    /// Roughness in G, metalness in B.
+    #[argument(descriptor, fallback = "white")]
+    pub metallic_roughness: Option<TextureSampler2dView<'a, B>>,
+    /// Tangent-space normal map.
+    #[argument(descriptor, fallback = "normal-up")]
+    pub normal: Option<TextureSampler2dView<'a, B>>,
+    /// Ambient occlusion in R.
+    #[argument(descriptor, fallback = "white")]
+    pub occlusion: Option<TextureSampler2dView<'a, B>>,
+    /// Emissive color (sRGB).
+    #[argument(descriptor, fallback = "white")]
+    pub emissive: Option<TextureSampler2dView<'a, B>>,
+}
+
+/// Arguments of a primitive: vertex and index buffers, and material.
+#[derive(Copy, Clone, Debug, Arguments)]
+pub struct PrimitiveArguments<'a, B: Backend> {
+    #[argument(inherit)]
+    pub material: TypedArgumentBlock<'a, B, MaterialArguments<'a, B>>,
+    #[argument(vertex_buffer)]
+    pub vertices: Buffer<'a, B, [GltfVertex]>,
+    #[argument(index_buffer)]
+    pub indices: Buffer<'a, B, [u32]>,
+}
+
+/// Axis-aligned bounding box.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: glm::Vec3,
+    pub max: glm::Vec3,
+}
+
+impl Bounds {
+    /// Returns the bounding box of a set of points, or `None` if there are no points.
+    pub fn from_points(points: &[[f32; 3]]) -> Option<Bounds> {
+        let first = glm::Vec3::from(*points.first()?);
+        Some(points.iter().skip(1).fold(
+            Bounds {
+                min: first,
+                max: first,
+            },
+            |b, p| {
+                let p = glm::Vec3::from(*p);
+                Bounds {
+                    min: glm::min2(&b.min, &p),
+                    max: glm::max2(&b.max, &p),
+                }
+            },
+        ))
+    }
+
+    /// Returns the bounding box of this box transformed by `m`.
+    pub fn transform(&self, m: &glm::Mat4) -> Bounds {
+        let corners = (0..8).map(|i| {
+            let c = glm::vec3(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            );
+            let c = m * glm::vec4(c.x, c.y, c.z, 1.0);
+            [c.x, c.y, c.z]
+        });
+        Bounds::from_points(&corners.collect::<Vec<_>>()).unwrap()
+    }
+}
+
+/// A material of the scene.
+#[derive(Copy, Clone, Debug)]
+pub struct Material<'a, B: Backend> {
+    pub alpha_mode: AlphaMode,
+    /// Alpha threshold when `alpha_mode` is `Mask`.
+    pub alpha_cutoff: f32,
+    pub double_sided: bool,
+    pub arguments: TypedArgumentBlock<'a, B, MaterialArguments<'a, B>>,
+}
+
+/// A triangle list with a material.
+#[derive(Copy, Clone, Debug)]
+pub struct Primitive<'a, B: Backend> {
+    pub arguments: TypedArgumentBlock<'a, B, PrimitiveArguments<'a, B>>,
+    /// Index of the material in [Scene::materials].
+    pub material: usize,
+    pub index_count: u32,
+    /// Bounds of the vertices in the space of the mesh.
+    pub bounds: Bounds,
+}
+
+impl<'a, B: Backend> Primitive<'a, B> {
+    /// Returns the parameters to draw all the triangles of the primitive.
+    pub fn draw_params(&self) -> DrawIndexedParams {
+        DrawIndexedParams {
+            first_index: 0,
+            index_count: self.index_count,
+            vertex_offset: 0,
+            first_instance: 0,
+            instance_count: 1,
+        }
+    }
+}
+
+/// A set of primitives, possibly instanced by several nodes.
+#[derive(Clone, Debug)]
+pub struct Mesh<'a, B: Backend> {
+    pub name: Option<String>,
+    pub primitives: Vec<Primitive<'a, B>>,
+}
+
+/// A node of the scene graph.
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub name: Option<String>,
+    /// Transform relative to the parent node.
+    pub local_transform: glm::Mat4,
+    /// Transform relative to the root of the scene.
+    pub world_transform: glm::Mat4,
+    /// Index of the mesh in [Scene::meshes].
+    pub mesh: Option<usize>,
+    /// Indices of the child nodes in [Scene::nodes].
+    pub children: Vec<usize>,
+}
+
+/// A scene loaded from a glTF file, with all its resources allocated in an arena.
+#[derive(Clone, Debug)]
+pub struct Scene<'a, B: Backend> {
+    /// All nodes of the default scene (or of the first scene if there is no default scene).
+    pub nodes: Vec<Node>,
+    /// Indices of the root nodes in `nodes`.
+    pub roots: Vec<usize>,
+    pub meshes: Vec<Mesh<'a, B>>,
+    pub materials: Vec<Material<'a, B>>,
+    pub images: Vec<Image2d<'a, B>>,
+}
+
+impl<'a, B: Backend> Scene<'a, B> {
+    /// Returns all primitives instanced in the scene, with their world transform.
+    pub fn primitives<'s>(
+        &'s self,
+    ) -> impl Iterator<Item = (&'s glm::Mat4, &'s Primitive<'a, B>)> + 's {
+        self.nodes.iter().flat_map(move |node| {
+            let primitives = match node.mesh {
+                Some(mesh) => &self.meshes[mesh].primitives[..],
+                None => &[],
+            };
+            primitives.iter().map(move |p| (&node.world_transform, p))
+        })
+    }
+
+    /// Returns the bounds of the scene in world space, or `None` if the scene is empty.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.primitives()
+            .map(|(world, p)| p.bounds.transform(world))
+            .fold(None, |acc: Option<Bounds>, b| {
+                Some(match acc {
+                    Some(acc) => Bounds {
+                        min: glm::min2(&acc.min, &b.min),
+                        max: glm::max2(&acc.max, &b.max),
+                    },
+                    None => b,
+                })
+            })
+    }
+}
+
+/// Loads the default scene of a glTF file (`.gltf` or `.glb`) into the arena.
+pub fn load<'a, B: Backend>(
+    arena: &'a Arena<'a, B>,
+    path: impl AsRef<Path>,
+) -> Result<Scene<'a, B>, gltf::Error> {
+    let (document, buffers, images) = gltf::import(path)?;
+    let mut loader = Loader {
+        arena,
+        buffers: &buffers,
+        images: &images,
+        srgb_images: srgb_images(&document),
+        created_images: HashMap::new(),
+        scene_images: Vec::new(),
+        materials: Vec::new(),
+        material_indices: HashMap::new(),
+    };
+
+    let meshes = document
+        .meshes()
+        .map(|mesh| loader.load_mesh(&mesh))
+        .collect();
+
+    let mut nodes = Vec::new();
+    let mut roots = Vec::new();
+    if let Some(scene) = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    {
+        for node in scene.nodes() {
+            let root = add_node(&mut nodes, &node, &glm::Mat4::identity());
+            roots.push(root);
+        }
+    }
+
+    Ok(Scene {
+        nodes,
+        roots,
+        meshes,
+        materials: loader.materials,
+        images: loader.scene_images,
+    })
+}
+
+/// Adds a node and its children to the flattened node list, returns the index of the node.
+fn add_node(nodes: &mut Vec<Node>, node: &gltf::Node, parent_transform: &glm::Mat4) -> usize {
+    let local_transform = glm::Mat4::from(node.transform().matrix());
+    let world_transform = parent_transform * local_transform;
+    let index = nodes.len();
+    nodes.push(Node {
+        name: node.name().map(|s| s.to_owned()),
+        local_transform,
+        world_transform,
+        mesh: node.mesh().map(|m| m.index()),
+        children: Vec::new(),
+    });
+    let children = node
+        .children()
+        .map(|child| add_node(nodes, &child, &world_transform))
+        .collect();
+    nodes[index].children = children;
+    index
+}
+
+/// Returns the indices of the images that contain color data (base color and emissive),
+/// which are loaded with a sRGB format.
+fn srgb_images(document: &gltf::Document) -> Vec<usize> {
+    let mut srgb = Vec::new();
+    for material in document.materials() {
+        let color = material.pbr_metallic_roughness().base_color_texture();
+        let emissive = material.emissive_texture();
+        for info in color.iter().chain(emissive.iter()) {
+            srgb.push(info.texture().source().index());
+        }
+    }
+    srgb
+}
+
+fn image_format(format: gltf::image::Format, srgb: bool) -> Format {
+    use gltf::image::Format as F;
+    match (format, srgb) {
+        (F::R8, _) => Format::R8_UNORM,
+        (F::R8G8, _) => Format::R8G8_UNORM,
+        (F::R8G8B8, false) => Format::R8G8B8_UNORM,
+        (F::R8G8B8, true) => Format::R8G8B8_SRGB,
+        (F::R8G8B8A8, false) => Format::R8G8B8A8_UNORM,
+        (F::R8G8B8A8, true) => Format::R8G8B8A8_SRGB,
+    }
+}
+
+fn sampler_description(sampler: &gltf::texture::Sampler) -> SamplerDescription {
+    use gltf::texture::{MagFilter, MinFilter, WrappingMode};
+
+    let address_mode = |mode| match mode {
+        WrappingMode::ClampToEdge => SamplerAddressMode::Clamp,
+        WrappingMode::MirroredRepeat => SamplerAddressMode::Mirror,
+        WrappingMode::Repeat => SamplerAddressMode::Wrap,
+    };
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => Filter::Nearest,
+        Some(MagFilter::Linear) | None => Filter::Linear,
+    };
+    let (min_filter, mipmap_mode) = match sampler.min_filter() {
+        Some(MinFilter::Nearest) | Some(MinFilter::NearestMipmapNearest) => {
+            (Filter::Nearest, SamplerMipmapMode::Nearest)
+        }
+        Some(MinFilter::NearestMipmapLinear) => (Filter::Nearest, SamplerMipmapMode::Linear),
+        Some(MinFilter::Linear) | Some(MinFilter::LinearMipmapNearest) => {
+            (Filter::Linear, SamplerMipmapMode::Nearest)
+        }
+        Some(MinFilter::LinearMipmapLinear) | None => (Filter::Linear, SamplerMipmapMode::Linear),
+    };
+
+    SamplerDescription {
+        addr_u: address_mode(sampler.wrap_s()),
+        addr_v: address_mode(sampler.wrap_t()),
+        addr_w: SamplerAddressMode::Wrap,
+        min_filter,
+        mag_filter,
+        mipmap_mode,
+    }
+}
+
+struct Loader<'a, 'd, B: Backend> {
+    arena: &'a Arena<'a, B>,
+    buffers: &'d [gltf::buffer::Data],
+    images: &'d [gltf::image::Data],
+    srgb_images: Vec<usize>,
+    /// Images created in the arena, by glTF image index and color space.
+    created_images: HashMap<(usize, bool), Image2d<'a, B>>,
+    scene_images: Vec<Image2d<'a, B>>,
+    materials: Vec<Material<'a, B>>,
+    /// Index in `materials` by glTF material index (`None` for the default material).
+    material_indices: HashMap<Option<usize>, usize>,
+}
+
+impl<'a, 'd, B: Backend> Loader<'a, 'd, B> {
+    fn load_mesh(&mut self, mesh: &gltf::Mesh) -> Mesh<'a, B> {
+        let primitives = mesh
+            .primitives()
+            .filter_map(|primitive| self.load_primitive(mesh, &primitive))
+            .collect();
+        Mesh {
+            name: mesh.name().map(|s| s.to_owned()),
+            primitives,
+        }
+    }
+
+    fn load_primitive(
+        &mut self,
+        mesh: &gltf::Mesh,
+        primitive: &gltf::Primitive,
+    ) -> Option<Primitive<'a, B>> {
+        if primitive.mode() != gltf::mesh::Mode::Triangles {
+            warn!(
+                "mesh {}: skipping primitive with unsupported mode {:?}",
+                mesh.index(),
+                primitive.mode()
+            );
+            return None;
+        }
+
+        let buffers = self.buffers;
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()][..]));
+        let positions: Vec<[f32; 3]> = match reader.read_positions() {
+            Some(positions) => positions.collect(),
+            None => {
+                warn!(
+                    "mesh {}: skipping primitive without positions",
+                    mesh.index()
+                );
+                return None;
+            }
+        };
+        let bounds = Bounds::from_points(&positions)?;
+
+        let mut vertices: Vec<_> = positions
+            .iter()
+            .map(|&position| GltfVertex {
+                position,
+                normal: [0.0, 0.0, 1.0],
+                tangent: [1.0, 0.0, 0.0, 1.0],
+                texcoord: [0.0, 0.0],
+            })
+            .collect();
+        if let Some(normals) = reader.read_normals() {
+            for (v, n) in vertices.iter_mut().zip(normals) {
+                v.normal = n;
+            }
+        }
+        if let Some(tangents) = reader.read_tangents() {
+            for (v, t) in vertices.iter_mut().zip(tangents) {
+                v.tangent = t;
+            }
+        }
+        if let Some(texcoords) = reader.read_tex_coords(0) {
+            for (v, t) in vertices.iter_mut().zip(texcoords.into_f32()) {
+                v.texcoord = t;
+            }
+        }
+
+        let indices: Vec<u32> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..vertices.len() as u32).collect(),
+        };
+
+        let material = self.load_material(&primitive.material());
+        let arguments = self.arena.create_typed_argument_block(PrimitiveArguments {
+            material: self.materials[material].arguments,
+            vertices: self.arena.upload_slice(&vertices),
+            indices: self.arena.upload_slice(&indices),
+        });
+
+        Some(Primitive {
+            arguments,
+            material,
+            index_count: indices.len() as u32,
+            bounds,
+        })
+    }
+
+    fn load_material(&mut self, material: &gltf::Material) -> usize {
+        if let Some(&index) = self.material_indices.get(&material.index()) {
+            return index;
+        }
+
+        let pbr = material.pbr_metallic_roughness();
+        let normal = material.normal_texture();
+        let occlusion = material.occlusion_texture();
+        let emissive = material.emissive_factor();
+
+        let params = self.arena.upload(&MaterialParams {
+            base_color_factor: pbr.base_color_factor(),
+            emissive_factor: [emissive[0], emissive[1], emissive[2], 0.0],
+            metallic_factor: pbr.metallic_factor(),
+            roughness_factor: pbr.roughness_factor(),
+            normal_scale: normal.as_ref().map(|n| n.scale()).unwrap_or(1.0),
+            occlusion_strength: occlusion.as_ref().map(|o| o.strength()).unwrap_or(1.0),
+        });
+
+        let arguments = MaterialArguments {
+            params: params.into(),
+            base_color: pbr
+                .base_color_texture()
+                .map(|info| self.load_texture(&info.texture(), info.tex_coord())),
+            metallic_roughness: pbr
+                .metallic_roughness_texture()
+                .map(|info| self.load_texture(&info.texture(), info.tex_coord())),
+            normal: normal.map(|n| self.load_texture(&n.texture(), n.tex_coord())),
+            occlusion: occlusion.map(|o| self.load_texture(&o.texture(), o.tex_coord())),
+            emissive: material
+                .emissive_texture()
+                .map(|info| self.load_texture(&info.texture(), info.tex_coord())),
+        };
+
+        let index = self.materials.len();
+        self.materials.push(Material {
+            alpha_mode: material.alpha_mode(),
+            alpha_cutoff: material.alpha_cutoff(),
+            double_sided: material.double_sided(),
+            arguments: self.arena.create_typed_argument_block(arguments),
+        });
+        self.material_indices.insert(material.index(), index);
+        index
+    }
+
+    fn load_texture(
+        &mut self,
+        texture: &gltf::Texture,
+        tex_coord: u32,
+    ) -> TextureSampler2dView<'a, B> {
+        if tex_coord != 0 {
+            warn!(
+                "texture {}: texture coordinate set {} is not supported, using set 0",
+                texture.index(),
+                tex_coord
+            );
+        }
+        let image = self.load_image(texture.source().index());
+        image.sampled(sampler_description(&texture.sampler()))
+    }
+
+    fn load_image(&mut self, index: usize) -> Image2d<'a, B> {
+        let srgb = self.srgb_images.contains(&index);
+        if let Some(&image) = self.created_images.get(&(index, srgb)) {
+            return image;
+        }
+
+        let data = &self.images[index];
+        let (image, _) = self.arena.create_image_compatible(
+            AliasScope::no_alias(),
+            image_format(data.format, srgb),
+            Dimensions::Dim2d {
+                width: data.width,
+                height: data.height,
+                array_layers: 1,
+            },
+            MipmapsOption::Generate,
+            1,
+            ImageUsageFlags::SAMPLED,
+            Some(&data.pixels[..]),
+        );
+        // the image was created with the dimensions of a 2D texture
+        let image = unsafe { Image2d::from_raw(image.inner()) };
+        self.created_images.insert((index, srgb), image);
+        self.scene_images.push(image);
+        image
+    }
+}
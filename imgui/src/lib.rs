@@ -2,6 +2,7 @@
 #![feature(proc_macro_hygiene)]
 use autograph_api::{
     buffer::{Buffer, StructuredBufferData, TypedConstantBufferView},
+    clip,
     command::{CommandBuffer, DrawIndexedParams},
     format::Format,
     glm,
@@ -13,7 +14,7 @@ use autograph_api::{
     pipeline::{
         Arguments, ColorBlendState, DepthStencilState, GraphicsPipelineCreateInfo,
         InputAssemblyState, MultisampleState, RasterisationState, ReflectedShader, Scissor,
-        ScissorRect, TypedArgumentBlock, TypedGraphicsPipeline, Viewport, ViewportState,
+        ScissorRect, TypedArgumentBlock, TypedGraphicsPipeline, ViewCount, Viewport, ViewportState,
     },
     vertex::{IndexBufferView, VertexBufferView, VertexData},
     Arena, Backend,
@@ -76,6 +77,7 @@ fn create_pipeline<'a, B: Backend>(
         depth_stencil_state: DepthStencilState::default(),
         input_assembly_state: InputAssemblyState::default(),
         color_blend_state: ColorBlendState::ALPHA_BLENDING,
+        view_count: ViewCount::default(),
     };
 
     arena.create_graphics_pipeline(&create_info)
@@ -150,24 +152,8 @@ impl<'a, B: Backend> ImGuiRenderer<'a, B> {
             return;
         }
 
-        let mat = glm::transpose(&glm::mat4(
-            2.0 / width as f32,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            2.0 / height as f32,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            -1.0,
-            0.0,
-            -1.0,
-            -1.0,
-            0.0,
-            1.0,
-        ));
+        // imgui coordinates are in pixels, with the origin at the top-left corner
+        let mat = clip::ortho_for(frame_arena.api(), 0.0, width, height, 0.0, -1.0, 1.0);
 
         let mut idx_start = 0u32;
 
@@ -126,7 +126,7 @@ fn test_imgui() {
             let mut cmdbuf = r.create_command_buffer();
             cmdbuf.clear_image(0x0, color_buffer, &[0.0, 0.2, 0.8, 1.0]);
             imgui_renderer.render(&mut cmdbuf, 0x0, &arena_frame, ui);
-            cmdbuf.present(0x0, color_buffer, default_swapchain);
+            cmdbuf.present_image(0x0, color_buffer, default_swapchain);
             r.submit_frame(vec![cmdbuf]);
 
             if should_close {
@@ -10,10 +10,15 @@ use regex::Regex;
 use shaderc::{self, IncludeType, ResolvedInclude};
 use std::{
     cell::RefCell,
-    fs,
+    env, fs,
     path::{Path, PathBuf},
 };
-use syn::export::ToTokens;
+use syn::{
+    export::ToTokens,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Token,
+};
 
 mod reflection;
 
@@ -95,23 +100,346 @@ fn compile_embedded_shader(
 ) -> proc_macro::TokenStream {
     // parse a string literal
     let litstr: syn::LitStr = syn::parse_macro_input!(src);
-    compile_glsl_shader(&litstr.value(), None, &litstr.span(), stage, false).into()
+    let settings = match CompileSettings::from_env() {
+        Ok(settings) => settings,
+        Err(msg) => {
+            return syn::Error::new(litstr.span(), msg)
+                .to_compile_error()
+                .into()
+        }
+    };
+    compile_glsl_shader(
+        &litstr.value(),
+        None,
+        &litstr.span(),
+        stage,
+        &[],
+        &settings,
+        ShaderOutput::Reflected,
+    )
+    .into()
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Optimization level of the generated SPIR-V.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Optimization {
+    Zero,
+    Size,
+    Performance,
+}
+
+impl Optimization {
+    fn parse(value: &str) -> Result<Optimization, String> {
+        match value {
+            "zero" => Ok(Optimization::Zero),
+            "size" => Ok(Optimization::Size),
+            "performance" => Ok(Optimization::Performance),
+            _ => Err(format!(
+                "invalid optimization level `{}`: expected `zero`, `size` or `performance`",
+                value
+            )),
+        }
+    }
+
+    fn to_shaderc(self) -> shaderc::OptimizationLevel {
+        match self {
+            Optimization::Zero => shaderc::OptimizationLevel::Zero,
+            Optimization::Size => shaderc::OptimizationLevel::Size,
+            Optimization::Performance => shaderc::OptimizationLevel::Performance,
+        }
+    }
+}
+
+/// Version of the generated SPIR-V.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum SpirvVersion {
+    /// SPIR-V 1.0, the only version accepted by `GL_ARB_gl_spirv`.
+    V1_0,
+    /// SPIR-V 1.3, for Vulkan 1.1.
+    V1_3,
+}
+
+impl SpirvVersion {
+    fn parse(value: &str) -> Result<SpirvVersion, String> {
+        match value {
+            "1.0" => Ok(SpirvVersion::V1_0),
+            "1.3" => Ok(SpirvVersion::V1_3),
+            _ => Err(format!(
+                "unsupported SPIR-V version `{}`: expected `1.0` or `1.3`",
+                value
+            )),
+        }
+    }
+
+    /// Version of the Vulkan target environment that produces this version of SPIR-V
+    /// (in the format of `VK_MAKE_VERSION`).
+    fn vulkan_env_version(self) -> u32 {
+        match self {
+            SpirvVersion::V1_0 => 1 << 22,
+            SpirvVersion::V1_3 => (1 << 22) | (1 << 12),
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "1" | "true" | "on" => Ok(true),
+        "0" | "false" | "off" => Ok(false),
+        _ => Err(format!("invalid boolean `{}`: expected `1` or `0`", value)),
+    }
+}
+
+/// Environment variable that selects the optimization level: `zero`, `size` or `performance`.
+const ENV_OPTIMIZATION: &str = "AUTOGRAPH_SHADER_OPT";
+/// Environment variable that enables (`1`) or disables (`0`) the generation of debug info.
+const ENV_DEBUG_INFO: &str = "AUTOGRAPH_SHADER_DEBUG_INFO";
+/// Environment variable that selects the version of SPIR-V: `1.0` or `1.3`.
+const ENV_SPIRV_VERSION: &str = "AUTOGRAPH_SPIRV_VERSION";
+
+/// Options passed to shaderc.
+#[derive(Copy, Clone, Debug)]
+struct CompileSettings {
+    optimization: Optimization,
+    debug_info: bool,
+    spirv_version: SpirvVersion,
+}
+
+impl CompileSettings {
+    /// Defaults for the build profile of the crate being compiled: unoptimized in debug builds,
+    /// optimized for performance in release builds. Debug info is never generated.
+    fn profile_defaults() -> CompileSettings {
+        CompileSettings {
+            optimization: if cfg!(debug_assertions) {
+                Optimization::Zero
+            } else {
+                Optimization::Performance
+            },
+            debug_info: false,
+            spirv_version: SpirvVersion::V1_0,
+        }
+    }
+
+    /// Profile defaults, overridden by the environment variables that are set.
+    fn from_env() -> Result<CompileSettings, String> {
+        fn var<T>(
+            name: &str,
+            parse: impl FnOnce(&str) -> Result<T, String>,
+        ) -> Result<Option<T>, String> {
+            match env::var(name) {
+                Ok(value) => parse(&value)
+                    .map(Some)
+                    .map_err(|e| format!("{} (in environment variable {})", e, name)),
+                Err(_) => Ok(None),
+            }
+        }
+
+        let mut settings = CompileSettings::profile_defaults();
+        if let Some(optimization) = var(ENV_OPTIMIZATION, Optimization::parse)? {
+            settings.optimization = optimization;
+        }
+        if let Some(debug_info) = var(ENV_DEBUG_INFO, parse_bool)? {
+            settings.debug_info = debug_info;
+        }
+        if let Some(spirv_version) = var(ENV_SPIRV_VERSION, SpirvVersion::parse)? {
+            settings.spirv_version = spirv_version;
+        }
+        Ok(settings)
+    }
+
+    /// Settings from the environment, overridden by the options of an invocation.
+    fn with_options(options: &CompileOptions) -> Result<CompileSettings, String> {
+        let mut settings = CompileSettings::from_env()?;
+        if let Some(optimization) = options.optimization {
+            settings.optimization = optimization;
+        }
+        if let Some(debug_info) = options.debug_info {
+            settings.debug_info = debug_info;
+        }
+        if let Some(spirv_version) = options.spirv_version {
+            settings.spirv_version = spirv_version;
+        }
+        Ok(settings)
+    }
+}
+
+/// Compilation options specified in an invocation of `include_glsl!`.
+#[derive(Copy, Clone, Debug, Default)]
+struct CompileOptions {
+    optimization: Option<Optimization>,
+    debug_info: Option<bool>,
+    spirv_version: Option<SpirvVersion>,
+}
+
+fn parse_str_option<T>(
+    input: ParseStream,
+    parse: impl FnOnce(&str) -> Result<T, String>,
+) -> syn::Result<T> {
+    let lit: syn::LitStr = input.parse()?;
+    parse(&lit.value()).map_err(|msg| syn::Error::new(lit.span(), msg))
+}
+
+/// A constant shared between Rust and GLSL: `path: type = value`.
+struct SharedConstant {
+    path: syn::Path,
+    ty: syn::Type,
+    value: syn::Lit,
+}
+
+impl Parse for SharedConstant {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(SharedConstant { path, ty, value })
+    }
 }
 
+impl SharedConstant {
+    /// Name of the preprocessor definition: the last segment of the path.
+    fn name(&self) -> String {
+        self.path.segments.iter().last().unwrap().ident.to_string()
+    }
+
+    /// Value of the preprocessor definition.
+    fn define_value(&self) -> syn::Result<String> {
+        match self.value {
+            syn::Lit::Int(ref v) => Ok(v.value().to_string()),
+            // debug-format so that the value is always a GLSL floating-point literal
+            syn::Lit::Float(ref v) => Ok(format!("{:?}", v.value())),
+            syn::Lit::Bool(ref v) => Ok(v.value.to_string()),
+            ref other => Err(syn::Error::new(
+                other.span(),
+                "expected an integer, floating-point or boolean literal",
+            )),
+        }
+    }
+}
+
+/// Input of `include_glsl!`: `"path/to/shader", constants = { ... }, optimize = "..."`.
+struct IncludeGlslInput {
+    path: syn::LitStr,
+    constants: Vec<SharedConstant>,
+    options: CompileOptions,
+}
+
+impl Parse for IncludeGlslInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let mut constants = Vec::new();
+        let mut options = CompileOptions::default();
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+        while !input.is_empty() {
+            let option: syn::Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            if option == "constants" {
+                let content;
+                syn::braced!(content in input);
+                let list: Punctuated<SharedConstant, Token![,]> =
+                    content.parse_terminated(SharedConstant::parse)?;
+                constants.extend(list.into_iter());
+            } else if option == "optimize" {
+                options.optimization = Some(parse_str_option(input, Optimization::parse)?);
+            } else if option == "debug_info" {
+                let lit: syn::LitBool = input.parse()?;
+                options.debug_info = Some(lit.value);
+            } else if option == "spirv_version" {
+                options.spirv_version = Some(parse_str_option(input, SpirvVersion::parse)?);
+            } else {
+                return Err(syn::Error::new(
+                    option.span(),
+                    format!(
+                        "unknown option `{}`: expected `constants`, `optimize`, `debug_info` \
+                         or `spirv_version`",
+                        option
+                    ),
+                ));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(IncludeGlslInput {
+            path,
+            constants,
+            options,
+        })
+    }
+}
+
+/// Compiles a GLSL shader file, located relative to the current source file, and returns its
+/// bytecode with reflection information (`ReflectedShader`).
+///
+/// The stage of the shader is deduced from the extension of the file.
+///
+/// Rust constants can be shared with the shader with the `constants` option:
+/// ```ignore
+/// static SHADING_FRAG: ReflectedShader = include_glsl!("shading.frag", constants = {
+///     crate::MAX_LIGHTS: usize = 16,
+/// });
+/// ```
+/// This defines the preprocessor macro `MAX_LIGHTS` with value `16` in the shader, and checks at
+/// compile time that the Rust constant `crate::MAX_LIGHTS` has the same value (procedural macros
+/// cannot read the value of constants, so it's repeated in the invocation). On mismatch, the
+/// compiler reports an array of the wrong size.
+///
+/// # Compilation settings
+///
+/// By default, shaders are compiled to SPIR-V 1.0 without debug info, unoptimized in debug
+/// builds and optimized for performance in release builds. The environment variables
+/// `AUTOGRAPH_SHADER_OPT` (`zero`, `size` or `performance`), `AUTOGRAPH_SHADER_DEBUG_INFO`
+/// (`1` or `0`) and `AUTOGRAPH_SPIRV_VERSION` (`1.0` or `1.3`) override the defaults for all
+/// shaders, and the options of an invocation override both:
+/// ```ignore
+/// static BLUR_COMP: ReflectedShader = include_glsl!("blur.comp", optimize = "size");
+/// static DEBUG_FRAG: ReflectedShader = include_glsl!("debug.frag", debug_info = true);
+/// ```
+/// Changes to the environment variables are not tracked: rebuild the crates that embed shaders
+/// after changing them (e.g. with `cargo clean -p`).
+///
+/// The OpenGL backend only accepts SPIR-V 1.0.
 #[proc_macro]
 pub fn include_glsl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     include_glsl_inner(input, false)
 }
 
+/// Same as `include_glsl!`, but returns only the bytecode.
 #[proc_macro]
 pub fn include_glsl_raw(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     include_glsl_inner(input, true)
 }
 
-fn include_glsl_inner(input: proc_macro::TokenStream, raw: bool) -> proc_macro::TokenStream {
-    let rel_path_lit: syn::LitStr = syn::parse_macro_input!(input);
+/// Shader source loaded from a file, with its stage and preprocessor definitions.
+struct ShaderFile {
+    src: String,
+    path: PathBuf,
+    stage: shaderc::ShaderKind,
+    defines: Vec<(String, String)>,
+    settings: CompileSettings,
+}
+
+fn load_shader_file(input: &IncludeGlslInput) -> Result<ShaderFile, TokenStream> {
+    let rel_path_lit = &input.path;
     let rel_path = PathBuf::from(rel_path_lit.value());
 
+    let mut defines = Vec::new();
+    for c in input.constants.iter() {
+        match c.define_value() {
+            Ok(value) => defines.push((c.name(), value)),
+            Err(e) => return Err(e.to_compile_error()),
+        }
+    }
+
+    let settings = match CompileSettings::with_options(&input.options) {
+        Ok(settings) => settings,
+        Err(msg) => return Err(syn::Error::new(rel_path_lit.span(), msg).to_compile_error()),
+    };
+
     let stage = match rel_path.extension() {
         Some(ext) if ext == "vert" => shaderc::ShaderKind::Vertex,
         Some(ext) if ext == "frag" => shaderc::ShaderKind::Fragment,
@@ -131,13 +459,170 @@ fn include_glsl_inner(input: proc_macro::TokenStream, raw: bool) -> proc_macro::
         panic!("failed to open GLSL shader source")
     };
 
-    let sh = compile_glsl_shader(&src, Some(&path), &rel_path_lit.span(), stage, raw);
+    Ok(ShaderFile {
+        src,
+        path,
+        stage,
+        defines,
+        settings,
+    })
+}
+
+fn include_glsl_inner(input: proc_macro::TokenStream, raw: bool) -> proc_macro::TokenStream {
+    let input: IncludeGlslInput = syn::parse_macro_input!(input);
+    let file = match load_shader_file(&input) {
+        Ok(file) => file,
+        Err(e) => return e.into(),
+    };
+    let rel_path_lit = &input.path;
+
+    let sh = compile_glsl_shader(
+        &file.src,
+        Some(&file.path),
+        &rel_path_lit.span(),
+        file.stage,
+        &file.defines,
+        &file.settings,
+        if raw {
+            ShaderOutput::Raw
+        } else {
+            ShaderOutput::Reflected
+        },
+    );
+
+    // check that the values of the constants match on the Rust side
+    let checks = input.constants.iter().map(|c| {
+        let check_name =
+            syn::Ident::new(&format!("__GLSL_CONSTANT_{}", c.name()), Span::call_site());
+        let path = &c.path;
+        let ty = &c.ty;
+        let value = &c.value;
+        quote! {
+            #[allow(dead_code, non_upper_case_globals)]
+            const #check_name: [(); 1] = [(); (#path == (#value as #ty)) as usize];
+        }
+    });
 
     // include_str so that it is considered when tracking dirty files
-    let q = quote! { (#sh, include_str!(#rel_path_lit)).0 };
+    let q = quote! {
+        {
+            #(#checks)*
+            (#sh, include_str!(#rel_path_lit)).0
+        }
+    };
+    q.into()
+}
+
+/// Input of `include_glsl_reflection!`: `pub mod name = "path/to/shader", ...`.
+struct IncludeGlslReflectionInput {
+    vis: syn::Visibility,
+    ident: syn::Ident,
+    shader: IncludeGlslInput,
+}
+
+impl Parse for IncludeGlslReflectionInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let vis = input.parse()?;
+        input.parse::<Token![mod]>()?;
+        let ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let shader = input.parse()?;
+        Ok(IncludeGlslReflectionInput { vis, ident, shader })
+    }
+}
+
+/// Compiles a GLSL shader file and generates a module of constants describing its interface,
+/// which can be used in constant expressions (e.g. to check at compile time that an `Arguments`
+/// struct declares as many descriptors as the shader).
+///
+/// ```ignore
+/// include_glsl_reflection!(pub mod shading_frag = "shading.frag");
+///
+/// // number of descriptors, vertex inputs and fragment outputs
+/// const _CHECK: [(); 3] = [(); shading_frag::DESCRIPTOR_COUNT];
+/// // `(set, binding)` of the descriptor named `lights`
+/// const LIGHTS: (u32, u32) = shading_frag::bindings::lights;
+/// ```
+///
+/// Accepts the same options as `include_glsl!`.
+#[proc_macro]
+pub fn include_glsl_reflection(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input: IncludeGlslReflectionInput = syn::parse_macro_input!(input);
+    let file = match load_shader_file(&input.shader) {
+        Ok(file) => file,
+        Err(e) => return e.into(),
+    };
+    let rel_path_lit = &input.shader.path;
+
+    let consts = compile_glsl_shader(
+        &file.src,
+        Some(&file.path),
+        &rel_path_lit.span(),
+        file.stage,
+        &file.defines,
+        &file.settings,
+        ShaderOutput::Consts,
+    );
+
+    let vis = &input.vis;
+    let ident = &input.ident;
+    let q = quote! {
+        #vis mod #ident {
+            #consts
+            // include_str so that it is considered when tracking dirty files
+            #[allow(dead_code)]
+            const __SOURCE: &str = include_str!(#rel_path_lit);
+        }
+    };
     q.into()
 }
 
+/// Compiles a GLSL shader file and generates `#[repr(C)]` structs for its uniform blocks and
+/// push constants.
+///
+/// ```ignore
+/// // in `shading.frag`:
+/// // layout(set=0, binding=0) uniform Globals { mat3 normal_matrix; vec2 resolution; };
+/// include_glsl_interface!("shading.frag");
+///
+/// let globals = arena.upload(&Globals {
+///     normal_matrix: [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0]],
+///     resolution: [1280.0, 720.0],
+///     _pad0: [0; 2],
+/// });
+/// ```
+///
+/// The structs are named after the block types, and their fields after the members of the
+/// blocks. Structures used in the blocks get their own struct. Uniform blocks are laid out with
+/// the std140 rules, and push constants with the std430 rules: padding fields (`_pad0`,
+/// `_pad1`...) are inserted between members and at the end of the structs so that the offsets
+/// and the size match. Columns of matrices and elements of arrays of scalars or vectors are
+/// padded to their stride (e.g. a `mat3` in a uniform block is a `[[f32; 4]; 3]`).
+///
+/// The structs implement `StructuredBufferData` with the type and layout of the shader.
+/// The names of the blocks and of their members must be present in the compiled shader.
+///
+/// Accepts the same options as `include_glsl!`.
+#[proc_macro]
+pub fn include_glsl_interface(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input: IncludeGlslInput = syn::parse_macro_input!(input);
+    let file = match load_shader_file(&input) {
+        Ok(file) => file,
+        Err(e) => return e.into(),
+    };
+
+    compile_glsl_shader(
+        &file.src,
+        Some(&file.path),
+        &input.path.span(),
+        file.stage,
+        &file.defines,
+        &file.settings,
+        ShaderOutput::Interface,
+    )
+    .into()
+}
+
 fn resolve_include(
     current_path: &Path,
     include_rel_path: &str,
@@ -171,12 +656,27 @@ fn resolve_include(
     }
 }
 
+/// What to generate from a compiled shader.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum ShaderOutput {
+    /// `ReflectedShader` expression
+    Reflected,
+    /// Bytecode only
+    Raw,
+    /// Constant items describing the interface
+    Consts,
+    /// Structs of the uniform blocks and push constants
+    Interface,
+}
+
 fn compile_glsl_shader(
     src: &str,
     file_path: Option<&Path>,
     span: &Span,
     stage: shaderc::ShaderKind,
-    raw: bool,
+    defines: &[(String, String)],
+    settings: &CompileSettings,
+    output: ShaderOutput,
 ) -> proc_macro2::TokenStream {
     // the doc says that we should preferably create one instance of the compiler
     // and reuse it, but I don't see a way to reuse a compiler instance
@@ -185,8 +685,17 @@ fn compile_glsl_shader(
     let mut compiler = shaderc::Compiler::new().unwrap();
     let all_includes = RefCell::new(Vec::new()); // RefCell because include_callback is not FnMut
     let mut opt = shaderc::CompileOptions::new().unwrap();
-    opt.set_target_env(shaderc::TargetEnv::Vulkan, 0);
-    opt.set_optimization_level(shaderc::OptimizationLevel::Zero);
+    opt.set_target_env(
+        shaderc::TargetEnv::Vulkan,
+        settings.spirv_version.vulkan_env_version(),
+    );
+    opt.set_optimization_level(settings.optimization.to_shaderc());
+    if settings.debug_info {
+        opt.set_generate_debug_info();
+    }
+    for (name, value) in defines {
+        opt.add_macro_definition(name, Some(value));
+    }
     opt.set_include_callback(|name, include_type, source_name, depth| {
         if let Some(file_path) = file_path {
             let mut all_includes = all_includes.borrow_mut();
@@ -260,7 +769,10 @@ fn compile_glsl_shader(
             }
 
             diag.emit();
-            quote!(&[])
+            match output {
+                ShaderOutput::Consts | ShaderOutput::Interface => quote!(),
+                _ => quote!(&[]),
+            }
         }
         // Compilation successful
         Ok(ca) => {
@@ -280,18 +792,46 @@ fn compile_glsl_shader(
             let a = a.iter();
             let q = quote!((#binstr, #(include_str!(#a)),*).0);
 
-            if raw {
+            match output {
                 // raw output, without reflection info
-                q
-            } else {
+                ShaderOutput::Raw => q,
                 // reflection info requested
-                let refl = reflection::generate_reflection_info(span, bin, stage);
-                quote! {
-                    #G::pipeline::ReflectedShader {
-                         bytecode: #q,
-                         reflection: &#refl
+                ShaderOutput::Reflected => {
+                    let refl = reflection::generate_reflection_info(span, bin, stage);
+                    quote! {
+                        #G::pipeline::ReflectedShader {
+                             bytecode: #q,
+                             reflection: &#refl
+                        }
                     }
                 }
+                // reflection as constants, for use in constant expressions
+                ShaderOutput::Consts => {
+                    let consts = reflection::generate_reflection_consts(bin, stage);
+                    quote! {
+                        #consts
+                        #[allow(dead_code)]
+                        const __INCLUDES: &[&str] = &[#(include_str!(#a)),*];
+                    }
+                }
+                // structs of the blocks
+                ShaderOutput::Interface => {
+                    let main = file_path
+                        .and_then(|p| p.canonicalize().ok())
+                        .map(|p| p.to_str().unwrap().to_owned());
+                    let sources = main.iter().chain(a);
+                    let source_name = file_path
+                        .and_then(|p| p.file_name())
+                        .map_or("embedded GLSL".to_string(), |n| {
+                            n.to_string_lossy().into_owned()
+                        });
+                    reflection::generate_interface_structs(
+                        span,
+                        bin,
+                        &source_name,
+                        quote!(&[#(include_str!(#sources)),*]),
+                    )
+                }
             }
         }
     }
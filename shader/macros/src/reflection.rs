@@ -192,6 +192,13 @@ fn gen_stage_flags(sk: ShaderKind) -> TokenStream {
     }
 }
 
+fn gen_name(v: &Variable) -> TokenStream {
+    match v.name {
+        Some(name) => quote!(Some(#name)),
+        None => quote!(None),
+    }
+}
+
 fn gen_descriptor_reflection_info(
     s: &Span,
     v: &spirv::ast::Variable,
@@ -201,6 +208,7 @@ fn gen_descriptor_reflection_info(
 ) -> TokenStream {
     let has_buffer_block_deco = v.has_buffer_block_decoration().is_some();
     let stage_flags = gen_stage_flags(stage);
+    let name = gen_name(v);
     let a = spirv::DroplessArena::new();
 
     if v.storage == spirv::headers::StorageClass::Uniform
@@ -221,7 +229,8 @@ fn gen_descriptor_reflection_info(
                 count: 1,
                 data_ty: Some(&#tyinfo),
                 data_layout: Some(&#tylayoutinfo),
-                data_format: #G::Format::UNDEFINED
+                data_format: #G::Format::UNDEFINED,
+                name: #name
             }
         }
     } else if (v.storage == spirv::headers::StorageClass::Uniform && has_buffer_block_deco)
@@ -242,7 +251,8 @@ fn gen_descriptor_reflection_info(
                 count: 1,
                 data_ty: Some(&#tyinfo),
                 data_layout: Some(&#tylayoutinfo),
-                data_format: #G::Format::UNDEFINED
+                data_format: #G::Format::UNDEFINED,
+                name: #name
             }
         }
     } else if v.storage == spirv::headers::StorageClass::UniformConstant {
@@ -265,7 +275,28 @@ fn gen_descriptor_reflection_info(
                     count: 1,
                     data_ty: Some(&#sampled_ty),
                     data_layout: None,
-                    data_format: #G::Format::#format
+                    data_format: #G::Format::#format,
+                    name: #name
+                }
+            }
+        } else if let &TypeDesc::Pointer(&TypeDesc::SampledImage(&ImageType {
+            dimensions: spirv::headers::Dim::DimBuffer,
+            ..
+        })) = v.ty
+        {
+            // texel buffer (samplerBuffer) --------------------------------------------------------
+            // the format is specified by the buffer view
+            quote! {
+                #G::descriptor::ResourceBinding {
+                    set: Some(#set),
+                    index: #binding,
+                    ty: #G::descriptor::ResourceBindingType::TexelBuffer,
+                    stage_flags: #stage_flags,
+                    count: 1,
+                    data_ty: None,
+                    data_layout: None,
+                    data_format: #G::Format::UNDEFINED,
+                    name: #name
                 }
             }
         } else if let &TypeDesc::Pointer(&TypeDesc::SampledImage(image_ty)) = v.ty {
@@ -282,7 +313,8 @@ fn gen_descriptor_reflection_info(
                     count: 1,
                     data_ty: Some(&#sampled_ty),
                     data_layout: None,
-                    data_format: #G::Format::#format
+                    data_format: #G::Format::#format,
+                    name: #name
                 }
             }
         } else {
@@ -301,11 +333,37 @@ fn gen_descriptor_reflection_info(
 
 fn gen_vertex_input_reflection_info(_s: &Span, v: &Variable, location: u32) -> TokenStream {
     let ty = gen_type_info(v.ty.pointee_type().expect("expected pointer type"));
+    let name = gen_name(v);
     quote! {
         #G::pipeline::VertexInputAttributeDescription {
             location: Some(#location),
             ty: &#ty,
-            semantic: None  // No match by semantic in GL or Vulkan
+            semantic: None,  // No match by semantic in GL or Vulkan
+            name: #name
+        }
+    }
+}
+
+fn gen_fragment_output_attribute_info(_s: &Span, v: &Variable, location: u32) -> TokenStream {
+    let ty = gen_type_info(v.ty.pointee_type().expect("expected pointer type"));
+    let name = gen_name(v);
+    quote! {
+        #G::pipeline::FragmentOutputAttributeDescription {
+            location: #location,
+            ty: &#ty,
+            name: #name
+        }
+    }
+}
+
+fn gen_interface_variable_info(_s: &Span, v: &Variable, location: u32) -> TokenStream {
+    let ty = gen_type_info(v.ty.pointee_type().expect("expected pointer type"));
+    let name = gen_name(v);
+    quote! {
+        #G::pipeline::InterfaceVariableDescription {
+            location: #location,
+            ty: &#ty,
+            name: #name
         }
     }
 }
@@ -315,6 +373,9 @@ fn gen_fragment_output_reflection_info(_s: &Span, _v: &Variable, _location: u32)
     quote! {
         #G::pipeline::FragmentOutputDescription {
             // TODO
+            blend: None,
+            load_op: #G::pipeline::LoadOp::Load,
+            store_op: #G::pipeline::StoreOp::Store,
         }
     }
 }
@@ -327,6 +388,9 @@ pub fn generate_reflection_info(s: &Span, bytecode: &[u8], stage: ShaderKind) ->
     let mut descriptor_infos = Vec::new();
     let mut vtx_input_infos = Vec::new();
     let mut frag_output_infos = Vec::new();
+    let mut frag_output_attribs = Vec::new();
+    let mut stage_inputs = Vec::new();
+    let mut stage_outputs = Vec::new();
 
     for (_, v) in ast.variables() {
         if let Some((_, set)) = v.descriptor_set_decoration() {
@@ -349,6 +413,20 @@ pub fn generate_reflection_info(s: &Span, bytecode: &[u8], stage: ShaderKind) ->
                 .location_decoration()
                 .expect("expected location decoration");
             frag_output_infos.push(gen_fragment_output_reflection_info(s, v, loc));
+            frag_output_attribs.push(gen_fragment_output_attribute_info(s, v, loc));
+        }
+
+        // interface between stages (built-ins have no location) -------------------------------
+        if stage != ShaderKind::Vertex && v.storage == spirv::headers::StorageClass::Input {
+            if let Some((_, loc)) = v.location_decoration() {
+                stage_inputs.push(gen_interface_variable_info(s, v, loc));
+            }
+        }
+
+        if stage != ShaderKind::Fragment && v.storage == spirv::headers::StorageClass::Output {
+            if let Some((_, loc)) = v.location_decoration() {
+                stage_outputs.push(gen_interface_variable_info(s, v, loc));
+            }
         }
     }
 
@@ -360,6 +438,316 @@ pub fn generate_reflection_info(s: &Span, bytecode: &[u8], stage: ShaderKind) ->
             descriptors: &[#(#descriptor_infos,)*],
             vertex_input_attributes: &[#(#vtx_input_infos,)*],
             fragment_outputs: &[#(#frag_output_infos,)*],
+            fragment_output_attributes: &[#(#frag_output_attribs,)*],
+            stage_inputs: &[#(#stage_inputs,)*],
+            stage_outputs: &[#(#stage_outputs,)*],
+        }
+    }
+}
+
+/// Generates constants describing the interface of a shader, usable in constant expressions.
+pub fn generate_reflection_consts(bytecode: &[u8], stage: ShaderKind) -> TokenStream {
+    let a = spirv::DroplessArena::new();
+    let m = spirv::Module::from_bytes(bytecode).expect("failed to parse generated SPIR-V module");
+    let ast = spirv::ast::Ast::new(&a, &m);
+
+    let mut descriptor_count = 0usize;
+    let mut vtx_input_count = 0usize;
+    let mut frag_output_count = 0usize;
+    let mut bindings = Vec::new();
+
+    for (_, v) in ast.variables() {
+        if let Some((_, set)) = v.descriptor_set_decoration() {
+            let (_, binding) = v.binding_decoration().expect("expected binding decoration");
+            descriptor_count += 1;
+            // names that are not valid Rust identifiers (e.g. keywords) are skipped
+            if let Some(ident) = v
+                .name
+                .and_then(|name| syn::parse_str::<syn::Ident>(name).ok())
+            {
+                bindings.push(quote! {
+                    pub const #ident: (u32, u32) = (#set, #binding);
+                });
+            }
+        }
+
+        if stage == ShaderKind::Vertex && v.storage == spirv::headers::StorageClass::Input {
+            vtx_input_count += 1;
+        }
+
+        if stage == ShaderKind::Fragment && v.storage == spirv::headers::StorageClass::Output {
+            frag_output_count += 1;
+        }
+    }
+
+    quote! {
+        /// Number of descriptors declared by the shader.
+        pub const DESCRIPTOR_COUNT: usize = #descriptor_count;
+        /// Number of vertex inputs declared by the shader (vertex shaders only).
+        pub const VERTEX_INPUT_COUNT: usize = #vtx_input_count;
+        /// Number of fragment outputs declared by the shader (fragment shaders only).
+        pub const FRAGMENT_OUTPUT_COUNT: usize = #frag_output_count;
+
+        /// `(set, binding)` of the descriptors of the shader, by name.
+        #[allow(non_upper_case_globals)]
+        pub mod bindings {
+            #(#bindings)*
         }
     }
 }
+
+/// Generator of the Rust structs of the blocks of a shader.
+struct InterfaceStructs<'a, 'tcx> {
+    ast: &'a spirv::ast::Ast<'tcx>,
+    /// Display name of the shader, for the docs of the structs.
+    source_name: &'a str,
+    /// `&[&str]` expression with the contents of the source files, so that their changes are
+    /// tracked.
+    sources: TokenStream,
+    /// Structures already generated, with the layout they were generated with.
+    generated: Vec<(u32, Layout<'tcx>)>,
+    items: Vec<TokenStream>,
+}
+
+fn gen_scalar_rust_type(ty: spirv::PrimitiveType) -> Result<TokenStream, String> {
+    match ty {
+        spirv::PrimitiveType::Float => Ok(quote!(f32)),
+        spirv::PrimitiveType::Int => Ok(quote!(i32)),
+        spirv::PrimitiveType::UnsignedInt | spirv::PrimitiveType::Bool => Ok(quote!(u32)),
+        ty => Err(format!("unsupported scalar type in block: {:?}", ty)),
+    }
+}
+
+/// Converts a member name to a Rust identifier (keywords get a trailing underscore).
+fn gen_member_ident(name: &str) -> syn::Ident {
+    syn::parse_str::<syn::Ident>(name)
+        .unwrap_or_else(|_| syn::Ident::new(&format!("{}_", name), Span::call_site()))
+}
+
+impl<'a, 'tcx> InterfaceStructs<'a, 'tcx> {
+    /// Returns the Rust type of a member of type `ty` laid out as `layout`, generating the
+    /// structs it refers to.
+    ///
+    /// `large_array` is set if the type contains an array of more than 32 elements (which
+    /// does not implement `Debug`).
+    fn member_type(
+        &mut self,
+        ty: &'tcx TypeDesc<'tcx>,
+        layout: &'tcx Layout<'tcx>,
+        large_array: &mut bool,
+    ) -> Result<TokenStream, String> {
+        match (ty, layout.details) {
+            (&TypeDesc::Primitive(p), _) => gen_scalar_rust_type(p),
+            (&TypeDesc::Vector { elem_ty, len }, _) => {
+                let elem_ty = gen_scalar_rust_type(elem_ty)?;
+                let len = len as usize;
+                Ok(quote!([#elem_ty; #len]))
+            }
+            (
+                &TypeDesc::Matrix {
+                    elem_ty, columns, ..
+                },
+                LayoutDetails::Array(array),
+            ) => {
+                // columns are padded to the stride
+                let elem_ty = gen_scalar_rust_type(elem_ty)?;
+                let rows = array.stride / 4;
+                let columns = columns as usize;
+                Ok(quote!([[#elem_ty; #rows]; #columns]))
+            }
+            (&TypeDesc::Array { elem_ty, len }, LayoutDetails::Array(array)) => {
+                if len == 0 {
+                    return Err("runtime-sized arrays are not supported in blocks".to_string());
+                }
+                if len > 32 {
+                    *large_array = true;
+                }
+                let elem = if array.stride == array.elem_layout.size {
+                    self.member_type(elem_ty, array.elem_layout, large_array)?
+                } else {
+                    // elements are padded to the stride
+                    match *elem_ty {
+                        TypeDesc::Primitive(p) | TypeDesc::Vector { elem_ty: p, .. } => {
+                            let scalar = gen_scalar_rust_type(p)?;
+                            let n = array.stride / 4;
+                            quote!([#scalar; #n])
+                        }
+                        ref ty => {
+                            return Err(format!(
+                                "unsupported array element type in block: {:?}",
+                                ty
+                            ))
+                        }
+                    }
+                };
+                Ok(quote!([#elem; #len]))
+            }
+            (&TypeDesc::Struct { .. }, LayoutDetails::Struct(_)) => {
+                let ident = self.gen_struct(ty, layout, None)?;
+                Ok(quote!(#ident))
+            }
+            (ty, _) => Err(format!("unsupported type in block: {:?}", ty)),
+        }
+    }
+
+    /// Generates the struct of the structure type `ty` laid out as `layout`, and returns its
+    /// name.
+    fn gen_struct(
+        &mut self,
+        ty: &'tcx TypeDesc<'tcx>,
+        layout: &'tcx Layout<'tcx>,
+        block: Option<String>,
+    ) -> Result<syn::Ident, String> {
+        let info = self
+            .ast
+            .struct_info(ty)
+            .ok_or_else(|| format!("unknown structure type: {:?}", ty))?;
+        let name = info.name.ok_or_else(|| {
+            "structure without a name: the shader must be compiled with names".to_string()
+        })?;
+        let ident = syn::parse_str::<syn::Ident>(name)
+            .map_err(|_| format!("structure name `{}` is not a valid identifier", name))?;
+
+        if let Some(&(_, ref prev_layout)) = self.generated.iter().find(|(id, _)| *id == info.id) {
+            return if prev_layout == layout {
+                Ok(ident)
+            } else {
+                Err(format!(
+                    "structure `{}` is used in blocks with different layouts",
+                    name
+                ))
+            };
+        }
+        self.generated.push((info.id, *layout));
+
+        let (fields, fields_layout) = match (ty, layout.details) {
+            (&TypeDesc::Struct { fields }, LayoutDetails::Struct(fields_layout)) => {
+                (fields, fields_layout)
+            }
+            _ => panic!("expected structure type"),
+        };
+
+        let mut members = Vec::new();
+        let mut large_array = false;
+        let mut offset = 0;
+        let mut pad_count = 0;
+        let mut pad = |members: &mut Vec<TokenStream>, size: usize| {
+            let ident = syn::Ident::new(&format!("_pad{}", pad_count), Span::call_site());
+            let n = size / 4;
+            members.push(quote!(pub #ident: [u32; #n]));
+            pad_count += 1;
+        };
+
+        for (i, &field_ty) in fields.iter().enumerate() {
+            let field_offset = fields_layout.offsets[i];
+            let field_layout = fields_layout.layouts[i];
+            if field_offset > offset {
+                pad(&mut members, field_offset - offset);
+            }
+            let field_ident = match info.member_names.get(i).and_then(|name| *name) {
+                Some(name) => gen_member_ident(name),
+                None => syn::Ident::new(&format!("member{}", i), Span::call_site()),
+            };
+            let field_rust_ty = self.member_type(field_ty, field_layout, &mut large_array)?;
+            members.push(quote!(pub #field_ident: #field_rust_ty));
+            offset = field_offset + field_layout.size;
+        }
+        if layout.size > offset {
+            pad(&mut members, layout.size - offset);
+        }
+
+        let doc = match block {
+            Some(block) => format!("{} of `{}`.", block, self.source_name),
+            None => format!("Structure `{}` of `{}`.", name, self.source_name),
+        };
+        let derives = if large_array {
+            quote!(#[derive(Copy, Clone)])
+        } else {
+            quote!(#[derive(Copy, Clone, Debug)])
+        };
+        let tyinfo = gen_type_info(ty);
+        let layoutinfo = gen_layout_info(layout);
+        let size = layout.size;
+        let sources = &self.sources;
+
+        self.items.push(quote! {
+            #[doc = #doc]
+            #[repr(C)]
+            #derives
+            pub struct #ident {
+                #(#members,)*
+            }
+
+            impl #ident {
+                #[allow(dead_code)]
+                const __SIZE_CHECK: [(); #size] = [(); ::std::mem::size_of::<#ident>()];
+                // include_str so that changes to the shader are tracked
+                #[allow(dead_code)]
+                const __SOURCES: &'static [&'static str] = #sources;
+            }
+
+            unsafe impl #G::buffer::StructuredBufferData for #ident {
+                const TYPE: #G::typedesc::TypeDesc<'static> = #tyinfo;
+                const LAYOUT: #G::typedesc::Layout<'static> = #layoutinfo;
+            }
+        });
+
+        Ok(ident)
+    }
+}
+
+/// Generates `#[repr(C)]` structs for the uniform blocks and push constants of a shader, and
+/// for the structures that they contain.
+///
+/// `sources` is a `&[&str]` expression with the contents of the source files of the shader.
+pub fn generate_interface_structs(
+    s: &Span,
+    bytecode: &[u8],
+    source_name: &str,
+    sources: TokenStream,
+) -> TokenStream {
+    let a = spirv::DroplessArena::new();
+    let m = spirv::Module::from_bytes(bytecode).expect("failed to parse generated SPIR-V module");
+    let ast = spirv::ast::Ast::new(&a, &m);
+
+    let mut structs = InterfaceStructs {
+        ast: &ast,
+        source_name,
+        sources,
+        generated: Vec::new(),
+        items: Vec::new(),
+    };
+
+    for (_, v) in ast.variables() {
+        let ty = match v.ty.pointee_type() {
+            Some(ty @ &TypeDesc::Struct { .. }) => ty,
+            _ => continue,
+        };
+        let is_buffer_block = ast.struct_info(ty).map_or(false, |s| s.buffer_block);
+
+        let (layout, block) = match v.storage {
+            spirv::headers::StorageClass::Uniform if !is_buffer_block => {
+                let set = v.descriptor_set_decoration().map_or(0, |(_, set)| set);
+                let binding = v.binding_decoration().map_or(0, |(_, binding)| binding);
+                (
+                    spirv::layout::Layout::std140(&a, ty),
+                    format!("Uniform block (set {}, binding {})", set, binding),
+                )
+            }
+            spirv::headers::StorageClass::PushConstant => (
+                spirv::layout::Layout::std430(&a, ty),
+                "Push constants".to_string(),
+            ),
+            _ => continue,
+        };
+
+        if let Err(msg) = structs.gen_struct(ty, layout, Some(block)) {
+            s.unstable()
+                .error(format!("cannot generate the struct of a block: {}", msg))
+                .emit();
+        }
+    }
+
+    let items = structs.items;
+    quote!(#(#items)*)
+}
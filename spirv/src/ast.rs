@@ -20,6 +20,8 @@ pub enum ParsedDecoration {
 #[derive(Copy, Clone, Debug)]
 pub struct Variable<'tcx> {
     pub id: u32,
+    /// Name of the variable (`OpName`), if debug information is present.
+    pub name: Option<&'tcx str>,
     pub ty: &'tcx TypeDesc<'tcx>,
     pub deco: &'tcx [(IPtr, ParsedDecoration)],
     pub storage: StorageClass,
@@ -81,18 +83,33 @@ impl<'tcx> Variable<'tcx> {
     }
 }
 
+/// A structure type, with its name and the names of its members (`OpName` and `OpMemberName`)
+/// if debug information is present.
+#[derive(Copy, Clone, Debug)]
+pub struct Struct<'tcx> {
+    pub id: u32,
+    pub ty: &'tcx TypeDesc<'tcx>,
+    pub name: Option<&'tcx str>,
+    pub member_names: &'tcx [Option<&'tcx str>],
+    /// Whether the structure is decorated with `BufferBlock` (storage buffers).
+    pub buffer_block: bool,
+}
+
 pub struct Ast<'tcx> {
     _tymap: HashMap<u32, &'tcx TypeDesc<'tcx>>,
     vars: &'tcx [(IPtr, Variable<'tcx>)],
+    structs: &'tcx [Struct<'tcx>],
 }
 
 impl<'tcx> Ast<'tcx> {
     pub fn new(arena: &'tcx DroplessArena, module: &Module) -> Ast<'tcx> {
         let tymap = parse_types(arena, module);
         let vars = parse_variables(arena, module, &tymap);
+        let structs = parse_structs(arena, module, &tymap);
         Ast {
             _tymap: tymap,
             vars,
+            structs,
         }
     }
 
@@ -100,12 +117,22 @@ impl<'tcx> Ast<'tcx> {
         self.vars.iter()
     }
 
+    /// Returns the structure of type `ty`, with its names.
+    ///
+    /// `ty` must come from this AST: structure types are compared by address, since two
+    /// different structures can have the same fields.
+    pub fn struct_info(&self, ty: &TypeDesc<'tcx>) -> Option<&'tcx Struct<'tcx>> {
+        self.structs.iter().find(|s| std::ptr::eq(s.ty, ty))
+    }
+
     //pub fn uniform_buffers(&self)
 }
 
 fn parse_types<'tcx>(a: &'tcx DroplessArena, m: &Module) -> HashMap<u32, &'tcx TypeDesc<'tcx>> {
     // build a map from id to type
     let mut tymap = HashMap::<u32, &'tcx TypeDesc<'tcx>>::new();
+    // values of integer constants, for the lengths of arrays
+    let mut constants = HashMap::<u32, u32>::new();
 
     // can process types in order, since the spec specifies that:
     // "Types are built bottom up: A parameterizing operand in a type must be defined before being used."
@@ -214,13 +241,22 @@ fn parse_types<'tcx>(a: &'tcx DroplessArena, m: &Module) -> HashMap<u32, &'tcx T
                     panic!("expected image type")
                 };
             }
+            Instruction::Constant(IConstant {
+                result_id, data, ..
+            }) => {
+                if data.len() == 1 {
+                    constants.insert(*result_id, data[0]);
+                }
+            }
             Instruction::TypeArray(ITypeArray {
                 result_id,
                 type_id,
-                length_id: _,
+                length_id,
             }) => {
                 let elem_ty = tymap[type_id];
-                tymap.insert(*result_id, a.alloc(TypeDesc::Array { elem_ty, len: 0 }));
+                // TODO lengths given by specialization constants
+                let len = constants.get(length_id).cloned().unwrap_or(0) as usize;
+                tymap.insert(*result_id, a.alloc(TypeDesc::Array { elem_ty, len }));
             }
             Instruction::TypeRuntimeArray(ITypeRuntimeArray { result_id, type_id }) => {
                 let elem_ty = tymap[type_id];
@@ -252,6 +288,47 @@ fn parse_types<'tcx>(a: &'tcx DroplessArena, m: &Module) -> HashMap<u32, &'tcx T
     tymap
 }
 
+fn alloc_str<'tcx>(a: &'tcx DroplessArena, s: &str) -> &'tcx str {
+    let bytes = a.alloc_extend(s.bytes());
+    // the bytes come from a valid `str`
+    unsafe { std::str::from_utf8_unchecked(bytes) }
+}
+
+fn parse_structs<'tcx>(
+    a: &'tcx DroplessArena,
+    m: &Module,
+    tymap: &HashMap<u32, &'tcx TypeDesc<'tcx>>,
+) -> &'tcx [Struct<'tcx>] {
+    let structs: Vec<_> = m
+        .filter_instructions::<ITypeStruct>()
+        .map(|(_, s)| {
+            let id = s.result_id;
+            let member_names: Vec<_> = (0..s.member_types.len() as u32)
+                .map(|member| {
+                    m.filter_instructions::<IMemberName>()
+                        .find(|(_, n)| {
+                            n.target_id == id && n.member == member && !n.name.is_empty()
+                        })
+                        .map(|(_, n)| alloc_str(a, &n.name))
+                })
+                .collect();
+            Struct {
+                id,
+                ty: tymap[&id],
+                name: m
+                    .filter_instructions::<IName>()
+                    .find(|(_, n)| n.target_id == id && !n.name.is_empty())
+                    .map(|(_, n)| alloc_str(a, &n.name)),
+                member_names: a.alloc_extend(member_names.into_iter()),
+                buffer_block: m
+                    .filter_instructions::<IDecorate>()
+                    .any(|(_, d)| d.target_id == id && d.decoration == Decoration::BufferBlock),
+            }
+        })
+        .collect();
+    a.alloc_extend(structs.into_iter())
+}
+
 fn parse_variables<'tcx>(
     a: &'tcx DroplessArena,
     m: &Module,
@@ -264,6 +341,10 @@ fn parse_variables<'tcx>(
                 iptr,
                 Variable {
                     id: v.result_id,
+                    name: m
+                        .filter_instructions::<IName>()
+                        .find(|(_, n)| n.target_id == v.result_id && !n.name.is_empty())
+                        .map(|(_, n)| alloc_str(a, &n.name)),
                     ty: tymap[&v.result_type_id],
                     deco: a.alloc_extend(
                         m.filter_instructions::<IDecorate>()
@@ -60,15 +60,36 @@ pub enum LayoutDetails<'tcx> {
     Struct(FieldsLayout<'tcx>),
 }
 
+/// Layout rules of a block.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Rules {
+    /// Uniform blocks.
+    Std140,
+    /// Storage buffers and push constants: like std140, but the alignment of arrays and
+    /// structures is not rounded up to the alignment of a vec4.
+    Std430,
+}
+
+impl Rules {
+    fn base_align(self, align: usize) -> usize {
+        match self {
+            Rules::Std140 => round_up(align, 16),
+            Rules::Std430 => align,
+        }
+    }
+}
+
 fn std140_array_layout<'tcx>(
     a: &'tcx DroplessArena,
+    rules: Rules,
     elem_ty: &TypeDesc,
     arraylen: usize,
 ) -> &'tcx Layout<'tcx> {
-    let elem_layout = std140_layout(a, elem_ty);
+    let elem_layout = std140_layout(a, rules, elem_ty);
     // alignment = column type align rounded up to vec4 align (16 bytes)
-    let base_align = round_up(elem_layout.align, 16);
-    let stride = round_up(elem_layout.size, elem_layout.align);
+    let base_align = rules.base_align(elem_layout.align);
+    // the stride is also rounded up to the base alignment (std140 rule 4)
+    let stride = round_up(elem_layout.size, base_align);
     // total array size = num columns * stride, rounded up to the next multiple of the base alignment.
     // actually the spec says nothing about the 'size' of an element, only about the alignment
     // of the next element in the structure.
@@ -83,7 +104,11 @@ fn std140_array_layout<'tcx>(
     })
 }
 
-fn std140_struct_layout<'tcx>(a: &'tcx DroplessArena, fields: &[&TypeDesc]) -> &'tcx Layout<'tcx> {
+fn std140_struct_layout<'tcx>(
+    a: &'tcx DroplessArena,
+    rules: Rules,
+    fields: &[&TypeDesc],
+) -> &'tcx Layout<'tcx> {
     /* If the member is a structure, the base alignment of the structure is N,
     where N is the largest base alignment value of any of its members,
     and rounded up to the base alignment of a vec4.
@@ -94,7 +119,10 @@ fn std140_struct_layout<'tcx>(a: &'tcx DroplessArena, fields: &[&TypeDesc]) -> &
     */
     // TODO: zero-sized structures?
 
-    let layouts: Vec<_> = fields.iter().map(|&mty| std140_layout(a, mty)).collect();
+    let layouts: Vec<_> = fields
+        .iter()
+        .map(|&mty| std140_layout(a, rules, mty))
+        .collect();
     let layouts = a.alloc_extend(layouts.into_iter());
     let n = layouts.iter().map(|l| l.align).max().unwrap_or(0);
     if n == 0 {
@@ -110,12 +138,14 @@ fn std140_struct_layout<'tcx>(a: &'tcx DroplessArena, fields: &[&TypeDesc]) -> &
     }
 
     // round up to base alignment of vec4
-    let n = round_up(n, 16);
+    let n = rules.base_align(n);
 
     // compute field offsets
     let offsets = a.alloc_extend(iter::repeat(0).take(fields.len()));
     let mut off = 0;
     for i in 0..fields.len() {
+        // each member starts at an offset that is a multiple of its base alignment
+        off = round_up(off, layouts[i].align);
         offsets[i] = off;
         off += layouts[i].size;
     }
@@ -163,7 +193,7 @@ fn std140_vector_layout(prim_ty: PrimitiveType, len: u8) -> Layout<'static> {
     }
 }
 
-fn std140_layout<'tcx>(a: &'tcx DroplessArena, ty: &TypeDesc) -> &'tcx Layout<'tcx> {
+fn std140_layout<'tcx>(a: &'tcx DroplessArena, rules: Rules, ty: &TypeDesc) -> &'tcx Layout<'tcx> {
     match *ty {
         TypeDesc::Primitive(p) => a.alloc(std140_primitive_layout(p)),
         TypeDesc::Vector { elem_ty, len } => a.alloc(std140_vector_layout(elem_ty, len)),
@@ -173,22 +203,29 @@ fn std140_layout<'tcx>(a: &'tcx DroplessArena, ty: &TypeDesc) -> &'tcx Layout<'t
             columns,
         } => std140_array_layout(
             a,
+            rules,
             &TypeDesc::Vector { elem_ty, len: rows },
             columns as usize,
         ),
         TypeDesc::Array { elem_ty, len } => match elem_ty {
             TypeDesc::Primitive(_) | TypeDesc::Vector { .. } | TypeDesc::Struct { .. } => {
-                std140_array_layout(a, elem_ty, len)
+                std140_array_layout(a, rules, elem_ty, len)
             }
             ty => panic!("unsupported array element type: {:?}", ty),
         },
-        TypeDesc::Struct { fields } => std140_struct_layout(a, fields),
+        TypeDesc::Struct { fields } => std140_struct_layout(a, rules, fields),
         ty => panic!("unsupported type: {:?}", ty),
     }
 }
 
 impl<'tcx> Layout<'tcx> {
+    /// Layout of a type in a uniform block.
     pub fn std140(a: &'tcx DroplessArena, ty: &TypeDesc) -> &'tcx Layout<'tcx> {
-        std140_layout(a, ty)
+        std140_layout(a, Rules::Std140, ty)
+    }
+
+    /// Layout of a type in a push constant or storage block.
+    pub fn std430(a: &'tcx DroplessArena, ty: &TypeDesc) -> &'tcx Layout<'tcx> {
+        std140_layout(a, Rules::Std430, ty)
     }
 }
@@ -1,15 +1,18 @@
 #![feature(proc_macro_hygiene)]
 use autograph_imgui::ImGuiRenderer;
 use autograph_api::{glm, prelude::*};
-use autograph_api_boilerplate::{App, Event, KeyboardInput, WindowEvent};
+use autograph_api_boilerplate::{
+    timer::frame_stats_overlay, App, Event, KeyboardInput, WindowEvent,
+};
 use autograph_api_extra::{blackboard::Blackboard, commandext::CommandBufferExt, quad::Quad};
 use autograph_api_gl::prelude::*;
 use imgui::{im_str, FontGlyphRange, ImGui};
 use log::{debug, info, warn};
 use openimageio as oiio;
-use std::{env, iter, mem, path::Path, slice, time};
+use std::{env, iter, mem, path::Path, slice};
 use autograph_api::pipeline::{DynamicSignatureBuilder, VertexInputBinding};
 use autograph_api::vertex::VertexInputRate;
+use autograph_api::format::ChannelData;
 
 // Shaders -----------------------------------------------------------------------------------------
 static QUAD_VERT: ReflectedShader = include_glsl!("quad.vert");
@@ -347,6 +350,7 @@ impl<'a> Pipelines<'a> {
             depth_stencil_state: DepthStencilState::default(),
             input_assembly_state: InputAssemblyState::default(),
             color_blend_state: ColorBlendState::DISABLED,
+            view_count: ViewCount::default(),
         };
 
         let edge_detection_sobel_rgbd = GraphicsPipelineCreateInfo {
@@ -360,6 +364,7 @@ impl<'a> Pipelines<'a> {
             depth_stencil_state: DepthStencilState::default(),
             input_assembly_state: InputAssemblyState::default(),
             color_blend_state: ColorBlendState::DISABLED,
+            view_count: ViewCount::default(),
         };
 
         let substrate_deferred_lighting = GraphicsPipelineCreateInfo {
@@ -373,6 +378,7 @@ impl<'a> Pipelines<'a> {
             depth_stencil_state: DepthStencilState::default(),
             input_assembly_state: InputAssemblyState::default(),
             color_blend_state: ColorBlendState::DISABLED,
+            view_count: ViewCount::default(),
         };
 
         let watercolor_shading = GraphicsPipelineCreateInfo {
@@ -386,6 +392,7 @@ impl<'a> Pipelines<'a> {
             depth_stencil_state: DepthStencilState::default(),
             input_assembly_state: InputAssemblyState::default(),
             color_blend_state: ColorBlendState::DISABLED,
+            view_count: ViewCount::default(),
         };
 
         let substrate_distortion = GraphicsPipelineCreateInfo {
@@ -397,6 +404,7 @@ impl<'a> Pipelines<'a> {
             depth_stencil_state: DepthStencilState::default(),
             input_assembly_state: InputAssemblyState::default(),
             color_blend_state: ColorBlendState::DISABLED,
+            view_count: ViewCount::default(),
         };
 
         let watercolor_shading_signature = DynamicSignatureBuilder::new().vertex_input(VertexInputBinding {
@@ -455,13 +463,15 @@ const AO_CHANNEL_NAMES: &[&str] = &[
     //"RenderLayer.AO.B",
 ];
 
-fn load_image_data<'a, T: oiio::ImageData>(
+/// Loads the specified channels of an image. The format of the image is deduced from the
+/// channel type and the number of channels.
+fn load_image_data<'a, T: oiio::ImageData + ChannelData>(
     a: &'a Arena,
     input: &mut oiio::ImageInput,
     chans: &[&str],
-    fmt: Format,
 ) -> Image2d<'a> {
     let (w, h, _) = input.spec().size();
+    let fmt = Format::from_channels(T::CHANNEL_TYPE, chans.len()).unwrap();
     let data: oiio::ImageBuffer<T> = input.channels_by_name(chans).unwrap().read().unwrap();
     a.image_2d(fmt, w, h).with_data(data.as_bytes())
 }
@@ -472,7 +482,6 @@ const FONT_SIZE: f32 = 15.0;
 pub struct ImGuiContext {
     app_hidpi_factor: f64,
     imgui: imgui::ImGui,
-    last_frame_time: time::Instant,
 }
 
 impl ImGuiContext {
@@ -485,7 +494,6 @@ impl ImGuiContext {
         ImGuiContext {
             app_hidpi_factor,
             imgui,
-            last_frame_time: time::Instant::now(),
         }
     }
 
@@ -498,13 +506,9 @@ impl ImGuiContext {
         );
     }
 
-    pub fn frame(&mut self, window: &winit::Window) -> imgui::Ui {
+    pub fn frame(&mut self, window: &winit::Window, delta_time: f64) -> imgui::Ui {
         let frame_size =
             imgui_winit_support::get_frame_size(window, self.app_hidpi_factor).unwrap();
-        let elapsed = self.last_frame_time.elapsed();
-        let delta_time =
-            (elapsed.as_secs() as f64) + (elapsed.subsec_nanos() as f64 / 1_000_000_000.0);
-        self.last_frame_time = time::Instant::now();
         self.imgui.frame(frame_size, delta_time as f32)
     }
 
@@ -528,39 +532,15 @@ fn main() {
 
     let (frame_width, frame_height, _) = img.spec().size();
 
-    let diffuse_color = load_image_data::<u16>(
-        &arena_0,
-        &mut img,
-        DIFFUSE_COLOR_CHANNEL_NAMES,
-        Format::R16G16B16_UNORM,
-    );
-    let diffuse_direct_lighting = load_image_data::<u16>(
-        &arena_0,
-        &mut img,
-        DIFFUSE_DIRECT_COMPONENT_CHANNEL_NAMES,
-        Format::R16G16B16_UNORM,
-    );
-    let specular_color = load_image_data::<u16>(
-        &arena_0,
-        &mut img,
-        SPECULAR_COLOR_CHANNEL_NAMES,
-        Format::R16G16B16_UNORM,
-    );
-    let specular_direct_lighting = load_image_data::<u16>(
-        &arena_0,
-        &mut img,
-        SPECULAR_DIRECT_COMPONENT_CHANNEL_NAMES,
-        Format::R16G16B16_UNORM,
-    );
-    let normals = load_image_data::<f32>(
-        &arena_0,
-        &mut img,
-        NORMAL_CHANNEL_NAMES,
-        Format::R32G32B32_SFLOAT,
-    );
-    let ambient_occlusion =
-        load_image_data::<u16>(&arena_0, &mut img, AO_CHANNEL_NAMES, Format::R16_UNORM);
-    let depth = load_image_data::<f32>(&arena_0, &mut img, DEPTH_CHANNEL_NAME, Format::R32_SFLOAT);
+    let diffuse_color = load_image_data::<u16>(&arena_0, &mut img, DIFFUSE_COLOR_CHANNEL_NAMES);
+    let diffuse_direct_lighting =
+        load_image_data::<u16>(&arena_0, &mut img, DIFFUSE_DIRECT_COMPONENT_CHANNEL_NAMES);
+    let specular_color = load_image_data::<u16>(&arena_0, &mut img, SPECULAR_COLOR_CHANNEL_NAMES);
+    let specular_direct_lighting =
+        load_image_data::<u16>(&arena_0, &mut img, SPECULAR_DIRECT_COMPONENT_CHANNEL_NAMES);
+    let normals = load_image_data::<f32>(&arena_0, &mut img, NORMAL_CHANNEL_NAMES);
+    let ambient_occlusion = load_image_data::<u16>(&arena_0, &mut img, AO_CHANNEL_NAMES);
+    let depth = load_image_data::<f32>(&arena_0, &mut img, DEPTH_CHANNEL_NAME);
 
     // load substrate texture
     let mut substrate_img = oiio::ImageInput::open("data/rough_default_2k.jpg").unwrap();
@@ -722,7 +702,9 @@ fn main() {
                 let should_close =
                     app.poll_events(|event| imguictx.handle_event(app.window(), &event));
 
-                let mut ui = imguictx.frame(app.window());
+                let frame_timer = app.frame_timer();
+                let mut ui = imguictx.frame(app.window(), frame_timer.delta_time());
+                frame_stats_overlay(&ui, &frame_timer);
                 let mut open = true;
                 ui.show_demo_window(&mut open);
                 ui.slider_float(
@@ -792,7 +774,7 @@ fn main() {
                 imgui_renderer.render(&mut cmdbuf, 0x0, &inner_arena, ui);
 
                 // Present
-                cmdbuf.present(0x0, color_buffer, default_swapchain);
+                cmdbuf.present_image(0x0, color_buffer, default_swapchain);
                 r.submit_frame(vec![cmdbuf]);
 
                 if should_close {